@@ -1,9 +1,10 @@
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use anyhow::Context;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use dimpact::DfgBuilder;
 use dimpact::EngineConfig;
 use dimpact::attach_slice_selection_summary;
 use dimpact::cache;
-use dimpact::compute_impact;
 use dimpact::dfg::{DataFlowGraph, DependencyKind, PdgBuilder, RubyDfgBuilder, RustDfgBuilder};
 use dimpact::dfg_to_dot;
 use dimpact::engine::{AutoPolicy, EngineKind, make_engine_with_auto_policy};
@@ -13,34 +14,46 @@ use dimpact::impact::{
 };
 use dimpact::ir::SymbolId;
 use dimpact::ir::reference::{EdgeCertainty, EdgeProvenance, RefKind, Reference, SymbolIndex};
-use dimpact::languages::path::normalize_path_like;
+use dimpact::languages::path::{normalize_path_like, workspace_file};
 use dimpact::languages::{LanguageKind, analyzer_for_path};
-use dimpact::{ChangedOutput, LanguageMode};
-use dimpact::{DiffParseError, parse_unified_diff};
+use dimpact::{ChangeScope, classify_change_scopes};
+use dimpact::{ChangedOutput, HtmlOptions, LanguageMode};
+use dimpact::{DiffParseError, FileChanges, merge_file_changes, parse_unified_diff};
 use dimpact::{
-    ImpactDirection, ImpactOptions, ImpactOutput, ImpactSliceBridgeKind, ImpactSliceCandidateLane,
-    ImpactSliceCandidateScoringSummary, ImpactSliceCandidateSourceKind,
+    GraphLimits, ImpactDirection, ImpactOptions, ImpactOutput, ImpactSliceBridgeKind,
+    ImpactSliceCandidateLane, ImpactSliceCandidateScoringSummary, ImpactSliceCandidateSourceKind,
     ImpactSliceCandidateSupportMetadata, ImpactSliceEvidenceKind, ImpactSliceFileMetadata,
     ImpactSliceNegativeEvidenceKind, ImpactSlicePlannerKind, ImpactSlicePruneReason,
     ImpactSliceReasonKind, ImpactSliceReasonMetadata, ImpactSliceScopes, ImpactSliceScoreTuple,
     ImpactSliceSelectionSummary,
 };
+use dimpact::{GraphSource, changed_symbols_in_index, classify_symbol_changes};
 use dimpact::{
     ResolvedSchemaProfile, SchemaCommand, SchemaOutputFormat, SchemaProfileInput,
     list_registered_schemas, read_schema_document, resolve_schema_profile,
 };
+use dimpact::{
+    attach_changed_lines, attach_snippets, cap_changed_symbols, cap_impacted_symbols, snippets_for,
+};
+use dimpact::{check_graph_limits, compute_impact};
 use env_logger::Env;
 use is_terminal::IsTerminal;
 use serde::Serialize;
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum OutputFormat {
     Json,
     Yaml,
     Dot,
+    /// GraphML document of the impact graph (impact only), for yEd/Gephi/networkx
+    Graphml,
     Html,
+    /// GitHub Actions workflow commands (`::notice`/`::warning` annotations)
+    GithubActions,
+    /// Indented tree for terminals (impact only; default for interactive `impact`)
+    Text,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -50,7 +63,7 @@ enum Mode {
     Impact,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 enum LangOpt {
     Auto,
     Rust,
@@ -62,6 +75,116 @@ enum LangOpt {
     Tsx,
     Go,
     Java,
+    #[value(alias = "kt")]
+    Kotlin,
+    #[value(alias = "c")]
+    Cpp,
+}
+
+impl LangOpt {
+    fn mode(self) -> LanguageMode {
+        match self {
+            LangOpt::Auto => LanguageMode::Auto,
+            LangOpt::Rust => LanguageMode::Rust,
+            LangOpt::Ruby => LanguageMode::Ruby,
+            LangOpt::Python => LanguageMode::Python,
+            LangOpt::Javascript => LanguageMode::Javascript,
+            LangOpt::Typescript => LanguageMode::Typescript,
+            LangOpt::Tsx => LanguageMode::Tsx,
+            LangOpt::Go => LanguageMode::Go,
+            LangOpt::Java => LanguageMode::Java,
+            LangOpt::Kotlin => LanguageMode::Kotlin,
+            LangOpt::Cpp => LanguageMode::Cpp,
+        }
+    }
+
+    fn kind(self) -> LanguageKind {
+        match self {
+            LangOpt::Auto => LanguageKind::Auto,
+            LangOpt::Rust => LanguageKind::Rust,
+            LangOpt::Ruby => LanguageKind::Ruby,
+            LangOpt::Python => LanguageKind::Python,
+            LangOpt::Javascript => LanguageKind::Javascript,
+            LangOpt::Typescript => LanguageKind::Typescript,
+            LangOpt::Tsx => LanguageKind::Tsx,
+            LangOpt::Go => LanguageKind::Go,
+            LangOpt::Java => LanguageKind::Java,
+            LangOpt::Kotlin => LanguageKind::Kotlin,
+            LangOpt::Cpp => LanguageKind::Cpp,
+        }
+    }
+}
+
+/// Split `--lang` values into the language the engines run in and the
+/// languages analysis is restricted to: one language runs as itself, several
+/// run as `auto` limited to them, and `auto` keeps every language.
+fn lang_selection(langs: &[LangOpt]) -> (LangOpt, Vec<LanguageKind>) {
+    if langs.is_empty() || langs.contains(&LangOpt::Auto) {
+        return (LangOpt::Auto, Vec::new());
+    }
+    let mut kinds: Vec<LanguageKind> = Vec::new();
+    for l in langs {
+        if !kinds.contains(&l.kind()) {
+            kinds.push(l.kind());
+        }
+    }
+    let single = if kinds.len() == 1 {
+        langs[0]
+    } else {
+        LangOpt::Auto
+    };
+    (single, kinds)
+}
+
+/// Keep only the changed files and symbols in `languages` (all when empty).
+fn retain_languages(
+    report: &mut ChangedOutput,
+    languages: &[LanguageKind],
+    analyzers: &dimpact::languages::AnalyzerConfig,
+) {
+    if languages.is_empty() {
+        return;
+    }
+    report.changed_files.retain(|f| {
+        analyzers
+            .language_for_path(f)
+            .is_some_and(|k| languages.contains(&k))
+    });
+    report
+        .changed_symbols
+        .retain(|s| LanguageKind::from_name(&s.language).is_some_and(|k| languages.contains(&k)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EdgeKindOpt {
+    Call,
+    TypeUse,
+    Inherit,
+}
+
+impl EdgeKindOpt {
+    fn ref_kind(self) -> RefKind {
+        match self {
+            EdgeKindOpt::Call => RefKind::Call,
+            EdgeKindOpt::TypeUse => RefKind::TypeUse,
+            EdgeKindOpt::Inherit => RefKind::Inherit,
+        }
+    }
+}
+
+/// Parse an `--edge-weight KIND=N` value.
+fn parse_edge_weight(s: &str) -> Result<(RefKind, usize), String> {
+    let (kind, weight) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KIND=N, got `{s}`"))?;
+    let kind = EdgeKindOpt::from_str(kind, true)?;
+    let weight: usize = weight
+        .parse()
+        .map_err(|_| format!("invalid weight `{weight}`"))?;
+    if weight == 0 {
+        return Err("edge weight must be at least 1".to_string());
+    }
+    Ok((kind.ref_kind(), weight))
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -71,6 +194,16 @@ enum DirectionOpt {
     Both,
 }
 
+/// Which dependences `impact --via` follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ViaOpt {
+    /// Call and reference edges only
+    CallGraph,
+    /// Also data flow: the PDG of the changed files with symbolic
+    /// propagation, as with --with-propagation
+    Dataflow,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum EngineOpt {
     Auto,
@@ -82,6 +215,7 @@ enum EngineOpt {
 enum AutoPolicyOpt {
     Compat,
     StrictIfAvailable,
+    Probe,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -134,8 +268,183 @@ struct ImpactOutputRendered<'a> {
     confidence_filter: Option<&'a ConfidenceFilterSummary>,
 }
 
-fn print_json_output<T: Serialize>(data: T) -> anyhow::Result<()> {
-    println!("{}", serde_json::to_string_pretty(&data)?);
+/// Where command results go: stdout, or the `--output` file.
+struct Output {
+    writer: Box<dyn Write>,
+    /// Directory of the `--output` file, where HTML sidecar assets are
+    /// written; empty (the current directory) for stdout.
+    dir: std::path::PathBuf,
+    /// Whether results go to an interactive terminal.
+    terminal: bool,
+    /// --compact: single-line JSON output.
+    compact: bool,
+}
+
+impl Output {
+    /// Results to `path`, creating parent directories and truncating the
+    /// file, or to stdout when unset.
+    fn open(path: Option<&std::path::Path>, compact: bool) -> anyhow::Result<Self> {
+        let Some(path) = path else {
+            return Ok(Output {
+                writer: Box::new(io::stdout()),
+                dir: std::path::PathBuf::new(),
+                terminal: io::stdout().is_terminal(),
+                compact,
+            });
+        };
+        let dir = path
+            .parent()
+            .unwrap_or(std::path::Path::new(""))
+            .to_path_buf();
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("create output directory {}", dir.display()))?;
+        }
+        let file = fs::File::create(path)
+            .with_context(|| format!("create output file {}", path.display()))?;
+        Ok(Output {
+            writer: Box::new(io::BufWriter::new(file)),
+            dir,
+            terminal: false,
+            compact,
+        })
+    }
+
+    /// ANSI colors for text output: only on a terminal, and never when
+    /// `NO_COLOR` is set to a non-empty value (https://no-color.org).
+    fn use_color(&self) -> bool {
+        self.terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Language detection and optional relationships for the analyzers (--map,
+/// --include-macros, --include-constructions).
+fn analyzer_config(args: &Args) -> dimpact::languages::AnalyzerConfig {
+    dimpact::languages::AnalyzerConfig {
+        include_macros: args.include_macros,
+        include_constructions: args.include_constructions,
+        language_map: args.map.clone(),
+    }
+}
+
+/// Which files workspace scans visit (--follow-symlinks, --generated-marker,
+/// --include-generated).
+fn walk_options(args: &Args) -> dimpact::graph_source::WalkOptions {
+    let mut walk = dimpact::graph_source::WalkOptions {
+        follow_symlinks: args.follow_symlinks,
+        ..Default::default()
+    };
+    if args.include_generated {
+        walk.generated_marker = None;
+    } else if let Some(re) = &args.generated_marker {
+        walk.generated_marker = Some(re.clone());
+    }
+    walk
+}
+
+/// How the workspace graph is analyzed and resolved (--reexport-depth,
+/// --strict-resolution, --include-external and the analyzer and walk
+/// settings).
+fn analyze_options(args: &Args) -> dimpact::AnalyzeOptions {
+    dimpact::AnalyzeOptions {
+        reexport_depth: args.reexport_depth,
+        strict_resolution: args.strict_resolution,
+        include_external: args.include_external,
+        analyzers: analyzer_config(args),
+        walk: walk_options(args),
+        ..Default::default()
+    }
+}
+
+fn load_impact_baseline(path: &std::path::Path) -> anyhow::Result<ImpactOutput> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("read baseline {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("baseline {} is not `impact -f json` output", path.display()))
+}
+
+/// Where the diff under analysis is read from.
+#[derive(Debug, Clone, Default)]
+struct DiffSource {
+    /// Patch files from --diff, analyzed together instead of a single diff.
+    files: Vec<String>,
+    /// `git diff` arguments from --staged/--worktree/--since; the diff is read from stdin when unset.
+    git_args: Option<Vec<String>>,
+    /// Workspace root git runs in (the current directory when unset).
+    root: Option<std::path::PathBuf>,
+}
+
+fn git_diff_args(staged: bool, worktree: bool, since: Option<&str>) -> Option<Vec<String>> {
+    if !staged && !worktree && since.is_none() {
+        return None;
+    }
+    let mut out = vec![
+        "diff".to_string(),
+        "--no-ext-diff".to_string(),
+        "--no-color".to_string(),
+    ];
+    if staged {
+        out.push("--cached".to_string());
+    }
+    if let Some(rev) = since {
+        out.push(rev.to_string());
+    }
+    Some(out)
+}
+
+fn read_diff_from_git(
+    git_args: &[String],
+    root: Option<&std::path::Path>,
+) -> anyhow::Result<String> {
+    let dir = match root {
+        Some(root) => root.to_path_buf(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    let inside = std::process::Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(&dir)
+        .output()
+        .context("failed to run git")?;
+    if !inside.status.success() {
+        anyhow::bail!(
+            "--staged/--worktree/--since need a git repository, but {} is not inside one",
+            dir.display()
+        );
+    }
+    let out = std::process::Command::new("git")
+        .args(git_args)
+        .current_dir(&dir)
+        .output()
+        .context("failed to run git")?;
+    if !out.status.success() {
+        anyhow::bail!(
+            "`git {}` failed: {}",
+            git_args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    String::from_utf8(out.stdout).context("git diff output is not valid UTF-8")
+}
+
+/// Write `data` as JSON command output, pretty-printed unless `output` is
+/// compact.
+fn print_json_output<T: Serialize>(output: &mut Output, data: T) -> anyhow::Result<()> {
+    let json = if output.compact {
+        serde_json::to_string(&data)?
+    } else {
+        serde_json::to_string_pretty(&data)?
+    };
+    writeln!(output, "{json}")?;
     Ok(())
 }
 
@@ -218,14 +527,50 @@ fn apply_confidence_filter(
     if !keep_edges_in_output {
         filtered.edges.clear();
     }
+    filtered.edges_included = keep_edges_in_output;
     (filtered, Some(summary))
 }
 
+fn write_html_sidecar_assets(
+    output: &Output,
+    out: &ImpactOutput,
+    html_opts: &HtmlOptions,
+) -> anyhow::Result<()> {
+    if html_opts.inline_assets {
+        return Ok(());
+    }
+    // next to the --output file if any, else the current directory
+    let dir = &output.dir;
+    for (name, contents) in dimpact::render::HTML_SIDECAR_ASSETS {
+        fs::write(dir.join(name), contents)?;
+    }
+    fs::write(
+        dir.join(dimpact::render::HTML_DATA_FILE),
+        dimpact::to_html_data(out),
+    )?;
+    eprintln!(
+        "html sidecar assets written: {}, {}",
+        dimpact::render::HTML_SIDECAR_ASSETS
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        dimpact::render::HTML_DATA_FILE
+    );
+    Ok(())
+}
+
+/// Print an impact result, or its comparison against `baseline` (`impact --baseline`);
+/// returns whether nothing was impacted.
 fn print_impact_output(
+    output: &mut Output,
     fmt: OutputFormat,
     out: &ImpactOutput,
     confidence_filter: Option<&ConfidenceFilterSummary>,
-) -> anyhow::Result<()> {
+    baseline: Option<&ImpactOutput>,
+    html_opts: &HtmlOptions,
+) -> anyhow::Result<bool> {
+    let _timer = dimpact::profile::start(dimpact::profile::Phase::Render);
     if let Some(cf) = confidence_filter {
         eprintln!(
             "confidence filter applied: min_confidence={} exclude_dynamic_fallback={} kept_edges={}/{}",
@@ -235,23 +580,83 @@ fn print_impact_output(
             cf.input_edge_count
         );
     }
+    let no_impact = !out.summary.has_impact;
+    if no_impact {
+        eprintln!(
+            "no symbols impacted (changed symbols: {})",
+            out.changed_symbols.len()
+        );
+    }
+    if let Some(baseline) = baseline {
+        print_impact_comparison(output, fmt, &dimpact::compare_impact(baseline, out))?;
+        return Ok(no_impact);
+    }
     match fmt {
         OutputFormat::Json => {
             let rendered = ImpactOutputRendered {
                 output: out,
                 confidence_filter,
             };
-            print_json_output(rendered)?;
+            print_json_output(output, rendered)?;
         }
-        OutputFormat::Yaml => print!(
+        OutputFormat::Yaml => write!(
+            output,
             "{}",
             serde_yaml::to_string(&ImpactOutputRendered {
                 output: out,
                 confidence_filter,
             })?
-        ),
-        OutputFormat::Dot => println!("{}", dimpact::to_dot(out)),
-        OutputFormat::Html => println!("{}", dimpact::to_html(out)),
+        )?,
+        OutputFormat::Dot => writeln!(output, "{}", dimpact::to_dot(out))?,
+        OutputFormat::Graphml => write!(output, "{}", dimpact::to_graphml(out))?,
+        OutputFormat::Html => {
+            writeln!(output, "{}", dimpact::to_html_with_options(out, html_opts))?;
+            write_html_sidecar_assets(output, out, html_opts)?;
+        }
+        OutputFormat::GithubActions => write!(output, "{}", dimpact::to_github_annotations(out))?,
+        OutputFormat::Text => write!(output, "{}", dimpact::to_text(out, output.use_color()))?,
+    }
+    Ok(no_impact)
+}
+
+fn print_impact_comparison(
+    output: &mut Output,
+    fmt: OutputFormat,
+    cmp: &dimpact::ImpactComparison,
+) -> anyhow::Result<()> {
+    eprintln!(
+        "baseline: symbols +{} -{} edges {}",
+        cmp.added_symbols.len(),
+        cmp.removed_symbols.len(),
+        if cmp.edges_compared {
+            format!("+{} -{}", cmp.added_edges.len(), cmp.removed_edges.len())
+        } else {
+            "(not compared)".to_string()
+        }
+    );
+    match fmt {
+        OutputFormat::Json => print_json_output(output, cmp)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(cmp)?)?,
+        OutputFormat::Text => {
+            for s in &cmp.added_symbols {
+                writeln!(output, "+ {}", s.id.0)?;
+            }
+            for s in &cmp.removed_symbols {
+                writeln!(output, "- {}", s.id.0)?;
+            }
+            for e in &cmp.added_edges {
+                writeln!(output, "+ {} -> {} ({})", e.from.0, e.to.0, e.kind.as_str())?;
+            }
+            for e in &cmp.removed_edges {
+                writeln!(output, "- {} -> {} ({})", e.from.0, e.to.0, e.kind.as_str())?;
+            }
+        }
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("impact --baseline supports only json, yaml, or text output")
+        }
     }
     Ok(())
 }
@@ -266,6 +671,11 @@ enum KindOpt {
     Trait,
     #[value(alias = "module")]
     Mod,
+    Const,
+    Static,
+    #[value(alias = "type-alias")]
+    Type,
+    Macro,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -281,7 +691,7 @@ enum CacheScopeOpt {
     about = "Analyze git diff and serialize changes"
 )]
 struct Args {
-    /// Output format (json, yaml, dot, html)
+    /// Output format (json, yaml, dot, html, github-actions)
     #[arg(short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Json, global = true)]
     format: OutputFormat,
 
@@ -289,7 +699,8 @@ struct Args {
     #[arg(long = "mode", value_enum, default_value_t = Mode::Diff, hide = true)]
     mode: Mode,
 
-    /// Language mode for symbol extraction / detection
+    /// Language for files whose extension is not recognized (each file's
+    /// language is otherwise detected from its extension)
     #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
     lang: LangOpt,
 
@@ -326,10 +737,152 @@ struct Args {
     #[arg(long = "engine", value_enum, default_value_t = EngineOpt::Auto)]
     engine: EngineOpt,
 
-    /// Auto engine policy: compat (default) or strict-if-available
+    /// Auto engine policy: probe (default; LSP when a server answers, else TS),
+    /// compat (always TS) or strict-if-available
     #[arg(long = "auto-policy", value_enum, global = true)]
     auto_policy: Option<AutoPolicyOpt>,
 
+    /// HTML report: use the dark color scheme
+    #[arg(long = "html-dark", default_value_t = false, global = true)]
+    html_dark: bool,
+
+    /// HTML report: reference sidecar CSS/JS files and load the graph from a
+    /// sidecar `report_data.json` (written to the current directory) instead
+    /// of embedding them; keeps reports with thousands of nodes responsive
+    #[arg(long = "html-external-assets", default_value_t = false, global = true)]
+    html_external_assets: bool,
+
+    /// JSON output: print each document on one line instead of pretty-printing
+    #[arg(long = "compact", default_value_t = false, global = true)]
+    compact: bool,
+
+    /// Write the result to this file instead of stdout (parent dirs are created)
+    #[arg(short = 'o', long = "output", value_name = "PATH", global = true)]
+    output: Option<std::path::PathBuf>,
+
+    /// Workspace root to analyze instead of the current directory; diff paths
+    /// are relative to it, while files named on the command line stay
+    /// relative to the current directory
+    #[arg(long = "root", value_name = "DIR", global = true)]
+    root: Option<std::path::PathBuf>,
+
+    /// Cap the number of worker threads used for workspace scans (overrides DIMPACT_JOBS)
+    #[arg(long = "jobs", value_name = "N", global = true)]
+    jobs: Option<usize>,
+
+    /// More log output on stderr: -v info, -vv debug, -vvv trace (RUST_LOG wins when set)
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count, global = true, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Only log errors on stderr (RUST_LOG wins when set)
+    #[arg(short = 'q', long = "quiet", default_value_t = false, global = true)]
+    quiet: bool,
+
+    /// Rust: add macro_rules!/proc-macro-derive definitions as symbols, and
+    /// `name!(..)` invocations and `#[derive(..)]` entries as references
+    #[arg(long = "include-macros", default_value_t = false, global = true)]
+    include_macros: bool,
+
+    /// Rust: add struct literals, struct patterns and enum variants in function
+    /// bodies as type-use references, so field and variant changes impact
+    /// construction sites and matches
+    #[arg(long = "include-constructions", default_value_t = false, global = true)]
+    include_constructions: bool,
+
+    /// Rust: resolve a reference only among symbols in exactly the module its
+    /// path or `use` import names, when any exist, before the score heuristics
+    #[arg(long = "strict-resolution", default_value_t = false, global = true)]
+    strict_resolution: bool,
+
+    /// Turn qualified or imported calls that match nothing in the workspace
+    /// (e.g. `serde_json::from_str`) into boundary symbols in file
+    /// `<external>`, reported as impacted but not traversed further
+    #[arg(long = "include-external", default_value_t = false, global = true)]
+    include_external: bool,
+
+    /// Ignore edits that only change whitespace (re-indentation, trailing
+    /// spaces, blank lines) when mapping changed lines to symbols, like a
+    /// diff produced with `-w`
+    #[arg(long = "ignore-whitespace", default_value_t = false, global = true)]
+    ignore_whitespace: bool,
+
+    /// Print per-phase durations (enumerate, analyze, resolve, impact, render)
+    /// to stderr as `<phase>_ms=<millis>` lines when the run ends
+    #[arg(long = "profile", default_value_t = false, global = true)]
+    profile: bool,
+
+    /// Descend into symlinked directories while scanning the workspace (each
+    /// real path is visited once); by default they are skipped
+    #[arg(long = "follow-symlinks", default_value_t = false, global = true)]
+    follow_symlinks: bool,
+
+    /// Skip files whose first lines match REGEX as generated (default:
+    /// '@generated' or Go's 'Code generated ... DO NOT EDIT')
+    #[arg(
+        long = "generated-marker",
+        value_name = "REGEX",
+        global = true,
+        value_parser = regex::Regex::new
+    )]
+    generated_marker: Option<regex::Regex>,
+
+    /// Scan generated files too instead of skipping them
+    #[arg(
+        long = "include-generated",
+        default_value_t = false,
+        global = true,
+        conflicts_with = "generated_marker"
+    )]
+    include_generated: bool,
+
+    /// Analyze files matching GLOB as LANG ahead of extension detection
+    /// (e.g. '*.es6=javascript'; globs with a '/' match the whole path). Repeatable.
+    #[arg(
+        long = "map",
+        value_name = "GLOB=LANG",
+        global = true,
+        value_parser = dimpact::languages::parse_language_mapping
+    )]
+    map: Vec<(String, LanguageKind)>,
+
+    /// Follow up to N re-export hops (TS/JS barrel files, Rust `pub use`) when
+    /// resolving references; 0 disables it
+    #[arg(long = "reexport-depth", value_name = "N", global = true)]
+    reexport_depth: Option<usize>,
+
+    /// Read the diff from `git diff --cached` (staged changes) instead of stdin
+    #[arg(
+        long = "staged",
+        default_value_t = false,
+        global = true,
+        conflicts_with = "worktree"
+    )]
+    staged: bool,
+
+    /// Read the diff from `git diff` (unstaged changes) instead of stdin
+    #[arg(
+        long = "worktree",
+        default_value_t = false,
+        global = true,
+        conflicts_with = "since"
+    )]
+    worktree: bool,
+
+    /// Read the diff from `git diff REV` (changes since REV) instead of stdin;
+    /// with --staged only staged changes are compared against REV
+    #[arg(long = "since", value_name = "REV", global = true)]
+    since: Option<String>,
+
+    /// Read the diff from this patch file ('-' for stdin) instead; repeatable,
+    /// e.g. one patch per commit of a branch, and the changes are unioned
+    #[arg(
+        long = "diff",
+        value_name = "PATH",
+        global = true,
+        conflicts_with_all = ["staged", "worktree", "since"]
+    )]
+    diff_files: Vec<String>,
+
     /// LSP strict mode: do not fallback to TS on failure
     #[arg(long = "engine-lsp-strict", default_value_t = false)]
     engine_lsp_strict: bool,
@@ -358,25 +911,55 @@ struct Args {
 }
 
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once; `impact` simply has many flags
 enum Command {
     /// Show parsed diff from stdin
     Diff,
     /// Show changed symbols from diff
     Changed {
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
-        /// Analysis engine: auto (TS default), ts, lsp (experimental)
+        /// Languages to analyze, comma-separated or repeated (auto keeps every
+        /// supported language)
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
+        /// Analysis engine: auto (LSP if available, else TS), ts, lsp (experimental)
         #[arg(long = "engine", value_enum, default_value_t = EngineOpt::Auto)]
         engine: EngineOpt,
         #[arg(long = "engine-lsp-strict", default_value_t = false)]
         engine_lsp_strict: bool,
         #[arg(long = "engine-dump-capabilities", default_value_t = false)]
         engine_dump_capabilities: bool,
+        /// Classify each changed symbol as added, removed, or modified (adds `symbol_changes`)
+        #[arg(long = "with-change-kind", default_value_t = false)]
+        with_change_kind: bool,
+        /// Mark each changed function as a signature or body-only change (adds `change_scopes`)
+        #[arg(long = "with-change-scope", default_value_t = false)]
+        with_change_scope: bool,
+        /// Embed each symbol's source text (up to 20 lines) in a `snippets` map
+        #[arg(long = "with-snippets", default_value_t = false)]
+        with_snippets: bool,
+        /// List the added lines inside each changed symbol in a `changed_lines` map
+        #[arg(long = "with-changed-lines", default_value_t = false)]
+        with_changed_lines: bool,
+        /// List at most N changed symbols (first by id); `total_changed` keeps the full count
+        #[arg(long = "max-results", value_name = "N")]
+        max_results: Option<usize>,
     },
     /// Compute impact from diff or seeds
     Impact {
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
+        /// Languages to analyze, comma-separated or repeated (auto keeps every
+        /// supported language)
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
         #[arg(long = "direction", value_enum, default_value_t = DirectionOpt::Callers)]
         direction: DirectionOpt,
         #[arg(long = "max-depth")]
@@ -403,7 +986,12 @@ enum Command {
         /// Enable symbolic propagation across variables and functions (implies PDG)
         #[arg(long = "with-propagation", default_value_t = false)]
         with_propagation: bool,
-        /// Analysis engine: auto (TS default), ts, lsp
+        /// Dependences to follow; `dataflow` traverses def/use edges of the
+        /// changed code too and maps them back to enclosing symbols (same as
+        /// --with-propagation)
+        #[arg(long = "via", value_enum, default_value_t = ViaOpt::CallGraph)]
+        via: ViaOpt,
+        /// Analysis engine: auto (LSP if available, else TS), ts, lsp
         #[arg(long = "engine", value_enum, default_value_t = EngineOpt::Auto)]
         engine: EngineOpt,
         #[arg(long = "engine-lsp-strict", default_value_t = false)]
@@ -414,12 +1002,78 @@ enum Command {
         seed_symbols: Vec<String>,
         #[arg(long = "seed-json")]
         seed_json: Option<String>,
+        /// Seed from every symbol defined in this file (repeatable; unions with
+        /// the other seeds)
+        #[arg(long = "seed-file", value_name = "PATH")]
+        seed_files: Vec<String>,
+        /// With seeds, still read the diff and start from its changed symbols
+        /// as well (by default seeds replace the diff)
+        #[arg(long = "union-seeds", default_value_t = false)]
+        union_seeds: bool,
         /// Ignore directories (relative prefixes). Repeatable.
         #[arg(long = "ignore-dir")]
         ignore_dir: Vec<String>,
         /// Group impact per changed/seed symbol; output per-seed results
         #[arg(long = "per-seed", default_value_t = false)]
         per_seed: bool,
+        /// Build the graph from a git tree-ish (e.g. the merge base) instead of the
+        /// working tree; the diff is mapped onto its pre-change symbols
+        #[arg(long = "graph-base", value_name = "TREEISH")]
+        graph_base: Option<String>,
+        /// Start only from changed symbols whose API may have changed: functions
+        /// whose edits stay inside the body are dropped (diff-based impact only)
+        #[arg(
+            long = "api-only",
+            default_value_t = false,
+            conflicts_with_all = [
+                "seed_symbols", "seed_json", "seed_files", "per_seed",
+                "graph_base", "with_pdg", "with_propagation",
+            ]
+        )]
+        api_only: bool,
+        /// Only follow these reference kinds (repeatable; default: all)
+        #[arg(long = "edge-kind", value_enum)]
+        edge_kinds: Vec<EdgeKindOpt>,
+        /// Depth cost of following an edge kind, as KIND=N (repeatable; default
+        /// 1 each); --max-depth then bounds the summed cost of a path
+        #[arg(long = "edge-weight", value_name = "KIND=N", value_parser = parse_edge_weight)]
+        edge_weights: Vec<(RefKind, usize)>,
+        /// Don't traverse into test functions (test files, `#[test]`, `test_*`)
+        #[arg(long = "exclude-tests", default_value_t = false)]
+        exclude_tests: bool,
+        /// Embed each symbol's source text (up to 20 lines) in a `snippets` map
+        #[arg(long = "with-snippets", default_value_t = false)]
+        with_snippets: bool,
+        /// Print how many symbols were impacted at each depth to stderr
+        #[arg(long = "depth-histogram", default_value_t = false)]
+        depth_histogram: bool,
+        /// List at most N impacted symbols, nearest first; the summary is marked
+        /// `truncated` with the full count in `total_impacted`
+        #[arg(long = "max-results", value_name = "N")]
+        max_results: Option<usize>,
+        /// Fail when the project graph has more symbols than this
+        #[arg(long = "max-symbols", value_name = "N")]
+        max_symbols: Option<usize>,
+        /// Fail when the project graph has more edges than this
+        #[arg(long = "max-edges", value_name = "N")]
+        max_edges: Option<usize>,
+        /// Stop traversal after reaching N symbols and flag the output `truncated`
+        #[arg(long = "max-visited", value_name = "N")]
+        max_visited: Option<usize>,
+        /// Stop traversal after this many milliseconds and flag the output `truncated`
+        #[arg(long = "time-budget-ms", value_name = "MS")]
+        time_budget_ms: Option<u64>,
+        /// Instead of computing impact, print how the reference sites inside
+        /// (or resolving to) this symbol were resolved: candidates and scores
+        #[arg(long = "explain", value_name = "SYMBOL_ID")]
+        explain: Option<String>,
+        /// Exit with this status when no symbol is impacted (default: 0)
+        #[arg(long = "no-impact-exit-code", value_name = "CODE")]
+        no_impact_exit_code: Option<i32>,
+        /// Compare against a saved `impact -f json` result and print the
+        /// symbols/edges newly impacted or no longer impacted instead
+        #[arg(long = "baseline", value_name = "FILE", conflicts_with = "per_seed")]
+        baseline: Option<std::path::PathBuf>,
     },
     /// Generate a Symbol ID from file, line and name
     Id {
@@ -432,15 +1086,29 @@ enum Command {
         /// Symbol name (e.g. function/method/struct name) (optional)
         #[arg(long = "name")]
         name: Option<String>,
-        /// Language override (auto by default)
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
+        /// Language override (auto by default); several restrict the search
+        /// to those languages
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
         /// Optional kind filter to narrow candidates
         #[arg(long = "kind", value_enum)]
         kind: Option<KindOpt>,
         /// If exactly one candidate, print plain ID
         #[arg(long = "raw", default_value_t = false)]
         raw: bool,
+        /// Read a diff (stdin, --diff, --staged, ...) and print the IDs of the
+        /// symbols it changes, ready for `impact --seed-symbol`
+        #[arg(
+            long = "from-diff",
+            default_value_t = false,
+            conflicts_with_all = ["path", "line", "name"]
+        )]
+        from_diff: bool,
     },
     /// List, fetch, or resolve registered JSON schema documents
     Schema {
@@ -458,6 +1126,98 @@ enum Command {
         #[command(subcommand)]
         cmd: CacheCmd,
     },
+    /// Export the whole project call graph (every symbol and reference)
+    Graph,
+    /// Detect cyclic call dependencies in the project graph
+    Cycles,
+    /// Report per-symbol caller/callee degree over the project graph
+    Metrics {
+        /// Only print the N highest-ranked symbols
+        #[arg(long = "top", value_name = "N")]
+        top: Option<usize>,
+    },
+    /// List functions and methods nothing in the workspace references
+    Deadcode {
+        /// Treat public API symbols as used (they may be called from outside)
+        #[arg(long = "pub-as-roots", default_value_t = false)]
+        pub_as_roots: bool,
+        /// Leave symbols from test files out of the report
+        #[arg(long = "exclude-tests", default_value_t = false)]
+        exclude_tests: bool,
+    },
+    /// List the files a workspace scan would analyze, without parsing them
+    Files {
+        /// Ignore directories (relative prefixes). Repeatable.
+        #[arg(long = "ignore-dir")]
+        ignore_dir: Vec<String>,
+    },
+    /// List tests reached by the changes in a diff (from stdin), for targeted test runs
+    Tests {
+        #[arg(long = "max-depth")]
+        max_depth: Option<usize>,
+        /// Ignore directories (relative prefixes). Repeatable.
+        #[arg(long = "ignore-dir")]
+        ignore_dir: Vec<String>,
+    },
+    /// Build the data flow graph of a single file (Rust or Ruby)
+    Dfg {
+        /// Source file to analyze
+        path: String,
+        /// Language override (detected from the extension by default)
+        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
+        lang: LangOpt,
+        /// Keep only control-dependence edges
+        #[arg(
+            long = "control-only",
+            default_value_t = false,
+            conflicts_with = "data_only"
+        )]
+        control_only: bool,
+        /// Keep only data-dependence edges
+        #[arg(long = "data-only", default_value_t = false)]
+        data_only: bool,
+    },
+    /// Check that language servers for `--engine lsp` start and advertise the
+    /// capabilities dimpact needs (exits non-zero when none pass)
+    EngineCheck {
+        /// Languages to check, comma-separated (auto checks every supported server)
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
+    },
+    /// Re-run impact on the working tree diff (or --staged/--since) whenever
+    /// source files change, keeping the cache warm between runs
+    Watch {
+        /// Languages to analyze, comma-separated or repeated (auto keeps every
+        /// supported language)
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
+        #[arg(long = "direction", value_enum, default_value_t = DirectionOpt::Callers)]
+        direction: DirectionOpt,
+        #[arg(long = "max-depth")]
+        max_depth: Option<usize>,
+        /// Ignore directories (relative prefixes). Repeatable.
+        #[arg(long = "ignore-dir")]
+        ignore_dir: Vec<String>,
+        /// Only report changed symbols, not what they impact
+        #[arg(long = "changed-only", default_value_t = false)]
+        changed_only: bool,
+        /// Wait until files have been quiet this long before re-running
+        #[arg(long = "debounce-ms", value_name = "MS", default_value_t = 300)]
+        debounce_ms: u64,
+        /// Exit after N reports (the first one is printed on startup)
+        #[arg(long = "max-runs", value_name = "N")]
+        max_runs: Option<usize>,
+    },
     /// Generate shell completion script for this CLI
     Completions {
         /// Target shell (bash, zsh, fish, powershell, elvish)
@@ -479,8 +1239,13 @@ enum SchemaCliCmd {
 enum SchemaResolveTarget {
     Diff,
     Changed {
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
         #[arg(long = "engine", value_enum, default_value_t = EngineOpt::Auto)]
         engine: EngineOpt,
         #[arg(long = "engine-lsp-strict", default_value_t = false)]
@@ -489,8 +1254,13 @@ enum SchemaResolveTarget {
         engine_dump_capabilities: bool,
     },
     Impact {
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
         #[arg(long = "direction", value_enum, default_value_t = DirectionOpt::Callers)]
         direction: DirectionOpt,
         #[arg(long = "max-depth")]
@@ -507,6 +1277,8 @@ enum SchemaResolveTarget {
         with_pdg: bool,
         #[arg(long = "with-propagation", default_value_t = false)]
         with_propagation: bool,
+        #[arg(long = "via", value_enum, default_value_t = ViaOpt::CallGraph)]
+        via: ViaOpt,
         #[arg(long = "engine", value_enum, default_value_t = EngineOpt::Auto)]
         engine: EngineOpt,
         #[arg(long = "engine-lsp-strict", default_value_t = false)]
@@ -529,12 +1301,19 @@ enum SchemaResolveTarget {
         line: Option<u32>,
         #[arg(long = "name")]
         name: Option<String>,
-        #[arg(long = "lang", value_enum, default_value_t = LangOpt::Auto)]
-        lang: LangOpt,
+        #[arg(
+            long = "lang",
+            value_enum,
+            value_delimiter = ',',
+            default_value = "auto"
+        )]
+        lang: Vec<LangOpt>,
         #[arg(long = "kind", value_enum)]
         kind: Option<KindOpt>,
         #[arg(long = "raw", default_value_t = false)]
         raw: bool,
+        #[arg(long = "from-diff", default_value_t = false)]
+        from_diff: bool,
     },
 }
 
@@ -571,6 +1350,33 @@ enum CacheCmd {
         #[arg(long = "dir")]
         dir: Option<String>,
     },
+    /// Compute impact of seed symbols straight from the cached graph (no diff)
+    Query {
+        #[arg(long = "scope", value_enum, default_value_t = CacheScopeOpt::Local)]
+        scope: CacheScopeOpt,
+        #[arg(long = "dir")]
+        dir: Option<String>,
+        #[arg(long = "seed-symbol")]
+        seed_symbols: Vec<String>,
+        #[arg(long = "seed-json")]
+        seed_json: Option<String>,
+        #[arg(long = "direction", value_enum, default_value_t = DirectionOpt::Callers)]
+        direction: DirectionOpt,
+        #[arg(long = "max-depth")]
+        max_depth: Option<usize>,
+        #[arg(long = "with-edges", default_value_t = false)]
+        with_edges: bool,
+        /// Only follow these reference kinds (repeatable; default: all)
+        #[arg(long = "edge-kind", value_enum)]
+        edge_kinds: Vec<EdgeKindOpt>,
+        /// Depth cost of following an edge kind, as KIND=N (repeatable; default
+        /// 1 each); --max-depth then bounds the summed cost of a path
+        #[arg(long = "edge-weight", value_name = "KIND=N", value_parser = parse_edge_weight)]
+        edge_weights: Vec<(RefKind, usize)>,
+        /// Bring a stale cache up to date first instead of failing
+        #[arg(long = "verify", default_value_t = false)]
+        verify: bool,
+    },
 }
 
 fn schema_output_format(fmt: OutputFormat) -> SchemaOutputFormat {
@@ -578,7 +1384,10 @@ fn schema_output_format(fmt: OutputFormat) -> SchemaOutputFormat {
         OutputFormat::Json => SchemaOutputFormat::Json,
         OutputFormat::Yaml => SchemaOutputFormat::Yaml,
         OutputFormat::Dot => SchemaOutputFormat::Dot,
+        OutputFormat::Graphml => SchemaOutputFormat::Graphml,
         OutputFormat::Html => SchemaOutputFormat::Html,
+        OutputFormat::GithubActions => SchemaOutputFormat::GithubActions,
+        OutputFormat::Text => SchemaOutputFormat::Text,
     }
 }
 
@@ -604,12 +1413,13 @@ fn resolve_schema_profile_for_target(
             with_edges,
             with_pdg,
             with_propagation,
+            via,
             ..
         } => SchemaCommand::Impact {
             per_seed: *per_seed,
             with_edges: *with_edges,
             with_pdg: *with_pdg,
-            with_propagation: *with_propagation,
+            with_propagation: *with_propagation || *via == ViaOpt::Dataflow,
         },
         SchemaResolveTarget::Id { raw, .. } => SchemaCommand::Id { raw: *raw },
     };
@@ -634,6 +1444,7 @@ fn resolve_schema_profile_for_args(
             with_edges,
             with_pdg,
             with_propagation,
+            via,
             ..
         }) => SchemaProfileInput {
             format: schema_output_format(args.format),
@@ -641,7 +1452,7 @@ fn resolve_schema_profile_for_args(
                 per_seed: *per_seed,
                 with_edges: *with_edges,
                 with_pdg: *with_pdg,
-                with_propagation: *with_propagation,
+                with_propagation: *with_propagation || *via == ViaOpt::Dataflow,
             },
         },
         Some(Command::Id { raw, .. }) => SchemaProfileInput {
@@ -663,6 +1474,51 @@ fn resolve_schema_profile_for_args(
                 subcommand: "completions",
             });
         }
+        Some(Command::Graph) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "graph",
+            });
+        }
+        Some(Command::Cycles) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "cycles",
+            });
+        }
+        Some(Command::Metrics { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "metrics",
+            });
+        }
+        Some(Command::EngineCheck { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "engine-check",
+            });
+        }
+        Some(Command::Deadcode { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "deadcode",
+            });
+        }
+        Some(Command::Files { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "files",
+            });
+        }
+        Some(Command::Tests { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "tests",
+            });
+        }
+        Some(Command::Dfg { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "dfg",
+            });
+        }
+        Some(Command::Watch { .. }) => {
+            return Err(dimpact::SchemaProfileResolveError::UnsupportedCommand {
+                subcommand: "watch",
+            });
+        }
         None => match args.mode {
             Mode::Diff => SchemaProfileInput {
                 format: schema_output_format(args.format),
@@ -688,6 +1544,7 @@ fn resolve_schema_profile_for_args(
 }
 
 fn run_schema(
+    output: &mut Output,
     fmt: OutputFormat,
     list: bool,
     schema_id: Option<&str>,
@@ -711,9 +1568,13 @@ fn run_schema(
                 })
                 .collect();
             match fmt {
-                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&items)?),
-                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&items)?),
-                OutputFormat::Dot | OutputFormat::Html => {
+                OutputFormat::Json => print_json_output(output, &items)?,
+                OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&items)?)?,
+                OutputFormat::Dot
+                | OutputFormat::Graphml
+                | OutputFormat::Html
+                | OutputFormat::GithubActions
+                | OutputFormat::Text => {
                     anyhow::bail!("schema --list supports only json or yaml output")
                 }
             }
@@ -722,16 +1583,20 @@ fn run_schema(
             let document = read_schema_document(schema_id)?;
             match fmt {
                 OutputFormat::Json => {
-                    print!("{document}");
+                    write!(output, "{document}")?;
                     if !document.ends_with('\n') {
-                        println!();
+                        writeln!(output)?;
                     }
                 }
                 OutputFormat::Yaml => {
                     let value: serde_json::Value = serde_json::from_str(&document)?;
-                    print!("{}", serde_yaml::to_string(&value)?);
+                    write!(output, "{}", serde_yaml::to_string(&value)?)?;
                 }
-                OutputFormat::Dot | OutputFormat::Html => {
+                OutputFormat::Dot
+                | OutputFormat::Graphml
+                | OutputFormat::Html
+                | OutputFormat::GithubActions
+                | OutputFormat::Text => {
                     anyhow::bail!("schema --id supports only json or yaml output")
                 }
             }
@@ -745,14 +1610,14 @@ fn run_schema(
             }
 
             let resolved = resolve_schema_profile_for_target(fmt, target)?;
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&SchemaResolveResult {
+            print_json_output(
+                output,
+                SchemaResolveResult {
                     profile: resolved.profile_slug,
                     schema_id: resolved.schema_id,
                     schema_path: resolved.schema_path,
-                })?
-            );
+                },
+            )?;
         }
         (true, Some(_), _) | (true, _, Some(_)) | (false, Some(_), Some(_)) => {
             anyhow::bail!("choose exactly one of --list, --id, or resolve")
@@ -765,11 +1630,155 @@ fn run_schema(
     Ok(())
 }
 
+/// Workspace config files, in lookup order; the first one found is used.
+/// JSON is read as YAML; TOML is not supported.
+const CONFIG_FILES: &[&str] = &[".dimpact.yaml", ".dimpact.yml", ".dimpact.json"];
+
+/// Shared defaults for command-line flags, read from a workspace config file
+/// and then `DIMPACT_*` environment variables. Each one becomes the default
+/// of the flag with the same name on every subcommand that has it, so an
+/// explicit flag still wins.
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigDefaults {
+    engine: Option<String>,
+    lang: Option<String>,
+    direction: Option<String>,
+    max_depth: Option<usize>,
+    #[serde(default)]
+    ignore_dir: Vec<String>,
+    #[serde(default)]
+    map: Vec<String>,
+}
+
+impl ConfigDefaults {
+    /// The first of [`CONFIG_FILES`] under `root`, or no defaults.
+    fn load(root: &std::path::Path) -> anyhow::Result<Self> {
+        for name in CONFIG_FILES {
+            let path = root.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            if text.trim().is_empty() {
+                return Ok(Self::default());
+            }
+            return serde_yaml::from_str(&text)
+                .with_context(|| format!("invalid config file {}", path.display()));
+        }
+        Ok(Self::default())
+    }
+
+    /// Override with `DIMPACT_ENGINE`, `DIMPACT_LANG`, `DIMPACT_DIRECTION`,
+    /// `DIMPACT_MAX_DEPTH` and `DIMPACT_IGNORE_DIR` (comma-separated) when set.
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> anyhow::Result<()> {
+        if let Some(v) = var("DIMPACT_ENGINE") {
+            self.engine = Some(v);
+        }
+        if let Some(v) = var("DIMPACT_LANG") {
+            self.lang = Some(v);
+        }
+        if let Some(v) = var("DIMPACT_DIRECTION") {
+            self.direction = Some(v);
+        }
+        if let Some(v) = var("DIMPACT_MAX_DEPTH") {
+            let depth = v
+                .parse()
+                .with_context(|| format!("invalid DIMPACT_MAX_DEPTH {v:?}"))?;
+            self.max_depth = Some(depth);
+        }
+        if let Some(v) = var("DIMPACT_IGNORE_DIR") {
+            self.ignore_dir = v
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// `cmd` with these values as flag defaults, on it and all subcommands.
+    fn apply(&self, mut cmd: clap::Command) -> clap::Command {
+        let singles = [
+            ("engine", self.engine.clone()),
+            ("lang", self.lang.clone()),
+            ("direction", self.direction.clone()),
+            ("max_depth", self.max_depth.map(|d| d.to_string())),
+        ];
+        for (id, value) in singles {
+            let Some(value) = value else { continue };
+            let Some(arg) = cmd.get_arguments().find(|a| a.get_id() == id) else {
+                continue;
+            };
+            // A list like `lang: rust,ruby` only applies where the flag takes several
+            if let Some(delimiter) = arg.get_value_delimiter() {
+                let values: Vec<String> = value
+                    .split(delimiter)
+                    .map(|v| v.trim().to_string())
+                    .collect();
+                cmd = cmd.mut_arg(id, |a| a.default_values(values));
+            } else if !value.contains(',') {
+                cmd = cmd.mut_arg(id, |a| a.default_value(value));
+            }
+        }
+        let lists = [("ignore_dir", &self.ignore_dir), ("map", &self.map)];
+        for (id, values) in lists {
+            if !values.is_empty() && cmd.get_arguments().any(|a| a.get_id() == id) {
+                let values = values.clone();
+                cmd = cmd.mut_arg(id, |a| a.default_values(values));
+            }
+        }
+        let names: Vec<String> = cmd
+            .get_subcommands()
+            .map(|c| c.get_name().to_string())
+            .collect();
+        for name in names {
+            cmd = cmd.mut_subcommand(name, |c| self.apply(c));
+        }
+        cmd
+    }
+}
+
+/// The directory given to `--root` on the raw command line, so the config
+/// file can be located before the arguments are parsed.
+fn root_from_argv(argv: &[std::ffi::OsString]) -> Option<std::path::PathBuf> {
+    let mut iter = argv.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == "--" {
+            break;
+        }
+        if arg == "--root" {
+            return iter.next().map(std::path::PathBuf::from);
+        }
+        if let Some(dir) = arg.strip_prefix("--root=") {
+            return Some(std::path::PathBuf::from(dir));
+        }
+    }
+    None
+}
+
 fn main() -> anyhow::Result<()> {
-    // Initialize logger once; default level comes from RUST_LOG
-    let _ = env_logger::Builder::from_env(Env::default().default_filter_or(""))
-        .format_timestamp(None)
-        .try_init();
+    let started = std::time::Instant::now();
+    let argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    // Without --root the config comes from the repository root, as the cache does
+    let root = root_from_argv(&argv)
+        .or_else(cache::find_repo_root)
+        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    let mut config = ConfigDefaults::load(&root)?;
+    config.apply_env(|name| std::env::var(name).ok())?;
+    let matches = config.apply(Args::command()).get_matches_from(argv);
+    let mut args = Args::from_arg_matches(&matches)?;
+    // Initialize logger once; RUST_LOG overrides the level chosen by -q/-v
+    let _ = env_logger::Builder::from_env(
+        Env::default().default_filter_or(log_filter(args.quiet, args.verbose)),
+    )
+    .format_timestamp(None)
+    .try_init();
     // Optional parallelism override for rayon (for cache build/update)
     if let Ok(j) = std::env::var("DIMPACT_JOBS")
         && let Ok(n) = j.parse::<usize>()
@@ -779,8 +1788,55 @@ fn main() -> anyhow::Result<()> {
             .build_global();
     }
 
-    let args = Args::parse();
+    // Humans running `impact` in a terminal get the text tree unless -f is given
+    if matches!(
+        args.cmd,
+        Some(Command::Impact { .. })
+            | Some(Command::Watch {
+                changed_only: false,
+                ..
+            })
+    ) && args.output.is_none()
+        && matches.value_source("format") == Some(ValueSource::DefaultValue)
+        && std::io::stdout().is_terminal()
+    {
+        args.format = OutputFormat::Text;
+    }
+
+    let profile = args.profile;
+    dimpact::profile::set_enabled(profile);
+    // --jobs takes precedence over DIMPACT_JOBS and only applies to this invocation
+    let result = if let Some(n) = args.jobs {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(n).build()?;
+        pool.install(|| run(args))
+    } else {
+        run(args)
+    };
+    if profile {
+        eprint!("{}", dimpact::profile::report(started.elapsed()));
+    }
+    let code = result?;
+    if code != 0 {
+        use std::io::Write;
+        std::io::stdout().flush()?;
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Default log filter for the -q/-v flags; warnings show unless silenced.
+fn log_filter(quiet: bool, verbose: u8) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "info",
+        (false, 2) => "debug",
+        (false, _) => "trace",
+    }
+}
 
+/// Run the parsed command; returns the process exit code.
+fn run(args: Args) -> anyhow::Result<i32> {
     if let Ok(resolved) = resolve_schema_profile_for_args(&args) {
         log::debug!(
             "resolved_schema_profile={} schema_id={}",
@@ -789,42 +1845,77 @@ fn main() -> anyhow::Result<()> {
         );
     }
 
-    // Prefer subcommands if provided; fallback to deprecated --mode
-    if let Some(cmd) = args.cmd {
-        match cmd {
-            Command::Diff => run_diff(args.format),
-            Command::Changed {
-                lang,
+    let mut output = Output::open(args.output.as_deref(), args.compact)?;
+    // Only the workspace moves; paths given on the command line stay relative
+    // to where dimpact was invoked
+    let root = args
+        .root
+        .as_deref()
+        .map(|root| {
+            root.canonicalize()
+                .with_context(|| format!("invalid --root {}", root.display()))
+        })
+        .transpose()?;
+
+    let diff = DiffSource {
+        files: args.diff_files.clone(),
+        git_args: git_diff_args(args.staged, args.worktree, args.since.as_deref()),
+        root: root.clone(),
+    };
+    let analysis = dimpact::AnalyzeOptions {
+        root: root.clone(),
+        ..analyze_options(&args)
+    };
+    let mapping = dimpact::MappingOptions {
+        ignore_whitespace: args.ignore_whitespace,
+        analyzers: analyzer_config(&args),
+        root,
+    };
+    let html_opts = HtmlOptions {
+        dark_mode: args.html_dark,
+        inline_assets: !args.html_external_assets,
+    };
+
+    // Prefer subcommands if provided; fallback to deprecated --mode
+    if let Some(cmd) = args.cmd {
+        let mut exit_code = 0;
+        match cmd {
+            Command::Diff => run_diff(&mut output, args.format, &diff),
+            Command::Changed {
+                lang,
                 engine,
                 engine_lsp_strict,
                 engine_dump_capabilities,
+                with_change_kind,
+                with_change_scope,
+                with_snippets,
+                with_changed_lines,
+                max_results,
             } => run_changed(
+                &mut output,
                 args.format,
-                lang,
+                &diff,
+                &lang,
                 engine,
                 args.auto_policy,
                 engine_lsp_strict,
                 engine_dump_capabilities,
+                ChangedExtras {
+                    with_change_kind,
+                    with_change_scope,
+                    with_snippets,
+                    with_changed_lines,
+                    max_results,
+                },
+                &analysis,
+                &mapping,
             ),
             Command::Impact {
-                lang,
-                direction,
-                max_depth,
-                with_edges,
-                min_confidence,
-                exclude_dynamic_fallback,
-                op_profile,
-                with_pdg,
-                with_propagation,
-                engine,
-                engine_lsp_strict,
-                engine_dump_capabilities,
-                seed_symbols,
-                seed_json,
+                explain: Some(symbol_id),
                 ignore_dir,
-                per_seed,
-            } => run_impact(
-                args.format,
+                ..
+            } => run_explain(&mut output, args.format, &symbol_id, ignore_dir, &analysis),
+            Command::Impact {
                 lang,
                 direction,
                 max_depth,
@@ -834,15 +1925,96 @@ fn main() -> anyhow::Result<()> {
                 op_profile,
                 with_pdg,
                 with_propagation,
+                via,
                 engine,
-                args.auto_policy,
                 engine_lsp_strict,
                 engine_dump_capabilities,
                 seed_symbols,
                 seed_json,
+                seed_files,
+                union_seeds,
                 ignore_dir,
                 per_seed,
-            ),
+                graph_base,
+                api_only,
+                edge_kinds,
+                edge_weights,
+                exclude_tests,
+                with_snippets,
+                depth_histogram,
+                max_results,
+                max_symbols,
+                max_edges,
+                max_visited,
+                time_budget_ms,
+                explain: None,
+                no_impact_exit_code,
+                baseline,
+            } => {
+                let baseline = baseline
+                    .map(|path| load_impact_baseline(&path))
+                    .transpose()?;
+                if via == ViaOpt::Dataflow && api_only {
+                    anyhow::bail!("--api-only cannot be used with --via dataflow");
+                }
+                let printed_no_impact = run_impact(
+                    &mut output,
+                    args.format,
+                    &diff,
+                    &lang,
+                    direction,
+                    max_depth,
+                    with_edges,
+                    min_confidence,
+                    exclude_dynamic_fallback,
+                    op_profile,
+                    with_pdg,
+                    with_propagation || via == ViaOpt::Dataflow,
+                    engine,
+                    args.auto_policy,
+                    engine_lsp_strict,
+                    engine_dump_capabilities,
+                    SeedInput {
+                        symbols: seed_symbols,
+                        json: seed_json,
+                        files: seed_files,
+                        union_with_diff: union_seeds,
+                    },
+                    ignore_dir,
+                    per_seed,
+                    graph_base,
+                    api_only,
+                    edge_kinds,
+                    edge_weights,
+                    exclude_tests,
+                    ImpactExtras {
+                        with_snippets,
+                        depth_histogram,
+                        max_results,
+                    },
+                    GraphLimits {
+                        max_symbols,
+                        max_edges,
+                        max_visited,
+                    },
+                    time_budget_ms,
+                    baseline,
+                    html_opts,
+                    &analysis,
+                    &mapping,
+                )?;
+                if printed_no_impact {
+                    exit_code = no_impact_exit_code.unwrap_or(0);
+                }
+                Ok(())
+            }
+            Command::Id {
+                lang,
+                kind,
+                raw,
+                from_diff: true,
+                ..
+            } => run_id_from_diff(&mut output, args.format, &diff, &lang, kind, raw, &mapping),
             Command::Id {
                 path,
                 line,
@@ -850,45 +2022,144 @@ fn main() -> anyhow::Result<()> {
                 lang,
                 kind,
                 raw,
+                from_diff: false,
             } => run_id(
+                &mut output,
                 args.format,
                 path.as_deref(),
                 line,
                 name.as_deref(),
-                lang,
+                &lang,
                 kind,
                 raw,
+                &analysis.analyzers,
+                analysis.root.as_deref(),
             ),
             Command::Schema {
                 list,
                 schema_id,
                 cmd,
-            } => run_schema(args.format, list, schema_id.as_deref(), cmd.as_ref()),
-            Command::Cache { cmd } => run_cache(cmd),
-            Command::Completions { shell } => run_completions(shell),
+            } => run_schema(
+                &mut output,
+                args.format,
+                list,
+                schema_id.as_deref(),
+                cmd.as_ref(),
+            ),
+            Command::Cache { cmd } => {
+                run_cache(&mut output, cmd, args.format, &html_opts, &analysis)
+            }
+            Command::Completions { shell } => run_completions(&mut output, shell),
+            Command::Graph => run_graph(&mut output, args.format, &analysis),
+            Command::Cycles => run_cycles(&mut output, args.format, &analysis),
+            Command::Metrics { top } => run_metrics(&mut output, args.format, top, &analysis),
+            Command::EngineCheck { lang } => {
+                run_engine_check(&mut output, args.format, &lang, analysis.root.as_deref())
+            }
+            Command::Deadcode {
+                pub_as_roots,
+                exclude_tests,
+            } => run_deadcode(
+                &mut output,
+                args.format,
+                pub_as_roots,
+                exclude_tests,
+                &analysis,
+            ),
+            Command::Files { ignore_dir } => {
+                run_files(&mut output, args.format, ignore_dir, &analysis)
+            }
+            Command::Tests {
+                max_depth,
+                ignore_dir,
+            } => run_tests(
+                &mut output,
+                args.format,
+                &diff,
+                max_depth,
+                ignore_dir,
+                &analysis,
+            ),
+            Command::Dfg {
+                path,
+                lang,
+                control_only,
+                data_only,
+            } => run_dfg(
+                &mut output,
+                args.format,
+                &path,
+                lang,
+                control_only,
+                data_only,
+                &analysis.analyzers,
+            ),
+            Command::Watch {
+                lang,
+                direction,
+                max_depth,
+                ignore_dir,
+                changed_only,
+                debounce_ms,
+                max_runs,
+            } => run_watch(
+                args.format,
+                &diff,
+                &lang,
+                ImpactOptions {
+                    direction: match direction {
+                        DirectionOpt::Callers => ImpactDirection::Callers,
+                        DirectionOpt::Callees => ImpactDirection::Callees,
+                        DirectionOpt::Both => ImpactDirection::Both,
+                    },
+                    max_depth: max_depth.or(Some(100)),
+                    with_edges: Some(false),
+                    ignore_dirs: ignore_dir,
+                    root: analysis.root.clone(),
+                    ..ImpactOptions::default()
+                },
+                WatchSettings {
+                    changed_only,
+                    debounce: std::time::Duration::from_millis(debounce_ms),
+                    max_runs,
+                    output: args.output.clone(),
+                    compact: args.compact,
+                },
+                &html_opts,
+                &analysis,
+                &mapping,
+            ),
         }?;
-        return Ok(());
+        output.flush()?;
+        return Ok(exit_code);
     }
 
     match args.mode {
         Mode::Diff => {
-            run_diff(args.format)?;
+            run_diff(&mut output, args.format, &diff)?;
         }
         Mode::Changed => {
             run_changed(
+                &mut output,
                 args.format,
-                args.lang,
+                &diff,
+                &[args.lang],
                 args.engine,
                 args.auto_policy,
                 args.engine_lsp_strict,
                 args.engine_dump_capabilities,
+                ChangedExtras::default(),
+                &analysis,
+                &mapping,
             )?;
         }
         Mode::Impact => {
             // PDG mode not available in deprecated mode
             run_impact(
+                &mut output,
                 args.format,
-                args.lang,
+                &diff,
+                &[args.lang],
                 args.direction,
                 args.max_depth,
                 args.with_edges,
@@ -901,18 +2172,41 @@ fn main() -> anyhow::Result<()> {
                 args.auto_policy,
                 args.engine_lsp_strict,
                 args.engine_dump_capabilities,
-                args.seed_symbols,
-                args.seed_json,
+                SeedInput {
+                    symbols: args.seed_symbols,
+                    json: args.seed_json,
+                    files: Vec::new(),
+                    union_with_diff: false,
+                },
                 args.ignore_dir,
                 args.per_seed,
+                None,
+                false,
+                Vec::new(),
+                Vec::new(),
+                false,
+                ImpactExtras::default(),
+                GraphLimits::default(),
+                None,
+                None,
+                html_opts,
+                &analysis,
+                &mapping,
             )?;
         }
     }
 
-    Ok(())
+    output.flush()?;
+    Ok(0)
 }
 
-fn run_cache(cmd: CacheCmd) -> anyhow::Result<()> {
+fn run_cache(
+    output: &mut Output,
+    cmd: CacheCmd,
+    fmt: OutputFormat,
+    html_opts: &HtmlOptions,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
     match cmd {
         CacheCmd::Build { scope, dir } => {
             let scope = match scope {
@@ -920,8 +2214,8 @@ fn run_cache(cmd: CacheCmd) -> anyhow::Result<()> {
                 CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
             };
             let path_override = dir.as_deref().map(std::path::Path::new);
-            let mut db = dimpact::cache::open(scope, path_override)?;
-            let st = dimpact::cache::build_all(&mut db.conn)?;
+            let mut db = dimpact::cache::open(scope, path_override, analysis.root.as_deref())?;
+            let st = dimpact::cache::build_all(&mut db.conn, analysis)?;
             eprintln!(
                 "cache build: files={} symbols={} edges={}",
                 st.files, st.symbols, st.edges
@@ -933,9 +2227,9 @@ fn run_cache(cmd: CacheCmd) -> anyhow::Result<()> {
                 CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
             };
             let path_override = dir.as_deref().map(std::path::Path::new);
-            let mut db = dimpact::cache::open(scope, path_override)?;
+            let mut db = dimpact::cache::open(scope, path_override, analysis.root.as_deref())?;
             let st_before = dimpact::cache::stats(&db.conn)?;
-            let st_after = dimpact::cache::verify(&mut db.conn)?;
+            let st_after = dimpact::cache::verify(&mut db.conn, analysis)?;
             eprintln!(
                 "cache update: files={} symbols={} edges={} (was files={} symbols={} edges={})",
                 st_after.files,
@@ -952,46 +2246,715 @@ fn run_cache(cmd: CacheCmd) -> anyhow::Result<()> {
                 CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
             };
             let path_override = dir.as_deref().map(std::path::Path::new);
-            let db = dimpact::cache::open(scope, path_override)?;
+            let db = dimpact::cache::open(scope, path_override, analysis.root.as_deref())?;
             let st = dimpact::cache::stats(&db.conn)?;
-            println!(
+            writeln!(
+                output,
                 "{{\"files\":{},\"symbols\":{},\"edges\":{}}}",
                 st.files, st.symbols, st.edges
+            )?;
+        }
+        CacheCmd::Clear { scope, dir } => {
+            let scope = match scope {
+                CacheScopeOpt::Local => dimpact::cache::CacheScope::Local,
+                CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
+            };
+            let path_override = dir.as_deref().map(std::path::Path::new);
+            let paths =
+                dimpact::cache::resolve_paths(scope, path_override, analysis.root.as_deref())?;
+            dimpact::cache::clear(&paths)?;
+            eprintln!("cache cleared: {}", paths.db.display());
+        }
+        CacheCmd::Query {
+            scope,
+            dir,
+            seed_symbols,
+            seed_json,
+            direction,
+            max_depth,
+            with_edges,
+            edge_kinds,
+            edge_weights,
+            verify,
+        } => {
+            let seeds = collect_seeds(&seed_symbols, seed_json.as_deref(), analysis.root())?;
+            if seeds.is_empty() {
+                anyhow::bail!("cache query requires --seed-symbol or --seed-json");
+            }
+            let scope = match scope {
+                CacheScopeOpt::Local => dimpact::cache::CacheScope::Local,
+                CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
+            };
+            let path_override = dir.as_deref().map(std::path::Path::new);
+            let mut db = dimpact::cache::open(scope, path_override, analysis.root.as_deref())?;
+            if dimpact::cache::stats(&db.conn)?.symbols == 0 {
+                anyhow::bail!("cache is empty; run `dimpact cache build` first");
+            }
+            if verify {
+                dimpact::cache::verify(&mut db.conn, analysis)?;
+            } else {
+                let stale = dimpact::cache::stale_paths(&db.conn, analysis)?;
+                if !stale.is_empty() {
+                    anyhow::bail!(
+                        "cache is stale ({} file(s) changed); run `dimpact cache update` or pass --verify",
+                        stale.len()
+                    );
+                }
+            }
+            let (index, refs) = dimpact::cache::load_graph(&db.conn)?;
+            let opts = ImpactOptions {
+                direction: match direction {
+                    DirectionOpt::Callers => ImpactDirection::Callers,
+                    DirectionOpt::Callees => ImpactDirection::Callees,
+                    DirectionOpt::Both => ImpactDirection::Both,
+                },
+                max_depth: max_depth.or(Some(100)),
+                with_edges: Some(with_edges),
+                ignore_dirs: Vec::new(),
+                edge_kinds: edge_kinds.into_iter().map(EdgeKindOpt::ref_kind).collect(),
+                edge_weights: edge_weights.into_iter().collect(),
+                exclude_tests: false,
+                limits: GraphLimits::default(),
+                time_budget_ms: None,
+                languages: Vec::new(),
+                root: analysis.root.clone(),
+            };
+            log::info!(
+                "cache query: seeds={} symbols={} edges={}",
+                seeds.len(),
+                index.symbols.len(),
+                refs.len()
+            );
+            let out = compute_impact(&seeds, &index, &refs, &opts);
+            print_impact_output(output, fmt, &out, None, None, html_opts)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_completions(output: &mut Output, shell: CompletionShell) -> anyhow::Result<()> {
+    use clap_complete::{generate, shells};
+    let mut cmd = Args::command();
+    let name = cmd.get_name().to_string();
+    let mut buf: Vec<u8> = Vec::new();
+    match shell {
+        CompletionShell::Bash => generate(shells::Bash, &mut cmd, name, &mut buf),
+        CompletionShell::Zsh => generate(shells::Zsh, &mut cmd, name, &mut buf),
+        CompletionShell::Fish => generate(shells::Fish, &mut cmd, name, &mut buf),
+        CompletionShell::PowerShell => generate(shells::PowerShell, &mut cmd, name, &mut buf),
+        CompletionShell::Elvish => generate(shells::Elvish, &mut cmd, name, &mut buf),
+    }
+    write!(output, "{}", String::from_utf8_lossy(&buf))?;
+    Ok(())
+}
+
+fn load_project_graph(
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<(SymbolIndex, Vec<Reference>)> {
+    let (scope, dir_override) = cache::scope_from_env();
+    let mut db = cache::open(scope, dir_override.as_deref(), analysis.root.as_deref())?;
+    // Incremental once built: re-analyzes only files edited since the last run
+    cache::build_all(&mut db.conn, analysis)?;
+    cache::load_graph(&db.conn)
+}
+
+fn run_explain(
+    output: &mut Output,
+    fmt: OutputFormat,
+    symbol_id: &str,
+    ignore_dir: Vec<String>,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let opts = dimpact::AnalyzeOptions {
+        exclude: ignore_dir,
+        ..analysis.clone()
+    };
+    let traces = dimpact::explain_workspace(analysis.root(), opts, symbol_id)?;
+    eprintln!("explain: sites={}", traces.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &traces)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&traces)?)?,
+        OutputFormat::Text => {
+            for t in &traces {
+                writeln!(
+                    output,
+                    "{}:{} {}{} -> {} ({:?})",
+                    t.file,
+                    t.line,
+                    t.qualifier
+                        .as_deref()
+                        .map(|q| format!("{q}::"))
+                        .unwrap_or_default(),
+                    t.target_name,
+                    t.resolved_to.as_deref().unwrap_or("<unresolved>"),
+                    t.stage
+                )?;
+                if let Some(p) = &t.imported_prefix {
+                    writeln!(output, "  import prefix: {p}")?;
+                }
+                if !t.glob_prefixes.is_empty() {
+                    writeln!(output, "  glob prefixes: {}", t.glob_prefixes.join(", "))?;
+                }
+                for c in &t.candidates {
+                    let b = &c.score;
+                    writeln!(
+                        output,
+                        "  {:>4} {} (same_file {:+}, same_dir {:+}, qualifier {:+}, import_prefix {:+}, kind {:+})",
+                        c.total,
+                        c.symbol_id,
+                        b.same_file,
+                        b.same_dir,
+                        b.qualifier,
+                        b.import_prefix,
+                        b.kind
+                    )?;
+                }
+            }
+        }
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("impact --explain supports only json, yaml, or text output")
+        }
+    }
+    Ok(())
+}
+
+fn run_graph(
+    output: &mut Output,
+    fmt: OutputFormat,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let (index, refs) = load_project_graph(analysis)?;
+    let graph = dimpact::export_project_graph(&index, &refs);
+    eprintln!(
+        "graph: nodes={} edges={}",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &graph)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&graph)?)?,
+        OutputFormat::Dot => write!(output, "{}", dimpact::project_graph_to_dot(&graph))?,
+        OutputFormat::Graphml => write!(output, "{}", dimpact::project_graph_to_graphml(&graph))?,
+        OutputFormat::Html | OutputFormat::GithubActions | OutputFormat::Text => {
+            anyhow::bail!("graph supports only json, yaml, dot, or graphml output")
+        }
+    }
+    Ok(())
+}
+
+fn run_cycles(
+    output: &mut Output,
+    fmt: OutputFormat,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let (index, refs) = load_project_graph(analysis)?;
+    let cycles = dimpact::find_cycles(&index, &refs);
+    eprintln!("cycles: found={}", cycles.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &cycles)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&cycles)?)?,
+        OutputFormat::Dot => write!(output, "{}", dimpact::cycles_to_dot(&cycles, &refs))?,
+        OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
+            anyhow::bail!("cycles supports only json, yaml, or dot output")
+        }
+    }
+    Ok(())
+}
+
+fn run_metrics(
+    output: &mut Output,
+    fmt: OutputFormat,
+    top: Option<usize>,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let (index, refs) = load_project_graph(analysis)?;
+    let mut metrics = dimpact::compute_symbol_metrics(&index, &refs);
+    if let Some(n) = top {
+        metrics.truncate(n);
+    }
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &metrics)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&metrics)?)?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
+            anyhow::bail!("metrics supports only json or yaml output")
+        }
+    }
+    Ok(())
+}
+
+fn run_deadcode(
+    output: &mut Output,
+    fmt: OutputFormat,
+    pub_as_roots: bool,
+    exclude_tests: bool,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let (index, refs) = dimpact::analyze_workspace(analysis.root(), analysis.clone())?;
+    let opts = dimpact::DeadCodeOptions {
+        pub_as_roots,
+        exclude_test_files: exclude_tests,
+        root: analysis.root.clone(),
+    };
+    let dead = dimpact::find_dead_code(&index, &refs, &opts);
+    eprintln!("deadcode: found={}", dead.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &dead)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&dead)?)?,
+        OutputFormat::Text => write!(output, "{}", dimpact::dead_code_to_text(&dead))?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("deadcode supports only json, yaml, or text output")
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct AnalyzedFile {
+    path: String,
+    language: &'static str,
+}
+
+fn run_files(
+    output: &mut Output,
+    fmt: OutputFormat,
+    ignore_dir: Vec<String>,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let opts = dimpact::AnalyzeOptions {
+        exclude: ignore_dir,
+        ..analysis.clone()
+    };
+    let files: Vec<AnalyzedFile> = dimpact::list_analyzed_files(analysis.root(), &opts)?
+        .into_iter()
+        .map(|(path, kind)| AnalyzedFile {
+            path,
+            language: kind.name(),
+        })
+        .collect();
+    eprintln!("files: count={}", files.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &files)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&files)?)?,
+        OutputFormat::Text => {
+            for f in &files {
+                writeln!(output, "{}", f.path)?;
+            }
+        }
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("files supports only json, yaml, or text output")
+        }
+    }
+    Ok(())
+}
+
+fn run_tests(
+    output: &mut Output,
+    fmt: OutputFormat,
+    diff: &DiffSource,
+    max_depth: Option<usize>,
+    ignore_dir: Vec<String>,
+    analysis: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<()> {
+    let files = read_file_changes(diff)?;
+    let (index, refs) = dimpact::analyze_workspace(analysis.root(), analysis.clone())?;
+    let changed = changed_symbols_in_index(&files, &index);
+    let opts = ImpactOptions {
+        direction: ImpactDirection::Callers,
+        max_depth: max_depth.or(Some(100)),
+        with_edges: Some(false),
+        ignore_dirs: ignore_dir,
+        root: analysis.root.clone(),
+        ..ImpactOptions::default()
+    };
+    let tests = dimpact::find_impacted_tests(&changed, &index, &refs, &opts);
+    eprintln!("tests: changed={} impacted={}", changed.len(), tests.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &tests)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&tests)?)?,
+        OutputFormat::Text => write!(output, "{}", dimpact::impacted_tests_to_text(&tests))?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("tests supports only json, yaml, or text output")
+        }
+    }
+    Ok(())
+}
+
+/// How `watch` paces and limits its re-runs.
+struct WatchSettings {
+    changed_only: bool,
+    debounce: std::time::Duration,
+    max_runs: Option<usize>,
+    /// `--output`, rewritten with each run's result.
+    output: Option<std::path::PathBuf>,
+    compact: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_watch(
+    fmt: OutputFormat,
+    diff: &DiffSource,
+    langs: &[LangOpt],
+    mut opts: ImpactOptions,
+    settings: WatchSettings,
+    html_opts: &HtmlOptions,
+    analysis: &dimpact::AnalyzeOptions,
+    mapping: &dimpact::MappingOptions,
+) -> anyhow::Result<()> {
+    use notify::Watcher;
+    if settings.changed_only && !matches!(fmt, OutputFormat::Json | OutputFormat::Yaml) {
+        anyhow::bail!("format not supported for 'watch --changed-only': use json|yaml");
+    }
+    let (lang_opt, languages) = lang_selection(langs);
+    opts.languages = languages;
+    let lang = match lang_opt {
+        LangOpt::Auto => LanguageMode::Auto,
+        LangOpt::Rust => LanguageMode::Rust,
+        LangOpt::Ruby => LanguageMode::Ruby,
+        LangOpt::Python => LanguageMode::Python,
+        LangOpt::Javascript => LanguageMode::Javascript,
+        LangOpt::Typescript => LanguageMode::Typescript,
+        LangOpt::Tsx => LanguageMode::Tsx,
+        LangOpt::Go => LanguageMode::Go,
+        LangOpt::Java => LanguageMode::Java,
+        LangOpt::Kotlin => LanguageMode::Kotlin,
+        LangOpt::Cpp => LanguageMode::Cpp,
+    };
+    // Without --staged/--since, watch the unstaged changes
+    let diff = DiffSource {
+        files: diff.files.clone(),
+        git_args: diff
+            .git_args
+            .clone()
+            .or_else(|| git_diff_args(false, true, None)),
+        root: diff.root.clone(),
+    };
+    if diff_reads_stdin(&diff) {
+        anyhow::bail!("watch re-reads the diff on every run and cannot take it from stdin");
+    }
+
+    let (scope, dir_override) = cache::scope_from_env();
+    let mut db = cache::open(scope, dir_override.as_deref(), analysis.root.as_deref())?;
+    if cache::stats(&db.conn)?.symbols == 0 {
+        cache::build_all(&mut db.conn, analysis)?;
+    } else {
+        cache::verify(&mut db.conn, analysis)?;
+    }
+
+    let root = analysis.root().canonicalize()?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&root, notify::RecursiveMode::Recursive)?;
+
+    let mut touched: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    let mut runs = 0;
+    loop {
+        if !touched.is_empty() {
+            let paths: Vec<String> = std::mem::take(&mut touched).into_iter().collect();
+            cache::update_paths(&mut db.conn, &paths, analysis)?;
+        }
+        runs += 1;
+        // Everything a run reports on is rebuilt here, never carried over
+        let report = (|| -> anyhow::Result<()> {
+            let mut output = Output::open(settings.output.as_deref(), settings.compact)?;
+            let files = read_file_changes(&diff)?;
+            let ecfg = EngineConfig {
+                lsp_strict: false,
+                dump_capabilities: false,
+                mock_lsp: false,
+                mock_caps: None,
+                analysis: analysis.clone(),
+                mapping: mapping.clone(),
+                lsp_retry: Default::default(),
+            };
+            let engine = make_engine_with_auto_policy(EngineKind::Ts, ecfg, None);
+            let mut changed: ChangedOutput = engine.changed_symbols(&files, lang)?;
+            retain_languages(&mut changed, &opts.languages, &mapping.analyzers);
+            changed
+                .changed_symbols
+                .retain(|s| !dimpact::impact::path_is_ignored(&s.file, &opts.ignore_dirs));
+            if settings.changed_only {
+                eprintln!(
+                    "watch: run={} changed={}",
+                    runs,
+                    changed.changed_symbols.len()
+                );
+                match fmt {
+                    OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&changed)?)?,
+                    _ => print_json_output(&mut output, &changed)?,
+                }
+                output.flush()?;
+                return Ok(());
+            }
+            let (index, refs) = cache::load_graph(&db.conn)?;
+            let out = compute_impact(&changed.changed_symbols, &index, &refs, &opts);
+            eprintln!(
+                "watch: run={} changed={} impacted={}",
+                runs,
+                out.changed_symbols.len(),
+                out.impacted_symbols.len()
             );
+            print_impact_output(&mut output, fmt, &out, None, None, html_opts)?;
+            output.flush()?;
+            Ok(())
+        })();
+        // A broken intermediate state (e.g. mid-rebase) shouldn't end the session
+        if let Err(e) = report {
+            eprintln!("watch: run={runs} failed: {e:#}");
+        }
+        if settings.max_runs.is_some_and(|n| runs >= n) {
+            return Ok(());
+        }
+
+        // Block until a watched source file changes, then wait for the
+        // burst of saves to settle before re-running.
+        while touched.is_empty() {
+            let event = rx.recv().context("file watcher stopped")?;
+            collect_watch_paths(
+                event,
+                &root,
+                &opts.ignore_dirs,
+                &analysis.analyzers,
+                &mut touched,
+            );
+        }
+        while let Ok(event) = rx.recv_timeout(settings.debounce) {
+            collect_watch_paths(
+                event,
+                &root,
+                &opts.ignore_dirs,
+                &analysis.analyzers,
+                &mut touched,
+            );
+        }
+    }
+}
+
+/// Workspace-relative source files touched by a watcher event. Reads, VCS and
+/// cache internals, build output, and ignored directories don't count.
+fn collect_watch_paths(
+    event: notify::Result<notify::Event>,
+    root: &std::path::Path,
+    ignore_dirs: &[String],
+    analyzers: &dimpact::languages::AnalyzerConfig,
+    out: &mut std::collections::BTreeSet<String>,
+) {
+    let event = match event {
+        Ok(ev) => ev,
+        Err(e) => {
+            log::warn!("watch: {e}");
+            return;
+        }
+    };
+    if matches!(
+        event.kind,
+        notify::EventKind::Access(_) | notify::EventKind::Other
+    ) {
+        return;
+    }
+    for path in &event.paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        if rel.components().any(|c| {
+            matches!(
+                c.as_os_str().to_str(),
+                Some(".git" | ".dimpact" | "target" | "node_modules")
+            )
+        }) {
+            continue;
+        }
+        let rel = normalize_path_like(rel);
+        if analyzers.language_for_path(&rel).is_some()
+            && !dimpact::impact::path_is_ignored(&rel, ignore_dirs)
+        {
+            out.insert(rel);
+        }
+    }
+}
+
+fn run_dfg(
+    output: &mut Output,
+    fmt: OutputFormat,
+    path: &str,
+    lang_opt: LangOpt,
+    control_only: bool,
+    data_only: bool,
+    analyzers: &dimpact::languages::AnalyzerConfig,
+) -> anyhow::Result<()> {
+    let lang = match lang_opt {
+        LangOpt::Auto => analyzers.language_for_path(path),
+        LangOpt::Rust => Some(LanguageKind::Rust),
+        LangOpt::Ruby => Some(LanguageKind::Ruby),
+        _ => None,
+    };
+    let src = fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+    let mut dfg = match lang {
+        Some(LanguageKind::Rust) => RustDfgBuilder::build(path, &src),
+        Some(LanguageKind::Ruby) => RubyDfgBuilder::build(path, &src),
+        _ => anyhow::bail!("dfg supports only rust and ruby files: {path}"),
+    };
+    if control_only {
+        dfg.edges.retain(|e| e.kind == DependencyKind::Control);
+    } else if data_only {
+        dfg.edges.retain(|e| e.kind == DependencyKind::Data);
+    }
+    eprintln!("dfg: nodes={} edges={}", dfg.nodes.len(), dfg.edges.len());
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &dfg)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&dfg)?)?,
+        OutputFormat::Dot => write!(output, "{}", dfg_to_dot(&dfg))?,
+        OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
+            anyhow::bail!("dfg supports only json, yaml, or dot output")
+        }
+    }
+    Ok(())
+}
+
+fn run_engine_check(
+    output: &mut Output,
+    fmt: OutputFormat,
+    lang_opts: &[LangOpt],
+    root: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    use dimpact::engine::lsp::{LspConfig, check_language_server};
+    let servers = |lang_opt: LangOpt| match lang_opt {
+        // JS/TS/TSX share typescript-language-server
+        LangOpt::Auto => vec![
+            LanguageMode::Rust,
+            LanguageMode::Ruby,
+            LanguageMode::Python,
+            LanguageMode::Typescript,
+            LanguageMode::Go,
+            LanguageMode::Java,
+            LanguageMode::Kotlin,
+            LanguageMode::Cpp,
+        ],
+        LangOpt::Rust => vec![LanguageMode::Rust],
+        LangOpt::Ruby => vec![LanguageMode::Ruby],
+        LangOpt::Python => vec![LanguageMode::Python],
+        LangOpt::Javascript => vec![LanguageMode::Javascript],
+        LangOpt::Typescript => vec![LanguageMode::Typescript],
+        LangOpt::Tsx => vec![LanguageMode::Tsx],
+        LangOpt::Go => vec![LanguageMode::Go],
+        LangOpt::Java => vec![LanguageMode::Java],
+        LangOpt::Kotlin => vec![LanguageMode::Kotlin],
+        LangOpt::Cpp => vec![LanguageMode::Cpp],
+    };
+    let selected = if lang_selection(lang_opts).1.is_empty() {
+        vec![LangOpt::Auto]
+    } else {
+        lang_opts.to_vec()
+    };
+    let mut langs: Vec<LanguageMode> = Vec::new();
+    for l in selected {
+        for server in servers(l) {
+            if !langs.contains(&server) {
+                langs.push(server);
+            }
+        }
+    }
+    let files = collect_candidate_files(None, lang_opts, root)?;
+    let cfg = LspConfig {
+        mock: std::env::var("DIMPACT_TEST_LSP_MOCK").ok().as_deref() == Some("1"),
+        root: root.map(std::path::Path::to_path_buf),
+        ..LspConfig::default()
+    };
+    let checks: Vec<_> = langs
+        .into_iter()
+        .map(|lang| check_language_server(lang, cfg.clone(), &files))
+        .collect();
+    match fmt {
+        OutputFormat::Json => print_json_output(output, &checks)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&checks)?)?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
+            anyhow::bail!("engine-check supports only json or yaml output")
         }
-        CacheCmd::Clear { scope, dir } => {
-            let scope = match scope {
-                CacheScopeOpt::Local => dimpact::cache::CacheScope::Local,
-                CacheScopeOpt::Global => dimpact::cache::CacheScope::Global,
-            };
-            let path_override = dir.as_deref().map(std::path::Path::new);
-            let paths = dimpact::cache::resolve_paths(scope, path_override, None)?;
-            dimpact::cache::clear(&paths)?;
-            eprintln!("cache cleared: {}", paths.db.display());
+    }
+    for c in &checks {
+        match &c.reason {
+            None => eprintln!("engine-check: {}: pass", c.language),
+            Some(reason) => eprintln!("engine-check: {}: fail ({})", c.language, reason),
         }
     }
+    if !checks.iter().any(|c| c.ok) {
+        anyhow::bail!("no usable language server found");
+    }
     Ok(())
 }
 
-fn run_completions(shell: CompletionShell) -> anyhow::Result<()> {
-    use clap_complete::{generate, shells};
-    let mut cmd = Args::command();
-    let name = cmd.get_name().to_string();
-    match shell {
-        CompletionShell::Bash => generate(shells::Bash, &mut cmd, name, &mut std::io::stdout()),
-        CompletionShell::Zsh => generate(shells::Zsh, &mut cmd, name, &mut std::io::stdout()),
-        CompletionShell::Fish => generate(shells::Fish, &mut cmd, name, &mut std::io::stdout()),
-        CompletionShell::PowerShell => {
-            generate(shells::PowerShell, &mut cmd, name, &mut std::io::stdout())
+// A/B compare helpers removed in TS-only mode
+
+/// The files changed by the diff under analysis: each --diff patch parsed on
+/// its own and merged, or else the single diff from git or stdin. A path
+/// touched several times appears once.
+fn read_file_changes(diff: &DiffSource) -> anyhow::Result<Vec<FileChanges>> {
+    let texts = if diff.files.is_empty() {
+        vec![read_diff_from_stdin(diff)?]
+    } else {
+        diff.files
+            .iter()
+            .map(|p| read_diff_file(p))
+            .collect::<anyhow::Result<Vec<_>>>()?
+    };
+    let mut batches = Vec::new();
+    for text in &texts {
+        match parse_unified_diff(text) {
+            Ok(f) => batches.push(f),
+            Err(DiffParseError::MissingHeader) => {}
+            Err(e) => return Err(anyhow::anyhow!(e)),
         }
-        CompletionShell::Elvish => generate(shells::Elvish, &mut cmd, name, &mut std::io::stdout()),
     }
-    Ok(())
+    Ok(merge_file_changes(batches))
 }
 
-// A/B compare helpers removed in TS-only mode
+fn read_diff_file(path: &str) -> anyhow::Result<String> {
+    if path == "-" {
+        let mut s = String::new();
+        io::stdin().read_to_string(&mut s)?;
+        return Ok(s);
+    }
+    fs::read_to_string(path).with_context(|| format!("failed to read --diff {path}"))
+}
+
+/// Whether [`read_file_changes`] consumes stdin.
+fn diff_reads_stdin(diff: &DiffSource) -> bool {
+    if diff.files.is_empty() {
+        diff.git_args.is_none()
+    } else {
+        diff.files.iter().any(|p| p == "-")
+    }
+}
 
-fn read_diff_from_stdin() -> anyhow::Result<String> {
+fn read_diff_from_stdin(diff: &DiffSource) -> anyhow::Result<String> {
+    if let Some(git_args) = &diff.git_args {
+        return read_diff_from_git(git_args, diff.root.as_deref());
+    }
     if std::io::stdin().is_terminal() {
         anyhow::bail!("no stdin detected: please pipe `git diff` output into dimpact");
     }
@@ -1000,29 +2963,38 @@ fn read_diff_from_stdin() -> anyhow::Result<String> {
     Ok(s)
 }
 
-fn parse_seed_symbol(s: &str) -> anyhow::Result<dimpact::Symbol> {
-    // Format: {LANG}:{PATH}:{KIND}:{NAME}:{LINE}
-    let parts: Vec<&str> = s.splitn(5, ':').collect();
-    if parts.len() != 5 {
-        anyhow::bail!("invalid seed symbol format: {}", s);
+/// Seed paths use the same workspace-relative form as the symbol index.
+fn seed_path(path: &str, root: &std::path::Path) -> String {
+    dimpact::normalize_path(path, root)
+}
+
+/// A seed KIND: an id kind code, or the long names `function`, `module` and
+/// `type_alias`.
+fn seed_kind(kind: &str) -> Option<dimpact::SymbolKind> {
+    match kind {
+        "function" => Some(dimpact::SymbolKind::Function),
+        "module" => Some(dimpact::SymbolKind::Module),
+        "type_alias" => Some(dimpact::SymbolKind::TypeAlias),
+        code => dimpact::SymbolKind::from_code(code),
     }
-    let lang = parts[0];
-    let file = parts[1];
-    let kind_str = parts[2];
-    let name = parts[3];
-    let line: u32 = parts[4]
-        .parse()
-        .map_err(|_| anyhow::anyhow!("invalid LINE in seed symbol: {}", parts[4]))?;
-
-    let kind = match kind_str {
-        "fn" | "function" => dimpact::SymbolKind::Function,
-        "method" => dimpact::SymbolKind::Method,
-        "struct" => dimpact::SymbolKind::Struct,
-        "enum" => dimpact::SymbolKind::Enum,
-        "trait" => dimpact::SymbolKind::Trait,
-        "mod" | "module" => dimpact::SymbolKind::Module,
-        other => anyhow::bail!("unknown KIND in seed symbol: {}", other),
-    };
+}
+
+fn parse_seed_symbol(s: &str, root: &std::path::Path) -> anyhow::Result<dimpact::Symbol> {
+    // Format: {LANG}:{PATH}:{KIND}:{NAME}:{LINE}; PATH may contain colons
+    let parts = dimpact::parse_symbol_id(s).ok_or_else(|| {
+        anyhow::anyhow!(
+            "invalid seed symbol format (want LANG:PATH:KIND:NAME:LINE): {}",
+            s
+        )
+    })?;
+    let lang = parts.lang.as_str();
+    let file = &seed_path(&parts.file, root);
+    let kind_str = parts.kind.as_str();
+    let name = &parts.name;
+    let line = parts.line;
+
+    let kind = seed_kind(kind_str)
+        .ok_or_else(|| anyhow::anyhow!("unknown KIND in seed symbol: {}", kind_str))?;
 
     let id = dimpact::SymbolId::new(lang, file, &kind, name, line);
     let sym = dimpact::Symbol {
@@ -1039,7 +3011,80 @@ fn parse_seed_symbol(s: &str) -> anyhow::Result<dimpact::Symbol> {
     Ok(sym)
 }
 
-fn parse_seed_json_input(arg: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
+/// Seed symbols from --seed-symbol/--seed-json/--seed-file and whether to add
+/// the diff's changed symbols to them (--union-seeds).
+struct SeedInput {
+    symbols: Vec<String>,
+    json: Option<String>,
+    files: Vec<String>,
+    union_with_diff: bool,
+}
+
+/// `first` followed by the symbols of `second` it doesn't already contain.
+fn union_symbols(
+    mut first: Vec<dimpact::Symbol>,
+    second: Vec<dimpact::Symbol>,
+) -> Vec<dimpact::Symbol> {
+    for sym in second {
+        if !first.iter().any(|s| s.id == sym.id) {
+            first.push(sym);
+        }
+    }
+    first
+}
+
+/// Every symbol defined in `files` (--seed-file), in line order per file,
+/// taken from the graph built from `source`.
+fn seeds_from_files(
+    files: &[String],
+    opts: &dimpact::AnalyzeOptions,
+) -> anyhow::Result<Vec<dimpact::Symbol>> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (index, _) = dimpact::analyze_workspace(opts.root(), opts.clone())?;
+    let cwd = std::env::current_dir()?;
+    let mut seeds = Vec::new();
+    for file in files {
+        // Given relative to where dimpact was invoked, not to --root
+        let key = match &opts.root {
+            Some(root) => dimpact::normalize_path(&cwd.join(file).to_string_lossy(), root),
+            None => dimpact::normalize_path(file, &cwd),
+        };
+        let mut syms = index
+            .by_file
+            .get(&key)
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::anyhow!("--seed-file {}: no analyzed symbols in this file", file)
+            })?;
+        syms.sort_by_key(|s| (s.range.start_line, s.range.end_line));
+        seeds = union_symbols(seeds, syms);
+    }
+    Ok(seeds)
+}
+
+/// Seeds from `--seed-json` followed by each `--seed-symbol`.
+fn collect_seeds(
+    seed_symbols: &[String],
+    seed_json: Option<&str>,
+    root: &std::path::Path,
+) -> anyhow::Result<Vec<dimpact::Symbol>> {
+    let mut seeds: Vec<dimpact::Symbol> = Vec::new();
+    if let Some(sj) = seed_json {
+        seeds.append(&mut parse_seed_json_input(sj, root)?);
+    }
+    for s in seed_symbols {
+        seeds.push(parse_seed_symbol(s, root)?);
+    }
+    Ok(seeds)
+}
+
+fn parse_seed_json_input(
+    arg: &str,
+    root: &std::path::Path,
+) -> anyhow::Result<Vec<dimpact::Symbol>> {
     // Determine source: stdin ('-'), file path, or inline JSON
     let content = if arg == "-" {
         let mut s = String::new();
@@ -1050,10 +3095,10 @@ fn parse_seed_json_input(arg: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
     } else {
         arg.to_string()
     };
-    parse_seed_json(&content)
+    parse_seed_json(&content, root)
 }
 
-fn parse_seed_json(content: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
+fn parse_seed_json(content: &str, root: &std::path::Path) -> anyhow::Result<Vec<dimpact::Symbol>> {
     let v: serde_json::Value = serde_json::from_str(content)
         .map_err(|e| anyhow::anyhow!("failed to parse seed JSON: {}", e))?;
     let arr = v
@@ -1062,13 +3107,13 @@ fn parse_seed_json(content: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
     let mut out: Vec<dimpact::Symbol> = Vec::with_capacity(arr.len());
     for item in arr {
         if let Some(s) = item.as_str() {
-            out.push(parse_seed_symbol(s)?);
+            out.push(parse_seed_symbol(s, root)?);
             continue;
         }
         if let Some(obj) = item.as_object() {
             // If { id: "..." } provided
             if let Some(serde_json::Value::String(id)) = obj.get("id") {
-                out.push(parse_seed_symbol(id)?);
+                out.push(parse_seed_symbol(id, root)?);
                 continue;
             }
             let lang = obj
@@ -1081,6 +3126,7 @@ fn parse_seed_json(content: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
                 .or_else(|| obj.get("file"))
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow::anyhow!("seed object missing 'path' or 'file'"))?;
+            let file = &seed_path(file, root);
             let kind_str = obj
                 .get("kind")
                 .and_then(|v| v.as_str())
@@ -1096,15 +3142,8 @@ fn parse_seed_json(content: &str) -> anyhow::Result<Vec<dimpact::Symbol>> {
                 .ok_or_else(|| anyhow::anyhow!("seed object missing 'line' or 'start_line'"))?
                 as u32;
 
-            let kind = match kind_str {
-                "fn" | "function" => dimpact::SymbolKind::Function,
-                "method" => dimpact::SymbolKind::Method,
-                "struct" => dimpact::SymbolKind::Struct,
-                "enum" => dimpact::SymbolKind::Enum,
-                "trait" => dimpact::SymbolKind::Trait,
-                "mod" | "module" => dimpact::SymbolKind::Module,
-                other => anyhow::bail!("unknown KIND in seed object: {}", other),
-            };
+            let kind = seed_kind(kind_str)
+                .ok_or_else(|| anyhow::anyhow!("unknown KIND in seed object: {}", kind_str))?;
             let id = dimpact::SymbolId::new(lang, file, &kind, name, line);
             out.push(dimpact::Symbol {
                 id,
@@ -1128,42 +3167,54 @@ fn map_auto_policy(opt: AutoPolicyOpt) -> AutoPolicy {
     match opt {
         AutoPolicyOpt::Compat => AutoPolicy::Compat,
         AutoPolicyOpt::StrictIfAvailable => AutoPolicy::StrictIfAvailable,
+        AutoPolicyOpt::Probe => AutoPolicy::Probe,
     }
 }
 
-fn run_diff(fmt: OutputFormat) -> anyhow::Result<()> {
-    let diff_text = read_diff_from_stdin()?;
-    let files = match parse_unified_diff(&diff_text) {
-        Ok(f) => f,
-        Err(DiffParseError::MissingHeader) => Vec::new(),
-        Err(e) => return Err(anyhow::anyhow!(e)),
-    };
+fn run_diff(output: &mut Output, fmt: OutputFormat, diff: &DiffSource) -> anyhow::Result<()> {
+    let files = read_file_changes(diff)?;
     match fmt {
         OutputFormat::Json => {
-            print_json_output(&files)?;
+            print_json_output(output, &files)?;
         }
-        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&files)?),
-        OutputFormat::Dot | OutputFormat::Html => {
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&files)?)?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
             anyhow::bail!("format not supported for 'diff': use json|yaml")
         }
     }
     Ok(())
 }
 
+/// Optional additions to the `changed` report.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChangedExtras {
+    with_change_kind: bool,
+    with_change_scope: bool,
+    with_snippets: bool,
+    with_changed_lines: bool,
+    max_results: Option<usize>,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_changed(
+    output: &mut Output,
     fmt: OutputFormat,
-    lang_opt: LangOpt,
+    diff: &DiffSource,
+    langs: &[LangOpt],
     engine_opt: EngineOpt,
     auto_policy: Option<AutoPolicyOpt>,
     lsp_strict: bool,
     dump_caps: bool,
+    extras: ChangedExtras,
+    analysis: &dimpact::AnalyzeOptions,
+    mapping: &dimpact::MappingOptions,
 ) -> anyhow::Result<()> {
-    let diff_text = read_diff_from_stdin()?;
-    let files = match parse_unified_diff(&diff_text) {
-        Ok(f) => f,
-        Err(DiffParseError::MissingHeader) => Vec::new(),
-        Err(e) => return Err(anyhow::anyhow!(e)),
-    };
+    let files = read_file_changes(diff)?;
+    let (lang_opt, languages) = lang_selection(langs);
     let lang = match lang_opt {
         LangOpt::Auto => LanguageMode::Auto,
         LangOpt::Rust => LanguageMode::Rust,
@@ -1174,6 +3225,8 @@ fn run_changed(
         LangOpt::Tsx => LanguageMode::Tsx,
         LangOpt::Go => LanguageMode::Go,
         LangOpt::Java => LanguageMode::Java,
+        LangOpt::Kotlin => LanguageMode::Kotlin,
+        LangOpt::Cpp => LanguageMode::Cpp,
     };
     let ekind = match engine_opt {
         EngineOpt::Auto => EngineKind::Auto,
@@ -1185,6 +3238,9 @@ fn run_changed(
         dump_capabilities: dump_caps,
         mock_lsp: std::env::var("DIMPACT_TEST_LSP_MOCK").ok().as_deref() == Some("1"),
         mock_caps: None,
+        analysis: analysis.clone(),
+        mapping: mapping.clone(),
+        lsp_retry: Default::default(),
     };
     let engine = make_engine_with_auto_policy(ekind, ecfg, auto_policy.map(map_auto_policy));
     if dump_caps && !matches!(engine_opt, EngineOpt::Lsp) {
@@ -1206,14 +3262,38 @@ fn run_changed(
         files.len(),
         lang
     );
-    let report: ChangedOutput = engine.changed_symbols(&files, lang)?;
+    let mut report: ChangedOutput = engine.changed_symbols(&files, lang)?;
+    retain_languages(&mut report, &languages, &mapping.analyzers);
+    if extras.with_change_kind {
+        classify_symbol_changes(&mut report, &files, lang, mapping);
+    }
+    if extras.with_change_scope {
+        classify_change_scopes(&mut report, &files, lang, mapping);
+    }
+    if extras.with_snippets {
+        report.snippets = snippets_for(mapping.root.as_deref(), &report.changed_symbols);
+    }
+    if extras.with_changed_lines {
+        attach_changed_lines(&mut report, &files);
+    }
+    if let Some(max) = extras.max_results {
+        cap_changed_symbols(&mut report, max);
+    }
     match fmt {
         OutputFormat::Json => {
-            print_json_output(&report)?;
+            print_json_output(output, &report)?;
         }
-        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&report)?),
-        OutputFormat::Dot | OutputFormat::Html => {
-            anyhow::bail!("format not supported for 'changed': use json|yaml")
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&report)?)?,
+        OutputFormat::Text => write!(
+            output,
+            "{}",
+            dimpact::changed_to_text(&report, output.use_color())
+        )?,
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions => {
+            anyhow::bail!("format not supported for 'changed': use json|yaml|text")
         }
     }
     Ok(())
@@ -1399,11 +3479,12 @@ fn rust_signature_param_names(source: &str, start_line: u32) -> std::collections
 
 fn collect_rust_tier2_semantic_evidence(
     completion_symbol: &dimpact::Symbol,
+    root: Option<&std::path::Path>,
 ) -> Tier2SemanticEvidence {
     if completion_symbol.language != "rust" || !completion_symbol.file.ends_with(".rs") {
         return Tier2SemanticEvidence::default();
     }
-    let Ok(src) = fs::read_to_string(&completion_symbol.file) else {
+    let Ok(src) = fs::read_to_string(workspace_file(root, &completion_symbol.file)) else {
         return Tier2SemanticEvidence::default();
     };
     let param_names = rust_signature_param_names(&src, completion_symbol.range.start_line);
@@ -1482,11 +3563,12 @@ fn collect_rust_tier2_semantic_evidence(
 
 fn collect_ruby_narrow_fallback_boundary_evidence(
     boundary_symbol: &dimpact::Symbol,
+    root: Option<&std::path::Path>,
 ) -> RubyNarrowFallbackBoundaryEvidence {
     if boundary_symbol.language != "ruby" || !boundary_symbol.file.ends_with(".rb") {
         return RubyNarrowFallbackBoundaryEvidence::default();
     }
-    let Ok(src) = fs::read_to_string(&boundary_symbol.file) else {
+    let Ok(src) = fs::read_to_string(workspace_file(root, &boundary_symbol.file)) else {
         return RubyNarrowFallbackBoundaryEvidence::default();
     };
 
@@ -1502,7 +3584,7 @@ fn collect_ruby_narrow_fallback_boundary_evidence(
             let resolved = normalize_path_like(&base_dir.join(raw));
             format!("{resolved}.rb")
         })
-        .filter(|path| fs::metadata(path).map(|m| m.is_file()).unwrap_or(false))
+        .filter(|path| workspace_file(root, path).is_file())
         .collect();
 
     let dynamic_target_re = regex::Regex::new(
@@ -1585,11 +3667,12 @@ fn collect_ruby_narrow_fallback_candidate_evidence(
     literal_dynamic_targets: &std::collections::BTreeMap<String, u32>,
     literal_dynamic_target_hints: &std::collections::BTreeSet<String>,
     literal_runtime_constant_target_families: &std::collections::BTreeSet<String>,
+    root: Option<&std::path::Path>,
 ) -> Option<RubyNarrowFallbackCandidateEvidence> {
     if literal_dynamic_targets.is_empty() || !candidate_file.ends_with(".rb") {
         return None;
     }
-    let Ok(src) = fs::read_to_string(candidate_file) else {
+    let Ok(src) = fs::read_to_string(workspace_file(root, candidate_file)) else {
         return None;
     };
     let analyzer = analyzer_for_path(candidate_file, LanguageKind::Ruby)?;
@@ -1693,8 +3776,9 @@ fn collect_ruby_narrow_fallback_candidates(
     direct_boundary_paths: &std::collections::BTreeSet<String>,
     boundary_symbol: &dimpact::Symbol,
     boundary_file: &str,
+    root: Option<&std::path::Path>,
 ) -> Vec<Tier2Candidate> {
-    let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(boundary_symbol);
+    let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(boundary_symbol, root);
     if boundary_evidence.explicit_require_relative_loads.is_empty()
         || boundary_evidence.literal_dynamic_targets.is_empty()
     {
@@ -1715,6 +3799,7 @@ fn collect_ruby_narrow_fallback_candidates(
                 &boundary_evidence.literal_dynamic_targets,
                 &boundary_evidence.literal_dynamic_target_hints,
                 &boundary_evidence.literal_runtime_constant_target_families,
+                root,
             )?;
             Some(Tier2Candidate {
                 path: candidate_file.clone(),
@@ -1732,20 +3817,23 @@ fn collect_ruby_narrow_fallback_candidates(
         .collect()
 }
 
-fn build_local_dfg_for_paths<'a>(paths: impl IntoIterator<Item = &'a str>) -> DataFlowGraph {
+fn build_local_dfg_for_paths<'a>(
+    root: Option<&std::path::Path>,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> DataFlowGraph {
     let mut combined = DataFlowGraph {
         nodes: Vec::new(),
         edges: Vec::new(),
     };
     for path in paths {
         if path.ends_with(".rs") {
-            if let Ok(src) = fs::read_to_string(path) {
+            if let Ok(src) = fs::read_to_string(workspace_file(root, path)) {
                 let dfg = RustDfgBuilder::build(path, &src);
                 combined.nodes.extend(dfg.nodes);
                 combined.edges.extend(dfg.edges);
             }
         } else if path.ends_with(".rb")
-            && let Ok(src) = fs::read_to_string(path)
+            && let Ok(src) = fs::read_to_string(workspace_file(root, path))
         {
             let dfg = RubyDfgBuilder::build(path, &src);
             combined.nodes.extend(dfg.nodes);
@@ -3245,6 +5333,7 @@ fn collect_bridge_continuation_candidates<'a>(
         String,
         Tier2SemanticEvidence,
     >,
+    root: Option<&std::path::Path>,
 ) -> Vec<RepresentativeCandidate> {
     let mut continuation_candidates = Vec::new();
 
@@ -3319,7 +5408,7 @@ fn collect_bridge_continuation_candidates<'a>(
             };
             let semantic_evidence = tier2_semantic_evidence_by_symbol_id
                 .entry(continuation_symbol_id.clone())
-                .or_insert_with(|| collect_rust_tier2_semantic_evidence(continuation_symbol))
+                .or_insert_with(|| collect_rust_tier2_semantic_evidence(continuation_symbol, root))
                 .clone();
             let scoring = tier2_scoring_summary(
                 anchor_symbol,
@@ -3398,6 +5487,7 @@ fn collect_bridge_continuation_candidates<'a>(
     continuation_candidates
 }
 
+#[allow(clippy::too_many_arguments)]
 fn plan_bounded_slice(
     cache_update_roots: &[String],
     local_dfg_roots: &[String],
@@ -3406,6 +5496,7 @@ fn plan_bounded_slice(
     refs: &[Reference],
     direction: ImpactDirection,
     root_reason_kind: ImpactSliceReasonKind,
+    root: Option<&std::path::Path>,
 ) -> BoundedSlicePlan {
     let mut overall = SliceSelectionAccumulator::default();
 
@@ -3527,7 +5618,9 @@ fn plan_bounded_slice(
                 };
                 let semantic_evidence = tier2_semantic_evidence_by_symbol_id
                     .entry(completion_symbol_id.clone())
-                    .or_insert_with(|| collect_rust_tier2_semantic_evidence(completion_symbol))
+                    .or_insert_with(|| {
+                        collect_rust_tier2_semantic_evidence(completion_symbol, root)
+                    })
                     .clone();
                 let scoring = tier2_scoring_summary(
                     boundary_symbol,
@@ -3558,6 +5651,7 @@ fn plan_bounded_slice(
                 &direct_boundary_paths,
                 boundary_symbol,
                 boundary_file,
+                root,
             ) {
                 record_same_path_candidate(
                     &mut side_candidates,
@@ -3663,6 +5757,7 @@ fn plan_bounded_slice(
             refs,
             direction,
             &mut tier2_semantic_evidence_by_symbol_id,
+            root,
         );
         let mut selected_continuation_paths = std::collections::BTreeSet::new();
         for candidate in continuation_candidates {
@@ -3721,12 +5816,13 @@ fn build_pdg_context(
     direction: ImpactDirection,
     with_propagation: bool,
     root_reason_kind: ImpactSliceReasonKind,
+    analysis: &dimpact::AnalyzeOptions,
 ) -> anyhow::Result<PdgContext> {
     let (scope, dir_override) = cache::scope_from_env();
-    let mut db = cache::open(scope, dir_override.as_deref())?;
+    let mut db = cache::open(scope, dir_override.as_deref(), analysis.root.as_deref())?;
     let st = cache::stats(&db.conn)?;
     if st.symbols == 0 {
-        cache::build_all(&mut db.conn)?;
+        cache::build_all(&mut db.conn, analysis)?;
     }
 
     let mut initial_cache_update_paths: std::collections::BTreeSet<String> =
@@ -3736,7 +5832,7 @@ fn build_pdg_context(
     let initial_cache_update_paths: Vec<String> = initial_cache_update_paths.into_iter().collect();
 
     if !initial_cache_update_paths.is_empty() {
-        cache::update_paths(&mut db.conn, &initial_cache_update_paths)?;
+        cache::update_paths(&mut db.conn, &initial_cache_update_paths, analysis)?;
     }
 
     let (mut index, mut refs) = cache::load_graph(&db.conn)?;
@@ -3748,6 +5844,7 @@ fn build_pdg_context(
         &refs,
         direction,
         root_reason_kind,
+        analysis.root.as_deref(),
     );
 
     let additional_cache_update_paths: Vec<String> = plan
@@ -3757,13 +5854,16 @@ fn build_pdg_context(
         .cloned()
         .collect();
     if !additional_cache_update_paths.is_empty() {
-        cache::update_paths(&mut db.conn, &additional_cache_update_paths)?;
+        cache::update_paths(&mut db.conn, &additional_cache_update_paths, analysis)?;
         let loaded = cache::load_graph(&db.conn)?;
         index = loaded.0;
         refs = loaded.1;
     }
 
-    let combined = build_local_dfg_for_paths(plan.local_dfg_paths.iter().map(String::as_str));
+    let combined = build_local_dfg_for_paths(
+        analysis.root.as_deref(),
+        plan.local_dfg_paths.iter().map(String::as_str),
+    );
     let mut pdg = PdgBuilder::build(&combined, &refs);
     if with_propagation {
         PdgBuilder::augment_symbolic_propagation(&mut pdg, &refs, &index);
@@ -3778,6 +5878,54 @@ fn build_pdg_context(
     })
 }
 
+/// Optional decorations applied to every `impact` result before printing.
+#[derive(Debug, Clone, Copy, Default)]
+struct ImpactExtras {
+    with_snippets: bool,
+    depth_histogram: bool,
+    max_results: Option<usize>,
+}
+
+impl ImpactExtras {
+    fn apply(&self, out: &mut ImpactOutput, root: Option<&std::path::Path>) {
+        if let Some(max) = self.max_results {
+            cap_impacted_symbols(out, max);
+        }
+        if self.with_snippets {
+            attach_snippets(out, root);
+        }
+        if self.depth_histogram {
+            eprint!("{}", depth_histogram_text(&out.summary.by_depth));
+        }
+    }
+
+    fn apply_grouped(&self, grouped: &mut [PerSeedOutput], root: Option<&std::path::Path>) {
+        for group in grouped {
+            for impact in &mut group.impacts {
+                if self.depth_histogram {
+                    eprintln!("{} ({:?}):", group.changed_symbol.id.0, impact.direction);
+                }
+                self.apply(&mut impact.output, root);
+            }
+        }
+    }
+}
+
+fn depth_histogram_text(by_depth: &[dimpact::ImpactDepthBucket]) -> String {
+    if by_depth.is_empty() {
+        return "depth histogram: no impacted symbols\n".to_string();
+    }
+    by_depth
+        .iter()
+        .map(|b| {
+            format!(
+                "depth {}: {} symbol(s) in {} file(s)\n",
+                b.depth, b.symbol_count, b.file_count
+            )
+        })
+        .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn build_grouped_impact_outputs(
     seeds: &[dimpact::Symbol],
@@ -3807,7 +5955,7 @@ fn build_grouped_impact_outputs(
                 with_edges,
             );
             if let Some(slice_selection) = slice_selection {
-                attach_slice_selection_summary(&mut output, slice_selection);
+                attach_slice_selection_summary(&mut output, slice_selection, opts.root.as_deref());
             }
             impacts.push(PerSeedImpact {
                 direction: ImpactDirection::Callers,
@@ -3824,7 +5972,7 @@ fn build_grouped_impact_outputs(
                 with_edges,
             );
             if let Some(slice_selection) = slice_selection {
-                attach_slice_selection_summary(&mut output, slice_selection);
+                attach_slice_selection_summary(&mut output, slice_selection, opts.root.as_deref());
             }
             impacts.push(PerSeedImpact {
                 direction: ImpactDirection::Callees,
@@ -3840,7 +5988,7 @@ fn build_grouped_impact_outputs(
                 with_edges,
             );
             if let Some(slice_selection) = slice_selection {
-                attach_slice_selection_summary(&mut output, slice_selection);
+                attach_slice_selection_summary(&mut output, slice_selection, opts.root.as_deref());
             }
             impacts.push(PerSeedImpact {
                 direction: opts.direction,
@@ -3945,8 +6093,10 @@ fn merge_pdg_references(
 
 #[allow(clippy::too_many_arguments)]
 fn run_impact(
+    output: &mut Output,
     fmt: OutputFormat,
-    lang_opt: LangOpt,
+    diff: &DiffSource,
+    langs: &[LangOpt],
     dir_opt: DirectionOpt,
     max_depth: Option<usize>,
     with_edges: bool,
@@ -3959,23 +6109,44 @@ fn run_impact(
     auto_policy: Option<AutoPolicyOpt>,
     lsp_strict: bool,
     dump_caps: bool,
-    seed_symbols: Vec<String>,
-    seed_json: Option<String>,
+    seed_input: SeedInput,
     ignore_dir: Vec<String>,
     per_seed: bool,
-) -> anyhow::Result<()> {
-    // Gather seeds
-    let mut seeds: Vec<dimpact::Symbol> = Vec::new();
-    if let Some(sj) = seed_json.as_ref() {
-        let mut from_json = parse_seed_json_input(sj)?;
-        seeds.append(&mut from_json);
-    }
-    if !seed_symbols.is_empty() {
-        for s in &seed_symbols {
-            seeds.push(parse_seed_symbol(s)?);
-        }
+    graph_base: Option<String>,
+    api_only: bool,
+    edge_kinds: Vec<EdgeKindOpt>,
+    edge_weights: Vec<(RefKind, usize)>,
+    exclude_tests: bool,
+    extras: ImpactExtras,
+    limits: GraphLimits,
+    time_budget_ms: Option<u64>,
+    baseline: Option<ImpactOutput>,
+    html_opts: HtmlOptions,
+    analysis: &dimpact::AnalyzeOptions,
+    mapping: &dimpact::MappingOptions,
+) -> anyhow::Result<bool> {
+    let seeds = collect_seeds(
+        &seed_input.symbols,
+        seed_input.json.as_deref(),
+        analysis.root(),
+    )?;
+    let file_opts = dimpact::AnalyzeOptions {
+        source: graph_base
+            .clone()
+            .map(GraphSource::GitTree)
+            .unwrap_or_default(),
+        ..analysis.clone()
+    };
+    let seeds = union_symbols(seeds, seeds_from_files(&seed_input.files, &file_opts)?);
+    // Only unions when there are seeds; without them the diff is used anyway
+    let union_with_diff = seed_input.union_with_diff && !seeds.is_empty();
+    if union_with_diff && seed_input.json.as_deref() == Some("-") && diff_reads_stdin(diff) {
+        anyhow::bail!(
+            "--union-seeds reads the diff from stdin, so --seed-json cannot be '-' (use --worktree/--staged/--since for the diff)"
+        );
     }
 
+    let (lang_opt, languages) = lang_selection(langs);
     // Determine language: prefer seeds' language when provided
     let lang: LanguageMode = if !seeds.is_empty() {
         let mut langs: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
@@ -4003,6 +6174,8 @@ fn run_impact(
             LangOpt::Tsx => LanguageMode::Tsx,
             LangOpt::Go => LanguageMode::Go,
             LangOpt::Java => LanguageMode::Java,
+            LangOpt::Kotlin => LanguageMode::Kotlin,
+            LangOpt::Cpp => LanguageMode::Cpp,
         }
     };
     let direction = match dir_opt {
@@ -4018,6 +6191,13 @@ fn run_impact(
         max_depth: max_depth.or(Some(100)),
         with_edges: Some(compute_with_edges),
         ignore_dirs: ignore_dir.clone(),
+        edge_kinds: edge_kinds.into_iter().map(EdgeKindOpt::ref_kind).collect(),
+        edge_weights: edge_weights.into_iter().collect(),
+        exclude_tests,
+        limits,
+        time_budget_ms,
+        languages,
+        root: analysis.root.clone(),
     };
     let ekind = match engine_opt {
         EngineOpt::Auto => EngineKind::Auto,
@@ -4029,6 +6209,9 @@ fn run_impact(
         dump_capabilities: dump_caps,
         mock_lsp: std::env::var("DIMPACT_TEST_LSP_MOCK").ok().as_deref() == Some("1"),
         mock_caps: None,
+        analysis: analysis.clone(),
+        mapping: mapping.clone(),
+        lsp_retry: Default::default(),
     };
     let engine = make_engine_with_auto_policy(ekind, ecfg, auto_policy.map(map_auto_policy));
     if dump_caps && !matches!(engine_opt, EngineOpt::Lsp) {
@@ -4044,16 +6227,72 @@ fn run_impact(
         );
     }
 
+    if let Some(base) = graph_base {
+        if per_seed || with_pdg || with_propagation {
+            anyhow::bail!(
+                "--graph-base cannot be combined with --per-seed, --with-pdg or --with-propagation"
+            );
+        }
+        let (index, refs) = dimpact::analyze_workspace(
+            analysis.root(),
+            dimpact::AnalyzeOptions {
+                source: GraphSource::GitTree(base.clone()),
+                languages: opts.languages.clone(),
+                ..analysis.clone()
+            },
+        )?;
+        check_graph_limits(&index, &refs, &opts.limits)?;
+        let changed = if seeds.is_empty() || union_with_diff {
+            let files = read_file_changes(diff)?;
+            union_symbols(seeds, changed_symbols_in_index(&files, &index))
+        } else {
+            seeds
+        };
+        log::info!(
+            "mode=impact(graph-base) base={} changed={} symbols={} edges={}",
+            base,
+            changed.len(),
+            index.symbols.len(),
+            refs.len()
+        );
+        let (mut out, confidence_filter) = apply_confidence_filter(
+            compute_impact(&changed, &index, &refs, &opts),
+            &opts,
+            min_confidence,
+            exclude_dynamic_fallback,
+            with_edges,
+        );
+        extras.apply(&mut out, opts.root.as_deref());
+        return print_impact_output(
+            output,
+            fmt,
+            &out,
+            confidence_filter.as_ref(),
+            baseline.as_ref(),
+            &html_opts,
+        );
+    }
+
+    // Explicit seeds plus the diff's changed symbols; from here on the
+    // combined set is handled like any other seed list.
+    let seeds = if union_with_diff {
+        let files = read_file_changes(diff)?;
+        let changed: ChangedOutput = engine.changed_symbols(&files, lang)?;
+        log::info!(
+            "union-seeds: seeds={} changed={}",
+            seeds.len(),
+            changed.changed_symbols.len()
+        );
+        union_symbols(seeds, changed.changed_symbols)
+    } else {
+        seeds
+    };
+
     // Per-seed grouping for call-graph or PDG-enhanced impact (diff or seed based)
     if per_seed {
         // Diff-based grouping: seeds := changed symbols
         if seeds.is_empty() {
-            let diff_text = read_diff_from_stdin()?;
-            let files = match parse_unified_diff(&diff_text) {
-                Ok(f) => f,
-                Err(DiffParseError::MissingHeader) => Vec::new(),
-                Err(e) => return Err(anyhow::anyhow!(e)),
-            };
+            let files = read_file_changes(diff)?;
             if with_pdg || with_propagation {
                 let changed: ChangedOutput = engine.changed_symbols(&files, lang)?;
                 validate_selected_engine_for_pdg_diff(&*engine, &files, lang, &opts)?;
@@ -4064,8 +6303,9 @@ fn run_impact(
                     opts.direction,
                     with_propagation,
                     ImpactSliceReasonKind::ChangedFile,
+                    analysis,
                 )?;
-                let grouped = build_grouped_impact_outputs(
+                let mut grouped = build_grouped_impact_outputs(
                     &changed.changed_symbols,
                     &pdg.refs,
                     &pdg.index,
@@ -4075,30 +6315,36 @@ fn run_impact(
                     with_edges,
                     Some(&pdg.per_seed_slice_selection),
                 );
+                extras.apply_grouped(&mut grouped, opts.root.as_deref());
                 match fmt {
                     OutputFormat::Json => {
-                        print_json_output(&grouped)?;
+                        print_json_output(output, &grouped)?;
                     }
-                    OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&grouped)?),
-                    OutputFormat::Dot | OutputFormat::Html => {
+                    OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&grouped)?)?,
+                    OutputFormat::Dot
+                    | OutputFormat::Graphml
+                    | OutputFormat::Html
+                    | OutputFormat::GithubActions
+                    | OutputFormat::Text => {
                         anyhow::bail!("format not supported for 'impact --per-seed': use json|yaml")
                     }
                 }
-                return Ok(());
+                return Ok(false);
             }
 
             let changed: ChangedOutput = engine.changed_symbols(&files, lang)?;
             let (scope, dir_override) = cache::scope_from_env();
-            let mut db = cache::open(scope, dir_override.as_deref())?;
+            let mut db = cache::open(scope, dir_override.as_deref(), analysis.root.as_deref())?;
             let st = cache::stats(&db.conn)?;
             if st.symbols == 0 {
-                cache::build_all(&mut db.conn)?;
+                cache::build_all(&mut db.conn, analysis)?;
             }
             if !changed.changed_files.is_empty() {
-                cache::update_paths(&mut db.conn, &changed.changed_files)?;
+                cache::update_paths(&mut db.conn, &changed.changed_files, analysis)?;
             }
             let (index, refs) = cache::load_graph(&db.conn)?;
-            let grouped = build_grouped_impact_outputs(
+            check_graph_limits(&index, &refs, &opts.limits)?;
+            let mut grouped = build_grouped_impact_outputs(
                 &changed.changed_symbols,
                 &refs,
                 &index,
@@ -4108,16 +6354,21 @@ fn run_impact(
                 with_edges,
                 None,
             );
+            extras.apply_grouped(&mut grouped, opts.root.as_deref());
             match fmt {
                 OutputFormat::Json => {
-                    print_json_output(&grouped)?;
+                    print_json_output(output, &grouped)?;
                 }
-                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&grouped)?),
-                OutputFormat::Dot | OutputFormat::Html => {
+                OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&grouped)?)?,
+                OutputFormat::Dot
+                | OutputFormat::Graphml
+                | OutputFormat::Html
+                | OutputFormat::GithubActions
+                | OutputFormat::Text => {
                     anyhow::bail!("format not supported for 'impact --per-seed': use json|yaml")
                 }
             }
-            return Ok(());
+            return Ok(false);
         }
         // Seed-based grouping: group per provided seed
         if with_pdg || with_propagation {
@@ -4131,8 +6382,9 @@ fn run_impact(
                 opts.direction,
                 with_propagation,
                 ImpactSliceReasonKind::SeedFile,
+                analysis,
             )?;
-            let grouped = build_grouped_impact_outputs(
+            let mut grouped = build_grouped_impact_outputs(
                 &seeds,
                 &pdg.refs,
                 &pdg.index,
@@ -4142,26 +6394,32 @@ fn run_impact(
                 with_edges,
                 Some(&pdg.per_seed_slice_selection),
             );
+            extras.apply_grouped(&mut grouped, opts.root.as_deref());
             match fmt {
                 OutputFormat::Json => {
-                    print_json_output(&grouped)?;
+                    print_json_output(output, &grouped)?;
                 }
-                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&grouped)?),
-                OutputFormat::Dot | OutputFormat::Html => {
+                OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&grouped)?)?,
+                OutputFormat::Dot
+                | OutputFormat::Graphml
+                | OutputFormat::Html
+                | OutputFormat::GithubActions
+                | OutputFormat::Text => {
                     anyhow::bail!("format not supported for 'impact --per-seed': use json|yaml")
                 }
             }
-            return Ok(());
+            return Ok(false);
         }
 
         let (scope, dir_override) = cache::scope_from_env();
-        let mut db = cache::open(scope, dir_override.as_deref())?;
+        let mut db = cache::open(scope, dir_override.as_deref(), analysis.root.as_deref())?;
         let st = cache::stats(&db.conn)?;
         if st.symbols == 0 {
-            cache::build_all(&mut db.conn)?;
+            cache::build_all(&mut db.conn, analysis)?;
         }
         let (index, refs) = cache::load_graph(&db.conn)?;
-        let grouped = build_grouped_impact_outputs(
+        check_graph_limits(&index, &refs, &opts.limits)?;
+        let mut grouped = build_grouped_impact_outputs(
             &seeds,
             &refs,
             &index,
@@ -4171,26 +6429,26 @@ fn run_impact(
             with_edges,
             None,
         );
+        extras.apply_grouped(&mut grouped, opts.root.as_deref());
         match fmt {
             OutputFormat::Json => {
-                print_json_output(&grouped)?;
+                print_json_output(output, &grouped)?;
             }
-            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&grouped)?),
-            OutputFormat::Dot | OutputFormat::Html => {
+            OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&grouped)?)?,
+            OutputFormat::Dot
+            | OutputFormat::Graphml
+            | OutputFormat::Html
+            | OutputFormat::GithubActions
+            | OutputFormat::Text => {
                 anyhow::bail!("format not supported for 'impact --per-seed': use json|yaml")
             }
         }
-        return Ok(());
+        return Ok(false);
     }
 
     // diff-based impact (default when --per-seed not set and no seeds)
     if seeds.is_empty() {
-        let diff_text = read_diff_from_stdin()?;
-        let files = match parse_unified_diff(&diff_text) {
-            Ok(f) => f,
-            Err(DiffParseError::MissingHeader) => Vec::new(),
-            Err(e) => return Err(anyhow::anyhow!(e)),
-        };
+        let files = read_file_changes(diff)?;
         log::info!(
             "mode=impact(diff) engine={:?} files={} lang={:?} dir={:?} max_depth={:?} with_edges={} profile={:?} min_conf={:?} exclude_dynamic_fallback={} pdg={} ignore_dirs={:?}",
             ekind,
@@ -4215,10 +6473,11 @@ fn run_impact(
                 opts.direction,
                 with_propagation,
                 ImpactSliceReasonKind::ChangedFile,
+                analysis,
             )?;
             if matches!(fmt, OutputFormat::Dot) {
-                println!("{}", dfg_to_dot(&pdg.pdg));
-                return Ok(());
+                writeln!(output, "{}", dfg_to_dot(&pdg.pdg))?;
+                return Ok(false);
             }
             let (mut out, confidence_filter) = apply_confidence_filter(
                 compute_impact(&changed.changed_symbols, &pdg.index, &pdg.refs, &opts),
@@ -4227,19 +6486,40 @@ fn run_impact(
                 exclude_dynamic_fallback,
                 with_edges,
             );
-            attach_slice_selection_summary(&mut out, &pdg.slice_selection);
-            print_impact_output(fmt, &out, confidence_filter.as_ref())?;
-            return Ok(());
+            attach_slice_selection_summary(&mut out, &pdg.slice_selection, opts.root.as_deref());
+            extras.apply(&mut out, opts.root.as_deref());
+            return print_impact_output(
+                output,
+                fmt,
+                &out,
+                confidence_filter.as_ref(),
+                baseline.as_ref(),
+                &html_opts,
+            );
         }
-        let (out, confidence_filter) = apply_confidence_filter(
-            engine.impact(&files, lang, &opts)?,
+        let impact = if api_only {
+            let api_changed =
+                api_changed_symbols(engine.changed_symbols(&files, lang)?, &files, lang, mapping);
+            engine.impact_from_symbols(&api_changed, lang, &opts)?
+        } else {
+            engine.impact(&files, lang, &opts)?
+        };
+        let (mut out, confidence_filter) = apply_confidence_filter(
+            impact,
             &opts,
             min_confidence,
             exclude_dynamic_fallback,
             with_edges,
         );
-        print_impact_output(fmt, &out, confidence_filter.as_ref())?;
-        return Ok(());
+        extras.apply(&mut out, opts.root.as_deref());
+        return print_impact_output(
+            output,
+            fmt,
+            &out,
+            confidence_filter.as_ref(),
+            baseline.as_ref(),
+            &html_opts,
+        );
     }
 
     log::info!(
@@ -4266,6 +6546,7 @@ fn run_impact(
             opts.direction,
             with_propagation,
             ImpactSliceReasonKind::SeedFile,
+            analysis,
         )?;
         let (mut out, confidence_filter) = apply_confidence_filter(
             compute_impact(&seeds, &pdg.index, &pdg.refs, &opts),
@@ -4274,20 +6555,58 @@ fn run_impact(
             exclude_dynamic_fallback,
             with_edges,
         );
-        attach_slice_selection_summary(&mut out, &pdg.slice_selection);
-        print_impact_output(fmt, &out, confidence_filter.as_ref())?;
-        return Ok(());
+        attach_slice_selection_summary(&mut out, &pdg.slice_selection, opts.root.as_deref());
+        extras.apply(&mut out, opts.root.as_deref());
+        return print_impact_output(
+            output,
+            fmt,
+            &out,
+            confidence_filter.as_ref(),
+            baseline.as_ref(),
+            &html_opts,
+        );
     }
 
-    let (out, confidence_filter) = apply_confidence_filter(
+    let (mut out, confidence_filter) = apply_confidence_filter(
         engine.impact_from_symbols(&seeds, lang, &opts)?,
         &opts,
         min_confidence,
         exclude_dynamic_fallback,
         with_edges,
     );
-    print_impact_output(fmt, &out, confidence_filter.as_ref())?;
-    Ok(())
+    extras.apply(&mut out, opts.root.as_deref());
+    print_impact_output(
+        output,
+        fmt,
+        &out,
+        confidence_filter.as_ref(),
+        baseline.as_ref(),
+        &html_opts,
+    )
+}
+
+/// The changed symbols minus functions and methods whose edits all lie inside
+/// their body, for `impact --api-only`.
+fn api_changed_symbols(
+    mut report: ChangedOutput,
+    files: &[FileChanges],
+    lang: LanguageMode,
+    mapping: &dimpact::MappingOptions,
+) -> Vec<dimpact::Symbol> {
+    classify_change_scopes(&mut report, files, lang, mapping);
+    let total = report.changed_symbols.len();
+    let scopes = report.change_scopes;
+    let kept: Vec<dimpact::Symbol> = report
+        .changed_symbols
+        .into_iter()
+        .filter(|s| scopes.get(&s.id.0) != Some(&ChangeScope::Body))
+        .collect();
+    log::info!(
+        "api-only: changed={} body_only_skipped={}",
+        total,
+        total - kept.len()
+    );
+    kept
 }
 
 fn lang_mode_from_str(s: &str) -> Option<LanguageMode> {
@@ -4299,41 +6618,37 @@ fn lang_mode_from_str(s: &str) -> Option<LanguageMode> {
         "tsx" => Some(LanguageMode::Tsx),
         "go" | "golang" => Some(LanguageMode::Go),
         "java" => Some(LanguageMode::Java),
+        "kotlin" | "kt" => Some(LanguageMode::Kotlin),
+        "cpp" | "c" | "c++" => Some(LanguageMode::Cpp),
         "python" | "py" => Some(LanguageMode::Python),
         "auto" => Some(LanguageMode::Auto),
         _ => None,
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_id(
+    output: &mut Output,
     fmt: OutputFormat,
     path: Option<&str>,
     line: Option<u32>,
     name: Option<&str>,
-    lang_opt: LangOpt,
+    langs: &[LangOpt],
     kind_opt: Option<KindOpt>,
     raw: bool,
+    analyzers: &dimpact::languages::AnalyzerConfig,
+    root: Option<&std::path::Path>,
 ) -> anyhow::Result<()> {
     // Determine search scope (single file or workspace)
     if line.is_some() && path.is_none() {
         anyhow::bail!("--line requires --path (cannot use line without file context)");
     }
 
-    let files = collect_candidate_files(path, lang_opt)?;
+    let files = collect_candidate_files(path, langs, root)?;
+    let lkind = lang_selection(langs).0.kind();
     let mut all_syms: Vec<dimpact::Symbol> = Vec::new();
     for fp in &files {
-        let lkind = match lang_opt {
-            LangOpt::Auto => dimpact::LanguageKind::Auto,
-            LangOpt::Rust => dimpact::LanguageKind::Rust,
-            LangOpt::Ruby => dimpact::LanguageKind::Ruby,
-            LangOpt::Python => dimpact::LanguageKind::Python,
-            LangOpt::Javascript => dimpact::LanguageKind::Javascript,
-            LangOpt::Typescript => dimpact::LanguageKind::Typescript,
-            LangOpt::Tsx => dimpact::LanguageKind::Tsx,
-            LangOpt::Go => dimpact::LanguageKind::Go,
-            LangOpt::Java => dimpact::LanguageKind::Java,
-        };
-        let Some(analyzer) = dimpact::languages::analyzer_for_path(fp, lkind) else {
+        let Some(analyzer) = analyzers.analyzer_for_path(fp, lkind) else {
             continue;
         };
         let Ok(source) = fs::read_to_string(fp) else {
@@ -4386,11 +6701,51 @@ fn run_id(
     }
 
     let mut sorted = current;
-    sorted.sort_by_key(|s| (s.range.end_line - s.range.start_line, key_of_kind(&s.kind)));
+    sorted.sort_by_key(|s| (s.range.end_line - s.range.start_line, s.kind.specificity()));
+    print_symbol_ids(output, fmt, &sorted, raw)
+}
+
+/// `id --from-diff`: the IDs of the symbols the diff changes, in file and
+/// line order.
+fn run_id_from_diff(
+    output: &mut Output,
+    fmt: OutputFormat,
+    diff: &DiffSource,
+    langs: &[LangOpt],
+    kind_opt: Option<KindOpt>,
+    raw: bool,
+    mapping: &dimpact::MappingOptions,
+) -> anyhow::Result<()> {
+    let files = read_file_changes(diff)?;
+    let (lang_opt, languages) = lang_selection(langs);
+    let mut changed =
+        dimpact::compute_changed_symbols_with_options(&files, lang_opt.mode(), mapping)?;
+    retain_languages(&mut changed, &languages, &mapping.analyzers);
+    let mut syms = changed.changed_symbols;
+    if let Some(kopt) = kind_opt {
+        let want = map_kind_opt(kopt);
+        syms.retain(|s| s.kind == want);
+    }
+    syms.sort_by(|a, b| {
+        (a.file.as_str(), a.range.start_line, a.id.0.as_str()).cmp(&(
+            b.file.as_str(),
+            b.range.start_line,
+            b.id.0.as_str(),
+        ))
+    });
+    print_symbol_ids(output, fmt, &syms, raw)
+}
 
+/// `id` output: one plain ID per line with `raw`, else `{id, symbol}` items.
+fn print_symbol_ids(
+    output: &mut Output,
+    fmt: OutputFormat,
+    sorted: &[dimpact::Symbol],
+    raw: bool,
+) -> anyhow::Result<()> {
     if raw {
-        for s in &sorted {
-            println!("{}", s.id.0);
+        for s in sorted {
+            writeln!(output, "{}", s.id.0)?;
         }
         return Ok(());
     }
@@ -4406,19 +6761,29 @@ fn run_id(
                     })
                 })
                 .collect();
-            print_json_output(&items)?;
+            print_json_output(output, &items)?;
         }
         OutputFormat::Yaml => {
-            print!("{}", serde_yaml::to_string(&sorted)?);
+            write!(output, "{}", serde_yaml::to_string(&sorted)?)?;
         }
-        OutputFormat::Dot | OutputFormat::Html => {
+        OutputFormat::Dot
+        | OutputFormat::Graphml
+        | OutputFormat::Html
+        | OutputFormat::GithubActions
+        | OutputFormat::Text => {
             anyhow::bail!("format not supported for 'id': use json|yaml or --raw")
         }
     }
     Ok(())
 }
 
-fn collect_candidate_files(path: Option<&str>, lang_opt: LangOpt) -> anyhow::Result<Vec<String>> {
+/// `path` when given, otherwise every file of `langs` under the workspace
+/// `root` (the current directory when unset).
+fn collect_candidate_files(
+    path: Option<&str>,
+    langs: &[LangOpt],
+    root: Option<&std::path::Path>,
+) -> anyhow::Result<Vec<String>> {
     if let Some(p) = path {
         let md = fs::metadata(p);
         if md.as_ref().map(|m| m.is_file()).unwrap_or(false) {
@@ -4429,8 +6794,11 @@ fn collect_candidate_files(path: Option<&str>, lang_opt: LangOpt) -> anyhow::Res
     }
     // Workspace scan by extensions
     let mut out = Vec::new();
-    let exts = match lang_opt {
-        LangOpt::Auto => vec!["rs", "rb", "js", "ts", "tsx", "py", "go", "java"],
+    let extensions = |lang_opt: LangOpt| match lang_opt {
+        LangOpt::Auto => vec![
+            "rs", "rb", "js", "ts", "tsx", "py", "go", "java", "kt", "kts", "c", "h", "cc", "cpp",
+            "cxx", "hh", "hpp", "hxx",
+        ],
         LangOpt::Rust => vec!["rs"],
         LangOpt::Ruby => vec!["rb"],
         LangOpt::Python => vec!["py"],
@@ -4439,8 +6807,18 @@ fn collect_candidate_files(path: Option<&str>, lang_opt: LangOpt) -> anyhow::Res
         LangOpt::Tsx => vec!["tsx"],
         LangOpt::Go => vec!["go"],
         LangOpt::Java => vec!["java"],
+        LangOpt::Kotlin => vec!["kt", "kts"],
+        LangOpt::Cpp => vec!["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+    };
+    let exts: Vec<&str> = if lang_selection(langs).1.is_empty() {
+        extensions(LangOpt::Auto)
+    } else {
+        langs.iter().flat_map(|&l| extensions(l)).collect()
+    };
+    let root = match root {
+        Some(root) => root.to_path_buf(),
+        None => std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")),
     };
-    let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     scan_dir(&root, &exts, &mut out)?;
     Ok(out)
 }
@@ -4479,21 +6857,10 @@ fn scan_dir(dir: &std::path::Path, exts: &[&str], out: &mut Vec<String>) -> anyh
 
 #[allow(dead_code)]
 fn choose_most_specific(mut v: Vec<dimpact::Symbol>) -> dimpact::Symbol {
-    v.sort_by_key(|s| (s.range.end_line - s.range.start_line, key_of_kind(&s.kind)));
+    v.sort_by_key(|s| (s.range.end_line - s.range.start_line, s.kind.specificity()));
     v.into_iter().next().unwrap()
 }
 
-fn key_of_kind(k: &dimpact::SymbolKind) -> u8 {
-    match k {
-        dimpact::SymbolKind::Method => 0,
-        dimpact::SymbolKind::Function => 1,
-        dimpact::SymbolKind::Struct => 2,
-        dimpact::SymbolKind::Enum => 3,
-        dimpact::SymbolKind::Trait => 4,
-        dimpact::SymbolKind::Module => 5,
-    }
-}
-
 fn map_kind_opt(k: KindOpt) -> dimpact::SymbolKind {
     match k {
         KindOpt::Fn => dimpact::SymbolKind::Function,
@@ -4502,11 +6869,23 @@ fn map_kind_opt(k: KindOpt) -> dimpact::SymbolKind {
         KindOpt::Enum => dimpact::SymbolKind::Enum,
         KindOpt::Trait => dimpact::SymbolKind::Trait,
         KindOpt::Mod => dimpact::SymbolKind::Module,
+        KindOpt::Const => dimpact::SymbolKind::Const,
+        KindOpt::Static => dimpact::SymbolKind::Static,
+        KindOpt::Type => dimpact::SymbolKind::TypeAlias,
+        KindOpt::Macro => dimpact::SymbolKind::Macro,
     }
 }
 
 #[allow(dead_code)]
-fn impact_from_diff(args: Args, files: Vec<dimpact::FileChanges>) -> anyhow::Result<()> {
+fn impact_from_diff(
+    output: &mut Output,
+    args: Args,
+    files: Vec<dimpact::FileChanges>,
+) -> anyhow::Result<()> {
+    let analysis = dimpact::AnalyzeOptions {
+        root: args.root.clone(),
+        ..analyze_options(&args)
+    };
     let lang = match args.lang {
         LangOpt::Auto => LanguageMode::Auto,
         LangOpt::Rust => LanguageMode::Rust,
@@ -4517,6 +6896,8 @@ fn impact_from_diff(args: Args, files: Vec<dimpact::FileChanges>) -> anyhow::Res
         LangOpt::Tsx => LanguageMode::Tsx,
         LangOpt::Go => LanguageMode::Go,
         LangOpt::Java => LanguageMode::Java,
+        LangOpt::Kotlin => LanguageMode::Kotlin,
+        LangOpt::Cpp => LanguageMode::Cpp,
     };
     let direction = match args.direction {
         DirectionOpt::Callers => ImpactDirection::Callers,
@@ -4528,6 +6909,13 @@ fn impact_from_diff(args: Args, files: Vec<dimpact::FileChanges>) -> anyhow::Res
         max_depth: args.max_depth.or(Some(100)),
         with_edges: Some(args.with_edges),
         ignore_dirs: args.ignore_dir.clone(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: analysis.root.clone(),
     };
     let ekind = match args.engine {
         EngineOpt::Auto => EngineKind::Auto,
@@ -4539,6 +6927,13 @@ fn impact_from_diff(args: Args, files: Vec<dimpact::FileChanges>) -> anyhow::Res
         dump_capabilities: args.engine_dump_capabilities,
         mock_lsp: false,
         mock_caps: None,
+        analysis,
+        mapping: dimpact::MappingOptions {
+            ignore_whitespace: args.ignore_whitespace,
+            analyzers: analyzer_config(&args),
+            root: args.root.clone(),
+        },
+        lsp_retry: Default::default(),
     };
     let engine = make_engine_with_auto_policy(ekind, ecfg, args.auto_policy.map(map_auto_policy));
     log::info!(
@@ -4552,10 +6947,13 @@ fn impact_from_diff(args: Args, files: Vec<dimpact::FileChanges>) -> anyhow::Res
     );
     let out: ImpactOutput = engine.impact(&files, lang, &opts)?;
     match args.format {
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&out)?),
-        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&out)?),
-        OutputFormat::Dot => println!("{}", dimpact::to_dot(&out)),
-        OutputFormat::Html => println!("{}", dimpact::to_html(&out)),
+        OutputFormat::Json => print_json_output(output, &out)?,
+        OutputFormat::Yaml => write!(output, "{}", serde_yaml::to_string(&out)?)?,
+        OutputFormat::Dot => writeln!(output, "{}", dimpact::to_dot(&out))?,
+        OutputFormat::Graphml => write!(output, "{}", dimpact::to_graphml(&out))?,
+        OutputFormat::Html => writeln!(output, "{}", dimpact::to_html(&out))?,
+        OutputFormat::GithubActions => write!(output, "{}", dimpact::to_github_annotations(&out))?,
+        OutputFormat::Text => write!(output, "{}", dimpact::to_text(&out, output.use_color()))?,
     }
     Ok(())
 }
@@ -4676,6 +7074,7 @@ fn caller() -> i32 {
         assert_eq!(lang_mode_from_str("go"), Some(LanguageMode::Go));
         assert_eq!(lang_mode_from_str("golang"), Some(LanguageMode::Go));
         assert_eq!(lang_mode_from_str("java"), Some(LanguageMode::Java));
+        assert_eq!(lang_mode_from_str("kt"), Some(LanguageMode::Kotlin));
     }
 
     #[test]
@@ -4683,37 +7082,149 @@ fn caller() -> i32 {
         let a = Args::try_parse_from(["dimpact", "changed", "--lang", "go"])
             .expect("go should be accepted by --lang");
         match a.cmd {
-            Some(Command::Changed { lang, .. }) => assert!(matches!(lang, LangOpt::Go)),
+            Some(Command::Changed { lang, .. }) => assert!(matches!(lang[..], [LangOpt::Go])),
             _ => panic!("expected changed subcommand"),
         }
 
         let b = Args::try_parse_from(["dimpact", "impact", "--lang", "java"])
             .expect("java should be accepted by --lang");
         match b.cmd {
-            Some(Command::Impact { lang, .. }) => assert!(matches!(lang, LangOpt::Java)),
+            Some(Command::Impact { lang, .. }) => assert!(matches!(lang[..], [LangOpt::Java])),
             _ => panic!("expected impact subcommand"),
         }
 
         let c = Args::try_parse_from(["dimpact", "changed", "--lang", "python"])
             .expect("python should be accepted by --lang");
         match c.cmd {
-            Some(Command::Changed { lang, .. }) => assert!(matches!(lang, LangOpt::Python)),
+            Some(Command::Changed { lang, .. }) => assert!(matches!(lang[..], [LangOpt::Python])),
             _ => panic!("expected changed subcommand"),
         }
 
         let d = Args::try_parse_from(["dimpact", "changed", "--lang", "py"])
             .expect("py alias should be accepted by --lang");
         match d.cmd {
-            Some(Command::Changed { lang, .. }) => assert!(matches!(lang, LangOpt::Python)),
+            Some(Command::Changed { lang, .. }) => assert!(matches!(lang[..], [LangOpt::Python])),
             _ => panic!("expected changed subcommand"),
         }
 
         let e = Args::try_parse_from(["dimpact", "changed", "--lang", "rust"])
             .expect("rust should keep working by --lang");
         match e.cmd {
-            Some(Command::Changed { lang, .. }) => assert!(matches!(lang, LangOpt::Rust)),
+            Some(Command::Changed { lang, .. }) => assert!(matches!(lang[..], [LangOpt::Rust])),
+            _ => panic!("expected changed subcommand"),
+        }
+    }
+
+    #[test]
+    fn verbosity_flags_pick_the_default_log_filter() {
+        let a = Args::try_parse_from(["dimpact", "impact", "-vv"]).expect("-vv after subcommand");
+        assert_eq!(log_filter(a.quiet, a.verbose), "debug");
+        let b = Args::try_parse_from(["dimpact", "-q", "changed"]).expect("-q before subcommand");
+        assert_eq!(log_filter(b.quiet, b.verbose), "error");
+        let c = Args::try_parse_from(["dimpact", "diff"]).unwrap();
+        assert_eq!(log_filter(c.quiet, c.verbose), "warn");
+        assert_eq!(log_filter(false, 1), "info");
+        assert_eq!(log_filter(false, 5), "trace");
+        assert!(Args::try_parse_from(["dimpact", "-q", "-v", "diff"]).is_err());
+    }
+
+    #[test]
+    fn lang_accepts_several_languages() {
+        let a = Args::try_parse_from(["dimpact", "impact", "--lang", "rust,ruby", "--lang", "py"])
+            .expect("comma-separated and repeated --lang should be accepted");
+        match a.cmd {
+            Some(Command::Impact { lang, .. }) => {
+                assert_eq!(lang, vec![LangOpt::Rust, LangOpt::Ruby, LangOpt::Python]);
+                assert_eq!(
+                    lang_selection(&lang),
+                    (
+                        LangOpt::Auto,
+                        vec![LanguageKind::Rust, LanguageKind::Ruby, LanguageKind::Python]
+                    )
+                );
+            }
+            _ => panic!("expected impact subcommand"),
+        }
+        assert_eq!(
+            lang_selection(&[LangOpt::Go, LangOpt::Go]),
+            (LangOpt::Go, vec![LanguageKind::Go])
+        );
+        assert_eq!(
+            lang_selection(&[LangOpt::Rust, LangOpt::Auto]),
+            (LangOpt::Auto, Vec::new())
+        );
+
+        // A configured list fills multi-valued --lang and leaves single ones alone
+        let config = ConfigDefaults {
+            lang: Some("rust,ruby".to_string()),
+            ..ConfigDefaults::default()
+        };
+        let parse = |argv: &[&str]| {
+            let matches = config.apply(Args::command()).try_get_matches_from(argv)?;
+            Args::from_arg_matches(&matches)
+        };
+        match parse(&["dimpact", "changed"]).unwrap().cmd {
+            Some(Command::Changed { lang, .. }) => {
+                assert_eq!(lang, vec![LangOpt::Rust, LangOpt::Ruby])
+            }
             _ => panic!("expected changed subcommand"),
         }
+        match parse(&["dimpact", "dfg", "a.rs"]).unwrap().cmd {
+            Some(Command::Dfg { lang, .. }) => assert_eq!(lang, LangOpt::Auto),
+            _ => panic!("expected dfg subcommand"),
+        }
+    }
+
+    #[test]
+    fn config_defaults_yield_to_env_and_command_line_flags() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".dimpact.yaml"),
+            "engine: ts\nlang: rust\nmax_depth: 2\nignore_dir: [vendor]\n",
+        )
+        .unwrap();
+        let mut config = ConfigDefaults::load(dir.path()).unwrap();
+        config
+            .apply_env(|name| (name == "DIMPACT_MAX_DEPTH").then(|| "3".to_string()))
+            .unwrap();
+        let parse = |argv: &[&str]| {
+            let matches = config.apply(Args::command()).try_get_matches_from(argv)?;
+            Args::from_arg_matches(&matches)
+        };
+
+        let a = parse(&["dimpact", "impact"]).unwrap();
+        match a.cmd {
+            Some(Command::Impact {
+                engine,
+                lang,
+                max_depth,
+                ignore_dir,
+                ..
+            }) => {
+                assert!(matches!(engine, EngineOpt::Ts));
+                assert!(matches!(lang[..], [LangOpt::Rust]));
+                assert_eq!(max_depth, Some(3));
+                assert_eq!(ignore_dir, vec!["vendor"]);
+            }
+            _ => panic!("expected impact subcommand"),
+        }
+        let b = parse(&["dimpact", "impact", "--max-depth", "5", "--engine", "auto"]).unwrap();
+        match b.cmd {
+            Some(Command::Impact {
+                engine, max_depth, ..
+            }) => {
+                assert!(matches!(engine, EngineOpt::Auto));
+                assert_eq!(max_depth, Some(5));
+            }
+            _ => panic!("expected impact subcommand"),
+        }
+
+        fs::write(dir.path().join(".dimpact.yaml"), "depth: 2\n").unwrap();
+        assert!(ConfigDefaults::load(dir.path()).is_err());
+        assert_eq!(
+            root_from_argv(&["dimpact", "impact", "--root=sub"].map(std::ffi::OsString::from)),
+            Some(std::path::PathBuf::from("sub"))
+        );
     }
 
     #[test]
@@ -4765,6 +7276,9 @@ fn caller() -> i32 {
                 dump_capabilities: false,
                 mock_lsp: true,
                 mock_caps: Some(caps),
+                analysis: Default::default(),
+                mapping: Default::default(),
+                lsp_retry: Default::default(),
             },
             None,
         );
@@ -4773,6 +7287,13 @@ fn caller() -> i32 {
             max_depth: Some(4),
             with_edges: Some(false),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: std::collections::HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
 
         let cwd = std::env::current_dir().unwrap();
@@ -4804,6 +7325,9 @@ fn caller() -> i32 {
                     references: false,
                     definition: false,
                 }),
+                analysis: Default::default(),
+                mapping: Default::default(),
+                lsp_retry: Default::default(),
             },
             None,
         );
@@ -4814,6 +7338,9 @@ fn caller() -> i32 {
                 dump_capabilities: false,
                 mock_lsp: false,
                 mock_caps: None,
+                analysis: Default::default(),
+                mapping: Default::default(),
+                lsp_retry: Default::default(),
             },
             None,
         );
@@ -4822,6 +7349,13 @@ fn caller() -> i32 {
             max_depth: Some(4),
             with_edges: Some(false),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: std::collections::HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
 
         let cwd = std::env::current_dir().unwrap();
@@ -5052,7 +7586,7 @@ fn caller() -> i32 {
             language: "rust".to_string(),
         };
 
-        let evidence = collect_rust_tier2_semantic_evidence(&symbol);
+        let evidence = collect_rust_tier2_semantic_evidence(&symbol, None);
         assert!(evidence.param_to_return_flow);
     }
 
@@ -5087,6 +7621,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5164,6 +7699,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5264,6 +7800,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5328,6 +7865,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5429,6 +7967,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5518,6 +8057,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5629,6 +8169,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         let value_file = slice_selection_file(&plan.slice_selection, "value.rs");
@@ -5766,6 +8307,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -5920,6 +8462,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
         std::env::set_current_dir(cwd).expect("restore cwd");
 
@@ -6056,6 +8599,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
         std::env::set_current_dir(cwd).expect("restore cwd");
 
@@ -6217,6 +8761,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
         std::env::set_current_dir(cwd).expect("restore cwd");
 
@@ -6374,6 +8919,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -6497,6 +9043,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -6575,6 +9122,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         let leaf_file = slice_selection_file(&plan.slice_selection, "shared.rs");
@@ -6690,6 +9238,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         let leaf_file = slice_selection_file(&plan.slice_selection, "lib/leaf.rb");
@@ -6787,7 +9336,7 @@ fn caller() -> i32 {
             },
             language: "ruby".to_string(),
         };
-        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary);
+        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary, None);
         assert_eq!(
             boundary_evidence.explicit_require_relative_loads,
             std::collections::BTreeSet::from([
@@ -6805,6 +9354,7 @@ fn caller() -> i32 {
                 &boundary_evidence.literal_dynamic_targets,
                 &boundary_evidence.literal_dynamic_target_hints,
                 &boundary_evidence.literal_runtime_constant_target_families,
+                None,
             )
             .is_none()
         );
@@ -6813,6 +9363,7 @@ fn caller() -> i32 {
             &boundary_evidence.literal_dynamic_targets,
             &boundary_evidence.literal_dynamic_target_hints,
             &boundary_evidence.literal_runtime_constant_target_families,
+            None,
         )
         .expect("route runtime fallback evidence");
         assert_eq!(route_runtime_evidence.matched_call_line, 10);
@@ -6826,6 +9377,7 @@ fn caller() -> i32 {
             &std::collections::BTreeSet::new(),
             &boundary,
             &router_file,
+            None,
         );
         assert_eq!(
             candidates
@@ -6885,7 +9437,7 @@ fn caller() -> i32 {
             },
             language: "ruby".to_string(),
         };
-        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary);
+        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary, None);
         assert_eq!(
             boundary_evidence.literal_runtime_constant_target_families,
             std::collections::BTreeSet::from(["RuntimeProxy".to_string()])
@@ -6896,6 +9448,7 @@ fn caller() -> i32 {
                 &boundary_evidence.literal_dynamic_targets,
                 &boundary_evidence.literal_dynamic_target_hints,
                 &boundary_evidence.literal_runtime_constant_target_families,
+                None,
             )
             .is_none()
         );
@@ -6905,6 +9458,7 @@ fn caller() -> i32 {
                 &boundary_evidence.literal_dynamic_targets,
                 &boundary_evidence.literal_dynamic_target_hints,
                 &boundary_evidence.literal_runtime_constant_target_families,
+                None,
             )
             .is_some()
         );
@@ -6914,6 +9468,7 @@ fn caller() -> i32 {
             &std::collections::BTreeSet::new(),
             &boundary,
             &service_file,
+            None,
         );
         assert_eq!(
             candidates
@@ -6967,7 +9522,7 @@ fn caller() -> i32 {
             },
             language: "ruby".to_string(),
         };
-        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary);
+        let boundary_evidence = collect_ruby_narrow_fallback_boundary_evidence(&boundary, None);
         assert_eq!(
             boundary_evidence.explicit_require_relative_loads,
             std::collections::BTreeSet::from([runtime_file.clone()])
@@ -6981,6 +9536,7 @@ fn caller() -> i32 {
             &boundary_evidence.literal_dynamic_targets,
             &boundary_evidence.literal_dynamic_target_hints,
             &boundary_evidence.literal_runtime_constant_target_families,
+            None,
         )
         .expect("runtime fallback evidence");
         assert_eq!(candidate_evidence.matched_call_line, 9);
@@ -7005,6 +9561,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -7086,6 +9643,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert_eq!(
@@ -7141,6 +9699,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         assert!(plan.cache_update_paths.contains(&"helper.js".to_string()));
@@ -7167,6 +9726,7 @@ fn caller() -> i32 {
             &refs,
             ImpactDirection::Callees,
             ImpactSliceReasonKind::SeedFile,
+            None,
         );
 
         let notes = slice_selection_file(&plan.slice_selection, "notes.rs");
@@ -7687,4 +10247,18 @@ fn caller() -> i32 {
             ]
         );
     }
+
+    #[test]
+    fn parse_seed_symbol_accepts_colon_in_path() {
+        let sym =
+            parse_seed_symbol(r"rust:C:\work\lib.rs:fn:foo:12", std::path::Path::new(".")).unwrap();
+        assert_eq!(sym.file, r"C:\work\lib.rs");
+        assert_eq!(sym.name, "foo");
+        assert_eq!(sym.range.start_line, 12);
+        assert_eq!(sym.id.0, r"rust:C%3A\work\lib.rs:fn:foo:12");
+
+        let again = parse_seed_symbol(&sym.id.0, std::path::Path::new(".")).unwrap();
+        assert_eq!(again.file, sym.file);
+        assert_eq!(again.id, sym.id);
+    }
 }
@@ -3,9 +3,11 @@ use rusqlite::{Connection, params};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::impact::AnalyzeOptions;
 use crate::ir::reference::{Reference, SymbolIndex, UnresolvedRef};
 use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
-use crate::languages::{LanguageKind, analyzer_for_path};
+use crate::languages::path::{normalize_path, workspace_file};
+use crate::languages::{AnalyzerConfig, LanguageKind};
 type SymbolsByPath = std::collections::HashMap<String, Vec<Symbol>>;
 type UrefsByPath = std::collections::HashMap<String, Vec<UnresolvedRef>>;
 type ImportMapByPath = std::collections::HashMap<String, std::collections::HashMap<String, String>>;
@@ -36,7 +38,7 @@ pub struct CacheStats {
     pub edges: i64,
 }
 
-const SCHEMA_VERSION: &str = "v1";
+const SCHEMA_VERSION: &str = "v4";
 
 pub fn resolve_paths(
     scope: CacheScope,
@@ -68,7 +70,9 @@ pub fn resolve_paths(
                 .map(PathBuf::from)
                 .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
                 .unwrap_or_else(|| PathBuf::from(".config"));
-            let root = find_repo_root().unwrap_or_else(|| PathBuf::from("."));
+            let root = repo_root
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| find_repo_root().unwrap_or_else(|| PathBuf::from(".")));
             let key = repo_key(&root);
             let dir = xdg
                 .join("dimpact")
@@ -84,16 +88,73 @@ pub fn resolve_paths(
     }
 }
 
-pub fn open(scope: CacheScope, override_dir: Option<&Path>) -> anyhow::Result<CacheDb> {
-    let paths = resolve_paths(scope, override_dir, None)?;
+/// Open (creating if needed) the cache for the workspace at `repo_root`,
+/// or for the repository around the current directory when unset.
+pub fn open(
+    scope: CacheScope,
+    override_dir: Option<&Path>,
+    repo_root: Option<&Path>,
+) -> anyhow::Result<CacheDb> {
+    let paths = resolve_paths(scope, override_dir, repo_root)?;
     fs::create_dir_all(&paths.dir)
         .with_context(|| format!("create cache dir: {}", paths.dir.display()))?;
     let mut conn = Connection::open(&paths.db)
         .with_context(|| format!("open cache db: {}", paths.db.display()))?;
-    init_db(&mut conn)?;
+    {
+        let _lock = WriteLock::acquire(&paths.lock)?;
+        init_db(&mut conn)?;
+    }
     Ok(CacheDb { conn, paths })
 }
 
+/// Exclusive advisory lock on the cache's `.lock` file. Writers (schema
+/// setup, build, update, clear) hold it so concurrent `dimpact` processes
+/// sharing a cache take turns; readers rely on WAL and never take it.
+/// Released on drop.
+struct WriteLock {
+    _file: fs::File,
+}
+
+impl WriteLock {
+    /// Take the lock, waiting for another process that holds it.
+    fn acquire(path: &Path) -> anyhow::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("open cache lock: {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => {
+                log::warn!(
+                    "cache is locked by another dimpact process; waiting for {}",
+                    path.display()
+                );
+                file.lock()
+                    .with_context(|| format!("lock cache: {}", path.display()))?;
+            }
+            Err(fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("lock cache: {}", path.display()));
+            }
+        }
+        Ok(Self { _file: file })
+    }
+
+    /// Lock the cache `conn` belongs to; the lock file sits next to the
+    /// database. In-memory databases are private and need no lock.
+    fn for_conn(conn: &Connection) -> anyhow::Result<Option<Self>> {
+        let Some(dir) = conn
+            .path()
+            .filter(|p| !p.is_empty())
+            .and_then(|p| Path::new(p).parent())
+        else {
+            return Ok(None);
+        };
+        Self::acquire(&dir.join(".lock")).map(Some)
+    }
+}
+
 pub fn scope_from_env() -> (CacheScope, Option<PathBuf>) {
     let scope = match std::env::var("DIMPACT_CACHE_SCOPE").ok().as_deref() {
         Some("global") | Some("GLOBAL") => CacheScope::Global,
@@ -146,11 +207,21 @@ fn init_db(conn: &mut Connection) -> anyhow::Result<()> {
             kind TEXT NOT NULL,
             file_id INTEGER NOT NULL,
             line INTEGER NOT NULL,
+            certainty TEXT NOT NULL,
+            provenance TEXT NOT NULL,
             FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
         );
         CREATE INDEX IF NOT EXISTS idx_edges_from ON edges(from_sid);
         CREATE INDEX IF NOT EXISTS idx_edges_to ON edges(to_sid);
         CREATE INDEX IF NOT EXISTS idx_edges_file ON edges(file_id);
+
+        CREATE TABLE IF NOT EXISTS imports (
+            file_id INTEGER NOT NULL,
+            alias TEXT NOT NULL,
+            target TEXT NOT NULL,
+            PRIMARY KEY(file_id, alias),
+            FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
         "#,
     )?;
 
@@ -179,6 +250,11 @@ pub fn stats(conn: &Connection) -> anyhow::Result<CacheStats> {
 }
 
 pub fn clear(paths: &CachePaths) -> anyhow::Result<()> {
+    let _lock = paths
+        .dir
+        .exists()
+        .then(|| WriteLock::acquire(&paths.lock))
+        .transpose()?;
     if paths.db.exists() {
         fs::remove_file(&paths.db).ok();
     }
@@ -186,26 +262,79 @@ pub fn clear(paths: &CachePaths) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn build_all(conn: &mut Connection) -> anyhow::Result<CacheStats> {
+/// Bring the cache in line with the workspace.
+///
+/// An empty cache is built from scratch; a populated one is updated in place
+/// (see [`verify`]), so only files whose digest changed and the files whose
+/// edges they can affect are analyzed again. The stats cover the whole cache.
+pub fn build_all(conn: &mut Connection, opts: &AnalyzeOptions) -> anyhow::Result<CacheStats> {
+    let _lock = WriteLock::for_conn(conn)?;
+    // Checked under the lock: another process may have just built it
+    let cached: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |r| r.get(0))?;
+    if cached > 0 && !settings_changed(conn, opts)? {
+        return refresh_stale(conn, opts);
+    }
+    rebuild_all(conn, opts)
+}
+
+/// Whether the graph was analyzed under other settings than `opts` (e.g.
+/// another `--strict-resolution` or `--include-macros`), making every cached
+/// symbol and edge stale.
+fn settings_changed(conn: &Connection, opts: &AnalyzeOptions) -> anyhow::Result<bool> {
+    let cached: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key='resolution'", [], |r| {
+            r.get(0)
+        })
+        .ok();
+    Ok(cached.as_deref().unwrap_or("default") != opts.cache_key())
+}
+
+fn rebuild_all(conn: &mut Connection, opts: &AnalyzeOptions) -> anyhow::Result<CacheStats> {
     // Rebuild from scratch using parallel analysis
-    let files = list_workspace_files();
-    let (symbols, urefs, file_imports) = analyze_paths_parallel(&files);
+    let files = list_workspace_files(opts);
+    let (symbols, urefs, file_imports) = analyze_paths_parallel(&files, opts);
     let index = SymbolIndex::build(symbols);
-    let refs = crate::impact::resolve_references(&index, &urefs, &file_imports);
+    let refs = crate::impact::resolve_references(&index, &urefs, &file_imports, opts);
     let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT OR REPLACE INTO meta(key, value) VALUES('resolution', ?1)",
+        params![opts.cache_key()],
+    )?;
     tx.execute("DELETE FROM symbols", [])?;
     tx.execute("DELETE FROM edges", [])?;
+    tx.execute("DELETE FROM imports", [])?;
     tx.execute("DELETE FROM files", [])?;
 
     // Insert files encountered in symbols
     let mut file_ids: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
     for s in &index.symbols {
         file_ids.entry(s.file.clone()).or_insert_with(|| {
-            let dig = file_digest(&s.file);
+            let dig = file_digest(opts.root.as_deref(), &s.file);
             let lang = s.language.clone();
             tx.execute(
                 "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, ?2, ?3, ?4, 1)",
-                params![&s.file, &lang, dig, file_mtime(&s.file)],
+                params![
+                    &s.file,
+                    &lang,
+                    dig,
+                    file_mtime(opts.root.as_deref(), &s.file)
+                ],
+            )
+            .unwrap();
+            tx.last_insert_rowid()
+        });
+    }
+    // Record symbol-less files too so stale_paths doesn't keep reporting them
+    for path in &files {
+        file_ids.entry(path.clone()).or_insert_with(|| {
+            tx.execute(
+                "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, ?2, ?3, ?4, 1)",
+                params![
+                    path,
+                    guess_lang(path, &opts.analyzers),
+                    file_digest(opts.root.as_deref(), path),
+                    file_mtime(opts.root.as_deref(), path)
+                ],
             )
             .unwrap();
             tx.last_insert_rowid()
@@ -234,19 +363,48 @@ pub fn build_all(conn: &mut Connection) -> anyhow::Result<CacheStats> {
     // Insert edges
     {
         let mut edge_stmt = tx.prepare(
-            "INSERT INTO edges(from_sid, to_sid, kind, file_id, line) VALUES(?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO edges(from_sid, to_sid, kind, file_id, line, certainty, provenance) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         )?;
         for e in &refs {
             // file_id derived from e.file
             let file_id = *file_ids.entry(e.file.clone()).or_insert_with(|| {
                 tx.execute(
                     "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, ?2, ?3, ?4, 1)",
-                    params![&e.file, guess_lang_from_ext(&e.file), file_digest(&e.file), file_mtime(&e.file)],
+                    params![&e.file, guess_lang(&e.file, &opts.analyzers), file_digest(opts.root.as_deref(), &e.file), file_mtime(opts.root.as_deref(), &e.file)],
                 ).unwrap();
                 tx.last_insert_rowid()
             });
-            edge_stmt.execute(params![&e.from.0, &e.to.0, "call", file_id, e.line as i64])?;
+            edge_stmt.execute(params![
+                &e.from.0,
+                &e.to.0,
+                e.kind.as_str(),
+                file_id,
+                e.line as i64,
+                e.certainty.as_str(),
+                e.provenance.as_str()
+            ])?;
+        }
+    }
+
+    // Insert per-file import maps
+    for (path, imports) in &file_imports {
+        if imports.is_empty() {
+            continue;
         }
+        let file_id = *file_ids.entry(path.clone()).or_insert_with(|| {
+            tx.execute(
+                "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, ?2, ?3, ?4, 1)",
+                params![
+                    path,
+                    guess_lang(path, &opts.analyzers),
+                    file_digest(opts.root.as_deref(), path),
+                    file_mtime(opts.root.as_deref(), path)
+                ],
+            )
+            .unwrap();
+            tx.last_insert_rowid()
+        });
+        store_imports(&tx, file_id, imports)?;
     }
     tx.commit()?;
     let st = stats(conn)?;
@@ -256,7 +414,22 @@ pub fn build_all(conn: &mut Connection) -> anyhow::Result<CacheStats> {
 /// Verify cache consistency against current workspace without requiring a diff.
 /// - Recompute digests for current files and update entries whose digest/present/lang changed
 /// - Mark missing files as present=0 and drop their symbols/edges
-pub fn verify(conn: &mut Connection) -> anyhow::Result<CacheStats> {
+pub fn verify(conn: &mut Connection, opts: &AnalyzeOptions) -> anyhow::Result<CacheStats> {
+    let _lock = WriteLock::for_conn(conn)?;
+    if settings_changed(conn, opts)? {
+        return rebuild_all(conn, opts);
+    }
+    refresh_stale(conn, opts)
+}
+
+fn refresh_stale(conn: &mut Connection, opts: &AnalyzeOptions) -> anyhow::Result<CacheStats> {
+    let to_update = stale_paths(conn, opts)?;
+    apply_updates(conn, &to_update, opts)
+}
+
+/// Workspace files whose cached entry is missing or out of date (changed
+/// digest or language, or deleted since they were cached).
+pub fn stale_paths(conn: &Connection, opts: &AnalyzeOptions) -> anyhow::Result<Vec<String>> {
     // Load DB snapshot
     let mut db_files: std::collections::HashMap<String, (String, i64, String)> =
         std::collections::HashMap::new();
@@ -276,15 +449,15 @@ pub fn verify(conn: &mut Connection) -> anyhow::Result<CacheStats> {
     }
 
     // Scan current workspace files
-    let fs_files = list_workspace_files();
+    let fs_files = list_workspace_files(opts);
     let fs_set: std::collections::HashSet<String> = fs_files.iter().cloned().collect();
 
     // Determine updates for existing files
     let mut to_update: Vec<String> = Vec::new();
     for p in &fs_files {
-        let dig = file_digest(p);
+        let dig = file_digest(opts.root.as_deref(), p);
         let present_expected: i64 = 1;
-        let lang = guess_lang_from_ext(p).to_string();
+        let lang = guess_lang(p, &opts.analyzers).to_string();
         match db_files.get(p) {
             None => to_update.push(p.clone()),
             Some((db_dig, db_present, db_lang)) => {
@@ -304,25 +477,89 @@ pub fn verify(conn: &mut Connection) -> anyhow::Result<CacheStats> {
     // Dedup in case of overlap
     to_update.sort();
     to_update.dedup();
-    update_paths(conn, &to_update)
+    Ok(to_update)
+}
+
+pub fn update_paths(
+    conn: &mut Connection,
+    paths: &[String],
+    opts: &AnalyzeOptions,
+) -> anyhow::Result<CacheStats> {
+    if settings_changed(conn, opts)? {
+        let _lock = WriteLock::for_conn(conn)?;
+        return rebuild_all(conn, opts);
+    }
+    if paths.is_empty() {
+        return stats(conn);
+    }
+    let _lock = WriteLock::for_conn(conn)?;
+    apply_updates(conn, paths, opts)
 }
 
-pub fn update_paths(conn: &mut Connection, paths: &[String]) -> anyhow::Result<CacheStats> {
+fn apply_updates(
+    conn: &mut Connection,
+    paths: &[String],
+    opts: &AnalyzeOptions,
+) -> anyhow::Result<CacheStats> {
     if paths.is_empty() {
         return stats(conn);
     }
     // Analyze changed files in parallel
-    let (symbols_by_file, urefs_by_file, imports_by_file) = analyze_specific_paths_parallel(paths);
+    let (mut symbols_by_file, mut urefs_by_file, mut imports_by_file) =
+        analyze_specific_paths_parallel(paths, opts);
+
+    // An import/alias edit can change how calls elsewhere resolve, and edges
+    // point at symbol ids (which carry line numbers), so files that depend on
+    // a file whose import map or symbols changed are re-resolved as well, as
+    // are files mentioning a name that a changed file newly defines.
+    let mut dependency_changed = Vec::new();
+    let mut added_names = std::collections::BTreeSet::new();
+    for p in paths {
+        let current = imports_by_file.get(p).cloned().unwrap_or_default();
+        let cached = load_symbols(conn, p)?;
+        let syms = symbols_by_file
+            .get(p)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let sids_changed = cached.len() != syms.len()
+            || syms
+                .iter()
+                .any(|s| !cached.iter().any(|(sid, _)| *sid == s.id.0));
+        if sids_changed || load_imports(conn, p)? != current {
+            dependency_changed.push(p.clone());
+        }
+        for s in syms {
+            if !cached.iter().any(|(_, name)| *name == s.name) {
+                added_names.insert(leaf_name(&s.name).to_string());
+            }
+        }
+    }
+    let mut paths = paths.to_vec();
+    if !dependency_changed.is_empty() || !added_names.is_empty() {
+        let mut dependents = dependent_files(conn, &dependency_changed)?;
+        dependents.extend(files_mentioning(&added_names, opts));
+        dependents.sort();
+        dependents.dedup();
+        dependents.retain(|d| !paths.contains(d));
+        if !dependents.is_empty() {
+            let (syms, urefs, imports) = analyze_specific_paths_parallel(&dependents, opts);
+            symbols_by_file.extend(syms);
+            urefs_by_file.extend(urefs);
+            imports_by_file.extend(imports);
+            paths.extend(dependents);
+        }
+    }
+    let paths = paths.as_slice();
 
     // Write symbols in a single transaction
     {
         let tx = conn.transaction()?;
         for p in paths {
-            let exists = fs::metadata(p).map(|m| m.is_file()).unwrap_or(false);
-            let lang = guess_lang_from_ext(p).to_string();
+            let exists = workspace_file(opts.root.as_deref(), p).is_file();
+            let lang = guess_lang(p, &opts.analyzers).to_string();
             tx.execute(
                 "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, ?2, ?3, ?4, ?5)\n                 ON CONFLICT(path) DO UPDATE SET lang=excluded.lang, digest=excluded.digest, mtime=excluded.mtime, present=excluded.present",
-                params![p, &lang, file_digest(p), file_mtime(p), if exists {1} else {0}],
+                params![p, &lang, file_digest(opts.root.as_deref(), p), file_mtime(opts.root.as_deref(), p), if exists {1} else {0}],
             )?;
             let file_id: i64 =
                 tx.query_row("SELECT id FROM files WHERE path=?1", params![p], |r| {
@@ -330,6 +567,10 @@ pub fn update_paths(conn: &mut Connection, paths: &[String]) -> anyhow::Result<C
                 })?;
             tx.execute("DELETE FROM symbols WHERE file_id=?1", params![file_id])?;
             tx.execute("DELETE FROM edges WHERE file_id=?1", params![file_id])?;
+            tx.execute("DELETE FROM imports WHERE file_id=?1", params![file_id])?;
+            if let Some(imports) = imports_by_file.get(p) {
+                store_imports(&tx, file_id, imports)?;
+            }
             if let Some(syms) = symbols_by_file.get(p) {
                 let mut stmt = tx.prepare("INSERT INTO symbols(sid, file_id, name, kind, start_line, end_line, language, sig_hash, parent_sid) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)")?;
                 for s in syms {
@@ -350,33 +591,32 @@ pub fn update_paths(conn: &mut Connection, paths: &[String]) -> anyhow::Result<C
         tx.commit()?;
     }
 
-    // Build index including newly inserted symbols
+    // Build index including newly inserted symbols; re-exports are chased
+    // through every cached import map, not just the updated files'
     let index = load_index(conn)?;
+    let all_imports = load_all_imports(conn)?;
 
     // Insert edges for changed files using prepared unresolved refs/imports
     {
         let tx = conn.transaction()?;
         {
-            let mut edge_stmt = tx.prepare("INSERT INTO edges(from_sid, to_sid, kind, file_id, line) VALUES(?1, ?2, ?3, ?4, ?5)")?;
+            let mut edge_stmt = tx.prepare("INSERT INTO edges(from_sid, to_sid, kind, file_id, line, certainty, provenance) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
             for p in paths {
                 let file_id: i64 =
                     tx.query_row("SELECT id FROM files WHERE path=?1", params![p], |r| {
                         r.get(0)
                     })?;
                 let urefs = urefs_by_file.get(p).cloned().unwrap_or_default();
-                let imports = imports_by_file.get(p).cloned().unwrap_or_default();
-                let refs = crate::impact::resolve_references(
-                    &index,
-                    &urefs,
-                    &std::collections::HashMap::from([(p.clone(), imports)]),
-                );
+                let refs = crate::impact::resolve_references(&index, &urefs, &all_imports, opts);
                 for e in refs {
                     edge_stmt.execute(params![
                         &e.from.0,
                         &e.to.0,
-                        "call",
+                        e.kind.as_str(),
                         file_id,
-                        e.line as i64
+                        e.line as i64,
+                        e.certainty.as_str(),
+                        e.provenance.as_str()
                     ])?;
                 }
             }
@@ -386,64 +626,193 @@ pub fn update_paths(conn: &mut Connection, paths: &[String]) -> anyhow::Result<C
     stats(conn)
 }
 
-// Parallel build helpers
-fn list_workspace_files() -> Vec<String> {
-    let mut out = Vec::new();
-    for entry in walkdir::WalkDir::new(".")
+fn store_imports(
+    conn: &Connection,
+    file_id: i64,
+    imports: &std::collections::HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let mut stmt =
+        conn.prepare("INSERT INTO imports(file_id, alias, target) VALUES(?1, ?2, ?3)")?;
+    for (alias, target) in imports {
+        stmt.execute(params![file_id, alias, target])?;
+    }
+    Ok(())
+}
+
+/// Cached import map of `path` (empty when the file is not cached).
+fn load_imports(
+    conn: &Connection,
+    path: &str,
+) -> anyhow::Result<std::collections::HashMap<String, String>> {
+    let mut stmt = conn.prepare(
+        "SELECT alias, target FROM imports JOIN files ON imports.file_id = files.id WHERE files.path=?1",
+    )?;
+    let rows = stmt.query_map(params![path], |r| Ok((r.get(0)?, r.get(1)?)))?;
+    let mut out = std::collections::HashMap::new();
+    for r in rows {
+        let (alias, target): (String, String) = r?;
+        out.insert(alias, target);
+    }
+    Ok(out)
+}
+
+/// Cached import maps of all present files, keyed by path.
+fn load_all_imports(conn: &Connection) -> anyhow::Result<ImportMapByPath> {
+    let mut stmt = conn.prepare(
+        "SELECT files.path, alias, target FROM imports JOIN files ON imports.file_id = files.id WHERE files.present=1",
+    )?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?;
+    let mut out: ImportMapByPath = std::collections::HashMap::new();
+    for r in rows {
+        let (path, alias, target): (String, String, String) = r?;
+        out.entry(path).or_default().insert(alias, target);
+    }
+    Ok(out)
+}
+
+/// Cached `(sid, name)` pairs of the symbols in `path`.
+fn load_symbols(conn: &Connection, path: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT symbols.sid, symbols.name FROM symbols JOIN files ON symbols.file_id = files.id WHERE files.path=?1",
+    )?;
+    let rows = stmt.query_map(params![path], |r| Ok((r.get(0)?, r.get(1)?)))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn leaf_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Workspace files containing any of `names` as an identifier: their
+/// references may now resolve to a newly defined symbol.
+fn files_mentioning(
+    names: &std::collections::BTreeSet<String>,
+    opts: &AnalyzeOptions,
+) -> Vec<String> {
+    if names.is_empty() {
+        return Vec::new();
+    }
+    list_workspace_files(opts)
         .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name == "node_modules" || name.starts_with('.'))
+        .filter(|p| {
+            fs::read_to_string(workspace_file(opts.root.as_deref(), p)).is_ok_and(|src| {
+                src.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|tok| names.contains(tok))
+            })
         })
-        .filter_map(Result::ok)
-    {
-        let path = entry.path();
-        if path.is_file() {
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-            if ["rs", "rb", "js", "ts", "tsx", "py", "go", "java"].contains(&ext) {
-                let path_str = path
-                    .strip_prefix("./")
-                    .unwrap_or(path)
-                    .to_string_lossy()
-                    .to_string();
-                out.push(path_str);
+        .collect()
+}
+
+/// Module name other files use to refer to `path` (`mod.rs`, `index.*` and
+/// `__init__.py` are named after their directory).
+fn module_stem(path: &str) -> Option<&str> {
+    let p = Path::new(path);
+    let stem = p.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "index" | "__init__") {
+        p.parent()?.file_name()?.to_str()
+    } else {
+        Some(stem)
+    }
+}
+
+/// Present files that import from any of `changed`: files with cached edges
+/// into their symbols, or whose import targets name their module.
+fn dependent_files(conn: &Connection, changed: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut out = std::collections::BTreeSet::new();
+    let mut by_edge = conn.prepare(
+        "SELECT DISTINCT src.path FROM edges
+         JOIN symbols ON edges.to_sid = symbols.sid
+         JOIN files dst ON symbols.file_id = dst.id
+         JOIN files src ON edges.file_id = src.id
+         WHERE dst.path=?1 AND src.path != ?1 AND src.present=1",
+    )?;
+    let mut by_import = conn.prepare(
+        "SELECT files.path, imports.target FROM imports JOIN files ON imports.file_id = files.id
+         WHERE files.path != ?1 AND files.present=1",
+    )?;
+    for path in changed {
+        let rows = by_edge.query_map(params![path], |r| r.get::<_, String>(0))?;
+        for r in rows {
+            out.insert(r?);
+        }
+        let Some(stem) = module_stem(path) else {
+            continue;
+        };
+        let rows = by_import.query_map(params![path], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+        })?;
+        for r in rows {
+            let (importer, target) = r?;
+            if target
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .any(|seg| seg == stem)
+            {
+                out.insert(importer);
             }
         }
     }
+    Ok(out.into_iter().collect())
+}
+
+// Parallel build helpers
+fn list_workspace_files(opts: &AnalyzeOptions) -> Vec<String> {
+    let _timer = crate::profile::start(crate::profile::Phase::Enumerate);
+    let mut out = Vec::new();
+    let skip = |name: &str| {
+        name == ".git" || name == "target" || name == "node_modules" || name.starts_with('.')
+    };
+    let root = opts.root();
+    for path in crate::graph_source::walk_files(root, skip, &opts.walk) {
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let path_str = normalize_path(&rel.to_string_lossy(), Path::new("."));
+        if opts.analyzers.language_for_path(&path_str).is_some()
+            && !opts.walk.is_generated_file(&path)
+        {
+            out.push(path_str);
+        }
+    }
     out
 }
 
 #[allow(clippy::type_complexity)]
-fn analyze_paths_parallel(paths: &[String]) -> (Vec<Symbol>, Vec<UnresolvedRef>, ImportMapByPath) {
+fn analyze_paths_parallel(
+    paths: &[String],
+    opts: &AnalyzeOptions,
+) -> (Vec<Symbol>, Vec<UnresolvedRef>, ImportMapByPath) {
     use rayon::prelude::*;
-    let results: Vec<(
-        Vec<Symbol>,
-        Vec<UnresolvedRef>,
-        (String, std::collections::HashMap<String, String>),
-    )> = paths
-        .par_iter()
-        .map(|p| {
-            let kind = LanguageKind::Auto;
-            let Some(analyzer) = analyzer_for_path(p, kind) else {
-                return (Vec::new(), Vec::new(), (p.clone(), Default::default()));
-            };
-            let Ok(src) = fs::read_to_string(p) else {
-                return (Vec::new(), Vec::new(), (p.clone(), Default::default()));
-            };
-            let syms = analyzer.symbols_in_file(p, &src);
-            let urefs = analyzer.unresolved_refs(p, &src);
-            let im = analyzer.imports_in_file(p, &src);
-            (syms, urefs, (p.clone(), im))
-        })
-        .collect();
+    let _timer = crate::profile::start(crate::profile::Phase::Analyze);
     let mut symbols = Vec::new();
     let mut urefs_all = Vec::new();
     let mut imports_map: ImportMapByPath = std::collections::HashMap::new();
-    for (syms, urefs, (p, im)) in results {
-        symbols.extend(syms);
-        urefs_all.extend(urefs);
-        imports_map.insert(p, im);
+    // Analyze in bounded chunks so only one chunk's sources are parsed at a
+    // time (the merged results still cover every file); chunks are merged in
+    // path order, so output matches a single all-at-once pass.
+    for chunk in paths.chunks(crate::impact::SCAN_CHUNK_SIZE) {
+        let results: Vec<(
+            Vec<Symbol>,
+            Vec<UnresolvedRef>,
+            (String, std::collections::HashMap<String, String>),
+        )> = chunk
+            .par_iter()
+            .map(|p| {
+                let kind = LanguageKind::Auto;
+                let Some(analyzer) = opts.analyzers.analyzer_for_path(p, kind) else {
+                    return (Vec::new(), Vec::new(), (p.clone(), Default::default()));
+                };
+                let Ok(src) = fs::read_to_string(workspace_file(opts.root.as_deref(), p)) else {
+                    return (Vec::new(), Vec::new(), (p.clone(), Default::default()));
+                };
+                let syms = analyzer.symbols_in_file(p, &src);
+                let urefs = analyzer.unresolved_refs(p, &src);
+                let im = analyzer.imports_in_file(p, &src);
+                (syms, urefs, (p.clone(), im))
+            })
+            .collect();
+        for (syms, urefs, (p, im)) in results {
+            symbols.extend(syms);
+            urefs_all.extend(urefs);
+            imports_map.insert(p, im);
+        }
     }
     (symbols, urefs_all, imports_map)
 }
@@ -451,8 +820,10 @@ fn analyze_paths_parallel(paths: &[String]) -> (Vec<Symbol>, Vec<UnresolvedRef>,
 #[allow(clippy::type_complexity)]
 fn analyze_specific_paths_parallel(
     paths: &[String],
+    opts: &AnalyzeOptions,
 ) -> (SymbolsByPath, UrefsByPath, ImportMapByPath) {
     use rayon::prelude::*;
+    let _timer = crate::profile::start(crate::profile::Phase::Analyze);
     let results: Vec<(
         String,
         Vec<Symbol>,
@@ -462,14 +833,15 @@ fn analyze_specific_paths_parallel(
         .par_iter()
         .map(|p| {
             let p = p.clone();
-            if !std::path::Path::new(&p).is_file() {
+            let path = workspace_file(opts.root.as_deref(), &p);
+            if !path.is_file() || opts.walk.is_generated_file(&path) {
                 return (p, Vec::new(), Vec::new(), Default::default());
             }
             let kind = LanguageKind::Auto;
-            let Some(analyzer) = analyzer_for_path(&p, kind) else {
+            let Some(analyzer) = opts.analyzers.analyzer_for_path(&p, kind) else {
                 return (p, Vec::new(), Vec::new(), Default::default());
             };
-            let Ok(src) = fs::read_to_string(&p) else {
+            let Ok(src) = fs::read_to_string(&path) else {
                 return (p, Vec::new(), Vec::new(), Default::default());
             };
             let syms = analyzer.symbols_in_file(&p, &src);
@@ -492,28 +864,33 @@ fn analyze_specific_paths_parallel(
 pub fn load_graph(conn: &Connection) -> anyhow::Result<(SymbolIndex, Vec<Reference>)> {
     let index = load_index(conn)?;
     // Edges
-    let mut stmt = conn.prepare("SELECT from_sid, to_sid, kind, files.path, line FROM edges JOIN files ON edges.file_id = files.id")?;
+    let mut stmt = conn.prepare("SELECT from_sid, to_sid, kind, files.path, line, certainty, provenance FROM edges JOIN files ON edges.file_id = files.id")?;
     let edge_iter = stmt.query_map([], |row| {
         let from_sid: String = row.get(0)?;
         let to_sid: String = row.get(1)?;
-        let _kind: String = row.get(2)?; // currently only call
+        let kind: String = row.get(2)?;
         let file: String = row.get(3)?;
         let line: i64 = row.get(4)?;
+        let certainty: String = row.get(5)?;
+        let provenance: String = row.get(6)?;
         Ok(Reference {
             from: SymbolId(from_sid),
             to: SymbolId(to_sid),
-            kind: crate::ir::reference::RefKind::Call,
+            kind: crate::ir::reference::RefKind::from_str_opt(&kind)
+                .unwrap_or(crate::ir::reference::RefKind::Call),
             file,
             line: line as u32,
-            certainty: crate::ir::reference::EdgeCertainty::Inferred,
-            provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+            certainty: crate::ir::reference::EdgeCertainty::from_str_opt(&certainty)
+                .unwrap_or(crate::ir::reference::EdgeCertainty::Inferred),
+            provenance: crate::ir::reference::EdgeProvenance::from_str_opt(&provenance)
+                .unwrap_or_default(),
         })
     })?;
     let mut edges = Vec::new();
     for e in edge_iter {
         edges.push(e?);
     }
-    Ok((index, edges))
+    Ok((crate::impact::with_boundary_symbols(index, &edges), edges))
 }
 
 fn load_index(conn: &Connection) -> anyhow::Result<SymbolIndex> {
@@ -533,6 +910,10 @@ fn load_index(conn: &Connection) -> anyhow::Result<SymbolIndex> {
             "enum" => SymbolKind::Enum,
             "trait" => SymbolKind::Trait,
             "mod" | "module" => SymbolKind::Module,
+            "const" => SymbolKind::Const,
+            "static" => SymbolKind::Static,
+            "type" | "type_alias" => SymbolKind::TypeAlias,
+            "macro" => SymbolKind::Macro,
             _ => SymbolKind::Function,
         };
         Ok(Symbol {
@@ -554,17 +935,53 @@ fn load_index(conn: &Connection) -> anyhow::Result<SymbolIndex> {
     Ok(SymbolIndex::build(symbols))
 }
 
-fn find_repo_root() -> Option<PathBuf> {
-    let mut cur = std::env::current_dir().ok()?;
+/// The repository root above the current directory (see [`repo_root_above`]).
+pub fn find_repo_root() -> Option<PathBuf> {
+    repo_root_above(&std::env::current_dir().ok()?)
+}
+
+/// The nearest directory at or above `start` that roots a repository: one
+/// holding `.hg`, `.svn`, a `.git` directory, or a `.git` file that points
+/// at a git directory (`gitdir: ...`, as in linked worktrees and
+/// submodules), or a bare repository. A `.git` file naming no git directory
+/// is not a marker, and a `.git` directory itself belongs to its work tree.
+fn repo_root_above(start: &Path) -> Option<PathBuf> {
+    let mut cur = start.to_path_buf();
     loop {
-        if cur.join(".git").exists() || cur.join(".hg").exists() || cur.join(".svn").exists() {
+        let git = cur.join(".git");
+        let marked = git.is_dir()
+            || (git.is_file() && linked_git_dir(&git).is_some())
+            || cur.join(".hg").exists()
+            || cur.join(".svn").exists()
+            || (cur.file_name() != Some(std::ffi::OsStr::new(".git")) && is_bare_repo(&cur));
+        if marked {
             return Some(cur);
         }
         if !cur.pop() {
-            break;
+            return None;
         }
     }
-    None
+}
+
+/// The git directory a `.git` file points to with its `gitdir:` line
+/// (relative to the file's directory unless absolute), if it exists.
+fn linked_git_dir(git_file: &Path) -> Option<PathBuf> {
+    let text = fs::read_to_string(git_file).ok()?;
+    let target = text.lines().next()?.strip_prefix("gitdir:")?.trim();
+    let dir = git_file.parent()?.join(target);
+    is_git_dir(&dir).then_some(dir)
+}
+
+/// Whether `dir` looks like a git directory: a repository's own, which
+/// holds `HEAD` and `objects`, or a linked worktree's, which holds `HEAD`
+/// and a `commondir` file naming the shared one.
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && (dir.join("objects").is_dir() || dir.join("commondir").is_file())
+}
+
+/// Whether `dir` is a bare repository: a git directory with no work tree.
+fn is_bare_repo(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
 }
 
 fn repo_key(root: &Path) -> String {
@@ -578,8 +995,8 @@ fn repo_key(root: &Path) -> String {
     format!("{}-{}", short, base)
 }
 
-fn file_digest(path: &str) -> String {
-    match fs::read(path) {
+fn file_digest(root: Option<&Path>, path: &str) -> String {
+    match fs::read(workspace_file(root, path)) {
         Ok(bytes) => {
             let mut hasher = blake3::Hasher::new();
             hasher.update(&bytes);
@@ -589,8 +1006,8 @@ fn file_digest(path: &str) -> String {
     }
 }
 
-fn file_mtime(path: &str) -> i64 {
-    fs::metadata(path)
+fn file_mtime(root: Option<&Path>, path: &str) -> i64 {
+    fs::metadata(workspace_file(root, path))
         .and_then(|m| m.modified())
         .ok()
         .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
@@ -598,22 +1015,10 @@ fn file_mtime(path: &str) -> i64 {
         .unwrap_or_default()
 }
 
-fn guess_lang_from_ext(path: &str) -> &'static str {
-    let ext = std::path::Path::new(path)
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
-    match ext {
-        "rs" => "rust",
-        "rb" => "ruby",
-        "js" => "javascript",
-        "ts" => "typescript",
-        "tsx" => "tsx",
-        "py" => "python",
-        "go" => "go",
-        "java" => "java",
-        _ => "unknown",
-    }
+fn guess_lang(path: &str, analyzers: &AnalyzerConfig) -> &'static str {
+    analyzers
+        .language_for_path(path)
+        .map_or("unknown", LanguageKind::name)
 }
 
 fn kind_to_str(k: &SymbolKind) -> &'static str {
@@ -624,6 +1029,10 @@ fn kind_to_str(k: &SymbolKind) -> &'static str {
         SymbolKind::Enum => "enum",
         SymbolKind::Trait => "trait",
         SymbolKind::Module => "mod",
+        SymbolKind::Const => "const",
+        SymbolKind::Static => "static",
+        SymbolKind::TypeAlias => "type",
+        SymbolKind::Macro => "macro",
     }
 }
 
@@ -634,3 +1043,146 @@ fn sig_hash_for(s: &Symbol) -> String {
     hasher.update(kind_to_str(&s.kind).as_bytes());
     hasher.finalize().to_hex().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_root_above_follows_git_files_and_bare_repos() {
+        let td = tempfile::tempdir().unwrap();
+        let root = td.path();
+        let git_dir = |dir: &Path| {
+            fs::create_dir_all(dir.join("objects")).unwrap();
+            fs::create_dir_all(dir.join("refs")).unwrap();
+            fs::write(dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        };
+
+        // main checkout; its `.git` directory belongs to the work tree
+        let main = root.join("main");
+        git_dir(&main.join(".git"));
+        fs::create_dir_all(main.join("src/net")).unwrap();
+        assert_eq!(repo_root_above(&main.join("src/net")), Some(main.clone()));
+        assert_eq!(
+            repo_root_above(&main.join(".git/objects")),
+            Some(main.clone())
+        );
+
+        // a linked worktree nested in the checkout roots itself
+        let private = main.join(".git/worktrees/wt");
+        fs::create_dir_all(&private).unwrap();
+        fs::write(private.join("HEAD"), "ref: refs/heads/wt\n").unwrap();
+        fs::write(private.join("commondir"), "../..\n").unwrap();
+        let wt = main.join(".worktrees/wt");
+        fs::create_dir_all(wt.join("src")).unwrap();
+        fs::write(wt.join(".git"), "gitdir: ../../.git/worktrees/wt\n").unwrap();
+        assert_eq!(repo_root_above(&wt.join("src")), Some(wt.clone()));
+
+        // a `.git` file pointing nowhere is not a marker
+        let stray = main.join("vendor/lib");
+        fs::create_dir_all(&stray).unwrap();
+        fs::write(stray.join(".git"), "gitdir: /nonexistent/repo.git\n").unwrap();
+        assert_eq!(repo_root_above(&stray), Some(main.clone()));
+
+        let bare = root.join("bare.git");
+        git_dir(&bare);
+        assert_eq!(repo_root_above(&bare.join("refs")), Some(bare.clone()));
+    }
+
+    fn insert_file(conn: &Connection, path: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO files(path, lang, digest, mtime, present) VALUES(?1, 'rust', '', 0, 1)",
+            params![path],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn writers_wait_for_the_cache_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open(dir.path().join("index.db")).unwrap();
+        init_db(&mut conn).unwrap();
+        let held = WriteLock::acquire(&dir.path().join(".lock")).unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn(move || {
+            let lock = WriteLock::for_conn(&conn).unwrap();
+            tx.send(lock.is_some()).unwrap();
+        });
+        assert!(
+            rx.recv_timeout(std::time::Duration::from_millis(200))
+                .is_err()
+        );
+        drop(held);
+        assert_eq!(
+            rx.recv_timeout(std::time::Duration::from_secs(10)),
+            Ok(true)
+        );
+        writer.join().unwrap();
+
+        let memory = Connection::open_in_memory().unwrap();
+        assert!(WriteLock::for_conn(&memory).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_graph_restores_edge_kind_certainty_and_provenance() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_db(&mut conn).unwrap();
+        let file = insert_file(&conn, "src/lib.rs");
+        conn.execute(
+            "INSERT INTO edges(from_sid, to_sid, kind, file_id, line, certainty, provenance) VALUES('a', 'b', 'type_use', ?1, 3, 'dynamic_fallback', 'local_dfg')",
+            params![file],
+        )
+        .unwrap();
+
+        let (_, edges) = load_graph(&conn).unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].kind, crate::ir::reference::RefKind::TypeUse);
+        assert_eq!(
+            edges[0].certainty,
+            crate::ir::reference::EdgeCertainty::DynamicFallback
+        );
+        assert_eq!(
+            edges[0].provenance,
+            crate::ir::reference::EdgeProvenance::LocalDfg
+        );
+    }
+
+    #[test]
+    fn dependent_files_follow_edges_and_import_targets() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_db(&mut conn).unwrap();
+        let util = insert_file(&conn, "src/util.rs");
+        let caller = insert_file(&conn, "src/caller.rs");
+        let user = insert_file(&conn, "src/user.rs");
+        insert_file(&conn, "src/other.rs");
+        conn.execute(
+            "INSERT INTO symbols(sid, file_id, name, kind, start_line, end_line, language) VALUES('rust:src/util.rs:fn:helper:1', ?1, 'helper', 'fn', 1, 1, 'rust')",
+            params![util],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO edges(from_sid, to_sid, kind, file_id, line, certainty, provenance) VALUES('rust:src/caller.rs:fn:run:1', 'rust:src/util.rs:fn:helper:1', 'call', ?1, 2, 'confirmed', 'call_graph')",
+            params![caller],
+        )
+        .unwrap();
+        let imports = std::collections::HashMap::from([(
+            "helper".to_string(),
+            "crate::util::helper".to_string(),
+        )]);
+        store_imports(&conn, user, &imports).unwrap();
+
+        assert_eq!(load_imports(&conn, "src/user.rs").unwrap(), imports);
+        assert!(load_imports(&conn, "src/other.rs").unwrap().is_empty());
+        assert_eq!(
+            dependent_files(&conn, &["src/util.rs".to_string()]).unwrap(),
+            vec!["src/caller.rs".to_string(), "src/user.rs".to_string()]
+        );
+        assert!(
+            dependent_files(&conn, &["src/other.rs".to_string()])
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1386,11 +1386,7 @@ impl PdgBuilder {
 }
 
 fn collect_control_ranges_rust(source: &str) -> Vec<(u32, u32)> {
-    let spec = crate::ts_core::load_rust_spec();
-    let compiled = match crate::ts_core::compile_queries_rust(&spec) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
+    let compiled = crate::ts_core::shared_queries_rust();
     let Some(ctrl_q) = &compiled.control else {
         return Vec::new();
     };
@@ -1411,11 +1407,7 @@ fn collect_control_ranges_rust(source: &str) -> Vec<(u32, u32)> {
 }
 
 fn collect_control_ranges_ruby(source: &str) -> Vec<(u32, u32)> {
-    let spec = crate::ts_core::load_ruby_spec();
-    let compiled = match crate::ts_core::compile_queries_ruby(&spec) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
+    let compiled = crate::ts_core::shared_queries_ruby();
     let Some(ctrl_q) = &compiled.control else {
         return Vec::new();
     };
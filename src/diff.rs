@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fmt;
 use thiserror::Error;
 
@@ -17,6 +18,147 @@ pub struct FileChanges {
     pub changes: Vec<Change>,
 }
 
+/// How a file was touched by a diff, derived from the `---`/`+++` markers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// `--- /dev/null`: the file is new.
+    Added,
+    /// `+++ /dev/null`: the file was removed.
+    Deleted,
+    /// Same path on both sides.
+    Modified,
+    /// Different old and new paths.
+    Renamed,
+}
+
+impl FileChanges {
+    pub fn status(&self) -> FileStatus {
+        match (&self.old_path, &self.new_path) {
+            (None, _) => FileStatus::Added,
+            (Some(_), None) => FileStatus::Deleted,
+            (Some(old), Some(new)) if old != new => FileStatus::Renamed,
+            _ => FileStatus::Modified,
+        }
+    }
+
+    /// New-file line numbers of the lines this diff added: the post-image
+    /// lines whose text differs. Context lines are not included, and a
+    /// pure deletion yields none (see [`removed_old_lines`]).
+    ///
+    /// [`removed_old_lines`]: FileChanges::removed_old_lines
+    pub fn changed_new_lines(&self) -> BTreeSet<u32> {
+        self.lines_of(ChangeKind::Added, |ch| ch.new_line)
+    }
+
+    /// Old-file line numbers of the lines this diff removed. They number the
+    /// pre-image, so they only line up with the current file above the
+    /// first hunk.
+    pub fn removed_old_lines(&self) -> BTreeSet<u32> {
+        self.lines_of(ChangeKind::Removed, |ch| ch.old_line)
+    }
+
+    /// New-file line numbers of every line shown in a hunk, added or
+    /// context: the changes plus the surrounding lines the diff was produced
+    /// with (`-U`), so it depends on the diff's context size.
+    pub fn hunk_new_lines(&self) -> BTreeSet<u32> {
+        self.changes.iter().filter_map(|ch| ch.new_line).collect()
+    }
+
+    /// A copy without the edits that only touch whitespace: added or removed
+    /// blank lines, and removed/added pairs within a run of edits whose text
+    /// is equal once all whitespace is dropped (re-indentation, trailing
+    /// spaces). A hunk left with no edits is dropped with its context lines,
+    /// so it no longer maps to any symbol.
+    pub fn without_whitespace_changes(&self) -> FileChanges {
+        fn squash(text: &str) -> String {
+            text.chars().filter(|c| !c.is_whitespace()).collect()
+        }
+        let mut keep = vec![true; self.changes.len()];
+        let mut i = 0;
+        while i < self.changes.len() {
+            if self.changes[i].kind == ChangeKind::Context {
+                i += 1;
+                continue;
+            }
+            let run_end = (i..self.changes.len())
+                .find(|&j| self.changes[j].kind == ChangeKind::Context)
+                .unwrap_or(self.changes.len());
+            let run: Vec<(usize, String)> = (i..run_end)
+                .map(|j| (j, squash(&self.changes[j].content)))
+                .collect();
+            let mut removed: Vec<&(usize, String)> = Vec::new();
+            for entry @ (j, text) in &run {
+                if text.is_empty() {
+                    keep[*j] = false;
+                } else if self.changes[*j].kind == ChangeKind::Removed {
+                    removed.push(entry);
+                }
+            }
+            for (j, text) in &run {
+                if !keep[*j] || self.changes[*j].kind != ChangeKind::Added {
+                    continue;
+                }
+                if let Some(pos) = removed.iter().position(|(_, r)| r == text) {
+                    let (r, _) = removed.swap_remove(pos);
+                    keep[*r] = false;
+                    keep[*j] = false;
+                }
+            }
+            i = run_end;
+        }
+
+        let mut changes = Vec::with_capacity(self.changes.len());
+        for hunk in self.hunks() {
+            if hunk
+                .clone()
+                .any(|j| keep[j] && self.changes[j].kind != ChangeKind::Context)
+            {
+                changes.extend(hunk.filter(|&j| keep[j]).map(|j| self.changes[j].clone()));
+            }
+        }
+        FileChanges {
+            old_path: self.old_path.clone(),
+            new_path: self.new_path.clone(),
+            changes,
+        }
+    }
+
+    /// Index ranges of `changes` per hunk: a hunk ends where the old or new
+    /// line numbers stop counting up by one.
+    fn hunks(&self) -> Vec<std::ops::Range<usize>> {
+        let mut hunks = Vec::new();
+        let mut start = 0;
+        let (mut next_old, mut next_new) = (None, None);
+        for (j, ch) in self.changes.iter().enumerate() {
+            let continues = (ch.old_line.is_none() || ch.old_line == next_old)
+                && (ch.new_line.is_none() || ch.new_line == next_new);
+            if j > 0 && !continues {
+                hunks.push(start..j);
+                start = j;
+            }
+            if let Some(old) = ch.old_line {
+                next_old = Some(old + 1);
+            }
+            if let Some(new) = ch.new_line {
+                next_new = Some(new + 1);
+            }
+        }
+        if start < self.changes.len() {
+            hunks.push(start..self.changes.len());
+        }
+        hunks
+    }
+
+    fn lines_of(&self, kind: ChangeKind, line: fn(&Change) -> Option<u32>) -> BTreeSet<u32> {
+        self.changes
+            .iter()
+            .filter(|ch| ch.kind == kind)
+            .filter_map(line)
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Change {
     pub kind: ChangeKind,
@@ -55,16 +197,29 @@ impl fmt::Display for HunkRange {
 ///
 /// This parser is intentionally minimal and supports the common subset:
 /// - `diff --git a/.. b/..` headers (optional for parsing)
-/// - `--- a/path` and `+++ b/path`
+/// - `--- a/path` and `+++ b/path`, or `--- path` and `+++ path` from
+///   `git diff --no-prefix` / `diff.noprefix` (detected per diff, see
+///   [`PrefixStyle`])
 /// - Hunk headers like `@@ -l,s +l,s @@` (s optional)
 /// - Line prefixes: `+` added, `-` removed, ` ` context
+///
+/// Lines may end in `\n` or `\r\n`, mixed freely. One further trailing `\r`
+/// is dropped too, so a patch of CRLF files that was itself converted to
+/// CRLF (`\r\r\n`) yields the same paths and content as the original.
 pub fn parse_unified_diff(input: &str) -> Result<Vec<FileChanges>, DiffParseError> {
     let mut files: Vec<FileChanges> = Vec::new();
-    let mut lines = input.lines().peekable();
+    let mut lines = input
+        .lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .peekable();
 
     let mut cur_old_path: Option<String> = None;
     let mut cur_new_path: Option<String> = None;
     let mut cur_changes: Vec<Change> = Vec::new();
+    // Evidence of the current file's `diff --git` line, and whether any file
+    // so far was shown without prefixes
+    let mut cur_header_style = PrefixStyle::Unknown;
+    let mut no_prefix_seen = false;
 
     // helper to flush current file
     let flush_file = |files: &mut Vec<FileChanges>,
@@ -82,7 +237,7 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileChanges>, DiffParseErro
 
     // We don't require a global header; we look for file markers and hunks.
     while let Some(line) = lines.next() {
-        if line.starts_with("diff --git ") {
+        if let Some(header) = line.strip_prefix("diff --git ") {
             // New file diff section starts. Flush previous.
             flush_file(
                 &mut files,
@@ -90,31 +245,37 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileChanges>, DiffParseErro
                 &mut cur_new_path,
                 &mut cur_changes,
             );
-            // Not strictly needed to parse paths here; use ---/+++ for reliable values.
+            // Paths come from ---/+++; the header only tells the prefix style.
+            cur_header_style = PrefixStyle::from_git_header(header);
             continue;
         }
 
         if let Some(rest) = line.strip_prefix("--- ") {
-            // e.g., --- a/path or --- /dev/null
-            let old_path = rest.trim();
-            cur_old_path = if old_path == "/dev/null" {
-                None
-            } else {
-                Some(strip_a_b_prefix(old_path).to_string())
+            // e.g., --- a/path or --- /dev/null; only a header when +++ follows,
+            // so a commit message line starting with "--- " is not mistaken for one
+            let Some(new_rest) = lines.peek().and_then(|next| next.strip_prefix("+++ ")) else {
+                continue;
             };
-            // Expect +++ to follow (not strictly enforced here)
-            if let Some(next) = lines.next() {
-                if let Some(rest) = next.strip_prefix("+++ ") {
-                    let new_path = rest.trim();
-                    cur_new_path = if new_path == "/dev/null" {
-                        None
-                    } else {
-                        Some(strip_a_b_prefix(new_path).to_string())
-                    };
+            let old_path = Some(rest.trim()).filter(|p| *p != "/dev/null");
+            let new_path = Some(new_rest.trim()).filter(|p| *p != "/dev/null");
+            let style = match cur_header_style {
+                PrefixStyle::Unknown => PrefixStyle::from_paths(old_path, new_path),
+                style => style,
+            };
+            let prefixed = style == PrefixStyle::Prefixed
+                || (style == PrefixStyle::Unknown && !no_prefix_seen);
+            no_prefix_seen |= !prefixed;
+            let strip = |path: &str, prefixes: &[&str]| {
+                if prefixed {
+                    strip_prefix_of(path, prefixes).unwrap_or(path).to_string()
                 } else {
-                    // Unexpected but allow; push back by manual handling not supported, so ignore.
+                    path.to_string()
                 }
-            }
+            };
+            cur_old_path = old_path.map(|p| strip(p, OLD_PREFIXES));
+            cur_new_path = new_path.map(|p| strip(p, NEW_PREFIXES));
+            cur_header_style = PrefixStyle::Unknown;
+            lines.next();
             continue;
         }
 
@@ -125,15 +286,31 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileChanges>, DiffParseErro
             // Iterate following lines as hunk body until next header/file marker
             let mut old_ln = range.old_start;
             let mut new_ln = range.new_start;
+            let mut old_left = range.old_len;
+            let mut new_left = range.new_len;
 
             while let Some(&peek) = lines.peek() {
                 if peek.starts_with("@@ ")
                     || peek.starts_with("diff --git ")
-                    || peek.starts_with("--- ")
+                    || (old_left == 0 && peek.starts_with("--- "))
                 {
                     break; // end of hunk/file
                 }
+                // Once the header's line counts are used up only a "\ No newline"
+                // marker still belongs to the hunk; anything else is patch metadata
+                // such as a format-patch signature or the next commit message.
+                if old_left == 0 && new_left == 0 && !peek.starts_with('\\') {
+                    break;
+                }
                 let body = lines.next().unwrap();
+                if body.starts_with('+') {
+                    new_left = new_left.saturating_sub(1);
+                } else if body.starts_with('-') {
+                    old_left = old_left.saturating_sub(1);
+                } else if !body.starts_with('\\') {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
                 if let Some(stripped) = body.strip_prefix('+') {
                     cur_changes.push(Change {
                         kind: ChangeKind::Added,
@@ -194,16 +371,92 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileChanges>, DiffParseErro
     Ok(files)
 }
 
-fn strip_a_b_prefix(path: &str) -> &str {
-    if let Some(stripped) = path.strip_prefix("a/") {
-        stripped
-    } else if let Some(stripped) = path.strip_prefix("b/") {
-        stripped
-    } else {
-        path
+/// Combine the files of several diffs (e.g. one per commit of a branch) so
+/// each path appears once: entries are keyed by their new path, or the old
+/// one for deletions, keep the first entry's old path and the last one's new
+/// path, and concatenate their changes in input order.
+pub fn merge_file_changes(batches: Vec<Vec<FileChanges>>) -> Vec<FileChanges> {
+    let mut merged: Vec<FileChanges> = Vec::new();
+    let mut slot_by_key: std::collections::HashMap<String, usize> =
+        std::collections::HashMap::new();
+    for fc in batches.into_iter().flatten() {
+        let Some(key) = fc.new_path.clone().or_else(|| fc.old_path.clone()) else {
+            continue;
+        };
+        // A later commit may touch a file under the path an earlier one
+        // renamed it to, or delete it
+        let slot = slot_by_key
+            .get(&key)
+            .or_else(|| fc.old_path.as_ref().and_then(|p| slot_by_key.get(p)))
+            .copied();
+        match slot {
+            Some(i) => {
+                let entry = &mut merged[i];
+                entry.new_path = fc.new_path;
+                entry.changes.extend(fc.changes);
+                slot_by_key.insert(key, i);
+            }
+            None => {
+                slot_by_key.insert(key, merged.len());
+                merged.push(fc);
+            }
+        }
+    }
+    merged
+}
+
+/// Source-side prefixes git may put on paths: `a/`, or with
+/// `diff.mnemonicPrefix` commit, index or object.
+const OLD_PREFIXES: &[&str] = &["a/", "c/", "i/", "o/"];
+/// Destination-side prefixes: `b/`, or with `diff.mnemonicPrefix` worktree,
+/// index or object.
+const NEW_PREFIXES: &[&str] = &["b/", "w/", "i/", "o/"];
+
+/// Whether a diff's paths carry git's `a/`/`b/` prefixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrefixStyle {
+    /// Nothing rules either way; prefixed unless an earlier file of the same
+    /// diff was shown without prefixes.
+    Unknown,
+    Prefixed,
+    NoPrefix,
+}
+
+impl PrefixStyle {
+    /// From the `diff --git <old> <new>` paths: identical halves can only be
+    /// a no-prefix diff of an unrenamed file (prefixed ones differ in `a/`/`b/`).
+    fn from_git_header(header: &str) -> Self {
+        let header = header.trim_end();
+        let half = header.len() / 2;
+        if header.len() % 2 == 1
+            && header.is_char_boundary(half)
+            && header.as_bytes()[half] == b' '
+            && header[..half] == header[half + 1..]
+        {
+            PrefixStyle::NoPrefix
+        } else {
+            PrefixStyle::Unknown
+        }
+    }
+
+    /// From the `---`/`+++` paths (`None` for `/dev/null`): a side without
+    /// its prefix means no-prefix.
+    fn from_paths(old: Option<&str>, new: Option<&str>) -> Self {
+        let missing = |path: Option<&str>, prefixes: &[&str]| {
+            path.is_some_and(|p| strip_prefix_of(p, prefixes).is_none())
+        };
+        if missing(old, OLD_PREFIXES) || missing(new, NEW_PREFIXES) {
+            PrefixStyle::NoPrefix
+        } else {
+            PrefixStyle::Unknown
+        }
     }
 }
 
+fn strip_prefix_of<'a>(path: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|p| path.strip_prefix(p))
+}
+
 fn parse_hunk_header(h: &str) -> Result<(HunkRange, &str), DiffParseError> {
     // h like: -12,3 +34,2 @@ optional
     let after_minus = h;
@@ -350,4 +603,244 @@ index 1111111..2222222 100644
         assert_eq!(added[1].new_line, Some(11));
         assert_eq!(added[2].new_line, Some(12));
     }
+
+    const STATUS_DIFF: &str = r#"diff --git a/new.rs b/new.rs
+new file mode 100644
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1,1 @@
++fn added() {}
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1,1 +0,0 @@
+-fn removed() {}
+diff --git a/old.rs b/moved.rs
+--- a/old.rs
++++ b/moved.rs
+@@ -1,1 +1,1 @@
+-fn a() {}
++fn b() {}
+"#;
+
+    #[test]
+    fn status_follows_dev_null_markers() {
+        let files = parse_unified_diff(STATUS_DIFF).expect("parsed");
+        let statuses: Vec<_> = files.iter().map(FileChanges::status).collect();
+        assert_eq!(
+            statuses,
+            vec![FileStatus::Added, FileStatus::Deleted, FileStatus::Renamed]
+        );
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].changes[0].new_line, Some(1));
+        assert_eq!(files[1].new_path, None);
+        assert_eq!(files[1].changes[0].old_line, Some(1));
+        assert_eq!(
+            parse_unified_diff(MODIFIED_DIFF).unwrap()[0].status(),
+            FileStatus::Modified
+        );
+    }
+
+    const FORMAT_PATCH: &str = r#"From 1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b Mon Sep 17 00:00:00 2001
+From: Tester <tester@example.com>
+Date: Mon, 1 Jan 2024 00:00:00 +0000
+Subject: [PATCH 1/2] Tweak foo
+
+Details:
++ this line starts with a plus but is commit prose
+--- and so does this dashed one
+---
+ src/foo.rs | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/src/foo.rs b/src/foo.rs
+index 1111111..2222222 100644
+--- a/src/foo.rs
++++ b/src/foo.rs
+@@ -1,3 +1,3 @@
+ fn foo() {
+-    old();
++    new();
+ }
+-- 
+2.43.0
+
+From 0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e Mon Sep 17 00:00:00 2001
+From: Tester <tester@example.com>
+Subject: [PATCH 2/2] Add bar
+
++also not a diff line
+---
+diff --git a/src/bar.rs b/src/bar.rs
+index 3333333..4444444 100644
+--- a/src/bar.rs
++++ b/src/bar.rs
+@@ -1 +1,2 @@
+ fn bar() {}
++fn baz() {}
+\ No newline at end of file
+-- 
+2.43.0
+"#;
+
+    #[test]
+    fn format_patch_metadata_is_not_parsed_as_changes() {
+        let files = parse_unified_diff(FORMAT_PATCH).expect("parsed");
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].new_path.as_deref(), Some("src/foo.rs"));
+        assert_eq!(files[1].new_path.as_deref(), Some("src/bar.rs"));
+        let kinds = |f: &FileChanges| f.changes.iter().map(|c| c.kind.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds(&files[0]),
+            vec![
+                ChangeKind::Context,
+                ChangeKind::Removed,
+                ChangeKind::Added,
+                ChangeKind::Context
+            ]
+        );
+        assert_eq!(
+            kinds(&files[1]),
+            vec![ChangeKind::Context, ChangeKind::Added]
+        );
+        assert_eq!(files[1].changes[1].new_line, Some(2));
+        assert!(
+            files
+                .iter()
+                .flat_map(|f| &f.changes)
+                .all(|c| !c.content.contains("commit prose") && !c.content.contains("2.43.0"))
+        );
+    }
+
+    const NO_PREFIX_DIFF: &str = r#"diff --git a/lib.rs a/lib.rs
+index 1111111..2222222 100644
+--- a/lib.rs
++++ a/lib.rs
+@@ -1 +1 @@
+-fn old() {}
++fn new() {}
+diff --git b/new.rs b/new.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1 @@
++fn added() {}
+diff --git src/main.rs src/main.rs
+--- src/main.rs
++++ src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run(); }
+"#;
+
+    #[test]
+    fn no_prefix_diffs_keep_paths_as_given() {
+        let files = parse_unified_diff(NO_PREFIX_DIFF).expect("parsed");
+        let paths: Vec<_> = files
+            .iter()
+            .map(|f| (f.old_path.as_deref(), f.new_path.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (Some("a/lib.rs"), Some("a/lib.rs")),
+                (None, Some("b/new.rs")),
+                (Some("src/main.rs"), Some("src/main.rs")),
+            ]
+        );
+
+        // a rename from `a/` to `b/` reads either way; an earlier unprefixed
+        // file in the same diff settles it
+        let renames = "diff --git old.rs new.rs\n--- old.rs\n+++ new.rs\n@@ -1 +1 @@\n-a\n+b\ndiff --git a/y.rs b/y.rs\n--- a/y.rs\n+++ b/y.rs\n@@ -1 +1 @@\n-c\n+d\n";
+        let files = parse_unified_diff(renames).expect("parsed");
+        assert_eq!(files[0].new_path.as_deref(), Some("new.rs"));
+        assert_eq!(files[1].old_path.as_deref(), Some("a/y.rs"));
+        assert_eq!(files[1].new_path.as_deref(), Some("b/y.rs"));
+
+        let mnemonic = "diff --git i/src/x.rs w/src/x.rs\n--- i/src/x.rs\n+++ w/src/x.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let files = parse_unified_diff(mnemonic).expect("parsed");
+        assert_eq!(files[0].old_path.as_deref(), Some("src/x.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("src/x.rs"));
+    }
+
+    #[test]
+    fn merge_file_changes_unions_files_across_diffs() {
+        let first = parse_unified_diff(
+            "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,0 +2,1 @@\n+x\ndiff --git a/src/old.rs b/src/new.rs\n--- a/src/old.rs\n+++ b/src/new.rs\n@@ -1,0 +2,1 @@\n+y\n",
+        )
+        .unwrap();
+        let second = parse_unified_diff(
+            "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -9,0 +10,1 @@\n+z\ndiff --git a/src/new.rs b/src/new.rs\n--- a/src/new.rs\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-a\n-y\ndiff --git a/src/c.rs b/src/c.rs\n--- /dev/null\n+++ b/src/c.rs\n@@ -0,0 +1,1 @@\n+c\n",
+        )
+        .unwrap();
+        let merged = merge_file_changes(vec![first, second]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].new_path.as_deref(), Some("src/a.rs"));
+        let new_lines: Vec<_> = merged[0]
+            .changes
+            .iter()
+            .filter_map(|c| c.new_line)
+            .collect();
+        assert_eq!(new_lines, vec![2, 10]);
+        assert_eq!(merged[1].old_path.as_deref(), Some("src/old.rs"));
+        assert_eq!(merged[1].status(), FileStatus::Deleted);
+        assert_eq!(merged[2].status(), FileStatus::Added);
+    }
+
+    #[test]
+    fn changed_and_removed_line_sets() {
+        let diff = "diff --git a/m.rs b/m.rs\n--- a/m.rs\n+++ b/m.rs\n@@ -1,4 +1,5 @@\n a\n-b\n+B\n+B2\n c\n d\n@@ -10,2 +11 @@\n x\n-y\n";
+        let fc = &parse_unified_diff(diff).unwrap()[0];
+        assert_eq!(fc.changed_new_lines(), BTreeSet::from([2, 3]));
+        assert_eq!(fc.removed_old_lines(), BTreeSet::from([2, 11]));
+        assert_eq!(fc.hunk_new_lines(), BTreeSet::from([1, 2, 3, 4, 5, 11]));
+
+        // a pure deletion changes no new line
+        let deleted = "diff --git a/m.rs b/m.rs\n--- a/m.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n";
+        let fc = &parse_unified_diff(deleted).unwrap()[0];
+        assert!(fc.changed_new_lines().is_empty());
+        assert!(fc.hunk_new_lines().is_empty());
+        assert_eq!(fc.removed_old_lines(), BTreeSet::from([1]));
+    }
+
+    #[test]
+    fn without_whitespace_changes_drops_reindents_and_blank_lines() {
+        // hunk 1 only re-indents a line and adds a blank one; hunk 2 also
+        // edits a line for real next to a trailing-space change
+        let diff = "diff --git a/m.rs b/m.rs\n--- a/m.rs\n+++ b/m.rs\n@@ -1,3 +1,4 @@\n a\n-b(1);\n+    b( 1 );\n+\n c\n@@ -10,3 +11,3 @@\n x\n-y\n-z\n+z  \n+w\n";
+        let fc = &parse_unified_diff(diff).unwrap()[0];
+        let kept = fc.without_whitespace_changes();
+        assert_eq!(kept.changed_new_lines(), BTreeSet::from([13]));
+        assert_eq!(kept.removed_old_lines(), BTreeSet::from([11]));
+        assert_eq!(kept.hunk_new_lines(), BTreeSet::from([11, 13]));
+        assert_eq!(kept.new_path, fc.new_path);
+    }
+
+    #[test]
+    fn crlf_and_mixed_line_endings_parse_like_lf() {
+        let lf = parse_unified_diff(MODIFIED_DIFF).unwrap();
+        let crlf = MODIFIED_DIFF.replace('\n', "\r\n");
+        assert_eq!(parse_unified_diff(&crlf).unwrap(), lf);
+        // a CRLF file's patch converted to CRLF once more, and mixed endings
+        let doubled = MODIFIED_DIFF.replace('\n', "\r\r\n");
+        assert_eq!(parse_unified_diff(&doubled).unwrap(), lf);
+        let mixed: String = MODIFIED_DIFF
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(i, l)| {
+                if i % 2 == 0 {
+                    l.replace('\n', "\r\n")
+                } else {
+                    l.to_string()
+                }
+            })
+            .collect();
+        assert_eq!(parse_unified_diff(&mixed).unwrap(), lf);
+        // no stray `\r` on a last line without a newline
+        let unterminated = crlf.trim_end_matches("\r\n").to_string() + "\r";
+        let files = parse_unified_diff(&unterminated).unwrap();
+        assert_eq!(files[0].changes.last().unwrap().content, "}");
+    }
 }
@@ -9,8 +9,11 @@ pub enum EngineKind {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AutoPolicy {
+    /// Always TS (pins results regardless of installed servers)
     Compat,
     StrictIfAvailable,
+    /// LSP when a server for the language answers a quick probe, else TS
+    Probe,
 }
 
 pub trait AnalysisEngine {
@@ -33,12 +36,20 @@ pub trait AnalysisEngine {
     ) -> anyhow::Result<ImpactOutput>;
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct EngineConfig {
     pub lsp_strict: bool,
     pub dump_capabilities: bool,
     pub mock_lsp: bool,
     pub mock_caps: Option<CapsHint>,
+    /// How the cached graph is analyzed and resolved (re-export depth,
+    /// resolution mode); only the analysis settings are used, not the file
+    /// filters or source.
+    pub analysis: crate::impact::AnalyzeOptions,
+    /// How diffs are mapped onto changed symbols.
+    pub mapping: crate::mapping::MappingOptions,
+    /// Retries of LSP requests the server answers with an error.
+    pub lsp_retry: lsp::RetryPolicy,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -54,6 +65,7 @@ fn parse_auto_policy_env(v: &str) -> Option<AutoPolicy> {
     match v.trim().to_ascii_lowercase().as_str() {
         "compat" => Some(AutoPolicy::Compat),
         "strict-if-available" | "strict_if_available" => Some(AutoPolicy::StrictIfAvailable),
+        "probe" => Some(AutoPolicy::Probe),
         _ => None,
     }
 }
@@ -63,7 +75,7 @@ fn auto_policy_from_env_or_default() -> AutoPolicy {
         .ok()
         .as_deref()
         .and_then(parse_auto_policy_env)
-        .unwrap_or(AutoPolicy::Compat)
+        .unwrap_or(AutoPolicy::Probe)
 }
 
 pub fn make_engine_with_auto_policy(
@@ -75,7 +87,7 @@ pub fn make_engine_with_auto_policy(
         EngineKind::Auto => match auto_policy.unwrap_or_else(auto_policy_from_env_or_default) {
             AutoPolicy::Compat => {
                 log::info!("engine: kind=Auto policy=compat selected=TS");
-                Box::new(self::ts::TsEngine)
+                Box::new(self::ts::TsEngine::new(cfg))
             }
             AutoPolicy::StrictIfAvailable => {
                 let mut lsp_cfg = cfg;
@@ -89,8 +101,12 @@ pub fn make_engine_with_auto_policy(
                     Some(AutoPolicy::StrictIfAvailable),
                 ))
             }
+            AutoPolicy::Probe => {
+                log::info!("engine: kind=Auto policy=probe selected=per-language");
+                Box::new(self::auto::AutoEngine::new(cfg))
+            }
         },
-        EngineKind::Ts => Box::new(self::ts::TsEngine),
+        EngineKind::Ts => Box::new(self::ts::TsEngine::new(cfg)),
         EngineKind::Lsp => {
             log::info!("engine: kind=LSP (GA) strict={}", cfg.lsp_strict);
             Box::new(self::lsp::LspEngine::new(cfg))
@@ -117,6 +133,7 @@ mod tests {
             parse_auto_policy_env("strict_if_available"),
             Some(AutoPolicy::StrictIfAvailable)
         );
+        assert_eq!(parse_auto_policy_env("probe"), Some(AutoPolicy::Probe));
     }
 
     #[test]
@@ -127,5 +144,6 @@ mod tests {
 }
 
 // Submodules
+pub mod auto;
 pub mod lsp;
 pub mod ts;
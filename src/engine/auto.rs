@@ -0,0 +1,185 @@
+use super::lsp::{LspEngine, mode_for_path, server_available};
+use super::ts::TsEngine;
+use super::{AnalysisEngine, AutoPolicy, EngineConfig};
+use crate::{ChangedOutput, FileChanges, ImpactOptions, ImpactOutput, LanguageMode};
+
+/// Auto engine under the `probe` policy: each call resolves the language and
+/// uses LSP when [`server_available`] says a server answers, TS otherwise.
+pub struct AutoEngine {
+    lsp: LspEngine,
+    ts: TsEngine,
+}
+
+impl AutoEngine {
+    pub fn new(cfg: EngineConfig) -> Self {
+        // Never hard-fail from Auto: LSP errors fall back to TS.
+        let lsp_cfg = EngineConfig {
+            lsp_strict: false,
+            ..cfg.clone()
+        };
+        Self {
+            lsp: LspEngine::new_with_auto_policy(lsp_cfg, Some(AutoPolicy::Probe)),
+            ts: TsEngine::new(cfg),
+        }
+    }
+
+    fn select(&self, mode: Option<LanguageMode>) -> &dyn AnalysisEngine {
+        match mode {
+            Some(m) if server_available(m) => {
+                log::info!("engine: kind=Auto policy=probe selected=LSP lang={:?}", m);
+                &self.lsp
+            }
+            Some(m) => {
+                log::info!(
+                    "engine: kind=Auto policy=probe selected=TS reason=missing-server lang={:?}",
+                    m
+                );
+                &self.ts
+            }
+            None => {
+                log::info!("engine: kind=Auto policy=probe selected=TS reason=mixed-language");
+                &self.ts
+            }
+        }
+    }
+}
+
+/// The single language of `items`, or `None` when mixed or unknown.
+fn unique_mode(items: impl IntoIterator<Item = Option<LanguageMode>>) -> Option<LanguageMode> {
+    let mut chosen = None;
+    for mode in items.into_iter().flatten() {
+        match chosen {
+            None => chosen = Some(mode),
+            Some(prev) if prev == mode => {}
+            Some(_) => return None,
+        }
+    }
+    chosen
+}
+
+fn mode_for_diffs(lang: LanguageMode, diffs: &[FileChanges]) -> Option<LanguageMode> {
+    if lang != LanguageMode::Auto {
+        return Some(lang);
+    }
+    unique_mode(
+        diffs
+            .iter()
+            .filter_map(|fc| fc.new_path.as_deref().or(fc.old_path.as_deref()))
+            .map(mode_for_path),
+    )
+}
+
+fn mode_for_symbols(lang: LanguageMode, symbols: &[crate::ir::Symbol]) -> Option<LanguageMode> {
+    if lang != LanguageMode::Auto {
+        return Some(lang);
+    }
+    unique_mode(symbols.iter().map(|s| match s.language.as_str() {
+        "rust" => Some(LanguageMode::Rust),
+        "ruby" => Some(LanguageMode::Ruby),
+        "python" => Some(LanguageMode::Python),
+        "javascript" => Some(LanguageMode::Javascript),
+        "typescript" => Some(LanguageMode::Typescript),
+        "tsx" => Some(LanguageMode::Tsx),
+        "go" => Some(LanguageMode::Go),
+        "java" => Some(LanguageMode::Java),
+        "kotlin" => Some(LanguageMode::Kotlin),
+        "cpp" => Some(LanguageMode::Cpp),
+        _ => None,
+    }))
+}
+
+impl AnalysisEngine for AutoEngine {
+    fn changed_symbols(
+        &self,
+        diffs: &[FileChanges],
+        lang: LanguageMode,
+    ) -> anyhow::Result<ChangedOutput> {
+        self.select(mode_for_diffs(lang, diffs))
+            .changed_symbols(diffs, lang)
+    }
+
+    fn impact(
+        &self,
+        diffs: &[FileChanges],
+        lang: LanguageMode,
+        opts: &ImpactOptions,
+    ) -> anyhow::Result<ImpactOutput> {
+        self.select(mode_for_diffs(lang, diffs))
+            .impact(diffs, lang, opts)
+    }
+
+    fn impact_from_symbols(
+        &self,
+        changed: &[crate::ir::Symbol],
+        lang: LanguageMode,
+        opts: &ImpactOptions,
+    ) -> anyhow::Result<ImpactOutput> {
+        self.select(mode_for_symbols(lang, changed))
+            .impact_from_symbols(changed, lang, opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff_for(path: &str) -> FileChanges {
+        FileChanges {
+            old_path: Some(path.to_string()),
+            new_path: Some(path.to_string()),
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn mode_for_diffs_prefers_explicit_language() {
+        let diffs = vec![diff_for("a.py")];
+        assert_eq!(
+            mode_for_diffs(LanguageMode::Rust, &diffs),
+            Some(LanguageMode::Rust)
+        );
+    }
+
+    #[test]
+    fn mode_for_diffs_infers_single_language_and_rejects_mixed() {
+        let single = vec![diff_for("a.go"), diff_for("README.md"), diff_for("b.go")];
+        assert_eq!(
+            mode_for_diffs(LanguageMode::Auto, &single),
+            Some(LanguageMode::Go)
+        );
+        let mixed = vec![diff_for("a.go"), diff_for("b.rs")];
+        assert_eq!(mode_for_diffs(LanguageMode::Auto, &mixed), None);
+    }
+
+    #[test]
+    fn auto_resolves_kotlin_and_cpp() {
+        assert_eq!(
+            mode_for_diffs(LanguageMode::Auto, &[diff_for("App.kt")]),
+            Some(LanguageMode::Kotlin)
+        );
+        assert_eq!(
+            mode_for_diffs(LanguageMode::Auto, &[diff_for("a.cpp"), diff_for("a.h")]),
+            Some(LanguageMode::Cpp)
+        );
+        let symbol = |language: &str, file: &str| {
+            crate::ir::Symbol::new(
+                language,
+                file,
+                crate::ir::SymbolKind::Function,
+                "f",
+                crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+            )
+        };
+        assert_eq!(
+            mode_for_symbols(LanguageMode::Auto, &[symbol("kotlin", "App.kt")]),
+            Some(LanguageMode::Kotlin)
+        );
+        assert_eq!(
+            mode_for_symbols(LanguageMode::Auto, &[symbol("cpp", "a.cpp")]),
+            Some(LanguageMode::Cpp)
+        );
+    }
+}
@@ -1,4 +1,5 @@
 // moved from src/engine/lsp/mod.rs (flattened)
+use crate::languages::path::normalize_path;
 use crate::{ChangedOutput, FileChanges, ImpactOptions, ImpactOutput, LanguageMode};
 use log::{debug, info, trace, warn};
 use serde_json::json;
@@ -13,14 +14,68 @@ pub struct CapabilityMatrix {
     pub workspace_symbol: bool,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct LspConfig {
     pub strict: bool,
     pub dump_capabilities: bool,
     pub mock: bool,
     pub mock_caps: Option<super::CapsHint>,
+    /// Retries of `prepareCallHierarchy` and `documentSymbol` requests the
+    /// server answers with an error.
+    pub retry: RetryPolicy,
+    /// Workspace root sent as the server's `rootUri`, which symbol paths are
+    /// relative to (the current directory when unset).
+    pub root: Option<std::path::PathBuf>,
 }
 
+/// How often a request is sent again after the server answered it with an
+/// error, as rust-analyzer does for some requests while it is still
+/// indexing. A request that timed out or lost the connection is not retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; `0` disables retrying.
+    pub retries: u32,
+    /// Wait before the first retry, doubled before each further one.
+    pub backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            backoff_ms: 150,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `attempt` until it succeeds, fails with something other than an
+    /// error response, or the retries are spent; the last result is returned.
+    pub fn run<T>(
+        &self,
+        method: &str,
+        mut attempt: impl FnMut() -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut delay = self.backoff_ms;
+        for retry in 1..=self.retries {
+            match attempt() {
+                Err(e) if e.is::<LspErrorResponse>() => {
+                    debug!("lsp: {method} failed; retry {retry} in {delay}ms");
+                    std::thread::sleep(std::time::Duration::from_millis(delay));
+                    delay = delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+        attempt()
+    }
+}
+
+/// The server answered a request with an error object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("lsp error response")]
+pub struct LspErrorResponse;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct LangProfile {
     symbol_lang: &'static str,
@@ -63,6 +118,14 @@ fn profile_for_mode(lang: LanguageMode) -> Option<LangProfile> {
             symbol_lang: "java",
             lsp_language_id: "java",
         }),
+        LanguageMode::Kotlin => Some(LangProfile {
+            symbol_lang: "kotlin",
+            lsp_language_id: "kotlin",
+        }),
+        LanguageMode::Cpp => Some(LangProfile {
+            symbol_lang: "cpp",
+            lsp_language_id: "cpp",
+        }),
         LanguageMode::Auto => None,
     }
 }
@@ -92,9 +155,34 @@ fn profile_for_path(path: &str) -> Option<LangProfile> {
     if path.ends_with(".java") {
         return profile_for_mode(LanguageMode::Java);
     }
+    if is_kotlin_path(path) {
+        return profile_for_mode(LanguageMode::Kotlin);
+    }
+    if is_cpp_path(path) {
+        return profile_for_mode(LanguageMode::Cpp);
+    }
     None
 }
 
+/// Language mode whose server handles `path`, if any.
+pub(crate) fn mode_for_path(path: &str) -> Option<LanguageMode> {
+    let profile = profile_for_path(path)?;
+    [
+        LanguageMode::Rust,
+        LanguageMode::Ruby,
+        LanguageMode::Python,
+        LanguageMode::Javascript,
+        LanguageMode::Typescript,
+        LanguageMode::Tsx,
+        LanguageMode::Go,
+        LanguageMode::Java,
+        LanguageMode::Kotlin,
+        LanguageMode::Cpp,
+    ]
+    .into_iter()
+    .find(|m| profile_for_mode(*m) == Some(profile))
+}
+
 fn path_matches_mode(path: &str, lang: LanguageMode) -> bool {
     match lang {
         LanguageMode::Auto => profile_for_path(path).is_some(),
@@ -110,9 +198,22 @@ fn path_matches_mode(path: &str, lang: LanguageMode) -> bool {
         LanguageMode::Tsx => path.ends_with(".tsx"),
         LanguageMode::Go => path.ends_with(".go"),
         LanguageMode::Java => path.ends_with(".java"),
+        LanguageMode::Kotlin => is_kotlin_path(path),
+        LanguageMode::Cpp => is_cpp_path(path),
     }
 }
 
+fn is_kotlin_path(path: &str) -> bool {
+    path.ends_with(".kt") || path.ends_with(".kts")
+}
+
+fn is_cpp_path(path: &str) -> bool {
+    matches!(
+        crate::languages::language_for_path(path),
+        Some(crate::languages::LanguageKind::Cpp)
+    )
+}
+
 fn profile_for_path_or_mode(path: &str, lang: LanguageMode) -> Option<LangProfile> {
     profile_for_mode(lang).or_else(|| profile_for_path(path))
 }
@@ -126,6 +227,10 @@ fn auto_server_mode_for_path(path: &str) -> Option<LanguageMode> {
         Some(LanguageMode::Go)
     } else if path.ends_with(".java") {
         Some(LanguageMode::Java)
+    } else if is_kotlin_path(path) {
+        Some(LanguageMode::Kotlin)
+    } else if is_cpp_path(path) {
+        Some(LanguageMode::Cpp)
     } else {
         None
     }
@@ -158,6 +263,8 @@ fn session_mode_for_symbols(lang: LanguageMode, symbols: &[crate::ir::Symbol]) -
         let mode = match sym.language.as_str() {
             "go" => LanguageMode::Go,
             "java" => LanguageMode::Java,
+            "kotlin" => LanguageMode::Kotlin,
+            "cpp" => LanguageMode::Cpp,
             _ => continue,
         };
         match chosen {
@@ -176,14 +283,47 @@ fn capability_snapshot(c: &CapabilityMatrix) -> String {
     )
 }
 
+/// Advertised capabilities as a single comma-separated token (`none` when
+/// empty), so decision logs stay splittable on spaces and `=`.
+fn capability_list(c: &CapabilityMatrix) -> String {
+    let present: Vec<&str> = [
+        ("call_hierarchy", c.call_hierarchy),
+        ("references", c.references),
+        ("definition", c.definition),
+        ("document_symbol", c.document_symbol),
+        ("workspace_symbol", c.workspace_symbol),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect();
+    if present.is_empty() {
+        "none".to_string()
+    } else {
+        present.join(",")
+    }
+}
+
 fn required_impact_capability_hint(direction: crate::impact::ImpactDirection) -> &'static str {
     match direction {
         crate::impact::ImpactDirection::Callers => "call_hierarchy or (references/definition)",
-        crate::impact::ImpactDirection::Callees => "call_hierarchy or (definition/references)",
+        crate::impact::ImpactDirection::Callees => "call_hierarchy or definition",
         crate::impact::ImpactDirection::Both => "call_hierarchy or (references/definition)",
     }
 }
 
+/// Whether the references/definition path can answer `direction` without
+/// call hierarchy: callers need references (or definition), callees are found
+/// by resolving call sites with definition.
+fn supports_reference_impact(
+    caps: &CapabilityMatrix,
+    direction: crate::impact::ImpactDirection,
+) -> bool {
+    match direction {
+        crate::impact::ImpactDirection::Callees => caps.definition,
+        _ => caps.references || caps.definition,
+    }
+}
+
 fn required_changed_capability_hint() -> &'static str {
     "document_symbol or workspace_symbol"
 }
@@ -270,6 +410,8 @@ fn server_command_for_mode(lang: LanguageMode) -> Option<(&'static str, Vec<&'st
         }
         LanguageMode::Go => Some(("gopls", vec![])),
         LanguageMode::Java => Some(("jdtls", vec![])),
+        LanguageMode::Kotlin => Some(("kotlin-language-server", vec![])),
+        LanguageMode::Cpp => Some(("clangd", vec![])),
         LanguageMode::Auto => detect_python_lsp_server(),
     }
 }
@@ -277,6 +419,8 @@ fn server_command_for_mode(lang: LanguageMode) -> Option<(&'static str, Vec<&'st
 /// Stub LSP session. Will later speak JSON-RPC over stdio.
 pub struct LspSession {
     _cfg: LspConfig,
+    /// Absolute [`LspConfig::root`].
+    root: std::path::PathBuf,
     pub capabilities: CapabilityMatrix,
     child: Option<std::process::Child>,
     stdin: Option<std::process::ChildStdin>,
@@ -291,8 +435,71 @@ pub struct LspSession {
     opened_docs: std::collections::HashSet<String>,
 }
 
+/// Move every complete response in `acc` whose id is in `slots` into its
+/// slot of `out`, dropping other messages.
+fn take_responses(
+    acc: &mut Vec<u8>,
+    slots: &mut std::collections::HashMap<u64, usize>,
+    out: &mut [Option<PipelinedResponse>],
+) {
+    while let Ok((val, used)) = decode_jsonrpc_message(acc) {
+        acc.drain(..used);
+        let Some(slot) = val
+            .get("id")
+            .and_then(|v| v.as_u64())
+            .and_then(|id| slots.remove(&id))
+        else {
+            continue;
+        };
+        out[slot] = Some(if val.get("error").is_some() {
+            Err(LspErrorResponse)
+        } else {
+            Ok(val.get("result").cloned().unwrap_or(json!({})))
+        });
+    }
+}
+
+/// The answer to one pipelined request: its result, or the error the server
+/// answered with.
+type PipelinedResponse = Result<serde_json::Value, LspErrorResponse>;
+
+/// How long to wait for the server's `shutdown` reply and then for it to exit.
+const SHUTDOWN_TIMEOUT_MS: u64 = 2000;
+
+/// [`LspConfig::root`] (or the current directory) as an absolute path.
+fn session_root(cfg: &LspConfig) -> std::path::PathBuf {
+    let root = cfg
+        .root
+        .clone()
+        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    root.canonicalize()
+        .or_else(|_| std::env::current_dir().map(|cwd| cwd.join(&root)))
+        .unwrap_or(root)
+}
+
+impl Drop for LspSession {
+    fn drop(&mut self) {
+        self.shutdown_server();
+    }
+}
+
 impl LspSession {
+    /// Absolute path of the workspace-relative `file`.
+    fn abs_path(&self, file: &str) -> std::path::PathBuf {
+        let path = self.root.join(file);
+        std::fs::canonicalize(&path).unwrap_or(path)
+    }
+
     pub fn new(lang: LanguageMode, cfg: LspConfig) -> anyhow::Result<Self> {
+        Self::new_with_init_timeout(lang, cfg, std::time::Duration::from_millis(2000))
+    }
+
+    /// Like [`LspSession::new`], but gives up on the initialize handshake after `timeout`.
+    pub fn new_with_init_timeout(
+        lang: LanguageMode,
+        cfg: LspConfig,
+        timeout: std::time::Duration,
+    ) -> anyhow::Result<Self> {
         info!(
             "lsp: initializing session (strict={}, mock={})",
             cfg.strict, cfg.mock
@@ -321,6 +528,7 @@ impl LspSession {
                 }
             };
             return Ok(Self {
+                root: session_root(&cfg),
                 _cfg: cfg,
                 capabilities: caps,
                 child: None,
@@ -341,8 +549,10 @@ impl LspSession {
         let Some((exe, args)) = cmd else {
             anyhow::bail!("lsp server not determined for language")
         };
+        let root = session_root(&cfg);
         let mut child = std::process::Command::new(exe)
             .args(args)
+            .current_dir(&root)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::null())
@@ -358,8 +568,7 @@ impl LspSession {
             .ok_or_else(|| anyhow::anyhow!("no stdout"))?;
 
         // Send initialize request with workspace root to help servers (e.g. rust-analyzer)
-        let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-        let root_uri = path_to_uri(&cwd);
+        let root_uri = path_to_uri(&root);
         let init = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -380,8 +589,6 @@ impl LspSession {
         use std::io::Read;
         let mut acc: Vec<u8> = Vec::new();
         let start = std::time::Instant::now();
-        // Allow more time for real servers to initialize
-        let timeout = std::time::Duration::from_millis(2000);
         loop {
             let mut tmp = [0u8; 4096];
             match stdout.read(&mut tmp) {
@@ -413,6 +620,7 @@ impl LspSession {
                         let _ = stdin.write_all(&encode_jsonrpc_message(&initialized));
                         // Keep session handles to allow future requests
                         return Ok(Self {
+                            root,
                             _cfg: cfg,
                             capabilities: m,
                             child: Some(child),
@@ -514,7 +722,7 @@ impl LspSession {
                 if val.get("id").and_then(|v| v.as_u64()) == Some(id) {
                     if val.get("error").is_some() {
                         warn!("lsp: error for method {} id={}", method, id);
-                        anyhow::bail!("lsp error response")
+                        return Err(LspErrorResponse.into());
                     }
                     trace!("lsp: response id={} method={}", id, method);
                     return Ok(val.get("result").cloned().unwrap_or(json!({})));
@@ -526,6 +734,69 @@ impl LspSession {
         }
     }
 
+    /// [`LspSession::request`], sent again per [`LspConfig::retry`] while the
+    /// server answers with an error.
+    fn request_with_retry(
+        &mut self,
+        method: &str,
+        params: &serde_json::Value,
+        timeout_ms: u64,
+    ) -> anyhow::Result<serde_json::Value> {
+        let retry = self._cfg.retry;
+        retry.run(method, || self.request(method, params.clone(), timeout_ms))
+    }
+
+    /// Send one `method` request per entry of `params` before reading any
+    /// response, so the server works through them back to back instead of
+    /// waiting on a round trip each. Answers are in `params` order; requests
+    /// unanswered when `timeout_ms` runs out yield `None`.
+    fn request_pipelined(
+        &mut self,
+        method: &str,
+        params: Vec<serde_json::Value>,
+        timeout_ms: u64,
+    ) -> anyhow::Result<Vec<Option<PipelinedResponse>>> {
+        if self._cfg.mock || self.stdin.is_none() || self.stdout.is_none() {
+            anyhow::bail!("lsp request not available (mock or no io)")
+        }
+        let mut slots: std::collections::HashMap<u64, usize> = std::collections::HashMap::new();
+        let mut buf = Vec::new();
+        for (i, p) in params.into_iter().enumerate() {
+            let id = self.next_request_id();
+            slots.insert(id, i);
+            buf.extend(encode_jsonrpc_message(
+                &json!({"jsonrpc":"2.0","id": id, "method": method, "params": p}),
+            ));
+        }
+        let mut out = vec![None; slots.len()];
+        debug!("lsp: pipelined {} x{}", method, out.len());
+        use std::io::Write;
+        self.stdin.as_mut().unwrap().write_all(&buf)?;
+
+        use std::io::Read;
+        let mut acc: Vec<u8> = Vec::new();
+        let start = std::time::Instant::now();
+        let timeout = std::time::Duration::from_millis(timeout_ms);
+        while !slots.is_empty() {
+            let mut tmp = [0u8; 8192];
+            let n = self.stdout.as_mut().unwrap().read(&mut tmp)?;
+            if n == 0 {
+                anyhow::bail!("lsp server closed")
+            }
+            acc.extend_from_slice(&tmp[..n]);
+            take_responses(&mut acc, &mut slots, &mut out);
+            if start.elapsed() > timeout {
+                warn!(
+                    "lsp: {} pipelined {} request(s) unanswered at timeout",
+                    slots.len(),
+                    method
+                );
+                break;
+            }
+        }
+        Ok(out)
+    }
+
     #[allow(dead_code)]
     pub fn notify(&mut self, method: &str, params: serde_json::Value) -> anyhow::Result<()> {
         if self._cfg.mock || self.stdin.is_none() {
@@ -558,20 +829,62 @@ impl LspSession {
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Send `textDocument/didClose` for `uri` if this session opened it, so the
+    /// server can drop the document once its analysis is done.
+    fn close_document(&mut self, uri: &str) -> anyhow::Result<()> {
+        if !self.opened_docs.remove(uri) {
+            return Ok(());
+        }
+        self.notify(
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+    }
+
+    fn close_all_documents(&mut self) {
+        let uris: Vec<String> = self.opened_docs.iter().cloned().collect();
+        for uri in uris {
+            let _ = self.close_document(&uri);
+        }
+    }
+
     pub fn shutdown(mut self) {
-        if self._cfg.mock {
+        self.shutdown_server();
+    }
+
+    /// Close any documents still open, then `shutdown` and `exit` the server
+    /// and wait for it to go away, killing it if it outlives the grace period.
+    fn shutdown_server(&mut self) {
+        if self._cfg.mock || self.child.is_none() {
             return;
         }
-        if let (Some(mut _stdin), Some(mut child)) = (self.stdin.take(), self.child.take()) {
+        self.close_all_documents();
+        let _ = self.request("shutdown", serde_json::Value::Null, SHUTDOWN_TIMEOUT_MS);
+        if let Some(mut stdin) = self.stdin.take() {
             use std::io::Write;
-            let _ = _stdin.write_all(&encode_jsonrpc_message(
-                &json!({"jsonrpc":"2.0","id":9999,"method":"shutdown"}),
-            ));
-            let _ = _stdin.write_all(&encode_jsonrpc_message(
+            let _ = stdin.write_all(&encode_jsonrpc_message(
                 &json!({"jsonrpc":"2.0","method":"exit"}),
             ));
-            let _ = child.kill();
+        }
+        self.stdout = None;
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_millis(SHUTDOWN_TIMEOUT_MS);
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => return,
+                Ok(None) if std::time::Instant::now() < deadline => {
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                }
+                _ => {
+                    debug!("lsp: server did not exit after shutdown; killing it");
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return;
+                }
+            }
         }
     }
 
@@ -624,37 +937,43 @@ impl LspSession {
         }
         // pick first LSP-supported file, if any
         let first_supported = files.iter().find(|p| profile_for_path(p).is_some());
-        if let Some(path) = first_supported {
-            let p = std::path::Path::new(path);
-            if let Ok(abs) = std::fs::canonicalize(p) {
-                let uri = path_to_uri(&abs);
-                // Probe documentSymbol
-                let _ = self
-                    .request(
-                        "textDocument/documentSymbol",
-                        json!({"textDocument": {"uri": uri}}),
-                        400,
-                    )
-                    .map(|_| {
-                        self.capabilities.document_symbol = true;
-                    });
-                // Probe prepareCallHierarchy with a dummy position 0,0
-                let _ = self.request("textDocument/prepareCallHierarchy", json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 0}}), 400)
-                    .map(|_| { self.capabilities.call_hierarchy = true; });
-                // Probe references/definition quickly
-                let pos = json!({"line": 0, "character": 0});
-                let _ = self.request("textDocument/references", json!({"textDocument": {"uri": uri}, "position": pos, "context": {"includeDeclaration": true}}), 300)
+        if let Some(path) = first_supported
+            && let Ok(abs) = std::fs::canonicalize(self.root.join(path))
+        {
+            let uri = path_to_uri(&abs);
+            // Probe documentSymbol
+            let _ = self
+                .request(
+                    "textDocument/documentSymbol",
+                    json!({"textDocument": {"uri": uri}}),
+                    400,
+                )
+                .map(|_| {
+                    self.capabilities.document_symbol = true;
+                });
+            // Probe prepareCallHierarchy with a dummy position 0,0
+            let _ = self
+                .request(
+                    "textDocument/prepareCallHierarchy",
+                    json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 0}}),
+                    400,
+                )
+                .map(|_| {
+                    self.capabilities.call_hierarchy = true;
+                });
+            // Probe references/definition quickly
+            let pos = json!({"line": 0, "character": 0});
+            let _ = self.request("textDocument/references", json!({"textDocument": {"uri": uri}, "position": pos, "context": {"includeDeclaration": true}}), 300)
                     .map(|_| { self.capabilities.references = true; });
-                let _ = self
-                    .request(
-                        "textDocument/definition",
-                        json!({"textDocument": {"uri": uri}, "position": pos}),
-                        300,
-                    )
-                    .map(|_| {
-                        self.capabilities.definition = true;
-                    });
-            }
+            let _ = self
+                .request(
+                    "textDocument/definition",
+                    json!({"textDocument": {"uri": uri}, "position": pos}),
+                    300,
+                )
+                .map(|_| {
+                    self.capabilities.definition = true;
+                });
         }
     }
 
@@ -676,11 +995,11 @@ impl LspSession {
             return Ok(cached.clone());
         }
         let params = json!({"textDocument": {"uri": uri}, "position": {"line": line0, "character": character0}});
-        let v = self.request("textDocument/prepareCallHierarchy", params.clone(), 700)?;
+        let v = self.request_with_retry("textDocument/prepareCallHierarchy", &params, 700)?;
         let mut out = v.as_array().cloned().unwrap_or_default();
         if out.is_empty() {
             std::thread::sleep(std::time::Duration::from_millis(60));
-            let v2 = self.request("textDocument/prepareCallHierarchy", params, 900)?;
+            let v2 = self.request_with_retry("textDocument/prepareCallHierarchy", &params, 900)?;
             out = v2.as_array().cloned().unwrap_or_default();
         }
         self.prepare_call_hierarchy_cache.insert(key, out.clone());
@@ -742,12 +1061,64 @@ impl LspSession {
 
     fn req_document_symbol(&mut self, uri: &str) -> anyhow::Result<Vec<serde_json::Value>> {
         let params = json!({"textDocument": {"uri": uri}});
-        let v = self.request("textDocument/documentSymbol", params, 800)?;
+        let v = self.request_with_retry("textDocument/documentSymbol", &params, 800)?;
         let out = v.as_array().cloned().unwrap_or_default();
+        Ok(self.remember_document_symbols(uri, out))
+    }
+
+    /// `documentSymbol` for many documents at once, pipelined (see
+    /// [`LspSession::request_pipelined`]); results are in `uris` order, empty
+    /// for documents the server failed on. Documents answered with an error
+    /// are asked for again, together, per [`LspConfig::retry`]; unanswered
+    /// ones are not.
+    fn req_document_symbols(&mut self, uris: &[String]) -> Vec<Vec<serde_json::Value>> {
+        let retry = self._cfg.retry;
+        let mut results: Vec<Option<serde_json::Value>> = vec![None; uris.len()];
+        let mut pending: Vec<usize> = (0..uris.len()).collect();
+        let _ = retry.run("textDocument/documentSymbol", || {
+            let params = pending
+                .iter()
+                .map(|&i| json!({"textDocument": {"uri": uris[i]}}))
+                .collect();
+            let timeout_ms = 800 * pending.len() as u64;
+            let answers =
+                self.request_pipelined("textDocument/documentSymbol", params, timeout_ms)?;
+            let mut failed = Vec::new();
+            for (i, answer) in pending.iter().copied().zip(answers) {
+                match answer {
+                    Some(Ok(v)) => results[i] = Some(v),
+                    Some(Err(_)) => failed.push(i),
+                    None => {}
+                }
+            }
+            pending = failed;
+            if pending.is_empty() {
+                Ok(())
+            } else {
+                Err(LspErrorResponse.into())
+            }
+        });
+        uris.iter()
+            .zip(results)
+            .map(|(uri, v)| {
+                let out = v.and_then(|v| v.as_array().cloned()).unwrap_or_default();
+                self.remember_document_symbols(uri, out)
+            })
+            .collect()
+    }
+
+    /// Cache a non-empty `documentSymbol` result for `uri`, or fall back to the
+    /// last non-empty one when the server answers with nothing (e.g. while
+    /// it is still indexing).
+    fn remember_document_symbols(
+        &mut self,
+        uri: &str,
+        out: Vec<serde_json::Value>,
+    ) -> Vec<serde_json::Value> {
         if !out.is_empty() {
             self.doc_symbol_cache.insert(uri.to_string(), out.clone());
             trace!("lsp: documentSymbol uri={} -> {}", uri, out.len());
-            return Ok(out);
+            return out;
         }
         if let Some(cached) = self.doc_symbol_cache.get(uri) {
             trace!(
@@ -755,10 +1126,10 @@ impl LspSession {
                 uri,
                 cached.len()
             );
-            return Ok(cached.clone());
+            return cached.clone();
         }
         trace!("lsp: documentSymbol uri={} -> 0", uri);
-        Ok(out)
+        out
     }
 
     fn req_definition(
@@ -776,7 +1147,7 @@ impl LspSession {
         let mut out = Vec::new();
         if let Some(arr) = v.as_array() {
             out.extend(arr.clone());
-        } else if v.get("targetUri").is_some() {
+        } else if location_start(&v).is_some() {
             out.push(v.clone());
         }
         self.definition_cache.insert(key, out.clone());
@@ -880,8 +1251,8 @@ impl LspEngine {
         auto_policy: Option<super::AutoPolicy>,
     ) -> Self {
         Self {
+            fallback: super::ts::TsEngine::new(cfg.clone()),
             cfg,
-            fallback: super::ts::TsEngine,
             auto_policy,
         }
     }
@@ -890,13 +1261,74 @@ impl LspEngine {
         match self.auto_policy {
             Some(super::AutoPolicy::StrictIfAvailable) => "strict-if-available",
             Some(super::AutoPolicy::Compat) => "compat",
+            Some(super::AutoPolicy::Probe) => "probe",
             None if self.cfg.lsp_strict => "strict",
             None => "non-strict",
         }
     }
 
     fn prefers_lsp_in_auto(&self) -> bool {
-        matches!(self.auto_policy, Some(super::AutoPolicy::StrictIfAvailable))
+        matches!(
+            self.auto_policy,
+            Some(super::AutoPolicy::StrictIfAvailable | super::AutoPolicy::Probe)
+        )
+    }
+
+    fn log_changed_decision(
+        &self,
+        lang: LanguageMode,
+        caps: &CapabilityMatrix,
+        strategy: ChangedStrategy,
+    ) {
+        info!(
+            "engine.lsp.changed_symbols: policy={} decision={} reason={} language={:?} capabilities={}",
+            self.policy_label(),
+            strategy.as_str(),
+            strategy.reason(),
+            lang,
+            capability_list(caps)
+        );
+    }
+
+    fn log_impact_decision(
+        &self,
+        phase: &str,
+        lang: LanguageMode,
+        direction: crate::impact::ImpactDirection,
+        caps: &CapabilityMatrix,
+        strategy: ImpactStrategy,
+    ) {
+        info!(
+            "engine.lsp.{}: policy={} decision={} reason={} language={:?} direction={:?} capabilities={}",
+            phase,
+            self.policy_label(),
+            strategy.as_str(),
+            strategy.reason(),
+            lang,
+            direction,
+            capability_list(caps)
+        );
+    }
+
+    /// One attempt within the chosen strategy and what it produced; the next
+    /// step (or the TS fallback) follows when it comes back empty.
+    fn log_impact_step(&self, step: &str, impacted: usize) {
+        debug!(
+            "engine.lsp.impact: policy={} step={} outcome={} impacted={}",
+            self.policy_label(),
+            step,
+            if impacted == 0 { "empty" } else { "hit" },
+            impacted
+        );
+    }
+
+    fn log_impact_step_failed(&self, step: &str, err: &anyhow::Error) {
+        debug!(
+            "engine.lsp.impact: policy={} step={} outcome=error error={}",
+            self.policy_label(),
+            step,
+            err
+        );
     }
 
     fn log_capability_fallback_changed(
@@ -932,61 +1364,30 @@ impl LspEngine {
             capability_snapshot(caps)
         );
     }
-}
 
-impl super::AnalysisEngine for LspEngine {
-    fn changed_symbols(
+    /// LSP leg of `impact`. `Ok(None)` means LSP yielded nothing usable and,
+    /// outside strict mode, the caller should answer from TS alone.
+    fn impact_via_lsp(
         &self,
         diffs: &[FileChanges],
         lang: LanguageMode,
-    ) -> anyhow::Result<ChangedOutput> {
-        info!(
-            "engine.lsp.changed_symbols: strict={}, files={}",
-            self.cfg.lsp_strict,
-            diffs.len()
-        );
-        let files_list: Vec<String> = diffs.iter().filter_map(|fc| fc.new_path.clone()).collect();
-        let session_lang = session_mode_for_files(lang, &files_list);
-        // Default non-strict LSP behavior keeps TS fallback-first for compatibility.
-        if !self.cfg.lsp_strict && !self.prefers_lsp_in_auto() {
-            if self.cfg.dump_capabilities {
-                // ベストエフォートでcapabilitiesをダンプ
-                let lsp_cfg = LspConfig {
-                    strict: false,
-                    dump_capabilities: true,
-                    mock: self.cfg.mock_lsp,
-                    mock_caps: self.cfg.mock_caps,
-                };
-                match LspSession::new(session_lang, lsp_cfg) {
-                    Ok(mut s) => {
-                        s.probe_update();
-                        eprintln!(
-                            "{}",
-                            serde_json::to_string(&s.capabilities)
-                                .unwrap_or_else(|_| "{}".to_string())
-                        );
-                    }
-                    Err(_) => {
-                        eprintln!(
-                            "{}",
-                            serde_json::to_string(&CapabilityMatrix::default())
-                                .unwrap_or_else(|_| "{}".to_string())
-                        );
-                    }
-                }
-            }
-            return self.fallback.changed_symbols(diffs, lang);
-        }
+        opts: &ImpactOptions,
+        session_lang: LanguageMode,
+        files_list: &[String],
+    ) -> anyhow::Result<Option<ImpactOutput>> {
+        // Attempt LSP impact; if session init fails, fallback only when not strict
         let lsp_cfg = LspConfig {
             strict: self.cfg.lsp_strict,
             dump_capabilities: self.cfg.dump_capabilities,
             mock: self.cfg.mock_lsp,
             mock_caps: self.cfg.mock_caps,
+            retry: self.cfg.lsp_retry,
+            root: self.cfg.analysis.root.clone(),
         };
         match LspSession::new(session_lang, lsp_cfg) {
             Ok(mut _sess) => {
                 _sess.probe_update();
-                _sess.probe_files(&files_list);
+                _sess.probe_files(files_list);
                 if self.cfg.dump_capabilities {
                     eprintln!(
                         "{}",
@@ -994,139 +1395,26 @@ impl super::AnalysisEngine for LspEngine {
                             .unwrap_or_else(|_| "{}".to_string())
                     );
                 }
-                // Strategy selection
-                match decide_changed_strategy(&_sess.capabilities) {
-                    ChangedStrategy::DocumentSymbol | ChangedStrategy::WorkspaceSymbol => {
-                        let out = lsp_changed_symbols(&mut _sess, diffs, lang)?;
-                        if out.changed_symbols.is_empty() {
-                            if self.cfg.lsp_strict {
-                                anyhow::bail!(
-                                    "lsp changed_symbols returned no symbols; policy=strict language={:?}",
-                                    lang
-                                )
-                            } else {
-                                self.log_capability_fallback_changed(
-                                    lang,
-                                    &_sess.capabilities,
-                                    "empty-changed-symbols",
-                                );
-                                self.fallback.changed_symbols(diffs, lang)
-                            }
-                        } else {
-                            Ok(out)
-                        }
-                    }
-                    ChangedStrategy::TsFallback => {
-                        if self.cfg.lsp_strict {
-                            Err(strict_changed_capability_error(
-                                lang,
-                                &_sess.capabilities,
-                                self.policy_label(),
-                            ))
-                        } else {
-                            self.log_capability_fallback_changed(
-                                lang,
-                                &_sess.capabilities,
-                                "missing-document-or-workspace-symbol",
-                            );
-                            self.fallback.changed_symbols(diffs, lang)
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                if self.cfg.dump_capabilities {
-                    eprintln!(
-                        "{}",
-                        serde_json::to_string(&CapabilityMatrix::default())
-                            .unwrap_or_else(|_| "{}".to_string())
-                    );
-                }
-                if self.cfg.lsp_strict {
-                    Err(e)
-                } else {
-                    warn!(
-                        "engine.lsp.changed_symbols: policy={} fallback=ts reason=session-init-failed language={:?} error={}",
-                        self.policy_label(),
-                        lang,
-                        e
-                    );
-                    self.fallback.changed_symbols(diffs, lang)
-                }
-            }
-        }
-    }
-
-    fn impact(
-        &self,
-        diffs: &[FileChanges],
-        lang: LanguageMode,
-        opts: &ImpactOptions,
-    ) -> anyhow::Result<ImpactOutput> {
-        info!(
-            "engine.lsp.impact: strict={}, direction={:?}, max_depth={:?}",
-            self.cfg.lsp_strict, opts.direction, opts.max_depth
-        );
-        let files_list: Vec<String> = diffs.iter().filter_map(|fc| fc.new_path.clone()).collect();
-        let session_lang = session_mode_for_files(lang, &files_list);
-        if !self.cfg.lsp_strict && self.cfg.dump_capabilities {
-            // Print capabilities for diagnostics even if we fallback computation
-            let lsp_cfg = LspConfig {
-                strict: false,
-                dump_capabilities: true,
-                mock: self.cfg.mock_lsp,
-                mock_caps: self.cfg.mock_caps,
-            };
-            match LspSession::new(session_lang, lsp_cfg) {
-                Ok(mut s) => {
-                    s.probe_update();
-                    eprintln!(
-                        "{}",
-                        serde_json::to_string(&s.capabilities).unwrap_or_else(|_| "{}".to_string())
-                    );
-                }
-                Err(_) => {
-                    eprintln!(
-                        "{}",
-                        serde_json::to_string(&CapabilityMatrix::default())
-                            .unwrap_or_else(|_| "{}".to_string())
-                    );
-                }
-            }
-        }
-        if !self.cfg.lsp_strict && !self.cfg.mock_lsp && !self.prefers_lsp_in_auto() {
-            return self.fallback.impact(diffs, lang, opts);
-        }
-        // Attempt LSP impact; if session init fails, fallback only when not strict
-        let lsp_cfg = LspConfig {
-            strict: self.cfg.lsp_strict,
-            dump_capabilities: self.cfg.dump_capabilities,
-            mock: self.cfg.mock_lsp,
-            mock_caps: self.cfg.mock_caps,
-        };
-        match LspSession::new(session_lang, lsp_cfg) {
-            Ok(mut _sess) => {
-                _sess.probe_update();
-                _sess.probe_files(&files_list);
-                if self.cfg.dump_capabilities {
-                    eprintln!(
-                        "{}",
-                        serde_json::to_string(&_sess.capabilities)
-                            .unwrap_or_else(|_| "{}".to_string())
-                    );
-                }
-                // Use callHierarchy BFS when available; else fallback/strict error
-                if _sess.capabilities.call_hierarchy {
-                    let changed = lsp_changed_symbols(&mut _sess, diffs, lang)?;
-                    if _sess._cfg.mock {
-                        // In mock mode, fall back to TS graph impact for determinism in tests
-                        let (index, refs) = crate::impact::build_project_graph()?;
-                        return Ok(crate::impact::compute_impact(
-                            &changed.changed_symbols,
-                            &index,
-                            &refs,
-                            opts,
-                        ));
+                let strategy = impact_strategy_for(&_sess.capabilities, opts.direction);
+                self.log_impact_decision(
+                    "impact",
+                    lang,
+                    opts.direction,
+                    &_sess.capabilities,
+                    strategy,
+                );
+                // Use callHierarchy BFS when available; else fallback/strict error
+                if _sess.capabilities.call_hierarchy {
+                    let changed = lsp_changed_symbols(&mut _sess, diffs, lang, &self.cfg.mapping)?;
+                    if _sess._cfg.mock {
+                        // In mock mode, fall back to TS graph impact for determinism in tests
+                        let (index, refs) = crate::impact::build_project_graph()?;
+                        return Ok(Some(crate::impact::compute_impact(
+                            &changed.changed_symbols,
+                            &index,
+                            &refs,
+                            opts,
+                        )));
                     }
                     let out = lsp_impact_bfs(&mut _sess, changed.changed_symbols.clone(), opts);
                     match out {
@@ -1134,9 +1422,11 @@ impl super::AnalysisEngine for LspEngine {
                             if !o.impacted_symbols.is_empty()
                                 || changed.changed_symbols.is_empty() =>
                         {
-                            Ok(o)
+                            self.log_impact_step("call_hierarchy", o.impacted_symbols.len());
+                            Ok(Some(o))
                         }
-                        Ok(mut o_empty) => {
+                        Ok(o_empty) => {
+                            self.log_impact_step("call_hierarchy", 0);
                             // LSP内フォールバック: references/definition ベースを strict/非strict を問わず試す（Callers/Both）
                             if matches!(
                                 opts.direction,
@@ -1149,8 +1439,9 @@ impl super::AnalysisEngine for LspEngine {
                                     changed.changed_symbols.clone(),
                                     opts,
                                 )?;
+                                self.log_impact_step("references", out2.impacted_symbols.len());
                                 if !out2.impacted_symbols.is_empty() {
-                                    return Ok(out2);
+                                    return Ok(Some(out2));
                                 }
                                 // 両方向の場合はcallee側も補完する
                             }
@@ -1159,30 +1450,32 @@ impl super::AnalysisEngine for LspEngine {
                                 crate::impact::ImpactDirection::Callees
                                     | crate::impact::ImpactDirection::Both
                             ) {
-                                let (callees, extra_edges) =
-                                    scan_callees_for_changed(&mut _sess, &changed.changed_symbols);
-                                if !callees.is_empty() {
+                                let callees = lsp_impact_callees_by_definition(
+                                    &mut _sess,
+                                    &changed.changed_symbols,
+                                    opts,
+                                );
+                                self.log_impact_step(
+                                    "callees_by_definition",
+                                    callees.impacted_symbols.len(),
+                                );
+                                if !callees.impacted_symbols.is_empty() {
                                     // マージして返す（strictでもOK）
-                                    o_empty.impacted_symbols = callees;
-                                    if opts.with_edges.unwrap_or(false) {
-                                        o_empty.edges = extra_edges;
-                                    }
-                                    // impacted_files再計算
-                                    let mut files: Vec<String> = o_empty
-                                        .impacted_symbols
-                                        .iter()
-                                        .map(|s| s.file.clone())
-                                        .collect();
-                                    files.sort();
-                                    files.dedup();
-                                    o_empty.impacted_files = files;
-                                    return Ok(o_empty);
+                                    return Ok(Some(build_impact_output(
+                                        changed.changed_symbols.clone(),
+                                        callees.impacted_symbols,
+                                        callees.edges,
+                                        callees.summary_depth_by_symbol_id,
+                                        opts,
+                                        callees.truncated,
+                                    )));
                                 }
                             }
                             // LSPのみでプロジェクトグラフを構築（TS相当）してimpactを算出（strictでもOK）
                             if o_empty.impacted_symbols.is_empty()
                                 && !changed.changed_symbols.is_empty()
-                                && let Ok((index, refs)) = lsp_build_project_graph(&mut _sess)
+                                && let Ok((index, refs)) =
+                                    lsp_build_project_graph(&mut _sess, &self.cfg.analysis.walk)
                             {
                                 let out2 = crate::impact::compute_impact(
                                     &changed.changed_symbols,
@@ -1190,11 +1483,12 @@ impl super::AnalysisEngine for LspEngine {
                                     &refs,
                                     opts,
                                 );
-                                return Ok(out2);
+                                self.log_impact_step("project_graph", out2.impacted_symbols.len());
+                                return Ok(Some(out2));
                             }
                             // 非strictのみTSフォールバック
                             if self.cfg.lsp_strict {
-                                Ok(o_empty)
+                                Ok(Some(o_empty))
                             } else {
                                 self.log_capability_fallback_impact(
                                     lang,
@@ -1202,10 +1496,11 @@ impl super::AnalysisEngine for LspEngine {
                                     &_sess.capabilities,
                                     "lsp-produced-empty-impact",
                                 );
-                                self.fallback.impact(diffs, lang, opts)
+                                Ok(None)
                             }
                         }
                         Err(e) => {
+                            self.log_impact_step_failed("call_hierarchy", &e);
                             // まずはLSP内のreferencesルートへ（strictでもOK; Callers/Both）
                             if matches!(
                                 opts.direction,
@@ -1218,8 +1513,9 @@ impl super::AnalysisEngine for LspEngine {
                                     changed.changed_symbols.clone(),
                                     opts,
                                 )?;
+                                self.log_impact_step("references", out2.impacted_symbols.len());
                                 if !out2.impacted_symbols.is_empty() || self.cfg.lsp_strict {
-                                    return Ok(out2);
+                                    return Ok(Some(out2));
                                 }
                             }
                             if matches!(
@@ -1227,37 +1523,39 @@ impl super::AnalysisEngine for LspEngine {
                                 crate::impact::ImpactDirection::Callees
                                     | crate::impact::ImpactDirection::Both
                             ) {
-                                let (callees, extra_edges) =
-                                    scan_callees_for_changed(&mut _sess, &changed.changed_symbols);
-                                if !callees.is_empty() {
-                                    let mut summary_depth_by_symbol_id =
-                                        std::collections::HashMap::new();
-                                    for sym in &callees {
-                                        record_lsp_min_depth(
-                                            &mut summary_depth_by_symbol_id,
-                                            &sym.id.0,
-                                            1,
-                                        );
-                                    }
-                                    return Ok(build_impact_output(
+                                let callees = lsp_impact_callees_by_definition(
+                                    &mut _sess,
+                                    &changed.changed_symbols,
+                                    opts,
+                                );
+                                self.log_impact_step(
+                                    "callees_by_definition",
+                                    callees.impacted_symbols.len(),
+                                );
+                                if !callees.impacted_symbols.is_empty() {
+                                    return Ok(Some(build_impact_output(
                                         changed.changed_symbols.clone(),
-                                        callees,
-                                        extra_edges,
-                                        summary_depth_by_symbol_id,
-                                        opts.with_edges.unwrap_or(false),
-                                    ));
+                                        callees.impacted_symbols,
+                                        callees.edges,
+                                        callees.summary_depth_by_symbol_id,
+                                        opts,
+                                        callees.truncated,
+                                    )));
                                 }
                             }
                             // LSPでの全体グラフ構築にトライ
-                            if let Ok((index, refs)) = lsp_build_project_graph(&mut _sess) {
+                            if let Ok((index, refs)) =
+                                lsp_build_project_graph(&mut _sess, &self.cfg.analysis.walk)
+                            {
                                 let out2 = crate::impact::compute_impact(
                                     &changed.changed_symbols,
                                     &index,
                                     &refs,
                                     opts,
                                 );
+                                self.log_impact_step("project_graph", out2.impacted_symbols.len());
                                 if !out2.impacted_symbols.is_empty() || self.cfg.lsp_strict {
-                                    return Ok(out2);
+                                    return Ok(Some(out2));
                                 }
                             }
                             if self.cfg.lsp_strict {
@@ -1270,17 +1568,18 @@ impl super::AnalysisEngine for LspEngine {
                                     opts.direction,
                                     e
                                 );
-                                self.fallback.impact(diffs, lang, opts)
+                                Ok(None)
                             }
                         }
                     }
-                } else if _sess.capabilities.references || _sess.capabilities.definition {
-                    let changed = lsp_changed_symbols(&mut _sess, diffs, lang)?;
+                } else if supports_reference_impact(&_sess.capabilities, opts.direction) {
+                    let changed = lsp_changed_symbols(&mut _sess, diffs, lang, &self.cfg.mapping)?;
                     lsp_impact_references_definition(
                         &mut _sess,
                         changed.changed_symbols.clone(),
                         opts,
                     )
+                    .map(Some)
                 } else if self.cfg.lsp_strict {
                     Err(strict_impact_capability_error(
                         lang,
@@ -1296,7 +1595,7 @@ impl super::AnalysisEngine for LspEngine {
                         &_sess.capabilities,
                         "missing-impact-capabilities",
                     );
-                    self.fallback.impact(diffs, lang, opts)
+                    Ok(None)
                 }
             }
             Err(e) => {
@@ -1317,10 +1616,213 @@ impl super::AnalysisEngine for LspEngine {
                         opts.direction,
                         e
                     );
-                    self.fallback.impact(diffs, lang, opts)
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+impl super::AnalysisEngine for LspEngine {
+    fn changed_symbols(
+        &self,
+        diffs: &[FileChanges],
+        lang: LanguageMode,
+    ) -> anyhow::Result<ChangedOutput> {
+        info!(
+            "engine.lsp.changed_symbols: strict={}, files={}",
+            self.cfg.lsp_strict,
+            diffs.len()
+        );
+        let files_list: Vec<String> = diffs.iter().filter_map(|fc| fc.new_path.clone()).collect();
+        let session_lang = session_mode_for_files(lang, &files_list);
+        // Default non-strict LSP behavior keeps TS fallback-first for compatibility.
+        if !self.cfg.lsp_strict && !self.prefers_lsp_in_auto() {
+            if self.cfg.dump_capabilities {
+                // ベストエフォートでcapabilitiesをダンプ
+                let lsp_cfg = LspConfig {
+                    strict: false,
+                    dump_capabilities: true,
+                    mock: self.cfg.mock_lsp,
+                    mock_caps: self.cfg.mock_caps,
+                    retry: self.cfg.lsp_retry,
+                    root: self.cfg.analysis.root.clone(),
+                };
+                match LspSession::new(session_lang, lsp_cfg) {
+                    Ok(mut s) => {
+                        s.probe_update();
+                        eprintln!(
+                            "{}",
+                            serde_json::to_string(&s.capabilities)
+                                .unwrap_or_else(|_| "{}".to_string())
+                        );
+                    }
+                    Err(_) => {
+                        eprintln!(
+                            "{}",
+                            serde_json::to_string(&CapabilityMatrix::default())
+                                .unwrap_or_else(|_| "{}".to_string())
+                        );
+                    }
+                }
+            }
+            info!(
+                "engine.lsp.changed_symbols: policy={} decision=ts_fallback reason=lsp-not-preferred language={:?}",
+                self.policy_label(),
+                lang
+            );
+            return self.fallback.changed_symbols(diffs, lang);
+        }
+        let lsp_cfg = LspConfig {
+            strict: self.cfg.lsp_strict,
+            dump_capabilities: self.cfg.dump_capabilities,
+            mock: self.cfg.mock_lsp,
+            mock_caps: self.cfg.mock_caps,
+            retry: self.cfg.lsp_retry,
+            root: self.cfg.analysis.root.clone(),
+        };
+        match LspSession::new(session_lang, lsp_cfg) {
+            Ok(mut _sess) => {
+                _sess.probe_update();
+                _sess.probe_files(&files_list);
+                if self.cfg.dump_capabilities {
+                    eprintln!(
+                        "{}",
+                        serde_json::to_string(&_sess.capabilities)
+                            .unwrap_or_else(|_| "{}".to_string())
+                    );
+                }
+                // Strategy selection
+                let strategy = decide_changed_strategy(&_sess.capabilities);
+                self.log_changed_decision(lang, &_sess.capabilities, strategy);
+                match strategy {
+                    ChangedStrategy::DocumentSymbol | ChangedStrategy::WorkspaceSymbol => {
+                        let out = lsp_changed_symbols(&mut _sess, diffs, lang, &self.cfg.mapping)?;
+                        if out.changed_symbols.is_empty() {
+                            if self.cfg.lsp_strict {
+                                anyhow::bail!(
+                                    "lsp changed_symbols returned no symbols; policy=strict language={:?}",
+                                    lang
+                                )
+                            } else {
+                                self.log_capability_fallback_changed(
+                                    lang,
+                                    &_sess.capabilities,
+                                    "empty-changed-symbols",
+                                );
+                                self.fallback.changed_symbols(diffs, lang)
+                            }
+                        } else {
+                            Ok(out)
+                        }
+                    }
+                    ChangedStrategy::TsFallback => {
+                        if self.cfg.lsp_strict {
+                            Err(strict_changed_capability_error(
+                                lang,
+                                &_sess.capabilities,
+                                self.policy_label(),
+                            ))
+                        } else {
+                            self.log_capability_fallback_changed(
+                                lang,
+                                &_sess.capabilities,
+                                "missing-document-or-workspace-symbol",
+                            );
+                            self.fallback.changed_symbols(diffs, lang)
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                if self.cfg.dump_capabilities {
+                    eprintln!(
+                        "{}",
+                        serde_json::to_string(&CapabilityMatrix::default())
+                            .unwrap_or_else(|_| "{}".to_string())
+                    );
+                }
+                if self.cfg.lsp_strict {
+                    Err(e)
+                } else {
+                    warn!(
+                        "engine.lsp.changed_symbols: policy={} fallback=ts reason=session-init-failed language={:?} error={}",
+                        self.policy_label(),
+                        lang,
+                        e
+                    );
+                    self.fallback.changed_symbols(diffs, lang)
+                }
+            }
+        }
+    }
+
+    fn impact(
+        &self,
+        diffs: &[FileChanges],
+        lang: LanguageMode,
+        opts: &ImpactOptions,
+    ) -> anyhow::Result<ImpactOutput> {
+        info!(
+            "engine.lsp.impact: strict={}, direction={:?}, max_depth={:?}",
+            self.cfg.lsp_strict, opts.direction, opts.max_depth
+        );
+        let files_list: Vec<String> = diffs.iter().filter_map(|fc| fc.new_path.clone()).collect();
+        let session_lang = session_mode_for_files(lang, &files_list);
+        if !self.cfg.lsp_strict && self.cfg.dump_capabilities {
+            // Print capabilities for diagnostics even if we fallback computation
+            let lsp_cfg = LspConfig {
+                strict: false,
+                dump_capabilities: true,
+                mock: self.cfg.mock_lsp,
+                mock_caps: self.cfg.mock_caps,
+                retry: self.cfg.lsp_retry,
+                root: self.cfg.analysis.root.clone(),
+            };
+            match LspSession::new(session_lang, lsp_cfg) {
+                Ok(mut s) => {
+                    s.probe_update();
+                    eprintln!(
+                        "{}",
+                        serde_json::to_string(&s.capabilities).unwrap_or_else(|_| "{}".to_string())
+                    );
+                }
+                Err(_) => {
+                    eprintln!(
+                        "{}",
+                        serde_json::to_string(&CapabilityMatrix::default())
+                            .unwrap_or_else(|_| "{}".to_string())
+                    );
                 }
             }
         }
+        if !self.cfg.lsp_strict && !self.cfg.mock_lsp && !self.prefers_lsp_in_auto() {
+            info!(
+                "engine.lsp.impact: policy={} decision=ts_fallback reason=lsp-not-preferred language={:?} direction={:?}",
+                self.policy_label(),
+                lang,
+                opts.direction
+            );
+            return self.fallback.impact(diffs, lang, opts);
+        }
+        if self.cfg.lsp_strict {
+            return self
+                .impact_via_lsp(diffs, lang, opts, session_lang, &files_list)?
+                .ok_or_else(|| anyhow::anyhow!("lsp impact produced no result; policy=strict"));
+        }
+        // Hybrid: union what LSP found with the TS graph so neither side's
+        // partial coverage is lost. Edges are needed to recompute depths.
+        let graph_opts = ImpactOptions {
+            with_edges: Some(true),
+            ..opts.clone()
+        };
+        match self.impact_via_lsp(diffs, lang, &graph_opts, session_lang, &files_list)? {
+            Some(lsp_out) => {
+                let ts_out = self.fallback.impact(diffs, lang, &graph_opts)?;
+                Ok(merge_hybrid_impact(lsp_out, ts_out, opts))
+            }
+            None => self.fallback.impact(diffs, lang, opts),
+        }
     }
 
     fn impact_from_symbols(
@@ -1340,6 +1842,8 @@ impl super::AnalysisEngine for LspEngine {
             dump_capabilities: self.cfg.dump_capabilities,
             mock: self.cfg.mock_lsp,
             mock_caps: self.cfg.mock_caps,
+            retry: self.cfg.lsp_retry,
+            root: self.cfg.analysis.root.clone(),
         };
         let session_lang = session_mode_for_symbols(lang, changed);
         let mut sess = match LspSession::new(session_lang, lsp_cfg) {
@@ -1365,26 +1869,47 @@ impl super::AnalysisEngine for LspEngine {
                 serde_json::to_string(&sess.capabilities).unwrap_or_else(|_| "{}".to_string())
             );
         }
+        let strategy = impact_strategy_for(&sess.capabilities, opts.direction);
+        self.log_impact_decision(
+            "impact_from_symbols",
+            lang,
+            opts.direction,
+            &sess.capabilities,
+            strategy,
+        );
         // prefer callHierarchy BFS
         if sess.capabilities.call_hierarchy {
             let out = lsp_impact_bfs(&mut sess, changed.to_vec(), opts);
             match out {
-                Ok(o) if !o.impacted_symbols.is_empty() || changed.is_empty() => Ok(o),
+                Ok(o) if !o.impacted_symbols.is_empty() || changed.is_empty() => {
+                    self.log_impact_step("call_hierarchy", o.impacted_symbols.len());
+                    Ok(o)
+                }
                 Ok(o_empty) => {
+                    self.log_impact_step("call_hierarchy", 0);
                     // fall back to full LSP graph
-                    if let Ok((index, refs)) = lsp_build_project_graph(&mut sess) {
-                        return Ok(crate::impact::compute_impact(changed, &index, &refs, opts));
+                    if let Ok((index, refs)) =
+                        lsp_build_project_graph(&mut sess, &self.cfg.analysis.walk)
+                    {
+                        let out2 = crate::impact::compute_impact(changed, &index, &refs, opts);
+                        self.log_impact_step("project_graph", out2.impacted_symbols.len());
+                        return Ok(out2);
                     }
                     Ok(o_empty)
                 }
-                Err(_) => {
-                    if let Ok((index, refs)) = lsp_build_project_graph(&mut sess) {
-                        return Ok(crate::impact::compute_impact(changed, &index, &refs, opts));
+                Err(e) => {
+                    self.log_impact_step_failed("call_hierarchy", &e);
+                    if let Ok((index, refs)) =
+                        lsp_build_project_graph(&mut sess, &self.cfg.analysis.walk)
+                    {
+                        let out2 = crate::impact::compute_impact(changed, &index, &refs, opts);
+                        self.log_impact_step("project_graph", out2.impacted_symbols.len());
+                        return Ok(out2);
                     }
                     anyhow::bail!("lsp impact_from_symbols failed")
                 }
             }
-        } else if sess.capabilities.references || sess.capabilities.definition {
+        } else if supports_reference_impact(&sess.capabilities, opts.direction) {
             lsp_impact_references_definition(&mut sess, changed.to_vec(), opts)
         } else if self.cfg.lsp_strict {
             Err(strict_impact_capability_error(
@@ -1406,7 +1931,7 @@ impl super::AnalysisEngine for LspEngine {
     }
 }
 
-fn item_to_symbol(item: &serde_json::Value) -> Option<crate::ir::Symbol> {
+fn item_to_symbol(item: &serde_json::Value, root: &std::path::Path) -> Option<crate::ir::Symbol> {
     // callHierarchy responses can wrap items in {from: CallHierarchyItem} / {to: CallHierarchyItem}
     let obj = if item.get("name").is_some() {
         item
@@ -1421,7 +1946,7 @@ fn item_to_symbol(item: &serde_json::Value) -> Option<crate::ir::Symbol> {
     let name = obj.get("name")?.as_str()?.to_string();
     let kind = map_lsp_symbol_kind(obj.get("kind")?.as_u64().unwrap_or(12));
     let uri = obj.get("uri").and_then(|v| v.as_str())?;
-    let file = uri_to_path(uri);
+    let file = uri_to_path(uri, root);
     let profile = profile_for_path(&file)?;
     let range_v = obj.get("selectionRange").or_else(|| obj.get("range"))?;
     let sl = range_v
@@ -1469,8 +1994,7 @@ fn lsp_impact_bfs(
         let Some(profile) = profile_for_path(&s.file) else {
             continue;
         };
-        let abspath =
-            std::fs::canonicalize(&s.file).unwrap_or_else(|_| std::path::PathBuf::from(&s.file));
+        let abspath = sess.abs_path(&s.file);
         let uri = path_to_uri(&abspath);
         if !sess._cfg.mock
             && let Ok(text) = std::fs::read_to_string(&abspath)
@@ -1482,7 +2006,7 @@ fn lsp_impact_bfs(
             crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
         ) {
             // Directly seed from the changed callable itself
-            let (line0, ch0) = guess_callable_position(&s.file, s)
+            let (line0, ch0) = guess_callable_position(&sess.root, &s.file, s)
                 .unwrap_or((s.range.start_line.saturating_sub(1), 0));
             let mut roots = sess
                 .req_prepare_call_hierarchy(&uri, line0, ch0)
@@ -1495,25 +2019,14 @@ fn lsp_impact_bfs(
             // If direct prepare failed, resolve definition once and retry on its position.
             if roots.is_empty()
                 && let Ok(defs) = sess.req_definition(&uri, line0, ch0)
-                && let Some(loc) = defs.first()
-                && let Some(r) = loc.get("targetSelectionRange").or_else(|| loc.get("range"))
-                && let (Some(sl), Some(sc)) = (
-                    r.get("start")
-                        .and_then(|s| s.get("line"))
-                        .and_then(|n| n.as_u64()),
-                    r.get("start")
-                        .and_then(|s| s.get("character"))
-                        .and_then(|n| n.as_u64()),
-                )
+                && let Some((def_uri, dl0, dc0)) = defs.first().and_then(location_start)
             {
-                let dl0 = sl as u32;
-                let dc0 = sc as u32;
                 roots = sess
-                    .req_prepare_call_hierarchy(&uri, dl0, dc0)
+                    .req_prepare_call_hierarchy(&def_uri, dl0, dc0)
                     .unwrap_or_default();
                 if roots.is_empty() && dc0 != 0 {
                     roots = sess
-                        .req_prepare_call_hierarchy(&uri, dl0, 0)
+                        .req_prepare_call_hierarchy(&def_uri, dl0, 0)
                         .unwrap_or_default();
                 }
             }
@@ -1535,51 +2048,28 @@ fn lsp_impact_bfs(
             let defs = sess
                 .req_definition(&uri, s.range.start_line.saturating_sub(1), 0)
                 .unwrap_or_default();
-            let (def_uri, def_line0, def_ch0) = if let Some(loc) = defs.first() {
-                let u = loc
-                    .get("uri")
-                    .or_else(|| loc.get("targetUri"))
-                    .and_then(|v| v.as_str())
-                    .unwrap_or(&uri)
-                    .to_string();
-                let r = loc.get("targetSelectionRange").or_else(|| loc.get("range"));
-                let l0 = r
-                    .and_then(|rr| rr.get("start"))
-                    .and_then(|st| st.get("line"))
-                    .and_then(|n| n.as_u64())
-                    .unwrap_or(0) as u32;
-                let c0 = r
-                    .and_then(|rr| rr.get("start"))
-                    .and_then(|st| st.get("character"))
-                    .and_then(|n| n.as_u64())
-                    .unwrap_or(0) as u32;
-                (u, l0, c0)
-            } else {
-                (uri.clone(), s.range.start_line.saturating_sub(1), 0)
-            };
+            let (def_uri, def_line0, def_ch0) = defs
+                .first()
+                .and_then(location_start)
+                .unwrap_or_else(|| (uri.clone(), s.range.start_line.saturating_sub(1), 0));
             let refs = sess
                 .req_references(&def_uri, def_line0, def_ch0)
                 .unwrap_or_default();
             for loc in refs {
-                let loc_uri = loc.get("uri").and_then(|v| v.as_str()).unwrap_or("");
-                let file = uri_to_path(loc_uri);
-                let line0 = loc
-                    .get("range")
-                    .and_then(|r| r.get("start"))
-                    .and_then(|st| st.get("line"))
-                    .and_then(|n| n.as_u64())
-                    .unwrap_or(0) as u32;
-                let items = sess.req_document_symbol(loc_uri).unwrap_or_default();
+                let Some((loc_uri, line0)) = location_to_file_line(&loc) else {
+                    continue;
+                };
+                let file = uri_to_path(&loc_uri, &sess.root);
+                let items = sess.req_document_symbol(&loc_uri).unwrap_or_default();
                 if let Some(caller) = enclosing_symbol_in_doc(&items, &file, line0)
                     && matches!(
                         caller.kind,
                         crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
                     )
                 {
-                    let c_abs = std::fs::canonicalize(&caller.file)
-                        .unwrap_or_else(|_| std::path::PathBuf::from(&caller.file));
+                    let c_abs = sess.abs_path(&caller.file);
                     let c_uri = path_to_uri(&c_abs);
-                    let (l0, ch0) = guess_callable_position(&caller.file, &caller)
+                    let (l0, ch0) = guess_callable_position(&sess.root, &caller.file, &caller)
                         .unwrap_or((caller.range.start_line.saturating_sub(1), 0));
                     let mut roots = sess
                         .req_prepare_call_hierarchy(&c_uri, l0, ch0)
@@ -1607,17 +2097,27 @@ fn lsp_impact_bfs(
     }
     // If no roots prepared, return empty impact (caller will decide LSP内フォールバックやTSフォールバック)
     if seeded_roots == 0 {
+        sess.close_all_documents();
         return Ok(build_impact_output(
             changed,
             Vec::new(),
             Vec::new(),
             summary_depth_by_symbol_id,
-            opts.with_edges.unwrap_or(false),
+            opts,
+            false,
         ));
     }
 
+    // Owned so the enqueue env can hold it while `sess` is borrowed mutably
+    let root = sess.root.clone();
+    let budget = crate::impact::TimeBudget::start(opts);
+    let mut truncated = false;
     while let Some((item, d)) = q.pop_front() {
-        let cur_sym = if let Some(sym) = item_to_symbol(&item) {
+        if budget.is_spent() {
+            truncated = true;
+            break;
+        }
+        let cur_sym = if let Some(sym) = item_to_symbol(&item, &root) {
             sym
         } else {
             continue;
@@ -1638,11 +2138,12 @@ fn lsp_impact_bfs(
                     seen_keys: &mut seen_keys,
                     node_map: &mut node_map,
                     summary_depth_by_symbol_id: &mut summary_depth_by_symbol_id,
+                    root: &root,
                 };
                 let incoming = sess.req_incoming_calls(&item).unwrap_or_default();
                 for inc in &incoming {
                     if let Some(from) = inc.get("from") {
-                        enqueue_edge(&mut env, from, &cur_sym, d + 1, true);
+                        enqueue_edge(&mut env, from, &cur_sym, d + 1, true, call_line(inc));
                     }
                 }
                 // Supplement callers via references only for roots when callHierarchy yields nothing.
@@ -1657,10 +2158,11 @@ fn lsp_impact_bfs(
                     seen_keys: &mut seen_keys,
                     node_map: &mut node_map,
                     summary_depth_by_symbol_id: &mut summary_depth_by_symbol_id,
+                    root: &root,
                 };
                 for out in sess.req_outgoing_calls(&item).unwrap_or_default() {
                     if let Some(to) = out.get("to") {
-                        enqueue_edge(&mut env, to, &cur_sym, d + 1, false);
+                        enqueue_edge(&mut env, to, &cur_sym, d + 1, false, call_line(&out));
                     }
                 }
                 // Also scan body to enrich outgoing even when some were found
@@ -1673,11 +2175,12 @@ fn lsp_impact_bfs(
                     seen_keys: &mut seen_keys,
                     node_map: &mut node_map,
                     summary_depth_by_symbol_id: &mut summary_depth_by_symbol_id,
+                    root: &root,
                 };
                 let incoming = sess.req_incoming_calls(&item).unwrap_or_default();
                 for inc in &incoming {
                     if let Some(from) = inc.get("from") {
-                        enqueue_edge(&mut env, from, &cur_sym, d + 1, true);
+                        enqueue_edge(&mut env, from, &cur_sym, d + 1, true, call_line(inc));
                     }
                 }
                 if incoming.is_empty() && d == 0 {
@@ -1689,10 +2192,11 @@ fn lsp_impact_bfs(
                     seen_keys: &mut seen_keys,
                     node_map: &mut node_map,
                     summary_depth_by_symbol_id: &mut summary_depth_by_symbol_id,
+                    root: &root,
                 };
                 for out in sess.req_outgoing_calls(&item).unwrap_or_default() {
                     if let Some(to) = out.get("to") {
-                        enqueue_edge(&mut env2, to, &cur_sym, d + 1, false);
+                        enqueue_edge(&mut env2, to, &cur_sym, d + 1, false, call_line(&out));
                     }
                 }
                 let _ = scan_and_enqueue_callees(sess, &cur_sym, &mut env2, d + 1);
@@ -1700,6 +2204,7 @@ fn lsp_impact_bfs(
         }
     }
 
+    sess.close_all_documents();
     let changed_ids: HashSet<String> = changed.iter().map(|s| s.id.0.clone()).collect();
     let impacted_symbols: Vec<crate::ir::Symbol> = node_map
         .values()
@@ -1712,12 +2217,102 @@ fn lsp_impact_bfs(
         impacted_symbols,
         edges,
         summary_depth_by_symbol_id,
-        opts.with_edges.unwrap_or(false),
+        opts,
+        truncated,
     ))
 }
 
 // Heuristic: scan the function source for simple callsites like `name(` or `path::name(`,
 // then resolve definition via LSP and seed call hierarchy from there.
+/// Words followed by `(` that are not calls.
+const CALL_KEYWORDS: &[&str] = &["if", "while", "loop", "match", "for", "return", "fn"];
+
+/// A call target found by [`scan_call_sites`]: the last path or method segment
+/// before `(` and its byte offset in the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CallSite<'a> {
+    name: &'a str,
+    offset: usize,
+}
+
+/// Find call targets in one line of source: `foo(`, `a::b(`, `x.y(`, with
+/// turbofish segments such as `foo::<T>(` or `Vec::<u8>::new(` skipped, so the
+/// target is the identifier before them. Words in `keywords` are ignored.
+fn scan_call_sites<'a>(line: &'a str, keywords: &[&str]) -> Vec<CallSite<'a>> {
+    let bytes = line.as_bytes();
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_';
+    let mut out = Vec::new();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        if !(bytes[i].is_ascii_alphabetic() || bytes[i] == b'_') {
+            i += 1;
+            continue;
+        }
+        let mut seg = (i, i);
+        while i < bytes.len() {
+            if is_ident(bytes[i]) {
+                i += 1;
+                seg.1 = i;
+                continue;
+            }
+            // Rust path `::`, or a turbofish `::<...>` that keeps the current segment
+            if bytes[i] == b':' && bytes.get(i + 1) == Some(&b':') {
+                if bytes.get(i + 2) == Some(&b'<') {
+                    match skip_generic_args(bytes, i + 2) {
+                        Some(after) => {
+                            i = after;
+                            continue;
+                        }
+                        None => break,
+                    }
+                }
+                i += 2;
+                seg = (i, i);
+                continue;
+            }
+            // method call .name
+            if bytes[i] == b'.' && bytes.get(i + 1).is_some_and(|&c| is_ident(c)) {
+                i += 1;
+                seg = (i, i);
+                continue;
+            }
+            break;
+        }
+        let mut j = i;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        let name = &line[seg.0..seg.1];
+        if j < bytes.len() && bytes[j] == b'(' && !name.is_empty() && !keywords.contains(&name) {
+            out.push(CallSite {
+                name,
+                offset: seg.0,
+            });
+        }
+    }
+    out
+}
+
+/// Index just past the `>` that closes the `<` at `open`, or None when unbalanced.
+fn skip_generic_args(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (k, &c) in bytes.iter().enumerate().skip(open) {
+        match c {
+            b'<' => depth += 1,
+            // `->` inside `fn(..) -> T` does not close anything
+            b'>' if k > 0 && bytes[k - 1] == b'-' => {}
+            b'>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(k + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn scan_and_enqueue_callees(
     sess: &mut LspSession,
     cur_sym: &crate::ir::Symbol,
@@ -1726,12 +2321,7 @@ fn scan_and_enqueue_callees(
 ) -> usize {
     use std::io::Read;
     let mut added = 0usize;
-    let path = std::path::Path::new(&cur_sym.file);
-    let abspath = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
-    };
+    let abspath = sess.abs_path(&cur_sym.file);
     let uri = path_to_uri(&abspath);
     let mut s = String::new();
     if let Ok(mut f) = std::fs::File::open(&abspath) {
@@ -1745,136 +2335,64 @@ fn scan_and_enqueue_callees(
     let lines: Vec<&str> = s.lines().collect();
     let mut seen_names: std::collections::HashSet<(u32, u32)> = std::collections::HashSet::new();
     for (li, line) in lines.iter().enumerate().take(end0 + 1).skip(start0) {
-        let bytes = line.as_bytes();
-        let mut i = 0usize;
-        while i < bytes.len() {
-            // identifier or path segment
-            if bytes[i].is_ascii_alphabetic() || bytes[i] == b'_' {
-                let mut last_seg_start = i;
-                i += 1;
-                while i < bytes.len() {
-                    let c = bytes[i];
-                    if c.is_ascii_alphanumeric() || c == b'_' {
-                        i += 1;
-                        continue;
-                    }
-                    // Rust path ::
-                    if i + 1 < bytes.len() && c == b':' && bytes[i + 1] == b':' {
-                        i += 2;
-                        last_seg_start = i;
-                        continue;
-                    }
-                    // method call .name
-                    if c == b'.' {
-                        i += 1;
-                        last_seg_start = i;
-                        continue;
-                    }
-                    break;
-                }
-                // skip whitespace
-                let mut j = i;
-                while j < bytes.len() && bytes[j].is_ascii_whitespace() {
-                    j += 1;
-                }
-                if j < bytes.len() && bytes[j] == b'(' {
-                    // crude keyword/macro filter
-                    let name = &line[last_seg_start..i];
-                    if !(name == "if"
-                        || name == "while"
-                        || name == "loop"
-                        || name == "match"
-                        || name == "for"
-                        || name == "return"
-                        || name == "fn"
-                        || name.ends_with('!'))
-                    {
-                        // avoid self-edge on signature line or recursive detection by name-equality heuristic
-                        if name == cur_sym.name && (li as u32 + 1) == cur_sym.range.start_line {
-                            i = j;
+        for site in scan_call_sites(line, CALL_KEYWORDS) {
+            // avoid self-edge on signature line or recursive detection by name-equality heuristic
+            if site.name == cur_sym.name && (li as u32 + 1) == cur_sym.range.start_line {
+                continue;
+            }
+            let line0 = li as u32;
+            let ch0 = site.offset as u32;
+            if seen_names.insert((line0, ch0)) {
+                // try definition at name start
+                if let Ok(defs) = sess.req_definition(&uri, line0, ch0) {
+                    for loc in defs {
+                        let Some((u, rs, rc)) = location_start(&loc) else {
                             continue;
+                        };
+                        // prepare hierarchy at callee definition
+                        let mut roots = sess
+                            .req_prepare_call_hierarchy(&u, rs, rc)
+                            .unwrap_or_default();
+                        if roots.is_empty() {
+                            roots = sess
+                                .req_prepare_call_hierarchy(&u, rs, 0)
+                                .unwrap_or_default();
                         }
-                        let line0 = li as u32;
-                        let ch0 = last_seg_start as u32;
-                        if seen_names.insert((line0, ch0)) {
-                            // try definition at name start
-                            if let Ok(defs) = sess.req_definition(&uri, line0, ch0) {
-                                for loc in defs {
-                                    let u = loc
-                                        .get("uri")
-                                        .or_else(|| loc.get("targetUri"))
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("");
-                                    let r = loc
-                                        .get("range")
-                                        .or_else(|| loc.get("targetSelectionRange"));
-                                    if u.is_empty() || r.is_none() {
-                                        continue;
-                                    }
-                                    let rs = r
-                                        .unwrap()
-                                        .get("start")
-                                        .and_then(|st| st.get("line"))
-                                        .and_then(|n| n.as_u64())
-                                        .unwrap_or(0)
-                                        as u32;
-                                    let rc = r
-                                        .unwrap()
-                                        .get("start")
-                                        .and_then(|st| st.get("character"))
-                                        .and_then(|n| n.as_u64())
-                                        .unwrap_or(0)
-                                        as u32;
-                                    // prepare hierarchy at callee definition
-                                    let mut roots = sess
-                                        .req_prepare_call_hierarchy(u, rs, rc)
-                                        .unwrap_or_default();
-                                    if roots.is_empty() {
-                                        roots = sess
-                                            .req_prepare_call_hierarchy(u, rs, 0)
-                                            .unwrap_or_default();
-                                    }
-                                    for it in roots {
-                                        let key = format!(
-                                            "{}:{}:{}",
-                                            it.get("uri").and_then(|uu| uu.as_str()).unwrap_or(""),
-                                            it.get("name").and_then(|n| n.as_str()).unwrap_or(""),
-                                            it.get("kind").and_then(|k| k.as_u64()).unwrap_or(0)
-                                        );
-                                        if env.seen_keys.insert(key) {
-                                            // enqueue node and edge cur_sym -> it
-                                            env.q.push_back((it.clone(), next_depth));
-                                            if let Some(sym_to) = item_to_symbol(&it) {
-                                                env.node_map
-                                                    .entry(sym_to.id.0.clone())
-                                                    .or_insert(sym_to.clone());
-                                                record_lsp_min_depth(
-                                                    env.summary_depth_by_symbol_id,
-                                                    &sym_to.id.0,
-                                                    next_depth,
-                                                );
-                                                env.edges.push(crate::ir::reference::Reference {
-                                                    from: cur_sym.id.clone(),
-                                                    to: sym_to.id.clone(),
-                                                    kind: crate::ir::reference::RefKind::Call,
-                                                    file: cur_sym.file.clone(),
-                                                    line: li as u32 + 1,
-                                                    certainty: crate::ir::reference::EdgeCertainty::Confirmed,
-                                                    provenance: crate::ir::reference::EdgeProvenance::CallGraph,
-                                                });
-                                                added += 1;
-                                            }
-                                        }
-                                    }
+                        for it in roots {
+                            let key = format!(
+                                "{}:{}:{}",
+                                it.get("uri").and_then(|uu| uu.as_str()).unwrap_or(""),
+                                it.get("name").and_then(|n| n.as_str()).unwrap_or(""),
+                                it.get("kind").and_then(|k| k.as_u64()).unwrap_or(0)
+                            );
+                            if env.seen_keys.insert(key) {
+                                // enqueue node and edge cur_sym -> it
+                                env.q.push_back((it.clone(), next_depth));
+                                if let Some(sym_to) = item_to_symbol(&it, env.root) {
+                                    env.node_map
+                                        .entry(sym_to.id.0.clone())
+                                        .or_insert(sym_to.clone());
+                                    record_lsp_min_depth(
+                                        env.summary_depth_by_symbol_id,
+                                        &sym_to.id.0,
+                                        next_depth,
+                                    );
+                                    env.edges.push(crate::ir::reference::Reference {
+                                        from: cur_sym.id.clone(),
+                                        to: sym_to.id.clone(),
+                                        kind: crate::ir::reference::RefKind::Call,
+                                        file: cur_sym.file.clone(),
+                                        line: li as u32 + 1,
+                                        certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+                                        provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+                                    });
+                                    added += 1;
                                 }
                             }
                         }
                     }
                 }
-                i = j;
-                continue;
             }
-            i += 1;
         }
     }
     added
@@ -1886,8 +2404,8 @@ fn enqueue_callers_via_references(
     env: &mut EnqueueEnv<'_>,
     next_depth: usize,
 ) {
-    let uri = path_to_uri(std::path::Path::new(&cur_sym.file));
-    let (line0, ch0) = guess_callable_position(&cur_sym.file, cur_sym)
+    let uri = path_to_uri(&sess.abs_path(&cur_sym.file));
+    let (line0, ch0) = guess_callable_position(&sess.root, &cur_sym.file, cur_sym)
         .unwrap_or((cur_sym.range.start_line.saturating_sub(1), 0));
     let mut refs = sess.req_references(&uri, line0, ch0).unwrap_or_default();
 
@@ -1899,28 +2417,10 @@ fn enqueue_callers_via_references(
     // If direct references are sparse, resolve definition and retry at def position.
     if refs.len() <= 1 {
         let defs = sess.req_definition(&uri, line0, ch0).unwrap_or_default();
-        let (def_uri, def_line0, def_ch0) = if let Some(loc) = defs.first() {
-            let u = loc
-                .get("uri")
-                .or_else(|| loc.get("targetUri"))
-                .and_then(|v| v.as_str())
-                .unwrap_or(&uri)
-                .to_string();
-            let r = loc.get("targetSelectionRange").or_else(|| loc.get("range"));
-            let l0 = r
-                .and_then(|rr| rr.get("start"))
-                .and_then(|st| st.get("line"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
-            let c0 = r
-                .and_then(|rr| rr.get("start"))
-                .and_then(|st| st.get("character"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
-            (u, l0, c0)
-        } else {
-            (uri.clone(), line0, ch0)
-        };
+        let (def_uri, def_line0, def_ch0) = defs
+            .first()
+            .and_then(location_start)
+            .unwrap_or_else(|| (uri.clone(), line0, ch0));
         let refs2 = sess
             .req_references(&def_uri, def_line0, def_ch0)
             .unwrap_or_default();
@@ -1929,27 +2429,20 @@ fn enqueue_callers_via_references(
         }
     }
     for loc in refs {
-        let loc_uri = loc.get("uri").and_then(|v| v.as_str()).unwrap_or("");
-        if loc_uri.is_empty() {
+        let Some((loc_uri, line0)) = location_to_file_line(&loc) else {
             continue;
-        }
-        let file = uri_to_path(loc_uri);
-        let line0 = loc
-            .get("range")
-            .and_then(|r| r.get("start"))
-            .and_then(|st| st.get("line"))
-            .and_then(|n| n.as_u64())
-            .unwrap_or(0) as u32;
+        };
+        let file = uri_to_path(&loc_uri, &sess.root);
 
-        if !seen_ref_sites.insert((loc_uri.to_string(), line0)) {
+        if !seen_ref_sites.insert((loc_uri.clone(), line0)) {
             continue;
         }
 
-        let items = if let Some(cached) = doc_items_cache.get(loc_uri) {
+        let items = if let Some(cached) = doc_items_cache.get(&loc_uri) {
             cached.clone()
         } else {
-            let fetched = sess.req_document_symbol(loc_uri).unwrap_or_default();
-            doc_items_cache.insert(loc_uri.to_string(), fetched.clone());
+            let fetched = sess.req_document_symbol(&loc_uri).unwrap_or_default();
+            doc_items_cache.insert(loc_uri.clone(), fetched.clone());
             fetched
         };
         if let Some(caller) = enclosing_symbol_in_doc(&items, &file, line0)
@@ -1958,10 +2451,9 @@ fn enqueue_callers_via_references(
                 crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
             )
         {
-            let c_abs = std::fs::canonicalize(&caller.file)
-                .unwrap_or_else(|_| std::path::PathBuf::from(&caller.file));
+            let c_abs = sess.abs_path(&caller.file);
             let c_uri = path_to_uri(&c_abs);
-            let (l0, ch0) = guess_callable_position(&caller.file, &caller)
+            let (l0, ch0) = guess_callable_position(&sess.root, &caller.file, &caller)
                 .unwrap_or((caller.range.start_line.saturating_sub(1), 0));
             let mut roots = sess
                 .req_prepare_call_hierarchy(&c_uri, l0, ch0)
@@ -1980,7 +2472,7 @@ fn enqueue_callers_via_references(
                 );
                 if env.seen_keys.insert(key) {
                     env.q.push_back((it.clone(), next_depth));
-                    if let Some(sym_from) = item_to_symbol(&it) {
+                    if let Some(sym_from) = item_to_symbol(&it, env.root) {
                         env.node_map
                             .entry(sym_from.id.0.clone())
                             .or_insert(sym_from.clone());
@@ -1994,7 +2486,7 @@ fn enqueue_callers_via_references(
                             to: cur_sym.id.clone(),
                             kind: crate::ir::reference::RefKind::Call,
                             file: sym_from.file.clone(),
-                            line: sym_from.range.start_line,
+                            line: line0 + 1,
                             certainty: crate::ir::reference::EdgeCertainty::Confirmed,
                             provenance: crate::ir::reference::EdgeProvenance::CallGraph,
                         });
@@ -2005,29 +2497,51 @@ fn enqueue_callers_via_references(
     }
 }
 
-// Scan callees for all changed callables and return one-hop callee symbols + edges
-fn scan_callees_for_changed(
+/// Callee impact without call hierarchy: breadth-first from the changed
+/// callables, resolving every call site in a body with textDocument/definition
+/// (see [`scan_callees_symbols`]) up to `opts.max_depth` hops.
+fn lsp_impact_callees_by_definition(
     sess: &mut LspSession,
     changed: &[crate::ir::Symbol],
-) -> (Vec<crate::ir::Symbol>, Vec<crate::ir::reference::Reference>) {
-    let mut out_syms: Vec<crate::ir::Symbol> = Vec::new();
-    let mut out_edges: Vec<crate::ir::reference::Reference> = Vec::new();
-    let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
-    for s in changed.iter().filter(|s| {
-        matches!(
-            s.kind,
-            crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
-        )
-    }) {
-        let (syms, edges) = scan_callees_symbols(sess, s);
-        for sym in syms {
-            if seen.insert(sym.id.0.clone()) {
-                out_syms.push(sym);
+    opts: &crate::impact::ImpactOptions,
+) -> LspImpactBuild {
+    let max_depth = opts.max_depth.unwrap_or(usize::MAX);
+    let mut build = LspImpactBuild::default();
+    let mut seen: std::collections::HashSet<String> =
+        changed.iter().map(|s| s.id.0.clone()).collect();
+    let mut frontier: Vec<crate::ir::Symbol> = changed
+        .iter()
+        .filter(|s| {
+            matches!(
+                s.kind,
+                crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
+            )
+        })
+        .cloned()
+        .collect();
+    let budget = crate::impact::TimeBudget::start(opts);
+    let mut depth = 0;
+    while !frontier.is_empty() && depth < max_depth {
+        depth += 1;
+        let mut next = Vec::new();
+        for cur in &frontier {
+            if budget.is_spent() {
+                build.truncated = true;
+                return build;
+            }
+            let (syms, edges) = scan_callees_symbols(sess, cur);
+            build.edges.extend(edges);
+            for sym in syms {
+                if seen.insert(sym.id.0.clone()) {
+                    record_lsp_min_depth(&mut build.summary_depth_by_symbol_id, &sym.id.0, depth);
+                    build.impacted_symbols.push(sym.clone());
+                    next.push(sym);
+                }
             }
         }
-        out_edges.extend(edges);
+        frontier = next;
     }
-    (out_syms, out_edges)
+    build
 }
 
 // One-hop callee extraction using definitions + documentSymbol mapping
@@ -2039,12 +2553,7 @@ fn scan_callees_symbols(
     let mut out_syms: Vec<crate::ir::Symbol> = Vec::new();
     let mut out_edges: Vec<crate::ir::reference::Reference> = Vec::new();
     let mut seen_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
-    let path = std::path::Path::new(&cur_sym.file);
-    let abspath = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
-    };
+    let abspath = sess.abs_path(&cur_sym.file);
     let uri = path_to_uri(&abspath);
     let mut s = String::new();
     if let Ok(mut f) = std::fs::File::open(&abspath) {
@@ -2057,101 +2566,53 @@ fn scan_callees_symbols(
     let end0 = cur_sym.range.end_line.saturating_sub(1) as usize;
     let lines: Vec<&str> = s.lines().collect();
     for (li, line) in lines.iter().enumerate().take(end0 + 1).skip(start0) {
-        let bytes = line.as_bytes();
-        let mut i = 0usize;
-        while i < bytes.len() {
-            if bytes[i].is_ascii_alphabetic() || bytes[i] == b'_' {
-                let mut last_seg_start = i;
-                i += 1;
-                while i < bytes.len() {
-                    let c = bytes[i];
-                    if c.is_ascii_alphanumeric() || c == b'_' {
-                        i += 1;
-                        continue;
-                    }
-                    if i + 1 < bytes.len() && c == b':' && bytes[i + 1] == b':' {
-                        i += 2;
-                        last_seg_start = i;
-                        continue;
-                    }
-                    if c == b'.' {
-                        i += 1;
-                        last_seg_start = i;
-                        continue;
-                    }
-                    break;
-                }
-                let mut j = i;
-                while j < bytes.len() && bytes[j].is_ascii_whitespace() {
-                    j += 1;
-                }
-                if j < bytes.len() && bytes[j] == b'(' {
-                    let name = &line[last_seg_start..i];
-                    if !(name == "if"
-                        || name == "while"
-                        || name == "loop"
-                        || name == "match"
-                        || name == "for"
-                        || name == "return"
-                        || name == "fn"
-                        || name.ends_with('!'))
-                    {
-                        if name == cur_sym.name && (li as u32 + 1) == cur_sym.range.start_line {
-                            i = j;
-                            continue;
-                        }
-                        let line0 = li as u32;
-                        let ch0 = last_seg_start as u32;
-                        // defs at callsite
-                        let defs = sess.req_definition(&uri, line0, ch0).unwrap_or_default();
-                        for loc in defs {
-                            let def_uri = loc
-                                .get("uri")
-                                .or_else(|| loc.get("targetUri"))
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("");
-                            let def_file = uri_to_path(def_uri);
-                            let r = loc.get("targetSelectionRange").or_else(|| loc.get("range"));
-                            let def_l0 = r
-                                .and_then(|rr| rr.get("start"))
-                                .and_then(|st| st.get("line"))
-                                .and_then(|n| n.as_u64())
-                                .unwrap_or(0) as u32;
-                            let items = sess.req_document_symbol(def_uri).unwrap_or_default();
-                            if let Some(sym_to) = enclosing_symbol_in_doc(&items, &def_file, def_l0)
-                                && (sym_to.id.0 != cur_sym.id.0)
-                                && matches!(
-                                    sym_to.kind,
-                                    crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
-                                )
-                                && seen_ids.insert(sym_to.id.0.clone())
-                            {
-                                out_syms.push(sym_to.clone());
-                                out_edges.push(crate::ir::reference::Reference {
-                                    from: cur_sym.id.clone(),
-                                    to: sym_to.id.clone(),
-                                    kind: crate::ir::reference::RefKind::Call,
-                                    file: cur_sym.file.clone(),
-                                    line: li as u32 + 1,
-                                    certainty: crate::ir::reference::EdgeCertainty::Confirmed,
-                                    provenance: crate::ir::reference::EdgeProvenance::CallGraph,
-                                });
-                            }
-                        }
-                    }
-                }
-                i = j;
+        for site in scan_call_sites(line, CALL_KEYWORDS) {
+            // avoid self-edge on signature line or recursive detection by name-equality heuristic
+            if site.name == cur_sym.name && (li as u32 + 1) == cur_sym.range.start_line {
                 continue;
             }
-            i += 1;
+            let line0 = li as u32;
+            let ch0 = site.offset as u32;
+            // defs at callsite
+            let defs = sess.req_definition(&uri, line0, ch0).unwrap_or_default();
+            for loc in defs {
+                let Some((def_uri, def_l0)) = location_to_file_line(&loc) else {
+                    continue;
+                };
+                let def_file = uri_to_path(&def_uri, &sess.root);
+                let items = sess.req_document_symbol(&def_uri).unwrap_or_default();
+                if let Some(sym_to) = enclosing_symbol_in_doc(&items, &def_file, def_l0)
+                    && (sym_to.id.0 != cur_sym.id.0)
+                    && matches!(
+                        sym_to.kind,
+                        crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
+                    )
+                    && seen_ids.insert(sym_to.id.0.clone())
+                {
+                    out_syms.push(sym_to.clone());
+                    out_edges.push(crate::ir::reference::Reference {
+                        from: cur_sym.id.clone(),
+                        to: sym_to.id.clone(),
+                        kind: crate::ir::reference::RefKind::Call,
+                        file: cur_sym.file.clone(),
+                        line: li as u32 + 1,
+                        certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+                        provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+                    });
+                }
+            }
         }
     }
     (out_syms, out_edges)
 }
 
-fn guess_callable_position(file: &str, sym: &crate::ir::Symbol) -> Option<(u32, u32)> {
+fn guess_callable_position(
+    root: &std::path::Path,
+    file: &str,
+    sym: &crate::ir::Symbol,
+) -> Option<(u32, u32)> {
     use std::io::Read;
-    let mut f = std::fs::File::open(file).ok()?;
+    let mut f = std::fs::File::open(root.join(file)).ok()?;
     let mut s = String::new();
     f.read_to_string(&mut s).ok()?;
     let line_idx = (sym.range.start_line.saturating_sub(1)) as usize;
@@ -2174,6 +2635,8 @@ struct EnqueueEnv<'a> {
     seen_keys: &'a mut std::collections::HashSet<String>,
     node_map: &'a mut std::collections::HashMap<String, crate::ir::Symbol>,
     summary_depth_by_symbol_id: &'a mut std::collections::HashMap<String, usize>,
+    /// The session's workspace root, for locating the enqueued items' files.
+    root: &'a std::path::Path,
 }
 
 #[derive(Default)]
@@ -2181,6 +2644,8 @@ struct LspImpactBuild {
     impacted_symbols: Vec<crate::ir::Symbol>,
     edges: Vec<crate::ir::reference::Reference>,
     summary_depth_by_symbol_id: std::collections::HashMap<String, usize>,
+    /// The time budget ran out before the traversal finished.
+    truncated: bool,
 }
 
 fn record_lsp_min_depth(
@@ -2194,14 +2659,30 @@ fn record_lsp_min_depth(
         .or_insert(depth);
 }
 
+/// 1-based line of the first call site (`fromRanges`) of an incoming or
+/// outgoing call hierarchy entry.
+fn call_line(call: &serde_json::Value) -> Option<u32> {
+    let line0 = call
+        .get("fromRanges")?
+        .as_array()?
+        .first()?
+        .get("start")?
+        .get("line")?
+        .as_u64()?;
+    Some(line0 as u32 + 1)
+}
+
+/// Enqueue `next_item` and record its edge with `cur_sym`, placed at
+/// `call_line` (the caller's first line when the server gave no call site).
 fn enqueue_edge(
     env: &mut EnqueueEnv,
     next_item: &serde_json::Value,
     cur_sym: &crate::ir::Symbol,
     next_depth: usize,
     is_incoming: bool,
+    call_line: Option<u32>,
 ) {
-    if let Some(sym) = item_to_symbol(next_item) {
+    if let Some(sym) = item_to_symbol(next_item, env.root) {
         let key = sym.id.0.clone();
         if env.seen_keys.insert(key.clone()) {
             env.q.push_back((next_item.clone(), next_depth));
@@ -2218,7 +2699,7 @@ fn enqueue_edge(
             to: to.id.clone(),
             kind: crate::ir::reference::RefKind::Call,
             file: from.file.clone(),
-            line: from.range.start_line,
+            line: call_line.unwrap_or(from.range.start_line),
             certainty: crate::ir::reference::EdgeCertainty::Confirmed,
             provenance: crate::ir::reference::EdgeProvenance::CallGraph,
         });
@@ -2230,28 +2711,129 @@ fn build_impact_output(
     impacted_symbols: Vec<crate::ir::Symbol>,
     mut edges: Vec<crate::ir::reference::Reference>,
     summary_depth_by_symbol_id: std::collections::HashMap<String, usize>,
-    with_edges: bool,
+    opts: &crate::impact::ImpactOptions,
+    truncated: bool,
 ) -> crate::impact::ImpactOutput {
+    // Ordering and dedup happen in finalize_impact_output
+    let with_edges = opts.with_edges.unwrap_or(false);
     if !with_edges {
         edges.clear();
+    }
+    // Walking both ways records a call from its caller and from its callee
+    if matches!(opts.direction, crate::impact::ImpactDirection::Both) {
+        crate::impact::dedup_mirrored_edges(&mut edges);
+    }
+
+    let mut out = crate::impact::finalize_impact_output(
+        changed,
+        impacted_symbols,
+        edges,
+        &summary_depth_by_symbol_id,
+        std::collections::HashMap::new(),
+    );
+    out.edges_included = with_edges;
+    out.summary.truncated = truncated;
+    out
+}
+
+/// Union of an LSP-derived and a TS-derived impact result (non-strict hybrid
+/// mode). Symbols are deduplicated by id and edges by their normalized key,
+/// so the merge is deterministic; depths are recomputed over the merged
+/// edges, which therefore must have been requested from both sides.
+fn merge_hybrid_impact(lsp: ImpactOutput, ts: ImpactOutput, opts: &ImpactOptions) -> ImpactOutput {
+    let mut changed = lsp.changed_symbols;
+    for sym in ts.changed_symbols {
+        if !changed.iter().any(|c| c.id == sym.id) {
+            changed.push(sym);
+        }
+    }
+    let changed_ids: std::collections::HashSet<&str> =
+        changed.iter().map(|s| s.id.0.as_str()).collect();
+    let impacted: Vec<crate::ir::Symbol> = lsp
+        .impacted_symbols
+        .into_iter()
+        .chain(ts.impacted_symbols)
+        .filter(|s| !changed_ids.contains(s.id.0.as_str()))
+        .collect();
+    let mut edges = lsp.edges;
+    edges.extend(ts.edges);
+    if matches!(opts.direction, crate::impact::ImpactDirection::Both) {
+        crate::impact::dedup_mirrored_edges(&mut edges);
     } else {
-        edges.sort_by(|a, b| {
-            a.from
-                .0
-                .cmp(&b.from.0)
-                .then_with(|| a.to.0.cmp(&b.to.0))
-                .then_with(|| a.line.cmp(&b.line))
-        });
-        edges.dedup_by(|a, b| a.from.0 == b.from.0 && a.to.0 == b.to.0 && a.line == b.line);
+        crate::impact::normalize_edges(&mut edges);
+    }
+    let depths = hybrid_min_depths(&changed, &edges, opts.direction);
+
+    let mut witnesses = ts.impacted_witnesses;
+    for (id, w) in lsp.impacted_witnesses {
+        match witnesses.get(&id) {
+            Some(prev) if prev.depth <= w.depth => {}
+            _ => {
+                witnesses.insert(id, w);
+            }
+        }
+    }
+    let with_edges = opts.with_edges.unwrap_or(false);
+    if !with_edges {
+        edges.clear();
+    }
+    let mut out =
+        crate::impact::finalize_impact_output(changed, impacted, edges, &depths, witnesses);
+    out.edges_included = with_edges;
+    out.summary.truncated = lsp.summary.truncated || ts.summary.truncated;
+    out.summary.slice_selection = ts.summary.slice_selection.or(lsp.summary.slice_selection);
+    out
+}
+
+/// Minimum hop count from any changed symbol along `edges` in `direction`
+/// (both walks for `Both`); changed symbols are depth 0.
+fn hybrid_min_depths(
+    changed: &[crate::ir::Symbol],
+    edges: &[crate::ir::reference::Reference],
+    direction: crate::impact::ImpactDirection,
+) -> std::collections::HashMap<String, usize> {
+    use crate::impact::ImpactDirection;
+    use std::collections::{HashMap, VecDeque};
+    let walks: &[bool] = match direction {
+        ImpactDirection::Callers => &[true],
+        ImpactDirection::Callees => &[false],
+        ImpactDirection::Both => &[true, false],
+    };
+    let mut depths: HashMap<String, usize> = HashMap::new();
+    for &towards_callers in walks {
+        let mut next: HashMap<&str, Vec<&str>> = HashMap::new();
+        for e in edges {
+            let (a, b) = if towards_callers {
+                (e.to.0.as_str(), e.from.0.as_str())
+            } else {
+                (e.from.0.as_str(), e.to.0.as_str())
+            };
+            next.entry(a).or_default().push(b);
+        }
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        for sym in changed {
+            if seen.insert(sym.id.0.as_str(), 0).is_none() {
+                queue.push_back(sym.id.0.as_str());
+            }
+        }
+        while let Some(cur) = queue.pop_front() {
+            let d = seen[cur];
+            for &n in next.get(cur).into_iter().flatten() {
+                if !seen.contains_key(n) {
+                    seen.insert(n, d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+        for (id, d) in seen {
+            depths
+                .entry(id.to_string())
+                .and_modify(|prev| *prev = (*prev).min(d))
+                .or_insert(d);
+        }
     }
-
-    crate::impact::finalize_impact_output(
-        changed,
-        impacted_symbols,
-        edges,
-        &summary_depth_by_symbol_id,
-        std::collections::HashMap::new(),
-    )
+    depths
 }
 
 fn lsp_impact_references_build(
@@ -2274,66 +2856,47 @@ fn lsp_impact_references_build(
         node_map.insert(s.id.0.clone(), s.clone());
         queued_nodes.insert(s.id.0.clone());
     }
+    let budget = crate::impact::TimeBudget::start(opts);
+    let mut truncated = false;
     while let Some((sym, d)) = q.pop_front() {
+        if budget.is_spent() {
+            truncated = true;
+            break;
+        }
         if let Some(maxd) = opts.max_depth
             && d >= maxd
         {
             continue;
         }
-        let uri = path_to_uri(std::path::Path::new(&sym.file));
+        let uri = path_to_uri(&sess.abs_path(&sym.file));
         // definition at a precise position (prefer symbol name offset)
-        let (line0, ch0) = guess_callable_position(&sym.file, &sym)
+        let (line0, ch0) = guess_callable_position(&sess.root, &sym.file, &sym)
             .unwrap_or((sym.range.start_line.saturating_sub(1), 0));
         let defs = sess.req_definition(&uri, line0, ch0).unwrap_or_default();
-        let (def_uri, def_line0, def_ch0) = if let Some(loc) = defs.first() {
-            let u = loc
-                .get("uri")
-                .or_else(|| loc.get("targetUri"))
-                .and_then(|v| v.as_str())
-                .unwrap_or(&uri)
-                .to_string();
-            let r = loc.get("targetSelectionRange").or_else(|| loc.get("range"));
-            let l0 = r
-                .and_then(|rr| rr.get("start"))
-                .and_then(|s| s.get("line"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
-            let c0 = r
-                .and_then(|rr| rr.get("start"))
-                .and_then(|s| s.get("character"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
-            (u, l0, c0)
-        } else {
-            (uri.clone(), sym.range.start_line.saturating_sub(1), 0)
-        };
+        let (def_uri, def_line0, def_ch0) = defs
+            .first()
+            .and_then(location_start)
+            .unwrap_or_else(|| (uri.clone(), sym.range.start_line.saturating_sub(1), 0));
         let refs = sess
             .req_references(&def_uri, def_line0, def_ch0)
             .unwrap_or_default();
         let mut seen_ref_sites: HashSet<(String, u32)> = HashSet::new();
         for loc in refs {
-            let loc_uri = loc.get("uri").and_then(|v| v.as_str()).unwrap_or("");
-            if loc_uri.is_empty() {
+            let Some((loc_uri, line0)) = location_to_file_line(&loc) else {
                 continue;
-            }
-            let file = uri_to_path(loc_uri);
-            let line0 = loc
-                .get("range")
-                .and_then(|r| r.get("start"))
-                .and_then(|s| s.get("line"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
+            };
+            let file = uri_to_path(&loc_uri, &sess.root);
 
-            if !seen_ref_sites.insert((loc_uri.to_string(), line0)) {
+            if !seen_ref_sites.insert((loc_uri.clone(), line0)) {
                 continue;
             }
 
             // find enclosing symbol via documentSymbol (with local cache)
-            let items = if let Some(cached) = doc_items_cache.get(loc_uri) {
+            let items = if let Some(cached) = doc_items_cache.get(&loc_uri) {
                 cached.clone()
             } else {
-                let fetched = sess.req_document_symbol(loc_uri).unwrap_or_default();
-                doc_items_cache.insert(loc_uri.to_string(), fetched.clone());
+                let fetched = sess.req_document_symbol(&loc_uri).unwrap_or_default();
+                doc_items_cache.insert(loc_uri.clone(), fetched.clone());
                 fetched
             };
             if let Some(caller) = enclosing_symbol_in_doc(&items, &file, line0)
@@ -2351,7 +2914,7 @@ fn lsp_impact_references_build(
                         to: sym.id.clone(),
                         kind: crate::ir::reference::RefKind::Call,
                         file: caller.file.clone(),
-                        line: caller.range.start_line,
+                        line: line0 + 1,
                         certainty: crate::ir::reference::EdgeCertainty::Confirmed,
                         provenance: crate::ir::reference::EdgeProvenance::CallGraph,
                     });
@@ -2373,6 +2936,7 @@ fn lsp_impact_references_build(
         impacted_symbols,
         edges,
         summary_depth_by_symbol_id,
+        truncated,
     })
 }
 
@@ -2388,7 +2952,8 @@ fn lsp_impact_references(
         build.impacted_symbols,
         build.edges,
         build.summary_depth_by_symbol_id,
-        opts.with_edges.unwrap_or(false),
+        opts,
+        build.truncated,
     ))
 }
 
@@ -2416,23 +2981,26 @@ fn lsp_impact_references_definition(
     let mut edges: Vec<crate::ir::reference::Reference> = Vec::new();
     let mut summary_depth_by_symbol_id: std::collections::HashMap<String, usize> =
         std::collections::HashMap::new();
+    let mut truncated = false;
 
     if want_callers {
         let callers_build = lsp_impact_references_build(sess, &changed, opts)?;
         impacted_symbols.extend(callers_build.impacted_symbols);
         edges.extend(callers_build.edges);
+        truncated |= callers_build.truncated;
         for (symbol_id, depth) in callers_build.summary_depth_by_symbol_id {
             record_lsp_min_depth(&mut summary_depth_by_symbol_id, &symbol_id, depth);
         }
     }
 
-    if want_callees {
-        let (callees, callee_edges) = scan_callees_for_changed(sess, &changed);
-        for sym in &callees {
-            record_lsp_min_depth(&mut summary_depth_by_symbol_id, &sym.id.0, 1);
+    if want_callees && sess.capabilities.definition {
+        let callees_build = lsp_impact_callees_by_definition(sess, &changed, opts);
+        impacted_symbols.extend(callees_build.impacted_symbols);
+        edges.extend(callees_build.edges);
+        truncated |= callees_build.truncated;
+        for (symbol_id, depth) in callees_build.summary_depth_by_symbol_id {
+            record_lsp_min_depth(&mut summary_depth_by_symbol_id, &symbol_id, depth);
         }
-        impacted_symbols.extend(callees);
-        edges.extend(callee_edges);
     }
 
     Ok(build_impact_output(
@@ -2440,7 +3008,8 @@ fn lsp_impact_references_definition(
         impacted_symbols,
         edges,
         summary_depth_by_symbol_id,
-        opts.with_edges.unwrap_or(false),
+        opts,
+        truncated,
     ))
 }
 
@@ -2527,7 +3096,9 @@ fn enclosing_symbol_in_doc(
         let b_span = b.range.end_line.saturating_sub(b.range.start_line);
         a_span
             .cmp(&b_span)
+            .then_with(|| a.kind.specificity().cmp(&b.kind.specificity()))
             .then_with(|| b.range.start_line.cmp(&a.range.start_line))
+            .then_with(|| a.id.0.cmp(&b.id.0))
     })
 }
 
@@ -2535,27 +3106,32 @@ fn lsp_changed_symbols(
     sess: &mut LspSession,
     diffs: &[crate::FileChanges],
     lang: crate::mapping::LanguageMode,
+    opts: &crate::mapping::MappingOptions,
 ) -> anyhow::Result<crate::mapping::ChangedOutput> {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeSet, HashMap};
     if sess._cfg.mock {
-        return crate::mapping::compute_changed_symbols(diffs, lang);
+        return crate::mapping::compute_changed_symbols_with_options(diffs, lang, opts);
     }
+    let diffs = &*crate::mapping::significant_changes(diffs, opts);
     // collect changed files and changed line sets
     let mut changed_files: Vec<String> = Vec::new();
-    let mut changed_lines_by_file: HashMap<String, HashSet<u32>> = HashMap::new();
+    let mut changed_lines_by_file: HashMap<String, BTreeSet<u32>> = HashMap::new();
     for fc in diffs.iter() {
         if let Some(path) = &fc.new_path {
             changed_files.push(path.clone());
         }
         if let Some(path) = &fc.new_path {
             let set = changed_lines_by_file.entry(path.clone()).or_default();
-            for ch in &fc.changes {
-                if let Some(nl) = ch.new_line
-                    && (matches!(ch.kind, crate::diff::ChangeKind::Added)
-                        || matches!(ch.kind, crate::diff::ChangeKind::Context))
-                {
-                    set.insert(nl);
-                }
+            set.extend(fc.hunk_new_lines());
+            // Removed lines map through the pre-image to surviving symbols
+            if let Ok(text) = std::fs::read_to_string(sess.root.join(path)) {
+                set.extend(crate::mapping::removed_line_anchors(
+                    fc,
+                    path,
+                    &text,
+                    crate::languages::LanguageKind::Auto,
+                    &opts.analyzers,
+                ));
             }
         }
     }
@@ -2572,7 +3148,7 @@ fn lsp_changed_symbols(
         let Some(language_id) = did_open_language_id_for_path(path, lang) else {
             continue;
         };
-        let abspath = std::fs::canonicalize(path).unwrap_or(std::path::PathBuf::from(path));
+        let abspath = sess.abs_path(path);
         let uri = path_to_uri(&abspath);
         let text = std::fs::read_to_string(&abspath).unwrap_or_else(|_| String::new());
         let _ = sess.ensure_did_open(&uri, language_id, &text);
@@ -2581,6 +3157,7 @@ fn lsp_changed_symbols(
                 collect_symbols_from_item(path, profile.symbol_lang, item, &mut symbols, lines);
             }
         }
+        let _ = sess.close_document(&uri);
     }
     symbols.sort_by(|a, b| a.id.0.cmp(&b.id.0));
     symbols.dedup_by(|a, b| a.id.0 == b.id.0);
@@ -2588,9 +3165,37 @@ fn lsp_changed_symbols(
     Ok(crate::mapping::ChangedOutput {
         changed_files,
         changed_symbols: symbols,
+        symbol_changes: Vec::new(),
+        snippets: std::collections::HashMap::new(),
+        changed_lines: std::collections::HashMap::new(),
+        total_changed: None,
+        change_scopes: std::collections::HashMap::new(),
     })
 }
 
+/// Start of a `Location` (`uri` + `range`) or `LocationLink` (`targetUri` +
+/// `targetSelectionRange`, else `targetRange`) as `(uri, line0, character0)`.
+fn location_start(v: &serde_json::Value) -> Option<(String, u32, u32)> {
+    let (uri, range) = match v.get("targetUri") {
+        Some(u) => (
+            u,
+            v.get("targetSelectionRange")
+                .or_else(|| v.get("targetRange"))?,
+        ),
+        None => (v.get("uri")?, v.get("range")?),
+    };
+    let uri = uri.as_str().filter(|u| !u.is_empty())?;
+    let start = range.get("start")?;
+    let line0 = start.get("line")?.as_u64()? as u32;
+    let ch0 = start.get("character").and_then(|n| n.as_u64()).unwrap_or(0) as u32;
+    Some((uri.to_string(), line0, ch0))
+}
+
+/// URI and 0-based start line of a `Location` or `LocationLink`.
+fn location_to_file_line(v: &serde_json::Value) -> Option<(String, u32)> {
+    location_start(v).map(|(uri, line0, _)| (uri, line0))
+}
+
 fn path_to_uri(p: &std::path::Path) -> String {
     let mut s = String::from("file://");
     // crude percent-encoding for spaces only
@@ -2607,34 +3212,14 @@ fn path_to_uri(p: &std::path::Path) -> String {
     s
 }
 
-fn uri_to_path(uri: &str) -> String {
+fn uri_to_path(uri: &str, root: &std::path::Path) -> String {
     let raw = if let Some(rest) = uri.strip_prefix("file://") {
         rest.replace("%20", " ")
     } else {
         uri.to_string()
     };
     // Normalize to workspace-relative if possible
-    match std::env::current_dir() {
-        Ok(cwd) => {
-            let cwd = cwd.canonicalize().unwrap_or(cwd);
-            let rawp = std::path::Path::new(&raw);
-            if rawp.is_absolute() {
-                if let Ok(stripped) = rawp
-                    .canonicalize()
-                    .unwrap_or_else(|_| rawp.to_path_buf())
-                    .strip_prefix(&cwd)
-                {
-                    let s = stripped.to_string_lossy().to_string();
-                    if s.is_empty() { ".".to_string() } else { s }
-                } else {
-                    raw
-                }
-            } else {
-                raw
-            }
-        }
-        Err(_) => raw,
-    }
+    normalize_path(&raw, root)
 }
 
 fn collect_symbols_from_item(
@@ -2642,7 +3227,7 @@ fn collect_symbols_from_item(
     symbol_lang: &str,
     item: &serde_json::Value,
     out: &mut Vec<crate::ir::Symbol>,
-    changed_lines: &std::collections::HashSet<u32>,
+    changed_lines: &std::collections::BTreeSet<u32>,
 ) {
     // DocumentSymbol form: { name, kind, range{start{line},end{line}}, children? }
     // SymbolInformation form: { name, kind, location{range{...}} }
@@ -2715,19 +3300,13 @@ fn map_lsp_symbol_kind(k: u64) -> crate::ir::SymbolKind {
         22 => crate::ir::SymbolKind::Enum,     // EnumMember -> treat as Enum (non-callable)
         9 => crate::ir::SymbolKind::Trait,     // Interface -> Trait-ish
         2 => crate::ir::SymbolKind::Module,    // Namespace/Module
+        14 => crate::ir::SymbolKind::Const,    // Constant
         _ => crate::ir::SymbolKind::Function,
     }
 }
 
-fn intersects_lines(start: u32, end: u32, lines: &std::collections::HashSet<u32>) -> bool {
-    let mut ln = start;
-    while ln <= end {
-        if lines.contains(&ln) {
-            return true;
-        }
-        ln += 1;
-    }
-    false
+fn intersects_lines(start: u32, end: u32, lines: &std::collections::BTreeSet<u32>) -> bool {
+    start <= end && lines.range(start..=end).next().is_some()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -2744,6 +3323,61 @@ pub enum ImpactStrategy {
     TsFallback,
 }
 
+impl ChangedStrategy {
+    /// The snake_case name used in decision logs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangedStrategy::DocumentSymbol => "document_symbol",
+            ChangedStrategy::WorkspaceSymbol => "workspace_symbol",
+            ChangedStrategy::TsFallback => "ts_fallback",
+        }
+    }
+
+    /// Why [`decide_changed_strategy`] lands here.
+    fn reason(self) -> &'static str {
+        match self {
+            ChangedStrategy::DocumentSymbol => "document_symbol-supported",
+            ChangedStrategy::WorkspaceSymbol => "document_symbol-missing",
+            ChangedStrategy::TsFallback => "document_symbol-and-workspace_symbol-missing",
+        }
+    }
+}
+
+impl ImpactStrategy {
+    /// The snake_case name used in decision logs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImpactStrategy::CallHierarchy => "call_hierarchy",
+            ImpactStrategy::References => "references",
+            ImpactStrategy::TsFallback => "ts_fallback",
+        }
+    }
+
+    fn reason(self) -> &'static str {
+        match self {
+            ImpactStrategy::CallHierarchy => "call_hierarchy-supported",
+            ImpactStrategy::References => "call_hierarchy-missing",
+            ImpactStrategy::TsFallback => "no-impact-capability-for-direction",
+        }
+    }
+}
+
+/// The impact path [`LspEngine`] takes for `direction`: like
+/// [`decide_impact_strategy`], but references/definition only count when they
+/// can answer that direction.
+fn impact_strategy_for(
+    caps: &CapabilityMatrix,
+    direction: crate::impact::ImpactDirection,
+) -> ImpactStrategy {
+    if caps.call_hierarchy {
+        ImpactStrategy::CallHierarchy
+    } else if supports_reference_impact(caps, direction) {
+        ImpactStrategy::References
+    } else {
+        ImpactStrategy::TsFallback
+    }
+}
+
 pub fn decide_changed_strategy(caps: &CapabilityMatrix) -> ChangedStrategy {
     if caps.document_symbol {
         ChangedStrategy::DocumentSymbol
@@ -2764,6 +3398,119 @@ pub fn decide_impact_strategy(caps: &CapabilityMatrix) -> ImpactStrategy {
     }
 }
 
+/// How long the Auto engine waits for a server's initialize response.
+const AUTO_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Whether a real (never mocked) language server for `lang` starts within
+/// [`AUTO_PROBE_TIMEOUT`] and advertises enough capabilities for both changed
+/// and impact analysis. The result is cached for the rest of the process.
+pub fn server_available(lang: LanguageMode) -> bool {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+    static PROBED: OnceLock<Mutex<HashMap<LanguageMode, bool>>> = OnceLock::new();
+    let cache = PROBED.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(hit) = cache.lock().unwrap().get(&lang) {
+        return *hit;
+    }
+    let available = lang != LanguageMode::Auto
+        && server_command_for_mode(lang).is_some()
+        && match LspSession::new_with_init_timeout(lang, LspConfig::default(), AUTO_PROBE_TIMEOUT) {
+            Ok(sess) => {
+                let caps = sess.capabilities.clone();
+                sess.shutdown();
+                decide_changed_strategy(&caps) != ChangedStrategy::TsFallback
+                    && decide_impact_strategy(&caps) != ImpactStrategy::TsFallback
+            }
+            Err(e) => {
+                debug!("engine.auto: probe failed language={:?}: {}", lang, e);
+                false
+            }
+        };
+    cache.lock().unwrap().insert(lang, available);
+    available
+}
+
+/// Readiness of one language server, as reported by `dimpact engine-check`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EngineCheck {
+    pub language: String,
+    /// Server executable that was (or would be) spawned
+    pub server: Option<String>,
+    pub ok: bool,
+    /// Why the check failed; absent on success
+    pub reason: Option<String>,
+    /// Capabilities after the initialize handshake and probes
+    pub capabilities: Option<CapabilityMatrix>,
+}
+
+/// Spawn the language server for `lang`, run the initialize handshake and the
+/// capability probes, and report whether the LSP engine could use it.
+///
+/// `files` are candidate workspace files; the first one matching `lang` is
+/// used for the document-level probes.
+pub fn check_language_server(lang: LanguageMode, cfg: LspConfig, files: &[String]) -> EngineCheck {
+    let language = profile_for_mode(lang)
+        .map(|p| p.symbol_lang)
+        .unwrap_or("auto")
+        .to_string();
+    let server = if cfg.mock {
+        Some("mock".to_string())
+    } else {
+        server_command_for_mode(lang).map(|(exe, _)| exe.to_string())
+    };
+    let fail = |server: Option<String>, reason: String| EngineCheck {
+        language: language.clone(),
+        server,
+        ok: false,
+        reason: Some(reason),
+        capabilities: None,
+    };
+    if server.is_none() && std::env::var("DIMPACT_DISABLE_REAL_LSP").ok().as_deref() != Some("1") {
+        return fail(None, "no language server found".to_string());
+    }
+    let mut session = match LspSession::new(lang, cfg) {
+        Ok(s) => s,
+        Err(e) => {
+            let not_found = e
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound);
+            let reason = if not_found {
+                format!(
+                    "binary not found: {}",
+                    server.as_deref().unwrap_or_default()
+                )
+            } else {
+                e.to_string()
+            };
+            return fail(server, reason);
+        }
+    };
+    session.probe_update();
+    let lang_files: Vec<String> = files
+        .iter()
+        .filter(|p| path_matches_mode(p, lang))
+        .cloned()
+        .collect();
+    session.probe_files(&lang_files);
+    let caps = session.capabilities.clone();
+    session.shutdown();
+
+    let reason = if decide_changed_strategy(&caps) == ChangedStrategy::TsFallback {
+        Some("missing capability: documentSymbol or workspace/symbol".to_string())
+    } else if decide_impact_strategy(&caps) == ImpactStrategy::TsFallback {
+        Some("missing capability: callHierarchy, references or definition".to_string())
+    } else {
+        None
+    };
+    EngineCheck {
+        language,
+        server,
+        ok: reason.is_none(),
+        reason,
+        capabilities: Some(caps),
+    }
+}
+
 // ---- LSP graph builder (TS相当) ----
 
 fn collect_symbols_all(
@@ -2830,43 +3577,44 @@ fn collect_symbols_all(
     }
 }
 
+/// Documents whose `documentSymbol` requests are pipelined (and kept open) at once.
+const DOC_SYMBOL_BATCH: usize = 32;
+
 fn lsp_build_project_graph(
     sess: &mut LspSession,
+    walk: &crate::graph_source::WalkOptions,
 ) -> anyhow::Result<(
     crate::ir::reference::SymbolIndex,
     Vec<crate::ir::reference::Reference>,
 )> {
-    use walkdir::WalkDir;
     let mut all_symbols: Vec<crate::ir::Symbol> = Vec::new();
+    // documentSymbol results per URI, reused when mapping reference sites to callers
+    let mut doc_items_cache: std::collections::HashMap<String, Vec<serde_json::Value>> =
+        std::collections::HashMap::new();
     // 1) Collect function/method symbols
-    for entry in WalkDir::new(".")
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name.starts_with('.'))
-        })
-        .filter_map(Result::ok)
-    {
-        let path = entry.path();
-        if path.is_file() {
-            let path_str = if let Ok(rel) = path.strip_prefix("./") {
-                rel.to_string_lossy().to_string()
-            } else {
-                path.to_string_lossy().to_string()
-            };
-            let Some(profile) = profile_for_path(&path_str) else {
-                continue;
-            };
-            let abspath = std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
-            let uri = path_to_uri(&abspath);
-            // didOpen
-            let text = std::fs::read_to_string(&abspath).unwrap_or_default();
-            let _ = sess.ensure_did_open(&uri, profile.lsp_language_id, &text);
-            // documentSymbol
-            if let Ok(items) = sess.req_document_symbol(&uri) {
-                collect_symbols_all(&path_str, profile.symbol_lang, &items, &mut all_symbols);
-            }
+    let skip = |name: &str| name == ".git" || name == "target" || name.starts_with('.');
+    let files: Vec<(String, LangProfile, String)> =
+        crate::graph_source::walk_files(&sess.root, skip, walk)
+            .into_iter()
+            .filter(|path| !walk.is_generated_file(path))
+            .filter_map(|path| {
+                let path_str = normalize_path(&path.to_string_lossy(), &sess.root);
+                let profile = profile_for_path(&path_str)?;
+                let abspath = std::fs::canonicalize(&path).unwrap_or(path);
+                Some((path_str, profile, path_to_uri(&abspath)))
+            })
+            .collect();
+    for batch in files.chunks(DOC_SYMBOL_BATCH) {
+        for (path_str, profile, uri) in batch {
+            let text = std::fs::read_to_string(sess.root.join(path_str)).unwrap_or_default();
+            let _ = sess.ensure_did_open(uri, profile.lsp_language_id, &text);
+        }
+        let uris: Vec<String> = batch.iter().map(|(_, _, uri)| uri.clone()).collect();
+        let results = sess.req_document_symbols(&uris);
+        for ((path_str, profile, uri), items) in batch.iter().zip(results) {
+            collect_symbols_all(path_str, profile.symbol_lang, &items, &mut all_symbols);
+            doc_items_cache.insert(uri.clone(), items);
+            let _ = sess.close_document(uri);
         }
     }
     all_symbols.sort_by(|a, b| a.id.0.cmp(&b.id.0));
@@ -2875,23 +3623,20 @@ fn lsp_build_project_graph(
     // 2) Build edges via references at callee definitions
     let mut edges: Vec<crate::ir::reference::Reference> = Vec::new();
     for to_sym in &all_symbols {
-        let abspath = std::fs::canonicalize(&to_sym.file)
-            .unwrap_or_else(|_| std::path::PathBuf::from(&to_sym.file));
+        let abspath = sess.abs_path(&to_sym.file);
         let uri = path_to_uri(&abspath);
-        let (line0, ch0) = guess_callable_position(&to_sym.file, to_sym)
+        let (line0, ch0) = guess_callable_position(&sess.root, &to_sym.file, to_sym)
             .unwrap_or((to_sym.range.start_line.saturating_sub(1), 0));
         let refs = sess.req_references(&uri, line0, ch0).unwrap_or_default();
         for loc in refs {
-            let loc_uri = loc.get("uri").and_then(|v| v.as_str()).unwrap_or("");
-            let file = uri_to_path(loc_uri);
-            let line0 = loc
-                .get("range")
-                .and_then(|r| r.get("start"))
-                .and_then(|s| s.get("line"))
-                .and_then(|n| n.as_u64())
-                .unwrap_or(0) as u32;
-            let items = sess.req_document_symbol(loc_uri).unwrap_or_default();
-            if let Some(caller) = enclosing_symbol_in_doc(&items, &file, line0)
+            let Some((loc_uri, line0)) = location_to_file_line(&loc) else {
+                continue;
+            };
+            let file = uri_to_path(&loc_uri, &sess.root);
+            let items = doc_items_cache
+                .entry(loc_uri.clone())
+                .or_insert_with(|| sess.req_document_symbol(&loc_uri).unwrap_or_default());
+            if let Some(caller) = enclosing_symbol_in_doc(items, &file, line0)
                 && caller.id.0 != to_sym.id.0
                 && matches!(
                     caller.kind,
@@ -2903,7 +3648,7 @@ fn lsp_build_project_graph(
                     to: to_sym.id.clone(),
                     kind: crate::ir::reference::RefKind::Call,
                     file: caller.file.clone(),
-                    line: caller.range.start_line,
+                    line: line0 + 1,
                     certainty: crate::ir::reference::EdgeCertainty::Confirmed,
                     provenance: crate::ir::reference::EdgeProvenance::CallGraph,
                 });
@@ -2975,20 +3720,201 @@ mod tests {
             dump_capabilities: false,
             mock: true,
             mock_caps: None,
+            retry: RetryPolicy::default(),
+            root: None,
         };
         let sess = LspSession::new(crate::mapping::LanguageMode::Rust, cfg).expect("mock ok");
         assert!(sess.capabilities.document_symbol);
         assert!(sess.capabilities.call_hierarchy);
     }
 
+    #[test]
+    fn retry_policy_resends_error_responses_until_success() {
+        let policy = RetryPolicy {
+            retries: 2,
+            backoff_ms: 1,
+        };
+        // the server errors while indexing, then answers
+        let mut calls = 0;
+        let out = policy.run("textDocument/prepareCallHierarchy", || {
+            calls += 1;
+            if calls == 1 {
+                Err(LspErrorResponse.into())
+            } else {
+                Ok(json!([{"name": "f"}]))
+            }
+        });
+        assert_eq!(out.unwrap(), json!([{"name": "f"}]));
+        assert_eq!(calls, 2);
+
+        // spent retries give the last error; other failures are not retried
+        let mut calls = 0;
+        let err = policy
+            .run("textDocument/documentSymbol", || -> anyhow::Result<()> {
+                calls += 1;
+                Err(LspErrorResponse.into())
+            })
+            .unwrap_err();
+        assert!(err.is::<LspErrorResponse>());
+        assert_eq!(calls, 3);
+        let mut calls = 0;
+        policy
+            .run("textDocument/documentSymbol", || -> anyhow::Result<()> {
+                calls += 1;
+                anyhow::bail!("lsp request timeout")
+            })
+            .unwrap_err();
+        assert_eq!(calls, 1);
+    }
+
+    /// A stand-in server that answers the first `documentSymbol` and
+    /// `prepareCallHierarchy` request for each document with an error, as a
+    /// server still indexing does, and every later one with a symbol.
+    #[cfg(unix)]
+    const FLAKY_SERVER: &str = r#"
+cr=$(printf '\r')
+seen=
+while IFS= read -r header; do
+    len=${header#Content-Length: }
+    len=${len%"$cr"}
+    IFS= read -r _
+    body=$(head -c "$len")
+    case $body in *'"id":'*) ;; *) continue ;; esac
+    id=$(printf '%s' "$body" | sed 's/.*"id":\([0-9]*\).*/\1/')
+    key=$(printf '%s' "$body" | sed 's/.*"method":"\([^"]*\)".*"uri":"\([^"]*\)".*/\1|\2/')
+    case $key in
+    textDocument/*)
+        case $seen in
+        *"<$key>"*) msg='{"jsonrpc":"2.0","id":'$id',"result":[{"name":"f"}]}' ;;
+        *)
+            seen="$seen<$key>"
+            msg='{"jsonrpc":"2.0","id":'$id',"error":{"code":-32801,"message":"content modified"}}'
+            ;;
+        esac
+        ;;
+    *) msg='{"jsonrpc":"2.0","id":'$id',"result":null}' ;;
+    esac
+    printf 'Content-Length: %d\r\n\r\n%s' "${#msg}" "$msg"
+done
+"#;
+
+    #[cfg(unix)]
+    #[test]
+    fn session_retries_requests_the_server_first_answers_with_errors() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", FLAKY_SERVER])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .expect("spawn sh");
+        let mut sess = LspSession {
+            root: std::path::PathBuf::from("."),
+            _cfg: LspConfig {
+                retry: RetryPolicy {
+                    retries: 1,
+                    backoff_ms: 1,
+                },
+                ..Default::default()
+            },
+            capabilities: CapabilityMatrix::default(),
+            stdin: child.stdin.take(),
+            stdout: child.stdout.take(),
+            child: Some(child),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+            doc_symbol_cache: std::collections::HashMap::new(),
+            prepare_call_hierarchy_cache: std::collections::HashMap::new(),
+            definition_cache: std::collections::HashMap::new(),
+            references_cache: std::collections::HashMap::new(),
+            incoming_calls_cache: std::collections::HashMap::new(),
+            outgoing_calls_cache: std::collections::HashMap::new(),
+            opened_docs: std::collections::HashSet::new(),
+        };
+        let symbol = vec![json!({"name": "f"})];
+
+        assert_eq!(
+            sess.req_prepare_call_hierarchy("file:///a.rs", 0, 3)
+                .unwrap(),
+            symbol
+        );
+        assert_eq!(sess.req_document_symbol("file:///a.rs").unwrap(), symbol);
+        // a.rs is answered at once; b.rs errors and is asked for again
+        let uris = ["file:///a.rs".to_string(), "file:///b.rs".to_string()];
+        sess.doc_symbol_cache.clear();
+        assert_eq!(
+            sess.req_document_symbols(&uris),
+            vec![symbol.clone(), symbol.clone()]
+        );
+        // 2 prepareCallHierarchy + 2 documentSymbol + 2 pipelined + 1 retry
+        assert_eq!(sess.next_id.load(std::sync::atomic::Ordering::SeqCst), 8);
+    }
+
+    #[test]
+    fn pipelined_responses_land_in_request_order() {
+        let mut acc = Vec::new();
+        for msg in [
+            json!({"jsonrpc":"2.0","method":"window/logMessage","params":{}}),
+            json!({"jsonrpc":"2.0","id":12,"result":["b"]}),
+            json!({"jsonrpc":"2.0","id":11,"error":{"code":-32603,"message":"boom"}}),
+            json!({"jsonrpc":"2.0","id":10,"result":["a"]}),
+        ] {
+            acc.extend(encode_jsonrpc_message(&msg));
+        }
+        // The next response is still in flight
+        let partial = encode_jsonrpc_message(&json!({"jsonrpc":"2.0","id":13,"result":[]}));
+        acc.extend_from_slice(&partial[..partial.len() - 2]);
+        let mut slots: std::collections::HashMap<u64, usize> =
+            [(10, 0), (11, 1), (12, 2), (13, 3)].into_iter().collect();
+        let mut out = vec![None; 4];
+        take_responses(&mut acc, &mut slots, &mut out);
+        assert_eq!(
+            out,
+            vec![
+                Some(Ok(json!(["a"]))),
+                Some(Err(LspErrorResponse)),
+                Some(Ok(json!(["b"]))),
+                None
+            ]
+        );
+        assert_eq!(slots.keys().copied().collect::<Vec<_>>(), vec![13]);
+        acc.extend_from_slice(&partial[partial.len() - 2..]);
+        take_responses(&mut acc, &mut slots, &mut out);
+        assert_eq!(out[3], Some(Ok(json!([]))));
+        assert!(slots.is_empty() && acc.is_empty());
+    }
+
+    #[test]
+    fn closed_documents_leave_the_open_set() {
+        let cfg = LspConfig {
+            mock: true,
+            ..Default::default()
+        };
+        let mut sess = LspSession::new(crate::mapping::LanguageMode::Rust, cfg).expect("mock ok");
+        sess.ensure_did_open("file:///a.rs", "rust", "fn a() {}")
+            .unwrap();
+        sess.ensure_did_open("file:///b.rs", "rust", "fn b() {}")
+            .unwrap();
+        sess.close_document("file:///a.rs").unwrap();
+        assert_eq!(sess.opened_docs.len(), 1);
+        assert!(sess.opened_docs.contains("file:///b.rs"));
+        // Closing again is a no-op; reopening works after a close
+        sess.close_document("file:///a.rs").unwrap();
+        sess.ensure_did_open("file:///a.rs", "rust", "fn a() {}")
+            .unwrap();
+        sess.close_all_documents();
+        assert!(sess.opened_docs.is_empty());
+    }
+
     #[test]
     fn probe_update_non_mock_without_io_disables_caps() {
         let mut sess = LspSession {
+            root: std::path::PathBuf::from("."),
             _cfg: LspConfig {
                 strict: true,
                 dump_capabilities: false,
                 mock: false,
                 mock_caps: None,
+                retry: RetryPolicy::default(),
+                root: None,
             },
             capabilities: CapabilityMatrix {
                 call_hierarchy: true,
@@ -3031,6 +3957,8 @@ mod tests {
                 document_symbol: true,
                 workspace_symbol: false,
             }),
+            retry: RetryPolicy::default(),
+            root: None,
         };
         let mut sess = LspSession::new(crate::mapping::LanguageMode::Rust, cfg).expect("mock ok");
         sess.probe_update();
@@ -3049,6 +3977,9 @@ mod tests {
             dump_capabilities: false,
             mock_lsp: true,
             mock_caps: None,
+            analysis: Default::default(),
+            mapping: Default::default(),
+            lsp_retry: Default::default(),
         };
         let e = LspEngine::new_with_auto_policy(
             cfg,
@@ -3344,7 +4275,8 @@ mod tests {
                 }
             }
         });
-        let sym = item_to_symbol(&incoming).expect("incoming wrapper should parse");
+        let sym = item_to_symbol(&incoming, std::path::Path::new("/work"))
+            .expect("incoming wrapper should parse");
         assert_eq!(sym.name, "foo");
         assert_eq!(sym.file, "/tmp/src/main.rs");
         assert_eq!(sym.range.start_line, 10);
@@ -3360,7 +4292,8 @@ mod tests {
                 }
             }
         });
-        let sym2 = item_to_symbol(&outgoing).expect("outgoing wrapper should parse");
+        let sym2 = item_to_symbol(&outgoing, std::path::Path::new("/work"))
+            .expect("outgoing wrapper should parse");
         assert_eq!(sym2.name, "bar");
         assert_eq!(sym2.file, "/tmp/src/lib.rs");
         assert_eq!(sym2.range.start_line, 5);
@@ -3398,4 +4331,296 @@ mod tests {
             .expect("should pick enclosing symbol");
         assert_eq!(sym.name, "main");
     }
+
+    #[test]
+    fn location_to_file_line_reads_location() {
+        let loc = json!({
+            "uri": "file:///tmp/src/lib.rs",
+            "range": {
+                "start": {"line": 4, "character": 7},
+                "end": {"line": 4, "character": 10}
+            }
+        });
+        assert_eq!(
+            location_to_file_line(&loc),
+            Some(("file:///tmp/src/lib.rs".to_string(), 4))
+        );
+        assert_eq!(
+            location_start(&loc),
+            Some(("file:///tmp/src/lib.rs".to_string(), 4, 7))
+        );
+        assert_eq!(
+            location_to_file_line(&json!({"uri": "", "range": loc["range"]})),
+            None
+        );
+    }
+
+    #[test]
+    fn location_to_file_line_reads_location_link() {
+        let link = json!({
+            "originSelectionRange": {
+                "start": {"line": 0, "character": 0},
+                "end": {"line": 0, "character": 3}
+            },
+            "targetUri": "file:///tmp/src/main.rs",
+            "targetRange": {
+                "start": {"line": 9, "character": 0},
+                "end": {"line": 12, "character": 1}
+            },
+            "targetSelectionRange": {
+                "start": {"line": 9, "character": 3},
+                "end": {"line": 9, "character": 6}
+            }
+        });
+        assert_eq!(
+            location_start(&link),
+            Some(("file:///tmp/src/main.rs".to_string(), 9, 3))
+        );
+        let mut bare = link.clone();
+        bare.as_object_mut().unwrap().remove("targetSelectionRange");
+        assert_eq!(
+            location_to_file_line(&bare),
+            Some(("file:///tmp/src/main.rs".to_string(), 9))
+        );
+    }
+
+    fn call_names(line: &str) -> Vec<(&str, usize)> {
+        scan_call_sites(line, CALL_KEYWORDS)
+            .into_iter()
+            .map(|c| (c.name, c.offset))
+            .collect()
+    }
+
+    #[test]
+    fn call_hierarchy_edges_sit_on_the_call_site_line() {
+        let caller = serde_json::json!({
+            "name": "caller",
+            "kind": 12,
+            "uri": "file:///w/src/lib.rs",
+            "range": {"start": {"line": 9, "character": 0}, "end": {"line": 14, "character": 1}},
+        });
+        let incoming = serde_json::json!({
+            "from": caller,
+            "fromRanges": [
+                {"start": {"line": 12, "character": 4}, "end": {"line": 12, "character": 10}},
+                {"start": {"line": 13, "character": 4}, "end": {"line": 13, "character": 10}},
+            ],
+        });
+        assert_eq!(call_line(&incoming), Some(13));
+        assert_eq!(call_line(&serde_json::json!({"from": caller})), None);
+
+        let callee = fn_sym("src/lib.rs", "callee", 1);
+        let mut q = std::collections::VecDeque::new();
+        let mut edges = Vec::new();
+        let mut seen_keys = std::collections::HashSet::new();
+        let mut node_map = std::collections::HashMap::new();
+        let mut summary_depth_by_symbol_id = std::collections::HashMap::new();
+        let mut env = EnqueueEnv {
+            q: &mut q,
+            edges: &mut edges,
+            seen_keys: &mut seen_keys,
+            node_map: &mut node_map,
+            summary_depth_by_symbol_id: &mut summary_depth_by_symbol_id,
+            root: std::path::Path::new("."),
+        };
+        enqueue_edge(&mut env, &caller, &callee, 1, true, call_line(&incoming));
+        enqueue_edge(&mut env, &caller, &callee, 1, true, None);
+        let lines: Vec<u32> = edges.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![13, 10]);
+    }
+
+    #[test]
+    fn scan_call_sites_skips_turbofish() {
+        assert_eq!(call_names("    foo::<Bar>(x);"), vec![("foo", 4)]);
+        assert_eq!(
+            call_names("let v = obj.method::<T>(1);"),
+            vec![("method", 12)]
+        );
+        assert_eq!(
+            call_names("parse::<Vec<Option<u8>>>(s)"),
+            vec![("parse", 0)]
+        );
+        assert_eq!(
+            call_names("Vec::<u8>::with_capacity(4)"),
+            vec![("with_capacity", 11)]
+        );
+        assert_eq!(call_names("apply::<fn(u8) -> u8>(f)"), vec![("apply", 0)]);
+    }
+
+    #[test]
+    fn scan_call_sites_finds_each_link_of_a_chain() {
+        assert_eq!(
+            call_names("a.b().c::<T>(x).d (y)"),
+            vec![("b", 2), ("c", 6), ("d", 16)]
+        );
+        assert_eq!(
+            call_names("if ok(x) { return crate::m::run(); }"),
+            vec![("ok", 3), ("run", 28)]
+        );
+        assert!(call_names("let x = a.b;").is_empty());
+    }
+
+    fn fn_sym(file: &str, name: &str, line: u32) -> crate::ir::Symbol {
+        let kind = crate::ir::SymbolKind::Function;
+        crate::ir::Symbol {
+            id: crate::ir::SymbolId::new("rust", file, &kind, name, line),
+            name: name.to_string(),
+            kind,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: line,
+                end_line: line,
+            },
+            language: "rust".to_string(),
+        }
+    }
+
+    fn call(from: &crate::ir::Symbol, to: &crate::ir::Symbol) -> crate::ir::reference::Reference {
+        crate::ir::reference::Reference {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            kind: crate::ir::reference::RefKind::Call,
+            file: from.file.clone(),
+            line: from.range.start_line,
+            certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+            provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+        }
+    }
+
+    #[test]
+    fn hybrid_merge_unions_symbols_and_recomputes_depths() {
+        let target = fn_sym("src/a.rs", "target", 1);
+        let near = fn_sym("src/b.rs", "near", 1);
+        let far = fn_sym("src/c.rs", "far", 1);
+        let top = fn_sym("src/c.rs", "top", 5);
+        let output = |impacted: Vec<crate::ir::Symbol>, edges| {
+            let depths = std::collections::HashMap::new();
+            crate::impact::finalize_impact_output(
+                vec![target.clone()],
+                impacted,
+                edges,
+                &depths,
+                std::collections::HashMap::new(),
+            )
+        };
+        // LSP only saw target <- near; TS saw target <- far <- top and the
+        // same target <- near edge.
+        let lsp = output(vec![near.clone()], vec![call(&near, &target)]);
+        let ts = output(
+            vec![near.clone(), far.clone(), top.clone()],
+            vec![call(&near, &target), call(&far, &target), call(&top, &far)],
+        );
+        let opts = ImpactOptions {
+            direction: crate::impact::ImpactDirection::Callers,
+            with_edges: Some(true),
+            ..ImpactOptions::default()
+        };
+        let out = merge_hybrid_impact(lsp, ts, &opts);
+
+        assert_eq!(out.changed_symbols.len(), 1);
+        let names: Vec<&str> = out
+            .impacted_symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["near", "far", "top"]);
+        assert_eq!(out.edges.len(), 3);
+        assert!(out.edges_included);
+        let buckets: Vec<(usize, usize)> = out
+            .summary
+            .by_depth
+            .iter()
+            .map(|b| (b.depth, b.symbol_count))
+            .collect();
+        assert_eq!(buckets, vec![(1, 2), (2, 1)]);
+
+        let opts = ImpactOptions {
+            with_edges: Some(false),
+            ..opts
+        };
+        let lsp = output(vec![near.clone()], vec![call(&near, &target)]);
+        let ts = output(vec![far.clone()], vec![call(&far, &target)]);
+        let out = merge_hybrid_impact(lsp, ts, &opts);
+        assert!(out.edges.is_empty());
+        assert!(!out.edges_included);
+        assert_eq!(out.impacted_symbols.len(), 2);
+    }
+
+    #[test]
+    fn hybrid_merge_in_both_directions_keeps_one_edge_per_call_site() {
+        let target = fn_sym("src/a.rs", "target", 1);
+        let caller = fn_sym("src/b.rs", "caller", 1);
+        let output = |edges| {
+            crate::impact::finalize_impact_output(
+                vec![target.clone()],
+                vec![caller.clone()],
+                edges,
+                &std::collections::HashMap::new(),
+                std::collections::HashMap::new(),
+            )
+        };
+        // LSP saw the call; TS recorded the same site as a type use
+        let mut type_use = call(&caller, &target);
+        type_use.kind = crate::ir::reference::RefKind::TypeUse;
+        let opts = ImpactOptions {
+            direction: crate::impact::ImpactDirection::Both,
+            with_edges: Some(true),
+            ..ImpactOptions::default()
+        };
+        let out = merge_hybrid_impact(
+            output(vec![call(&caller, &target)]),
+            output(vec![type_use]),
+            &opts,
+        );
+        assert_eq!(out.edges.len(), 1);
+        assert_eq!(out.edges[0].kind, crate::ir::reference::RefKind::Call);
+    }
+
+    #[test]
+    fn impact_strategy_follows_direction_capabilities() {
+        use crate::impact::ImpactDirection;
+        let refs_only = CapabilityMatrix {
+            references: true,
+            document_symbol: true,
+            ..Default::default()
+        };
+        let s = impact_strategy_for(&refs_only, ImpactDirection::Callees);
+        assert_eq!(s, ImpactStrategy::TsFallback);
+        assert_eq!(s.as_str(), "ts_fallback");
+        let s = impact_strategy_for(&refs_only, ImpactDirection::Callers);
+        assert_eq!(s.as_str(), "references");
+        assert_eq!(s.reason(), "call_hierarchy-missing");
+        assert_eq!(capability_list(&refs_only), "references,document_symbol");
+        assert_eq!(capability_list(&CapabilityMatrix::default()), "none");
+    }
+
+    #[test]
+    fn callee_reference_impact_requires_definition() {
+        use crate::impact::ImpactDirection;
+        let refs_only = CapabilityMatrix {
+            references: true,
+            ..Default::default()
+        };
+        let defs_only = CapabilityMatrix {
+            definition: true,
+            ..Default::default()
+        };
+        assert!(!supports_reference_impact(
+            &refs_only,
+            ImpactDirection::Callees
+        ));
+        assert!(supports_reference_impact(
+            &refs_only,
+            ImpactDirection::Callers
+        ));
+        assert!(supports_reference_impact(
+            &defs_only,
+            ImpactDirection::Callees
+        ));
+        assert!(supports_reference_impact(&defs_only, ImpactDirection::Both));
+        assert_eq!(
+            required_impact_capability_hint(ImpactDirection::Callees),
+            "call_hierarchy or definition"
+        );
+    }
 }
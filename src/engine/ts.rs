@@ -1,11 +1,28 @@
 use crate::cache;
 use crate::{
-    ChangedOutput, FileChanges, ImpactOptions, ImpactOutput, LanguageMode, compute_changed_symbols,
-    compute_impact,
+    ChangedOutput, FileChanges, ImpactOptions, ImpactOutput, LanguageMode, MappingOptions,
+    check_graph_limits, compute_changed_symbols_with_options, compute_impact,
 };
 
-#[derive(Default)]
-pub struct TsEngine;
+pub struct TsEngine {
+    analysis: crate::impact::AnalyzeOptions,
+    mapping: MappingOptions,
+}
+
+impl TsEngine {
+    pub fn new(cfg: super::EngineConfig) -> Self {
+        Self {
+            analysis: cfg.analysis,
+            mapping: cfg.mapping,
+        }
+    }
+}
+
+impl Default for TsEngine {
+    fn default() -> Self {
+        Self::new(super::EngineConfig::default())
+    }
+}
 
 impl super::AnalysisEngine for TsEngine {
     fn changed_symbols(
@@ -13,7 +30,7 @@ impl super::AnalysisEngine for TsEngine {
         diffs: &[FileChanges],
         lang: LanguageMode,
     ) -> anyhow::Result<ChangedOutput> {
-        compute_changed_symbols(diffs, lang)
+        compute_changed_symbols_with_options(diffs, lang, &self.mapping)
     }
 
     fn impact(
@@ -22,23 +39,29 @@ impl super::AnalysisEngine for TsEngine {
         lang: LanguageMode,
         opts: &ImpactOptions,
     ) -> anyhow::Result<ImpactOutput> {
-        let changed: ChangedOutput = compute_changed_symbols(diffs, lang)?;
+        let changed: ChangedOutput =
+            compute_changed_symbols_with_options(diffs, lang, &self.mapping)?;
         // Open local cache and ensure built; then update changed files incrementally
         let (scope, dir_override) = cache::scope_from_env();
-        let mut db = cache::open(scope, dir_override.as_deref())?;
+        let mut db = cache::open(
+            scope,
+            dir_override.as_deref(),
+            self.analysis.root.as_deref(),
+        )?;
         let st = cache::stats(&db.conn)?;
         if st.symbols == 0 {
             log::info!("cache: empty → build all");
-            cache::build_all(&mut db.conn)?;
+            cache::build_all(&mut db.conn, &self.analysis)?;
         }
         if !changed.changed_files.is_empty() {
             log::info!(
                 "cache: updating {} changed file(s)",
                 changed.changed_files.len()
             );
-            cache::update_paths(&mut db.conn, &changed.changed_files)?;
+            cache::update_paths(&mut db.conn, &changed.changed_files, &self.analysis)?;
         }
         let (index, refs) = cache::load_graph(&db.conn)?;
+        check_graph_limits(&index, &refs, &opts.limits)?;
         let out = compute_impact(&changed.changed_symbols, &index, &refs, opts);
         Ok(out)
     }
@@ -50,13 +73,18 @@ impl super::AnalysisEngine for TsEngine {
         opts: &ImpactOptions,
     ) -> anyhow::Result<ImpactOutput> {
         let (scope, dir_override) = cache::scope_from_env();
-        let mut db = cache::open(scope, dir_override.as_deref())?;
+        let mut db = cache::open(
+            scope,
+            dir_override.as_deref(),
+            self.analysis.root.as_deref(),
+        )?;
         let st = cache::stats(&db.conn)?;
         if st.symbols == 0 {
             log::info!("cache: empty → build all");
-            cache::build_all(&mut db.conn)?;
+            cache::build_all(&mut db.conn, &self.analysis)?;
         }
         let (index, refs) = cache::load_graph(&db.conn)?;
+        check_graph_limits(&index, &refs, &opts.limits)?;
         let out = compute_impact(changed, &index, &refs, opts);
         Ok(out)
     }
@@ -0,0 +1,405 @@
+//! Where the project graph reads source files from.
+//!
+//! The default is the working tree under the analysis root. A git tree-ish
+//! (commit, branch, merge base, ...) can be used instead so that impact is
+//! computed against the pre-change codebase; blobs are then read with
+//! `git cat-file --batch` and never touch the filesystem.
+
+use crate::languages::{AnalyzerConfig, LanguageKind};
+use anyhow::Context;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+/// Marker that flags a file as generated unless [`WalkOptions`] says
+/// otherwise: `@generated`, or Go's `Code generated ... DO NOT EDIT.`
+pub const DEFAULT_GENERATED_MARKER: &str = r"@generated|Code generated .* DO NOT EDIT";
+
+/// How many leading lines of a file are checked for the generated marker.
+pub const GENERATED_MARKER_LINES: usize = 5;
+
+/// Which files workspace scans visit.
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// Descend into symlinked directories. Off by default: symlinked
+    /// directories are skipped and symlinked files are only read when they
+    /// point inside the scanned root. When on, each real directory and file is
+    /// visited once, so symlink cycles terminate.
+    pub follow_symlinks: bool,
+    /// Skip files whose first [`GENERATED_MARKER_LINES`] lines match this,
+    /// keeping codegen output out of the symbol index; `None` scans generated
+    /// files like any other. Defaults to [`DEFAULT_GENERATED_MARKER`].
+    pub generated_marker: Option<regex::Regex>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: false,
+            generated_marker: Some(regex::Regex::new(DEFAULT_GENERATED_MARKER).unwrap()),
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Whether `source` carries the generated marker in its leading lines.
+    pub(crate) fn is_generated_source(&self, source: &str) -> bool {
+        self.generated_marker.as_ref().is_some_and(|re| {
+            source
+                .lines()
+                .take(GENERATED_MARKER_LINES)
+                .any(|l| re.is_match(l))
+        })
+    }
+
+    /// Like [`WalkOptions::is_generated_source`], reading only the leading
+    /// lines of `path`.
+    pub(crate) fn is_generated_file(&self, path: &Path) -> bool {
+        let Some(re) = &self.generated_marker else {
+            return false;
+        };
+        let Ok(file) = std::fs::File::open(path) else {
+            return false;
+        };
+        BufReader::new(file)
+            .lines()
+            .take(GENERATED_MARKER_LINES)
+            .map_while(Result::ok)
+            .any(|l| re.is_match(&l))
+    }
+}
+
+/// Regular files under `root`, skipping entries (below the root) whose name
+/// matches `skip_name`. Paths keep the `root` prefix; symlinks are
+/// handled per [`WalkOptions::follow_symlinks`].
+pub(crate) fn walk_files(
+    root: &Path,
+    skip_name: impl Fn(&str) -> bool,
+    walk: &WalkOptions,
+) -> Vec<PathBuf> {
+    let follow = walk.follow_symlinks;
+    let real_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut seen_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut seen_files: HashSet<PathBuf> = HashSet::new();
+    let mut out = Vec::new();
+    let walker = WalkDir::new(root).follow_links(follow).sort_by_file_name();
+    for entry in walker
+        .into_iter()
+        .filter_entry(|e| {
+            if e.depth() > 0 && skip_name(e.file_name().to_str().unwrap_or("")) {
+                return false;
+            }
+            // Following links, a directory reached a second time (through a
+            // symlink or a cycle) is not descended into again.
+            if follow && e.file_type().is_dir() {
+                let real = e
+                    .path()
+                    .canonicalize()
+                    .unwrap_or_else(|_| e.path().to_path_buf());
+                return seen_dirs.insert(real);
+            }
+            true
+        })
+        // Loops walkdir detects itself surface as errors and are dropped here
+        .filter_map(Result::ok)
+    {
+        let path = entry.path();
+        if entry.path_is_symlink() {
+            let Ok(real) = path.canonicalize() else {
+                continue;
+            };
+            if !real.is_file() || (!follow && !real.starts_with(&real_root)) {
+                continue;
+            }
+            if follow && !seen_files.insert(real) {
+                continue;
+            }
+        } else if !entry.file_type().is_file() {
+            continue;
+        } else if follow {
+            let real = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            if !seen_files.insert(real) {
+                continue;
+            }
+        }
+        out.push(path.to_path_buf());
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GraphSource {
+    /// Files under the analysis root.
+    #[default]
+    WorkingTree,
+    /// Blobs of a git tree-ish in the repository containing the analysis root.
+    GitTree(String),
+}
+
+fn is_skipped_dir_name(name: &str) -> bool {
+    name == ".git" || name == "target" || name.starts_with('.')
+}
+
+impl GraphSource {
+    /// List analyzable files (relative to `root`) with their language, as
+    /// `analyzers` detects it; working-tree scans walk per `walk`.
+    pub(crate) fn list_files(
+        &self,
+        root: &Path,
+        analyzers: &AnalyzerConfig,
+        walk: &WalkOptions,
+    ) -> anyhow::Result<Vec<(String, LanguageKind)>> {
+        match self {
+            GraphSource::WorkingTree => Ok(list_working_tree_files(root, analyzers, walk)),
+            GraphSource::GitTree(treeish) => list_git_tree_files(root, treeish, analyzers),
+        }
+    }
+
+    /// Read the contents of `root`-relative `paths` in order; `None` marks
+    /// unreadable files.
+    pub(crate) fn read_files(
+        &self,
+        root: &Path,
+        paths: &[String],
+    ) -> anyhow::Result<Vec<Option<String>>> {
+        match self {
+            GraphSource::WorkingTree => {
+                use rayon::prelude::*;
+                Ok(paths
+                    .par_iter()
+                    .map(|p| std::fs::read_to_string(root.join(p)).ok())
+                    .collect())
+            }
+            GraphSource::GitTree(treeish) => read_git_blobs(root, treeish, paths),
+        }
+    }
+}
+
+fn list_working_tree_files(
+    root: &Path,
+    analyzers: &AnalyzerConfig,
+    walk: &WalkOptions,
+) -> Vec<(String, LanguageKind)> {
+    let mut out = Vec::new();
+    for path in walk_files(root, is_skipped_dir_name, walk) {
+        let path_str = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        if let Some(kind) = analyzers.language_for_path(&path_str)
+            && !walk.is_generated_file(&path)
+        {
+            out.push((path_str, kind));
+        }
+    }
+    out
+}
+
+fn list_git_tree_files(
+    root: &Path,
+    treeish: &str,
+    analyzers: &AnalyzerConfig,
+) -> anyhow::Result<Vec<(String, LanguageKind)>> {
+    // Without --full-tree, ls-tree lists paths relative to (and below) its cwd,
+    // matching the working-tree walk.
+    let output = Command::new("git")
+        .args(["ls-tree", "-r", "-z", "--name-only", treeish])
+        .current_dir(root)
+        .output()
+        .with_context(|| format!("run git ls-tree {treeish}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git ls-tree {} failed: {}",
+            treeish,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mut out = Vec::new();
+    for raw in output.stdout.split(|b| *b == 0) {
+        if raw.is_empty() {
+            continue;
+        }
+        let path = String::from_utf8_lossy(raw).to_string();
+        let mut dirs: Vec<&str> = path.split('/').collect();
+        dirs.pop();
+        if dirs.iter().any(|d| is_skipped_dir_name(d)) {
+            continue;
+        }
+        if let Some(kind) = analyzers.language_for_path(&path) {
+            out.push((path, kind));
+        }
+    }
+    Ok(out)
+}
+
+fn read_git_blobs(
+    root: &Path,
+    treeish: &str,
+    paths: &[String],
+) -> anyhow::Result<Vec<Option<String>>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut child = Command::new("git")
+        .args(["cat-file", "--batch"])
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn git cat-file --batch")?;
+    // Feed requests from a separate thread so a large response cannot block
+    // the writer on a full stdout pipe.
+    let mut stdin = child.stdin.take().context("git cat-file stdin")?;
+    let requests: String = paths.iter().map(|p| format!("{treeish}:./{p}\n")).collect();
+    let writer = std::thread::spawn(move || stdin.write_all(requests.as_bytes()));
+
+    let mut reader = BufReader::new(child.stdout.take().context("git cat-file stdout")?);
+    let mut out = Vec::with_capacity(paths.len());
+    for _ in paths {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            anyhow::bail!("git cat-file --batch ended early");
+        }
+        // "<sha> <type> <size>" or "<object> missing"
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let size = match fields.as_slice() {
+            [_, _, size] => size.parse::<usize>().ok(),
+            _ => None,
+        };
+        let Some(size) = size else {
+            out.push(None);
+            continue;
+        };
+        let mut body = vec![0u8; size + 1]; // contents + trailing LF
+        reader.read_exact(&mut body)?;
+        body.pop();
+        out.push(if fields[1] == "blob" {
+            String::from_utf8(body).ok()
+        } else {
+            None
+        });
+    }
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("git cat-file writer panicked"))??;
+    child.wait()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn git(cwd: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn default_generated_marker_checks_only_the_leading_lines() {
+        let walk = WalkOptions::default();
+        assert!(walk.is_generated_source("// @generated\nfn a() {}\n"));
+        assert!(
+            walk.is_generated_source(
+                "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n"
+            )
+        );
+        assert!(!walk.is_generated_source("fn a() {}\n"));
+        let late = format!("{}// @generated\n", "\n".repeat(GENERATED_MARKER_LINES));
+        assert!(!walk.is_generated_source(&late));
+    }
+
+    #[test]
+    #[serial]
+    fn git_tree_source_reads_committed_blobs() {
+        let dir = tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-q"]);
+        git(repo, &["config", "user.email", "tester@example.com"]);
+        git(repo, &["config", "user.name", "Tester"]);
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::create_dir_all(repo.join(".hidden")).unwrap();
+        fs::write(repo.join("src/lib.rs"), "fn old() {}\n").unwrap();
+        fs::write(repo.join(".hidden/x.rs"), "fn hidden() {}\n").unwrap();
+        fs::write(repo.join("README.md"), "readme\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", "init", "-q"]);
+        fs::write(repo.join("src/lib.rs"), "fn new() {}\n").unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(repo).unwrap();
+        let source = GraphSource::GitTree("HEAD".to_string());
+        let root = Path::new(".");
+        let files = source.list_files(root, &AnalyzerConfig::default(), &WalkOptions::default());
+        let contents =
+            source.read_files(root, &["src/lib.rs".to_string(), "missing.rs".to_string()]);
+        std::env::set_current_dir(cwd).unwrap();
+
+        let files = files.unwrap();
+        assert_eq!(files, vec![("src/lib.rs".to_string(), LanguageKind::Rust)]);
+        assert_eq!(
+            contents.unwrap(),
+            vec![Some("fn old() {}\n".to_string()), None]
+        );
+    }
+
+    #[cfg(unix)]
+    fn walked(root: &Path, follow: bool) -> Vec<String> {
+        let walk = WalkOptions {
+            follow_symlinks: follow,
+            ..WalkOptions::default()
+        };
+        let mut out: Vec<String> = walk_files(root, is_skipped_dir_name, &walk)
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_not_followed_out_of_the_root_by_default() {
+        use std::os::unix::fs::symlink;
+        let outside = tempdir().unwrap();
+        fs::write(outside.path().join("ext.rs"), "fn ext() {}\n").unwrap();
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        symlink(root.join("src"), root.join("src/loop")).unwrap();
+        symlink(outside.path(), root.join("vendor")).unwrap();
+        symlink(outside.path().join("ext.rs"), root.join("ext.rs")).unwrap();
+        symlink(root.join("src/lib.rs"), root.join("alias.rs")).unwrap();
+
+        assert_eq!(walked(root, false), vec!["alias.rs", "src/lib.rs"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn following_symlinks_visits_each_real_path_once() {
+        use std::os::unix::fs::symlink;
+        let outside = tempdir().unwrap();
+        fs::write(outside.path().join("ext.rs"), "fn ext() {}\n").unwrap();
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        symlink(root.join("src"), root.join("src/loop")).unwrap();
+        symlink(root, root.join("src/up")).unwrap();
+        symlink(outside.path(), root.join("vendor")).unwrap();
+
+        assert_eq!(walked(root, true), vec!["src/lib.rs", "vendor/ext.rs"]);
+    }
+}
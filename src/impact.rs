@@ -1,10 +1,12 @@
+use crate::graph_source::{DEFAULT_GENERATED_MARKER, GraphSource, WalkOptions};
 use crate::ir::Symbol;
 use crate::ir::reference::{EdgeProvenance, RefKind, Reference, SymbolIndex, UnresolvedRef};
-use crate::languages::{LanguageKind, analyzer_for_path};
+use crate::languages::path::{normalize_path, workspace_file};
+use crate::languages::{AnalyzerConfig, LanguageKind};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use walkdir::WalkDir;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -24,6 +26,107 @@ pub struct ImpactOptions {
     /// is excluded from seeds and results.
     #[serde(default)]
     pub ignore_dirs: Vec<String>,
+    /// Reference kinds to traverse (empty = all). PDG data/control edges are
+    /// always kept so this only narrows the call/type graph.
+    #[serde(default)]
+    pub edge_kinds: Vec<RefKind>,
+    /// Depth cost of following an edge of each kind (1 when absent, at least
+    /// 1). With weights, `max_depth` bounds the summed cost of a path, so e.g.
+    /// weighting `type_use` 3 lets calls reach further than type references.
+    #[serde(default)]
+    pub edge_weights: HashMap<RefKind, usize>,
+    /// Resource guards for oversized graphs (all unset by default).
+    #[serde(default)]
+    pub limits: GraphLimits,
+    /// Don't traverse into test functions (see [`find_impacted_tests`] for
+    /// what counts as a test), so neither they nor the helpers reached only
+    /// through them show up. Changed symbols are always kept as seeds.
+    #[serde(default)]
+    pub exclude_tests: bool,
+    /// Wall-clock budget for the traversal in milliseconds. Once spent, the
+    /// search stops at the next step and the output is flagged
+    /// [`ImpactSummary::truncated`] with what was reached so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_budget_ms: Option<u64>,
+    /// Languages to analyze (empty = all). Seeds in other languages are
+    /// dropped and edges into or out of them are not followed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<LanguageKind>,
+    /// Workspace root that symbol files are read from to tell test
+    /// functions apart (the current directory when unset).
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+}
+
+/// When the traversal started and when its [`ImpactOptions::time_budget_ms`]
+/// runs out.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimeBudget(Option<std::time::Instant>);
+
+impl TimeBudget {
+    pub(crate) fn start(opts: &ImpactOptions) -> Self {
+        Self(
+            opts.time_budget_ms
+                .map(|ms| std::time::Instant::now() + std::time::Duration::from_millis(ms)),
+        )
+    }
+
+    pub(crate) fn is_spent(&self) -> bool {
+        self.0.is_some_and(|at| std::time::Instant::now() >= at)
+    }
+}
+
+/// Upper bounds that keep analysis of huge or vendored trees predictable.
+///
+/// Exceeding `max_symbols`/`max_edges` is an error (see [`check_graph_limits`]);
+/// reaching `max_visited` stops traversal and flags the output as
+/// [`ImpactSummary::truncated`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GraphLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_symbols: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_edges: Option<usize>,
+    /// Maximum number of symbols (seeds included) a traversal may reach.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_visited: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum GraphLimitError {
+    #[error(
+        "graph has {count} symbols, over the limit of {max}; narrow the workspace with --ignore-dir or raise --max-symbols"
+    )]
+    TooManySymbols { count: usize, max: usize },
+    #[error(
+        "graph has {count} edges, over the limit of {max}; narrow the workspace with --ignore-dir or raise --max-edges"
+    )]
+    TooManyEdges { count: usize, max: usize },
+}
+
+/// Fail fast when a loaded graph is bigger than `limits` allow.
+pub fn check_graph_limits(
+    index: &SymbolIndex,
+    refs: &[Reference],
+    limits: &GraphLimits,
+) -> Result<(), GraphLimitError> {
+    if let Some(max) = limits.max_symbols
+        && index.symbols.len() > max
+    {
+        return Err(GraphLimitError::TooManySymbols {
+            count: index.symbols.len(),
+            max,
+        });
+    }
+    if let Some(max) = limits.max_edges
+        && refs.len() > max
+    {
+        return Err(GraphLimitError::TooManyEdges {
+            count: refs.len(),
+            max,
+        });
+    }
+    Ok(())
 }
 
 impl Default for ImpactOptions {
@@ -33,12 +136,29 @@ impl Default for ImpactOptions {
             max_depth: Some(100),
             with_edges: Some(false),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            limits: GraphLimits::default(),
+            exclude_tests: false,
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct ImpactSummary {
+    /// False when no symbol beyond the changed ones was reached, so an empty
+    /// result is distinguishable from a failed run.
+    #[serde(default)]
+    pub has_impact: bool,
+    #[serde(default)]
+    pub changed_symbol_count: usize,
+    #[serde(default)]
+    pub impacted_symbol_count: usize,
+    #[serde(default)]
+    pub impacted_file_count: usize,
     #[serde(default)]
     pub by_depth: Vec<ImpactDepthBucket>,
     #[serde(default)]
@@ -47,6 +167,15 @@ pub struct ImpactSummary {
     pub risk: Option<ImpactRiskSummary>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub slice_selection: Option<ImpactSliceSelectionSummary>,
+    /// Set when traversal stopped at [`GraphLimits::max_visited`] or
+    /// [`ImpactOptions::time_budget_ms`], or the impacted list was capped by
+    /// [`cap_impacted_symbols`].
+    #[serde(default, skip_serializing_if = "impact_slice_bool_is_false")]
+    pub truncated: bool,
+    /// How many symbols were impacted before [`cap_impacted_symbols`] cut the
+    /// list; absent when nothing was cut.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_impacted: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -614,12 +743,56 @@ pub struct ImpactOutput {
     pub changed_symbols: Vec<Symbol>,
     pub impacted_symbols: Vec<Symbol>,
     pub impacted_files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub edges: Vec<Reference>,
+    /// Whether edges were requested; tells "no edges exist" apart from "edges
+    /// left out" when `edges` is absent.
+    #[serde(default)]
+    pub edges_included: bool,
+    #[serde(serialize_with = "serialize_sorted_by_file")]
     pub impacted_by_file: std::collections::HashMap<String, Vec<Symbol>>, // file -> impacted symbols in that file
+    /// Changed symbols grouped by file, the counterpart of `impacted_by_file`
+    /// for file-oriented views.
+    #[serde(
+        default,
+        skip_serializing_if = "std::collections::HashMap::is_empty",
+        serialize_with = "serialize_sorted_by_file"
+    )]
+    pub changed_by_file: std::collections::HashMap<String, Vec<Symbol>>,
     #[serde(default)]
     pub impacted_witnesses: std::collections::HashMap<String, ImpactWitness>,
     #[serde(default)]
     pub summary: ImpactSummary,
+    /// Source text per symbol id; only filled by [`attach_snippets`].
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub snippets: std::collections::HashMap<String, String>,
+}
+
+/// Serializes a file-keyed map with its keys in path order so JSON output is
+/// stable across runs.
+fn serialize_sorted_by_file<S: serde::Serializer>(
+    map: &HashMap<String, Vec<Symbol>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let sorted: std::collections::BTreeMap<&String, &Vec<Symbol>> = map.iter().collect();
+    sorted.serialize(serializer)
+}
+
+/// Groups symbols under their normalized file path, each group sorted and
+/// deduplicated by id.
+fn group_symbols_by_file(symbols: &[Symbol], root: &Path) -> HashMap<String, Vec<Symbol>> {
+    let mut by_file: HashMap<String, Vec<Symbol>> = HashMap::new();
+    for s in symbols {
+        by_file
+            .entry(normalize_path(&s.file, root))
+            .or_default()
+            .push(s.clone());
+    }
+    for v in by_file.values_mut() {
+        v.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+        v.dedup_by(|a, b| a.id.0 == b.id.0);
+    }
+    by_file
 }
 
 pub(crate) fn build_by_depth_summary(
@@ -699,7 +872,7 @@ fn is_entry_like_module_file(name: &str) -> bool {
 }
 
 fn affected_module_for_file(file: &str) -> String {
-    let normalized = file.strip_prefix("./").unwrap_or(file).replace('\\', "/");
+    let normalized = normalize_path(&file.replace('\\', "/"), Path::new("."));
     let path = std::path::Path::new(&normalized);
     let parent = path
         .parent()
@@ -781,6 +954,8 @@ fn ref_kind_rank(kind: &RefKind) -> usize {
         RefKind::Data => 0,
         RefKind::Call => 1,
         RefKind::Control => 2,
+        RefKind::TypeUse => 3,
+        RefKind::Inherit => 4,
     }
 }
 
@@ -884,49 +1059,145 @@ fn update_witness_candidate(
     }
 }
 
+/// What changed in the blast radius between a saved impact run (the
+/// baseline) and the current one; see [`compare_impact`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImpactComparison {
+    /// Impacted now but not in the baseline.
+    pub added_symbols: Vec<Symbol>,
+    /// Impacted in the baseline but no longer.
+    pub removed_symbols: Vec<Symbol>,
+    pub added_edges: Vec<Reference>,
+    pub removed_edges: Vec<Reference>,
+    /// False when either run was made without edges, in which case the edge
+    /// lists are left empty.
+    pub edges_compared: bool,
+}
+
+/// Set difference of `current` against `baseline`: impacted symbols by id,
+/// edges by (from, to, kind).
+pub fn compare_impact(baseline: &ImpactOutput, current: &ImpactOutput) -> ImpactComparison {
+    fn symbol_diff(from: &[Symbol], without: &[Symbol]) -> Vec<Symbol> {
+        let ids: HashSet<&str> = without.iter().map(|s| s.id.0.as_str()).collect();
+        let mut out: Vec<Symbol> = from
+            .iter()
+            .filter(|s| !ids.contains(s.id.0.as_str()))
+            .cloned()
+            .collect();
+        out.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+        out.dedup_by(|a, b| a.id.0 == b.id.0);
+        out
+    }
+    fn edge_diff(from: &[Reference], without: &[Reference]) -> Vec<Reference> {
+        let keys: HashSet<(&str, &str, &RefKind)> = without
+            .iter()
+            .map(|e| (e.from.0.as_str(), e.to.0.as_str(), &e.kind))
+            .collect();
+        let mut out: Vec<Reference> = from
+            .iter()
+            .filter(|e| !keys.contains(&(e.from.0.as_str(), e.to.0.as_str(), &e.kind)))
+            .cloned()
+            .collect();
+        normalize_edges(&mut out);
+        out
+    }
+
+    let edges_compared = baseline.edges_included && current.edges_included;
+    let (added_edges, removed_edges) = if edges_compared {
+        (
+            edge_diff(&current.edges, &baseline.edges),
+            edge_diff(&baseline.edges, &current.edges),
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    ImpactComparison {
+        added_symbols: symbol_diff(&current.impacted_symbols, &baseline.impacted_symbols),
+        removed_symbols: symbol_diff(&baseline.impacted_symbols, &current.impacted_symbols),
+        added_edges,
+        removed_edges,
+        edges_compared,
+    }
+}
+
+/// Sort edges by (from, to, line, kind) and drop exact repeats, so every
+/// output path yields the same order for the same graph.
+pub fn normalize_edges(edges: &mut Vec<Reference>) {
+    edges.sort_by(|a, b| {
+        (
+            a.from.0.as_str(),
+            a.to.0.as_str(),
+            a.line,
+            ref_kind_rank(&a.kind),
+        )
+            .cmp(&(
+                b.from.0.as_str(),
+                b.to.0.as_str(),
+                b.line,
+                ref_kind_rank(&b.kind),
+            ))
+    });
+    edges.dedup_by(|a, b| a.from == b.from && a.to == b.to && a.line == b.line && a.kind == b.kind);
+}
+
+/// Keep one edge per (from, to, line). With [`ImpactDirection::Both`] a call
+/// can be reached from its caller and from its callee, so the same call site
+/// may be recorded twice under different kinds or provenances; the copy that
+/// [`normalize_edges`] orders first wins.
+pub(crate) fn dedup_mirrored_edges(edges: &mut Vec<Reference>) {
+    normalize_edges(edges);
+    edges.dedup_by(|a, b| a.from == b.from && a.to == b.to && a.line == b.line);
+}
+
 pub(crate) fn finalize_impact_output(
     changed_symbols: Vec<Symbol>,
     mut impacted_symbols: Vec<Symbol>,
-    edges: Vec<Reference>,
+    mut edges: Vec<Reference>,
     min_depth_by_symbol_id: &HashMap<String, usize>,
     impacted_witnesses: std::collections::HashMap<String, ImpactWitness>,
 ) -> ImpactOutput {
     impacted_symbols.sort_by(|a, b| a.id.0.cmp(&b.id.0));
     impacted_symbols.dedup_by(|a, b| a.id.0 == b.id.0);
+    normalize_edges(&mut edges);
 
-    let mut impacted_files: Vec<String> = impacted_symbols.iter().map(|s| s.file.clone()).collect();
+    // Group by normalized path so `./src/a.rs` and `src/a.rs` are one file
+    let root = std::env::current_dir().unwrap_or_default();
+    let mut impacted_files: Vec<String> = impacted_symbols
+        .iter()
+        .map(|s| normalize_path(&s.file, &root))
+        .collect();
     impacted_files.sort();
     impacted_files.dedup();
 
-    let mut impacted_by_file: std::collections::HashMap<String, Vec<Symbol>> =
-        std::collections::HashMap::new();
-    for s in &impacted_symbols {
-        impacted_by_file
-            .entry(s.file.clone())
-            .or_default()
-            .push(s.clone());
-    }
-    for v in impacted_by_file.values_mut() {
-        v.sort_by(|a, b| a.id.0.cmp(&b.id.0));
-        v.dedup_by(|a, b| a.id.0 == b.id.0);
-    }
+    let impacted_by_file = group_symbols_by_file(&impacted_symbols, &root);
+    let changed_by_file = group_symbols_by_file(&changed_symbols, &root);
     let by_depth = build_by_depth_summary(&impacted_symbols, min_depth_by_symbol_id);
     let affected_modules = build_affected_modules_summary(&impacted_symbols);
     let risk = build_risk_summary(&by_depth, impacted_files.len(), impacted_symbols.len());
+    let summary = ImpactSummary {
+        has_impact: !impacted_symbols.is_empty(),
+        changed_symbol_count: changed_symbols.len(),
+        impacted_symbol_count: impacted_symbols.len(),
+        impacted_file_count: impacted_files.len(),
+        by_depth,
+        affected_modules,
+        risk: Some(risk),
+        slice_selection: None,
+        truncated: false,
+        total_impacted: None,
+    };
 
     ImpactOutput {
         changed_symbols,
         impacted_symbols,
         impacted_files,
         edges,
+        edges_included: false,
         impacted_by_file,
+        changed_by_file,
         impacted_witnesses,
-        summary: ImpactSummary {
-            by_depth,
-            affected_modules,
-            risk: Some(risk),
-            slice_selection: None,
-        },
+        summary,
+        snippets: std::collections::HashMap::new(),
     }
 }
 
@@ -1583,14 +1854,14 @@ fn relative_require_target(from_path: &str, to_path: &str) -> Option<String> {
     (!relative.is_empty()).then_some(relative)
 }
 
-fn file_has_require_relative_load(from_path: &str, to_path: &str) -> bool {
+fn file_has_require_relative_load(root: Option<&Path>, from_path: &str, to_path: &str) -> bool {
     if !(from_path.ends_with(".rb") && to_path.ends_with(".rb")) {
         return false;
     }
     let Some(target) = relative_require_target(from_path, to_path) else {
         return false;
     };
-    let Ok(contents) = fs::read_to_string(from_path) else {
+    let Ok(contents) = fs::read_to_string(workspace_file(root, from_path)) else {
         return false;
     };
     [
@@ -1758,6 +2029,7 @@ fn build_bridge_execution_provenance_from_representatives(
 }
 
 fn build_bridge_execution_provenance_compact(
+    root: Option<&Path>,
     slice_context: &ImpactWitnessSliceContext,
     provenance_chain: &[EdgeProvenance],
 ) -> (
@@ -1805,7 +2077,11 @@ fn build_bridge_execution_provenance_compact(
 
             if bridge_kind != ImpactSliceBridgeKind::RequireRelativeChain
                 && let Some(anchor_path) = reason.via_path.clone()
-                && file_has_require_relative_load(anchor_path.as_str(), file_context.path.as_str())
+                && file_has_require_relative_load(
+                    root,
+                    anchor_path.as_str(),
+                    file_context.path.as_str(),
+                )
             {
                 let require_relative_step = ImpactBridgeExecutionStepCompact {
                     family: ImpactBridgeExecutionFamily::RequireRelativeContinuation,
@@ -1899,6 +2175,7 @@ fn build_bridge_execution_provenance_compact(
 pub fn attach_slice_selection_summary(
     output: &mut ImpactOutput,
     slice_selection: &ImpactSliceSelectionSummary,
+    root: Option<&Path>,
 ) {
     output.summary.slice_selection = Some(slice_selection.clone());
 
@@ -1924,7 +2201,11 @@ pub fn attach_slice_selection_summary(
                 bridge_execution_family,
                 winning_bridge_execution_chain_compact,
                 observed_supporting_steps_compact,
-            ) = build_bridge_execution_provenance_compact(slice_context, &witness.provenance_chain);
+            ) = build_bridge_execution_provenance_compact(
+                root,
+                slice_context,
+                &witness.provenance_chain,
+            );
             witness.bridge_execution_family = bridge_execution_family;
             witness.bridge_execution_chain_compact = winning_bridge_execution_chain_compact.clone();
             witness.winning_bridge_execution_chain_compact = winning_bridge_execution_chain_compact;
@@ -1939,22 +2220,12 @@ pub fn path_is_ignored(path: &str, ignore_dirs: &[String]) -> bool {
     if ignore_dirs.is_empty() {
         return false;
     }
-    // Normalize path
-    let mut p = path.replace('\\', "/");
-    if let Some(stripped) = p.strip_prefix("./") {
-        p = stripped.to_string();
-    }
+    let p = normalize_path(&path.replace('\\', "/"), Path::new("."));
     for dir in ignore_dirs {
         if dir.is_empty() {
             continue;
         }
-        let mut d = dir.replace('\\', "/");
-        if let Some(stripped) = d.strip_prefix("./") {
-            d = stripped.to_string();
-        }
-        if d.ends_with('/') {
-            d.pop();
-        }
+        let d = normalize_path(&dir.replace('\\', "/"), Path::new("."));
         if p == d || p.starts_with(&(d.clone() + "/")) {
             return true;
         }
@@ -1962,353 +2233,853 @@ pub fn path_is_ignored(path: &str, ignore_dirs: &[String]) -> bool {
     false
 }
 
+/// Number of files read and analyzed per batch during workspace scans; always
+/// on. Only bounds how many file sources are held in memory at once: symbols,
+/// references and imports still accumulate for the whole workspace before the
+/// index is built.
+pub(crate) const SCAN_CHUNK_SIZE: usize = 256;
+
+/// How many re-export hops (TS/JS barrel files, Rust `pub use`) reference
+/// resolution follows by default.
+pub const DEFAULT_REEXPORT_DEPTH: usize = 10;
+
+/// Which files [`analyze_workspace`] reads and analyzes.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzeOptions {
+    /// Workspace root that files are listed and read under, and that symbol
+    /// paths are relative to (the current directory when unset).
+    pub root: Option<PathBuf>,
+    /// Only analyze files under these root-relative directory prefixes (all when empty).
+    pub include: Vec<String>,
+    /// Skip files under these root-relative directory prefixes.
+    pub exclude: Vec<String>,
+    /// Only analyze these languages (every supported language when empty).
+    pub languages: Vec<LanguageKind>,
+    /// Read files from the working tree (default) or a git tree-ish.
+    pub source: GraphSource,
+    /// Symlink handling and which files count as generated.
+    pub walk: WalkOptions,
+    /// Re-export hops to follow when resolving imported names
+    /// ([`DEFAULT_REEXPORT_DEPTH`] when unset; `Some(0)` disables the chase).
+    pub reexport_depth: Option<usize>,
+    /// Rust: when a reference's target module is known — from its qualifier,
+    /// a `use` import, or the referencing module itself for bare calls — only
+    /// same-named symbols declared in exactly that module (crate-relative, see
+    /// [`rust_module_key`]) are candidates. The score heuristics still pick
+    /// among them, and apply unchanged when no symbol is in that module.
+    /// Method calls are unaffected.
+    pub strict_resolution: bool,
+    /// A qualified or imported reference that matches no workspace symbol,
+    /// such as `serde_json::from_str` or `lodash::debounce`, gets an edge to a
+    /// boundary symbol for that path (see [`Symbol::external`]) instead of
+    /// being dropped. Impact reports boundary symbols but does not traverse
+    /// past them. Method calls and paths rooted in the workspace are never
+    /// treated as external.
+    pub include_external: bool,
+    /// Optional relationships the language analyzers extract.
+    pub analyzers: AnalyzerConfig,
+}
+
+impl AnalyzeOptions {
+    /// [`AnalyzeOptions::root`], or `.` when unset.
+    pub fn root(&self) -> &Path {
+        self.root.as_deref().unwrap_or(Path::new("."))
+    }
+
+    fn reexport_hops(&self) -> usize {
+        self.reexport_depth.unwrap_or(DEFAULT_REEXPORT_DEPTH)
+    }
+
+    /// `"strict"` or `"default"`, plus `+external`, `+macros`,
+    /// `+constructions`, `+symlinks`, the generated marker and the language
+    /// map for the non-default settings; recorded by the cache so a graph analyzed under other
+    /// settings is rebuilt.
+    pub(crate) fn cache_key(&self) -> String {
+        let mut key = if self.strict_resolution {
+            "strict"
+        } else {
+            "default"
+        }
+        .to_string();
+        if self.include_external {
+            key.push_str("+external");
+        }
+        if self.analyzers.include_macros {
+            key.push_str("+macros");
+        }
+        if self.analyzers.include_constructions {
+            key.push_str("+constructions");
+        }
+        if self.walk.follow_symlinks {
+            key.push_str("+symlinks");
+        }
+        match &self.walk.generated_marker {
+            None => key.push_str("+generated"),
+            Some(re) if re.as_str() != DEFAULT_GENERATED_MARKER => {
+                key.push_str(&format!("+marker:{}", re.as_str()));
+            }
+            Some(_) => {}
+        }
+        for (glob, kind) in &self.analyzers.language_map {
+            key.push_str(&format!("+map:{glob}={}", kind.name()));
+        }
+        key
+    }
+}
+
 /// Build symbol index and resolved reference edges for the current workspace (cwd).
 pub fn build_project_graph() -> anyhow::Result<(SymbolIndex, Vec<Reference>)> {
+    build_project_graph_from(&GraphSource::WorkingTree)
+}
+
+/// Build the project graph from `source` (working tree or a git tree-ish).
+pub fn build_project_graph_from(
+    source: &GraphSource,
+) -> anyhow::Result<(SymbolIndex, Vec<Reference>)> {
+    analyze_workspace(
+        Path::new("."),
+        AnalyzeOptions {
+            source: source.clone(),
+            ..AnalyzeOptions::default()
+        },
+    )
+}
+
+/// Build the symbol index and resolved reference edges for the workspace at
+/// `root`, without depending on the process working directory. Symbol files
+/// are reported relative to `root`.
+pub fn analyze_workspace(
+    root: &Path,
+    opts: AnalyzeOptions,
+) -> anyhow::Result<(SymbolIndex, Vec<Reference>)> {
+    let scan = scan_workspace(root, &opts)?;
+    let index = SymbolIndex::build(scan.symbols);
+    let refs = resolve_references(&index, &scan.urefs, &scan.file_imports, &opts);
+    Ok((with_boundary_symbols(index, &refs), refs))
+}
+
+/// How the reference sites inside `symbol_id`, and those that considered it
+/// as a target, were resolved in the workspace at `root`; ordered by file
+/// and line.
+pub fn explain_workspace(
+    root: &Path,
+    opts: AnalyzeOptions,
+    symbol_id: &str,
+) -> anyhow::Result<Vec<ResolutionTrace>> {
+    let scan = scan_workspace(root, &opts)?;
+    let index = SymbolIndex::build(scan.symbols);
+    let packages = file_packages(&scan.file_imports);
+    let mut traces = Vec::new();
+    for r in &scan.urefs {
+        let from = index
+            .enclosing_symbol(&r.file, r.line)
+            .map(|s| s.id.0.clone());
+        let mut trace = ResolutionTrace {
+            file: r.file.clone(),
+            line: r.line,
+            name: r.name.clone(),
+            from,
+            is_method: r.is_method,
+            qualifier: None,
+            target_name: r.name.clone(),
+            imported_prefix: None,
+            glob_prefixes: Vec::new(),
+            stage: ResolutionStage::Unresolved,
+            candidates: Vec::new(),
+            resolved_to: None,
+        };
+        resolve_ref(
+            &index,
+            r,
+            &scan.file_imports,
+            &packages,
+            &opts,
+            Some(&mut trace),
+        );
+        let relevant = trace.from.as_deref() == Some(symbol_id)
+            || trace.candidates.iter().any(|c| c.symbol_id == symbol_id);
+        if relevant {
+            traces.push(trace);
+        }
+    }
+    traces.sort_by(|a, b| {
+        (a.file.as_str(), a.line, a.name.as_str()).cmp(&(b.file.as_str(), b.line, b.name.as_str()))
+    });
+    Ok(traces)
+}
+
+/// Analyzer output for every file of a workspace, before resolution.
+struct WorkspaceScan {
+    symbols: Vec<Symbol>,
+    urefs: Vec<UnresolvedRef>,
+    file_imports: HashMap<String, HashMap<String, String>>,
+}
+
+/// The files [`analyze_workspace`] would analyze under `opts`, with their
+/// languages, in scan order; nothing is read or parsed.
+pub fn list_analyzed_files(
+    root: &Path,
+    opts: &AnalyzeOptions,
+) -> anyhow::Result<Vec<(String, LanguageKind)>> {
+    Ok(opts
+        .source
+        .list_files(root, &opts.analyzers, &opts.walk)?
+        .into_iter()
+        .filter(|(p, kind)| {
+            (opts.include.is_empty() || path_is_ignored(p, &opts.include))
+                && !path_is_ignored(p, &opts.exclude)
+                && (opts.languages.is_empty() || opts.languages.contains(kind))
+        })
+        .collect())
+}
+
+fn scan_workspace(root: &Path, opts: &AnalyzeOptions) -> anyhow::Result<WorkspaceScan> {
+    use rayon::prelude::*;
+    let source = &opts.source;
+    let paths = {
+        let _timer = crate::profile::start(crate::profile::Phase::Enumerate);
+        list_analyzed_files(root, opts)?
+    };
+    let _timer = crate::profile::start(crate::profile::Phase::Analyze);
+
     let mut symbols = Vec::new();
     let mut urefs = Vec::new();
     let mut file_imports: std::collections::HashMap<
         String,
         std::collections::HashMap<String, String>,
     > = std::collections::HashMap::new();
-    for entry in WalkDir::new(".")
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path();
-            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            !(name == ".git" || name == "target" || name.starts_with('.'))
-        })
-        .filter_map(Result::ok)
-    {
-        let path = entry.path();
-        if path.is_file() {
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-            if ext != "rs"
-                && ext != "rb"
-                && ext != "js"
-                && ext != "ts"
-                && ext != "tsx"
-                && ext != "py"
-            {
-                continue;
-            }
-            let path_str = path
-                .strip_prefix("./")
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
-            let Ok(src) = fs::read_to_string(path) else {
-                continue;
-            };
-            let kind = if ext == "rs" {
-                LanguageKind::Rust
-            } else if ext == "rb" {
-                LanguageKind::Ruby
-            } else if ext == "js" {
-                LanguageKind::Javascript
-            } else if ext == "ts" {
-                LanguageKind::Typescript
-            } else if ext == "tsx" {
-                LanguageKind::Tsx
-            } else {
-                LanguageKind::Python
-            };
-            let Some(analyzer) = analyzer_for_path(&path_str, kind) else {
-                continue;
-            };
-            symbols.extend(analyzer.symbols_in_file(&path_str, &src));
-            urefs.extend(analyzer.unresolved_refs(&path_str, &src));
-            let im = analyzer.imports_in_file(&path_str, &src);
-            file_imports.insert(path_str.clone(), im);
+    for chunk in paths.chunks(SCAN_CHUNK_SIZE) {
+        let chunk_paths: Vec<String> = chunk.iter().map(|(p, _)| p.clone()).collect();
+        let sources = source.read_files(root, &chunk_paths)?;
+        let results: Vec<_> = chunk
+            .par_iter()
+            .zip(sources.par_iter())
+            .filter_map(|((path_str, kind), src)| {
+                let src = src.as_deref()?;
+                // Tree-ish blobs are only checked for the marker once read
+                if opts.walk.is_generated_source(src) {
+                    return None;
+                }
+                let analyzer = opts.analyzers.analyzer_for_path(path_str, *kind)?;
+                Some((
+                    analyzer.symbols_in_file(path_str, src),
+                    analyzer.unresolved_refs(path_str, src),
+                    path_str.clone(),
+                    analyzer.imports_in_file(path_str, src),
+                ))
+            })
+            .collect();
+        for (syms, file_urefs, path_str, im) in results {
+            symbols.extend(syms);
+            urefs.extend(file_urefs);
+            file_imports.insert(path_str, im);
         }
     }
-    let index = SymbolIndex::build(symbols);
-    let refs = resolve_references(&index, &urefs, &file_imports);
-    Ok((index, refs))
+    Ok(WorkspaceScan {
+        symbols,
+        urefs,
+        file_imports,
+    })
 }
 
 pub(crate) fn resolve_references(
     index: &SymbolIndex,
     urefs: &[UnresolvedRef],
     file_imports: &std::collections::HashMap<String, std::collections::HashMap<String, String>>,
+    opts: &AnalyzeOptions,
 ) -> Vec<Reference> {
+    let _timer = crate::profile::start(crate::profile::Phase::Resolve);
+    let packages = file_packages(file_imports);
+    let local_roots = if opts.include_external {
+        local_module_roots(index)
+    } else {
+        HashSet::new()
+    };
     let mut out = Vec::new();
     for r in urefs {
-        // find from symbol by containing line
-        let Some(from_sym) = index.enclosing_symbol(&r.file, r.line) else {
+        let Some((from_sym, to_sym)) = resolve_site(index, r, file_imports, &packages, opts) else {
+            if opts.include_external
+                && let Some(from_sym) = index.enclosing_symbol(&r.file, r.line)
+                && let Some(path) = external_path(index, r, file_imports, &local_roots)
+            {
+                let kind = match r.kind {
+                    RefKind::TypeUse => crate::ir::SymbolKind::Struct,
+                    RefKind::Inherit => crate::ir::SymbolKind::Trait,
+                    _ => crate::ir::SymbolKind::Function,
+                };
+                let boundary = Symbol::external(&from_sym.language, kind, &path);
+                out.push(reference_edge(r, from_sym, &boundary));
+            }
             continue;
         };
-        // Determine candidate name, considering alias from imports
-        let imports = file_imports.get(&r.file).cloned().unwrap_or_default();
-        let mut target_name = r.name.as_str();
-        let qualifier = r.qualifier.as_deref();
-        // normalize qualifier using imports (handle alias on the first segment)
-        let from_mod = module_path_for_file(&r.file);
-        let norm_qual =
-            qualifier.and_then(|q| normalize_qualifier_with_imports(q, &imports, &from_mod));
-        let qualifier = norm_qual.as_deref().or(qualifier);
-        let mut imported_prefix: Option<String> = None;
-        let mut glob_prefixes: Vec<String> = imports
-            .iter()
-            .filter_map(|(k, v)| {
-                if k.starts_with("__glob__") {
-                    Some(v.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-        if qualifier.is_none()
-            && let Some(full) = imports.get(&r.name)
+        out.push(reference_edge(r, from_sym, to_sym));
+        if r.kind == RefKind::Inherit
+            && let Some(member) = implemented_trait_member(index, from_sym, to_sym)
         {
-            let prior = full.rsplit_once("::").map(|(p, _)| p).unwrap_or("");
-            let ip = if prior.contains("self::")
-                || prior.contains("super::")
-                || prior.contains("crate::")
-            {
-                expand_relative_path(&from_mod, prior)
-            } else {
-                prior.to_string()
-            };
-            imported_prefix = Some(ip);
-            target_name = full.rsplit_once("::").map(|(_, n)| n).unwrap_or(full);
+            out.push(reference_edge(r, from_sym, member));
         }
+    }
+    out
+}
 
-        // Re-export fallback: if imported_prefix points to an aggregator module, try to map to the underlying module via its export map
-        if let Some(mut ip) = imported_prefix.clone() {
-            // resolve through aggregator chain (up to 10 hops, guard cycles)
-            let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
-            for _ in 0..10 {
-                if !visited.insert(ip.clone()) {
-                    break;
-                }
-                let mut agg_files: Vec<&String> = file_imports
-                    .keys()
-                    .filter(|f| file_matches_module_path(f, &ip))
-                    .collect();
-                if agg_files.len() > 1 {
-                    agg_files.sort_by_key(|f| {
-                        if f.ends_with("/index.js")
-                            || f.ends_with("/index.ts")
-                            || f.ends_with("/index.tsx")
-                        {
-                            0
-                        } else {
-                            1
-                        }
-                    });
-                }
-                let Some(agg_path) = agg_files.first() else {
-                    break;
-                };
-                let Some(exp_map) = file_imports.get(*agg_path) else {
-                    break;
-                };
-                for (k, v) in exp_map.iter() {
-                    if k.starts_with("__export_glob__") {
-                        glob_prefixes.push(v.clone());
-                    }
-                }
-                let key = format!("__export__{}", target_name);
-                if let Some(real) = exp_map.get(&key) {
-                    ip = real
-                        .rsplit_once("::")
-                        .map(|(p, _)| p)
-                        .unwrap_or("")
-                        .to_string();
-                    imported_prefix = Some(ip.clone());
-                    target_name = real.rsplit_once("::").map(|(_, n)| n).unwrap_or(real);
-                    continue;
-                }
-                break;
-            }
-        }
+/// Every segment of every indexed file's module path (`src`, `net`,
+/// `client` for `src/net/client.rs`): roots a workspace path can start with.
+fn local_module_roots(index: &SymbolIndex) -> HashSet<String> {
+    index
+        .by_file
+        .keys()
+        .flat_map(|f| {
+            module_path_for_file(f)
+                .split("::")
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
 
-        // Try candidates by exact name first
-        let mut best: Option<&crate::ir::Symbol> = None;
-        if let Some(cands) = index.by_name.get(target_name) {
-            // If qualifier given, prefer candidates whose module path matches it
-            let filtered: Vec<&crate::ir::Symbol> = if let Some(q) = qualifier {
-                let v: Vec<_> = cands
-                    .iter()
-                    .filter(|s| file_matches_module_path(&s.file, q))
-                    .collect();
-                if v.is_empty() {
-                    cands.iter().collect()
-                } else {
-                    v
-                }
-            } else {
-                cands.iter().collect()
-            };
-            best = filtered
+/// The path an unresolved reference names outside the workspace: its
+/// qualifier (with an import alias on the first segment applied) or the
+/// path its name was imported from, ending in the name. `None` for method
+/// calls, unqualified names that were not imported, and paths rooted in the
+/// workspace: `crate`/`self`/`super`/`Self`, a segment in `local_roots`, or
+/// a symbol name such as a local type.
+fn external_path(
+    index: &SymbolIndex,
+    r: &UnresolvedRef,
+    file_imports: &HashMap<String, HashMap<String, String>>,
+    local_roots: &HashSet<String>,
+) -> Option<String> {
+    if r.is_method {
+        return None;
+    }
+    let imports = file_imports.get(&r.file);
+    let imported = |alias: &str| imports.and_then(|m| m.get(alias));
+    let path = match r.qualifier.as_deref() {
+        Some(q) => {
+            let q = q.replace('.', "::");
+            let (head, rest) = q.split_once("::").unwrap_or((q.as_str(), ""));
+            let head = imported(head).map(String::as_str).unwrap_or(head);
+            [head, rest, r.name.as_str()]
                 .into_iter()
-                .filter(|to_sym| {
-                    matches!(
-                        to_sym.kind,
-                        crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
-                    )
-                })
-                .max_by(|a, b| {
-                    let score_for = |to_sym: &&crate::ir::Symbol| {
-                        let mut best = score_candidate(
-                            &r.file,
-                            qualifier,
-                            imported_prefix.as_deref(),
-                            to_sym,
-                            r.is_method,
-                        );
-                        for gp in &glob_prefixes {
-                            let s = score_candidate(
-                                &r.file,
-                                qualifier,
-                                Some(gp.as_str()),
-                                to_sym,
-                                r.is_method,
-                            );
-                            if s > best {
-                                best = s;
-                            }
-                        }
-                        best
-                    };
-                    let sa = score_for(a);
-                    let sb = score_for(b);
-                    sa.cmp(&sb)
-                        // tie-break: prefer earlier declaration line to reduce
-                        // overload-related misses (stable + deterministic)
-                        .then_with(|| b.range.start_line.cmp(&a.range.start_line))
-                        .then_with(|| a.id.0.cmp(&b.id.0))
-                });
-        }
-
-        // Fallback: no same-name match → choose best symbol within the imported/qualified module
-        if best.is_none() {
-            let mut module_hints: Vec<String> = Vec::new();
-            if let Some(q) = qualifier {
-                module_hints.push(q.to_string());
-            }
-            if let Some(ip) = &imported_prefix
-                && !ip.is_empty()
-            {
-                module_hints.push(ip.clone());
-            }
-            for gp in &glob_prefixes {
-                if !module_hints.contains(gp) {
-                    module_hints.push(gp.clone());
-                }
-            }
-            if !module_hints.is_empty() {
-                let cands: Vec<&crate::ir::Symbol> = index
-                    .symbols
-                    .iter()
-                    .filter(|s| {
-                        matches!(
-                            s.kind,
-                            crate::ir::SymbolKind::Function | crate::ir::SymbolKind::Method
-                        )
-                    })
-                    .filter(|s| {
-                        module_hints
-                            .iter()
-                            .any(|mp| file_matches_module_path(&s.file, mp))
-                    })
-                    .collect();
-                if !cands.is_empty() {
-                    best = cands.into_iter().max_by(|a, b| {
-                        let score_for = |to_sym: &&crate::ir::Symbol| {
-                            let mut score = score_candidate(
-                                &r.file,
-                                qualifier,
-                                imported_prefix.as_deref(),
-                                to_sym,
-                                r.is_method,
-                            );
-                            for gp in &glob_prefixes {
-                                let s = score_candidate(
-                                    &r.file,
-                                    qualifier,
-                                    Some(gp.as_str()),
-                                    to_sym,
-                                    r.is_method,
-                                );
-                                if s > score {
-                                    score = s;
-                                }
-                            }
-                            score
-                        };
-                        let sa = score_for(a);
-                        let sb = score_for(b);
-                        sa.cmp(&sb)
-                            .then_with(|| b.range.start_line.cmp(&a.range.start_line))
-                            .then_with(|| a.id.0.cmp(&b.id.0))
-                    });
-                }
-            }
+                .filter(|seg| !seg.is_empty())
+                .collect::<Vec<_>>()
+                .join("::")
         }
+        None => imported(&r.name)?.clone(),
+    };
+    let path = path.replace('/', "::");
+    let root = path.split("::").next().unwrap_or_default();
+    let local = root.is_empty()
+        || matches!(root, "crate" | "self" | "super" | "Self")
+        || local_roots.contains(root)
+        || index.by_name.contains_key(root);
+    (!local).then_some(path)
+}
 
-        if let Some(to_sym) = best {
-            out.push(Reference {
-                from: from_sym.id.clone(),
-                to: to_sym.id.clone(),
-                kind: r.kind.clone(),
-                file: r.file.clone(),
-                line: r.line,
-                certainty: crate::ir::reference::EdgeCertainty::Inferred,
-                provenance: crate::ir::reference::EdgeProvenance::CallGraph,
-            });
-        }
+/// `index` plus the boundary symbols `refs` point to, which exist only as
+/// edge targets (see [`AnalyzeOptions::include_external`]).
+pub(crate) fn with_boundary_symbols(index: SymbolIndex, refs: &[Reference]) -> SymbolIndex {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let boundaries: Vec<Symbol> = refs
+        .iter()
+        .filter(|e| seen.insert(e.to.0.as_str()))
+        .filter_map(|e| Symbol::from_external_id(&e.to))
+        .collect();
+    if boundaries.is_empty() {
+        return index;
     }
-    out
+    let mut symbols = index.symbols;
+    symbols.extend(boundaries);
+    SymbolIndex::build(symbols)
 }
 
-fn function_is_method_compatible(language: &str) -> bool {
-    matches!(language, "ruby" | "python")
+/// Resolve a single reference site the way [`analyze_workspace`] does: an
+/// edge from the symbol enclosing `uref` to its best-scoring target, given
+/// every file's import map. `None` when the site is outside any symbol or
+/// nothing matches.
+pub fn resolve_one(
+    index: &SymbolIndex,
+    uref: &UnresolvedRef,
+    file_imports: &HashMap<String, HashMap<String, String>>,
+) -> Option<Reference> {
+    let packages = file_packages(file_imports);
+    let (from_sym, to_sym) = resolve_site(
+        index,
+        uref,
+        file_imports,
+        &packages,
+        &AnalyzeOptions::default(),
+    )?;
+    Some(reference_edge(uref, from_sym, to_sym))
 }
 
-fn score_candidate(
-    from_file: &str,
-    qualifier: Option<&str>,
-    imported_prefix: Option<&str>,
-    cand: &crate::ir::Symbol,
-    call_is_method: bool,
-) -> i32 {
-    let mut score = 0;
-    if cand.file == from_file {
-        score += 30;
-    }
-    // same directory
-    if std::path::Path::new(&cand.file).parent() == std::path::Path::new(from_file).parent() {
-        score += 10;
-    }
-    if let Some(q) = qualifier
-        && file_matches_module_path(&cand.file, q)
-    {
-        score += 20;
+/// The enclosing symbol of `r` and the symbol it resolves to.
+fn resolve_site<'a>(
+    index: &'a SymbolIndex,
+    r: &UnresolvedRef,
+    file_imports: &HashMap<String, HashMap<String, String>>,
+    packages: &HashMap<&str, &str>,
+    opts: &AnalyzeOptions,
+) -> Option<(&'a Symbol, &'a Symbol)> {
+    // find from symbol by containing line
+    let from_sym = index.enclosing_symbol(&r.file, r.line)?;
+    let to_sym = resolve_ref(index, r, file_imports, packages, opts, None)?;
+    Some((from_sym, to_sym))
+}
+
+fn reference_edge(r: &UnresolvedRef, from: &Symbol, to: &Symbol) -> Reference {
+    Reference {
+        from: from.id.clone(),
+        to: to.id.clone(),
+        kind: r.kind.clone(),
+        file: r.file.clone(),
+        line: r.line,
+        certainty: crate::ir::reference::EdgeCertainty::Inferred,
+        provenance: crate::ir::reference::EdgeProvenance::CallGraph,
     }
-    if let Some(ip) = imported_prefix
-        && !ip.is_empty()
-        && file_matches_module_path(&cand.file, ip)
+}
+
+/// The method of trait `tr` that `method` implements: the same-named
+/// function declared inside the trait, when `method` inherits from it
+/// through an `impl Trait for Type` block.
+fn implemented_trait_member<'a>(
+    index: &'a SymbolIndex,
+    method: &Symbol,
+    tr: &Symbol,
+) -> Option<&'a Symbol> {
+    use crate::ir::SymbolKind;
+    if !matches!(tr.kind, SymbolKind::Trait)
+        || !matches!(method.kind, SymbolKind::Function | SymbolKind::Method)
     {
-        score += 15;
+        return None;
     }
-    // prefer method symbol if call site looked like a method.
-    // Dynamic languages may represent methods as Function in some paths,
-    // so keep a language-scoped fallback.
-    if call_is_method {
-        if matches!(cand.kind, crate::ir::SymbolKind::Method) {
-            score += 25;
-        } else if matches!(cand.kind, crate::ir::SymbolKind::Function)
-            && function_is_method_compatible(&cand.language)
-        {
-            score += 20;
+    index.by_name.get(&method.name)?.iter().find(|s| {
+        s.id != method.id
+            && s.file == tr.file
+            && matches!(s.kind, SymbolKind::Function | SymbolKind::Method)
+            && s.range.start_line >= tr.range.start_line
+            && s.range.end_line <= tr.range.end_line
+    })
+}
+
+/// Which pass of [`resolve_references`] picked the target of a reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionStage {
+    /// Among symbols with the referenced name.
+    ByName,
+    /// No same-name match; the best symbol in the qualified/imported module.
+    ModuleFallback,
+    /// Nothing matched.
+    Unresolved,
+}
+
+/// One candidate target of a reference and how it scored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidateTrace {
+    pub symbol_id: String,
+    pub score: ScoreBreakdown,
+    pub total: i32,
+    /// Import or glob prefix the best score was computed against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+/// How one reference site was resolved: the normalized lookup inputs, every
+/// candidate with its score breakdown (best first) and the chosen target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionTrace {
+    pub file: String,
+    pub line: u32,
+    pub name: String,
+    /// Enclosing symbol of the reference site.
+    pub from: Option<String>,
+    pub is_method: bool,
+    /// Qualifier after applying import aliases.
+    pub qualifier: Option<String>,
+    /// Name looked up after import aliases and re-exports.
+    pub target_name: String,
+    pub imported_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glob_prefixes: Vec<String>,
+    pub stage: ResolutionStage,
+    pub candidates: Vec<CandidateTrace>,
+    pub resolved_to: Option<String>,
+}
+
+/// Resolve a single reference site to its target symbol. With `trace`, also
+/// record the inputs and candidate scores behind the decision.
+fn resolve_ref<'a>(
+    index: &'a SymbolIndex,
+    r: &UnresolvedRef,
+    file_imports: &HashMap<String, HashMap<String, String>>,
+    packages: &HashMap<&str, &str>,
+    opts: &AnalyzeOptions,
+    mut trace: Option<&mut ResolutionTrace>,
+) -> Option<&'a Symbol> {
+    // Determine candidate name, considering alias from imports
+    let imports = file_imports.get(&r.file).cloned().unwrap_or_default();
+    let mut target_name = r.name.as_str();
+    let qualifier = r.qualifier.as_deref();
+    // normalize qualifier using imports (handle alias on the first segment)
+    let from_mod = module_path_for_file(&r.file);
+    let norm_qual =
+        qualifier.and_then(|q| normalize_qualifier_with_imports(q, &imports, &from_mod));
+    let qualifier = norm_qual.as_deref().or(qualifier);
+    let mut imported_prefix: Option<String> = None;
+    let mut glob_prefixes: Vec<String> = imports
+        .iter()
+        .filter_map(|(k, v)| {
+            if k.starts_with("__glob__") {
+                Some(v.clone())
+            } else {
+                None
+            }
+        })
+        .collect();
+    if qualifier.is_none()
+        && let Some(full) = imports.get(&r.name)
+    {
+        let prior = full.rsplit_once("::").map(|(p, _)| p).unwrap_or("");
+        let ip =
+            if prior.contains("self::") || prior.contains("super::") || prior.contains("crate::") {
+                expand_relative_path(&from_mod, prior)
+            } else {
+                prior.to_string()
+            };
+        imported_prefix = Some(ip);
+        target_name = full.rsplit_once("::").map(|(_, n)| n).unwrap_or(full);
+    }
+
+    // Re-export fallback: if imported_prefix points to an aggregator module, try to map to the underlying module via its export map
+    if let Some(mut ip) = imported_prefix.clone() {
+        // resolve through aggregator chain (up to `reexport_depth` hops, guard cycles)
+        let mut visited: std::collections::HashSet<String> = std::collections::HashSet::new();
+        for _ in 0..opts.reexport_hops() {
+            if !visited.insert(ip.clone()) {
+                break;
+            }
+            let mut agg_files: Vec<&String> = file_imports
+                .keys()
+                .filter(|f| file_matches_module_path(f, &ip))
+                .collect();
+            if agg_files.len() > 1 {
+                agg_files.sort_by_key(|f| {
+                    if f.ends_with("/index.js")
+                        || f.ends_with("/index.ts")
+                        || f.ends_with("/index.tsx")
+                    {
+                        0
+                    } else {
+                        1
+                    }
+                });
+            }
+            let Some(agg_path) = agg_files.first() else {
+                break;
+            };
+            let Some(exp_map) = file_imports.get(*agg_path) else {
+                break;
+            };
+            // Rust `pub use` paths are relative to the re-exporting module
+            let agg_mod = module_path_for_file(agg_path);
+            let expand = |path: &str| {
+                if path.starts_with("self::")
+                    || path.starts_with("super::")
+                    || path.starts_with("crate::")
+                {
+                    expand_relative_path(&agg_mod, path)
+                } else {
+                    path.to_string()
+                }
+            };
+            for (k, v) in exp_map.iter() {
+                if k.starts_with("__export_glob__") {
+                    glob_prefixes.push(expand(v));
+                }
+            }
+            let key = format!("__export__{}", target_name);
+            if let Some(real) = exp_map.get(&key) {
+                ip = expand(real.rsplit_once("::").map(|(p, _)| p).unwrap_or(""));
+                imported_prefix = Some(ip.clone());
+                target_name = real.rsplit_once("::").map(|(_, n)| n).unwrap_or(real);
+                continue;
+            }
+            break;
+        }
+    }
+
+    // Best score over the imported prefix and every glob prefix, with the
+    // prefix that produced it
+    let score_for = |to_sym: &Symbol| -> (ScoreBreakdown, Option<&str>) {
+        let mut best = (
+            score_breakdown(
+                &r.file,
+                qualifier,
+                imported_prefix.as_deref(),
+                to_sym,
+                r.is_method,
+                packages,
+            ),
+            imported_prefix.as_deref().filter(|ip| !ip.is_empty()),
+        );
+        for gp in &glob_prefixes {
+            let s = score_breakdown(
+                &r.file,
+                qualifier,
+                Some(gp.as_str()),
+                to_sym,
+                r.is_method,
+                packages,
+            );
+            if s.total() > best.0.total() {
+                best = (s, Some(gp.as_str()));
+            }
+        }
+        best
+    };
+    let rank = |a: &&'a Symbol, b: &&'a Symbol| {
+        let sa = score_for(a).0.total();
+        let sb = score_for(b).0.total();
+        sa.cmp(&sb)
+            // tie-break: prefer earlier declaration line to reduce
+            // overload-related misses (stable + deterministic)
+            .then_with(|| b.range.start_line.cmp(&a.range.start_line))
+            .then_with(|| a.id.0.cmp(&b.id.0))
+    };
+    let mut record = |cands: &[&Symbol]| {
+        if let Some(t) = trace.as_deref_mut() {
+            for c in cands {
+                let (score, prefix) = score_for(c);
+                t.candidates.push(CandidateTrace {
+                    symbol_id: c.id.0.clone(),
+                    score,
+                    total: score.total(),
+                    prefix: prefix.map(str::to_string),
+                });
+            }
+        }
+    };
+
+    // Try candidates by exact name first
+    let mut best: Option<&Symbol> = None;
+    let mut stage = ResolutionStage::Unresolved;
+    if let Some(cands) = index.by_name.get(target_name) {
+        // If qualifier given, prefer candidates whose module path matches it
+        let filtered: Vec<&Symbol> = if let Some(q) = qualifier {
+            let v: Vec<_> = cands
+                .iter()
+                .filter(|s| file_in_module(&s.file, q, packages))
+                .collect();
+            if v.is_empty() {
+                cands.iter().collect()
+            } else {
+                v
+            }
+        } else {
+            cands.iter().collect()
+        };
+        let mut kept: Vec<&Symbol> = filtered
+            .into_iter()
+            .filter(|to_sym| ref_target_kind_matches(&r.kind, &to_sym.kind))
+            .collect();
+        if opts.strict_resolution && r.file.ends_with(".rs") && !r.is_method {
+            let modules: Vec<String> = match (qualifier, imported_prefix.as_deref()) {
+                (Some(q), _) => vec![rust_module_key(q)],
+                (None, Some(ip)) => vec![rust_module_key(ip)],
+                (None, None) => std::iter::once(from_mod.as_str())
+                    .chain(glob_prefixes.iter().map(String::as_str))
+                    .map(rust_module_key)
+                    .collect(),
+            };
+            let exact: Vec<&Symbol> = kept
+                .iter()
+                .copied()
+                .filter(|s| {
+                    s.file.ends_with(".rs")
+                        && modules.contains(&rust_module_key(&module_path_for_file(&s.file)))
+                })
+                .collect();
+            if !exact.is_empty() {
+                kept = exact;
+            }
+        }
+        record(&kept);
+        best = kept.into_iter().max_by(rank);
+        if best.is_some() {
+            stage = ResolutionStage::ByName;
+        }
+    }
+
+    // Fallback: no same-name match → choose best symbol within the imported/qualified module
+    if best.is_none() {
+        let mut module_hints: Vec<String> = Vec::new();
+        if let Some(q) = qualifier {
+            module_hints.push(q.to_string());
+        }
+        if let Some(ip) = &imported_prefix
+            && !ip.is_empty()
+        {
+            module_hints.push(ip.clone());
+        }
+        for gp in &glob_prefixes {
+            if !module_hints.contains(gp) {
+                module_hints.push(gp.clone());
+            }
+        }
+        if !module_hints.is_empty() {
+            let cands: Vec<&Symbol> = index
+                .symbols
+                .iter()
+                .filter(|s| ref_target_kind_matches(&r.kind, &s.kind))
+                .filter(|s| {
+                    module_hints
+                        .iter()
+                        .any(|mp| file_in_module(&s.file, mp, packages))
+                })
+                .collect();
+            record(&cands);
+            best = cands.into_iter().max_by(rank);
+            if best.is_some() {
+                stage = ResolutionStage::ModuleFallback;
+            }
+        }
+    }
+
+    if let Some(t) = trace {
+        t.qualifier = qualifier.map(str::to_string);
+        t.target_name = target_name.to_string();
+        t.imported_prefix = imported_prefix.clone();
+        t.glob_prefixes = glob_prefixes.clone();
+        t.stage = stage;
+        t.resolved_to = best.map(|s| s.id.0.clone());
+        t.candidates.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| a.symbol_id.cmp(&b.symbol_id))
+        });
+    }
+    best
+}
+
+/// Symbol kinds a reference of `ref_kind` can resolve to.
+fn ref_target_kind_matches(ref_kind: &RefKind, kind: &crate::ir::SymbolKind) -> bool {
+    use crate::ir::SymbolKind;
+    match ref_kind {
+        RefKind::TypeUse => matches!(
+            kind,
+            SymbolKind::Struct
+                | SymbolKind::Enum
+                | SymbolKind::Trait
+                | SymbolKind::TypeAlias
+                | SymbolKind::Macro
+        ),
+        RefKind::Inherit => matches!(kind, SymbolKind::Trait | SymbolKind::Struct),
+        RefKind::Call | RefKind::Data | RefKind::Control => {
+            matches!(
+                kind,
+                SymbolKind::Function | SymbolKind::Method | SymbolKind::Macro
+            )
+        }
+    }
+}
+
+fn function_is_method_compatible(language: &str) -> bool {
+    matches!(language, "ruby" | "python")
+}
+
+/// Points a reference candidate earned, by reason (see
+/// [`ResolutionTrace`]); the candidate with the highest [`total`] wins.
+///
+/// [`total`]: ScoreBreakdown::total
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// +30 when the candidate is in the referencing file.
+    pub same_file: i32,
+    /// +10 when it is in the same directory.
+    pub same_dir: i32,
+    /// +20 when its file matches the reference's qualifier.
+    pub qualifier: i32,
+    /// +15 when its file matches the imported (or glob) prefix.
+    pub import_prefix: i32,
+    /// +25 for a method at a method call (+20 for a Ruby/Python function),
+    /// +5 for a function at a plain call.
+    pub kind: i32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> i32 {
+        self.same_file + self.same_dir + self.qualifier + self.import_prefix + self.kind
+    }
+}
+
+fn score_breakdown(
+    from_file: &str,
+    qualifier: Option<&str>,
+    imported_prefix: Option<&str>,
+    cand: &crate::ir::Symbol,
+    call_is_method: bool,
+    packages: &HashMap<&str, &str>,
+) -> ScoreBreakdown {
+    let mut score = ScoreBreakdown::default();
+    if cand.file == from_file {
+        score.same_file = 30;
+    }
+    // same directory
+    if std::path::Path::new(&cand.file).parent() == std::path::Path::new(from_file).parent() {
+        score.same_dir = 10;
+    }
+    if let Some(q) = qualifier
+        && file_in_module(&cand.file, q, packages)
+    {
+        score.qualifier = 20;
+    }
+    if let Some(ip) = imported_prefix
+        && !ip.is_empty()
+        && file_in_module(&cand.file, ip, packages)
+    {
+        score.import_prefix = 15;
+    }
+    // prefer method symbol if call site looked like a method.
+    // Dynamic languages may represent methods as Function in some paths,
+    // so keep a language-scoped fallback.
+    if call_is_method {
+        if matches!(cand.kind, crate::ir::SymbolKind::Method) {
+            score.kind = 25;
+        } else if matches!(cand.kind, crate::ir::SymbolKind::Function)
+            && function_is_method_compatible(&cand.language)
+        {
+            score.kind = 20;
         }
     } else if matches!(cand.kind, crate::ir::SymbolKind::Function) {
-        score += 5;
+        score.kind = 5;
     }
     score
 }
 
+/// `package` declarations of JVM files, from the `__package__` entry their
+/// analyzers put in the import map.
+fn file_packages(file_imports: &HashMap<String, HashMap<String, String>>) -> HashMap<&str, &str> {
+    file_imports
+        .iter()
+        .filter_map(|(file, im)| Some((file.as_str(), im.get("__package__")?.as_str())))
+        .collect()
+}
+
+/// [`file_matches_module_path`], also accepting JVM files whose `package`
+/// is the module (`import a.b.helper` for a top-level Kotlin function) or
+/// whose package-based module path is it (`a::b::Util` for `Util.kt`).
+fn file_in_module(file: &str, module_path: &str, packages: &HashMap<&str, &str>) -> bool {
+    if file_matches_module_path(file, module_path) {
+        return true;
+    }
+    let Some(pkg) = packages.get(file) else {
+        return false;
+    };
+    let module_path = module_path.replace('/', "::");
+    *pkg == module_path || module_path_for_package_file(file, pkg) == module_path
+}
+
 fn file_matches_module_path(file: &str, module_path: &str) -> bool {
     if module_path.is_empty() {
         return false;
@@ -2321,7 +3092,10 @@ fn file_matches_module_path(file: &str, module_path: &str) -> bool {
     };
     // Match either <base> with supported extensions (and Rust mod.rs),
     // JS/TS index files, and Python package entry files.
-    file_norm.ends_with(&(base.clone() + ".rs"))
+    C_FAMILY_EXTS
+        .iter()
+        .any(|ext| file_norm.ends_with(&format!("{base}{ext}")))
+        || file_norm.ends_with(&(base.clone() + ".rs"))
         || file_norm.ends_with(&(base.clone() + ".rb"))
         || file_norm.ends_with(&(base.clone() + ".js"))
         || file_norm.ends_with(&(base.clone() + ".ts"))
@@ -2329,6 +3103,7 @@ fn file_matches_module_path(file: &str, module_path: &str) -> bool {
         || file_norm.ends_with(&(base.clone() + ".py"))
         || file_norm.ends_with(&(base.clone() + ".go"))
         || file_norm.ends_with(&(base.clone() + ".java"))
+        || file_norm.ends_with(&(base.clone() + ".kt"))
         || file_norm.ends_with(&(base.clone() + "/index.js"))
         || file_norm.ends_with(&(base.clone() + "/index.ts"))
         || file_norm.ends_with(&(base.clone() + "/index.tsx"))
@@ -2360,6 +3135,11 @@ fn normalize_qualifier_with_imports(
     }
 }
 
+/// C/C++ source and header extensions. A header and the source files sharing
+/// its stem map to one module path, which is how `#include "util.h"` reaches
+/// the definitions in `util.c`/`util.cpp`.
+const C_FAMILY_EXTS: [&str; 8] = [".c", ".h", ".cc", ".cpp", ".cxx", ".hh", ".hpp", ".hxx"];
+
 pub fn module_path_for_file(file: &str) -> String {
     let mut p = std::path::Path::new(file);
     // strip leading ./ if any
@@ -2387,6 +3167,7 @@ pub fn module_path_for_file(file: &str) -> String {
         || s.ends_with(".py")
         || s.ends_with(".go")
         || s.ends_with(".java")
+        || s.ends_with(".kt")
     {
         let no_ext = s
             .trim_end_matches(".rs")
@@ -2396,12 +3177,31 @@ pub fn module_path_for_file(file: &str) -> String {
             .trim_end_matches(".tsx")
             .trim_end_matches(".py")
             .trim_end_matches(".go")
-            .trim_end_matches(".java");
+            .trim_end_matches(".java")
+            .trim_end_matches(".kt");
+        return no_ext.replace('/', "::");
+    }
+    if let Some(no_ext) = C_FAMILY_EXTS.iter().find_map(|ext| s.strip_suffix(ext)) {
         return no_ext.replace('/', "::");
     }
     s.replace('/', "::")
 }
 
+/// Module path of a JVM file from its `package` declaration instead of its
+/// directory, which Kotlin does not tie to the package: `package a.b` (as
+/// `a::b`) in `src/Util.kt` gives `a::b::Util`.
+pub fn module_path_for_package_file(file: &str, package: &str) -> String {
+    let stem = std::path::Path::new(file)
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    if package.is_empty() {
+        stem.into_owned()
+    } else {
+        format!("{package}::{stem}")
+    }
+}
+
 fn expand_relative_path(current_mod: &str, path: &str) -> String {
     if path.starts_with("crate::") {
         return path.trim_start_matches("crate::").to_string();
@@ -2427,23 +3227,31 @@ fn expand_relative_path(current_mod: &str, path: &str) -> String {
     }
 }
 
-pub fn compute_impact(
-    changed: &[Symbol],
-    index: &SymbolIndex,
-    refs: &[Reference],
-    opts: &ImpactOptions,
-) -> ImpactOutput {
-    let by_id: HashMap<&str, &Symbol> =
-        index.symbols.iter().map(|s| (s.id.0.as_str(), s)).collect();
+/// A Rust module path relative to its crate, so that file-derived paths
+/// (`src::util`, `crates::core::src::util`) and `crate::`/`use` paths
+/// (`util`) compare equal: everything up to the last `src` segment, then a
+/// leading `crate`, is dropped.
+fn rust_module_key(module_path: &str) -> String {
+    let mut segs: Vec<&str> = module_path.split("::").filter(|s| !s.is_empty()).collect();
+    if let Some(i) = segs.iter().rposition(|s| *s == "src") {
+        segs.drain(..=i);
+    }
+    if segs.first() == Some(&"crate") {
+        segs.remove(0);
+    }
+    segs.join("::")
+}
+
+type Adjacency<'a> = HashMap<&'a str, Vec<&'a Reference>>;
 
-    // Build adjacency maps
-    let mut fwd: HashMap<&str, Vec<&Reference>> = HashMap::new(); // from -> [edge]
-    let mut rev: HashMap<&str, Vec<&Reference>> = HashMap::new(); // to -> [edge]
+/// Build forward (from -> [edge]) and reverse (to -> [edge]) adjacency maps
+/// with edges in deterministic order.
+fn build_adjacency(refs: &[Reference]) -> (Adjacency<'_>, Adjacency<'_>) {
+    let mut fwd: Adjacency<'_> = HashMap::new();
+    let mut rev: Adjacency<'_> = HashMap::new();
     for e in refs {
-        let from = e.from.0.as_str();
-        let to = e.to.0.as_str();
-        fwd.entry(from).or_default().push(e);
-        rev.entry(to).or_default().push(e);
+        fwd.entry(e.from.0.as_str()).or_default().push(e);
+        rev.entry(e.to.0.as_str()).or_default().push(e);
     }
     for edges in fwd.values_mut() {
         edges.sort_by_key(|edge| reference_sort_key(edge));
@@ -2451,6 +3259,61 @@ pub fn compute_impact(
     for edges in rev.values_mut() {
         edges.sort_by_key(|edge| reference_sort_key(edge));
     }
+    (fwd, rev)
+}
+
+/// Symbols reached from `changed` over `refs` per `opts`. Pure over its
+/// inputs: symbols are looked up in `index` and the traversal only follows
+/// `refs`, so it never reads the workspace.
+pub fn compute_impact(
+    changed: &[Symbol],
+    index: &SymbolIndex,
+    refs: &[Reference],
+    opts: &ImpactOptions,
+) -> ImpactOutput {
+    let _timer = crate::profile::start(crate::profile::Phase::Impact);
+    let by_id: HashMap<&str, &Symbol> =
+        index.symbols.iter().map(|s| (s.id.0.as_str(), s)).collect();
+
+    let language_selected = |language: &str| {
+        opts.languages.is_empty()
+            || LanguageKind::from_name(language).is_some_and(|k| opts.languages.contains(&k))
+    };
+    let selected: Vec<Symbol>;
+    let changed = if opts.languages.is_empty() {
+        changed
+    } else {
+        selected = changed
+            .iter()
+            .filter(|s| language_selected(&s.language))
+            .cloned()
+            .collect();
+        &selected
+    };
+    let in_languages = |id: &str| {
+        by_id
+            .get(id)
+            .is_none_or(|sym| language_selected(&sym.language))
+    };
+
+    let kept: Vec<Reference>;
+    let refs = if opts.edge_kinds.is_empty() && opts.languages.is_empty() {
+        refs
+    } else {
+        kept = refs
+            .iter()
+            .filter(|e| {
+                (opts.edge_kinds.is_empty()
+                    || matches!(e.kind, RefKind::Data | RefKind::Control)
+                    || opts.edge_kinds.contains(&e.kind))
+                    && in_languages(&e.from.0)
+                    && in_languages(&e.to.0)
+            })
+            .cloned()
+            .collect();
+        &kept
+    };
+    let (fwd, rev) = build_adjacency(refs);
 
     let changed_ids: HashSet<String> = changed.iter().map(|s| s.id.0.clone()).collect();
 
@@ -2459,6 +3322,12 @@ pub fn compute_impact(
     let mut witness_candidates_by_symbol_id: HashMap<String, WitnessCandidate> = HashMap::new();
     let mut reached_changed_via_callees: HashSet<String> = HashSet::new();
     let mut q: VecDeque<(String, usize)> = VecDeque::new();
+    let mut truncated = false;
+    let mut tests = TestDetector {
+        root: opts.root.clone(),
+        ..Default::default()
+    };
+    let mut test_ids: HashMap<String, bool> = HashMap::new();
     // Seed queue with non-ignored changed symbols
     for s in changed {
         if !path_is_ignored(&s.file, &opts.ignore_dirs) {
@@ -2466,7 +3335,12 @@ pub fn compute_impact(
             q.push_back((s.id.0.clone(), 0));
         }
     }
+    let budget = TimeBudget::start(opts);
     while let Some((cur, d)) = q.pop_front() {
+        if budget.is_spent() {
+            truncated = true;
+            break;
+        }
         if min_depth_by_symbol_id
             .get(cur.as_str())
             .is_some_and(|best| d > *best)
@@ -2478,6 +3352,10 @@ pub fn compute_impact(
         {
             continue;
         }
+        // Boundary symbols are reported but lead nowhere inside the workspace
+        if by_id.get(cur.as_str()).is_some_and(|s| s.is_external()) {
+            continue;
+        }
 
         let current_root = witness_candidates_by_symbol_id
             .get(cur.as_str())
@@ -2489,7 +3367,23 @@ pub fn compute_impact(
             .unwrap_or_default();
 
         let mut consider_edge = |edge: &Reference, next_symbol_id: &str| {
-            let next_depth = d + 1;
+            if opts.exclude_tests && !changed_ids.contains(next_symbol_id) {
+                let is_test = *test_ids
+                    .entry(next_symbol_id.to_string())
+                    .or_insert_with(|| {
+                        by_id
+                            .get(next_symbol_id)
+                            .is_some_and(|sym| tests.is_test(sym))
+                    });
+                if is_test {
+                    return;
+                }
+            }
+            let weight = opts.edge_weights.get(&edge.kind).copied().unwrap_or(1);
+            let next_depth = d + weight.max(1);
+            if opts.max_depth.is_some_and(|maxd| next_depth > maxd) {
+                return;
+            }
             record_min_depth(&mut summary_depth_by_symbol_id, next_symbol_id, next_depth);
 
             let mut candidate_path = current_path.clone();
@@ -2515,6 +3409,14 @@ pub fn compute_impact(
             }
 
             let should_enqueue = match min_depth_by_symbol_id.get(next_symbol_id).copied() {
+                None if opts
+                    .limits
+                    .max_visited
+                    .is_some_and(|max| min_depth_by_symbol_id.len() >= max) =>
+                {
+                    truncated = true;
+                    false
+                }
                 None => {
                     record_min_depth(&mut min_depth_by_symbol_id, next_symbol_id, next_depth);
                     true
@@ -2631,7 +3533,7 @@ pub fn compute_impact(
         })
         .collect();
 
-    let edges = if opts.with_edges.unwrap_or(false) {
+    let mut edges = if opts.with_edges.unwrap_or(false) {
         // Keep the primary relationship graph for changed+impacted nodes.
         // For callees mode we avoid inbound context edges from outside the explored node set
         // to keep oracle comparison stable (e.g. exclude f10->f09 when f10 is outside scope).
@@ -2689,14 +3591,516 @@ pub fn compute_impact(
     } else {
         Vec::new()
     };
+    if matches!(opts.direction, ImpactDirection::Both) {
+        dedup_mirrored_edges(&mut edges);
+    }
 
-    finalize_impact_output(
+    let mut out = finalize_impact_output(
         changed.to_vec(),
         impacted_symbols,
         edges,
         &summary_depth_by_symbol_id,
         impacted_witnesses,
-    )
+    );
+    out.summary.truncated = truncated;
+    out.edges_included = opts.with_edges.unwrap_or(false);
+    out
+}
+
+/// [`compute_impact`] over a graph the caller built, e.g. from its own
+/// parser: symbols via [`Symbol::new`] and [`SymbolIndex::build`], edges via
+/// [`Reference::new`]. Touches neither the filesystem nor the cache.
+pub fn impact_over(
+    index: &SymbolIndex,
+    refs: &[Reference],
+    seeds: &[Symbol],
+    opts: &ImpactOptions,
+) -> ImpactOutput {
+    compute_impact(seeds, index, refs, opts)
+}
+
+/// The whole resolved project graph: every indexed symbol plus every
+/// reference edge, for tools that want the raw call graph.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProjectGraph {
+    pub nodes: Vec<Symbol>,
+    pub edges: Vec<Reference>,
+}
+
+/// Flatten an index and its references into a [`ProjectGraph`]. Nodes are
+/// sorted by id and edges normalized, so the export is deterministic.
+pub fn export_project_graph(index: &SymbolIndex, refs: &[Reference]) -> ProjectGraph {
+    let mut nodes = index.symbols.clone();
+    nodes.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    nodes.dedup_by(|a, b| a.id == b.id);
+    let mut edges = refs.to_vec();
+    normalize_edges(&mut edges);
+    ProjectGraph { nodes, edges }
+}
+
+/// Per-symbol degree centrality over the resolved reference graph.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SymbolMetrics {
+    pub id: crate::ir::SymbolId,
+    pub name: String,
+    pub file: String,
+    /// Number of distinct symbols referencing this one (callers).
+    pub in_degree: usize,
+    /// Number of distinct symbols this one references (callees).
+    pub out_degree: usize,
+}
+
+/// Compute in/out-degree for every indexed symbol.
+///
+/// Results are sorted by in-degree (descending), then out-degree (descending),
+/// then symbol id, so the riskiest symbols to change come first.
+pub fn compute_symbol_metrics(index: &SymbolIndex, refs: &[Reference]) -> Vec<SymbolMetrics> {
+    let (fwd, rev) = build_adjacency(refs);
+    let distinct = |edges: Option<&Vec<&Reference>>, pick: fn(&Reference) -> &str| -> usize {
+        edges
+            .map(|edges| edges.iter().map(|e| pick(e)).collect::<HashSet<_>>().len())
+            .unwrap_or(0)
+    };
+    let mut metrics: Vec<SymbolMetrics> = index
+        .symbols
+        .iter()
+        .map(|s| SymbolMetrics {
+            id: s.id.clone(),
+            name: s.name.clone(),
+            file: s.file.clone(),
+            in_degree: distinct(rev.get(s.id.0.as_str()), |e| e.from.0.as_str()),
+            out_degree: distinct(fwd.get(s.id.0.as_str()), |e| e.to.0.as_str()),
+        })
+        .collect();
+    metrics.sort_by(|a, b| {
+        b.in_degree
+            .cmp(&a.in_degree)
+            .then(b.out_degree.cmp(&a.out_degree))
+            .then_with(|| a.id.0.cmp(&b.id.0))
+    });
+    metrics.dedup_by(|a, b| a.id == b.id);
+    metrics
+}
+
+/// Detect call cycles in the reference graph.
+///
+/// Runs an iterative Tarjan SCC pass (no recursion, so deep graphs cannot
+/// overflow the stack) and returns every strongly-connected component with
+/// more than one symbol, plus symbols that reference themselves. Each cycle is
+/// sorted by symbol id and the list of cycles is sorted for deterministic output.
+pub fn find_cycles(index: &SymbolIndex, refs: &[Reference]) -> Vec<Vec<crate::ir::SymbolId>> {
+    let mut ids: Vec<&str> = index.symbols.iter().map(|s| s.id.0.as_str()).collect();
+    ids.extend(
+        refs.iter()
+            .flat_map(|e| [e.from.0.as_str(), e.to.0.as_str()]),
+    );
+    ids.sort_unstable();
+    ids.dedup();
+    let pos: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let n = ids.len();
+    let mut adj: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut self_loop = vec![false; n];
+    for e in refs {
+        let from = pos[e.from.0.as_str()];
+        let to = pos[e.to.0.as_str()];
+        if from == to {
+            self_loop[from] = true;
+        }
+        adj[from].push(to);
+    }
+    for succ in adj.iter_mut() {
+        succ.sort_unstable();
+        succ.dedup();
+    }
+
+    const UNVISITED: usize = usize::MAX;
+    let mut order = vec![UNVISITED; n];
+    let mut low = vec![0usize; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut next_order = 0usize;
+    let mut cycles: Vec<Vec<crate::ir::SymbolId>> = Vec::new();
+
+    for root in 0..n {
+        if order[root] != UNVISITED {
+            continue;
+        }
+        // DFS frames: (node, index of the next successor to visit)
+        let mut frames: Vec<(usize, usize)> = vec![(root, 0)];
+        order[root] = next_order;
+        low[root] = next_order;
+        next_order += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(frame) = frames.last_mut() {
+            let v = frame.0;
+            if let Some(&w) = adj[v].get(frame.1) {
+                frame.1 += 1;
+                if order[w] == UNVISITED {
+                    order[w] = next_order;
+                    low[w] = next_order;
+                    next_order += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    frames.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(order[w]);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] != order[v] {
+                continue;
+            }
+            let mut component: Vec<usize> = Vec::new();
+            while let Some(w) = stack.pop() {
+                on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            if component.len() > 1 || self_loop[v] {
+                let mut cycle: Vec<crate::ir::SymbolId> = component
+                    .into_iter()
+                    .map(|i| crate::ir::SymbolId(ids[i].to_string()))
+                    .collect();
+                cycle.sort_by(|a, b| a.0.cmp(&b.0));
+                cycles.push(cycle);
+            }
+        }
+    }
+
+    cycles.sort_by(|a, b| a.iter().map(|s| &s.0).cmp(b.iter().map(|s| &s.0)));
+    cycles
+}
+
+/// Options for [`find_dead_code`].
+#[derive(Debug, Clone, Default)]
+pub struct DeadCodeOptions {
+    /// Treat public API symbols (`pub`, `export`, `public`, exported Go names)
+    /// as roots: they may be used from outside the workspace.
+    pub pub_as_roots: bool,
+    /// Leave symbols declared in test files out of the result.
+    pub exclude_test_files: bool,
+    /// Workspace root that symbol files are read from (the current directory
+    /// when unset).
+    pub root: Option<PathBuf>,
+}
+
+/// Whether `path` looks like a test file by common per-language conventions.
+pub fn is_test_path(path: &str) -> bool {
+    let path = normalize_path(&path.replace('\\', "/"), Path::new("."));
+    if path
+        .split('/')
+        .any(|seg| matches!(seg, "tests" | "test" | "spec" | "__tests__"))
+    {
+        return true;
+    }
+    let file = path.rsplit('/').next().unwrap_or(&path);
+    let stem = file.split('.').next().unwrap_or(file);
+    stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_spec")
+        || stem.ends_with("Test")
+        || file.contains(".test.")
+        || file.contains(".spec.")
+}
+
+/// Declaration line of `sym` plus the lines just above it (for attributes
+/// and decorators), read from the working tree.
+fn declaration_context<'a>(lines: &'a [String], sym: &Symbol) -> (&'a str, Vec<&'a str>) {
+    let idx = (sym.range.start_line as usize).saturating_sub(1);
+    let decl = lines.get(idx).map(String::as_str).unwrap_or("");
+    let above = lines[idx.saturating_sub(3).min(lines.len())..idx.min(lines.len())]
+        .iter()
+        .map(|l| l.trim())
+        .collect();
+    (decl, above)
+}
+
+/// Test-function detection shared by [`find_impacted_tests`] and
+/// [`ImpactOptions::exclude_tests`]; source files are read once and cached.
+#[derive(Default)]
+struct TestDetector {
+    root: Option<PathBuf>,
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl TestDetector {
+    fn is_test(&mut self, s: &Symbol) -> bool {
+        use crate::ir::SymbolKind;
+        if !matches!(s.kind, SymbolKind::Function | SymbolKind::Method) {
+            return false;
+        }
+        if is_test_path(&s.file) {
+            return true;
+        }
+        let lines = self.sources.entry(s.file.clone()).or_insert_with(|| {
+            fs::read_to_string(workspace_file(self.root.as_deref(), &s.file))
+                .map(|src| src.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        let (_, attrs) = declaration_context(lines, s);
+        is_test_symbol(s, &attrs)
+    }
+}
+
+fn is_test_symbol(sym: &Symbol, attrs: &[&str]) -> bool {
+    let name = sym.name.rsplit("::").next().unwrap_or(&sym.name);
+    name.starts_with("test_")
+        || (sym.language == "go" && name.starts_with("Test"))
+        || attrs
+            .iter()
+            .any(|a| a.starts_with("#[test") || a.ends_with("::test]") || a.starts_with("@Test"))
+}
+
+fn is_public_symbol(sym: &Symbol, decl: &str) -> bool {
+    let decl = decl.trim_start();
+    let name = sym.name.rsplit("::").next().unwrap_or(&sym.name);
+    match sym.language.as_str() {
+        "rust" => decl.starts_with("pub ") || decl.starts_with("pub("),
+        "javascript" | "typescript" | "tsx" => decl.starts_with("export "),
+        "java" => decl.split_whitespace().any(|w| w == "public"),
+        // Kotlin declarations are public unless marked otherwise
+        "kotlin" => !decl
+            .split_whitespace()
+            .any(|w| matches!(w, "private" | "internal" | "protected")),
+        "go" => name.chars().next().is_some_and(char::is_uppercase),
+        // file-local `static` functions are the only ones no other unit can call
+        "cpp" => !decl.starts_with("static "),
+        "python" => !name.starts_with('_'),
+        // Ruby methods are public unless declared under `private`, which the
+        // declaration line alone can't tell
+        "ruby" => true,
+        _ => false,
+    }
+}
+
+/// Functions and methods that nothing in the graph references.
+///
+/// Entry points are never reported: `main`, test functions (by name or
+/// `#[test]`/`@Test` attribute), and public API symbols when
+/// [`DeadCodeOptions::pub_as_roots`] is set. Results are sorted by file and line.
+pub fn find_dead_code(
+    index: &SymbolIndex,
+    refs: &[Reference],
+    opts: &DeadCodeOptions,
+) -> Vec<Symbol> {
+    use crate::ir::SymbolKind;
+    let (_, rev) = build_adjacency(refs);
+    let mut sources: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut dead: Vec<Symbol> = index
+        .symbols
+        .iter()
+        .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
+        .filter(|s| {
+            // Recursion alone doesn't keep a symbol alive
+            !rev.get(s.id.0.as_str())
+                .is_some_and(|edges| edges.iter().any(|e| e.from != s.id))
+        })
+        .filter(|s| !(opts.exclude_test_files && is_test_path(&s.file)))
+        .filter(|s| {
+            let name = s.name.rsplit("::").next().unwrap_or(&s.name);
+            if name == "main" {
+                return false;
+            }
+            let lines = sources.entry(s.file.as_str()).or_insert_with(|| {
+                fs::read_to_string(workspace_file(opts.root.as_deref(), &s.file))
+                    .map(|src| src.lines().map(str::to_string).collect())
+                    .unwrap_or_default()
+            });
+            let (decl, attrs) = declaration_context(lines, s);
+            let is_root =
+                is_test_symbol(s, &attrs) || (opts.pub_as_roots && is_public_symbol(s, decl));
+            !is_root
+        })
+        .cloned()
+        .collect();
+    dead.sort_by(|a, b| {
+        (a.file.as_str(), a.range.start_line, a.id.0.as_str()).cmp(&(
+            b.file.as_str(),
+            b.range.start_line,
+            b.id.0.as_str(),
+        ))
+    });
+    dead.dedup_by(|a, b| a.id == b.id);
+    dead
+}
+
+/// A test that a change reaches, with the changed symbols that reach it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImpactedTest {
+    pub test: Symbol,
+    pub reached_from: Vec<crate::ir::SymbolId>,
+}
+
+/// Tests among the changed symbols and their callers, for targeted test runs.
+///
+/// A test is a function or method in a test file (see [`is_test_path`]), named
+/// `test_*` (`Test*` in Go), or carrying a `#[test]`/`@Test` attribute. Each
+/// changed symbol is traversed on its own (callers only, honoring the depth,
+/// ignore and edge-kind settings of `opts`) so that every test lists exactly
+/// the changes that reach it. Results are sorted by file and line.
+pub fn find_impacted_tests(
+    changed: &[Symbol],
+    index: &SymbolIndex,
+    refs: &[Reference],
+    opts: &ImpactOptions,
+) -> Vec<ImpactedTest> {
+    let opts = ImpactOptions {
+        direction: ImpactDirection::Callers,
+        with_edges: Some(false),
+        exclude_tests: false,
+        ..opts.clone()
+    };
+    let mut tests = TestDetector {
+        root: opts.root.clone(),
+        ..Default::default()
+    };
+    let mut by_test: HashMap<String, ImpactedTest> = HashMap::new();
+    for seed in changed {
+        let out = compute_impact(std::slice::from_ref(seed), index, refs, &opts);
+        for sym in std::iter::once(seed).chain(out.impacted_symbols.iter()) {
+            if !tests.is_test(sym) {
+                continue;
+            }
+            let entry = by_test
+                .entry(sym.id.0.clone())
+                .or_insert_with(|| ImpactedTest {
+                    test: sym.clone(),
+                    reached_from: Vec::new(),
+                });
+            if !entry.reached_from.contains(&seed.id) {
+                entry.reached_from.push(seed.id.clone());
+            }
+        }
+    }
+    let mut tests: Vec<ImpactedTest> = by_test.into_values().collect();
+    for t in &mut tests {
+        t.reached_from.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    tests.sort_by(|a, b| {
+        (
+            a.test.file.as_str(),
+            a.test.range.start_line,
+            a.test.id.0.as_str(),
+        )
+            .cmp(&(
+                b.test.file.as_str(),
+                b.test.range.start_line,
+                b.test.id.0.as_str(),
+            ))
+    });
+    tests
+}
+
+/// Upper bound on the lines embedded per symbol by [`snippets_for`].
+pub const MAX_SNIPPET_LINES: usize = 20;
+
+/// Source text of each symbol's range, keyed by symbol id.
+///
+/// Ranges longer than [`MAX_SNIPPET_LINES`] are truncated; symbols whose file
+/// can't be read (or whose range lies outside it) are left out.
+pub fn snippets_for<'a>(
+    root: Option<&Path>,
+    symbols: impl IntoIterator<Item = &'a Symbol>,
+) -> HashMap<String, String> {
+    let mut sources: HashMap<&str, Option<Vec<String>>> = HashMap::new();
+    let mut out = HashMap::new();
+    for s in symbols {
+        let Some(lines) = sources
+            .entry(s.file.as_str())
+            .or_insert_with(|| {
+                fs::read_to_string(workspace_file(root, &s.file))
+                    .ok()
+                    .map(|src| src.lines().map(str::to_string).collect())
+            })
+            .as_ref()
+        else {
+            continue;
+        };
+        let start = s.range.start_line.max(1) as usize - 1;
+        if start >= lines.len() {
+            continue;
+        }
+        let end = (s.range.end_line as usize)
+            .clamp(start + 1, start + MAX_SNIPPET_LINES)
+            .min(lines.len());
+        out.insert(s.id.0.clone(), lines[start..end].join("\n"));
+    }
+    out
+}
+
+/// Fill [`ImpactOutput::snippets`] for every changed and impacted symbol,
+/// reading their files under `root`.
+pub fn attach_snippets(output: &mut ImpactOutput, root: Option<&Path>) {
+    output.snippets = snippets_for(
+        root,
+        output
+            .changed_symbols
+            .iter()
+            .chain(output.impacted_symbols.iter()),
+    );
+}
+
+/// Keep at most `max` impacted symbols, the nearest first (by witness depth,
+/// then id), dropping the rest from every per-symbol view of `output`. The
+/// summary counts describe what is kept; [`ImpactSummary::total_impacted`]
+/// records the full count. A no-op when `max` is not exceeded.
+pub fn cap_impacted_symbols(output: &mut ImpactOutput, max: usize) {
+    let total = output.impacted_symbols.len();
+    if total <= max {
+        return;
+    }
+    let depth_of = |s: &Symbol| {
+        output
+            .impacted_witnesses
+            .get(&s.id.0)
+            .map_or(usize::MAX, |w| w.depth)
+    };
+    let mut ranked: Vec<(usize, Symbol)> = output
+        .impacted_symbols
+        .drain(..)
+        .map(|s| (depth_of(&s), s))
+        .collect();
+    ranked.sort_by(|(da, a), (db, b)| da.cmp(db).then_with(|| a.id.0.cmp(&b.id.0)));
+    ranked.truncate(max);
+    output.impacted_symbols = ranked.into_iter().map(|(_, s)| s).collect();
+
+    let kept: HashSet<&str> = output
+        .changed_symbols
+        .iter()
+        .chain(output.impacted_symbols.iter())
+        .map(|s| s.id.0.as_str())
+        .collect();
+    output
+        .impacted_witnesses
+        .retain(|id, _| kept.contains(id.as_str()));
+    output.snippets.retain(|id, _| kept.contains(id.as_str()));
+    output
+        .edges
+        .retain(|e| kept.contains(e.from.0.as_str()) && kept.contains(e.to.0.as_str()));
+    for symbols in output.impacted_by_file.values_mut() {
+        symbols.retain(|s| kept.contains(s.id.0.as_str()));
+    }
+    output
+        .impacted_by_file
+        .retain(|_, symbols| !symbols.is_empty());
+    output
+        .impacted_files
+        .retain(|f| output.impacted_by_file.contains_key(f));
+
+    output.summary.impacted_symbol_count = output.impacted_symbols.len();
+    output.summary.impacted_file_count = output.impacted_files.len();
+    output.summary.truncated = true;
+    output.summary.total_impacted = Some(total);
 }
 
 #[cfg(test)]
@@ -2706,26 +4110,337 @@ mod tests {
     use tempfile::tempdir;
 
     #[test]
-    #[serial]
-    fn impact_simple_callers() {
+    #[serial]
+    fn impact_simple_callers() {
+        let td = tempdir().unwrap();
+        let f = td.path().join("main.rs");
+        let code = r#"fn bar() {}
+fn foo() { bar(); }
+"#;
+        fs::write(&f, code).unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(td.path()).unwrap();
+        let (index, refs) = build_project_graph().unwrap();
+        let bar = index
+            .symbols
+            .iter()
+            .find(|s| s.name == "bar")
+            .unwrap()
+            .clone();
+        let out = compute_impact(&[bar], &index, &refs, &ImpactOptions::default());
+        std::env::set_current_dir(cwd).unwrap();
+        assert!(out.impacted_symbols.iter().any(|s| s.name == "foo"));
+    }
+
+    #[test]
+    fn impact_json_leaves_out_unrequested_edges() {
+        use crate::ir::{SymbolKind, TextRange};
+        let at = |line| TextRange {
+            start_line: line,
+            end_line: line,
+        };
+        let bar = Symbol::new("rust", "main.rs", SymbolKind::Function, "bar", at(1));
+        let foo = Symbol::new("rust", "main.rs", SymbolKind::Function, "foo", at(2));
+        let lone = Symbol::new("rust", "main.rs", SymbolKind::Function, "lone", at(4));
+        let index = SymbolIndex::build(vec![bar.clone(), foo.clone(), lone.clone()]);
+        let refs = vec![Reference::new(
+            foo.id.clone(),
+            bar.id.clone(),
+            RefKind::Call,
+            "main.rs",
+            2,
+        )];
+        let json = |seed: &Symbol, with_edges| {
+            let opts = ImpactOptions {
+                with_edges,
+                ..ImpactOptions::default()
+            };
+            let out = compute_impact(std::slice::from_ref(seed), &index, &refs, &opts);
+            serde_json::to_value(&out).unwrap()
+        };
+
+        let off = json(&bar, None);
+        assert!(off.get("edges").is_none());
+        assert_eq!(off["edges_included"], false);
+
+        let on = json(&bar, Some(true));
+        assert_eq!(on["edges"].as_array().unwrap().len(), 1);
+        assert_eq!(on["edges_included"], true);
+
+        // Requested but none exist: still omitted, unlike "not requested"
+        let none = json(&lone, Some(true));
+        assert!(none.get("edges").is_none());
+        assert_eq!(none["edges_included"], true);
+    }
+
+    #[test]
+    fn impact_over_walks_a_caller_built_graph() {
+        use crate::ir::{SymbolKind, TextRange};
+        let at = |line| TextRange {
+            start_line: line,
+            end_line: line,
+        };
+        let fetch = Symbol::new("go", "net/fetch.go", SymbolKind::Function, "Fetch", at(3));
+        let retry = Symbol::new("go", "net/retry.go", SymbolKind::Function, "Retry", at(5));
+        let main = Symbol::new("go", "cmd/main.go", SymbolKind::Function, "main", at(8));
+        let index = SymbolIndex::build(vec![fetch.clone(), retry.clone(), main.clone()]);
+        let refs = vec![
+            Reference::new(
+                retry.id.clone(),
+                fetch.id.clone(),
+                RefKind::Call,
+                "net/retry.go",
+                6,
+            ),
+            Reference::new(
+                main.id.clone(),
+                retry.id.clone(),
+                RefKind::Call,
+                "cmd/main.go",
+                9,
+            ),
+        ];
+
+        let out = impact_over(&index, &refs, &[fetch], &ImpactOptions::default());
+        let names: HashSet<&str> = out
+            .impacted_symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, HashSet::from(["Retry", "main"]));
+        assert_eq!(out.impacted_witnesses[&main.id.0].depth, 2);
+    }
+
+    #[test]
+    fn analyze_workspace_uses_explicit_root_and_filters() {
+        let td = tempdir().unwrap();
+        let root = td.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn bar() {}\nfn foo() { bar(); }\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/app.py"), "def py():\n    pass\n").unwrap();
+        fs::write(root.join("vendor/dep.rs"), "fn dep() {}\n").unwrap();
+
+        let (index, refs) = analyze_workspace(
+            root,
+            AnalyzeOptions {
+                exclude: vec!["vendor".to_string()],
+                languages: vec![LanguageKind::Rust],
+                ..AnalyzeOptions::default()
+            },
+        )
+        .unwrap();
+        let mut names: Vec<(&str, &str)> = index
+            .symbols
+            .iter()
+            .map(|s| (s.file.as_str(), s.name.as_str()))
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![("src/main.rs", "bar"), ("src/main.rs", "foo")]);
+        assert_eq!(refs.len(), 1);
+
+        let (index, _) = analyze_workspace(
+            root,
+            AnalyzeOptions {
+                include: vec!["vendor/".to_string()],
+                ..AnalyzeOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(index.symbols.len(), 1);
+        assert_eq!(index.symbols[0].name, "dep");
+    }
+
+    #[test]
+    fn rust_pub_use_reexports_are_followed_up_to_reexport_depth() {
+        let td = tempdir().unwrap();
+        let root = td.path();
+        fs::create_dir_all(root.join("src/api")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "use crate::api::helper;\nfn run() { helper(); }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/api/mod.rs"),
+            "mod inner;\npub use self::inner::helper;\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/api/inner.rs"), "\npub fn helper() {}\n").unwrap();
+        fs::write(root.join("src/other.rs"), "pub fn helper() {}\n").unwrap();
+
+        let target = |reexport_depth| {
+            let (_, refs) = analyze_workspace(
+                root,
+                AnalyzeOptions {
+                    reexport_depth,
+                    ..AnalyzeOptions::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(refs.len(), 1);
+            refs[0].to.parts().unwrap().file
+        };
+        assert_eq!(target(None), "src/api/inner.rs");
+        assert_eq!(target(Some(0)), "src/other.rs");
+    }
+
+    #[test]
+    fn resolve_one_resolves_a_single_call_site_through_imports() {
+        let sym = |file: &str, name: &str, line: u32| Symbol {
+            id: crate::ir::SymbolId::new(
+                "rust",
+                file,
+                &crate::ir::SymbolKind::Function,
+                name,
+                line,
+            ),
+            name: name.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: line,
+                end_line: line + 2,
+            },
+            language: "rust".to_string(),
+        };
+        let index = SymbolIndex::build(vec![
+            sym("src/main.rs", "run", 2),
+            sym("src/a.rs", "helper", 1),
+            sym("src/b.rs", "helper", 1),
+        ]);
+        let mut file_imports = HashMap::new();
+        file_imports.insert(
+            "src/main.rs".to_string(),
+            HashMap::from([("helper".to_string(), "crate::b::helper".to_string())]),
+        );
+        let call = |line: u32| UnresolvedRef {
+            name: "helper".to_string(),
+            kind: RefKind::Call,
+            file: "src/main.rs".to_string(),
+            line,
+            qualifier: None,
+            is_method: false,
+        };
+
+        let edge = resolve_one(&index, &call(3), &file_imports).unwrap();
+        assert_eq!(edge.from.parts().unwrap().name, "run");
+        assert_eq!(edge.to.parts().unwrap().file, "src/b.rs");
+        assert_eq!(edge.line, 3);
+        assert_eq!(
+            resolve_references(
+                &index,
+                &[call(3)],
+                &file_imports,
+                &AnalyzeOptions::default()
+            ),
+            vec![edge]
+        );
+        // outside any symbol there is no edge to report
+        assert_eq!(resolve_one(&index, &call(9), &file_imports), None);
+    }
+
+    #[test]
+    fn external_path_names_calls_outside_the_workspace() {
+        let sym = |file: &str, name: &str| Symbol {
+            id: crate::ir::SymbolId::new("rust", file, &crate::ir::SymbolKind::Struct, name, 1),
+            name: name.to_string(),
+            kind: crate::ir::SymbolKind::Struct,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 9,
+            },
+            language: "rust".to_string(),
+        };
+        let index = SymbolIndex::build(vec![sym("src/net/client.rs", "Client")]);
+        let roots = local_module_roots(&index);
+        let file_imports = HashMap::from([(
+            "src/main.rs".to_string(),
+            HashMap::from([
+                ("from_str".to_string(), "serde_json::from_str".to_string()),
+                ("json".to_string(), "serde_json".to_string()),
+                ("helper".to_string(), "crate::util::helper".to_string()),
+            ]),
+        )]);
+        let path = |qualifier: Option<&str>, name: &str, is_method: bool| {
+            let r = UnresolvedRef {
+                name: name.to_string(),
+                kind: RefKind::Call,
+                file: "src/main.rs".to_string(),
+                line: 3,
+                qualifier: qualifier.map(str::to_string),
+                is_method,
+            };
+            external_path(&index, &r, &file_imports, &roots)
+        };
+
+        assert_eq!(
+            path(Some("serde::de"), "from_str", false).as_deref(),
+            Some("serde::de::from_str")
+        );
+        // import aliases apply to the first segment and to bare names
+        assert_eq!(
+            path(Some("json"), "to_string", false).as_deref(),
+            Some("serde_json::to_string")
+        );
+        assert_eq!(
+            path(None, "from_str", false).as_deref(),
+            Some("serde_json::from_str")
+        );
+        // workspace roots, local types, method calls and plain names stay internal
+        assert_eq!(path(None, "helper", false), None);
+        assert_eq!(path(Some("net::client"), "connect", false), None);
+        assert_eq!(path(Some("Client"), "default", false), None);
+        assert_eq!(path(Some("Self"), "build", false), None);
+        assert_eq!(path(Some("serde"), "from_str", true), None);
+        assert_eq!(path(None, "unknown", false), None);
+    }
+
+    #[test]
+    fn rust_trait_impl_methods_link_to_the_trait_method() {
         let td = tempdir().unwrap();
-        let f = td.path().join("main.rs");
-        let code = r#"fn bar() {}
-fn foo() { bar(); }
-"#;
-        fs::write(&f, code).unwrap();
-        let cwd = std::env::current_dir().unwrap();
-        std::env::set_current_dir(td.path()).unwrap();
-        let (index, refs) = build_project_graph().unwrap();
-        let bar = index
-            .symbols
-            .iter()
-            .find(|s| s.name == "bar")
-            .unwrap()
-            .clone();
-        let out = compute_impact(&[bar], &index, &refs, &ImpactOptions::default());
-        std::env::set_current_dir(cwd).unwrap();
-        assert!(out.impacted_symbols.iter().any(|s| s.name == "foo"));
+        let root = td.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/shape.rs"),
+            "pub trait Shape {\n    fn area(&self) -> f64;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/point.rs"),
+            "use crate::shape::Shape;\npub struct Point;\nimpl Shape for Point {\n    fn area(&self) -> f64 { 0.0 }\n}\n",
+        )
+        .unwrap();
+
+        let (index, refs) = analyze_workspace(root, AnalyzeOptions::default()).unwrap();
+        let sym = |name: &str| {
+            index
+                .symbols
+                .iter()
+                .find(|s| s.id.parts().unwrap().name == name)
+                .unwrap()
+                .clone()
+        };
+        let trait_method = sym("Shape::area");
+        let impl_method = sym("Point::area");
+        assert!(refs.iter().any(|r| r.from == impl_method.id
+            && r.to == trait_method.id
+            && r.kind == RefKind::Inherit));
+
+        let opts = ImpactOptions::default();
+        let out = compute_impact(std::slice::from_ref(&trait_method), &index, &refs, &opts);
+        assert!(out.impacted_symbols.iter().any(|s| s.id == impl_method.id));
+        let opts = ImpactOptions {
+            direction: ImpactDirection::Callees,
+            ..ImpactOptions::default()
+        };
+        let out = compute_impact(&[impl_method], &index, &refs, &opts);
+        assert!(out.impacted_symbols.iter().any(|s| s.id == trait_method.id));
     }
 
     #[test]
@@ -2896,6 +4611,13 @@ fn foo() { bar(); }
             max_depth: Some(10),
             with_edges: Some(true),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
 
         let out = compute_impact(std::slice::from_ref(&changed), &index, &refs, &opts);
@@ -3068,6 +4790,13 @@ fn foo() { bar(); }
             max_depth: Some(10),
             with_edges: Some(true),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
 
         let out = compute_impact(&[changed], &index, &refs, &opts);
@@ -3159,6 +4888,13 @@ fn foo() { bar(); }
             max_depth: Some(10),
             with_edges: Some(true),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
 
         let out = compute_impact(&[changed], &index, &refs, &opts);
@@ -3263,6 +4999,13 @@ fn foo() { bar(); }
             max_depth: Some(10),
             with_edges: Some(true),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
         let mut out = compute_impact(std::slice::from_ref(&seed), &index, &refs, &opts);
 
@@ -3356,7 +5099,7 @@ fn foo() { bar(); }
             pruned_candidates: Vec::new(),
         };
 
-        attach_slice_selection_summary(&mut out, &slice_selection);
+        attach_slice_selection_summary(&mut out, &slice_selection, None);
 
         assert_eq!(out.summary.slice_selection, Some(slice_selection.clone()));
         let witness = out
@@ -3549,6 +5292,13 @@ fn foo() { bar(); }
             max_depth: Some(10),
             with_edges: Some(true),
             ignore_dirs: Vec::new(),
+            edge_kinds: Vec::new(),
+            edge_weights: HashMap::new(),
+            exclude_tests: false,
+            limits: GraphLimits::default(),
+            time_budget_ms: None,
+            languages: Vec::new(),
+            root: None,
         };
         let mut out = compute_impact(std::slice::from_ref(&seed), &index, &refs, &opts);
 
@@ -3663,7 +5413,7 @@ fn foo() { bar(); }
             }],
         };
 
-        attach_slice_selection_summary(&mut out, &slice_selection);
+        attach_slice_selection_summary(&mut out, &slice_selection, None);
 
         let witness = out
             .impacted_witnesses
@@ -4447,8 +6197,11 @@ fn foo() { bar(); }
             )]),
             impacted_files: vec![],
             edges: vec![],
+            edges_included: false,
             impacted_by_file: HashMap::new(),
+            changed_by_file: HashMap::new(),
             summary: ImpactSummary::default(),
+            snippets: HashMap::new(),
         };
         let slice_selection = ImpactSliceSelectionSummary {
             planner: ImpactSlicePlannerKind::BoundedSlice,
@@ -4511,7 +6264,7 @@ fn foo() { bar(); }
             pruned_candidates: Vec::new(),
         };
 
-        attach_slice_selection_summary(&mut out, &slice_selection);
+        attach_slice_selection_summary(&mut out, &slice_selection, None);
 
         let witness = out
             .impacted_witnesses
@@ -4635,7 +6388,7 @@ fn foo() { bar(); }
         };
 
         let (family, winning_steps, supporting_steps) =
-            build_bridge_execution_provenance_compact(&slice_context, &[]);
+            build_bridge_execution_provenance_compact(None, &slice_context, &[]);
 
         std::env::set_current_dir(cwd).expect("restore cwd");
 
@@ -4681,7 +6434,7 @@ fn foo() { bar(); }
     }
 
     #[test]
-    fn score_candidate_prefers_method_but_allows_python_function_fallback() {
+    fn score_breakdown_prefers_method_but_allows_python_function_fallback() {
         let method = Symbol {
             id: crate::ir::SymbolId::new(
                 "python",
@@ -4734,9 +6487,12 @@ fn foo() { bar(); }
             language: "rust".to_string(),
         };
 
-        let m_score = score_candidate("pkg/b.py", None, None, &method, true);
-        let py_fn_score = score_candidate("pkg/b.py", None, None, &py_fn, true);
-        let rust_fn_score = score_candidate("pkg/b.py", None, None, &rust_fn, true);
+        let m_score =
+            score_breakdown("pkg/b.py", None, None, &method, true, &HashMap::new()).total();
+        let py_fn_score =
+            score_breakdown("pkg/b.py", None, None, &py_fn, true, &HashMap::new()).total();
+        let rust_fn_score =
+            score_breakdown("pkg/b.py", None, None, &rust_fn, true, &HashMap::new()).total();
 
         assert!(m_score > py_fn_score, "method should score highest");
         assert!(
@@ -4754,6 +6510,14 @@ fn foo() { bar(); }
         assert!(file_matches_module_path("demo/Ops.java", "demo::Ops"));
     }
 
+    #[test]
+    fn rust_module_key_is_crate_relative() {
+        assert_eq!(rust_module_key("src::net::util"), "net::util");
+        assert_eq!(rust_module_key("crates::core::src::util"), "util");
+        assert_eq!(rust_module_key("crate::util"), "util");
+        assert_eq!(rust_module_key("src"), "");
+    }
+
     #[test]
     fn module_path_for_file_strips_go_and_java_extensions() {
         assert_eq!(
@@ -4763,6 +6527,32 @@ fn foo() { bar(); }
         assert_eq!(module_path_for_file("demo/Ops.java"), "demo::Ops");
     }
 
+    #[test]
+    fn kotlin_files_match_modules_by_package() {
+        assert_eq!(module_path_for_file("app/Util.kt"), "app::Util");
+        assert_eq!(
+            module_path_for_package_file("src/main/kotlin/Util.kt", "com::acme"),
+            "com::acme::Util"
+        );
+        let packages = HashMap::from([("src/Util.kt", "com::acme")]);
+        assert!(file_in_module("src/Util.kt", "com::acme", &packages));
+        assert!(file_in_module("src/Util.kt", "com::acme::Util", &packages));
+        assert!(!file_in_module("src/Util.kt", "com::other", &packages));
+        assert!(file_in_module(
+            "com/acme/Util.kt",
+            "com::acme::Util",
+            &HashMap::new()
+        ));
+    }
+
+    #[test]
+    fn c_headers_and_sources_share_a_module_path() {
+        assert_eq!(module_path_for_file("src/util.h"), "src::util");
+        assert_eq!(module_path_for_file("src/util.cpp"), "src::util");
+        assert!(file_matches_module_path("src/util.c", "src/util"));
+        assert!(file_matches_module_path("src/util.hpp", "src::util"));
+    }
+
     #[test]
     fn affected_module_for_file_normalizes_entry_like_labels() {
         assert_eq!(affected_module_for_file("main.rs"), "(root)");
@@ -5181,7 +6971,7 @@ fn foo() { bar(); }
         };
 
         let (family, winning_steps, supporting_steps) =
-            build_bridge_execution_provenance_compact(&slice_context, &[]);
+            build_bridge_execution_provenance_compact(None, &slice_context, &[]);
 
         assert_eq!(family, Some(ImpactBridgeExecutionFamily::AliasResultStitch));
         assert_eq!(winning_steps.len(), 1);
@@ -5238,4 +7028,543 @@ fn foo() { bar(); }
         );
         assert!(supporting_steps[0].summary.is_some());
     }
+
+    fn call_edge(from: &str, to: &str) -> Reference {
+        Reference {
+            from: crate::ir::SymbolId(from.to_string()),
+            to: crate::ir::SymbolId(to.to_string()),
+            kind: RefKind::Call,
+            file: "main.rs".to_string(),
+            line: 1,
+            certainty: crate::ir::reference::EdgeCertainty::Inferred,
+            provenance: EdgeProvenance::CallGraph,
+        }
+    }
+
+    #[test]
+    fn is_test_path_recognizes_common_layouts() {
+        for path in [
+            "tests/cli.rs",
+            "pkg/server_test.go",
+            "app/test_views.py",
+            "spec/models/user_spec.rb",
+            "web/button.test.tsx",
+            "src/test/java/FooTest.java",
+        ] {
+            assert!(is_test_path(path), "{path}");
+        }
+        for path in ["src/lib.rs", "pkg/server.go", "app/testing_utils.py"] {
+            assert!(!is_test_path(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn snippets_slice_symbol_ranges_and_skip_missing_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let body: String = (1..=40).map(|i| format!("line{i}\n")).collect();
+        fs::write(&file, body).unwrap();
+        let file = file.to_string_lossy().to_string();
+        let mk = |name: &str, path: &str, start_line: u32, end_line: u32| Symbol {
+            id: crate::ir::SymbolId::new(
+                "rust",
+                path,
+                &crate::ir::SymbolKind::Function,
+                name,
+                start_line,
+            ),
+            name: name.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: path.to_string(),
+            range: crate::ir::TextRange {
+                start_line,
+                end_line,
+            },
+            language: "rust".to_string(),
+        };
+        let short = mk("short", &file, 2, 3);
+        let long = mk("long", &file, 5, 39);
+        let missing = mk("gone", "no/such/file.rs", 1, 2);
+        let snippets = snippets_for(None, [&short, &long, &missing]);
+        assert_eq!(snippets[&short.id.0], "line2\nline3");
+        assert_eq!(snippets[&long.id.0].lines().count(), MAX_SNIPPET_LINES);
+        assert!(snippets[&long.id.0].starts_with("line5\n"));
+        assert!(!snippets.contains_key(&missing.id.0));
+    }
+
+    #[test]
+    fn compare_impact_reports_added_and_removed_symbols_and_edges() {
+        let sym = |id: &str| Symbol {
+            id: crate::ir::SymbolId(id.to_string()),
+            name: id.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: "main.rs".to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 1,
+            },
+            language: "rust".to_string(),
+        };
+        let output = |impacted: &[&str], edges: Vec<Reference>| {
+            let mut out = finalize_impact_output(
+                vec![sym("leaf")],
+                impacted.iter().map(|id| sym(id)).collect(),
+                edges,
+                &HashMap::new(),
+                HashMap::new(),
+            );
+            out.edges_included = true;
+            out
+        };
+        let baseline = output(
+            &["mid", "old"],
+            vec![call_edge("mid", "leaf"), call_edge("old", "leaf")],
+        );
+        let current = output(
+            &["mid", "new"],
+            vec![
+                Reference {
+                    line: 9,
+                    ..call_edge("mid", "leaf")
+                },
+                call_edge("new", "mid"),
+            ],
+        );
+
+        let cmp = compare_impact(&baseline, &current);
+        let ids = |v: &[Symbol]| v.iter().map(|s| s.id.0.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&cmp.added_symbols), vec!["new"]);
+        assert_eq!(ids(&cmp.removed_symbols), vec!["old"]);
+        assert!(cmp.edges_compared);
+        assert_eq!(cmp.added_edges, vec![call_edge("new", "mid")]);
+        assert_eq!(cmp.removed_edges, vec![call_edge("old", "leaf")]);
+
+        let mut without_edges = current.clone();
+        without_edges.edges_included = false;
+        let cmp = compare_impact(&baseline, &without_edges);
+        assert!(!cmp.edges_compared);
+        assert!(cmp.added_edges.is_empty() && cmp.removed_edges.is_empty());
+    }
+
+    #[test]
+    fn edge_weights_spend_max_depth_per_edge_kind() {
+        let symbols: Vec<Symbol> = ["leaf", "mid", "top", "holder"]
+            .iter()
+            .map(|id| Symbol {
+                id: crate::ir::SymbolId(id.to_string()),
+                name: id.to_string(),
+                kind: crate::ir::SymbolKind::Function,
+                file: "main.rs".to_string(),
+                range: crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+                language: "rust".to_string(),
+            })
+            .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        let refs = vec![
+            call_edge("mid", "leaf"),
+            call_edge("top", "mid"),
+            Reference {
+                kind: RefKind::TypeUse,
+                ..call_edge("holder", "leaf")
+            },
+        ];
+        let names = |out: &ImpactOutput| -> Vec<String> {
+            out.impacted_symbols
+                .iter()
+                .map(|s| s.name.clone())
+                .collect()
+        };
+
+        let plain = ImpactOptions {
+            max_depth: Some(2),
+            ..ImpactOptions::default()
+        };
+        let out = compute_impact(&symbols[..1], &index, &refs, &plain);
+        assert_eq!(names(&out), vec!["holder", "mid", "top"]);
+
+        let weighted = ImpactOptions {
+            edge_weights: HashMap::from([(RefKind::TypeUse, 3)]),
+            ..plain
+        };
+        let out = compute_impact(&symbols[..1], &index, &refs, &weighted);
+        assert_eq!(names(&out), vec!["mid", "top"]);
+
+        let cheap_calls = ImpactOptions {
+            max_depth: Some(3),
+            ..weighted
+        };
+        let out = compute_impact(&symbols[..1], &index, &refs, &cheap_calls);
+        assert_eq!(names(&out), vec!["holder", "mid", "top"]);
+        let holder_depth = out
+            .summary
+            .by_depth
+            .iter()
+            .find(|b| b.depth == 3)
+            .map(|b| b.symbol_count);
+        assert_eq!(holder_depth, Some(1));
+    }
+
+    #[test]
+    fn graph_limits_error_on_size_and_truncate_traversal() {
+        let symbols: Vec<Symbol> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|id| Symbol {
+                id: crate::ir::SymbolId(id.to_string()),
+                name: id.to_string(),
+                kind: crate::ir::SymbolKind::Function,
+                file: "main.rs".to_string(),
+                range: crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+                language: "rust".to_string(),
+            })
+            .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        let refs = vec![
+            call_edge("b", "a"),
+            call_edge("c", "b"),
+            call_edge("d", "c"),
+            call_edge("e", "d"),
+        ];
+
+        let full = compute_impact(&symbols[..1], &index, &refs, &ImpactOptions::default());
+        assert_eq!(full.impacted_symbols.len(), 4);
+        assert!(!full.summary.truncated);
+
+        let opts = ImpactOptions {
+            limits: GraphLimits {
+                max_visited: Some(3),
+                ..GraphLimits::default()
+            },
+            ..ImpactOptions::default()
+        };
+        let cut = compute_impact(&symbols[..1], &index, &refs, &opts);
+        let names: Vec<&str> = cut
+            .impacted_symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["b", "c"]);
+        assert!(cut.summary.truncated);
+
+        let limits = GraphLimits {
+            max_symbols: Some(5),
+            max_edges: Some(3),
+            max_visited: None,
+        };
+        assert_eq!(
+            check_graph_limits(&index, &refs, &limits),
+            Err(GraphLimitError::TooManyEdges { count: 4, max: 3 })
+        );
+        let limits = GraphLimits {
+            max_symbols: Some(4),
+            ..limits
+        };
+        assert_eq!(
+            check_graph_limits(&index, &refs, &limits),
+            Err(GraphLimitError::TooManySymbols { count: 5, max: 4 })
+        );
+        assert!(check_graph_limits(&index, &refs, &GraphLimits::default()).is_ok());
+    }
+
+    #[test]
+    fn spent_time_budget_stops_traversal_and_flags_truncated() {
+        let symbols: Vec<Symbol> = ["a", "b"]
+            .iter()
+            .map(|id| Symbol {
+                id: crate::ir::SymbolId(id.to_string()),
+                name: id.to_string(),
+                kind: crate::ir::SymbolKind::Function,
+                file: "main.rs".to_string(),
+                range: crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+                language: "rust".to_string(),
+            })
+            .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        let refs = vec![call_edge("b", "a")];
+
+        let opts = ImpactOptions {
+            time_budget_ms: Some(60_000),
+            ..ImpactOptions::default()
+        };
+        let out = compute_impact(&symbols[..1], &index, &refs, &opts);
+        assert_eq!(out.impacted_symbols.len(), 1);
+        assert!(!out.summary.truncated);
+
+        let opts = ImpactOptions {
+            time_budget_ms: Some(0),
+            ..ImpactOptions::default()
+        };
+        let out = compute_impact(&symbols[..1], &index, &refs, &opts);
+        assert!(out.impacted_symbols.is_empty());
+        assert!(out.summary.truncated);
+    }
+
+    #[test]
+    fn exclude_tests_prunes_traversal_through_test_functions() {
+        let symbols: Vec<Symbol> = [
+            ("core", "src/lib.rs"),
+            ("wrapper", "src/lib.rs"),
+            ("end_to_end", "tests/it.rs"),
+            ("fixture", "src/fixture.rs"),
+        ]
+        .iter()
+        .map(|(id, file)| Symbol {
+            id: crate::ir::SymbolId(id.to_string()),
+            name: id.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 1,
+            },
+            language: "rust".to_string(),
+        })
+        .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        let refs = vec![
+            call_edge("wrapper", "core"),
+            call_edge("end_to_end", "core"),
+            call_edge("end_to_end", "fixture"),
+        ];
+        let names = |exclude_tests: bool| {
+            let opts = ImpactOptions {
+                direction: ImpactDirection::Both,
+                exclude_tests,
+                ..ImpactOptions::default()
+            };
+            let mut names: Vec<String> = compute_impact(&symbols[..1], &index, &refs, &opts)
+                .impacted_symbols
+                .into_iter()
+                .map(|s| s.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(false), vec!["end_to_end", "fixture", "wrapper"]);
+        assert_eq!(names(true), vec!["wrapper"]);
+    }
+
+    #[test]
+    fn impact_edges_come_out_sorted_whatever_the_input_order() {
+        let symbols: Vec<Symbol> = ["a", "b", "c"]
+            .iter()
+            .map(|id| Symbol {
+                id: crate::ir::SymbolId(id.to_string()),
+                name: id.to_string(),
+                kind: crate::ir::SymbolKind::Function,
+                file: "main.rs".to_string(),
+                range: crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+                language: "rust".to_string(),
+            })
+            .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        let mut refs = vec![
+            call_edge("c", "a"),
+            call_edge("b", "a"),
+            call_edge("c", "b"),
+            call_edge("b", "a"),
+        ];
+        refs[2].line = 7;
+        let opts = ImpactOptions {
+            with_edges: Some(true),
+            ..ImpactOptions::default()
+        };
+        let pairs = |refs: &[Reference]| {
+            compute_impact(&symbols[..1], &index, refs, &opts)
+                .edges
+                .iter()
+                .map(|e| (e.from.0.clone(), e.to.0.clone(), e.line))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            ("b".to_string(), "a".to_string(), 1),
+            ("c".to_string(), "a".to_string(), 1),
+            ("c".to_string(), "b".to_string(), 7),
+        ];
+        assert_eq!(pairs(&refs), expected);
+        refs.reverse();
+        assert_eq!(pairs(&refs), expected);
+    }
+
+    #[test]
+    fn both_direction_keeps_one_edge_per_call_site() {
+        let symbols: Vec<Symbol> = ["a", "b", "c"]
+            .iter()
+            .map(|id| Symbol {
+                id: crate::ir::SymbolId(id.to_string()),
+                name: id.to_string(),
+                kind: crate::ir::SymbolKind::Function,
+                file: "main.rs".to_string(),
+                range: crate::ir::TextRange {
+                    start_line: 1,
+                    end_line: 1,
+                },
+                language: "rust".to_string(),
+            })
+            .collect();
+        let index = SymbolIndex::build(symbols.clone());
+        // b -> a recorded once as a call and once, at the same site, as a type use
+        let mut type_use = call_edge("b", "a");
+        type_use.kind = RefKind::TypeUse;
+        let refs = vec![type_use, call_edge("b", "a"), call_edge("a", "c")];
+        let edges = |direction: ImpactDirection| {
+            let opts = ImpactOptions {
+                direction,
+                with_edges: Some(true),
+                ..ImpactOptions::default()
+            };
+            compute_impact(&symbols[..1], &index, &refs, &opts)
+                .edges
+                .iter()
+                .map(|e| (e.from.0.clone(), e.to.0.clone(), e.kind.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            edges(ImpactDirection::Both),
+            vec![
+                ("a".to_string(), "c".to_string(), RefKind::Call),
+                ("b".to_string(), "a".to_string(), RefKind::Call),
+            ]
+        );
+        // One direction still lists each kind
+        assert_eq!(edges(ImpactDirection::Callers).len(), 3);
+    }
+
+    #[test]
+    fn find_cycles_reports_sccs_and_self_loops_sorted() {
+        let refs = vec![
+            call_edge("c", "a"),
+            call_edge("a", "b"),
+            call_edge("b", "c"),
+            call_edge("c", "d"),
+            call_edge("e", "e"),
+            call_edge("f", "g"),
+        ];
+        let cycles = find_cycles(&SymbolIndex::default(), &refs);
+        let ids: Vec<Vec<&str>> = cycles
+            .iter()
+            .map(|c| c.iter().map(|s| s.0.as_str()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec!["a", "b", "c"], vec!["e"]]);
+    }
+
+    #[test]
+    fn find_cycles_handles_long_chains_without_recursion() {
+        let n = 50_000;
+        let mut refs: Vec<Reference> = (0..n)
+            .map(|i| call_edge(&format!("n{i:06}"), &format!("n{:06}", i + 1)))
+            .collect();
+        refs.push(call_edge(&format!("n{n:06}"), "n000000"));
+        let cycles = find_cycles(&SymbolIndex::default(), &refs);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), n + 1);
+    }
+
+    #[test]
+    fn compute_symbol_metrics_counts_distinct_callers_and_callees() {
+        let sym = |name: &str| Symbol {
+            id: crate::ir::SymbolId(name.to_string()),
+            name: name.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: "main.rs".to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 1,
+            },
+            language: "rust".to_string(),
+        };
+        let index = SymbolIndex::build(vec![sym("a"), sym("b"), sym("c"), sym("d")]);
+        let refs = vec![
+            call_edge("a", "c"),
+            call_edge("a", "c"),
+            call_edge("b", "c"),
+            call_edge("c", "d"),
+            call_edge("a", "b"),
+        ];
+        let metrics = compute_symbol_metrics(&index, &refs);
+        let got: Vec<(&str, usize, usize)> = metrics
+            .iter()
+            .map(|m| (m.name.as_str(), m.in_degree, m.out_degree))
+            .collect();
+        assert_eq!(
+            got,
+            vec![("c", 2, 1), ("b", 1, 1), ("d", 1, 0), ("a", 0, 2)]
+        );
+    }
+
+    #[test]
+    fn impacted_files_group_differently_prefixed_paths_together() {
+        let sym = |id: &str, file: &str| Symbol {
+            id: crate::ir::SymbolId(id.to_string()),
+            name: id.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 1,
+            },
+            language: "rust".to_string(),
+        };
+        let out = finalize_impact_output(
+            vec![],
+            vec![
+                sym("a", "./src/a.rs"),
+                sym("b", "src/a.rs"),
+                sym("c", "src/x/../a.rs"),
+            ],
+            vec![],
+            &HashMap::new(),
+            HashMap::new(),
+        );
+        assert_eq!(out.impacted_files, vec!["src/a.rs".to_string()]);
+        assert_eq!(out.impacted_by_file.len(), 1);
+        assert_eq!(out.impacted_by_file["src/a.rs"].len(), 3);
+    }
+
+    #[test]
+    fn finalize_groups_changed_symbols_by_file_in_path_order() {
+        let sym = |name: &str, file: &str| Symbol {
+            id: crate::ir::SymbolId::new("rust", file, &crate::ir::SymbolKind::Function, name, 1),
+            name: name.to_string(),
+            kind: crate::ir::SymbolKind::Function,
+            file: file.to_string(),
+            range: crate::ir::TextRange {
+                start_line: 1,
+                end_line: 1,
+            },
+            language: "rust".to_string(),
+        };
+        let out = finalize_impact_output(
+            vec![
+                sym("z", "src/z.rs"),
+                sym("a", "./src/a.rs"),
+                sym("b", "src/a.rs"),
+            ],
+            vec![sym("c", "src/c.rs")],
+            vec![],
+            &HashMap::new(),
+            HashMap::new(),
+        );
+        assert_eq!(out.changed_by_file.len(), 2);
+        assert_eq!(out.changed_by_file["src/a.rs"].len(), 2);
+        assert_eq!(out.changed_by_file["src/z.rs"].len(), 1);
+        assert!(!out.changed_by_file.contains_key("src/c.rs"));
+
+        let json = serde_json::to_string(&out).unwrap();
+        let by_file = &json[json.find("\"changed_by_file\"").unwrap()..];
+        let a = by_file.find("\"src/a.rs\"").unwrap();
+        let z = by_file.find("\"src/z.rs\"").unwrap();
+        assert!(a < z);
+    }
 }
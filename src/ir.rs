@@ -8,7 +8,7 @@ pub struct TextRange {
     pub end_line: u32,   // 1-based inclusive
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum SymbolKind {
     Function,
@@ -17,6 +17,63 @@ pub enum SymbolKind {
     Enum,
     Trait,
     Module,
+    Const,
+    Static,
+    TypeAlias,
+    /// A Rust `macro_rules!` macro or `#[proc_macro_derive]` derive.
+    Macro,
+}
+
+impl SymbolKind {
+    /// The short kind code used in symbol ids (`fn`, `method`, `struct`, ...).
+    pub fn code(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "fn",
+            SymbolKind::Method => "method",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Module => "mod",
+            SymbolKind::Const => "const",
+            SymbolKind::Static => "static",
+            SymbolKind::TypeAlias => "type",
+            SymbolKind::Macro => "macro",
+        }
+    }
+
+    /// Inverse of [`SymbolKind::code`].
+    pub fn from_code(code: &str) -> Option<Self> {
+        Some(match code {
+            "fn" => SymbolKind::Function,
+            "method" => SymbolKind::Method,
+            "struct" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "trait" => SymbolKind::Trait,
+            "mod" => SymbolKind::Module,
+            "const" => SymbolKind::Const,
+            "static" => SymbolKind::Static,
+            "type" => SymbolKind::TypeAlias,
+            "macro" => SymbolKind::Macro,
+            _ => return None,
+        })
+    }
+
+    /// Tie-break rank among symbols covering the same lines: lower is more
+    /// specific (a method wins over a function, a function over a struct).
+    pub fn specificity(&self) -> u8 {
+        match self {
+            SymbolKind::Method => 0,
+            SymbolKind::Function => 1,
+            SymbolKind::Struct => 2,
+            SymbolKind::Enum => 3,
+            SymbolKind::Trait => 4,
+            SymbolKind::Module => 5,
+            SymbolKind::Const => 6,
+            SymbolKind::Static => 7,
+            SymbolKind::TypeAlias => 8,
+            SymbolKind::Macro => 9,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -32,16 +89,303 @@ pub struct Symbol {
     pub language: String,
 }
 
+/// File of the boundary symbols standing for code outside the workspace
+/// (see [`Symbol::external`]).
+pub const EXTERNAL_FILE: &str = "<external>";
+
+impl Symbol {
+    /// A workspace symbol spanning `range` of `file`, with the id analyzers
+    /// give it (see [`SymbolId::new`]); for graphs built outside dimpact's
+    /// own analyzers.
+    pub fn new(
+        language: &str,
+        file: &str,
+        kind: SymbolKind,
+        name: &str,
+        range: TextRange,
+    ) -> Symbol {
+        Symbol {
+            id: SymbolId::new(language, file, &kind, name, range.start_line),
+            name: name.to_string(),
+            kind,
+            file: file.to_string(),
+            range,
+            language: language.to_string(),
+        }
+    }
+
+    /// A boundary symbol for `path` (e.g. `serde_json::from_str`), code
+    /// outside the analyzed workspace that a `language` file references. It
+    /// is named by the full path, lives in [`EXTERNAL_FILE`] and has no lines.
+    pub fn external(language: &str, kind: SymbolKind, path: &str) -> Symbol {
+        Symbol {
+            id: SymbolId::new(language, EXTERNAL_FILE, &kind, path, 0),
+            name: path.to_string(),
+            kind,
+            file: EXTERNAL_FILE.to_string(),
+            range: TextRange {
+                start_line: 0,
+                end_line: 0,
+            },
+            language: language.to_string(),
+        }
+    }
+
+    /// The boundary symbol `id` names, rebuilt from the id alone; `None` for
+    /// ids of workspace symbols.
+    pub fn from_external_id(id: &SymbolId) -> Option<Symbol> {
+        let parts = id.parts()?;
+        if parts.file != EXTERNAL_FILE {
+            return None;
+        }
+        Some(Symbol::external(
+            &parts.lang,
+            parts.symbol_kind()?,
+            &parts.name,
+        ))
+    }
+
+    /// Whether this is a boundary symbol from [`Symbol::external`].
+    pub fn is_external(&self) -> bool {
+        self.file == EXTERNAL_FILE
+    }
+}
+
 impl SymbolId {
     pub fn new(lang: &str, file: &str, kind: &SymbolKind, name: &str, start_line: u32) -> Self {
-        let k = match kind {
-            SymbolKind::Function => "fn",
-            SymbolKind::Method => "method",
-            SymbolKind::Struct => "struct",
-            SymbolKind::Enum => "enum",
-            SymbolKind::Trait => "trait",
-            SymbolKind::Module => "mod",
+        Self::new_qualified(lang, file, kind, None, name, start_line)
+    }
+
+    /// Like [`SymbolId::new`], but when the analyzer knows the enclosing type or
+    /// module the name component becomes `Qualifier::name` (e.g. `Foo::new`), so
+    /// same-named methods of different types stay distinguishable.
+    pub fn new_qualified(
+        lang: &str,
+        file: &str,
+        kind: &SymbolKind,
+        qualifier: Option<&str>,
+        name: &str,
+        start_line: u32,
+    ) -> Self {
+        let name = match qualifier {
+            Some(q) if !q.is_empty() => format!("{q}::{name}"),
+            _ => name.to_string(),
         };
-        Self(format!("{}:{}:{}:{}:{}", lang, file, k, name, start_line))
+        Self(format!(
+            "{}:{}:{}:{}:{}",
+            lang,
+            escape_id_component(file),
+            kind.code(),
+            escape_id_component(&name),
+            start_line
+        ))
+    }
+
+    /// Split the id back into its components, decoding the escaped file and name.
+    /// See [`parse_symbol_id`].
+    pub fn parts(&self) -> Option<SymbolIdParts> {
+        parse_symbol_id(&self.0)
+    }
+}
+
+/// Decode a symbol id string (`lang:file:kind:name:line`) into its components;
+/// `None` when it has fewer than five components or a non-numeric line.
+///
+/// Unescaped colons are accepted too: the file ends at the first known kind
+/// code and the name runs from there to the trailing line, so hand-written ids
+/// such as `rust:C:/a.rs:method:Foo::new:3` parse the same as escaped ones.
+pub fn parse_symbol_id(id: &str) -> Option<SymbolIdParts> {
+    let (lang, rest) = id.split_once(':')?;
+    let (rest, line) = rest.rsplit_once(':')?;
+    let line = line.parse().ok()?;
+    let comps: Vec<&str> = rest.split(':').collect();
+    if comps.len() < 3 {
+        return None;
+    }
+    let kind_at = (1..comps.len() - 1)
+        .find(|&i| SymbolKind::from_code(comps[i]).is_some())
+        .unwrap_or(comps.len() - 2);
+    Some(SymbolIdParts {
+        lang: lang.to_string(),
+        file: unescape_id_component(&comps[..kind_at].join(":")),
+        kind: comps[kind_at].to_string(),
+        name: unescape_id_component(&comps[kind_at + 1..].join(":")),
+        line,
+    })
+}
+
+/// Decoded components of a [`SymbolId`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolIdParts {
+    pub lang: String,
+    pub file: String,
+    pub kind: String,
+    pub name: String,
+    pub line: u32,
+}
+
+impl SymbolIdParts {
+    /// The kind code as a [`SymbolKind`]; `None` for codes this version does
+    /// not know.
+    pub fn symbol_kind(&self) -> Option<SymbolKind> {
+        SymbolKind::from_code(&self.kind)
+    }
+}
+
+/// Percent-escape `%` and `:` so a component cannot introduce extra separators.
+fn escape_id_component(s: &str) -> String {
+    if !s.contains([':', '%']) {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len() + 4);
+    for ch in s.chars() {
+        match ch {
+            '%' => out.push_str("%25"),
+            ':' => out.push_str("%3A"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Inverse of `escape_id_component`; unknown escapes are kept verbatim.
+pub fn unescape_id_component(s: &str) -> String {
+    if !s.contains('%') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('%') {
+        out.push_str(&rest[..pos]);
+        let esc = &rest[pos..];
+        if let Some(after) = esc.strip_prefix("%25") {
+            out.push('%');
+            rest = after;
+        } else if let Some(after) = esc.strip_prefix("%3A").or_else(|| esc.strip_prefix("%3a")) {
+            out.push(':');
+            rest = after;
+        } else {
+            out.push('%');
+            rest = &esc[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_id_round_trips_colon_paths() {
+        let id = SymbolId::new("rust", r"C:\work\a%b.rs", &SymbolKind::Function, "foo", 12);
+        assert_eq!(id.0, r"rust:C%3A\work\a%25b.rs:fn:foo:12");
+        let parts = id.parts().unwrap();
+        assert_eq!(parts.lang, "rust");
+        assert_eq!(parts.file, r"C:\work\a%b.rs");
+        assert_eq!(parts.kind, "fn");
+        assert_eq!(parts.name, "foo");
+        assert_eq!(parts.line, 12);
+    }
+
+    #[test]
+    fn symbol_id_plain_paths_are_unchanged() {
+        let id = SymbolId::new("ts", "src/a.ts", &SymbolKind::Method, "m", 3);
+        assert_eq!(id.0, "ts:src/a.ts:method:m:3");
+        assert_eq!(id.parts().unwrap().file, "src/a.ts");
+    }
+
+    #[test]
+    fn symbol_id_parts_accepts_unescaped_colon_paths() {
+        let id = SymbolId("rust:C:/work/a.rs:fn:foo:7".to_string());
+        let parts = id.parts().unwrap();
+        assert_eq!(parts.file, "C:/work/a.rs");
+        assert_eq!(parts.name, "foo");
+        assert_eq!(parts.line, 7);
+
+        let parts = parse_symbol_id("rust:C:/work/a.rs:method:Foo::new:3").unwrap();
+        assert_eq!(parts.file, "C:/work/a.rs");
+        assert_eq!(parts.kind, "method");
+        assert_eq!(parts.name, "Foo::new");
+        assert_eq!(parts.line, 3);
+        assert_eq!(parse_symbol_id("rust:a.rs:x:fn:7").unwrap().kind, "x");
+    }
+
+    #[test]
+    fn external_symbols_round_trip_through_their_id() {
+        let sym = Symbol::external("rust", SymbolKind::Function, "serde_json::from_str");
+        assert_eq!(sym.id.0, "rust:<external>:fn:serde_json%3A%3Afrom_str:0");
+        assert!(sym.is_external());
+        assert_eq!(Symbol::from_external_id(&sym.id), Some(sym));
+        let local = SymbolId::new("rust", "src/a.rs", &SymbolKind::Function, "f", 1);
+        assert_eq!(Symbol::from_external_id(&local), None);
+    }
+
+    #[test]
+    fn symbol_id_qualifier_is_optional_and_round_trips() {
+        let plain = SymbolId::new_qualified("rust", "f.rs", &SymbolKind::Method, None, "new", 10);
+        assert_eq!(plain.0, "rust:f.rs:method:new:10");
+        let id =
+            SymbolId::new_qualified("rust", "f.rs", &SymbolKind::Method, Some("Foo"), "new", 10);
+        assert_eq!(id.0, "rust:f.rs:method:Foo%3A%3Anew:10");
+        assert_eq!(id.parts().unwrap().name, "Foo::new");
+    }
+
+    #[test]
+    fn symbol_id_kind_codes_for_value_kinds() {
+        let id = |kind| SymbolId::new("rust", "src/lib.rs", &kind, "X", 1).0;
+        assert_eq!(id(SymbolKind::Const), "rust:src/lib.rs:const:X:1");
+        assert_eq!(id(SymbolKind::Static), "rust:src/lib.rs:static:X:1");
+        assert_eq!(id(SymbolKind::TypeAlias), "rust:src/lib.rs:type:X:1");
+        assert_eq!(
+            serde_json::to_string(&SymbolKind::TypeAlias).unwrap(),
+            "\"type_alias\""
+        );
+        assert_eq!(
+            serde_json::to_string(&SymbolKind::Module).unwrap(),
+            "\"module\""
+        );
+    }
+
+    #[test]
+    fn symbol_id_new_round_trips_through_parse() {
+        let kinds = [
+            SymbolKind::Function,
+            SymbolKind::Method,
+            SymbolKind::Struct,
+            SymbolKind::Enum,
+            SymbolKind::Trait,
+            SymbolKind::Module,
+            SymbolKind::Const,
+            SymbolKind::Static,
+            SymbolKind::TypeAlias,
+            SymbolKind::Macro,
+        ];
+        for (i, kind) in kinds.into_iter().enumerate() {
+            let file = format!("src/dir:{i}/100%.rs");
+            let line = i as u32 + 1;
+            let id = SymbolId::new_qualified("rust", &file, &kind, Some("Outer"), "item", line);
+            let parts = parse_symbol_id(&id.0).unwrap();
+            assert_eq!(parts, id.parts().unwrap());
+            assert_eq!(parts.lang, "rust");
+            assert_eq!(parts.file, file);
+            assert_eq!(parts.symbol_kind(), Some(kind));
+            assert_eq!(parts.name, "Outer::item");
+            assert_eq!(parts.line, line);
+        }
+    }
+
+    #[test]
+    fn parse_symbol_id_rejects_malformed_ids() {
+        assert_eq!(parse_symbol_id("rust:src/a.rs:fn:foo"), None);
+        assert_eq!(parse_symbol_id("rust:src/a.rs:fn:foo:x"), None);
+        assert_eq!(parse_symbol_id("foo"), None);
+        assert_eq!(
+            parse_symbol_id("rust:a.rs:widget:foo:1")
+                .unwrap()
+                .symbol_kind(),
+            None
+        );
     }
 }
@@ -7,6 +7,34 @@ pub enum RefKind {
     Call,
     Data,
     Control,
+    /// A type named in a struct field or function signature.
+    TypeUse,
+    /// A trait implemented (or class extended) by the referencing symbol.
+    Inherit,
+}
+
+impl RefKind {
+    /// The snake_case name used in serialized output and the cache.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefKind::Call => "call",
+            RefKind::Data => "data",
+            RefKind::Control => "control",
+            RefKind::TypeUse => "type_use",
+            RefKind::Inherit => "inherit",
+        }
+    }
+
+    pub fn from_str_opt(s: &str) -> Option<Self> {
+        match s {
+            "call" => Some(RefKind::Call),
+            "data" => Some(RefKind::Data),
+            "control" => Some(RefKind::Control),
+            "type_use" => Some(RefKind::TypeUse),
+            "inherit" => Some(RefKind::Inherit),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, Hash)]
@@ -18,6 +46,26 @@ pub enum EdgeProvenance {
     SymbolicPropagation,
 }
 
+impl EdgeProvenance {
+    /// The snake_case name used in serialized output and the cache.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeProvenance::CallGraph => "call_graph",
+            EdgeProvenance::LocalDfg => "local_dfg",
+            EdgeProvenance::SymbolicPropagation => "symbolic_propagation",
+        }
+    }
+
+    pub fn from_str_opt(s: &str) -> Option<Self> {
+        match s {
+            "call_graph" => Some(EdgeProvenance::CallGraph),
+            "local_dfg" => Some(EdgeProvenance::LocalDfg),
+            "symbolic_propagation" => Some(EdgeProvenance::SymbolicPropagation),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UnresolvedRef {
     pub name: String,
@@ -37,6 +85,26 @@ pub enum EdgeCertainty {
     DynamicFallback,
 }
 
+impl EdgeCertainty {
+    /// The snake_case name used in serialized output and the cache.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeCertainty::Confirmed => "confirmed",
+            EdgeCertainty::Inferred => "inferred",
+            EdgeCertainty::DynamicFallback => "dynamic_fallback",
+        }
+    }
+
+    pub fn from_str_opt(s: &str) -> Option<Self> {
+        match s {
+            "confirmed" => Some(EdgeCertainty::Confirmed),
+            "inferred" => Some(EdgeCertainty::Inferred),
+            "dynamic_fallback" => Some(EdgeCertainty::DynamicFallback),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Reference {
     pub from: SymbolId,
@@ -48,6 +116,21 @@ pub struct Reference {
     pub provenance: EdgeProvenance,
 }
 
+impl Reference {
+    /// A confirmed call-graph edge `from -> to` of `kind` at `file:line`.
+    pub fn new(from: SymbolId, to: SymbolId, kind: RefKind, file: &str, line: u32) -> Self {
+        Self {
+            from,
+            to,
+            kind,
+            file: file.to_string(),
+            line,
+            certainty: EdgeCertainty::Confirmed,
+            provenance: EdgeProvenance::CallGraph,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct ReferenceSer<'a> {
     from: &'a SymbolId,
@@ -133,6 +216,9 @@ impl SymbolIndex {
                 .or_insert_with(Vec::new)
                 .push(s.clone());
         }
+        for syms in by_file.values_mut() {
+            syms.sort_by(position_order);
+        }
         Self {
             symbols,
             by_name,
@@ -140,16 +226,47 @@ impl SymbolIndex {
         }
     }
 
+    /// The innermost symbol in `file` whose range covers `line`: the smallest
+    /// span wins, then the more specific kind ([`SymbolKind::specificity`]),
+    /// then the later start line and finally the id, so the choice never
+    /// depends on the order symbols were indexed in.
     pub fn enclosing_symbol(&self, file: &str, line: u32) -> Option<&Symbol> {
-        self.by_file
-            .get(file)?
+        self.file_symbols(file)
             .iter()
             .filter(|s| s.range.start_line <= line && line <= s.range.end_line)
             .min_by_key(|s| {
                 let span = s.range.end_line.saturating_sub(s.range.start_line);
-                (span, u32::MAX.saturating_sub(s.range.start_line))
+                (
+                    span,
+                    s.kind.specificity(),
+                    std::cmp::Reverse(s.range.start_line),
+                    &s.id.0,
+                )
             })
     }
+
+    /// The symbols defined in `file` in source order: by start line, an
+    /// enclosing item before the items nested in it, then by id. Empty when
+    /// the file has no symbols or is not indexed.
+    pub fn symbols_in_file(&self, file: &str) -> Vec<&Symbol> {
+        self.file_symbols(file).iter().collect()
+    }
+
+    fn file_symbols(&self, file: &str) -> &[Symbol] {
+        self.by_file
+            .get(file)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Source order for symbols of one file; `by_file` is kept sorted by it.
+fn position_order(a: &Symbol, b: &Symbol) -> std::cmp::Ordering {
+    a.range
+        .start_line
+        .cmp(&b.range.start_line)
+        .then(b.range.end_line.cmp(&a.range.end_line))
+        .then_with(|| a.id.0.cmp(&b.id.0))
 }
 
 #[cfg(test)]
@@ -257,4 +374,79 @@ confidence: inferred
         let r: Reference = serde_json::from_value(raw).expect("deserialize reference");
         assert_eq!(r.provenance, EdgeProvenance::CallGraph);
     }
+
+    fn symbol(name: &str, kind: crate::ir::SymbolKind, start: u32, end: u32) -> Symbol {
+        Symbol {
+            id: SymbolId::new("rust", "src/lib.rs", &kind, name, start),
+            name: name.to_string(),
+            kind,
+            file: "src/lib.rs".to_string(),
+            range: crate::ir::TextRange {
+                start_line: start,
+                end_line: end,
+            },
+            language: "rust".to_string(),
+        }
+    }
+
+    #[test]
+    fn enclosing_symbol_picks_innermost_nested_rust_item() {
+        use crate::languages::LanguageAnalyzer;
+        let src = "mod outer {\n    pub struct S;\n    impl S {\n        pub fn run(&self) {\n            helper();\n        }\n    }\n    fn helper() {}\n}\n";
+        let syms =
+            crate::languages::rust_ts::RustTsAnalyzer::new().symbols_in_file("src/lib.rs", src);
+        let index = SymbolIndex::build(syms);
+        let at = |line| {
+            index
+                .enclosing_symbol("src/lib.rs", line)
+                .map(|s| s.name.as_str())
+        };
+        assert_eq!(at(5), Some("run"));
+        assert_eq!(at(8), Some("helper"));
+        assert_eq!(at(2), Some("S"));
+        assert_eq!(index.enclosing_symbol("src/other.rs", 5), None);
+    }
+
+    #[test]
+    fn symbols_in_file_lists_symbols_in_source_order() {
+        use crate::ir::SymbolKind;
+        let index = SymbolIndex::build(vec![
+            symbol("helper", SymbolKind::Function, 8, 8),
+            symbol("run", SymbolKind::Method, 4, 6),
+            symbol("S", SymbolKind::Struct, 2, 2),
+            symbol("outer", SymbolKind::Module, 1, 9),
+            symbol("Runner", SymbolKind::Trait, 3, 7),
+            symbol("Inner", SymbolKind::Module, 1, 1),
+        ]);
+        let names: Vec<&str> = index
+            .symbols_in_file("src/lib.rs")
+            .into_iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["outer", "Inner", "S", "Runner", "run", "helper"]);
+        assert!(index.symbols_in_file("src/other.rs").is_empty());
+    }
+
+    #[test]
+    fn enclosing_symbol_breaks_span_ties_by_kind_regardless_of_order() {
+        use crate::ir::SymbolKind;
+        let syms = vec![
+            symbol("Wrapper", SymbolKind::Struct, 3, 5),
+            symbol("free", SymbolKind::Function, 3, 5),
+            symbol("method", SymbolKind::Method, 3, 5),
+            symbol("outer", SymbolKind::Module, 1, 10),
+        ];
+        for rotation in 0..syms.len() {
+            let mut v = syms.clone();
+            v.rotate_left(rotation);
+            let index = SymbolIndex::build(v);
+            let got = index.enclosing_symbol("src/lib.rs", 4).unwrap();
+            assert_eq!(got.name, "method");
+        }
+        let index = SymbolIndex::build(syms[..2].to_vec());
+        assert_eq!(
+            index.enclosing_symbol("src/lib.rs", 4).unwrap().name,
+            "free"
+        );
+    }
 }
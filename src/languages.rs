@@ -14,9 +14,11 @@ pub trait LanguageAnalyzer {
     }
 }
 
+pub mod cpp_spec;
 pub mod go_spec;
 pub mod java_spec;
 pub mod js_spec;
+pub mod kotlin_spec;
 pub mod path;
 pub mod py_spec;
 pub mod ruby_spec;
@@ -28,7 +30,8 @@ pub mod util;
 
 // TS-only now
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LanguageKind {
     Auto,
     Rust,
@@ -39,40 +42,202 @@ pub enum LanguageKind {
     Tsx,
     Go,
     Java,
+    Kotlin,
+    /// C and C++ (sources and headers share one analyzer).
+    Cpp,
 }
 
-pub fn analyzer_for_path(path: &str, lang: LanguageKind) -> Option<Box<dyn LanguageAnalyzer>> {
-    let ext = std::path::Path::new(path)
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
-    let target = match lang {
-        LanguageKind::Rust => "rs",
-        LanguageKind::Ruby => "rb",
-        LanguageKind::Python => "py",
-        LanguageKind::Javascript => "js",
-        LanguageKind::Typescript => "ts",
-        LanguageKind::Tsx => "tsx",
-        LanguageKind::Go => "go",
-        LanguageKind::Java => "java",
-        LanguageKind::Auto => ext,
-    };
-    match target {
-        "rs" => Some(Box::new(rust_spec::SpecRustAnalyzer::new())),
-        "rb" => Some(Box::new(ruby_spec::SpecRubyAnalyzer::new())),
-        "py" => Some(Box::new(py_spec::SpecPyAnalyzer::new())),
-        "js" => Some(Box::new(js_spec::SpecJsAnalyzer::new())),
-        "ts" => Some(Box::new(ts_spec::SpecTsAnalyzer::new_ts())),
-        "tsx" => Some(Box::new(ts_spec::SpecTsAnalyzer::new_tsx())),
-        "go" => Some(Box::new(go_spec::SpecGoAnalyzer::new())),
-        "java" => Some(Box::new(java_spec::SpecJavaAnalyzer::new())),
+impl LanguageKind {
+    /// Lowercase name, as used for `--lang` and in symbol ids.
+    pub fn name(self) -> &'static str {
+        match self {
+            LanguageKind::Auto => "auto",
+            LanguageKind::Rust => "rust",
+            LanguageKind::Ruby => "ruby",
+            LanguageKind::Python => "python",
+            LanguageKind::Javascript => "javascript",
+            LanguageKind::Typescript => "typescript",
+            LanguageKind::Tsx => "tsx",
+            LanguageKind::Go => "go",
+            LanguageKind::Java => "java",
+            LanguageKind::Kotlin => "kotlin",
+            LanguageKind::Cpp => "cpp",
+        }
+    }
+
+    /// Inverse of [`LanguageKind::name`] (`"c"` and `"c++"` also name [`LanguageKind::Cpp`]).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "auto" => LanguageKind::Auto,
+            "rust" => LanguageKind::Rust,
+            "ruby" => LanguageKind::Ruby,
+            "python" => LanguageKind::Python,
+            "javascript" => LanguageKind::Javascript,
+            "typescript" => LanguageKind::Typescript,
+            "tsx" => LanguageKind::Tsx,
+            "go" => LanguageKind::Go,
+            "java" => LanguageKind::Java,
+            "kotlin" => LanguageKind::Kotlin,
+            "cpp" | "c" | "c++" => LanguageKind::Cpp,
+            _ => return None,
+        })
+    }
+}
+
+/// Per-run analyzer settings: which language each file is analyzed as and
+/// which optional relationships the analyzers extract. [`analyzer_for_path`] and [`analyzer_for_file`] use the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzerConfig {
+    /// Rust: `macro_rules!` and `#[proc_macro_derive]` definitions become
+    /// `Macro` symbols, `name!(..)` invocations call references, and
+    /// `#[derive(..)]` entries type-use references.
+    pub include_macros: bool,
+    /// Rust: struct literals, struct patterns and enum variants inside
+    /// function bodies become `TypeUse` references to their type, so field
+    /// and variant changes impact construction sites and matches.
+    pub include_constructions: bool,
+    /// `GLOB=LANG` overrides checked ahead of extension detection, for
+    /// sources with nonstandard extensions (`*.rs.in`, vendored `*.es6`, ...).
+    /// Patterns containing `/` match the whole root-relative path, others the
+    /// file name; `*` matches any run of characters and `?` a single one. The
+    /// first matching pattern wins.
+    pub language_map: Vec<(String, LanguageKind)>,
+}
+
+impl AnalyzerConfig {
+    /// Language of `path` per [`AnalyzerConfig::language_map`], else by file
+    /// extension, if it is one dimpact analyzes.
+    pub fn language_for_path(&self, path: &str) -> Option<LanguageKind> {
+        mapped_language(&self.language_map, path).or_else(|| language_for_path(path))
+    }
+
+    /// [`analyzer_for_file`] under these settings.
+    pub fn analyzer_for_file(
+        &self,
+        path: &str,
+        fallback: LanguageKind,
+    ) -> Option<Box<dyn LanguageAnalyzer>> {
+        self.analyzer_for_path(path, self.language_for_path(path).unwrap_or(fallback))
+    }
+
+    /// [`analyzer_for_path`] under these settings.
+    pub fn analyzer_for_path(
+        &self,
+        path: &str,
+        lang: LanguageKind,
+    ) -> Option<Box<dyn LanguageAnalyzer>> {
+        let lang = match lang {
+            LanguageKind::Auto => self.language_for_path(path)?,
+            lang => lang,
+        };
+        match lang {
+            LanguageKind::Rust => Some(Box::new(rust_spec::SpecRustAnalyzer::with_config(self))),
+            LanguageKind::Ruby => Some(Box::new(ruby_spec::SpecRubyAnalyzer::new())),
+            LanguageKind::Python => Some(Box::new(py_spec::SpecPyAnalyzer::new())),
+            LanguageKind::Javascript => Some(Box::new(js_spec::SpecJsAnalyzer::new())),
+            LanguageKind::Typescript => Some(Box::new(ts_spec::SpecTsAnalyzer::new_ts())),
+            LanguageKind::Tsx => Some(Box::new(ts_spec::SpecTsAnalyzer::new_tsx())),
+            LanguageKind::Go => Some(Box::new(go_spec::SpecGoAnalyzer::new())),
+            LanguageKind::Java => Some(Box::new(java_spec::SpecJavaAnalyzer::new())),
+            LanguageKind::Kotlin => Some(Box::new(kotlin_spec::SpecKotlinAnalyzer::new())),
+            LanguageKind::Cpp => Some(Box::new(cpp_spec::SpecCppAnalyzer::new())),
+            LanguageKind::Auto => None,
+        }
+    }
+}
+
+/// Parse one `GLOB=LANG` entry of a language map (e.g. `*.es6=javascript`).
+pub fn parse_language_mapping(spec: &str) -> Result<(String, LanguageKind), String> {
+    let (glob, lang) = spec
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected GLOB=LANG, got {spec:?}"))?;
+    let glob = glob.trim();
+    if glob.is_empty() {
+        return Err(format!("empty glob in {spec:?}"));
+    }
+    match LanguageKind::from_name(lang.trim()) {
+        Some(LanguageKind::Auto) | None => {
+            Err(format!("unknown language {:?} in {spec:?}", lang.trim()))
+        }
+        Some(kind) => Ok((glob.to_string(), kind)),
+    }
+}
+
+fn mapped_language(map: &[(String, LanguageKind)], path: &str) -> Option<LanguageKind> {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    map.iter()
+        .find(|(glob, _)| {
+            let subject = if glob.contains('/') { path } else { file_name };
+            glob_matches(glob.as_bytes(), subject.as_bytes())
+        })
+        .map(|(_, kind)| *kind)
+}
+
+/// Whether `text` matches `pattern` (`*` and `?` wildcards). On a mismatch
+/// only the most recent `*` is retried one byte further, so matching takes
+/// at most `pattern.len() * text.len()` steps.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Language of `path` by file extension, if it is one dimpact analyzes;
+/// [`AnalyzerConfig::language_for_path`] also applies a language map.
+pub fn language_for_path(path: &str) -> Option<LanguageKind> {
+    let ext = std::path::Path::new(path).extension()?.to_str()?;
+    match ext {
+        "rs" => Some(LanguageKind::Rust),
+        "rb" => Some(LanguageKind::Ruby),
+        "py" => Some(LanguageKind::Python),
+        "js" => Some(LanguageKind::Javascript),
+        "ts" => Some(LanguageKind::Typescript),
+        "tsx" => Some(LanguageKind::Tsx),
+        "go" => Some(LanguageKind::Go),
+        "java" => Some(LanguageKind::Java),
+        "kt" | "kts" => Some(LanguageKind::Kotlin),
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Some(LanguageKind::Cpp),
         _ => None,
     }
 }
 
+/// Analyzer for one file of a (possibly mixed-language) change set: the
+/// language is detected from the extension, and `fallback` only applies when
+/// detection fails (e.g. `--lang ruby` for an extension-less script).
+pub fn analyzer_for_file(path: &str, fallback: LanguageKind) -> Option<Box<dyn LanguageAnalyzer>> {
+    AnalyzerConfig::default().analyzer_for_file(path, fallback)
+}
+
+pub fn analyzer_for_path(path: &str, lang: LanguageKind) -> Option<Box<dyn LanguageAnalyzer>> {
+    AnalyzerConfig::default().analyzer_for_path(path, lang)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{LanguageKind, analyzer_for_path};
+    use super::{
+        LanguageKind, analyzer_for_path, glob_matches, mapped_language, parse_language_mapping,
+    };
 
     #[test]
     fn analyzer_for_path_recognizes_go_java_python_extensions() {
@@ -84,6 +249,34 @@ mod tests {
         assert!(analyzer_for_path("main.any", LanguageKind::Python).is_some());
     }
 
+    #[test]
+    fn analyzer_for_path_routes_c_and_cpp_extensions_to_one_analyzer() {
+        for path in ["a.c", "a.h", "a.cc", "a.cpp", "a.hpp"] {
+            let a = analyzer_for_path(path, LanguageKind::Auto).expect(path);
+            assert_eq!(a.language(), "cpp", "{path}");
+        }
+        assert!(analyzer_for_path("main.any", LanguageKind::Cpp).is_some());
+    }
+
+    #[test]
+    fn analyzer_for_path_routes_kt_and_kts_to_kotlin() {
+        for path in ["Main.kt", "build.gradle.kts"] {
+            let a = analyzer_for_path(path, LanguageKind::Auto).expect(path);
+            assert_eq!(a.language(), "kotlin", "{path}");
+        }
+        assert!(analyzer_for_path("main.any", LanguageKind::Kotlin).is_some());
+    }
+
+    #[test]
+    fn analyzer_for_file_prefers_extension_over_fallback() {
+        use super::analyzer_for_file;
+        let ts = analyzer_for_file("web/main.ts", LanguageKind::Rust).unwrap();
+        assert_eq!(ts.language(), "typescript");
+        let rb = analyzer_for_file("bin/tool", LanguageKind::Ruby).unwrap();
+        assert_eq!(rb.language(), "ruby");
+        assert!(analyzer_for_file("bin/tool", LanguageKind::Auto).is_none());
+    }
+
     #[test]
     fn analyzer_for_path_existing_languages_unchanged() {
         assert!(analyzer_for_path("src/lib.rs", LanguageKind::Auto).is_some());
@@ -123,4 +316,62 @@ mod tests {
         let py_syms = py.symbols_in_file("main.py", "def run():\n    return 1\n");
         assert!(py_syms.iter().any(|s| s.name == "run"));
     }
+
+    #[test]
+    fn rust_analyzer_is_tree_sitter_backed() {
+        // `.rs` files go through the tree-sitter analyzer unconditionally (there
+        // is no feature gate); the regex analyzer only supplies `use` maps.
+        let rs = analyzer_for_path("src/lib.rs", LanguageKind::Auto).expect("rust analyzer");
+        assert_eq!(rs.language(), "rust");
+        let src = "struct S;\nimpl S {\n    fn m(&self) {\n        // ghost();\n        helper();\n    }\n}\nfn helper() {}\n";
+        let syms = rs.symbols_in_file("src/lib.rs", src);
+        let m = syms.iter().find(|s| s.name == "m").expect("impl method");
+        assert!(m.id.0.contains("S%3A%3Am"));
+        let names: Vec<_> = rs
+            .unresolved_refs("src/lib.rs", src)
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["helper".to_string()]);
+    }
+
+    #[test]
+    fn language_map_globs_match_file_names_or_whole_paths() {
+        let map = vec![
+            parse_language_mapping("*.es6=javascript").unwrap(),
+            parse_language_mapping("*.rs.in=rust").unwrap(),
+            parse_language_mapping("web/legacy/*.ts=tsx").unwrap(),
+        ];
+        assert_eq!(
+            mapped_language(&map, "vendor/lib.es6"),
+            Some(LanguageKind::Javascript)
+        );
+        assert_eq!(
+            mapped_language(&map, "./build/gen.rs.in"),
+            Some(LanguageKind::Rust)
+        );
+        assert_eq!(
+            mapped_language(&map, "web/legacy/app.ts"),
+            Some(LanguageKind::Tsx)
+        );
+        assert_eq!(mapped_language(&map, "web/app.ts"), None);
+        assert!(parse_language_mapping("*.es6").is_err());
+        assert!(parse_language_mapping("*.es6=cobol").is_err());
+    }
+
+    #[test]
+    fn glob_matching_backtracks_without_blowing_up() {
+        assert!(glob_matches(b"*.rs.in", b"gen.rs.in"));
+        assert!(glob_matches(b"a*b?d*", b"axxbcd"));
+        assert!(glob_matches(b"**", b""));
+        assert!(!glob_matches(b"*.rs", b"lib.rs.in"));
+        assert!(!glob_matches(b"a?", b"a"));
+        // Exponential for naive recursion: every `*` split is retried
+        let text = "a".repeat(200);
+        assert!(!glob_matches(
+            "*a".repeat(20).as_bytes(),
+            format!("{text}b").as_bytes()
+        ));
+        assert!(glob_matches("*a".repeat(20).as_bytes(), text.as_bytes()));
+    }
 }
@@ -0,0 +1,231 @@
+use crate::ir::reference::{RefKind, UnresolvedRef};
+use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
+use crate::languages::LanguageAnalyzer;
+use crate::languages::path::normalize_path_like;
+use crate::languages::util::{byte_to_line, line_offsets};
+use crate::ts_core::{QueryRunner, shared_queries_cpp};
+
+/// Tree-sitter analyzer for C and C++ sources and headers. Both dialects go
+/// through the C++ grammar, which accepts the C subset dimpact looks at.
+pub struct SpecCppAnalyzer {
+    queries: &'static crate::ts_core::CompiledQueries,
+    runner: QueryRunner,
+}
+
+impl SpecCppAnalyzer {
+    pub fn new() -> Self {
+        let queries = shared_queries_cpp();
+        let runner = QueryRunner::new_cpp();
+        Self { queries, runner }
+    }
+}
+
+impl Default for SpecCppAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageAnalyzer for SpecCppAnalyzer {
+    fn language(&self) -> &'static str {
+        "cpp"
+    }
+
+    fn symbols_in_file(&self, path: &str, source: &str) -> Vec<Symbol> {
+        let offs = line_offsets(source);
+        let matches = self.runner.run_captures(source, &self.queries.decl);
+        // class/struct bodies, so inline methods can be qualified by their type
+        let types: Vec<(usize, usize, &str)> = matches
+            .iter()
+            .filter_map(|caps| {
+                let decl = caps.iter().find(|c| c.name == "decl")?;
+                if !matches!(decl.kind.as_str(), "class_specifier" | "struct_specifier") {
+                    return None;
+                }
+                let name = caps.iter().find(|c| c.name == "name")?;
+                Some((decl.start, decl.end, &source[name.start..name.end]))
+            })
+            .collect();
+        let mut out = Vec::new();
+        for caps in &matches {
+            let Some(nc) = caps.iter().find(|c| c.name == "name") else {
+                continue;
+            };
+            let Some(dc) = caps.iter().find(|c| c.name == "decl") else {
+                continue;
+            };
+            let name = &source[nc.start..nc.end];
+            if name.is_empty() {
+                continue;
+            }
+            let scope = caps
+                .iter()
+                .find(|c| c.name == "scope")
+                .map(|c| &source[c.start..c.end]);
+            let (kind, qualifier) = match dc.kind.as_str() {
+                "class_specifier" | "struct_specifier" => (SymbolKind::Struct, None),
+                "enum_specifier" => (SymbolKind::Enum, None),
+                _ if scope.is_some() => (SymbolKind::Method, scope),
+                _ if nc.kind == "field_identifier" => {
+                    let owner = types
+                        .iter()
+                        .filter(|(s, e, _)| *s < dc.start && dc.end <= *e)
+                        .min_by_key(|(s, e, _)| e - s)
+                        .map(|(_, _, n)| *n);
+                    (SymbolKind::Method, owner)
+                }
+                _ => (SymbolKind::Function, None),
+            };
+            let sl = byte_to_line(&offs, dc.start);
+            let el = byte_to_line(&offs, dc.end.saturating_sub(1)).max(sl);
+            out.push(Symbol {
+                id: SymbolId::new_qualified("cpp", path, &kind, qualifier, name, sl),
+                name: name.to_string(),
+                kind,
+                file: path.to_string(),
+                range: TextRange {
+                    start_line: sl,
+                    end_line: el,
+                },
+                language: "cpp".to_string(),
+            });
+        }
+        out
+    }
+
+    fn unresolved_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
+        let offs = line_offsets(source);
+        let mut out = Vec::new();
+        for caps in self.runner.run_captures(source, &self.queries.calls) {
+            let Some(n) = caps.iter().find(|c| c.name == "name") else {
+                continue;
+            };
+            let name = source[n.start..n.end].to_string();
+            if name.is_empty() {
+                continue;
+            }
+            let is_method = n.kind == "field_identifier";
+            let qual = caps
+                .iter()
+                .find(|c| c.name == "qual")
+                .map(|q| source[q.start..q.end].to_string());
+            out.push(UnresolvedRef {
+                name,
+                kind: RefKind::Call,
+                file: path.to_string(),
+                line: byte_to_line(&offs, n.start),
+                qualifier: qual.filter(|s| !s.is_empty()),
+                is_method,
+            });
+        }
+        out
+    }
+
+    /// `#include "..."` lines become glob imports of the included header's
+    /// module path. A header and the source file sharing its stem map to the
+    /// same module path, so calls into an included header also prefer the
+    /// definitions in its companion `.c`/`.cpp` file.
+    fn imports_in_file(
+        &self,
+        path: &str,
+        source: &str,
+    ) -> std::collections::HashMap<String, String> {
+        let mut map = std::collections::HashMap::new();
+        for caps in self.runner.run_captures(source, &self.queries.imports) {
+            let Some(m) = caps.iter().find(|c| c.name == "mod") else {
+                continue;
+            };
+            let raw = source[m.start..m.end].trim_matches('"');
+            for module in include_module_paths(path, raw) {
+                map.insert(format!("__glob__{module}"), module);
+            }
+        }
+        map
+    }
+}
+
+/// Module paths an `#include "raw"` in `cur_file` may refer to: the header
+/// next to the including file (the compiler's first lookup for quoted
+/// includes) and, for non-relative specs, `raw` itself as if found on an
+/// include path. Header extensions are dropped.
+pub fn include_module_paths(cur_file: &str, raw: &str) -> Vec<String> {
+    let raw = raw.trim().replace('\\', "/");
+    let stem = HEADER_EXTS
+        .iter()
+        .find_map(|ext| raw.strip_suffix(ext))
+        .unwrap_or(&raw);
+    if stem.is_empty() {
+        return Vec::new();
+    }
+    let base = std::path::Path::new(cur_file)
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+    let mut out = vec![normalize_path_like(&base.join(stem))];
+    if !(stem.starts_with("./") || stem.starts_with("../")) {
+        let bare = normalize_path_like(std::path::Path::new(stem));
+        if !out.contains(&bare) {
+            out.push(bare);
+        }
+    }
+    out
+}
+
+const HEADER_EXTS: [&str; 5] = [".h", ".hh", ".hpp", ".hxx", ".inc"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_functions_types_and_methods() {
+        let a = SpecCppAnalyzer::new();
+        let src = r#"#include "util.h"
+struct Point { int x; };
+class Shape {
+public:
+    int area() { return helper(1); }
+};
+int helper(int);
+static char *name(void) { return 0; }
+int Shape::perimeter() { return 0; }
+int main() { Shape s; s.area(); return helper(2); }
+"#;
+        let syms = a.symbols_in_file("src/main.cpp", src);
+        let ids: Vec<&str> = syms.iter().map(|s| s.id.0.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "cpp:src/main.cpp:struct:Point:2",
+                "cpp:src/main.cpp:struct:Shape:3",
+                "cpp:src/main.cpp:method:Shape%3A%3Aarea:5",
+                "cpp:src/main.cpp:fn:name:8",
+                "cpp:src/main.cpp:method:Shape%3A%3Aperimeter:9",
+                "cpp:src/main.cpp:fn:main:10",
+            ]
+        );
+
+        let refs = a.unresolved_refs("src/main.cpp", src);
+        let calls: Vec<(&str, u32, bool)> = refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.line, r.is_method))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("helper", 5, false),
+                ("area", 10, true),
+                ("helper", 10, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_includes_resolve_next_to_the_including_file() {
+        let a = SpecCppAnalyzer::new();
+        let src = "#include \"util.h\"\n#include \"../include/api.hpp\"\n#include <vector>\n";
+        let im = a.imports_in_file("src/core/main.c", src);
+        let mut globs: Vec<&str> = im.values().map(String::as_str).collect();
+        globs.sort();
+        assert_eq!(globs, vec!["src/core/util", "src/include/api", "util"]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::ir::reference::{RefKind, UnresolvedRef};
 use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
-use crate::languages::LanguageAnalyzer;
-use crate::languages::util::{byte_to_line, line_offsets};
+use crate::languages::util::{byte_to_line, line_offsets, mask_comments_and_strings};
+use crate::languages::{LanguageAnalyzer, LanguageKind};
 
 pub struct SpecGoAnalyzer;
 
@@ -73,7 +73,7 @@ impl LanguageAnalyzer for SpecGoAnalyzer {
         let mut out = Vec::new();
         let mut seen: HashSet<(u32, String, Option<String>, bool)> = HashSet::new();
         let offs = line_offsets(source);
-        let scan_source = mask_go_non_code(source);
+        let scan_source = mask_comments_and_strings(source, LanguageKind::Go);
         let imports = self.imports_in_file(path, source);
         let import_aliases: HashSet<String> = imports.keys().cloned().collect();
         let decl_lines: HashSet<u32> = source
@@ -230,146 +230,6 @@ impl LanguageAnalyzer for SpecGoAnalyzer {
     }
 }
 
-fn mask_go_non_code(source: &str) -> String {
-    #[derive(Clone, Copy)]
-    enum State {
-        Code,
-        LineComment,
-        BlockComment,
-        String,
-        RawString,
-        Rune,
-    }
-
-    let src = source.as_bytes();
-    let mut out = src.to_vec();
-    let mut i = 0usize;
-    let mut state = State::Code;
-
-    while i < src.len() {
-        match state {
-            State::Code => {
-                if i + 1 < src.len() && src[i] == b'/' && src[i + 1] == b'/' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::LineComment;
-                    continue;
-                }
-                if i + 1 < src.len() && src[i] == b'/' && src[i + 1] == b'*' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::BlockComment;
-                    continue;
-                }
-                if src[i] == b'"' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::String;
-                    continue;
-                }
-                if src[i] == b'`' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::RawString;
-                    continue;
-                }
-                if src[i] == b'\'' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Rune;
-                    continue;
-                }
-                i += 1;
-            }
-            State::LineComment => {
-                if src[i] == b'\n' {
-                    state = State::Code;
-                } else {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::BlockComment => {
-                if i + 1 < src.len() && src[i] == b'*' && src[i + 1] == b'/' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::String => {
-                if src[i] == b'\\' {
-                    out[i] = b' ';
-                    if i + 1 < src.len() {
-                        if src[i + 1] != b'\n' {
-                            out[i + 1] = b' ';
-                        }
-                        i += 2;
-                    } else {
-                        i += 1;
-                    }
-                    continue;
-                }
-                if src[i] == b'"' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::RawString => {
-                if src[i] == b'`' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::Rune => {
-                if src[i] == b'\\' {
-                    out[i] = b' ';
-                    if i + 1 < src.len() {
-                        if src[i + 1] != b'\n' {
-                            out[i + 1] = b' ';
-                        }
-                        i += 2;
-                    } else {
-                        i += 1;
-                    }
-                    continue;
-                }
-                if src[i] == b'\'' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-        }
-    }
-
-    String::from_utf8_lossy(&out).into_owned()
-}
-
 fn find_decl_block_end(source: &str, decl_start: usize) -> Option<usize> {
     let bytes = source.as_bytes();
     let mut i = decl_start;
@@ -683,10 +543,7 @@ func run() int {
                 && r.line == 14
         }));
         assert!(refs.iter().any(|r| {
-            r.name == "Handle"
-                && r.qualifier.as_deref() == Some("h")
-                && r.is_method
-                && r.line == 20
+            r.name == "Handle" && r.qualifier.as_deref() == Some("h") && r.is_method && r.line == 20
         }));
         assert!(refs.iter().any(|r| {
             r.name == "methodValue" && r.qualifier.is_none() && !r.is_method && r.line == 24
@@ -766,4 +623,3 @@ func run() int {
         );
     }
 }
-
@@ -1,7 +1,7 @@
 use crate::ir::reference::{RefKind, UnresolvedRef};
 use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
-use crate::languages::LanguageAnalyzer;
-use crate::languages::util::{byte_to_line, line_offsets};
+use crate::languages::util::{byte_to_line, line_offsets, mask_comments_and_strings};
+use crate::languages::{LanguageAnalyzer, LanguageKind};
 
 pub struct SpecJavaAnalyzer;
 
@@ -158,7 +158,7 @@ impl LanguageAnalyzer for SpecJavaAnalyzer {
         let mut out = Vec::new();
         let mut seen: HashSet<(u32, String, Option<String>, bool)> = HashSet::new();
         let offs = line_offsets(source);
-        let scan_source = mask_java_non_code(source);
+        let scan_source = mask_comments_and_strings(source, LanguageKind::Java);
         let imports = self.imports_in_file(path, source);
         let import_aliases: HashSet<String> = imports.keys().cloned().collect();
 
@@ -170,7 +170,12 @@ impl LanguageAnalyzer for SpecJavaAnalyzer {
             .collect();
         let class_name_set: HashSet<String> = syms
             .iter()
-            .filter(|s| matches!(s.kind, SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Enum))
+            .filter(|s| {
+                matches!(
+                    s.kind,
+                    SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Enum
+                )
+            })
             .map(|s| s.name.clone())
             .collect();
 
@@ -183,7 +188,12 @@ impl LanguageAnalyzer for SpecJavaAnalyzer {
 
         let mut class_scopes: Vec<JavaClassScope> = syms
             .iter()
-            .filter(|s| matches!(s.kind, SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Enum))
+            .filter(|s| {
+                matches!(
+                    s.kind,
+                    SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Enum
+                )
+            })
             .map(|s| JavaClassScope {
                 start_line: s.range.start_line,
                 end_line: s.range.end_line,
@@ -423,6 +433,11 @@ impl LanguageAnalyzer for SpecJavaAnalyzer {
         use std::collections::HashMap;
 
         let mut map = HashMap::new();
+        let re_package = Regex::new(r"(?m)^\s*package\s+([A-Za-z_][A-Za-z0-9_.]*)\s*;")
+            .expect("valid java package regex");
+        if let Some(pkg) = re_package.captures(source).and_then(|c| c.get(1)) {
+            map.insert("__package__".to_string(), pkg.as_str().replace('.', "::"));
+        }
         let re_import =
             Regex::new(r"(?m)^\s*import\s+(static\s+)?([A-Za-z_][A-Za-z0-9_.]*)(\.\*)?\s*;")
                 .expect("valid java import regex");
@@ -452,152 +467,6 @@ impl LanguageAnalyzer for SpecJavaAnalyzer {
     }
 }
 
-fn mask_java_non_code(source: &str) -> String {
-    #[derive(Clone, Copy)]
-    enum State {
-        Code,
-        LineComment,
-        BlockComment,
-        String,
-        Char,
-        TextBlock,
-    }
-
-    let src = source.as_bytes();
-    let mut out = src.to_vec();
-    let mut i = 0usize;
-    let mut state = State::Code;
-
-    while i < src.len() {
-        match state {
-            State::Code => {
-                if i + 1 < src.len() && src[i] == b'/' && src[i + 1] == b'/' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::LineComment;
-                    continue;
-                }
-                if i + 1 < src.len() && src[i] == b'/' && src[i + 1] == b'*' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::BlockComment;
-                    continue;
-                }
-                if i + 2 < src.len() && src[i] == b'"' && src[i + 1] == b'"' && src[i + 2] == b'"'
-                {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    out[i + 2] = b' ';
-                    i += 3;
-                    state = State::TextBlock;
-                    continue;
-                }
-                if src[i] == b'"' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::String;
-                    continue;
-                }
-                if src[i] == b'\'' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Char;
-                    continue;
-                }
-                i += 1;
-            }
-            State::LineComment => {
-                if src[i] == b'\n' {
-                    state = State::Code;
-                } else {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::BlockComment => {
-                if i + 1 < src.len() && src[i] == b'*' && src[i + 1] == b'/' {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    i += 2;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::String => {
-                if src[i] == b'\\' {
-                    out[i] = b' ';
-                    if i + 1 < src.len() {
-                        if src[i + 1] != b'\n' {
-                            out[i + 1] = b' ';
-                        }
-                        i += 2;
-                    } else {
-                        i += 1;
-                    }
-                    continue;
-                }
-                if src[i] == b'"' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::Char => {
-                if src[i] == b'\\' {
-                    out[i] = b' ';
-                    if i + 1 < src.len() {
-                        if src[i + 1] != b'\n' {
-                            out[i + 1] = b' ';
-                        }
-                        i += 2;
-                    } else {
-                        i += 1;
-                    }
-                    continue;
-                }
-                if src[i] == b'\'' {
-                    out[i] = b' ';
-                    i += 1;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-            State::TextBlock => {
-                if i + 2 < src.len() && src[i] == b'"' && src[i + 1] == b'"' && src[i + 2] == b'"'
-                {
-                    out[i] = b' ';
-                    out[i + 1] = b' ';
-                    out[i + 2] = b' ';
-                    i += 3;
-                    state = State::Code;
-                    continue;
-                }
-                if src[i] != b'\n' {
-                    out[i] = b' ';
-                }
-                i += 1;
-            }
-        }
-    }
-
-    String::from_utf8_lossy(&out).into_owned()
-}
-
 fn is_java_control_like(name: &str) -> bool {
     matches!(
         name,
@@ -746,6 +615,7 @@ import static java.util.Collections.*;
         let ana = SpecJavaAnalyzer::new();
         let imports = ana.imports_in_file("src/Service.java", src);
 
+        assert_eq!(imports.get("__package__").map(String::as_str), Some("demo"));
         assert_eq!(
             imports.get("List").map(String::as_str),
             Some("java::util::List")
@@ -992,9 +862,7 @@ public class Main {
             r.name == "parse" && r.qualifier.as_deref() == Some("this") && r.is_method
         }));
         assert!(refs.iter().any(|r| {
-            r.name == "parseStatic"
-                && r.qualifier.as_deref() == Some("OverloadLab")
-                && !r.is_method
+            r.name == "parseStatic" && r.qualifier.as_deref() == Some("OverloadLab") && !r.is_method
         }));
         assert!(
             refs.iter()
@@ -1021,9 +889,10 @@ public class Main {
         let ana = SpecJavaAnalyzer::new();
 
         let syms = ana.symbols_in_file("demo/JavaOverloadLabV2.java", src);
-        assert!(syms.iter().any(|s| {
-            s.name == "JavaOverloadLabV2" && matches!(s.kind, SymbolKind::Struct)
-        }));
+        assert!(
+            syms.iter()
+                .any(|s| { s.name == "JavaOverloadLabV2" && matches!(s.kind, SymbolKind::Struct) })
+        );
         assert!(
             syms.iter()
                 .any(|s| s.name == "run" && matches!(s.kind, SymbolKind::Method))
@@ -1083,9 +952,10 @@ public class Main {
                 && r.qualifier.as_deref() == Some("JavaOverloadLabV2")
                 && !r.is_method
         }));
-        assert!(refs.iter().any(|r| {
-            r.name == "decode" && r.qualifier.is_none() && !r.is_method
-        }));
+        assert!(
+            refs.iter()
+                .any(|r| { r.name == "decode" && r.qualifier.is_none() && !r.is_method })
+        );
         assert!(
             refs.iter().all(|r| {
                 let on_decl_line = matches!(r.line, 12 | 16 | 20 | 24);
@@ -1100,7 +970,8 @@ public class Main {
             "refs={refs_dbg:?}"
         );
         assert!(
-            refs.iter().all(|r| !(r.name == "JavaOverloadLabV2" && r.qualifier.is_none())),
+            refs.iter()
+                .all(|r| !(r.name == "JavaOverloadLabV2" && r.qualifier.is_none())),
             "refs={refs_dbg:?}"
         );
 
@@ -1124,9 +995,11 @@ public class Main {
         let ana = SpecJavaAnalyzer::new();
 
         let syms = ana.symbols_in_file("demo/ExtractionFpPointsV4.java", src);
-        assert!(syms.iter().any(|s| {
-            s.name == "ExtractionFpPointsV4" && matches!(s.kind, SymbolKind::Struct)
-        }));
+        assert!(
+            syms.iter().any(|s| {
+                s.name == "ExtractionFpPointsV4" && matches!(s.kind, SymbolKind::Struct)
+            })
+        );
         assert!(
             syms.iter()
                 .any(|s| s.name == "run" && matches!(s.kind, SymbolKind::Method))
@@ -1148,9 +1021,8 @@ public class Main {
             "refs={refs_dbg:?}"
         );
         assert!(
-            refs.iter().any(|r| {
-                r.name == "invoke" && r.qualifier.is_none() && !r.is_method
-            }),
+            refs.iter()
+                .any(|r| { r.name == "invoke" && r.qualifier.is_none() && !r.is_method }),
             "refs={refs_dbg:?}"
         );
         assert!(
@@ -3,17 +3,16 @@ use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
 use crate::languages::LanguageAnalyzer;
 use crate::languages::path::resolve_module_path;
 use crate::languages::util::{byte_to_line, line_offsets};
-use crate::ts_core::{QueryRunner, compile_queries_javascript, load_javascript_spec};
+use crate::ts_core::{QueryRunner, shared_queries_javascript};
 
 pub struct SpecJsAnalyzer {
-    queries: crate::ts_core::CompiledQueries,
+    queries: &'static crate::ts_core::CompiledQueries,
     runner: QueryRunner,
 }
 
 impl SpecJsAnalyzer {
     pub fn new() -> Self {
-        let spec = load_javascript_spec();
-        let queries = compile_queries_javascript(&spec).expect("compile js queries");
+        let queries = shared_queries_javascript();
         let runner = QueryRunner::new_javascript();
         Self { queries, runner }
     }
@@ -0,0 +1,584 @@
+use crate::ir::reference::{RefKind, UnresolvedRef};
+use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
+use crate::languages::util::{byte_to_line, line_offsets, mask_comments_and_strings};
+use crate::languages::{LanguageAnalyzer, LanguageKind};
+
+pub struct SpecKotlinAnalyzer;
+
+impl SpecKotlinAnalyzer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SpecKotlinAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageAnalyzer for SpecKotlinAnalyzer {
+    fn language(&self) -> &'static str {
+        "kotlin"
+    }
+
+    fn symbols_in_file(&self, path: &str, source: &str) -> Vec<Symbol> {
+        kotlin_decls(path, source)
+            .into_iter()
+            .map(|d| d.symbol)
+            .collect()
+    }
+
+    fn unresolved_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
+        use regex::Regex;
+        use std::collections::{HashMap, HashSet};
+
+        let mut out = Vec::new();
+        let mut seen: HashSet<(u32, String, Option<String>, bool)> = HashSet::new();
+        let offs = line_offsets(source);
+        let scan_source = mask_comments_and_strings(source, LanguageKind::Kotlin);
+        let imports = self.imports_in_file(path, source);
+        let import_aliases: HashSet<String> = imports
+            .keys()
+            .filter(|k| !k.starts_with("__"))
+            .cloned()
+            .collect();
+
+        let decls = kotlin_decls(path, source);
+        // Only the declared name is skipped, so expression bodies on the
+        // declaration line (`fun total() = sum(xs)`) still yield refs
+        let decl_name_offsets: HashSet<usize> = decls.iter().map(|d| d.name_offset).collect();
+        let class_names: HashSet<&str> = decls
+            .iter()
+            .filter(|d| d.is_type())
+            .map(|d| d.symbol.name.as_str())
+            .collect();
+
+        struct ClassScope {
+            start_line: u32,
+            end_line: u32,
+            methods: HashSet<String>,
+        }
+        let mut class_scopes: Vec<ClassScope> = decls
+            .iter()
+            .filter(|d| d.is_type())
+            .map(|d| ClassScope {
+                start_line: d.symbol.range.start_line,
+                end_line: d.symbol.range.end_line,
+                methods: HashSet::new(),
+            })
+            .collect();
+        for m in decls
+            .iter()
+            .filter(|d| matches!(d.symbol.kind, SymbolKind::Method))
+        {
+            let line = m.symbol.range.start_line;
+            if let Some(scope) = class_scopes
+                .iter_mut()
+                .filter(|c| line >= c.start_line && line <= c.end_line)
+                .max_by_key(|c| c.start_line)
+            {
+                scope.methods.insert(m.symbol.name.clone());
+            }
+        }
+
+        // Receiver types from `val repo: UserRepo` (properties and
+        // constructor parameters) and `val repo = UserRepo(...)`
+        let re_typed_var = Regex::new(
+            r"\b(?:val|var)\s+([a-z_][A-Za-z0-9_]*)\s*(?::\s*([A-Z][A-Za-z0-9_]*)|=\s*([A-Z][A-Za-z0-9_]*)\s*[(<])",
+        )
+        .expect("valid kotlin typed variable regex");
+        let mut receiver_types: HashMap<String, String> = HashMap::new();
+        for caps in re_typed_var.captures_iter(&scan_source) {
+            let (Some(var), Some(ty)) = (caps.get(1), caps.get(2).or_else(|| caps.get(3))) else {
+                continue;
+            };
+            receiver_types
+                .entry(var.as_str().to_string())
+                .or_insert_with(|| ty.as_str().to_string());
+        }
+
+        let re_qualified_call = Regex::new(
+            r"\b([A-Za-z_][A-Za-z0-9_]*(?:\s*[?!]*\.\s*[A-Za-z_][A-Za-z0-9_]*)+)\s*(?:<[^<>()\n]*>)?\s*\(",
+        )
+        .expect("valid kotlin qualified call regex");
+        for caps in re_qualified_call.captures_iter(&scan_source) {
+            let Some(m) = caps.get(0) else {
+                continue;
+            };
+            let Some(chain_cap) = caps.get(1) else {
+                continue;
+            };
+            let ln = byte_to_line(&offs, m.start());
+            let compact = chain_cap.as_str().replace([' ', '\t', '\n', '?', '!'], "");
+            let mut parts = compact.split('.').collect::<Vec<_>>();
+            let Some(name) = parts.pop() else {
+                continue;
+            };
+            if parts.is_empty() {
+                continue;
+            }
+            let first = parts[0];
+            let (qual, is_method) = if first == "this" || first == "super" {
+                (parts.join("."), true)
+            } else if let Some(ty) = receiver_types.get(first) {
+                parts[0] = ty.as_str();
+                (parts.join("."), true)
+            } else if import_aliases.contains(first) {
+                (parts.join("."), false)
+            } else {
+                let lower = first
+                    .chars()
+                    .next()
+                    .is_some_and(|c| !c.is_ascii_uppercase());
+                (parts.join("."), lower)
+            };
+
+            let key = (ln, name.to_string(), Some(qual.clone()), is_method);
+            if !seen.insert(key) {
+                continue;
+            }
+            out.push(UnresolvedRef {
+                name: name.to_string(),
+                kind: RefKind::Call,
+                file: path.to_string(),
+                line: ln,
+                qualifier: Some(qual),
+                is_method,
+            });
+        }
+
+        let re_bare_call = Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*)\s*(?:<[^<>()\n]*>)?\s*\(")
+            .expect("valid kotlin bare call regex");
+        for caps in re_bare_call.captures_iter(&scan_source) {
+            let Some(m) = caps.get(0) else {
+                continue;
+            };
+            let Some(name_cap) = caps.get(1) else {
+                continue;
+            };
+            let name = name_cap.as_str();
+            if is_kotlin_control_like(name)
+                || class_names.contains(name)
+                || decl_name_offsets.contains(&name_cap.start())
+            {
+                continue;
+            }
+            // Skip qualified calls (already handled above)
+            let before = scan_source[..m.start()].trim_end();
+            if before.ends_with('.') || before.ends_with("::") {
+                continue;
+            }
+            let ln = byte_to_line(&offs, m.start());
+            let key = (ln, name.to_string(), None, false);
+            if !seen.insert(key) {
+                continue;
+            }
+            out.push(UnresolvedRef {
+                name: name.to_string(),
+                kind: RefKind::Call,
+                file: path.to_string(),
+                line: ln,
+                qualifier: None,
+                is_method: false,
+            });
+        }
+
+        // A bare call to a method of the enclosing class is an implicit
+        // `this.<method>` call
+        for r in out.clone() {
+            if r.is_method || r.qualifier.is_some() {
+                continue;
+            }
+            let Some(scope) = class_scopes
+                .iter()
+                .filter(|c| r.line >= c.start_line && r.line <= c.end_line)
+                .max_by_key(|c| c.start_line)
+            else {
+                continue;
+            };
+            if !scope.methods.contains(&r.name) {
+                continue;
+            }
+            let key = (r.line, r.name.clone(), Some("this".to_string()), true);
+            if !seen.insert(key) {
+                continue;
+            }
+            out.push(UnresolvedRef {
+                name: r.name,
+                kind: RefKind::Call,
+                file: path.to_string(),
+                line: r.line,
+                qualifier: Some("this".to_string()),
+                is_method: true,
+            });
+        }
+
+        out
+    }
+
+    fn imports_in_file(
+        &self,
+        _path: &str,
+        source: &str,
+    ) -> std::collections::HashMap<String, String> {
+        use regex::Regex;
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        let scan_source = mask_comments_and_strings(source, LanguageKind::Kotlin);
+        let re_package = Regex::new(r"(?m)^\s*package\s+([A-Za-z_][A-Za-z0-9_.]*)")
+            .expect("valid kotlin package regex");
+        if let Some(pkg) = re_package.captures(&scan_source).and_then(|c| c.get(1)) {
+            map.insert("__package__".to_string(), pkg.as_str().replace('.', "::"));
+        }
+
+        let re_import = Regex::new(
+            r"(?m)^\s*import\s+([A-Za-z_][A-Za-z0-9_.]*?)(\.\*)?(?:\s+as\s+([A-Za-z_][A-Za-z0-9_]*))?\s*;?\s*$",
+        )
+        .expect("valid kotlin import regex");
+        for caps in re_import.captures_iter(&scan_source) {
+            let Some(path_cap) = caps.get(1) else {
+                continue;
+            };
+            let path = path_cap.as_str();
+            let norm = path.replace('.', "::");
+            if caps.get(2).is_some() {
+                map.insert(format!("__glob__{norm}"), norm);
+                continue;
+            }
+            let alias = caps
+                .get(3)
+                .map(|a| a.as_str().to_string())
+                .unwrap_or_else(|| path.rsplit('.').next().unwrap_or(path).to_string());
+            if alias.is_empty() {
+                continue;
+            }
+            map.insert(alias, norm);
+        }
+
+        map
+    }
+}
+
+struct KotlinDecl {
+    symbol: Symbol,
+    /// Byte offset of the declared name.
+    name_offset: usize,
+}
+
+impl KotlinDecl {
+    fn is_type(&self) -> bool {
+        matches!(
+            self.symbol.kind,
+            SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Enum
+        )
+    }
+}
+
+/// Classes, interfaces, objects and functions of a Kotlin file. A `fun`
+/// declared inside a type body is a method; top-level and extension
+/// functions outside any type are functions.
+fn kotlin_decls(path: &str, source: &str) -> Vec<KotlinDecl> {
+    use regex::Regex;
+    use std::collections::HashSet;
+
+    let scan_source = mask_comments_and_strings(source, LanguageKind::Kotlin);
+    let offs = line_offsets(source);
+    let mut out: Vec<KotlinDecl> = Vec::new();
+    let mut seen_ids: HashSet<String> = HashSet::new();
+
+    let re_type = Regex::new(
+        r"(?m)^[ \t]*(?:@[A-Za-z_][A-Za-z0-9_.]*(?:\([^)]*\))?\s*)*((?:(?:public|protected|private|internal|abstract|final|open|sealed|data|inner|enum|annotation|value|inline|expect|actual|companion)\s+)*)(class|interface|object)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .expect("valid kotlin type regex");
+    let mut type_ranges: Vec<(usize, usize)> = Vec::new();
+    for caps in re_type.captures_iter(&scan_source) {
+        let (Some(full), Some(mods), Some(kw), Some(name)) =
+            (caps.get(0), caps.get(1), caps.get(2), caps.get(3))
+        else {
+            continue;
+        };
+        let kind = match kw.as_str() {
+            "interface" => SymbolKind::Trait,
+            "class" if mods.as_str().split_whitespace().any(|m| m == "enum") => SymbolKind::Enum,
+            _ => SymbolKind::Struct,
+        };
+        let end = find_decl_end(&scan_source, name.end());
+        type_ranges.push((full.start(), end));
+        push_decl(
+            &mut out,
+            &mut seen_ids,
+            path,
+            &offs,
+            kind,
+            name.as_str(),
+            name.start(),
+            full.start(),
+            end,
+        );
+    }
+
+    let re_fun = Regex::new(
+        r"(?m)^[ \t]*(?:@[A-Za-z_][A-Za-z0-9_.]*(?:\([^)]*\))?\s*)*(?:(?:public|protected|private|internal|open|override|abstract|final|suspend|inline|operator|infix|tailrec|external|expect|actual)\s+)*fun\s+(?:<[^>\n]+>\s*)?(?:[A-Z][A-Za-z0-9_]*(?:<[^>\n]*>)?\??\.)?([A-Za-z_][A-Za-z0-9_]*)\s*\(",
+    )
+    .expect("valid kotlin fun regex");
+    for caps in re_fun.captures_iter(&scan_source) {
+        let (Some(full), Some(name)) = (caps.get(0), caps.get(1)) else {
+            continue;
+        };
+        let in_type = type_ranges
+            .iter()
+            .any(|&(s, e)| full.start() > s && full.start() < e);
+        let kind = if in_type {
+            SymbolKind::Method
+        } else {
+            SymbolKind::Function
+        };
+        let end = find_decl_end(&scan_source, name.end());
+        push_decl(
+            &mut out,
+            &mut seen_ids,
+            path,
+            &offs,
+            kind,
+            name.as_str(),
+            name.start(),
+            full.start(),
+            end,
+        );
+    }
+
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_decl(
+    out: &mut Vec<KotlinDecl>,
+    seen_ids: &mut std::collections::HashSet<String>,
+    path: &str,
+    offs: &[usize],
+    kind: SymbolKind,
+    name: &str,
+    name_offset: usize,
+    start: usize,
+    end: usize,
+) {
+    let sl = byte_to_line(offs, start);
+    let el = byte_to_line(offs, end.saturating_sub(1)).max(sl);
+    let id = SymbolId::new("kotlin", path, &kind, name, sl);
+    if !seen_ids.insert(id.0.clone()) {
+        return;
+    }
+    out.push(KotlinDecl {
+        symbol: Symbol {
+            id,
+            name: name.to_string(),
+            kind,
+            file: path.to_string(),
+            range: TextRange {
+                start_line: sl,
+                end_line: el,
+            },
+            language: "kotlin".to_string(),
+        },
+        name_offset,
+    });
+}
+
+/// End of the declaration whose name ends at `from`: the matching `}` of a
+/// block body, otherwise the end of the header or expression body. A newline
+/// outside brackets ends the declaration unless the line ends in an operator
+/// or the next line continues it (`{`, `:`, `=`, `.`).
+fn find_decl_end(masked: &str, from: usize) -> usize {
+    let bytes = masked.as_bytes();
+    let mut parens = 0i32;
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' => parens += 1,
+            b')' | b']' => parens -= 1,
+            b'{' if parens <= 0 => return find_block_end(bytes, i),
+            b'\n' if parens <= 0 => {
+                let line_tail = masked[from..i].trim_end();
+                let next = masked[i + 1..].trim_start();
+                let continues = line_tail.ends_with(['=', ',', '.', ':', '(', '>'])
+                    || next.starts_with(['{', ':', '=', '.', '?']);
+                if !continues {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn find_block_end(bytes: &[u8], open: usize) -> usize {
+    let mut depth = 0i32;
+    for (i, &b) in bytes.iter().enumerate().skip(open) {
+        if b == b'{' {
+            depth += 1;
+        } else if b == b'}' {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
+    }
+    bytes.len()
+}
+
+fn is_kotlin_control_like(name: &str) -> bool {
+    matches!(
+        name,
+        "if" | "for"
+            | "while"
+            | "when"
+            | "catch"
+            | "return"
+            | "throw"
+            | "try"
+            | "do"
+            | "else"
+            | "fun"
+            | "super"
+            | "this"
+            | "constructor"
+            | "init"
+            | "object"
+            | "in"
+            | "is"
+            | "as"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_kotlin_types_methods_and_top_level_functions() {
+        let src = r#"package demo
+
+data class Point(val x: Int, val y: Int)
+
+enum class Mode { ON, OFF }
+
+interface Runner {
+    fun run()
+}
+
+class Service(private val repo: Repo) : Runner {
+    override fun run() {
+        handle(1)
+    }
+
+    private fun handle(v: Int): Int = v + 1
+}
+
+object Registry {
+    fun lookup(name: String) = name
+}
+
+fun topLevel(): Int {
+    return 1
+}
+
+fun String.shout() = uppercase()
+"#;
+        let syms = SpecKotlinAnalyzer::new().symbols_in_file("src/Service.kt", src);
+        let find = |name: &str| {
+            syms.iter()
+                .find(|s| s.name == name)
+                .unwrap_or_else(|| panic!("missing {name}: {syms:?}"))
+        };
+
+        assert!(matches!(find("Point").kind, SymbolKind::Struct));
+        assert_eq!(find("Point").range.end_line, 3);
+        assert!(matches!(find("Mode").kind, SymbolKind::Enum));
+        assert!(matches!(find("Runner").kind, SymbolKind::Trait));
+        assert!(matches!(find("Registry").kind, SymbolKind::Struct));
+        let service = find("Service");
+        assert_eq!((service.range.start_line, service.range.end_line), (11, 17));
+        assert!(matches!(find("handle").kind, SymbolKind::Method));
+        assert_eq!(find("handle").range.end_line, 16);
+        assert!(matches!(find("lookup").kind, SymbolKind::Method));
+        assert!(matches!(find("topLevel").kind, SymbolKind::Function));
+        assert!(matches!(find("shout").kind, SymbolKind::Function));
+        assert_eq!(
+            find("topLevel").id.0,
+            "kotlin:src/Service.kt:fn:topLevel:23"
+        );
+    }
+
+    #[test]
+    fn extract_kotlin_refs_with_receiver_types_and_implicit_this() {
+        let src = r#"package demo
+
+import demo.util.format
+import demo.store.UserRepo
+
+class Service(private val repo: UserRepo) {
+    fun run(id: Int): String {
+        val cache = Cache()
+        cache?.warm()
+        handle(id)
+        Registry.lookup("x")
+        return format(repo.find(id)) // helper()
+    }
+
+    fun handle(id: Int) = check(id)
+}
+"#;
+        let refs = SpecKotlinAnalyzer::new().unresolved_refs("demo/Service.kt", src);
+        let has = |name: &str, qual: Option<&str>, is_method: bool, line: u32| {
+            refs.iter().any(|r| {
+                r.name == name
+                    && r.qualifier.as_deref() == qual
+                    && r.is_method == is_method
+                    && r.line == line
+            })
+        };
+
+        assert!(has("find", Some("UserRepo"), true, 12), "{refs:?}");
+        assert!(has("warm", Some("Cache"), true, 9), "{refs:?}");
+        assert!(has("handle", Some("this"), true, 10), "{refs:?}");
+        assert!(has("lookup", Some("Registry"), false, 11), "{refs:?}");
+        assert!(has("format", None, false, 12), "{refs:?}");
+        assert!(has("check", None, false, 15), "{refs:?}");
+        assert!(!refs.iter().any(|r| r.name == "helper"));
+        assert!(!refs.iter().any(|r| r.name == "handle" && r.line == 15));
+    }
+
+    #[test]
+    fn extract_kotlin_imports_aliases_globs_and_package() {
+        let src = r#"package com.acme.app
+
+import com.acme.util.format
+import com.acme.store.UserRepo as Repo
+import kotlinx.coroutines.*
+"#;
+        let imports = SpecKotlinAnalyzer::new().imports_in_file("App.kt", src);
+
+        assert_eq!(
+            imports.get("__package__").map(String::as_str),
+            Some("com::acme::app")
+        );
+        assert_eq!(
+            imports.get("format").map(String::as_str),
+            Some("com::acme::util::format")
+        );
+        assert_eq!(
+            imports.get("Repo").map(String::as_str),
+            Some("com::acme::store::UserRepo")
+        );
+        assert_eq!(
+            imports
+                .get("__glob__kotlinx::coroutines")
+                .map(String::as_str),
+            Some("kotlinx::coroutines")
+        );
+    }
+}
@@ -1,5 +1,5 @@
-//! Common path normalization utilities for language spec modules.
-use std::path::{Component, Path};
+//! Common path normalization utilities for language spec modules and file keys.
+use std::path::{Component, Path, PathBuf};
 
 /// Normalize a path-like value by collapsing '.' and '..' without touching the filesystem.
 /// Converts backslashes to forward slashes.
@@ -17,6 +17,55 @@ pub fn normalize_path_like(p: &Path) -> String {
     out.to_string_lossy().replace('\\', "/")
 }
 
+/// Normalize a file path to the workspace-relative form used in symbol ids and
+/// per-file groupings: no leading `./`, `.` and `..` collapsed, and absolute
+/// paths under `root` made relative to it. Absolute paths outside `root` stay
+/// absolute; a path equal to `root` becomes `.`. Separators are joined with `/`.
+pub fn normalize_path(path: &str, root: &Path) -> String {
+    let p = Path::new(path);
+    let rel = if p.is_absolute() {
+        p.strip_prefix(root)
+            .ok()
+            .map(Path::to_path_buf)
+            .or_else(|| {
+                let root = root.canonicalize().ok()?;
+                let abs = p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+                abs.strip_prefix(&root).ok().map(Path::to_path_buf)
+            })
+    } else {
+        None
+    };
+    let mut out: Vec<String> = Vec::new();
+    for comp in rel.as_deref().unwrap_or(p).components() {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if out.last().is_some_and(|c| c != ".." && c != "/") {
+                    out.pop();
+                } else if out.first().is_none_or(|c| c != "/") {
+                    out.push("..".to_string());
+                }
+            }
+            Component::RootDir => out.push("/".to_string()),
+            other => out.push(other.as_os_str().to_string_lossy().into_owned()),
+        }
+    }
+    match out.split_first() {
+        None => ".".to_string(),
+        Some((first, rest)) if first == "/" => format!("/{}", rest.join("/")),
+        Some(_) => out.join("/"),
+    }
+}
+
+/// Where the workspace-relative `file` is read from: under `root`, or
+/// relative to the current directory when no root is set.
+pub fn workspace_file(root: Option<&Path>, file: &str) -> PathBuf {
+    match root {
+        Some(root) => root.join(file),
+        None => PathBuf::from(file),
+    }
+}
+
 /// Resolve a module specifier to a normalized path-like string.
 /// Trims supported extensions, replaces backslashes, collapses '.' and '..'.
 pub fn resolve_module_path(cur_file: &str, raw: &str, exts: &[&str]) -> Option<String> {
@@ -38,3 +87,25 @@ pub fn resolve_module_path(cur_file: &str, raw: &str, exts: &[&str]) -> Option<S
         Some(s.trim_start_matches('/').to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_path_gives_one_key_per_file() {
+        let root = Path::new("/work/repo");
+        for p in [
+            "src/a.rs",
+            "./src/a.rs",
+            "src/./a.rs",
+            "src/b/../a.rs",
+            "/work/repo/src/a.rs",
+        ] {
+            assert_eq!(normalize_path(p, root), "src/a.rs", "{p}");
+        }
+        assert_eq!(normalize_path("/work/repo", root), ".");
+        assert_eq!(normalize_path("/elsewhere/./x.rs", root), "/elsewhere/x.rs");
+        assert_eq!(normalize_path("../sibling/x.rs", root), "../sibling/x.rs");
+    }
+}
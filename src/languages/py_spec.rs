@@ -2,17 +2,16 @@ use crate::ir::reference::{RefKind, UnresolvedRef};
 use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
 use crate::languages::LanguageAnalyzer;
 use crate::languages::util::{byte_to_line, line_offsets};
-use crate::ts_core::{QueryRunner, compile_queries_python, load_python_spec};
+use crate::ts_core::{QueryRunner, shared_queries_python};
 
 pub struct SpecPyAnalyzer {
-    queries: crate::ts_core::CompiledQueries,
+    queries: &'static crate::ts_core::CompiledQueries,
     runner: QueryRunner,
 }
 
 impl SpecPyAnalyzer {
     pub fn new() -> Self {
-        let spec = load_python_spec();
-        let queries = compile_queries_python(&spec).expect("compile python queries");
+        let queries = shared_queries_python();
         let runner = QueryRunner::new_python();
         Self { queries, runner }
     }
@@ -843,14 +842,12 @@ impl LanguageAnalyzer for SpecPyAnalyzer {
             // 3b) Protocol-typed function parameters:
             // within `def f(arg: ProtocolType, ...):`, map `arg.method(...)` calls
             // to protocol method refs in that function body.
-            let re_func_sig = Regex::new(
-                r"^\s*def\s+[A-Za-z_][A-Za-z0-9_]*\s*\(([^\)]*)\)\s*(?:->[^:]+)?\s*:",
-            )
-            .expect("function signature regex");
-            let re_param_annot = Regex::new(
-                r"^\s*([A-Za-z_][A-Za-z0-9_]*)\s*:\s*([A-Za-z_][A-Za-z0-9_\.]*)",
-            )
-            .expect("parameter annotation regex");
+            let re_func_sig =
+                Regex::new(r"^\s*def\s+[A-Za-z_][A-Za-z0-9_]*\s*\(([^\)]*)\)\s*(?:->[^:]+)?\s*:")
+                    .expect("function signature regex");
+            let re_param_annot =
+                Regex::new(r"^\s*([A-Za-z_][A-Za-z0-9_]*)\s*:\s*([A-Za-z_][A-Za-z0-9_\.]*)")
+                    .expect("parameter annotation regex");
             for (i, line) in lines.iter().enumerate() {
                 let Some(cap) = re_func_sig.captures(line) else {
                     continue;
@@ -2146,9 +2143,8 @@ class Service:
             "refs={refs_dbg:?}"
         );
         assert!(
-            refs.iter().any(|r| {
-                r.name == "patched_handle" && r.qualifier.is_none() && !r.is_method
-            }),
+            refs.iter()
+                .any(|r| { r.name == "patched_handle" && r.qualifier.is_none() && !r.is_method }),
             "refs={refs_dbg:?}"
         );
         assert!(
@@ -2375,9 +2371,7 @@ class Service:
         );
         assert!(
             refs.iter().any(|r| {
-                r.name == "_make"
-                    && r.qualifier.as_deref() == Some("ServiceFactory")
-                    && r.is_method
+                r.name == "_make" && r.qualifier.as_deref() == Some("ServiceFactory") && r.is_method
             }),
             "refs={refs_dbg:?}"
         );
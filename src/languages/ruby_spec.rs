@@ -3,18 +3,17 @@ use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
 use crate::languages::LanguageAnalyzer;
 use crate::languages::path::normalize_path_like;
 use crate::languages::util::{byte_to_line, line_offsets};
-use crate::ts_core::{QueryRunner, compile_queries_ruby, load_ruby_spec};
+use crate::ts_core::{QueryRunner, shared_queries_ruby};
 use regex::Regex;
 
 pub struct SpecRubyAnalyzer {
-    queries: crate::ts_core::CompiledQueries,
+    queries: &'static crate::ts_core::CompiledQueries,
     runner: QueryRunner,
 }
 
 impl SpecRubyAnalyzer {
     pub fn new() -> Self {
-        let spec = load_ruby_spec();
-        let queries = compile_queries_ruby(&spec).expect("compile ruby queries");
+        let queries = shared_queries_ruby();
         let runner = QueryRunner::new_ruby();
         Self { queries, runner }
     }
@@ -668,11 +667,68 @@ impl LanguageAnalyzer for SpecRubyAnalyzer {
             .map(|s| (s.name.clone(), s.range.start_line))
             .collect();
 
+        // attr_* inside a class/module body generate reader and/or writer methods
+        let container_ranges: Vec<(u32, u32)> = out
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Struct | SymbolKind::Module))
+            .map(|s| (s.range.start_line, s.range.end_line))
+            .collect();
+
         for caps in self.runner.run_captures(source, &self.queries.calls) {
             let Some(name_cap) = caps.iter().find(|c| c.name == "name") else {
                 continue;
             };
             let call_name = source[name_cap.start..name_cap.end].trim();
+            let attr_kind = match call_name {
+                "attr_accessor" => Some((true, true)),
+                "attr_reader" => Some((true, false)),
+                "attr_writer" => Some((false, true)),
+                _ => None,
+            };
+            if let Some((reader, writer)) = attr_kind {
+                let Some(callnode) = caps.iter().find(|c| c.name == "call") else {
+                    continue;
+                };
+                let ln = byte_to_line(&offs, callnode.start);
+                if !container_ranges
+                    .iter()
+                    .any(|(sl, el)| *sl < ln && ln <= *el)
+                {
+                    continue;
+                }
+                let text = &source[callnode.start..callnode.end];
+                for raw_arg in extract_call_args(text, call_name, usize::MAX) {
+                    let arg = strip_ruby_inline_comment(raw_arg.as_str());
+                    let Some(attr) = re_symbol_lit
+                        .captures(&arg)
+                        .or_else(|| re_string_lit.captures(&arg))
+                        .and_then(|c| c.get(1).map(|m| m.as_str().to_string()))
+                    else {
+                        continue;
+                    };
+                    let names = [
+                        reader.then(|| attr.clone()),
+                        writer.then(|| format!("{attr}=")),
+                    ];
+                    for name in names.into_iter().flatten() {
+                        if !seen_dynamic_methods.insert((name.clone(), ln)) {
+                            continue;
+                        }
+                        out.push(Symbol {
+                            id: SymbolId::new("ruby", path, &SymbolKind::Method, &name, ln),
+                            name,
+                            kind: SymbolKind::Method,
+                            file: path.to_string(),
+                            range: TextRange {
+                                start_line: ln,
+                                end_line: ln,
+                            },
+                            language: "ruby".to_string(),
+                        });
+                    }
+                }
+                continue;
+            }
             if call_name != "define_method" {
                 continue;
             }
@@ -749,8 +805,7 @@ impl LanguageAnalyzer for SpecRubyAnalyzer {
             Regex::new(r#"^[\"']([A-Za-z_][A-Za-z0-9_?!]*)[\"']\.to_sym$"#).unwrap();
         let re_symbol_to_s = Regex::new(r#"^:([A-Za-z_][A-Za-z0-9_?!]*)\.to_s$"#).unwrap();
         let re_interpolated_to_sym =
-            Regex::new(r#"^[\"']([A-Za-z_][A-Za-z0-9_?!]*)#\{[^}]+\}[\"']\.to_sym$"#)
-                .unwrap();
+            Regex::new(r#"^[\"']([A-Za-z_][A-Za-z0-9_?!]*)#\{[^}]+\}[\"']\.to_sym$"#).unwrap();
         let re_var_cast =
             Regex::new(r#"^([@]{0,2}[A-Za-z_][A-Za-z0-9_]*|[A-Z][A-Za-z0-9_]*)\.(?:to_sym|to_s)$"#)
                 .unwrap();
@@ -1267,6 +1322,34 @@ end
         assert!(names.iter().filter(|&&n| n == "m").count() >= 3);
     }
 
+    #[test]
+    fn attr_macros_synthesize_reader_and_writer_methods() {
+        let src = r#"class Person
+  attr_accessor :age
+  attr_reader :name, "nick" # trailing comment
+  attr_writer(:secret)
+end
+attr_reader :top_level
+"#;
+        let ana = SpecRubyAnalyzer::new();
+        let syms = ana.symbols_in_file("person.rb", src);
+        let methods: Vec<(&str, u32)> = syms
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Method))
+            .map(|s| (s.name.as_str(), s.range.start_line))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                ("age", 2),
+                ("age=", 2),
+                ("name", 3),
+                ("nick", 3),
+                ("secret=", 4)
+            ]
+        );
+    }
+
     #[test]
     fn ruby_dynamic_fixture_send_public_send_symbol_string() {
         let src = include_str!(concat!(
@@ -1618,12 +1701,14 @@ end
         let ana = SpecRubyAnalyzer::new();
 
         let syms = ana.symbols_in_file("pkg/ruby_dynamic_dsl_chain_v4.rb", src);
-        assert!(syms.iter().any(|s| {
-            s.name == "emit_created" && matches!(s.kind, SymbolKind::Method)
-        }));
-        assert!(syms.iter().any(|s| {
-            s.name == "emit_cancelled" && matches!(s.kind, SymbolKind::Method)
-        }));
+        assert!(
+            syms.iter()
+                .any(|s| { s.name == "emit_created" && matches!(s.kind, SymbolKind::Method) })
+        );
+        assert!(
+            syms.iter()
+                .any(|s| { s.name == "emit_cancelled" && matches!(s.kind, SymbolKind::Method) })
+        );
 
         let refs = ana.unresolved_refs("pkg/ruby_dynamic_dsl_chain_v4.rb", src);
         let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
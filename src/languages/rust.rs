@@ -1,5 +1,7 @@
 use crate::ir::reference::{RefKind, UnresolvedRef};
 use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
+use crate::languages::LanguageKind;
+use crate::languages::util::mask_comments_and_strings;
 use regex::Regex;
 
 pub struct RustAnalyzer;
@@ -161,8 +163,9 @@ impl crate::languages::LanguageAnalyzer for RustAnalyzer {
         let re_call = Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*)\s*(!)?\s*\(").unwrap();
         // method: .name(
         let re_method = Regex::new(r"\.\s*([A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap();
+        let masked = mask_comments_and_strings(source, LanguageKind::Rust);
         let mut refs = Vec::new();
-        for (i, line) in source.lines().enumerate() {
+        for (i, line) in masked.lines().enumerate() {
             let ln = (i as u32) + 1;
             // qualified calls first to capture a::b::c(...)
             for cap in re_qcall.captures_iter(line) {
@@ -300,16 +303,18 @@ impl crate::languages::LanguageAnalyzer for RustAnalyzer {
             if !line.ends_with(';') {
                 continue;
             }
+            let reexport = line.starts_with("pub use ");
             if let Some(stripped) = line.strip_prefix("pub use ") {
                 line = stripped;
             } else if let Some(stripped) = line.strip_prefix("use ") {
                 line = stripped;
             }
             line = &line[..line.len() - 1];
+            let mut names = std::collections::HashMap::new();
             if let Some(brace_pos) = line.find('{') {
                 let prefix = line[..brace_pos].trim_end_matches("::").trim();
                 let rest = &line[brace_pos + 1..line.rfind('}').unwrap_or(line.len())];
-                flatten(rest, prefix, &mut map);
+                flatten(rest, prefix, &mut names);
             } else {
                 let (path_spec, alias) = if let Some((p, a)) = line.split_once(" as ") {
                     (p.trim(), a.trim())
@@ -318,11 +323,22 @@ impl crate::languages::LanguageAnalyzer for RustAnalyzer {
                 };
                 if path_spec.ends_with("::*") {
                     let pfx = path_spec.trim_end_matches("::*");
-                    map.insert(format!("__glob__{}", pfx), pfx.to_string());
+                    names.insert(format!("__glob__{}", pfx), pfx.to_string());
                 } else {
-                    map.insert(alias.to_string(), normalize(path_spec));
+                    names.insert(alias.to_string(), normalize(path_spec));
                 }
             }
+            if reexport {
+                // `pub use` also re-exports the names, like a TS barrel file
+                for (alias, full) in &names {
+                    let key = match alias.strip_prefix("__glob__") {
+                        Some(pfx) => format!("__export_glob__{}", pfx),
+                        None => format!("__export__{}", alias),
+                    };
+                    map.insert(key, full.clone());
+                }
+            }
+            map.extend(names);
         }
         // mod declarations map: mod foo; -> current_mod::foo
         let current_mod = super::super::impact::module_path_for_file(path);
@@ -383,6 +399,33 @@ enum E { A, B }
         assert!(!names.contains(&"println"));
     }
 
+    #[test]
+    fn refs_ignore_comments_and_string_literals() {
+        let src = r##"fn foo() {
+    // old_call();
+    /* block(); /* nested(); */ still(); */
+    let s = "fake(1) \" quoted()";
+    let r = r"raw()";
+    let b = br#"bytes()"#;
+    let c = '(';
+    real();
+}
+fn bar<'a>(x: &'a str) { after(); }
+"##;
+        let ana = RustAnalyzer::new();
+        let refs = ana.unresolved_refs("lib.rs", src);
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+        for phantom in [
+            "old_call", "block", "nested", "still", "fake", "quoted", "raw",
+        ] {
+            assert!(!names.contains(&phantom), "{phantom} in {names:?}");
+        }
+        let real = refs.iter().find(|r| r.name == "real").unwrap();
+        assert_eq!(real.line, 8);
+        let after = refs.iter().find(|r| r.name == "after").unwrap();
+        assert_eq!(after.line, 10);
+    }
+
     #[test]
     fn extract_qualified_refs() {
         let src = r#"fn foo() { crate::utils::call(); a::b::c(); }"#;
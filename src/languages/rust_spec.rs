@@ -2,20 +2,31 @@ use crate::ir::Symbol;
 use crate::ir::reference::{RefKind, UnresolvedRef};
 use crate::languages::rust_ts::RustTsAnalyzer;
 use crate::languages::util::{byte_to_line, line_offsets};
-use crate::languages::{LanguageAnalyzer, rust::RustAnalyzer};
-use crate::ts_core::{QueryRunner, compile_queries_rust, load_rust_spec};
+use crate::languages::{AnalyzerConfig, LanguageAnalyzer, rust::RustAnalyzer};
+use crate::ts_core::{QueryRunner, shared_queries_rust};
 
 pub struct SpecRustAnalyzer {
-    queries: crate::ts_core::CompiledQueries,
+    queries: &'static crate::ts_core::CompiledQueries,
     runner: QueryRunner,
+    include_macros: bool,
+    include_constructions: bool,
 }
 
 impl SpecRustAnalyzer {
     pub fn new() -> Self {
-        let spec = load_rust_spec();
-        let queries = compile_queries_rust(&spec).expect("compile rust queries");
+        Self::with_config(&AnalyzerConfig::default())
+    }
+
+    /// An analyzer extracting the optional relationships `cfg` turns on.
+    pub fn with_config(cfg: &AnalyzerConfig) -> Self {
+        let queries = shared_queries_rust();
         let runner = QueryRunner::new_rust();
-        Self { queries, runner }
+        Self {
+            queries,
+            runner,
+            include_macros: cfg.include_macros,
+            include_constructions: cfg.include_constructions,
+        }
     }
 }
 
@@ -32,7 +43,12 @@ impl LanguageAnalyzer for SpecRustAnalyzer {
 
     fn symbols_in_file(&self, path: &str, source: &str) -> Vec<Symbol> {
         // For reliability (methods inside impl), reuse the existing TS analyzer's symbol extraction
-        RustTsAnalyzer::new().symbols_in_file(path, source)
+        let ts = RustTsAnalyzer::new();
+        let mut out = ts.symbols_in_file(path, source);
+        if self.include_macros {
+            out.extend(ts.macro_symbols(path, source));
+        }
+        out
     }
 
     fn unresolved_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
@@ -80,6 +96,14 @@ impl LanguageAnalyzer for SpecRustAnalyzer {
                 }
             }
         }
+        let ts = RustTsAnalyzer::new();
+        out.extend(ts.type_refs(path, source));
+        if self.include_macros {
+            out.extend(ts.macro_refs(path, source));
+        }
+        if self.include_constructions {
+            out.extend(ts.construction_refs(path, source));
+        }
         out
     }
 
@@ -117,4 +141,86 @@ mod tests {
         assert!(refs.iter().any(|r| r.name == "handle" && r.is_method));
         assert!(refs.iter().any(|r| r.name == "clone" && r.is_method));
     }
+
+    #[test]
+    fn impl_functions_are_qualified_by_their_type() {
+        let src = "struct Foo;\nimpl Foo {\n    fn new() -> Self { Foo }\n}\nstruct Bar<T>(T);\nimpl<T> Default for Bar<T> {\n    fn new() -> Self { todo!() }\n}\nfn new() {}\n";
+        let syms = SpecRustAnalyzer::new().symbols_in_file("f.rs", src);
+        let mut names: Vec<String> = syms
+            .iter()
+            .filter(|s| s.name == "new")
+            .map(|s| s.id.parts().unwrap().name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["Bar::new", "Foo::new", "new"]);
+    }
+
+    #[test]
+    fn macros_are_extracted_only_when_configured() {
+        let src =
+            "macro_rules! square { ($x:expr) => { $x * $x }; }\nfn f() -> i32 { square!(2) }\n";
+        let macros = |ana: &SpecRustAnalyzer| {
+            let syms = ana.symbols_in_file("f.rs", src);
+            let refs = ana.unresolved_refs("f.rs", src);
+            (
+                syms.iter().filter(|s| s.kind == SymbolKind::Macro).count(),
+                refs.iter().filter(|r| r.name == "square").count(),
+            )
+        };
+        assert_eq!(macros(&SpecRustAnalyzer::new()), (0, 0));
+        let cfg = AnalyzerConfig {
+            include_macros: true,
+            ..AnalyzerConfig::default()
+        };
+        assert_eq!(macros(&SpecRustAnalyzer::with_config(&cfg)), (1, 1));
+    }
+
+    #[test]
+    fn constructions_are_extracted_only_when_configured() {
+        let src = "struct P { x: i32 }\nfn f() -> P { P { x: 1 } }\n";
+        let uses_at_body = |ana: &SpecRustAnalyzer| {
+            ana.unresolved_refs("f.rs", src)
+                .iter()
+                .filter(|r| r.kind == RefKind::TypeUse && r.name == "P")
+                .count()
+        };
+        let plain = uses_at_body(&SpecRustAnalyzer::new());
+        let cfg = AnalyzerConfig {
+            include_constructions: true,
+            ..AnalyzerConfig::default()
+        };
+        assert_eq!(
+            uses_at_body(&SpecRustAnalyzer::with_config(&cfg)),
+            plain + 1
+        );
+    }
+
+    #[test]
+    fn type_uses_and_trait_impls_become_typed_refs() {
+        let src = "trait Shape { fn area(&self) -> f64; }\nstruct Point { x: Coord, tag: crate::m::Tag }\nimpl Shape for Point {\n    fn area(&self) -> f64 { 0.0 }\n}\nfn make(c: Vec<Coord>) -> Point { todo!() }\n";
+        let refs = SpecRustAnalyzer::new().unresolved_refs("f.rs", src);
+        let typed: Vec<(RefKind, &str, Option<&str>, u32)> = refs
+            .iter()
+            .filter(|r| r.kind != RefKind::Call)
+            .map(|r| {
+                (
+                    r.kind.clone(),
+                    r.name.as_str(),
+                    r.qualifier.as_deref(),
+                    r.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            typed,
+            vec![
+                (RefKind::TypeUse, "Coord", None, 2),
+                (RefKind::TypeUse, "Tag", Some("crate::m"), 2),
+                (RefKind::Inherit, "Shape", None, 4),
+                (RefKind::TypeUse, "Vec", None, 6),
+                (RefKind::TypeUse, "Coord", None, 6),
+                (RefKind::TypeUse, "Point", None, 6),
+            ]
+        );
+    }
 }
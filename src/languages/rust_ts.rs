@@ -24,6 +24,336 @@ impl RustTsAnalyzer {
     }
 }
 
+impl RustTsAnalyzer {
+    /// Type-level references: `TypeUse` for types named in struct fields and
+    /// function signatures, and `Inherit` from each method of an
+    /// `impl Trait for Type` block to the trait (resolution also links the
+    /// method to the trait method it implements).
+    pub fn type_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
+        let tree = self.parser.borrow_mut().parse(source, None).unwrap();
+        let offs = line_offsets(source);
+        let text = |n: tree_sitter::Node| n.utf8_text(source.as_bytes()).unwrap_or("");
+        let mut out = Vec::new();
+        // (start byte, ref) so the result can be returned in source order
+        let mut push = |name: &str, qualifier: Option<String>, kind: RefKind, at: usize| {
+            if name.is_empty() || name == "Self" {
+                return;
+            }
+            let r = UnresolvedRef {
+                name: name.to_string(),
+                kind,
+                file: path.to_string(),
+                line: byte_to_line(&offs, at),
+                qualifier,
+                is_method: false,
+            };
+            out.push((at, r));
+        };
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let type_roots: Vec<tree_sitter::Node> = match node.kind() {
+                "field_declaration" => node.child_by_field_name("type").into_iter().collect(),
+                "function_item" => {
+                    let mut roots: Vec<_> = node
+                        .child_by_field_name("return_type")
+                        .into_iter()
+                        .collect();
+                    if let Some(params) = node.child_by_field_name("parameters") {
+                        for i in 0..params.named_child_count() {
+                            let p = params.named_child(i as u32).unwrap();
+                            roots.extend(p.child_by_field_name("type"));
+                        }
+                    }
+                    roots
+                }
+                _ => Vec::new(),
+            };
+            for root in type_roots {
+                let mut types = vec![root];
+                while let Some(t) = types.pop() {
+                    let at = t.start_byte();
+                    match t.kind() {
+                        "type_identifier" => push(text(t), None, RefKind::TypeUse, at),
+                        "scoped_type_identifier" => {
+                            let name = t.child_by_field_name("name").map(text).unwrap_or("");
+                            let qualifier =
+                                t.child_by_field_name("path").map(|p| text(p).to_string());
+                            push(name, qualifier, RefKind::TypeUse, at);
+                        }
+                        _ => {
+                            for i in 0..t.named_child_count() {
+                                types.push(t.named_child(i as u32).unwrap());
+                            }
+                        }
+                    }
+                }
+            }
+            if node.kind() == "impl_item"
+                && let Some(tr) = node.child_by_field_name("trait")
+                && let Some(body) = node.child_by_field_name("body")
+            {
+                let full = text(tr);
+                let full = full.split('<').next().unwrap_or(full);
+                let (qualifier, name) = match full.rsplit_once("::") {
+                    Some((q, n)) => (Some(q.to_string()), n),
+                    None => (None, full),
+                };
+                for i in 0..body.named_child_count() {
+                    let item = body.named_child(i as u32).unwrap();
+                    if item.kind() == "function_item" {
+                        push(name, qualifier.clone(), RefKind::Inherit, item.start_byte());
+                    }
+                }
+            }
+            for i in 0..node.named_child_count() {
+                stack.push(node.named_child(i as u32).unwrap());
+            }
+        }
+        out.sort_by_key(|(at, _)| *at);
+        out.into_iter().map(|(_, r)| r).collect()
+    }
+}
+
+impl RustTsAnalyzer {
+    /// Macro definitions: one `Macro` symbol per `macro_rules!` and per
+    /// `#[proc_macro_derive(Name)]` function (named `Name`, spanning the function).
+    pub fn macro_symbols(&self, path: &str, source: &str) -> Vec<Symbol> {
+        let tree = self.parser.borrow_mut().parse(source, None).unwrap();
+        let offs = line_offsets(source);
+        let text = |n: tree_sitter::Node| n.utf8_text(source.as_bytes()).unwrap_or("");
+        let mut out = Vec::new();
+        let mut push = |name: &str, node: tree_sitter::Node| {
+            let sl = byte_to_line(&offs, node.start_byte());
+            let el = byte_to_line(&offs, node.end_byte().saturating_sub(1));
+            out.push(Symbol {
+                id: SymbolId::new("rust", path, &SymbolKind::Macro, name, sl),
+                name: name.to_string(),
+                kind: SymbolKind::Macro,
+                file: path.to_string(),
+                range: TextRange {
+                    start_line: sl,
+                    end_line: el.max(sl),
+                },
+                language: "rust".to_string(),
+            });
+        };
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "macro_definition"
+                && let Some(name) = node.child_by_field_name("name")
+            {
+                push(text(name), node);
+            }
+            for (attrs, item) in attributed_items(node) {
+                if item.kind() != "function_item" {
+                    continue;
+                }
+                for (attr_name, args) in attrs.iter().filter_map(|a| attribute_call(*a, source)) {
+                    if attr_name == "proc_macro_derive"
+                        && let Some(derive) = args.first()
+                    {
+                        push(derive.rsplit("::").next().unwrap_or(derive), item);
+                    }
+                }
+            }
+            for i in 0..node.named_child_count() {
+                stack.push(node.named_child(i as u32).unwrap());
+            }
+        }
+        out.sort_by_key(|s| s.range.start_line);
+        out
+    }
+
+    /// Macro references: a `Call` for each `name!(..)` invocation and a
+    /// `TypeUse` for each entry of `#[derive(..)]`, reported on the line the
+    /// derived item starts so it resolves from that item.
+    pub fn macro_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
+        let tree = self.parser.borrow_mut().parse(source, None).unwrap();
+        let offs = line_offsets(source);
+        let text = |n: tree_sitter::Node| n.utf8_text(source.as_bytes()).unwrap_or("");
+        let mut out = Vec::new();
+        let mut push = |full: &str, kind: RefKind, at: usize| {
+            let (qualifier, name) = match full.rsplit_once("::") {
+                Some((q, n)) => (Some(q.to_string()), n),
+                None => (None, full),
+            };
+            if name.is_empty() {
+                return;
+            }
+            out.push((
+                at,
+                UnresolvedRef {
+                    name: name.to_string(),
+                    kind,
+                    file: path.to_string(),
+                    line: byte_to_line(&offs, at),
+                    qualifier,
+                    is_method: false,
+                },
+            ));
+        };
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "macro_invocation"
+                && let Some(m) = node.child_by_field_name("macro")
+            {
+                push(text(m), RefKind::Call, node.start_byte());
+            }
+            for (attrs, item) in attributed_items(node) {
+                for (attr_name, args) in attrs.iter().filter_map(|a| attribute_call(*a, source)) {
+                    if attr_name == "derive" {
+                        for derive in &args {
+                            push(derive, RefKind::TypeUse, item.start_byte());
+                        }
+                    }
+                }
+            }
+            for i in 0..node.named_child_count() {
+                stack.push(node.named_child(i as u32).unwrap());
+            }
+        }
+        out.sort_by_key(|(at, _)| *at);
+        out.into_iter().map(|(_, r)| r).collect()
+    }
+
+    /// Data-model uses inside function bodies, as `TypeUse` references to the
+    /// struct or enum involved: struct literals (`Point { .. }`), struct and
+    /// tuple-struct patterns, and enum variants (`Shape::Circle(..)`,
+    /// `Color::Red =>`). A field or variant change then reaches every
+    /// construction site and `match` on the type.
+    pub fn construction_refs(&self, path: &str, source: &str) -> Vec<UnresolvedRef> {
+        let tree = self.parser.borrow_mut().parse(source, None).unwrap();
+        let offs = line_offsets(source);
+        let text = |n: tree_sitter::Node| n.utf8_text(source.as_bytes()).unwrap_or("");
+        let mut out = Vec::new();
+        // (node, inside a function body)
+        let mut stack = vec![(tree.root_node(), false)];
+        while let Some((node, in_fn)) = stack.pop() {
+            let named_type = match node.kind() {
+                "struct_expression" => node.child_by_field_name("name"),
+                "struct_pattern" | "tuple_struct_pattern" => node.child_by_field_name("type"),
+                "scoped_identifier" => Some(node),
+                _ => None,
+            };
+            if in_fn && let Some(t) = named_type {
+                let full = text(t);
+                let full = full
+                    .split('<')
+                    .next()
+                    .unwrap_or(full)
+                    .trim_end_matches("::");
+                let (qualifier, name) = match full.rsplit_once("::") {
+                    Some((q, n)) => (Some(q), n),
+                    None => (None, full),
+                };
+                let capitalized = |s: &str| s.starts_with(|c: char| c.is_ascii_uppercase());
+                // `Enum::Variant` names the enum; `module::Type` the type itself
+                let target = match qualifier {
+                    Some(q) => {
+                        let (outer, last) = match q.rsplit_once("::") {
+                            Some((o, l)) => (Some(o), l),
+                            None => (None, q),
+                        };
+                        if capitalized(last) && capitalized(name) {
+                            Some((outer, last))
+                        } else if node.kind() != "scoped_identifier" {
+                            Some((qualifier, name))
+                        } else {
+                            None
+                        }
+                    }
+                    None if node.kind() != "scoped_identifier" => Some((None, name)),
+                    None => None,
+                };
+                if let Some((qualifier, name)) = target
+                    && !name.is_empty()
+                    && name != "Self"
+                {
+                    out.push((
+                        node.start_byte(),
+                        UnresolvedRef {
+                            name: name.to_string(),
+                            kind: RefKind::TypeUse,
+                            file: path.to_string(),
+                            line: byte_to_line(&offs, node.start_byte()),
+                            qualifier: qualifier.map(str::to_string),
+                            is_method: false,
+                        },
+                    ));
+                }
+            }
+            let in_fn = in_fn || node.kind() == "function_item";
+            for i in 0..node.named_child_count() {
+                let child = node.named_child(i as u32).unwrap();
+                // The type path was handled above (a variant path must not count twice)
+                if in_fn && named_type == Some(child) {
+                    continue;
+                }
+                stack.push((child, in_fn));
+            }
+        }
+        out.sort_by_key(|(at, _)| *at);
+        out.into_iter().map(|(_, r)| r).collect()
+    }
+}
+
+/// Items among `node`'s children paired with the outer attributes right above them.
+fn attributed_items(node: tree_sitter::Node) -> Vec<(Vec<tree_sitter::Node>, tree_sitter::Node)> {
+    let mut out = Vec::new();
+    let mut attrs = Vec::new();
+    for i in 0..node.named_child_count() {
+        let ch = node.named_child(i as u32).unwrap();
+        match ch.kind() {
+            "attribute_item" => attrs.push(ch),
+            "line_comment" | "block_comment" => {}
+            _ => {
+                if !attrs.is_empty() {
+                    out.push((std::mem::take(&mut attrs), ch));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// `("derive", ["Debug", "serde::Serialize"])` for `#[derive(Debug, serde::Serialize)]`;
+/// nested groups such as `attributes(..)` are skipped.
+fn attribute_call(attr_item: tree_sitter::Node, source: &str) -> Option<(String, Vec<String>)> {
+    let text = attr_item.utf8_text(source.as_bytes()).ok()?;
+    let inner = text.strip_prefix("#[")?.strip_suffix(']')?.trim();
+    let (name, rest) = inner.split_once('(')?;
+    let args = rest.strip_suffix(')')?;
+    let mut out = Vec::new();
+    let mut depth = 0usize;
+    let mut cur = String::new();
+    for c in args.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => out.push(std::mem::take(&mut cur)),
+            _ if depth == 0 => cur.push(c),
+            _ => {}
+        }
+    }
+    out.push(cur);
+    let out = out
+        .into_iter()
+        .map(|a| a.split_whitespace().collect::<String>())
+        .filter(|a| {
+            !a.is_empty()
+                && a.chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == ':')
+        })
+        .collect();
+    Some((name.trim().to_string(), out))
+}
+
+/// `Foo` for `Foo<T>` / `crate::a::Foo<T>`: the qualifier used in symbol ids.
+fn type_base_name(ty: &str) -> String {
+    let base = ty.split('<').next().unwrap_or(ty).trim();
+    base.rsplit("::").next().unwrap_or(base).to_string()
+}
+
 impl crate::languages::LanguageAnalyzer for RustTsAnalyzer {
     fn language(&self) -> &'static str {
         "rust"
@@ -34,8 +364,9 @@ impl crate::languages::LanguageAnalyzer for RustTsAnalyzer {
         let root = tree.root_node();
         let offs = line_offsets(source);
         let mut out = Vec::new();
-        let mut stack = vec![root];
-        while let Some(node) = stack.pop() {
+        // (node, enclosing impl/trait type used to qualify function ids)
+        let mut stack: Vec<(tree_sitter::Node, Option<String>)> = vec![(root, None)];
+        while let Some((node, qualifier)) = stack.pop() {
             let kind = node.kind();
             let s = if kind == "function_item" {
                 let name = node
@@ -43,6 +374,13 @@ impl crate::languages::LanguageAnalyzer for RustTsAnalyzer {
                     .map(|n| n.utf8_text(source.as_bytes()).unwrap())
                     .unwrap_or("");
                 Some((name.to_string(), SymbolKind::Function))
+            } else if kind == "function_signature_item" && qualifier.is_some() {
+                // required trait method: the target implementations inherit from
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| n.utf8_text(source.as_bytes()).unwrap())
+                    .unwrap_or("");
+                Some((name.to_string(), SymbolKind::Function))
             } else if kind == "struct_item" {
                 let name = node
                     .child_by_field_name("name")
@@ -94,8 +432,13 @@ impl crate::languages::LanguageAnalyzer for RustTsAnalyzer {
             {
                 let sl = byte_to_line(&offs, node.start_byte());
                 let el = byte_to_line(&offs, node.end_byte().saturating_sub(1));
+                let q = if matches!(kind, SymbolKind::Function) {
+                    qualifier.as_deref()
+                } else {
+                    None
+                };
                 out.push(Symbol {
-                    id: SymbolId::new("rust", path, &kind, &name, sl),
+                    id: SymbolId::new_qualified("rust", path, &kind, q, &name, sl),
                     name,
                     kind,
                     file: path.to_string(),
@@ -107,8 +450,19 @@ impl crate::languages::LanguageAnalyzer for RustTsAnalyzer {
                 });
             }
 
+            let child_qualifier = match kind {
+                "impl_item" => node
+                    .child_by_field_name("type")
+                    .map(|t| type_base_name(t.utf8_text(source.as_bytes()).unwrap_or(""))),
+                "trait_item" => node
+                    .child_by_field_name("name")
+                    .map(|n| n.utf8_text(source.as_bytes()).unwrap_or("").to_string()),
+                // nested fns are not methods of the outer type
+                "function_item" => None,
+                _ => qualifier,
+            };
             for i in 0..node.child_count() {
-                stack.push(node.child(i as u32).unwrap());
+                stack.push((node.child(i as u32).unwrap(), child_qualifier.clone()));
             }
         }
         out
@@ -315,4 +669,109 @@ mod tests {
         assert!(names.contains(&"foo"));
         assert!(names.contains(&"S"));
     }
+
+    #[test]
+    fn macro_definitions_invocations_and_derives() {
+        let ana = RustTsAnalyzer::new();
+        let src = r#"macro_rules! square {
+    ($x:expr) => { $x * $x };
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    input
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Point {
+    x: i32,
+}
+
+fn area() -> i32 {
+    square!(3) + util::twice!(2)
+}
+"#;
+        let syms: Vec<_> = ana
+            .macro_symbols("lib.rs", src)
+            .into_iter()
+            .map(|s| (s.name, s.range.start_line, s.range.end_line))
+            .collect();
+        assert_eq!(
+            syms,
+            vec![("square".to_string(), 1, 3), ("Builder".to_string(), 6, 8)]
+        );
+
+        let refs: Vec<_> = ana
+            .macro_refs("lib.rs", src)
+            .into_iter()
+            .map(|r| (r.name, r.kind, r.line, r.qualifier))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                ("Debug".to_string(), RefKind::TypeUse, 11, None),
+                (
+                    "Serialize".to_string(),
+                    RefKind::TypeUse,
+                    11,
+                    Some("serde".to_string())
+                ),
+                ("square".to_string(), RefKind::Call, 16, None),
+                (
+                    "twice".to_string(),
+                    RefKind::Call,
+                    16,
+                    Some("util".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn construction_refs_name_the_struct_or_enum_in_function_bodies() {
+        let ana = RustTsAnalyzer::new();
+        let src = r#"struct Point {
+    x: i32,
+}
+
+enum Shape {
+    Circle(f64),
+    Unit,
+}
+
+fn make() -> Point {
+    Point { x: 1 }
+}
+
+fn area(s: Shape, p: models::Pair) -> f64 {
+    let Point { x } = make();
+    let _ = Shape::Circle(1.0);
+    match s {
+        Shape::Circle(r) => r,
+        Shape::Unit => x as f64,
+    }
+}
+
+fn pair() {
+    let _ = models::Pair { a: 1 };
+    let _ = util::helper();
+}
+"#;
+        let refs: Vec<_> = ana
+            .construction_refs("lib.rs", src)
+            .into_iter()
+            .map(|r| (r.name, r.line, r.qualifier))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                ("Point".to_string(), 11, None),
+                ("Point".to_string(), 15, None),
+                ("Shape".to_string(), 16, None),
+                ("Shape".to_string(), 18, None),
+                ("Shape".to_string(), 19, None),
+                ("Pair".to_string(), 24, Some("models".to_string())),
+            ]
+        );
+    }
 }
@@ -3,18 +3,17 @@ use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
 use crate::languages::LanguageAnalyzer;
 use crate::languages::path::resolve_module_path;
 use crate::languages::util::{byte_to_line, line_offsets};
-use crate::ts_core::{QueryRunner, compile_queries_typescript, load_typescript_spec};
+use crate::ts_core::{QueryRunner, shared_queries_typescript};
 
 pub struct SpecTsAnalyzer {
-    queries: crate::ts_core::CompiledQueries,
+    queries: &'static crate::ts_core::CompiledQueries,
     runner: QueryRunner,
     tsx: bool,
 }
 
 impl SpecTsAnalyzer {
     pub fn new_ts() -> Self {
-        let spec = load_typescript_spec();
-        let queries = compile_queries_typescript(&spec, false).expect("compile ts queries");
+        let queries = shared_queries_typescript(false);
         let runner = QueryRunner::new_typescript(false);
         Self {
             queries,
@@ -23,8 +22,7 @@ impl SpecTsAnalyzer {
         }
     }
     pub fn new_tsx() -> Self {
-        let spec = load_typescript_spec();
-        let queries = compile_queries_typescript(&spec, true).expect("compile tsx queries");
+        let queries = shared_queries_typescript(true);
         let runner = QueryRunner::new_typescript(true);
         Self {
             queries,
@@ -50,7 +48,7 @@ impl LanguageAnalyzer for SpecTsAnalyzer {
                 }
                 let decl_cap = caps.iter().find(|c| c.name == "decl");
                 let kind = match decl_cap.map(|d| d.kind.as_str()) {
-                    Some("class_declaration") => SymbolKind::Struct,
+                    Some("class_declaration") | Some("class") => SymbolKind::Struct,
                     Some("method_definition") | Some("method_signature") => SymbolKind::Method,
                     _ => SymbolKind::Function,
                 };
@@ -488,9 +486,58 @@ mod tests {
             refs.iter()
                 .any(|r| r.name == "parse" && r.qualifier.is_none() && !r.is_method)
         );
-        assert!(refs.iter().any(|r| {
-            r.name == "call" && r.qualifier.as_deref() == Some("fn") && r.is_method
-        }));
+        assert!(
+            refs.iter().any(|r| {
+                r.name == "call" && r.qualifier.as_deref() == Some("fn") && r.is_method
+            })
+        );
+    }
+
+    #[test]
+    fn named_default_exports_keep_their_identifier() {
+        let src = "export default function foo() {\n  return 1;\n}\n";
+        let ana = SpecTsAnalyzer::new_ts();
+        let syms = ana.symbols_in_file("demo/a.ts", src);
+        assert!(
+            syms.iter()
+                .any(|s| s.name == "foo" && matches!(s.kind, SymbolKind::Function))
+        );
+        assert!(syms.iter().all(|s| s.name != "default"));
+
+        let src = "export default class Bar {\n  run() {}\n}\n";
+        let syms = ana.symbols_in_file("demo/b.ts", src);
+        assert!(
+            syms.iter()
+                .any(|s| s.name == "Bar" && matches!(s.kind, SymbolKind::Struct))
+        );
+        assert!(syms.iter().all(|s| s.name != "default"));
+    }
+
+    #[test]
+    fn anonymous_default_exports_are_named_default() {
+        let ana = SpecTsAnalyzer::new_ts();
+        let src = "export default function () {\n  return 1;\n}\n";
+        let syms = ana.symbols_in_file("demo/a.ts", src);
+        let d = syms
+            .iter()
+            .find(|s| s.name == "default")
+            .expect("default fn");
+        assert!(matches!(d.kind, SymbolKind::Function));
+        assert_eq!((d.range.start_line, d.range.end_line), (1, 3));
+
+        let src = "export default class {\n  run() {}\n}\n";
+        let syms = ana.symbols_in_file("demo/b.ts", src);
+        assert!(
+            syms.iter()
+                .any(|s| s.name == "default" && matches!(s.kind, SymbolKind::Struct))
+        );
+
+        let src = "export default () => 1;\n";
+        let syms = ana.symbols_in_file("demo/c.ts", src);
+        assert!(
+            syms.iter()
+                .any(|s| s.name == "default" && matches!(s.kind, SymbolKind::Function))
+        );
     }
 
     #[test]
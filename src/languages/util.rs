@@ -1,7 +1,11 @@
 //! Common utilities for language analyzers.
+use crate::languages::LanguageKind;
+
 /// Build a vector of byte offsets for each line start.
 /// The returned Vec has length = number of lines + 1,
 /// where element i is the starting byte index of line i (1-based).
+/// Lines break at `\n` only, so `\r\n` is one boundary (its `\r` ends the
+/// line before) and numbering agrees with [`str::lines`] and tree-sitter rows.
 pub fn line_offsets(src: &str) -> Vec<usize> {
     let mut offs = Vec::with_capacity(src.len() / 20 + 2);
     offs.push(0);
@@ -20,3 +24,389 @@ pub fn byte_to_line(offs: &[usize], byte: usize) -> u32 {
         Err(i) => i as u32,
     }
 }
+
+/// Replace the contents of comments and string/char literals in `source` with
+/// spaces. Newlines are kept and every other blanked byte becomes one space,
+/// so byte offsets and line numbers (and thus [`byte_to_line`]) are the same
+/// as in the original.
+///
+/// Handles the lexical forms the regex/token passes trip over: nested block
+/// comments (Rust, Kotlin) and `r#"..."#` raw strings (Rust), `${..}`
+/// interpolation in template literals (JS/TS, where the interpolated code
+/// stays visible), triple-quoted strings (Python, Java text blocks, Kotlin
+/// raw strings), backtick raw strings
+/// (Go), `R"d(..)d"` raw strings (C++) and `=begin`/`=end` (Ruby). Regex
+/// literals and heredocs are left alone. `Auto` returns the source unchanged.
+pub fn mask_comments_and_strings(source: &str, lang: LanguageKind) -> String {
+    if lang == LanguageKind::Auto {
+        return source.to_string();
+    }
+    let mut m = Masker {
+        src: source.as_bytes(),
+        out: source.as_bytes().to_vec(),
+        lang,
+    };
+    m.code(0, false);
+    // Only runs of whole characters between ASCII delimiters were blanked
+    String::from_utf8(m.out).expect("masking keeps UTF-8 valid")
+}
+
+struct Masker<'a> {
+    src: &'a [u8],
+    out: Vec<u8>,
+    lang: LanguageKind,
+}
+
+impl Masker<'_> {
+    fn blank(&mut self, from: usize, to: usize) {
+        let to = to.min(self.out.len());
+        for b in &mut self.out[from.min(to)..to] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    }
+
+    fn at(&self, i: usize, pat: &str) -> bool {
+        self.src[i..].starts_with(pat.as_bytes())
+    }
+
+    fn ident_before(&self, i: usize) -> bool {
+        i > 0 && (self.src[i - 1].is_ascii_alphanumeric() || self.src[i - 1] == b'_')
+    }
+
+    fn c_like(&self) -> bool {
+        !matches!(self.lang, LanguageKind::Python | LanguageKind::Ruby)
+    }
+
+    fn js_like(&self) -> bool {
+        matches!(
+            self.lang,
+            LanguageKind::Javascript | LanguageKind::Typescript | LanguageKind::Tsx
+        )
+    }
+
+    /// Scan code from `i`. With `in_interp`, stop at the `}` closing a
+    /// template `${` and return its index; otherwise scan to the end.
+    fn code(&mut self, mut i: usize, in_interp: bool) -> usize {
+        let mut braces = 0usize;
+        while i < self.src.len() {
+            let c = self.src[i];
+            i = match c {
+                b'{' if in_interp => {
+                    braces += 1;
+                    i + 1
+                }
+                b'}' if in_interp => {
+                    if braces == 0 {
+                        return i;
+                    }
+                    braces -= 1;
+                    i + 1
+                }
+                b'/' if self.c_like() && self.at(i, "//") => self.line_comment(i),
+                b'/' if self.c_like() && self.at(i, "/*") => self.block_comment(i),
+                b'#' if !self.c_like() => self.line_comment(i),
+                b'=' if self.lang == LanguageKind::Ruby
+                    && self.at(i, "=begin")
+                    && (i == 0 || self.src[i - 1] == b'\n') =>
+                {
+                    self.ruby_block_comment(i)
+                }
+                b'"' | b'\'' => self.quoted(i),
+                b'`' if self.js_like() => self.template(i),
+                b'`' if self.lang == LanguageKind::Go => self.until(i, 1, "`"),
+                b'r' if self.lang == LanguageKind::Rust => self.rust_raw(i),
+                b'R' if self.lang == LanguageKind::Cpp => self.cpp_raw(i),
+                _ => i + 1,
+            };
+        }
+        i
+    }
+
+    fn line_comment(&mut self, i: usize) -> usize {
+        let end = self.src[i..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.src.len(), |p| i + p);
+        self.blank(i, end);
+        end
+    }
+
+    fn block_comment(&mut self, start: usize) -> usize {
+        let nests = matches!(self.lang, LanguageKind::Rust | LanguageKind::Kotlin);
+        let mut depth = 0usize;
+        let mut i = start;
+        while i < self.src.len() {
+            if self.at(i, "/*") && (nests || depth == 0) {
+                depth += 1;
+                i += 2;
+            } else if self.at(i, "*/") {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        self.blank(start, i);
+        i
+    }
+
+    fn ruby_block_comment(&mut self, start: usize) -> usize {
+        let mut i = start;
+        let end = loop {
+            match self.src[i..].iter().position(|&b| b == b'\n') {
+                Some(p) => {
+                    i += p + 1;
+                    if self.at(i, "=end") {
+                        break self.line_comment(i);
+                    }
+                }
+                None => break self.src.len(),
+            }
+        };
+        self.blank(start, end);
+        end
+    }
+
+    /// Blank from `start` through the next `close` after skipping `open_len`
+    /// opening bytes, without escapes (raw strings).
+    fn until(&mut self, start: usize, open_len: usize, close: &str) -> usize {
+        let body = start + open_len;
+        let end = self.src[body.min(self.src.len())..]
+            .windows(close.len())
+            .position(|w| w == close.as_bytes())
+            .map_or(self.src.len(), |p| body + p + close.len());
+        self.blank(start, end);
+        end
+    }
+
+    /// `"..."`/`'...'` with backslash escapes, plus the triple-quoted forms.
+    fn quoted(&mut self, start: usize) -> usize {
+        let q = self.src[start];
+        let triple = if q == b'"' { "\"\"\"" } else { "'''" };
+        let has_triple = match self.lang {
+            LanguageKind::Python => true,
+            LanguageKind::Java | LanguageKind::Kotlin => q == b'"',
+            _ => false,
+        };
+        if has_triple && self.at(start, triple) {
+            return self.escaped_until(start, 3, triple, true);
+        }
+        if q == b'\'' && self.lang == LanguageKind::Rust {
+            return self.rust_char(start);
+        }
+        // Rust and Ruby strings may span lines; elsewhere a stray quote must
+        // not swallow the rest of the file
+        let multiline = matches!(self.lang, LanguageKind::Rust | LanguageKind::Ruby);
+        let close = if q == b'"' { "\"" } else { "'" };
+        self.escaped_until(start, 1, close, multiline)
+    }
+
+    fn escaped_until(
+        &mut self,
+        start: usize,
+        open_len: usize,
+        close: &str,
+        multiline: bool,
+    ) -> usize {
+        let mut i = start + open_len;
+        let end = loop {
+            if i >= self.src.len() {
+                break self.src.len();
+            }
+            if self.src[i] == b'\\' {
+                i += 2;
+            } else if self.at(i, close) {
+                break i + close.len();
+            } else if self.src[i] == b'\n' && !multiline {
+                break i;
+            } else {
+                i += 1;
+            }
+        };
+        self.blank(start, end);
+        end
+    }
+
+    /// `'x'`, `'\n'`, `'\u{..}'`; anything else after `'` is a lifetime.
+    fn rust_char(&mut self, start: usize) -> usize {
+        let rest = &self.src[start + 1..];
+        let end = if rest.first() == Some(&b'\\') {
+            rest.iter()
+                .take(12)
+                .skip(2)
+                .position(|&b| b == b'\'')
+                .map(|p| start + 1 + 2 + p + 1)
+        } else {
+            // Byte length of the UTF-8 sequence starting the literal
+            let len = match rest.first() {
+                Some(&b) if b >= 0xF0 => 4,
+                Some(&b) if b >= 0xE0 => 3,
+                Some(&b) if b >= 0xC0 => 2,
+                _ => 1,
+            };
+            (rest.get(len) == Some(&b'\'')).then_some(start + 1 + len + 1)
+        };
+        match end {
+            Some(end) => {
+                self.blank(start, end);
+                end
+            }
+            None => start + 1,
+        }
+    }
+
+    /// `r"..."`, `r#"..."#`, `br"..."` (the `b` was already passed as code).
+    fn rust_raw(&mut self, start: usize) -> usize {
+        let token_start = if start > 0 && self.src[start - 1] == b'b' {
+            start - 1
+        } else {
+            start
+        };
+        if self.ident_before(token_start) {
+            return start + 1;
+        }
+        let hashes = self.src[start + 1..]
+            .iter()
+            .take_while(|&&b| b == b'#')
+            .count();
+        if self.src.get(start + 1 + hashes) != Some(&b'"') {
+            return start + 1;
+        }
+        let close = format!("\"{}", "#".repeat(hashes));
+        self.until(start, hashes + 2, &close)
+    }
+
+    /// `R"delim( ... )delim"`, optionally after a `u8`/`u`/`U`/`L` prefix.
+    fn cpp_raw(&mut self, start: usize) -> usize {
+        if self.src.get(start + 1) != Some(&b'"') {
+            return start + 1;
+        }
+        let prefixed = [&b"u8"[..], b"u", b"U", b"L"]
+            .iter()
+            .any(|p| self.src[..start].ends_with(p) && !self.ident_before(start - p.len()));
+        if self.ident_before(start) && !prefixed {
+            return start + 1;
+        }
+        let Some(open) = self.src[start + 2..]
+            .iter()
+            .take(17)
+            .position(|&b| b == b'(')
+        else {
+            return start + 1;
+        };
+        let delim = String::from_utf8_lossy(&self.src[start + 2..start + 2 + open]);
+        let close = format!("){delim}\"");
+        self.until(start, open + 3, &close)
+    }
+
+    /// Template literal; `${..}` bodies are scanned as code and left visible.
+    fn template(&mut self, start: usize) -> usize {
+        let mut seg = start;
+        let mut i = start + 1;
+        while i < self.src.len() {
+            match self.src[i] {
+                b'\\' => i += 2,
+                b'`' => {
+                    self.blank(seg, i + 1);
+                    return i + 1;
+                }
+                b'$' if self.src.get(i + 1) == Some(&b'{') => {
+                    self.blank(seg, i + 2);
+                    let close = self.code(i + 2, true);
+                    self.blank(close, close + 1);
+                    seg = close + 1;
+                    i = close + 1;
+                }
+                _ => i += 1,
+            }
+        }
+        self.blank(seg, self.src.len());
+        self.src.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask(src: &str, lang: LanguageKind) -> String {
+        let out = mask_comments_and_strings(src, lang);
+        assert_eq!(out.len(), src.len(), "offsets must be preserved");
+        assert_eq!(out.lines().count(), src.lines().count());
+        out
+    }
+
+    #[test]
+    fn rust_nested_comments_raw_strings_and_lifetimes() {
+        let src = "fn f<'a>(x: &'a str) { /* a /* b() */ c() */ g(r#\"h(\"x\")\"#, 'é', '\\n'); }\n// i()\n";
+        assert_eq!(
+            mask(src, LanguageKind::Rust),
+            "fn f<'a>(x: &'a str) {                       g(           ,     ,     ); }\n      \n"
+        );
+    }
+
+    #[test]
+    fn js_template_literals_keep_interpolated_code() {
+        let src = "const s = `a() ${ f(`b()`) } c()`; // d()\nconst t = 'e()';\n";
+        assert_eq!(
+            mask(src, LanguageKind::Typescript),
+            "const s =         f(     )       ;       \nconst t =      ;\n"
+        );
+    }
+
+    #[test]
+    fn python_triple_quotes_and_hash_comments() {
+        let src = "x = \"\"\"a()\nb()\"\"\"  # c()\ny = '#' + f()\n";
+        assert_eq!(
+            mask(src, LanguageKind::Python),
+            "x =       \n             \ny =     + f()\n"
+        );
+    }
+
+    #[test]
+    fn cpp_raw_strings_and_unterminated_quotes() {
+        let src = "auto s = R\"d(f() )\" g())d\"; h();\nchar *p = \"oops\nk();\n";
+        assert_eq!(
+            mask(src, LanguageKind::Cpp),
+            "auto s =                  ; h();\nchar *p =      \nk();\n"
+        );
+    }
+
+    #[test]
+    fn kotlin_nested_comments_and_raw_strings() {
+        let src = "/* a /* b() */ c() */ f(\"\"\"g()\nh()\"\"\") // i()\n";
+        let out = mask(src, LanguageKind::Kotlin);
+        assert!(out.contains(" f("), "{out:?}");
+        for hidden in ["b()", "c()", "g()", "h()", "i()"] {
+            assert!(!out.contains(hidden), "{hidden} in {out:?}");
+        }
+    }
+
+    #[test]
+    fn ruby_block_comments_and_go_raw_strings() {
+        let rb = "=begin\nf()\n=end\ng(\"#{h()}\") # i()\n";
+        assert_eq!(
+            mask(rb, LanguageKind::Ruby),
+            "      \n   \n    \ng(        )      \n"
+        );
+        let go = "s := `a()\nb()` + c()\n";
+        assert_eq!(mask(go, LanguageKind::Go), "s :=     \n     + c()\n");
+    }
+
+    #[test]
+    fn crlf_counts_as_one_line_boundary() {
+        let src = "a\r\nb\nc\r\n";
+        let offs = line_offsets(src);
+        assert_eq!(offs, vec![0, 3, 5, 8]);
+        assert_eq!(offs.len() - 1, src.lines().count());
+        assert_eq!(byte_to_line(&offs, src.find('b').unwrap()), 2);
+        assert_eq!(byte_to_line(&offs, src.find('c').unwrap()), 3);
+        // the `\r` belongs to the line it ends
+        assert_eq!(byte_to_line(&offs, 1), 1);
+    }
+}
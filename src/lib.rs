@@ -2,35 +2,58 @@ pub mod cache;
 pub mod dfg;
 pub mod diff;
 pub mod engine;
+pub mod graph_source;
 pub mod impact;
 pub mod ir;
 pub mod languages;
 pub mod mapping;
+pub mod profile;
 pub mod render;
 pub mod schema;
 pub mod ts_core;
 
 pub use dfg::{DataFlowGraph, DependencyKind, DfgBuilder, DfgEdge, DfgNode};
-pub use diff::{Change, ChangeKind, DiffParseError, FileChanges, parse_unified_diff};
+pub use diff::{
+    Change, ChangeKind, DiffParseError, FileChanges, FileStatus, merge_file_changes,
+    parse_unified_diff,
+};
 pub use engine::EngineConfig;
 pub use engine::{AnalysisEngine, EngineKind};
+pub use graph_source::GraphSource;
 pub use impact::{
-    ImpactAffectedModule, ImpactDepthBucket, ImpactDirection, ImpactOptions, ImpactOutput,
-    ImpactRiskLevel, ImpactRiskSummary, ImpactSliceBridgeKind, ImpactSliceCandidateLane,
-    ImpactSliceCandidateScoringSummary, ImpactSliceCandidateSourceKind,
+    AnalyzeOptions, CandidateTrace, DeadCodeOptions, GraphLimitError, GraphLimits,
+    ImpactAffectedModule, ImpactComparison, ImpactDepthBucket, ImpactDirection, ImpactOptions,
+    ImpactOutput, ImpactRiskLevel, ImpactRiskSummary, ImpactSliceBridgeKind,
+    ImpactSliceCandidateLane, ImpactSliceCandidateScoringSummary, ImpactSliceCandidateSourceKind,
     ImpactSliceCandidateSupportMetadata, ImpactSliceEvidenceKind, ImpactSliceFileMetadata,
     ImpactSliceNegativeEvidenceKind, ImpactSlicePlannerKind, ImpactSlicePruneReason,
     ImpactSlicePrunedCandidate, ImpactSliceReasonKind, ImpactSliceReasonMetadata,
     ImpactSliceScopes, ImpactSliceScoreTuple, ImpactSliceSelectionSummary,
     ImpactSliceSupportEdgeCertainty, ImpactSummary, ImpactWitness, ImpactWitnessHop,
     ImpactWitnessSliceContext, ImpactWitnessSliceFileContext, ImpactWitnessSliceRankingBasis,
-    ImpactWitnessSliceSelectedVsPrunedReason, attach_slice_selection_summary, build_project_graph,
-    compute_impact, path_is_ignored,
+    ImpactWitnessSliceSelectedVsPrunedReason, ImpactedTest, ProjectGraph, ResolutionStage,
+    ResolutionTrace, ScoreBreakdown, SymbolMetrics, analyze_workspace,
+    attach_slice_selection_summary, attach_snippets, build_project_graph, build_project_graph_from,
+    cap_impacted_symbols, check_graph_limits, compare_impact, compute_impact,
+    compute_symbol_metrics, explain_workspace, export_project_graph, find_cycles, find_dead_code,
+    find_impacted_tests, impact_over, is_test_path, list_analyzed_files, normalize_edges,
+    path_is_ignored, resolve_one, snippets_for,
 };
-pub use ir::{Symbol, SymbolId, SymbolKind, TextRange};
+pub use ir::reference::{EdgeCertainty, EdgeProvenance, RefKind, Reference, SymbolIndex};
+pub use ir::{Symbol, SymbolId, SymbolIdParts, SymbolKind, TextRange, parse_symbol_id};
 pub use languages::LanguageKind;
-pub use mapping::{ChangedOutput, LanguageMode, compute_changed_symbols};
-pub use render::{dfg_to_dot, to_dot, to_html};
+pub use languages::path::{normalize_path, workspace_file};
+pub use mapping::{
+    ChangeScope, ChangedOutput, LanguageMode, MappingOptions, SymbolChange, SymbolChangeKind,
+    attach_changed_lines, cap_changed_symbols, changed_symbols_in_index, classify_change_scopes,
+    classify_symbol_changes, compute_changed_symbols, compute_changed_symbols_with_options,
+    signature_span,
+};
+pub use render::{
+    HtmlOptions, changed_to_text, cycles_to_dot, dead_code_to_text, dfg_to_dot,
+    impacted_tests_to_text, project_graph_to_dot, project_graph_to_graphml, to_dot,
+    to_github_annotations, to_graphml, to_html, to_html_data, to_html_with_options, to_text,
+};
 pub use schema::{
     ImpactSchemaEdgeDetail, ImpactSchemaGraphMode, ImpactSchemaLayout, ImpactSchemaProfile,
     JSON_SCHEMA_DRAFT_URL, JSON_SCHEMA_FORMAT, JSON_SCHEMA_MAJOR_VERSION, JSON_SCHEMA_NAMESPACE,
@@ -1,11 +1,14 @@
-use crate::diff::{ChangeKind, FileChanges};
-use crate::ir::{Symbol, TextRange};
-use crate::languages::{LanguageKind, analyzer_for_path};
+use crate::diff::{ChangeKind, FileChanges, FileStatus, merge_file_changes};
+use crate::ir::{Symbol, SymbolId, SymbolKind, TextRange};
+use crate::languages::path::workspace_file;
+use crate::languages::{AnalyzerConfig, LanguageKind};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LanguageMode {
     Auto,
     Rust,
@@ -16,18 +19,125 @@ pub enum LanguageMode {
     Tsx,
     Go,
     Java,
+    Kotlin,
+    Cpp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ChangedOutput {
     pub changed_files: Vec<String>,
     pub changed_symbols: Vec<Symbol>,
+    /// Per-symbol change classification; only filled by [`classify_symbol_changes`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbol_changes: Vec<SymbolChange>,
+    /// Source text per symbol id; only filled by [`crate::impact::snippets_for`].
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub snippets: std::collections::HashMap<String, String>,
+    /// Added lines (new-file numbers, ascending) inside each symbol, keyed by
+    /// symbol id; only filled by [`attach_changed_lines`].
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub changed_lines: std::collections::HashMap<String, Vec<u32>>,
+    /// How many symbols changed before [`cap_changed_symbols`] cut the list;
+    /// absent when nothing was cut.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_changed: Option<usize>,
+    /// Whether each changed function or method had its signature or only its
+    /// body edited, keyed by symbol id; only filled by [`classify_change_scopes`].
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub change_scopes: std::collections::HashMap<String, ChangeScope>,
 }
 
+/// Which part of a function a change touched.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeScope {
+    /// The declaration up to the body: name, parameters, return type. Callers
+    /// may be affected.
+    Signature,
+    /// Only lines inside the body; the function's API is unchanged.
+    Body,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SymbolChange {
+    pub id: SymbolId,
+    pub change_kind: SymbolChangeKind,
+}
+
+fn language_kind(lang: LanguageMode) -> LanguageKind {
+    match lang {
+        LanguageMode::Auto => LanguageKind::Auto,
+        LanguageMode::Rust => LanguageKind::Rust,
+        LanguageMode::Ruby => LanguageKind::Ruby,
+        LanguageMode::Python => LanguageKind::Python,
+        LanguageMode::Javascript => LanguageKind::Javascript,
+        LanguageMode::Typescript => LanguageKind::Typescript,
+        LanguageMode::Tsx => LanguageKind::Tsx,
+        LanguageMode::Go => LanguageKind::Go,
+        LanguageMode::Java => LanguageKind::Java,
+        LanguageMode::Kotlin => LanguageKind::Kotlin,
+        LanguageMode::Cpp => LanguageKind::Cpp,
+    }
+}
+
+/// How [`compute_changed_symbols_with_options`] maps a diff onto symbols.
+#[derive(Debug, Clone, Default)]
+pub struct MappingOptions {
+    /// Map changed lines to symbols as if the diff had been produced with
+    /// `-w`: edits that only touch whitespace are dropped first (see
+    /// [`FileChanges::without_whitespace_changes`]).
+    pub ignore_whitespace: bool,
+    /// Optional relationships the language analyzers extract (e.g. Rust
+    /// macros as changed symbols).
+    pub analyzers: AnalyzerConfig,
+    /// Workspace root that diff paths are relative to and changed files are
+    /// read from (the current directory when unset).
+    pub root: Option<PathBuf>,
+}
+
+/// `diffs`, without whitespace-only edits under
+/// [`MappingOptions::ignore_whitespace`].
+pub(crate) fn significant_changes<'a>(
+    diffs: &'a [FileChanges],
+    opts: &MappingOptions,
+) -> Cow<'a, [FileChanges]> {
+    if opts.ignore_whitespace {
+        Cow::Owned(
+            diffs
+                .iter()
+                .map(FileChanges::without_whitespace_changes)
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(diffs)
+    }
+}
+
+/// [`compute_changed_symbols_with_options`] with the default options.
 pub fn compute_changed_symbols(
     diffs: &[FileChanges],
     lang: LanguageMode,
 ) -> anyhow::Result<ChangedOutput> {
+    compute_changed_symbols_with_options(diffs, lang, &MappingOptions::default())
+}
+
+pub fn compute_changed_symbols_with_options(
+    diffs: &[FileChanges],
+    lang: LanguageMode,
+    opts: &MappingOptions,
+) -> anyhow::Result<ChangedOutput> {
+    // A diff may list one file in several sections (e.g. concatenated
+    // patches); fold them so each path maps its hunks, removed lines
+    // included, once.
+    let diffs = &merge_file_changes(vec![significant_changes(diffs, opts).into_owned()]);
     // Include both new_path (added/modified) and old_path for deletions/renames,
     // so cache can mark removed files as present=0 when they no longer exist.
     let mut changed_files: Vec<String> = Vec::new();
@@ -39,46 +149,44 @@ pub fn compute_changed_symbols(
         }
     }
 
-    let mut changed_lines_by_file: HashMap<String, HashSet<u32>> = HashMap::new();
+    let mut changed_lines_by_file: HashMap<String, BTreeSet<u32>> = HashMap::new();
+    let mut diff_by_file: HashMap<&str, &FileChanges> = HashMap::new();
     for fc in diffs {
+        // Deleted files have no post-image to map; their symbols are reported
+        // as removed by classify_symbol_changes.
+        if fc.status() == FileStatus::Deleted {
+            continue;
+        }
         if let Some(path) = &fc.new_path {
-            let set = changed_lines_by_file.entry(path.clone()).or_default();
-            for ch in &fc.changes {
-                // count Added, Removed, and Context lines as changes
-                if matches!(ch.kind, ChangeKind::Added)
-                    || matches!(ch.kind, ChangeKind::Removed)
-                    || matches!(ch.kind, ChangeKind::Context)
-                {
-                    // use new_line when available, else old_line for removals
-                    if let Some(nl) = ch.new_line {
-                        set.insert(nl);
-                    } else if let Some(ol) = ch.old_line {
-                        set.insert(ol);
-                    }
-                }
-            }
+            diff_by_file.insert(path.as_str(), fc);
+            // Added and Context lines by new_line; Removed lines are mapped
+            // through the pre-image below.
+            changed_lines_by_file
+                .entry(path.clone())
+                .or_default()
+                .extend(fc.hunk_new_lines());
         }
     }
 
     let mut changed_symbols = Vec::new();
-    for (path, lines) in changed_lines_by_file.iter() {
-        let kind = match lang {
-            LanguageMode::Auto => LanguageKind::Auto,
-            LanguageMode::Rust => LanguageKind::Rust,
-            LanguageMode::Ruby => LanguageKind::Ruby,
-            LanguageMode::Python => LanguageKind::Python,
-            LanguageMode::Javascript => LanguageKind::Javascript,
-            LanguageMode::Typescript => LanguageKind::Typescript,
-            LanguageMode::Tsx => LanguageKind::Tsx,
-            LanguageMode::Go => LanguageKind::Go,
-            LanguageMode::Java => LanguageKind::Java,
-        };
-        let Some(analyzer) = analyzer_for_path(path, kind) else {
+    for (path, lines) in changed_lines_by_file.iter_mut() {
+        let Some(analyzer) = opts.analyzers.analyzer_for_file(path, language_kind(lang)) else {
             continue;
         };
-        let Ok(source) = fs::read_to_string(path) else {
+        let Ok(source) = fs::read_to_string(workspace_file(opts.root.as_deref(), path)) else {
             continue;
         };
+        if let Some(fc) = diff_by_file.get(path.as_str())
+            && fc.status() != FileStatus::Added
+        {
+            lines.extend(removed_line_anchors(
+                fc,
+                path,
+                &source,
+                language_kind(lang),
+                &opts.analyzers,
+            ));
+        }
         let symbols = analyzer.symbols_in_file(path, &source);
         for s in symbols {
             if intersects(&s.range, lines) {
@@ -93,16 +201,494 @@ pub fn compute_changed_symbols(
     Ok(ChangedOutput {
         changed_files,
         changed_symbols,
+        symbol_changes: Vec::new(),
+        snippets: std::collections::HashMap::new(),
+        changed_lines: std::collections::HashMap::new(),
+        total_changed: None,
+        change_scopes: std::collections::HashMap::new(),
     })
 }
 
-fn intersects(range: &TextRange, lines: &HashSet<u32>) -> bool {
-    for ln in range.start_line..=range.end_line {
-        if lines.contains(&ln) {
-            return true;
+/// Keep the first `max` changed symbols by id, dropping the rest from the
+/// per-symbol maps too, and record the full count in
+/// [`ChangedOutput::total_changed`]. A no-op when `max` is not exceeded.
+pub fn cap_changed_symbols(report: &mut ChangedOutput, max: usize) {
+    let total = report.changed_symbols.len();
+    if total <= max {
+        return;
+    }
+    report.changed_symbols.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    report.changed_symbols.truncate(max);
+    let kept: HashSet<&str> = report
+        .changed_symbols
+        .iter()
+        .map(|s| s.id.0.as_str())
+        .collect();
+    report
+        .symbol_changes
+        .retain(|c| kept.contains(c.id.0.as_str()));
+    report.snippets.retain(|id, _| kept.contains(id.as_str()));
+    report
+        .changed_lines
+        .retain(|id, _| kept.contains(id.as_str()));
+    report
+        .change_scopes
+        .retain(|id, _| kept.contains(id.as_str()));
+    report.total_changed = Some(total);
+}
+
+/// Record in `report.changed_lines` which added lines of `diffs` fall inside
+/// each changed symbol, so editors can mark exactly the edited lines. Symbols
+/// with no added line (pure deletions, or removed symbols whose ranges are
+/// pre-image lines) get no entry.
+pub fn attach_changed_lines(report: &mut ChangedOutput, diffs: &[FileChanges]) {
+    let added_lines_by_file = added_lines_by_file(diffs);
+    let removed: HashSet<&str> = report
+        .symbol_changes
+        .iter()
+        .filter(|c| c.change_kind == SymbolChangeKind::Removed)
+        .map(|c| c.id.0.as_str())
+        .collect();
+    for s in &report.changed_symbols {
+        if removed.contains(s.id.0.as_str()) {
+            continue;
+        }
+        let Some(added) = added_lines_by_file
+            .get(s.file.as_str())
+            .filter(|_| s.range.start_line <= s.range.end_line)
+        else {
+            continue;
+        };
+        let inside: Vec<u32> = added
+            .range(s.range.start_line..=s.range.end_line)
+            .copied()
+            .collect();
+        if !inside.is_empty() {
+            report.changed_lines.insert(s.id.0.clone(), inside);
+        }
+    }
+}
+
+/// Lines of the function `sym` up to the one that opens its body: the first
+/// line with a `{`, or for Python the first ending in `:`. Ruby, and
+/// declarations without a body, count as signature throughout (Ruby: the
+/// `def` line only).
+pub fn signature_span(sym: &Symbol, source: &str) -> TextRange {
+    let start = sym.range.start_line;
+    let end = sym.range.end_line.max(start);
+    let lines: Vec<&str> = source
+        .lines()
+        .skip(start.saturating_sub(1) as usize)
+        .take((end - start + 1) as usize)
+        .collect();
+    let opens_body: fn(&str) -> bool = match sym.language.as_str() {
+        "ruby" => {
+            return TextRange {
+                start_line: start,
+                end_line: start,
+            };
+        }
+        "python" => |l| l.trim_end().ends_with(':'),
+        _ => |l| l.contains('{'),
+    };
+    let sig_end = lines
+        .iter()
+        .position(|l| opens_body(l))
+        .map_or(end, |i| start + i as u32);
+    TextRange {
+        start_line: start,
+        end_line: sig_end,
+    }
+}
+
+/// Record in `report.change_scopes` whether each changed function or method
+/// had its signature edited ([`signature_span`] contains an added line, or a
+/// removed line fell in the pre-change signature) or only its body.
+///
+/// Added functions count as signature changes; removed ones (see
+/// [`classify_symbol_changes`]) and symbols whose file cannot be read are
+/// left out.
+pub fn classify_change_scopes(
+    report: &mut ChangedOutput,
+    diffs: &[FileChanges],
+    lang: LanguageMode,
+    opts: &MappingOptions,
+) {
+    let removed: HashSet<&str> = report
+        .symbol_changes
+        .iter()
+        .filter(|c| c.change_kind == SymbolChangeKind::Removed)
+        .map(|c| c.id.0.as_str())
+        .collect();
+    let diff_by_file: HashMap<&str, &FileChanges> = diffs
+        .iter()
+        .filter_map(|fc| fc.new_path.as_deref().map(|p| (p, fc)))
+        .collect();
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    let mut pre_images: HashMap<&str, Option<(String, Vec<Symbol>)>> = HashMap::new();
+    for sym in &report.changed_symbols {
+        if !matches!(sym.kind, SymbolKind::Function | SymbolKind::Method)
+            || removed.contains(sym.id.0.as_str())
+        {
+            continue;
+        }
+        let Some(fc) = diff_by_file.get(sym.file.as_str()) else {
+            continue;
+        };
+        let Some(source) = sources.entry(sym.file.as_str()).or_insert_with(|| {
+            fs::read_to_string(workspace_file(opts.root.as_deref(), &sym.file)).ok()
+        }) else {
+            continue;
+        };
+        let sig = signature_span(sym, source);
+        let added_in_sig = fc
+            .changed_new_lines()
+            .range(sig.start_line..=sig.end_line)
+            .next()
+            .is_some();
+        let mut removed_in_sig = || {
+            let Some((old_src, old_syms)) =
+                pre_images.entry(sym.file.as_str()).or_insert_with(|| {
+                    pre_image_symbols(fc, &sym.file, source, language_kind(lang), &opts.analyzers)
+                })
+            else {
+                return false;
+            };
+            let Some(old_sym) = old_syms
+                .iter()
+                .filter(|o| o.name == sym.name && o.kind == sym.kind)
+                .min_by_key(|o| o.range.start_line.abs_diff(sym.range.start_line))
+            else {
+                return false;
+            };
+            let old_sig = signature_span(old_sym, old_src);
+            fc.removed_old_lines()
+                .range(old_sig.start_line..=old_sig.end_line)
+                .next()
+                .is_some()
+        };
+        let scope = if added_in_sig || removed_in_sig() {
+            ChangeScope::Signature
+        } else {
+            ChangeScope::Body
+        };
+        report.change_scopes.insert(sym.id.0.clone(), scope);
+    }
+}
+
+/// The file's text before `fc` was applied and its symbols; `None` when the
+/// pre-image cannot be rebuilt or the file has no analyzer.
+fn pre_image_symbols(
+    fc: &FileChanges,
+    path: &str,
+    new_src: &str,
+    kind: LanguageKind,
+    analyzers: &AnalyzerConfig,
+) -> Option<(String, Vec<Symbol>)> {
+    let analyzer = analyzers.analyzer_for_file(path, kind)?;
+    let old_src = reconstruct_pre_image(new_src, fc)?;
+    let symbols = analyzer.symbols_in_file(path, &old_src);
+    Some((old_src, symbols))
+}
+
+/// Classify every changed symbol as added, removed, or modified and record it in
+/// `report.symbol_changes`.
+///
+/// - A symbol whose every line in the new file is an added line is `Added`.
+/// - Symbols declared inside a contiguous block of removed lines that no longer
+///   exist (same name and kind) in the new file are `Removed`; they are
+///   reconstructed from the diff, appended to `changed_symbols`, and carry
+///   pre-image line numbers.
+/// - Everything else is `Modified`.
+pub fn classify_symbol_changes(
+    report: &mut ChangedOutput,
+    diffs: &[FileChanges],
+    lang: LanguageMode,
+    opts: &MappingOptions,
+) {
+    let added_lines_by_file = added_lines_by_file(diffs);
+
+    let mut changes: Vec<SymbolChange> = report
+        .changed_symbols
+        .iter()
+        .map(|s| {
+            let added = added_lines_by_file.get(s.file.as_str());
+            let all_added = added.is_some_and(|set| {
+                (s.range.start_line..=s.range.end_line).all(|ln| set.contains(&ln))
+            });
+            SymbolChange {
+                id: s.id.clone(),
+                change_kind: if all_added {
+                    SymbolChangeKind::Added
+                } else {
+                    SymbolChangeKind::Modified
+                },
+            }
+        })
+        .collect();
+
+    let known: HashSet<String> = report
+        .changed_symbols
+        .iter()
+        .map(|s| s.id.0.clone())
+        .collect();
+    let mut removed = removed_symbols(diffs, lang, opts);
+    removed.retain(|s| !known.contains(&s.id.0));
+    for s in &removed {
+        changes.push(SymbolChange {
+            id: s.id.clone(),
+            change_kind: SymbolChangeKind::Removed,
+        });
+    }
+    report.changed_symbols.extend(removed);
+    report.symbol_changes = changes;
+}
+
+/// Symbols declared entirely inside removed-line blocks that have no
+/// same-named, same-kind counterpart left in the new file.
+fn removed_symbols(
+    diffs: &[FileChanges],
+    lang: LanguageMode,
+    opts: &MappingOptions,
+) -> Vec<Symbol> {
+    let mut out = Vec::new();
+    for fc in diffs {
+        // Added files have no pre-image symbols to lose.
+        if fc.status() == FileStatus::Added {
+            continue;
+        }
+        let Some(old_path) = &fc.old_path else {
+            continue;
+        };
+        let Some(analyzer) = opts
+            .analyzers
+            .analyzer_for_file(old_path, language_kind(lang))
+        else {
+            continue;
+        };
+        // Contiguous runs of removed lines: (first old line, text)
+        let mut blocks: Vec<(u32, String)> = Vec::new();
+        let mut prev_old: Option<u32> = None;
+        for ch in &fc.changes {
+            let (ChangeKind::Removed, Some(ol)) = (&ch.kind, ch.old_line) else {
+                prev_old = None;
+                continue;
+            };
+            match blocks.last_mut() {
+                Some((_, text)) if prev_old == Some(ol - 1) => {
+                    text.push_str(&ch.content);
+                    text.push('\n');
+                }
+                _ => blocks.push((ol, format!("{}\n", ch.content))),
+            }
+            prev_old = Some(ol);
+        }
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let surviving: HashSet<(String, SymbolKind)> = fc
+            .new_path
+            .as_deref()
+            .and_then(|p| {
+                fs::read_to_string(workspace_file(opts.root.as_deref(), p))
+                    .ok()
+                    .map(|src| (p, src))
+            })
+            .map(|(p, src)| {
+                analyzer
+                    .symbols_in_file(p, &src)
+                    .into_iter()
+                    .map(|s| (s.name, s.kind))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (start, text) in blocks {
+            for s in analyzer.symbols_in_file(old_path, &text) {
+                if surviving.contains(&(s.name.clone(), s.kind.clone())) {
+                    continue;
+                }
+                let range = TextRange {
+                    start_line: s.range.start_line + start - 1,
+                    end_line: s.range.end_line + start - 1,
+                };
+                out.push(Symbol {
+                    id: SymbolId::new(&s.language, old_path, &s.kind, &s.name, range.start_line),
+                    range,
+                    ..s
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    out.dedup_by(|a, b| a.id.0 == b.id.0);
+    out
+}
+
+/// Rebuild the pre-change file content from the new content and its diff.
+///
+/// Returns `None` when the diff does not line up with `new_src` (e.g. the
+/// working file has moved on since the diff was taken).
+pub(crate) fn reconstruct_pre_image(new_src: &str, fc: &FileChanges) -> Option<String> {
+    let new_lines: Vec<&str> = new_src.lines().collect();
+    let mut old_lines: Vec<&str> = Vec::with_capacity(new_lines.len());
+    let mut next_new = 0usize; // 0-based index of the next unconsumed new line
+    for ch in &fc.changes {
+        match ch.kind {
+            ChangeKind::Removed => {
+                let ol = ch.old_line? as usize;
+                while old_lines.len() + 1 < ol {
+                    old_lines.push(new_lines.get(next_new)?);
+                    next_new += 1;
+                }
+                if old_lines.len() + 1 != ol {
+                    return None;
+                }
+                old_lines.push(ch.content.as_str());
+            }
+            ChangeKind::Added => {
+                let nl = ch.new_line? as usize;
+                while next_new + 1 < nl {
+                    old_lines.push(new_lines.get(next_new)?);
+                    next_new += 1;
+                }
+                if next_new + 1 != nl || *new_lines.get(next_new)? != ch.content {
+                    return None;
+                }
+                next_new += 1;
+            }
+            ChangeKind::Context => {}
+        }
+    }
+    old_lines.extend(&new_lines[next_new.min(new_lines.len())..]);
+    let mut out = old_lines.join("\n");
+    if new_src.ends_with('\n') || !fc.changes.is_empty() {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// New-file lines standing in for the removed lines of `fc`.
+///
+/// Each removed line is attributed to its innermost enclosing symbol in the
+/// pre-image; the start line of that symbol's counterpart in the new file (same
+/// name and kind, nearest position) is returned. Symbols deleted outright have
+/// no counterpart and contribute nothing. If the pre-image cannot be rebuilt,
+/// falls back to treating removed `old_line`s as new-file lines.
+pub(crate) fn removed_line_anchors(
+    fc: &FileChanges,
+    path: &str,
+    new_src: &str,
+    kind: LanguageKind,
+    analyzers: &AnalyzerConfig,
+) -> Vec<u32> {
+    let removed: Vec<u32> = fc.removed_old_lines().into_iter().collect();
+    if removed.is_empty() {
+        return Vec::new();
+    }
+    let Some(analyzer) = analyzers.analyzer_for_file(path, kind) else {
+        return Vec::new();
+    };
+    let Some(old_src) = reconstruct_pre_image(new_src, fc) else {
+        return removed;
+    };
+    let old_symbols = analyzer.symbols_in_file(path, &old_src);
+    let new_symbols = analyzer.symbols_in_file(path, new_src);
+    let mut anchors = Vec::new();
+    for ln in removed {
+        let Some(old_sym) = old_symbols
+            .iter()
+            .filter(|s| s.range.start_line <= ln && ln <= s.range.end_line)
+            .min_by_key(|s| s.range.end_line - s.range.start_line)
+        else {
+            continue;
+        };
+        if let Some(new_sym) = new_symbols
+            .iter()
+            .filter(|s| s.name == old_sym.name && s.kind == old_sym.kind)
+            .min_by_key(|s| s.range.start_line.abs_diff(old_sym.range.start_line))
+        {
+            anchors.push(new_sym.range.start_line);
+        }
+    }
+    anchors.sort_unstable();
+    anchors.dedup();
+    anchors
+}
+
+/// Map a diff onto symbols of a pre-change graph (e.g. one built from a git base tree).
+///
+/// Uses the old side of each hunk: removed and context lines are matched by
+/// `old_line`, and added lines are attributed to a symbol only when their
+/// insertion point lies inside it (both neighbouring old lines within its range).
+pub fn changed_symbols_in_index(
+    diffs: &[FileChanges],
+    index: &crate::ir::reference::SymbolIndex,
+) -> Vec<Symbol> {
+    let mut changed_symbols = Vec::new();
+    for fc in diffs {
+        let Some(path) = &fc.old_path else {
+            continue;
+        };
+        let Some(symbols) = index.by_file.get(path) else {
+            continue;
+        };
+        let mut lines: BTreeSet<u32> = BTreeSet::new();
+        let mut insertions: Vec<(u32, u32)> = Vec::new();
+        let (mut added_before, mut removed_before) = (0u32, 0u32);
+        for ch in &fc.changes {
+            match ch.kind {
+                ChangeKind::Removed | ChangeKind::Context => {
+                    if let Some(ol) = ch.old_line {
+                        lines.insert(ol);
+                    }
+                    if matches!(ch.kind, ChangeKind::Removed) {
+                        removed_before += 1;
+                    }
+                }
+                ChangeKind::Added => {
+                    if let Some(nl) = ch.new_line {
+                        let after = (nl + removed_before).saturating_sub(added_before);
+                        insertions.push((after.saturating_sub(1), after));
+                    }
+                    added_before += 1;
+                }
+            }
+        }
+        for s in symbols {
+            let r = &s.range;
+            let inside = |ln: u32| r.start_line <= ln && ln <= r.end_line;
+            if intersects(r, &lines) || insertions.iter().any(|(b, a)| inside(*b) && inside(*a)) {
+                changed_symbols.push(s.clone());
+            }
+        }
+    }
+    changed_symbols.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    changed_symbols.dedup_by(|a, b| a.id.0 == b.id.0);
+    changed_symbols
+}
+
+fn intersects(range: &TextRange, lines: &BTreeSet<u32>) -> bool {
+    range.start_line <= range.end_line
+        && lines
+            .range(range.start_line..=range.end_line)
+            .next()
+            .is_some()
+}
+
+/// [`FileChanges::changed_new_lines`] per new path, merged across diffs.
+fn added_lines_by_file(diffs: &[FileChanges]) -> HashMap<&str, BTreeSet<u32>> {
+    let mut by_file: HashMap<&str, BTreeSet<u32>> = HashMap::new();
+    for fc in diffs {
+        if let Some(path) = &fc.new_path {
+            by_file
+                .entry(path.as_str())
+                .or_default()
+                .extend(fc.changed_new_lines());
         }
     }
-    false
+    by_file
 }
 
 #[cfg(test)]
@@ -140,6 +726,45 @@ fn bar() {}
         assert!(!out.changed_symbols.iter().any(|s| s.name == "bar"));
     }
 
+    #[test]
+    #[serial]
+    fn repeated_file_sections_report_each_symbol_once() {
+        let dir = tempdir().unwrap();
+        let code =
+            "fn foo() {\n    one();\n    two();\n    three();\n}\n\nfn bar() {\n    keep();\n}\n";
+        fs::write(dir.path().join("main.rs"), code).unwrap();
+
+        // Two hunks inside foo, then the file again with a removal in bar
+        let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -1,0 +2 @@\n+    one();\n@@ -2,0 +4 @@\n+    three();\n\
+diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -6 +8,0 @@\n-    gone();\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+        assert_eq!(parsed.len(), 2);
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        std::env::set_current_dir(cwd).unwrap();
+        attach_changed_lines(&mut out, &parsed);
+
+        assert_eq!(out.changed_files, vec!["main.rs".to_string()]);
+        let names: Vec<&str> = out
+            .changed_symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names.iter().filter(|n| **n == "foo").count(), 1);
+        assert!(
+            names.contains(&"bar"),
+            "removal in bar is mapped: {names:?}"
+        );
+        let foo = out
+            .changed_symbols
+            .iter()
+            .find(|s| s.name == "foo")
+            .unwrap();
+        assert_eq!(out.changed_lines[&foo.id.0], vec![2, 4]);
+    }
+
     #[test]
     #[serial]
     fn changed_symbols_are_sorted_and_unique() {
@@ -274,4 +899,317 @@ diff --git a/b.rs b/b.rs
             "explicit python mode should preserve existing behavior"
         );
     }
+
+    #[test]
+    fn changed_symbols_in_index_uses_old_side_lines() {
+        use crate::ir::reference::SymbolIndex;
+        use crate::ir::{SymbolId, SymbolKind};
+        let sym = |name: &str, start: u32, end: u32| Symbol {
+            id: SymbolId::new("rust", "main.rs", &SymbolKind::Function, name, start),
+            name: name.to_string(),
+            kind: SymbolKind::Function,
+            file: "main.rs".to_string(),
+            range: TextRange {
+                start_line: start,
+                end_line: end,
+            },
+            language: "rust".to_string(),
+        };
+        // base: foo 1-3, bar 5-7, baz 9-11
+        let index = SymbolIndex::build(vec![sym("foo", 1, 3), sym("bar", 5, 7), sym("baz", 9, 11)]);
+        // insert a line inside foo, remove a line from baz, add a new fn between foo and bar
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -2,0 +3 @@\n+    x();\n@@ -3,0 +5,2 @@\n+fn qux() {}\n+\n@@ -10 +11,0 @@\n-    y();\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+        let names: Vec<String> = changed_symbols_in_index(&parsed, &index)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["baz".to_string(), "foo".to_string()]);
+    }
+
+    #[test]
+    #[serial]
+    fn changed_symbols_detect_language_per_file() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn rusty() {\n    a();\n}\n").unwrap();
+        fs::write(
+            dir.path().join("app.ts"),
+            "function typed(): void {\n  b();\n}\n",
+        )
+        .unwrap();
+        let diff = "diff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -2 +2 @@\n-    x();\n+    a();\ndiff --git a/app.ts b/app.ts\n--- a/app.ts\n+++ b/app.ts\n@@ -2 +2 @@\n-  y();\n+  b();\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let auto = compute_changed_symbols(&parsed, LanguageMode::Auto).unwrap();
+        // a forced --lang does not override a recognized extension
+        let forced = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        std::env::set_current_dir(cwd).unwrap();
+
+        for out in [auto, forced] {
+            let found: Vec<(&str, &str)> = out
+                .changed_symbols
+                .iter()
+                .map(|s| (s.language.as_str(), s.name.as_str()))
+                .collect();
+            assert_eq!(found, vec![("rust", "rusty"), ("typescript", "typed")]);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn removed_lines_map_through_pre_image() {
+        let dir = tempdir().unwrap();
+        // old: a() 1-3, b() 4-7 ; one line removed from each body
+        let new_code = "fn a() {\n}\nfn b() {\n    q();\n}\n";
+        fs::write(dir.path().join("main.rs"), new_code).unwrap();
+        let diff =
+            "--- a/main.rs\n+++ b/main.rs\n@@ -2 +1,0 @@\n-    p();\n@@ -6 +4,0 @@\n-    r();\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        assert_eq!(
+            reconstruct_pre_image(new_code, &parsed[0]).as_deref(),
+            Some("fn a() {\n    p();\n}\nfn b() {\n    q();\n    r();\n}\n")
+        );
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        std::env::set_current_dir(cwd).unwrap();
+
+        let names: Vec<&str> = out
+            .changed_symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    #[serial]
+    fn classify_symbol_changes_marks_added_removed_modified() {
+        let dir = tempdir().unwrap();
+        // old: keep() 1-3, gone() 5-7 ; new: keep() modified, fresh() added, gone() removed
+        let new_code = "fn keep() {\n    two();\n}\n\nfn fresh() {\n    three();\n}\n";
+        fs::write(dir.path().join("main.rs"), new_code).unwrap();
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    one();\n+    two();\n@@ -5,3 +5,3 @@\n-fn gone() {\n-    x();\n-}\n+fn fresh() {\n+    three();\n+}\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        classify_symbol_changes(
+            &mut out,
+            &parsed,
+            LanguageMode::Rust,
+            &MappingOptions::default(),
+        );
+        std::env::set_current_dir(cwd).unwrap();
+
+        let kinds: Vec<(String, SymbolChangeKind)> = out
+            .symbol_changes
+            .iter()
+            .map(|c| {
+                let name = out
+                    .changed_symbols
+                    .iter()
+                    .find(|s| s.id == c.id)
+                    .map(|s| s.name.clone())
+                    .unwrap();
+                (name, c.change_kind)
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("fresh".to_string(), SymbolChangeKind::Added),
+                ("keep".to_string(), SymbolChangeKind::Modified),
+                ("gone".to_string(), SymbolChangeKind::Removed),
+            ]
+        );
+        let gone = out
+            .changed_symbols
+            .iter()
+            .find(|s| s.name == "gone")
+            .unwrap();
+        assert_eq!(gone.range.start_line, 5);
+        assert_eq!(gone.range.end_line, 7);
+    }
+
+    #[test]
+    #[serial]
+    fn added_and_deleted_files_classify_by_status() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("new.rs"), "fn added() {}\n").unwrap();
+        let diff = "diff --git a/new.rs b/new.rs\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+fn added() {}\ndiff --git a/gone.rs b/gone.rs\n--- a/gone.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-fn removed() {}\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut out = compute_changed_symbols(&parsed, LanguageMode::Auto).unwrap();
+        classify_symbol_changes(
+            &mut out,
+            &parsed,
+            LanguageMode::Auto,
+            &MappingOptions::default(),
+        );
+        std::env::set_current_dir(cwd).unwrap();
+
+        assert_eq!(out.changed_files, vec!["new.rs", "gone.rs"]);
+        let kinds: Vec<(&str, SymbolChangeKind)> = out
+            .symbol_changes
+            .iter()
+            .map(|c| {
+                let s = out.changed_symbols.iter().find(|s| s.id == c.id).unwrap();
+                (s.name.as_str(), c.change_kind)
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("added", SymbolChangeKind::Added),
+                ("removed", SymbolChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn crlf_sources_and_diffs_map_like_lf() {
+        let dir = tempdir().unwrap();
+        let code = "fn foo() {\n    one();\n    two();\n}\n\nfn bar() {\n    three();\n}\n";
+        let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -2 +2,2 @@\n     one();\n+    two();\n@@ -6,3 +7,3 @@\n fn bar() {\n-    3();\n+    three();\n }\n";
+        let run = |code: &str, diff: &str| {
+            fs::write(dir.path().join("main.rs"), code).unwrap();
+            let parsed = parse_unified_diff(diff).unwrap();
+            let cwd = std::env::current_dir().unwrap();
+            std::env::set_current_dir(dir.path()).unwrap();
+            let mut out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+            classify_symbol_changes(
+                &mut out,
+                &parsed,
+                LanguageMode::Rust,
+                &MappingOptions::default(),
+            );
+            attach_changed_lines(&mut out, &parsed);
+            std::env::set_current_dir(cwd).unwrap();
+            out
+        };
+
+        let lf = run(code, diff);
+        assert_eq!(lf.changed_symbols.len(), 2);
+        // what `git diff` prints for a CRLF file: LF framing, CR kept in content
+        let crlf_diff: String = diff
+            .split_inclusive('\n')
+            .map(|l| {
+                if l.starts_with([' ', '+', '-']) && !l.starts_with("---") && !l.starts_with("+++")
+                {
+                    l.replace('\n', "\r\n")
+                } else {
+                    l.to_string()
+                }
+            })
+            .collect();
+        let crlf_code = code.replace('\n', "\r\n");
+        assert_eq!(run(&crlf_code, &crlf_diff), lf);
+        // the whole patch converted to CRLF as well
+        assert_eq!(run(&crlf_code, &crlf_diff.replace('\n', "\r\n")), lf);
+        // a file mixing both endings
+        let mixed_code = code.replacen('\n', "\r\n", 3);
+        assert_eq!(run(&mixed_code, diff), lf);
+    }
+
+    #[test]
+    #[serial]
+    fn classify_change_scopes_separates_signature_from_body() {
+        let dir = tempdir().unwrap();
+        // body(): body edit; renamed_arg(): parameter renamed on its own line
+        // (removed + added in the signature); widened(): new parameter line
+        let new_code = "fn body() {\n    two();\n}\n\nfn renamed_arg(\n    y: u32,\n) {\n    y;\n}\n\nfn widened(\n    a: u32,\n    b: u32,\n) {}\n";
+        fs::write(dir.path().join("main.rs"), new_code).unwrap();
+        let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    one();\n+    two();\n@@ -6 +6 @@\n-    x: u32,\n+    y: u32,\n@@ -12,0 +13 @@\n+    b: u32,\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        classify_change_scopes(
+            &mut out,
+            &parsed,
+            LanguageMode::Rust,
+            &MappingOptions::default(),
+        );
+        std::env::set_current_dir(cwd).unwrap();
+
+        let scopes: Vec<(&str, ChangeScope)> = out
+            .changed_symbols
+            .iter()
+            .map(|s| (s.name.as_str(), out.change_scopes[&s.id.0]))
+            .collect();
+        assert_eq!(
+            scopes,
+            vec![
+                ("body", ChangeScope::Body),
+                ("renamed_arg", ChangeScope::Signature),
+                ("widened", ChangeScope::Signature),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn removed_signature_line_counts_as_signature_change() {
+        let dir = tempdir().unwrap();
+        // a parameter line is deleted; no added line touches `f`
+        let new_code = "fn f(\n    a: u32,\n) {}\n";
+        fs::write(dir.path().join("main.rs"), new_code).unwrap();
+        let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -3 +2,0 @@\n-    b: u32,\n";
+        let parsed = parse_unified_diff(diff).unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let mut out = compute_changed_symbols(&parsed, LanguageMode::Rust).unwrap();
+        classify_change_scopes(
+            &mut out,
+            &parsed,
+            LanguageMode::Rust,
+            &MappingOptions::default(),
+        );
+        std::env::set_current_dir(cwd).unwrap();
+
+        let f = out.changed_symbols.iter().find(|s| s.name == "f").unwrap();
+        assert_eq!(out.change_scopes[&f.id.0], ChangeScope::Signature);
+    }
+
+    #[test]
+    fn signature_span_ends_at_the_line_opening_the_body() {
+        let sym = |language: &str, start_line, end_line| Symbol {
+            id: SymbolId("x".into()),
+            name: "f".into(),
+            kind: SymbolKind::Function,
+            file: "x".into(),
+            range: TextRange {
+                start_line,
+                end_line,
+            },
+            language: language.into(),
+        };
+        let rust = "// c\nfn f(\n    a: u32,\n) -> u32\n{\n    a\n}\n";
+        let span = signature_span(&sym("rust", 2, 7), rust);
+        assert_eq!((span.start_line, span.end_line), (2, 5));
+
+        let py = "def f(a,\n      b):\n    return a\n";
+        let span = signature_span(&sym("python", 1, 3), py);
+        assert_eq!((span.start_line, span.end_line), (1, 2));
+
+        let rb = "def f(a)\n  a\nend\n";
+        let span = signature_span(&sym("ruby", 1, 3), rb);
+        assert_eq!((span.start_line, span.end_line), (1, 1));
+
+        // no body: the whole declaration is signature
+        let decl = "fn f(a: u32);\n";
+        let span = signature_span(&sym("rust", 1, 1), decl);
+        assert_eq!((span.start_line, span.end_line), (1, 1));
+    }
 }
@@ -0,0 +1,120 @@
+//! Per-phase wall-clock timings behind `--profile`.
+//!
+//! Each phase accumulates the time spent in it across the whole run, so a
+//! phase entered several times (e.g. cache updates analyzing files in two
+//! passes) reports its sum. Timing is off by default and costs one relaxed
+//! atomic load per phase when off.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NANOS: [AtomicU64; Phase::ALL.len()] = [const { AtomicU64::new(0) }; Phase::ALL.len()];
+
+/// A timed stage of a run, in the order they are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Listing the files to analyze.
+    Enumerate,
+    /// Reading and parsing files into symbols and unresolved references.
+    Analyze,
+    /// Resolving references into edges.
+    Resolve,
+    /// Impact traversal over the graph.
+    Impact,
+    /// Formatting and writing the report.
+    Render,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Phase::Enumerate,
+        Phase::Analyze,
+        Phase::Resolve,
+        Phase::Impact,
+        Phase::Render,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Enumerate => "enumerate",
+            Phase::Analyze => "analyze",
+            Phase::Resolve => "resolve",
+            Phase::Impact => "impact",
+            Phase::Render => "render",
+        }
+    }
+}
+
+/// Start collecting phase timings.
+pub fn set_enabled(on: bool) {
+    ENABLED.store(on, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Adds the time until it is dropped to its phase; inert when profiling is off.
+#[must_use = "the phase is timed until the timer is dropped"]
+pub struct PhaseTimer(Option<(Phase, Instant)>);
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if let Some((phase, started)) = self.0 {
+            record(phase, started.elapsed());
+        }
+    }
+}
+
+pub fn start(phase: Phase) -> PhaseTimer {
+    PhaseTimer(enabled().then(|| (phase, Instant::now())))
+}
+
+pub fn record(phase: Phase, elapsed: Duration) {
+    let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+    NANOS[phase as usize].fetch_add(nanos, Ordering::Relaxed);
+}
+
+/// Time accumulated so far in `phase`.
+pub fn elapsed(phase: Phase) -> Duration {
+    Duration::from_nanos(NANOS[phase as usize].load(Ordering::Relaxed))
+}
+
+/// One `<phase>_ms=<millis>` line per phase, in [`Phase::ALL`] order and
+/// including phases that never ran, then `total_ms`.
+pub fn report(total: Duration) -> String {
+    let mut out = String::new();
+    for phase in Phase::ALL {
+        out.push_str(&format_line(phase.name(), elapsed(phase)));
+    }
+    out.push_str(&format_line("total", total));
+    out
+}
+
+fn format_line(name: &str, d: Duration) -> String {
+    format!("{}_ms={:.3}\n", name, d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_every_phase_in_order() {
+        let text = report(Duration::from_micros(1500));
+        let keys: Vec<&str> = text.lines().map(|l| l.split_once('=').unwrap().0).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "enumerate_ms",
+                "analyze_ms",
+                "resolve_ms",
+                "impact_ms",
+                "render_ms",
+                "total_ms"
+            ]
+        );
+        assert!(text.ends_with("total_ms=1.500\n"));
+    }
+}
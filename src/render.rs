@@ -1,26 +1,12 @@
 use crate::dfg::{DataFlowGraph, DependencyKind};
 use crate::impact::ImpactOutput;
-use crate::ir::SymbolKind;
+use crate::ir::reference::Reference;
+use crate::ir::{SymbolId, parse_symbol_id};
 
 fn esc_dot(s: &str) -> String {
     s.replace('"', "\\\"").replace('\n', " ")
 }
 
-fn parse_symbol_id(id: &str) -> Option<(String, String, String, String, u32)> {
-    // lang:file:kind:name:line
-    let parts: Vec<&str> = id.split(':').collect();
-    if parts.len() < 5 {
-        return None;
-    }
-    let lang = parts[0].to_string();
-    let kind = parts[2].to_string();
-    let name = parts[3].to_string();
-    let line: u32 = parts[4].parse().ok()?;
-    // Rejoin file (in case it contained colons in rare environments)
-    let file = parts[1].to_string();
-    Some((lang, file, kind, name, line))
-}
-
 /// Convert a DataFlowGraph (PDG) to GraphViz dot format.
 pub fn dfg_to_dot(graph: &DataFlowGraph) -> String {
     use std::fmt::Write as _;
@@ -145,9 +131,12 @@ mod impact_render_tests {
             impacted_symbols: vec![b.clone(), c.clone()],
             impacted_files: vec!["f.rs".into()],
             edges: edges.clone(),
+            edges_included: true,
             impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
             impacted_witnesses: std::collections::HashMap::new(),
             summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
         };
         let dot = to_dot(&out);
         assert!(
@@ -156,6 +145,218 @@ mod impact_render_tests {
         );
     }
 
+    #[test]
+    fn to_github_annotations_emits_workflow_commands() {
+        let changed = mk_sym("src/a,b.rs", "foo", 10);
+        let impacted = mk_sym("src/lib.rs", "bar", 3);
+        let out = ImpactOutput {
+            changed_symbols: vec![changed],
+            impacted_symbols: vec![impacted],
+            impacted_files: vec!["src/lib.rs".into()],
+            edges: vec![],
+            edges_included: false,
+            impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
+            impacted_witnesses: std::collections::HashMap::new(),
+            summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        let text = to_github_annotations(&out);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "::warning file=src/a%2Cb.rs,line=10::foo changed",
+                "::notice file=src/lib.rs,line=3::bar impacted by change",
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_to_text_groups_symbols_by_file() {
+        let a = mk_sym("src/a.rs", "a", 7);
+        let b = mk_sym("src/a.rs", "b", 2);
+        let out = crate::mapping::ChangedOutput {
+            changed_files: vec!["src/a.rs".into(), "README.md".into()],
+            changed_symbols: vec![a.clone(), b.clone()],
+            symbol_changes: vec![crate::mapping::SymbolChange {
+                id: a.id.clone(),
+                change_kind: crate::mapping::SymbolChangeKind::Added,
+            }],
+            snippets: std::collections::HashMap::new(),
+            changed_lines: [(b.id.0.clone(), vec![2, 3])].into_iter().collect(),
+            total_changed: None,
+            change_scopes: std::collections::HashMap::new(),
+        };
+        assert_eq!(
+            changed_to_text(&out, false),
+            "README.md\n  (no symbols)\nsrc/a.rs\n  fn b  :2  lines 2,3\n  + fn a  :7\n2 changed symbol(s) in 2 file(s)\n"
+        );
+        assert!(changed_to_text(&out, true).contains(ANSI_GREEN));
+    }
+
+    #[test]
+    fn to_text_groups_impact_under_changed_roots() {
+        let a = mk_sym("src/a.rs", "a", 1);
+        let b = mk_sym("src/b.rs", "b", 4);
+        let c = mk_sym("src/c.rs", "c", 9);
+        let witness = |sym: &Symbol, depth: usize| crate::impact::ImpactWitness {
+            symbol_id: sym.id.0.clone(),
+            depth,
+            root_symbol_id: a.id.0.clone(),
+            via_symbol_id: a.id.0.clone(),
+            edge: Reference {
+                from: sym.id.clone(),
+                to: a.id.clone(),
+                kind: RefKind::Call,
+                file: sym.file.clone(),
+                line: sym.range.start_line,
+                certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+                provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+            },
+            path: vec![],
+            provenance_chain: vec![],
+            kind_chain: vec![],
+            path_compact: vec![],
+            provenance_chain_compact: vec![],
+            kind_chain_compact: vec![],
+            bridge_execution_family: None,
+            bridge_execution_chain_compact: vec![],
+            winning_bridge_execution_chain_compact: vec![],
+            observed_supporting_steps_compact: vec![],
+            slice_context: None,
+        };
+        let out = ImpactOutput {
+            changed_symbols: vec![a.clone()],
+            impacted_symbols: vec![b.clone(), c.clone()],
+            impacted_files: vec!["src/b.rs".into(), "src/c.rs".into()],
+            edges: vec![],
+            edges_included: false,
+            impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
+            impacted_witnesses: [(b.id.0.clone(), witness(&b, 1))].into_iter().collect(),
+            summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        let text = to_text(&out, false);
+        assert_eq!(
+            text,
+            "fn a  src/a.rs:1\n  depth 1\n    fn b  src/b.rs:4\nimpacted (no witness)\n  src/c.rs\n    fn c  :9\n1 changed, 2 impacted in 2 file(s)\n"
+        );
+        assert!(to_text(&out, true).contains(ANSI_RESET));
+    }
+
+    #[test]
+    fn cycles_to_dot_clusters_cycle_members() {
+        let a = mk_sym("f.rs", "a", 1);
+        let b = mk_sym("f.rs", "b", 2);
+        let c = mk_sym("f.rs", "c", 3);
+        let call = |from: &Symbol, to: &Symbol| Reference {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            kind: RefKind::Call,
+            file: "f.rs".into(),
+            line: 1,
+            certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+            provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+        };
+        let refs = vec![call(&a, &b), call(&b, &a), call(&b, &c)];
+        let cycles = vec![vec![a.id.clone(), b.id.clone()]];
+        let dot = cycles_to_dot(&cycles, &refs);
+        assert!(dot.contains("subgraph cluster_0"));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", a.id.0, b.id.0)));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", b.id.0, a.id.0)));
+        assert!(!dot.contains(&c.id.0));
+    }
+
+    #[test]
+    fn to_dot_labels_decode_colon_paths() {
+        let a = mk_sym(r"C:\src\a.rs", "a", 1);
+        let b = mk_sym(r"C:\src\b.rs", "b", 2);
+        let out = ImpactOutput {
+            changed_symbols: vec![a.clone()],
+            impacted_symbols: vec![],
+            impacted_files: vec![],
+            edges: vec![Reference {
+                from: b.id.clone(),
+                to: a.id.clone(),
+                kind: RefKind::Call,
+                file: b.file.clone(),
+                line: 2,
+                certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+                provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+            }],
+            edges_included: true,
+            impacted_by_file: Default::default(),
+            changed_by_file: Default::default(),
+            impacted_witnesses: Default::default(),
+            summary: Default::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        let dot = to_dot(&out);
+        assert!(dot.contains(r#"label="b\nC:\src\b.rs:2""#), "{dot}");
+    }
+
+    #[test]
+    fn to_graphml_emits_directed_graph_with_node_attributes() {
+        let a = mk_sym("src/a&b.rs", "a", 1);
+        let b = mk_sym("src/b.rs", "b", 2);
+        let ctx = mk_sym("src/c.rs", "ctx", 7);
+        let out = ImpactOutput {
+            changed_symbols: vec![a.clone()],
+            impacted_symbols: vec![b.clone()],
+            impacted_files: vec!["src/b.rs".into()],
+            edges: vec![
+                Reference {
+                    from: b.id.clone(),
+                    to: a.id.clone(),
+                    kind: RefKind::Call,
+                    file: b.file.clone(),
+                    line: 2,
+                    certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+                    provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+                },
+                Reference {
+                    from: ctx.id.clone(),
+                    to: b.id.clone(),
+                    kind: RefKind::Data,
+                    file: ctx.file.clone(),
+                    line: 8,
+                    certainty: crate::ir::reference::EdgeCertainty::Inferred,
+                    provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+                },
+            ],
+            edges_included: true,
+            impacted_by_file: Default::default(),
+            changed_by_file: Default::default(),
+            impacted_witnesses: Default::default(),
+            summary: Default::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        let xml = to_graphml(&out);
+        assert!(xml.starts_with("<?xml"), "{xml}");
+        assert!(xml.contains("edgedefault=\"directed\""), "{xml}");
+        assert!(
+            xml.contains("<data key=\"file\">src/a&amp;b.rs</data>"),
+            "{xml}"
+        );
+        assert_eq!(xml.matches("<node ").count(), 3, "{xml}");
+        assert_eq!(xml.matches("<data key=\"changed\">true</data>").count(), 1);
+        assert!(xml.contains("<data key=\"name\">ctx</data>"), "{xml}");
+        assert!(xml.contains("<data key=\"line\">7</data>"), "{xml}");
+        let edge = format!(
+            "source=\"{}\" target=\"{}\"",
+            esc_xml(&b.id.0),
+            esc_xml(&a.id.0)
+        );
+        assert!(xml.contains(&edge), "{xml}");
+        assert!(
+            xml.contains("<data key=\"certainty\">inferred</data>"),
+            "{xml}"
+        );
+        assert!(xml.trim_end().ends_with("</graphml>"));
+    }
+
     #[test]
     fn to_html_embeds_assets() {
         let changed = mk_sym("src/lib.rs", "foo", 10);
@@ -164,9 +365,12 @@ mod impact_render_tests {
             impacted_symbols: vec![],
             impacted_files: vec!["src/lib.rs".into()],
             edges: vec![],
+            edges_included: false,
             impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
             impacted_witnesses: std::collections::HashMap::new(),
             summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
         };
         let html = super::to_html(&out);
         assert!(html.contains("<!doctype html>"));
@@ -176,6 +380,227 @@ mod impact_render_tests {
         );
         assert!(html.contains("class=\"symbol-select\""));
         assert!(html.contains("symbols-select-all"));
+        assert!(html.contains("function bindSymbolPages()"));
+    }
+
+    #[test]
+    fn to_html_groups_impacted_symbols_by_file() {
+        let changed = mk_sym("src/lib.rs", "foo", 10);
+        let late = mk_sym("src/b.rs", "late", 9);
+        let early = mk_sym("src/b.rs", "early", 2);
+        let other = mk_sym("src/a<x>.rs", "other", 1);
+        let out = ImpactOutput {
+            changed_symbols: vec![changed],
+            impacted_symbols: vec![late.clone(), early.clone(), other.clone()],
+            impacted_files: vec!["src/a<x>.rs".into(), "src/b.rs".into()],
+            edges: vec![],
+            edges_included: false,
+            impacted_by_file: std::collections::HashMap::from([
+                ("src/b.rs".to_string(), vec![late, early]),
+                ("src/a<x>.rs".to_string(), vec![other]),
+            ]),
+            changed_by_file: std::collections::HashMap::new(),
+            impacted_witnesses: std::collections::HashMap::new(),
+            summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        let html = super::to_html(&out);
+        assert!(html.contains("id=\"file-tree\""), "{html}");
+        assert_eq!(html.matches("class=\"file-group collapsed\"").count(), 2);
+        let a = html.find("<code>src/a&lt;x&gt;.rs</code>").unwrap();
+        let b = html
+            .find("<code>src/b.rs</code> <span class=\"badge\">2</span>")
+            .unwrap();
+        assert!(a < b);
+        assert!(html.find("early (line 2)").unwrap() < html.find("late (line 9)").unwrap());
+        assert!(html.contains("function bindFileTree()"));
+
+        let empty = ImpactOutput {
+            impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
+            ..out
+        };
+        assert!(!super::to_html(&empty).contains("id=\"file-tree\""));
+    }
+
+    #[test]
+    fn to_html_with_options_dark_and_external_assets() {
+        let changed = mk_sym("src/lib.rs", "foo", 10);
+        let out = ImpactOutput {
+            changed_symbols: vec![changed.clone()],
+            impacted_symbols: vec![],
+            impacted_files: vec!["src/lib.rs".into()],
+            edges: vec![],
+            edges_included: false,
+            impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
+            impacted_witnesses: std::collections::HashMap::new(),
+            summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
+        };
+        assert_eq!(
+            super::to_html(&out),
+            super::to_html_with_options(&out, &super::HtmlOptions::default())
+        );
+        assert!(!super::to_html(&out).contains("<body class=\"dark\">"));
+
+        let html = super::to_html_with_options(
+            &out,
+            &super::HtmlOptions {
+                dark_mode: true,
+                inline_assets: false,
+            },
+        );
+        assert!(html.contains("<body class=\"dark\">"));
+        assert!(html.contains("<link rel=\"stylesheet\" href=\"report.css\">"));
+        assert!(html.contains("<script src=\"report_main.js\"></script>"));
+        assert!(html.contains("const WORKER_SRC = null;"));
+        assert!(html.contains("const IMPACT_DATA = null;"));
+        assert!(!html.contains("<style>"));
+        let names: Vec<&str> = super::HTML_SIDECAR_ASSETS.iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, ["report.css", "report_main.js", "impact_worker.js"]);
+
+        // The sidecar data is what the inline page embeds
+        let data = super::to_html_data(&out);
+        assert!(data.contains("\"label\":\"foo\""), "{data}");
+        assert!(super::to_html(&out).contains(&format!("const IMPACT_DATA = {data};")));
+    }
+
+    fn impacted_report(n: usize) -> ImpactOutput {
+        ImpactOutput {
+            changed_symbols: vec![mk_sym("src/lib.rs", "root", 1)],
+            impacted_symbols: (0..n)
+                .map(|i| mk_sym("src/lib.rs", &format!("f{i}"), i as u32 + 10))
+                .collect(),
+            impacted_files: vec!["src/lib.rs".into()],
+            edges: vec![],
+            edges_included: false,
+            impacted_by_file: std::collections::HashMap::new(),
+            changed_by_file: std::collections::HashMap::new(),
+            impacted_witnesses: std::collections::HashMap::new(),
+            summary: crate::impact::ImpactSummary::default(),
+            snippets: std::collections::HashMap::new(),
+        }
+    }
+
+    /// The rows of the impacted symbol list in `html`.
+    fn impacted_rows(html: &str) -> Vec<&str> {
+        let start = html.find("id=\"symbol-impacted\">").unwrap();
+        let end = start + html[start..].find("</ul>").unwrap();
+        html[start..end]
+            .lines()
+            .map(str::trim_start)
+            .filter(|l| l.starts_with("<li"))
+            .collect()
+    }
+
+    #[test]
+    fn symbol_lists_split_into_pages_at_page_boundaries() {
+        let page = super::HTML_SYMBOL_PAGE;
+        for (n, pages, more) in [
+            (1, 1, None),
+            (page, 1, None),
+            (page + 1, 2, Some("show 1 more (1 hidden)")),
+            (2 * page, 2, Some("show 200 more (200 hidden)")),
+            (2 * page + 1, 3, Some("show 200 more (201 hidden)")),
+        ] {
+            let html = super::to_html(&impacted_report(n));
+            let rows = impacted_rows(&html);
+            let symbols: Vec<&str> = rows
+                .iter()
+                .copied()
+                .filter(|r| r.contains("symbol-select"))
+                .collect();
+            assert_eq!(symbols.len(), n);
+            for (i, row) in symbols.iter().enumerate() {
+                let p = i / page;
+                if p == 0 {
+                    assert!(row.starts_with("<li><label>"), "row {i} of {n}: {row}");
+                } else {
+                    let open = format!("<li data-page=\"{p}\" hidden>");
+                    assert!(row.starts_with(&open), "row {i} of {n}: {row}");
+                }
+            }
+            assert!(!html.contains(&format!("data-page=\"{pages}\"")), "{n}");
+            let button = rows.iter().find(|r| r.contains("class=\"more\""));
+            match more {
+                None => assert!(button.is_none(), "{n}"),
+                Some(label) => assert!(button.unwrap().contains(label), "{n}"),
+            }
+        }
+        // An empty list is a single placeholder row
+        let html = super::to_html(&impacted_report(0));
+        assert_eq!(impacted_rows(&html), ["<li><em>none</em></li>"]);
+    }
+
+    #[test]
+    fn html_data_sidecar_holds_every_node_and_edge() {
+        let foo = mk_sym("src/lib.rs", "foo", 10);
+        let bar = mk_sym("src/main.rs", "bar", 3);
+        let outside = SymbolId::new("rust", "src/util.rs", &SymbolKind::Function, "helper", 7);
+        let call = |from: &SymbolId, to: &SymbolId| Reference {
+            from: from.clone(),
+            to: to.clone(),
+            kind: crate::ir::reference::RefKind::Call,
+            file: "src/main.rs".into(),
+            line: 4,
+            certainty: crate::ir::reference::EdgeCertainty::Confirmed,
+            provenance: crate::ir::reference::EdgeProvenance::CallGraph,
+        };
+        let out = ImpactOutput {
+            edges: vec![call(&bar.id, &foo.id), call(&bar.id, &outside)],
+            edges_included: true,
+            changed_symbols: vec![foo.clone()],
+            impacted_symbols: vec![bar.clone()],
+            ..impacted_report(0)
+        };
+
+        let data: serde_json::Value = serde_json::from_str(&super::to_html_data(&out)).unwrap();
+        let nodes: Vec<(&str, bool)> = data["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| {
+                (
+                    n["data"]["label"].as_str().unwrap(),
+                    n["data"]["changed"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(nodes, [("foo", true), ("bar", false), ("helper", false)]);
+        let helper = &data["nodes"][2]["data"];
+        assert_eq!(helper["file"], "src/util.rs");
+        assert_eq!(helper["line"], 7);
+        let edges: Vec<(&str, &str)> = data["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["data"]["source"].as_str().unwrap(),
+                    e["data"]["target"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (bar.id.0.as_str(), foo.id.0.as_str()),
+                (bar.id.0.as_str(), outside.0.as_str())
+            ]
+        );
+        assert_eq!(data["edges"][0]["data"]["kind"], "call");
+
+        // The external-assets page holds no data; the inline one embeds the sidecar's
+        let external = super::to_html_with_options(
+            &out,
+            &super::HtmlOptions {
+                dark_mode: false,
+                inline_assets: false,
+            },
+        );
+        assert!(!external.contains("\"nodes\""));
+        assert!(super::to_html(&out).contains(&super::to_html_data(&out)));
     }
 }
 
@@ -276,12 +701,11 @@ pub fn to_dot(out: &ImpactOutput) -> String {
             if seen.contains(id) {
                 continue;
             }
-            let (label, file, line) =
-                if let Some((_lang, file, _kind, name, line)) = parse_symbol_id(id) {
-                    (esc_dot(&name).to_string(), esc_dot(&file), line)
-                } else {
-                    (esc_dot(id), String::new(), 0)
-                };
+            let (label, file, line) = if let Some(p) = parse_symbol_id(id) {
+                (esc_dot(&p.name).to_string(), esc_dot(&p.file), p.line)
+            } else {
+                (esc_dot(id), String::new(), 0)
+            };
             let _ = writeln!(
                 buf,
                 "  \"{}\" [label=\"{}\\n{}:{}\", style=filled, fillcolor=\"#eee\"];",
@@ -315,13 +739,565 @@ pub fn to_dot(out: &ImpactOutput) -> String {
     buf
 }
 
+fn esc_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Render the impact graph as GraphML (directed), for yEd, Gephi, networkx and friends.
+/// Nodes carry name/file/line/kind/changed; edges keep their from -> to direction.
+pub fn to_graphml(out: &ImpactOutput) -> String {
+    use std::fmt::Write as _;
+    let mut buf = String::new();
+    buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    buf.push_str(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns \
+         http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+    for (id, target, name, ty) in [
+        ("name", "node", "name", "string"),
+        ("file", "node", "file", "string"),
+        ("line", "node", "line", "int"),
+        ("kind", "node", "kind", "string"),
+        ("changed", "node", "changed", "boolean"),
+        ("ekind", "edge", "kind", "string"),
+        ("eline", "edge", "line", "int"),
+        ("certainty", "edge", "certainty", "string"),
+    ] {
+        let _ = writeln!(
+            buf,
+            "  <key id=\"{id}\" for=\"{target}\" attr.name=\"{name}\" attr.type=\"{ty}\"/>"
+        );
+    }
+    buf.push_str("  <graph id=\"impact\" edgedefault=\"directed\">\n");
+
+    let push_node = |buf: &mut String,
+                     id: &str,
+                     name: &str,
+                     file: &str,
+                     line: u32,
+                     kind: &str,
+                     changed: bool| {
+        let _ = writeln!(buf, "    <node id=\"{}\">", esc_xml(id));
+        let _ = writeln!(buf, "      <data key=\"name\">{}</data>", esc_xml(name));
+        let _ = writeln!(buf, "      <data key=\"file\">{}</data>", esc_xml(file));
+        let _ = writeln!(buf, "      <data key=\"line\">{line}</data>");
+        let _ = writeln!(buf, "      <data key=\"kind\">{}</data>", esc_xml(kind));
+        let _ = writeln!(buf, "      <data key=\"changed\">{changed}</data>");
+        buf.push_str("    </node>\n");
+    };
+
+    // Collect nodes (changed + impacted + edge context), de-dup by id
+    let mut seen = std::collections::BTreeSet::new();
+    for s in &out.changed_symbols {
+        if seen.insert(s.id.0.clone()) {
+            push_node(
+                &mut buf,
+                &s.id.0,
+                &s.name,
+                &s.file,
+                s.range.start_line,
+                s.kind.code(),
+                true,
+            );
+        }
+    }
+    for s in &out.impacted_symbols {
+        if seen.insert(s.id.0.clone()) {
+            push_node(
+                &mut buf,
+                &s.id.0,
+                &s.name,
+                &s.file,
+                s.range.start_line,
+                s.kind.code(),
+                false,
+            );
+        }
+    }
+    for e in &out.edges {
+        for id in [e.from.0.as_str(), e.to.0.as_str()] {
+            if !seen.insert(id.to_string()) {
+                continue;
+            }
+            if let Some(p) = parse_symbol_id(id) {
+                push_node(&mut buf, id, &p.name, &p.file, p.line, &p.kind, false);
+            } else {
+                push_node(&mut buf, id, id, "", 0, "", false);
+            }
+        }
+    }
+
+    for (i, e) in out.edges.iter().enumerate() {
+        let certainty = match e.certainty {
+            crate::ir::reference::EdgeCertainty::Confirmed => "confirmed",
+            crate::ir::reference::EdgeCertainty::Inferred => "inferred",
+            crate::ir::reference::EdgeCertainty::DynamicFallback => "dynamic_fallback",
+        };
+        let _ = writeln!(
+            buf,
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">",
+            esc_xml(&e.from.0),
+            esc_xml(&e.to.0)
+        );
+        let _ = writeln!(buf, "      <data key=\"ekind\">{}</data>", e.kind.as_str());
+        let _ = writeln!(buf, "      <data key=\"eline\">{}</data>", e.line);
+        let _ = writeln!(buf, "      <data key=\"certainty\">{certainty}</data>");
+        buf.push_str("    </edge>\n");
+    }
+    buf.push_str("  </graph>\n</graphml>\n");
+    buf
+}
+
+/// Render detected call cycles (see `find_cycles`) to GraphViz dot format.
+/// Each cycle becomes a cluster; only edges between members of the same cycle are drawn.
+pub fn cycles_to_dot(cycles: &[Vec<SymbolId>], refs: &[Reference]) -> String {
+    use std::fmt::Write as _;
+    let mut buf = String::new();
+    buf.push_str("digraph cycles {\n");
+    buf.push_str("  rankdir=LR;\n  node [shape=box, fontname=\"monospace\"];\n");
+    let mut member_of: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for (i, cycle) in cycles.iter().enumerate() {
+        let _ = writeln!(buf, "  subgraph cluster_{} {{", i);
+        let _ = writeln!(buf, "    label=\"cycle {}\";", i + 1);
+        for id in cycle {
+            member_of.insert(id.0.as_str(), i);
+            let (label, file, line) = if let Some(p) = parse_symbol_id(&id.0) {
+                (esc_dot(&p.name), esc_dot(&p.file), p.line)
+            } else {
+                (esc_dot(&id.0), String::new(), 0)
+            };
+            let _ = writeln!(
+                buf,
+                "    \"{}\" [label=\"{}\\n{}:{}\"];",
+                esc_dot(&id.0),
+                label,
+                file,
+                line
+            );
+        }
+        buf.push_str("  }\n");
+    }
+    let mut edges = std::collections::BTreeSet::new();
+    for e in refs {
+        match (
+            member_of.get(e.from.0.as_str()),
+            member_of.get(e.to.0.as_str()),
+        ) {
+            (Some(a), Some(b)) if a == b => {
+                edges.insert((e.from.0.as_str(), e.to.0.as_str()));
+            }
+            _ => {}
+        }
+    }
+    for (from, to) in edges {
+        let _ = writeln!(buf, "  \"{}\" -> \"{}\";", esc_dot(from), esc_dot(to));
+    }
+    buf.push_str("}\n");
+    buf
+}
+
+/// Render the whole project graph (see `export_project_graph`) to GraphViz
+/// dot format, one node per symbol and one arrow per reference.
+pub fn project_graph_to_dot(graph: &crate::impact::ProjectGraph) -> String {
+    use std::fmt::Write as _;
+    let mut buf = String::new();
+    buf.push_str("digraph project {\n");
+    buf.push_str("  rankdir=LR;\n  node [shape=box, fontname=\"monospace\"];\n");
+    for s in &graph.nodes {
+        let _ = writeln!(
+            buf,
+            "  \"{}\" [label=\"{}\\n{}:{}\"];",
+            esc_dot(&s.id.0),
+            esc_dot(&s.name),
+            esc_dot(&s.file),
+            s.range.start_line
+        );
+    }
+    for e in &graph.edges {
+        let _ = writeln!(
+            buf,
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            esc_dot(&e.from.0),
+            esc_dot(&e.to.0),
+            e.kind.as_str()
+        );
+    }
+    buf.push_str("}\n");
+    buf
+}
+
+/// Render the whole project graph as directed GraphML, with the same node and
+/// edge attributes as [`to_graphml`] minus `changed`.
+pub fn project_graph_to_graphml(graph: &crate::impact::ProjectGraph) -> String {
+    use std::fmt::Write as _;
+    let mut buf = String::new();
+    buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    buf.push_str(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns \
+         http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+    for (id, target, name, ty) in [
+        ("name", "node", "name", "string"),
+        ("file", "node", "file", "string"),
+        ("line", "node", "line", "int"),
+        ("kind", "node", "kind", "string"),
+        ("ekind", "edge", "kind", "string"),
+        ("eline", "edge", "line", "int"),
+        ("certainty", "edge", "certainty", "string"),
+    ] {
+        let _ = writeln!(
+            buf,
+            "  <key id=\"{id}\" for=\"{target}\" attr.name=\"{name}\" attr.type=\"{ty}\"/>"
+        );
+    }
+    buf.push_str("  <graph id=\"project\" edgedefault=\"directed\">\n");
+    for s in &graph.nodes {
+        let _ = writeln!(buf, "    <node id=\"{}\">", esc_xml(&s.id.0));
+        let _ = writeln!(buf, "      <data key=\"name\">{}</data>", esc_xml(&s.name));
+        let _ = writeln!(buf, "      <data key=\"file\">{}</data>", esc_xml(&s.file));
+        let _ = writeln!(
+            buf,
+            "      <data key=\"line\">{}</data>",
+            s.range.start_line
+        );
+        let _ = writeln!(buf, "      <data key=\"kind\">{}</data>", s.kind.code());
+        buf.push_str("    </node>\n");
+    }
+    for (i, e) in graph.edges.iter().enumerate() {
+        let certainty = match e.certainty {
+            crate::ir::reference::EdgeCertainty::Confirmed => "confirmed",
+            crate::ir::reference::EdgeCertainty::Inferred => "inferred",
+            crate::ir::reference::EdgeCertainty::DynamicFallback => "dynamic_fallback",
+        };
+        let _ = writeln!(
+            buf,
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">",
+            esc_xml(&e.from.0),
+            esc_xml(&e.to.0)
+        );
+        let _ = writeln!(buf, "      <data key=\"ekind\">{}</data>", e.kind.as_str());
+        let _ = writeln!(buf, "      <data key=\"eline\">{}</data>", e.line);
+        let _ = writeln!(buf, "      <data key=\"certainty\">{certainty}</data>");
+        buf.push_str("    </edge>\n");
+    }
+    buf.push_str("  </graph>\n</graphml>\n");
+    buf
+}
+
+/// Rendering options for the HTML report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// Render with the dark color scheme (`<body class="dark">`).
+    pub dark_mode: bool,
+    /// Embed CSS/JS and the graph data into the page. When false, the page
+    /// references the sidecar files listed in [`HTML_SIDECAR_ASSETS`] and
+    /// fetches the graph from [`HTML_DATA_FILE`] (see [`to_html_data`]), which
+    /// keeps reports with thousands of nodes quick to open.
+    pub inline_assets: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            dark_mode: false,
+            inline_assets: true,
+        }
+    }
+}
+
+/// Sidecar asset files (file name, contents) referenced by an HTML report
+/// rendered with `inline_assets: false`.
+pub const HTML_SIDECAR_ASSETS: [(&str, &str); 3] = [
+    ("report.css", html::STYLE),
+    ("report_main.js", html::SCRIPT_MAIN),
+    ("impact_worker.js", html::SCRIPT_WORKER),
+];
+
+/// Rows per page of the HTML report's changed and impacted symbol lists;
+/// longer lists show their first page and reveal the rest a page at a time.
+pub const HTML_SYMBOL_PAGE: usize = 200;
+
+/// Sidecar file holding the graph data of an HTML report rendered with
+/// `inline_assets: false`.
+pub const HTML_DATA_FILE: &str = "report_data.json";
+
+/// The graph data (Cytoscape nodes and edges) an HTML report shows, as
+/// written to [`HTML_DATA_FILE`].
+pub fn to_html_data(out: &ImpactOutput) -> String {
+    html::impact_data_json(out)
+}
+
+fn gha_escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn gha_escape_property(s: &str) -> String {
+    gha_escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Render GitHub Actions workflow commands: a `::warning` per changed symbol
+/// and a `::notice` per impacted symbol, anchored at the symbol's start line.
+pub fn to_github_annotations(out: &ImpactOutput) -> String {
+    use std::fmt::Write as _;
+    let mut buf = String::new();
+    for s in &out.changed_symbols {
+        let _ = writeln!(
+            buf,
+            "::warning file={},line={}::{}",
+            gha_escape_property(&s.file),
+            s.range.start_line,
+            gha_escape_data(&format!("{} changed", s.name))
+        );
+    }
+    for s in &out.impacted_symbols {
+        let _ = writeln!(
+            buf,
+            "::notice file={},line={}::{}",
+            gha_escape_property(&s.file),
+            s.range.start_line,
+            gha_escape_data(&format!("{} impacted by change", s.name))
+        );
+    }
+    buf
+}
+
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_CYAN: &str = "\x1b[36m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// `s` wrapped in the ANSI `code` when `color` is on.
+fn paint(color: bool, code: &str, s: &str) -> String {
+    if color {
+        format!("{code}{s}{ANSI_RESET}")
+    } else {
+        s.to_string()
+    }
+}
+
+/// Render impact as an indented tree for terminals: each changed symbol is a
+/// root, with the symbols it impacts grouped by distance. Impacted symbols
+/// without a witness are listed by file at the end. `color` adds ANSI styling.
+pub fn to_text(out: &ImpactOutput, color: bool) -> String {
+    use std::collections::BTreeMap;
+    use std::fmt::Write as _;
+    let paint = |code: &str, s: &str| paint(color, code, s);
+    let label = |sym: &crate::ir::Symbol| {
+        format!(
+            "{} {}  {}",
+            sym.kind.code(),
+            sym.name,
+            paint(ANSI_CYAN, &format!("{}:{}", sym.file, sym.range.start_line))
+        )
+    };
+
+    // root id -> depth -> impacted symbols
+    let mut by_root: BTreeMap<&str, BTreeMap<usize, Vec<&crate::ir::Symbol>>> = BTreeMap::new();
+    let mut unattributed: BTreeMap<&str, Vec<&crate::ir::Symbol>> = BTreeMap::new();
+    for sym in &out.impacted_symbols {
+        match out.impacted_witnesses.get(&sym.id.0) {
+            Some(w) => by_root
+                .entry(w.root_symbol_id.as_str())
+                .or_default()
+                .entry(w.depth)
+                .or_default()
+                .push(sym),
+            None => unattributed.entry(sym.file.as_str()).or_default().push(sym),
+        }
+    }
+
+    let mut buf = String::new();
+    for root in &out.changed_symbols {
+        let _ = writeln!(buf, "{}", paint(ANSI_BOLD, &label(root)));
+        let Some(depths) = by_root.get(root.id.0.as_str()) else {
+            let _ = writeln!(buf, "  {}", paint(ANSI_DIM, "(no impact)"));
+            continue;
+        };
+        for (depth, syms) in depths {
+            let _ = writeln!(buf, "  {}", paint(ANSI_DIM, &format!("depth {depth}")));
+            for sym in syms {
+                let _ = writeln!(buf, "    {}", label(sym));
+            }
+        }
+    }
+    if !unattributed.is_empty() {
+        let _ = writeln!(buf, "{}", paint(ANSI_BOLD, "impacted (no witness)"));
+        for (file, syms) in &unattributed {
+            let _ = writeln!(buf, "  {}", paint(ANSI_CYAN, file));
+            for sym in syms {
+                let _ = writeln!(
+                    buf,
+                    "    {} {}  :{}",
+                    sym.kind.code(),
+                    sym.name,
+                    sym.range.start_line
+                );
+            }
+        }
+    }
+    let _ = writeln!(
+        buf,
+        "{}",
+        paint(
+            ANSI_YELLOW,
+            &format!(
+                "{} changed, {} impacted in {} file(s)",
+                out.changed_symbols.len(),
+                out.impacted_symbols.len(),
+                out.impacted_files.len()
+            )
+        )
+    );
+    buf
+}
+
+/// Render changed symbols for terminals, grouped by file in line order:
+/// `kind name  :line`, prefixed with `+`/`-`/`~` when change kinds were
+/// classified and followed by the changed lines when those were attached.
+/// Changed files without symbols are listed too. `color` adds ANSI styling.
+pub fn changed_to_text(out: &crate::mapping::ChangedOutput, color: bool) -> String {
+    use crate::mapping::SymbolChangeKind;
+    use std::collections::BTreeMap;
+    use std::fmt::Write as _;
+    let paint = |code: &str, s: &str| paint(color, code, s);
+    let change_kinds: std::collections::HashMap<&str, SymbolChangeKind> = out
+        .symbol_changes
+        .iter()
+        .map(|c| (c.id.0.as_str(), c.change_kind))
+        .collect();
+
+    let mut by_file: BTreeMap<&str, Vec<&crate::ir::Symbol>> = out
+        .changed_files
+        .iter()
+        .map(|f| (f.as_str(), Vec::new()))
+        .collect();
+    for sym in &out.changed_symbols {
+        by_file.entry(sym.file.as_str()).or_default().push(sym);
+    }
+
+    let mut buf = String::new();
+    for (file, syms) in by_file.iter_mut() {
+        let _ = writeln!(buf, "{}", paint(ANSI_BOLD, &paint(ANSI_CYAN, file)));
+        if syms.is_empty() {
+            let _ = writeln!(buf, "  {}", paint(ANSI_DIM, "(no symbols)"));
+            continue;
+        }
+        syms.sort_by(|a, b| {
+            (a.range.start_line, a.name.as_str()).cmp(&(b.range.start_line, b.name.as_str()))
+        });
+        for sym in syms.iter() {
+            let marker = match change_kinds.get(sym.id.0.as_str()) {
+                Some(SymbolChangeKind::Added) => format!("{} ", paint(ANSI_GREEN, "+")),
+                Some(SymbolChangeKind::Removed) => format!("{} ", paint(ANSI_RED, "-")),
+                Some(SymbolChangeKind::Modified) => format!("{} ", paint(ANSI_YELLOW, "~")),
+                None => String::new(),
+            };
+            let _ = write!(
+                buf,
+                "  {marker}{} {}  {}",
+                sym.kind.code(),
+                sym.name,
+                paint(ANSI_CYAN, &format!(":{}", sym.range.start_line))
+            );
+            if let Some(lines) = out.changed_lines.get(&sym.id.0)
+                && !lines.is_empty()
+            {
+                let list: Vec<String> = lines.iter().map(u32::to_string).collect();
+                let _ = write!(
+                    buf,
+                    "  {}",
+                    paint(ANSI_DIM, &format!("lines {}", list.join(",")))
+                );
+            }
+            buf.push('\n');
+        }
+    }
+    let total = out.total_changed.unwrap_or(out.changed_symbols.len());
+    let shown = if total > out.changed_symbols.len() {
+        format!("{} of {}", out.changed_symbols.len(), total)
+    } else {
+        total.to_string()
+    };
+    let _ = writeln!(
+        buf,
+        "{}",
+        paint(
+            ANSI_YELLOW,
+            &format!(
+                "{} changed symbol(s) in {} file(s)",
+                shown,
+                out.changed_files.len()
+            )
+        )
+    );
+    buf
+}
+
+/// Plain-text listing of dead-code candidates: one `kind name  file:line`
+/// line per symbol, then a count.
+pub fn dead_code_to_text(symbols: &[crate::ir::Symbol]) -> String {
+    let mut buf = String::new();
+    for s in symbols {
+        buf.push_str(&format!(
+            "{} {}  {}:{}\n",
+            s.kind.code(),
+            s.name,
+            s.file,
+            s.range.start_line
+        ));
+    }
+    buf.push_str(&format!("{} unreferenced symbol(s)\n", symbols.len()));
+    buf
+}
+
+/// Plain-text listing of impacted tests: one `kind name  file:line  <- changed`
+/// line per test, naming the changed symbols that reach it, then a count.
+pub fn impacted_tests_to_text(tests: &[crate::impact::ImpactedTest]) -> String {
+    let mut buf = String::new();
+    for t in tests {
+        let via: Vec<String> = t
+            .reached_from
+            .iter()
+            .map(|id| id.parts().map(|p| p.name).unwrap_or_else(|| id.0.clone()))
+            .collect();
+        buf.push_str(&format!(
+            "{} {}  {}:{}  <- {}\n",
+            t.test.kind.code(),
+            t.test.name,
+            t.test.file,
+            t.test.range.start_line,
+            via.join(", ")
+        ));
+    }
+    buf.push_str(&format!("{} impacted test(s)\n", tests.len()));
+    buf
+}
+
 pub fn to_html(out: &ImpactOutput) -> String {
-    html::render(out)
+    to_html_with_options(out, &HtmlOptions::default())
+}
+
+pub fn to_html_with_options(out: &ImpactOutput, opts: &HtmlOptions) -> String {
+    html::render(out, opts)
 }
 
 mod html {
-    use super::{h, kind_code, parse_symbol_id};
+    use super::{HTML_SYMBOL_PAGE, HtmlOptions, h, parse_symbol_id};
     use crate::impact::ImpactOutput;
+    use crate::ir::Symbol;
     use serde_json::json;
     use std::collections::BTreeSet;
 
@@ -329,31 +1305,33 @@ mod html {
         env!("CARGO_MANIFEST_DIR"),
         "/src/assets/report.html"
     ));
-    const STYLE: &str = include_str!(concat!(
+    pub(super) const STYLE: &str = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/src/assets/report.css"
     ));
-    const SCRIPT_MAIN: &str = include_str!(concat!(
+    pub(super) const SCRIPT_MAIN: &str = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/src/assets/report_main.js"
     ));
-    const SCRIPT_WORKER: &str = include_str!(concat!(
+    pub(super) const SCRIPT_WORKER: &str = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/src/assets/impact_worker.js"
     ));
 
-    pub(super) fn render(out: &ImpactOutput) -> String {
-        HtmlReportPage { out }.render()
+    pub(super) fn render(out: &ImpactOutput, opts: &HtmlOptions) -> String {
+        HtmlReportPage { out, opts }.render()
     }
 
     struct HtmlReportPage<'a> {
         out: &'a ImpactOutput,
+        opts: &'a HtmlOptions,
     }
 
     impl<'a> HtmlReportPage<'a> {
         fn render(&self) -> String {
             let mut html = TEMPLATE.to_owned();
-            html = html.replace("{{STYLE}}", STYLE);
+            html = html.replace("{{STYLE_TAG}}", &self.style_tag());
+            html = html.replace("{{BODY_OPEN}}", self.body_open());
             html = html.replace(
                 "{{COUNT_CHANGED}}",
                 &self.out.changed_symbols.len().to_string(),
@@ -369,148 +1347,86 @@ mod html {
             html = html.replace("{{COUNT_EDGES}}", &self.out.edges.len().to_string());
             html = html.replace("{{CHANGED_LIST}}", &self.render_changed_list());
             html = html.replace("{{IMPACTED_LIST}}", &self.render_impacted_list());
+            html = html.replace("{{FILES_SECTION}}", &self.render_files_section());
             html = html.replace("{{EDGES_SECTION}}", &self.render_edges_section());
-            html = html.replace("{{IMPACT_DATA}}", &escape_script(&self.impact_data_json()));
+            html = html.replace("{{IMPACT_DATA}}", &self.impact_data_script());
             html = html.replace("{{WORKER_SRC}}", &self.worker_script_json());
-            html = html.replace("{{MAIN_SCRIPT}}", &escape_script(SCRIPT_MAIN));
+            html = html.replace("{{MAIN_SCRIPT_TAG}}", &self.main_script_tag());
             html
         }
 
-        fn impact_data_json(&self) -> String {
-            let mut nodes = Vec::new();
-            let mut seen: BTreeSet<String> = BTreeSet::new();
-
-            for s in &self.out.changed_symbols {
-                if seen.insert(s.id.0.clone()) {
-                    nodes.push(json!({
-                        "data": {
-                            "id": s.id.0,
-                            "label": s.name,
-                            "file": s.file,
-                            "line": s.range.start_line,
-                            "changed": true,
-                            "kind": kind_code(&s.kind),
-                        }
-                    }));
-                }
+        fn style_tag(&self) -> String {
+            if self.opts.inline_assets {
+                format!("<style>\n{}\n</style>", STYLE)
+            } else {
+                "<link rel=\"stylesheet\" href=\"report.css\">".to_string()
             }
+        }
 
-            for s in &self.out.impacted_symbols {
-                if seen.insert(s.id.0.clone()) {
-                    nodes.push(json!({
-                        "data": {
-                            "id": s.id.0,
-                            "label": s.name,
-                            "file": s.file,
-                            "line": s.range.start_line,
-                            "changed": false,
-                            "kind": kind_code(&s.kind),
-                        }
-                    }));
-                }
+        fn body_open(&self) -> &'static str {
+            if self.opts.dark_mode {
+                "<body class=\"dark\">\n"
+            } else {
+                ""
             }
+        }
 
-            let mut edges = Vec::new();
-            for e in &self.out.edges {
-                let certainty = match e.certainty {
-                    crate::ir::reference::EdgeCertainty::Confirmed => "confirmed",
-                    crate::ir::reference::EdgeCertainty::Inferred => "inferred",
-                    crate::ir::reference::EdgeCertainty::DynamicFallback => "dynamic_fallback",
-                };
-                let kind = match e.kind {
-                    crate::ir::reference::RefKind::Call => "call",
-                    crate::ir::reference::RefKind::Data => "data",
-                    crate::ir::reference::RefKind::Control => "control",
-                };
-                let provenance = match e.provenance {
-                    crate::ir::reference::EdgeProvenance::CallGraph => "call_graph",
-                    crate::ir::reference::EdgeProvenance::LocalDfg => "local_dfg",
-                    crate::ir::reference::EdgeProvenance::SymbolicPropagation => {
-                        "symbolic_propagation"
-                    }
-                };
-                edges.push(json!({
-                    "data": {
-                        "id": format!("{}->{}", e.from.0, e.to.0),
-                        "source": e.from.0,
-                        "target": e.to.0,
-                        "kind": kind,
-                        "certainty": certainty,
-                        "confidence": certainty,
-                        "provenance": provenance,
-                    }
-                }));
-
-                for id in [&e.from.0, &e.to.0] {
-                    if seen.contains(id) {
-                        continue;
-                    }
-                    if let Some((_lang, file, kind, name, line)) = parse_symbol_id(id) {
-                        nodes.push(json!({
-                            "data": {
-                                "id": id,
-                                "label": name,
-                                "file": file,
-                                "line": line,
-                                "changed": false,
-                                "kind": kind,
-                            }
-                        }));
-                    } else {
-                        nodes.push(json!({
-                            "data": {
-                                "id": id,
-                                "label": id,
-                                "file": "",
-                                "line": 0,
-                                "changed": false,
-                                "kind": "mod",
-                            }
-                        }));
-                    }
-                    seen.insert((*id).to_owned());
-                }
+        fn main_script_tag(&self) -> String {
+            if self.opts.inline_assets {
+                format!("<script>\n{}\n</script>", escape_script(SCRIPT_MAIN))
+            } else {
+                "<script src=\"report_main.js\"></script>".to_string()
             }
+        }
 
-            serde_json::to_string(&json!({ "nodes": nodes, "edges": edges }))
-                .unwrap_or_else(|_| "{}".to_string())
+        fn impact_data_script(&self) -> String {
+            if !self.opts.inline_assets {
+                // report_main.js fetches report_data.json when IMPACT_DATA is null
+                return "null".to_string();
+            }
+            escape_script(&impact_data_json(self.out))
         }
 
         fn render_changed_list(&self) -> String {
-            let mut buf = String::new();
-            for s in &self.out.changed_symbols {
-                buf.push_str(&format!(
-                    "<li><label><input type=\"checkbox\" class=\"symbol-select\" value=\"{}\" data-role=\"changed\" data-kind=\"{}\" data-changed=\"true\" checked> <code>{}</code> — {} ({}:{})</label></li>\n",
-                    h(&s.id.0),
-                    kind_code(&s.kind),
-                    h(&s.id.0),
-                    h(&s.name),
-                    h(&s.file),
-                    s.range.start_line
-                ));
-            }
-            if buf.is_empty() {
-                buf.push_str("<li><em>none</em></li>\n");
-            }
-            buf
+            symbol_list_items(&self.out.changed_symbols, "changed")
         }
 
         fn render_impacted_list(&self) -> String {
-            let mut buf = String::new();
-            for s in &self.out.impacted_symbols {
+            symbol_list_items(&self.out.impacted_symbols, "impacted")
+        }
+
+        /// Impacted symbols grouped under their file, each file collapsed by default.
+        fn render_files_section(&self) -> String {
+            if self.out.impacted_by_file.is_empty() {
+                return String::new();
+            }
+            let mut files: Vec<_> = self.out.impacted_by_file.iter().collect();
+            files.sort_by(|a, b| a.0.cmp(b.0));
+            let mut buf = String::from(
+                "<div class=\"sec card\"><div class=\"toolbar\"><strong>Impacted Files</strong><button type=\"button\" id=\"files-expand-all\">expand all</button><button type=\"button\" id=\"files-collapse-all\">collapse all</button></div>\n<ul class=\"file-tree\" id=\"file-tree\">\n",
+            );
+            for (file, symbols) in files {
+                let mut symbols: Vec<_> = symbols.iter().collect();
+                symbols.sort_by(|a, b| {
+                    (a.range.start_line, a.id.0.as_str())
+                        .cmp(&(b.range.start_line, b.id.0.as_str()))
+                });
                 buf.push_str(&format!(
-                    "<li><label><input type=\"checkbox\" class=\"symbol-select\" value=\"{}\" data-role=\"impacted\" data-kind=\"{}\" data-changed=\"false\" checked> <code>{}</code> — {} ({}:{})</label></li>\n",
-                    h(&s.id.0),
-                    kind_code(&s.kind),
-                    h(&s.id.0),
-                    h(&s.name),
-                    h(&s.file),
-                    s.range.start_line
+                    "<li class=\"file-group collapsed\"><button type=\"button\" class=\"file-toggle\" aria-expanded=\"false\">▸</button><code>{}</code> <span class=\"badge\">{}</span>\n<ul class=\"file-symbols\">\n",
+                    h(file),
+                    symbols.len()
                 ));
+                for s in symbols {
+                    buf.push_str(&format!(
+                        "<li><code>{}</code> {} (line {})</li>\n",
+                        s.kind.code(),
+                        h(&s.name),
+                        s.range.start_line
+                    ));
+                }
+                buf.push_str("</ul></li>\n");
             }
-            if buf.is_empty() {
-                buf.push_str("<li><em>none</em></li>\n");
-            }
+            buf.push_str("</ul></div>");
             buf
         }
 
@@ -531,6 +1447,8 @@ mod html {
                     crate::ir::reference::RefKind::Call => "call",
                     crate::ir::reference::RefKind::Data => "data",
                     crate::ir::reference::RefKind::Control => "control",
+                    crate::ir::reference::RefKind::TypeUse => "type_use",
+                    crate::ir::reference::RefKind::Inherit => "inherit",
                 };
                 let provenance = match e.provenance {
                     crate::ir::reference::EdgeProvenance::CallGraph => "call_graph",
@@ -553,10 +1471,152 @@ mod html {
         }
 
         fn worker_script_json(&self) -> String {
+            if !self.opts.inline_assets {
+                // report_main.js loads impact_worker.js by URL when WORKER_SRC is null
+                return "null".to_string();
+            }
             serde_json::to_string(SCRIPT_WORKER).unwrap_or_else(|_| "\"\"".to_string())
         }
     }
 
+    /// `<li>` rows of a symbol list, [`HTML_SYMBOL_PAGE`] per page. Rows past
+    /// the first page start hidden and carry their `data-page`; a trailing
+    /// "show more" row lets report_main.js reveal one page per click.
+    fn symbol_list_items(symbols: &[Symbol], role: &str) -> String {
+        if symbols.is_empty() {
+            return "<li><em>none</em></li>\n".to_string();
+        }
+        let mut buf = String::new();
+        for (i, s) in symbols.iter().enumerate() {
+            let page = i / HTML_SYMBOL_PAGE;
+            let open = if page == 0 {
+                "<li>".to_string()
+            } else {
+                format!("<li data-page=\"{page}\" hidden>")
+            };
+            buf.push_str(&format!(
+                "{open}<label><input type=\"checkbox\" class=\"symbol-select\" value=\"{}\" data-role=\"{role}\" data-kind=\"{}\" data-changed=\"{}\" checked> <code>{}</code> — {} ({}:{})</label></li>\n",
+                h(&s.id.0),
+                s.kind.code(),
+                role == "changed",
+                h(&s.id.0),
+                h(&s.name),
+                h(&s.file),
+                s.range.start_line
+            ));
+        }
+        if symbols.len() > HTML_SYMBOL_PAGE {
+            let hidden = symbols.len() - HTML_SYMBOL_PAGE;
+            buf.push_str(&format!(
+                "<li class=\"more\"><button type=\"button\">show {} more ({hidden} hidden)</button></li>\n",
+                hidden.min(HTML_SYMBOL_PAGE)
+            ));
+        }
+        buf
+    }
+
+    /// Cytoscape nodes (changed first, then impacted, then edge endpoints)
+    /// and edges of the report graph.
+    pub(super) fn impact_data_json(out: &ImpactOutput) -> String {
+        let mut nodes = Vec::new();
+        let mut seen: BTreeSet<String> = BTreeSet::new();
+
+        for s in &out.changed_symbols {
+            if seen.insert(s.id.0.clone()) {
+                nodes.push(json!({
+                    "data": {
+                        "id": s.id.0,
+                        "label": s.name,
+                        "file": s.file,
+                        "line": s.range.start_line,
+                        "changed": true,
+                        "kind": s.kind.code(),
+                    }
+                }));
+            }
+        }
+
+        for s in &out.impacted_symbols {
+            if seen.insert(s.id.0.clone()) {
+                nodes.push(json!({
+                    "data": {
+                        "id": s.id.0,
+                        "label": s.name,
+                        "file": s.file,
+                        "line": s.range.start_line,
+                        "changed": false,
+                        "kind": s.kind.code(),
+                    }
+                }));
+            }
+        }
+
+        let mut edges = Vec::new();
+        for e in &out.edges {
+            let certainty = match e.certainty {
+                crate::ir::reference::EdgeCertainty::Confirmed => "confirmed",
+                crate::ir::reference::EdgeCertainty::Inferred => "inferred",
+                crate::ir::reference::EdgeCertainty::DynamicFallback => "dynamic_fallback",
+            };
+            let kind = match e.kind {
+                crate::ir::reference::RefKind::Call => "call",
+                crate::ir::reference::RefKind::Data => "data",
+                crate::ir::reference::RefKind::Control => "control",
+                crate::ir::reference::RefKind::TypeUse => "type_use",
+                crate::ir::reference::RefKind::Inherit => "inherit",
+            };
+            let provenance = match e.provenance {
+                crate::ir::reference::EdgeProvenance::CallGraph => "call_graph",
+                crate::ir::reference::EdgeProvenance::LocalDfg => "local_dfg",
+                crate::ir::reference::EdgeProvenance::SymbolicPropagation => "symbolic_propagation",
+            };
+            edges.push(json!({
+                "data": {
+                    "id": format!("{}->{}", e.from.0, e.to.0),
+                    "source": e.from.0,
+                    "target": e.to.0,
+                    "kind": kind,
+                    "certainty": certainty,
+                    "confidence": certainty,
+                    "provenance": provenance,
+                }
+            }));
+
+            for id in [&e.from.0, &e.to.0] {
+                if seen.contains(id) {
+                    continue;
+                }
+                if let Some(p) = parse_symbol_id(id) {
+                    nodes.push(json!({
+                        "data": {
+                            "id": id,
+                            "label": p.name,
+                            "file": p.file,
+                            "line": p.line,
+                            "changed": false,
+                            "kind": p.kind,
+                        }
+                    }));
+                } else {
+                    nodes.push(json!({
+                        "data": {
+                            "id": id,
+                            "label": id,
+                            "file": "",
+                            "line": 0,
+                            "changed": false,
+                            "kind": "mod",
+                        }
+                    }));
+                }
+                seen.insert((*id).to_owned());
+            }
+        }
+
+        serde_json::to_string(&json!({ "nodes": nodes, "edges": edges }))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
     fn escape_script(src: &str) -> String {
         src.replace("</", "<\\/")
     }
@@ -567,14 +1627,3 @@ fn h(s: &str) -> String {
         .replace('<', "&lt;")
         .replace('>', "&gt;")
 }
-
-fn kind_code(k: &SymbolKind) -> &'static str {
-    match k {
-        SymbolKind::Function => "fn",
-        SymbolKind::Method => "method",
-        SymbolKind::Struct => "struct",
-        SymbolKind::Enum => "enum",
-        SymbolKind::Trait => "trait",
-        SymbolKind::Module => "mod",
-    }
-}
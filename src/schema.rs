@@ -14,7 +14,10 @@ pub enum SchemaOutputFormat {
     Json,
     Yaml,
     Dot,
+    Graphml,
     Html,
+    GithubActions,
+    Text,
 }
 
 impl SchemaOutputFormat {
@@ -23,7 +26,10 @@ impl SchemaOutputFormat {
             Self::Json => "json",
             Self::Yaml => "yaml",
             Self::Dot => "dot",
+            Self::Graphml => "graphml",
             Self::Html => "html",
+            Self::GithubActions => "github-actions",
+            Self::Text => "text",
         }
     }
 }
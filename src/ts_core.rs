@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::sync::OnceLock;
 use tree_sitter::StreamingIterator;
 
 #[derive(Debug, Deserialize)]
@@ -139,6 +140,32 @@ pub fn compile_queries_typescript(spec: &Spec, tsx: bool) -> anyhow::Result<Comp
     })
 }
 
+pub fn load_cpp_spec() -> Spec {
+    static YAML: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/specs/cpp.yml"
+    ));
+    serde_yaml::from_str(YAML).expect("valid cpp spec yaml")
+}
+
+pub fn compile_queries_cpp(spec: &Spec) -> anyhow::Result<CompiledQueries> {
+    let lang: tree_sitter::Language = tree_sitter_cpp::LANGUAGE.into();
+    let decl = tree_sitter::Query::new(&lang, &spec.queries.declarations)?;
+    let calls = tree_sitter::Query::new(&lang, &spec.queries.calls)?;
+    let imports = tree_sitter::Query::new(&lang, &spec.queries.imports)?;
+    let control = if spec.queries.control.trim().is_empty() {
+        None
+    } else {
+        Some(tree_sitter::Query::new(&lang, &spec.queries.control)?)
+    };
+    Ok(CompiledQueries {
+        decl,
+        calls,
+        imports,
+        control,
+    })
+}
+
 pub fn compile_queries_python(spec: &Spec) -> anyhow::Result<CompiledQueries> {
     let lang: tree_sitter::Language = tree_sitter_python::LANGUAGE.into();
     let decl = tree_sitter::Query::new(&lang, &spec.queries.declarations)?;
@@ -157,6 +184,48 @@ pub fn compile_queries_python(spec: &Spec) -> anyhow::Result<CompiledQueries> {
     })
 }
 
+// Process-wide compiled queries. Compiling a spec is comparatively expensive and the
+// result is immutable, so each language/dialect is compiled once and shared by every
+// analyzer instance (including those created per file on rayon workers).
+
+pub fn shared_queries_rust() -> &'static CompiledQueries {
+    static QUERIES: OnceLock<CompiledQueries> = OnceLock::new();
+    QUERIES.get_or_init(|| compile_queries_rust(&load_rust_spec()).expect("compile rust queries"))
+}
+
+pub fn shared_queries_ruby() -> &'static CompiledQueries {
+    static QUERIES: OnceLock<CompiledQueries> = OnceLock::new();
+    QUERIES.get_or_init(|| compile_queries_ruby(&load_ruby_spec()).expect("compile ruby queries"))
+}
+
+pub fn shared_queries_javascript() -> &'static CompiledQueries {
+    static QUERIES: OnceLock<CompiledQueries> = OnceLock::new();
+    QUERIES.get_or_init(|| {
+        compile_queries_javascript(&load_javascript_spec()).expect("compile js queries")
+    })
+}
+
+pub fn shared_queries_typescript(tsx: bool) -> &'static CompiledQueries {
+    static TS: OnceLock<CompiledQueries> = OnceLock::new();
+    static TSX: OnceLock<CompiledQueries> = OnceLock::new();
+    let cell = if tsx { &TSX } else { &TS };
+    cell.get_or_init(|| {
+        compile_queries_typescript(&load_typescript_spec(), tsx).expect("compile ts queries")
+    })
+}
+
+pub fn shared_queries_python() -> &'static CompiledQueries {
+    static QUERIES: OnceLock<CompiledQueries> = OnceLock::new();
+    QUERIES.get_or_init(|| {
+        compile_queries_python(&load_python_spec()).expect("compile python queries")
+    })
+}
+
+pub fn shared_queries_cpp() -> &'static CompiledQueries {
+    static QUERIES: OnceLock<CompiledQueries> = OnceLock::new();
+    QUERIES.get_or_init(|| compile_queries_cpp(&load_cpp_spec()).expect("compile cpp queries"))
+}
+
 pub struct QueryRunner {
     parser: std::cell::RefCell<tree_sitter::Parser>,
 }
@@ -211,6 +280,15 @@ impl QueryRunner {
         }
     }
 
+    pub fn new_cpp() -> Self {
+        let mut p = tree_sitter::Parser::new();
+        let lang: tree_sitter::Language = tree_sitter_cpp::LANGUAGE.into();
+        p.set_language(&lang).expect("lang");
+        Self {
+            parser: std::cell::RefCell::new(p),
+        }
+    }
+
     pub fn run_captures(&self, src: &str, q: &tree_sitter::Query) -> Vec<Vec<Capture>> {
         let tree = self.parser.borrow_mut().parse(src, None).expect("parse");
         let root = tree.root_node();
@@ -353,4 +431,36 @@ def foo(xs):
         assert_eq!(imports.len(), 2, "should capture import + from import");
         assert_eq!(ctrls.len(), 3, "should capture if/for/while control nodes");
     }
+
+    #[test]
+    fn cpp_spec_matches_definitions_calls_and_includes() {
+        let compiled = compile_queries_cpp(&load_cpp_spec()).unwrap();
+        let src = r#"#include "util.h"
+#include <stdio.h>
+struct Point { int x; };
+int helper(int);
+int run() { return helper(1); }
+void Foo::bar() { obj.m(); ns::f(); }
+"#;
+        let qr = QueryRunner::new_cpp();
+        let decls = qr.run_captures(src, &compiled.decl);
+        assert_eq!(decls.len(), 3, "struct + run + Foo::bar, not the prototype");
+        let calls = qr.run_captures(src, &compiled.calls);
+        assert_eq!(calls.len(), 3);
+        let includes = qr.run_captures(src, &compiled.imports);
+        assert_eq!(includes.len(), 1, "only quoted includes");
+    }
+
+    #[test]
+    fn shared_queries_are_compiled_once_per_dialect() {
+        assert!(std::ptr::eq(shared_queries_rust(), shared_queries_rust()));
+        assert!(std::ptr::eq(
+            shared_queries_typescript(true),
+            shared_queries_typescript(true)
+        ));
+        assert!(!std::ptr::eq(
+            shared_queries_typescript(false),
+            shared_queries_typescript(true)
+        ));
+    }
 }
@@ -0,0 +1,222 @@
+#![allow(deprecated)]
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("main.rs"),
+        "fn bar() {}\nfn foo() { bar(); }\nfn main() { foo(); }\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("empty.rs"), "// no symbols\n").unwrap();
+    dir
+}
+
+fn dimpact(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .env_remove("DIMPACT_CACHE_DIR")
+        .env_remove("DIMPACT_CACHE_SCOPE");
+    cmd
+}
+
+const SEED: &str = "rust:main.rs:fn:bar:1";
+
+/// Names impacted by [`SEED`] after `cache build`, both run with `extra`.
+fn impacted(dir: &TempDir, extra: &[&str]) -> Vec<String> {
+    dimpact(dir)
+        .args(["cache", "build"])
+        .args(extra)
+        .assert()
+        .success();
+    let assert = dimpact(dir)
+        .args(["cache", "query", "--seed-symbol", SEED, "-f", "json"])
+        .args(extra)
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let mut names: Vec<String> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn cache_query_reads_impact_from_cached_graph() {
+    let dir = setup();
+    dimpact(&dir)
+        .args(["cache", "query", "--seed-symbol", SEED])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cache is empty"));
+
+    dimpact(&dir).args(["cache", "build"]).assert().success();
+    let assert = dimpact(&dir)
+        .args(["cache", "query", "--seed-symbol", SEED, "-f", "json"])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let mut impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    impacted.sort();
+    assert_eq!(impacted, vec!["foo", "main"]);
+}
+
+#[test]
+fn cache_query_rejects_stale_cache_unless_verified() {
+    let dir = setup();
+    dimpact(&dir).args(["cache", "build"]).assert().success();
+    fs::write(
+        dir.path().join("main.rs"),
+        "fn bar() {}\nfn foo() {}\nfn main() { foo(); bar(); }\n",
+    )
+    .unwrap();
+
+    dimpact(&dir)
+        .args(["cache", "query", "--seed-symbol", SEED])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "cache is stale (1 file(s) changed)",
+        ));
+
+    let assert = dimpact(&dir)
+        .args([
+            "cache",
+            "query",
+            "--verify",
+            "--seed-symbol",
+            SEED,
+            "-f",
+            "json",
+        ])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["main"]);
+}
+
+#[test]
+fn cache_build_on_a_warm_cache_matches_a_cold_build() {
+    let dir = setup();
+    fs::write(
+        dir.path().join("main.rs"),
+        "fn bar() {}\nfn foo() { bar(); }\nfn main() { foo(); qux(); }\n",
+    )
+    .unwrap();
+    dimpact(&dir).args(["cache", "build"]).assert().success();
+    // main.rs is untouched, but its call to qux now has a target
+    fs::write(dir.path().join("extra.rs"), "fn qux() {}\n").unwrap();
+    let warm = dimpact(&dir).args(["cache", "build"]).assert().success();
+    let warm_stats = String::from_utf8_lossy(&warm.get_output().stderr).to_string();
+
+    let assert = dimpact(&dir)
+        .args([
+            "cache",
+            "query",
+            "--seed-symbol",
+            "rust:extra.rs:fn:qux:1",
+            "-f",
+            "json",
+        ])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["main"]);
+
+    dimpact(&dir).args(["cache", "clear"]).assert().success();
+    let cold = dimpact(&dir).args(["cache", "build"]).assert().success();
+    assert_eq!(
+        warm_stats,
+        String::from_utf8_lossy(&cold.get_output().stderr)
+    );
+    assert!(
+        warm_stats.contains("files=3 symbols=4 edges=3"),
+        "{warm_stats}"
+    );
+}
+
+#[test]
+fn cache_build_analyzes_files_the_language_map_names() {
+    let dir = setup();
+    fs::write(dir.path().join("gen.rs.in"), "fn baz() {}\n").unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() { baz(); }\n").unwrap();
+    dimpact(&dir)
+        .args(["cache", "build", "--map", "*.rs.in=rust"])
+        .assert()
+        .success();
+    let assert = dimpact(&dir)
+        .args(["cache", "query", "--map", "*.rs.in=rust"])
+        .args(["--seed-symbol", "rust:gen.rs.in:fn:baz:1", "-f", "json"])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["main"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn cache_rebuilds_when_symlink_following_is_toggled() {
+    let dir = setup();
+    let outside = TempDir::new().unwrap();
+    fs::write(outside.path().join("ext.rs"), "fn ext() { bar(); }\n").unwrap();
+    std::os::unix::fs::symlink(outside.path(), dir.path().join("vendor")).unwrap();
+    assert_eq!(impacted(&dir, &[]), vec!["foo", "main"]);
+    assert_eq!(
+        impacted(&dir, &["--follow-symlinks"]),
+        vec!["ext", "foo", "main"]
+    );
+    assert_eq!(impacted(&dir, &[]), vec!["foo", "main"]);
+}
+
+#[test]
+fn cache_rebuilds_when_the_generated_marker_changes() {
+    let dir = setup();
+    fs::write(
+        dir.path().join("gen.rs"),
+        "// @generated\nfn gen() { bar(); }\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("other.rs"),
+        "// AUTOGEN\nfn other() { bar(); }\n",
+    )
+    .unwrap();
+    assert_eq!(impacted(&dir, &[]), vec!["foo", "main", "other"]);
+    assert_eq!(
+        impacted(&dir, &["--include-generated"]),
+        vec!["foo", "gen", "main", "other"]
+    );
+    assert_eq!(
+        impacted(&dir, &["--generated-marker", "^// AUTOGEN"]),
+        vec!["foo", "gen", "main"]
+    );
+}
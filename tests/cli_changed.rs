@@ -80,3 +80,314 @@ fn cli_mode_changed_reports_rust_symbol() {
     let v = json_output::parse_payload(&stdout);
     assert!(v["changed_symbols"].is_array());
 }
+
+#[test]
+fn cli_changed_with_change_kind_classifies_symbols() {
+    let (_tmp, repo) = setup_repo();
+    // on top of the foo edit, add a new function and drop bar
+    let src = r#"fn foo() {
+    println!("one");
+    println!("two");
+}
+
+fn baz() {
+    println!("new");
+}
+"#;
+    fs::write(repo.join("main.rs"), src).unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .arg("changed")
+            .arg("--lang")
+            .arg("rust")
+            .args(extra)
+            .arg("--format")
+            .arg("json")
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string();
+        json_output::parse_payload(&stdout)
+    };
+
+    let plain = run(&[]);
+    assert!(plain.get("symbol_changes").is_none());
+
+    let v = run(&["--with-change-kind"]);
+    let kinds: std::collections::BTreeMap<String, String> = v["symbol_changes"]
+        .as_array()
+        .expect("symbol_changes array")
+        .iter()
+        .map(|c| {
+            let id = c["id"].as_str().unwrap();
+            let name = id.split(':').nth(3).unwrap().to_string();
+            (name, c["change_kind"].as_str().unwrap().to_string())
+        })
+        .collect();
+    assert_eq!(kinds.get("foo").map(String::as_str), Some("modified"));
+    assert_eq!(kinds.get("baz").map(String::as_str), Some("added"));
+    assert_eq!(kinds.get("bar").map(String::as_str), Some("removed"));
+}
+
+#[test]
+fn cli_changed_with_snippets_embeds_symbol_source() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["changed", "--lang", "rust", "--with-snippets", "-f", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    let snippets = v["snippets"].as_object().expect("snippets map");
+    let changed = v["changed_symbols"].as_array().unwrap();
+    assert_eq!(snippets.len(), changed.len(), "{v}");
+    let foo_id = changed
+        .iter()
+        .map(|s| s["id"].as_str().unwrap())
+        .find(|id| id.split(':').nth(3) == Some("foo"))
+        .unwrap();
+    let text = snippets[foo_id].as_str().unwrap();
+    assert!(text.starts_with("fn foo() {\n"));
+    assert!(text.contains("println!(\"two\");"));
+}
+
+#[test]
+fn cli_changed_with_changed_lines_lists_added_lines_per_symbol() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "changed",
+            "--lang",
+            "rust",
+            "--with-changed-lines",
+            "-f",
+            "json",
+        ])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let v = json_output::parse_payload_slice(&assert.get_output().stdout);
+    let lines = v["changed_lines"].as_object().expect("changed_lines map");
+    let foo_id = v["changed_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap())
+        .find(|id| id.split(':').nth(3) == Some("foo"))
+        .unwrap();
+    assert_eq!(lines[foo_id], serde_json::json!([3]));
+    assert_eq!(lines.len(), 1, "{v}");
+}
+
+#[test]
+fn cli_changed_reads_staged_and_worktree_diffs_from_git() {
+    let (_tmp, repo) = setup_repo();
+    fs::write(repo.join("util.rs"), "fn baz() {}\n").unwrap();
+    git(&repo, &["add", "util.rs"]);
+    git(&repo, &["commit", "-m", "util", "-q"]);
+    // main.rs's edit is staged; util.rs then gets an unstaged edit
+    git(&repo, &["add", "main.rs"]);
+    fs::write(
+        repo.join("util.rs"),
+        "fn baz() {\n    println!(\"three\");\n}\n",
+    )
+    .unwrap();
+
+    let files = |flags: &[&str]| -> Vec<String> {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["changed", "--lang", "rust", "-f", "json"])
+            .args(flags)
+            .assert()
+            .success();
+        let v = json_output::parse_payload_slice(&assert.get_output().stdout);
+        let mut files: Vec<String> = v["changed_files"]
+            .as_array()
+            .expect("changed_files array")
+            .iter()
+            .map(|f| f.as_str().unwrap().to_string())
+            .collect();
+        files.sort();
+        files
+    };
+    assert_eq!(files(&["--staged"]), ["main.rs"]);
+    assert_eq!(files(&["--worktree"]), ["util.rs"]);
+    assert_eq!(files(&["--since", "HEAD"]), ["main.rs", "util.rs"]);
+    assert_eq!(files(&["--staged", "--since", "HEAD"]), ["main.rs"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["changed", "--staged", "--worktree"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn cli_changed_staged_outside_a_git_repository_fails_clearly() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .env("GIT_CEILING_DIRECTORIES", dir.path().parent().unwrap())
+        .args(["changed", "--staged"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("need a git repository"));
+}
+
+#[test]
+fn cli_changed_max_results_caps_the_symbol_list() {
+    let (_tmp, repo) = setup_repo();
+    let src = r#"fn foo() {
+    println!("one");
+    println!("two");
+}
+
+fn bar() { let _b = 2; }
+"#;
+    fs::write(repo.join("main.rs"), src).unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["changed", "--lang", "rust", "--format", "json"])
+            .args(["--with-changed-lines"])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string();
+        json_output::parse_payload(&stdout)
+    };
+
+    let full = run(&["--max-results", "2"]);
+    assert_eq!(full["changed_symbols"].as_array().unwrap().len(), 2);
+    assert!(full.get("total_changed").is_none());
+
+    let capped = run(&["--max-results", "1"]);
+    let symbols = capped["changed_symbols"].as_array().unwrap();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0]["name"], "bar");
+    assert_eq!(capped["total_changed"], serde_json::json!(2));
+    let lines = capped["changed_lines"].as_object().unwrap();
+    assert!(lines.keys().all(|id| id.contains(":bar:")));
+}
+
+#[test]
+fn cli_changed_text_format_groups_by_file() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["changed", "--lang", "rust", "--engine", "ts", "-f", "text"])
+        .write_stdin(diff)
+        .assert()
+        .success()
+        .stdout("main.rs\n  fn foo  :1\n  fn bar  :6\n2 changed symbol(s) in 1 file(s)\n");
+}
+
+#[test]
+fn cli_changed_ignore_whitespace_skips_whitespace_only_edits() {
+    let (_tmp, repo) = setup_repo();
+    // on top of the foo edit, re-indent bar and pad it with a blank line
+    let src = r#"fn foo() {
+    println!("one");
+    println!("two");
+}
+
+
+fn  bar()  {}
+"#;
+    fs::write(repo.join("main.rs"), src).unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let changed = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["changed", "--lang", "rust", "--format", "json"])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        let v = json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ));
+        let mut names: Vec<String> = v["changed_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(changed(&[]), vec!["bar", "foo"]);
+    assert_eq!(changed(&["--ignore-whitespace"]), vec!["foo"]);
+}
+
+#[test]
+fn cli_id_from_diff_prints_changed_symbol_ids() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["id", "--from-diff", "--raw"])
+        .write_stdin(diff.clone())
+        .assert()
+        .success();
+    let ids = String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string();
+    assert_eq!(ids, "rust:main.rs:fn:foo:1\n");
+
+    // the JSON items carry the same ids and feed --seed-json directly
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["id", "--from-diff", "-f", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let v = json_output::parse_payload(&String::from_utf8_lossy(
+        assert.get_output().stdout.as_ref(),
+    ));
+    assert_eq!(v[0]["id"], "rust:main.rs:fn:foo:1");
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["impact", "--seed-symbol", ids.trim(), "-f", "json"])
+        .assert()
+        .success();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["id", "--from-diff", "--path", "main.rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
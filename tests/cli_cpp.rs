@@ -0,0 +1,112 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+// src/main.c includes lib/util.h; both lib/util.c and vendor/compat.c define
+// `helper`, and only the include tells them apart.
+fn setup_repo_c_include() -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    git(&path, &["init", "-q"]);
+    git(&path, &["config", "user.email", "tester@example.com"]);
+    git(&path, &["config", "user.name", "Tester"]);
+
+    fs::create_dir_all(path.join("src")).unwrap();
+    fs::create_dir_all(path.join("lib")).unwrap();
+    fs::create_dir_all(path.join("vendor")).unwrap();
+    fs::write(path.join("lib/util.h"), "int helper(int x);\n").unwrap();
+    fs::write(
+        path.join("lib/util.c"),
+        "#include \"util.h\"\n\n\nint helper(int x) {\n    return x;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("vendor/compat.c"),
+        "int helper(int x) {\n    return -x;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("src/main.c"),
+        "#include \"../lib/util.h\"\n\nint run(void) {\n    return helper(1);\n}\n\nint main(void) {\n    return run();\n}\n",
+    )
+    .unwrap();
+    git(&path, &["add", "."]);
+    git(&path, &["commit", "-m", "init", "-q"]);
+    (dir, path)
+}
+
+fn impact_callers(repo: &std::path::Path, diff: String) -> serde_json::Value {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(repo)
+        .arg("impact")
+        .arg("--direction")
+        .arg("callers")
+        .arg("--format")
+        .arg("json")
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    json_output::parse_payload(&stdout)
+}
+
+fn names(v: &serde_json::Value, key: &str) -> Vec<String> {
+    v[key]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|s| s["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn impact_follows_include_to_companion_source() {
+    let (_tmp, repo) = setup_repo_c_include();
+    fs::write(
+        repo.join("lib/util.c"),
+        "#include \"util.h\"\n\n\nint helper(int x) {\n    return x + 1;\n}\n",
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let v = impact_callers(&repo, String::from_utf8(diff.stdout).unwrap());
+
+    assert_eq!(names(&v, "changed_symbols"), vec!["helper"]);
+    let impacted = names(&v, "impacted_symbols");
+    assert!(impacted.contains(&"run".to_string()), "{impacted:?}");
+    assert!(impacted.contains(&"main".to_string()), "{impacted:?}");
+}
+
+#[test]
+fn impact_ignores_same_named_function_outside_includes() {
+    let (_tmp, repo) = setup_repo_c_include();
+    fs::write(
+        repo.join("vendor/compat.c"),
+        "int helper(int x) {\n    return -x - 1;\n}\n",
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let v = impact_callers(&repo, String::from_utf8(diff.stdout).unwrap());
+
+    assert_eq!(names(&v, "changed_symbols"), vec!["helper"]);
+    assert!(names(&v, "impacted_symbols").is_empty());
+}
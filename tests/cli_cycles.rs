@@ -0,0 +1,107 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+fn setup_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    fs::write(
+        repo.join("main.rs"),
+        "fn ping() { pong(); }\nfn pong() { ping(); }\nfn rec() { rec(); }\nfn leaf() {}\nfn main() { ping(); leaf(); }\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+    dir
+}
+
+#[test]
+fn cycles_reports_mutual_recursion_and_self_loops() {
+    let dir = setup_repo();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .arg("cycles")
+        .arg("--format")
+        .arg("json");
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let cycles = v.as_array().expect("cycles array");
+    let names: Vec<Vec<String>> = cycles
+        .iter()
+        .map(|c| {
+            c.as_array()
+                .unwrap()
+                .iter()
+                .map(|id| id.as_str().unwrap().split(':').nth(3).unwrap().to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            vec!["ping".to_string(), "pong".to_string()],
+            vec!["rec".to_string()],
+        ]
+    );
+}
+
+#[test]
+fn cycles_renders_dot() {
+    let dir = setup_repo();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .arg("cycles")
+        .arg("--format")
+        .arg("dot");
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    assert!(stdout.starts_with("digraph cycles {"));
+    assert!(stdout.contains("subgraph cluster_0"));
+    assert!(!stdout.contains("leaf"));
+}
+
+#[test]
+fn cycles_sees_edits_made_after_the_cache_was_built() {
+    let dir = setup_repo();
+    let repo = dir.path();
+    fs::write(repo.join("main.rs"), "fn a() { b(); }\nfn b() {}\n").unwrap();
+    let cycles = || -> Vec<serde_json::Value> {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(repo)
+            .args(["cycles", "--format", "json"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+        serde_json::from_str::<serde_json::Value>(&stdout)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .clone()
+    };
+    assert!(cycles().is_empty());
+
+    fs::write(repo.join("main.rs"), "fn a() { b(); }\nfn b() { a(); }\n").unwrap();
+    assert_eq!(cycles().len(), 1);
+}
@@ -0,0 +1,51 @@
+#![allow(deprecated)]
+use std::fs;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn api() { helper(); }\nfn helper() {}\nfn unused() {}\nfn rec() { rec(); }\nfn main() {}\n#[test]\nfn checks() {}\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("tests/it.rs"), "fn tool() {}\n").unwrap();
+    dir
+}
+
+fn dead_names(dir: &TempDir, extra: &[&str]) -> Vec<String> {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["deadcode", "-f", "json"])
+        .args(extra);
+    let assert = cmd.assert().success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    v.as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn deadcode_lists_unreferenced_functions_but_not_entry_points() {
+    let dir = setup();
+    assert_eq!(dead_names(&dir, &[]), vec!["api", "unused", "rec", "tool"]);
+    assert_eq!(
+        dead_names(&dir, &["--pub-as-roots", "--exclude-tests"]),
+        vec!["unused", "rec"]
+    );
+}
+
+#[test]
+fn deadcode_text_output_lists_locations() {
+    let dir = setup();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["deadcode", "-f", "text", "--exclude-tests"])
+        .assert()
+        .success()
+        .stdout("fn api  src/lib.rs:1\nfn unused  src/lib.rs:3\nfn rec  src/lib.rs:4\n3 unreferenced symbol(s)\n");
+}
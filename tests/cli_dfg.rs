@@ -0,0 +1,66 @@
+#![allow(deprecated)]
+use std::fs;
+use tempfile::TempDir;
+
+const SRC: &str = "fn f(a: i32) -> i32 {\n    let b = a + 1;\n    if b > 0 {\n        return b;\n    }\n    a\n}\n";
+
+fn dfg_json(dir: &TempDir, file: &str, extra: &[&str]) -> serde_json::Value {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["dfg", file, "--format", "json"])
+        .args(extra);
+    let assert = cmd.assert().success();
+    serde_json::from_slice(&assert.get_output().stdout).unwrap()
+}
+
+fn edge_kinds(v: &serde_json::Value) -> Vec<String> {
+    v["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["kind"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn dfg_prints_graph_and_filters_edge_kinds() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.rs"), SRC).unwrap();
+
+    let all = dfg_json(&dir, "lib.rs", &[]);
+    assert!(!all["nodes"].as_array().unwrap().is_empty());
+    let kinds = edge_kinds(&all);
+    assert!(kinds.iter().any(|k| k == "data"), "kinds: {kinds:?}");
+
+    let data = edge_kinds(&dfg_json(&dir, "lib.rs", &["--data-only"]));
+    assert!(!data.is_empty());
+    assert!(data.iter().all(|k| k == "data"));
+
+    let control = edge_kinds(&dfg_json(&dir, "lib.rs", &["--control-only"]));
+    assert!(control.iter().all(|k| k == "control"));
+    assert_eq!(
+        data.len() + control.len(),
+        kinds.len(),
+        "filters should partition the edges"
+    );
+}
+
+#[test]
+fn dfg_dot_output_and_unsupported_language() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.rs"), SRC).unwrap();
+    fs::write(dir.path().join("m.py"), "def f(a):\n    return a\n").unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["dfg", "lib.rs", "--format", "dot"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with("digraph"));
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path()).args(["dfg", "m.py"]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "dfg supports only rust and ruby files",
+    ));
+}
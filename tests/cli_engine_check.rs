@@ -0,0 +1,47 @@
+#![allow(deprecated)]
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[test]
+fn cli_engine_check_reports_pass_with_mock_server() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .env("DIMPACT_TEST_LSP_MOCK", "1")
+        .args(["engine-check", "--lang", "rust"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("engine-check: rust: pass"));
+
+    let out: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let checks = out.as_array().unwrap();
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0]["language"], "rust");
+    assert_eq!(checks[0]["ok"], true);
+    assert_eq!(checks[0]["capabilities"]["call_hierarchy"], true);
+}
+
+#[test]
+fn cli_engine_check_fails_with_reason_when_lsp_disabled() {
+    let dir = TempDir::new().unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .env("DIMPACT_DISABLE_REAL_LSP", "1")
+        .args(["engine-check", "--lang", "go"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("engine-check: go: fail"))
+        .stderr(predicate::str::contains("no usable language server found"));
+
+    let out: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(out[0]["ok"], false);
+    assert_eq!(
+        out[0]["reason"],
+        "real LSP disabled by DIMPACT_DISABLE_REAL_LSP=1"
+    );
+}
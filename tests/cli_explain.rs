@@ -0,0 +1,97 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .expect("git command failed to spawn");
+    assert!(
+        out.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+fn setup_repo() -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    git(&path, &["init", "-q"]);
+    git(&path, &["config", "user.email", "tester@example.com"]);
+    git(&path, &["config", "user.name", "Tester"]);
+    fs::create_dir_all(path.join("src")).unwrap();
+    fs::write(
+        path.join("src/main.rs"),
+        "mod util;\n\nfn helper() {}\n\nfn main() {\n    helper();\n}\n",
+    )
+    .unwrap();
+    fs::write(path.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+    git(&path, &["add", "."]);
+    git(&path, &["commit", "-m", "init", "-q"]);
+    (dir, path)
+}
+
+#[test]
+fn explain_lists_scored_candidates_for_sites_in_symbol() {
+    let (_tmp, repo) = setup_repo();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "--format",
+            "json",
+            "impact",
+            "--explain",
+            "rust:src/main.rs:fn:main:5",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    let sites = v.as_array().expect("array of traces");
+    assert_eq!(sites.len(), 1);
+    let site = &sites[0];
+    assert_eq!(site["line"], 6);
+    assert_eq!(site["stage"], "by_name");
+    assert_eq!(site["resolved_to"], "rust:src/main.rs:fn:helper:3");
+
+    let candidates = site["candidates"].as_array().unwrap();
+    assert_eq!(candidates.len(), 2);
+    let local = &candidates[0];
+    assert_eq!(local["symbol_id"], "rust:src/main.rs:fn:helper:3");
+    assert_eq!(local["score"]["same_file"], 30);
+    assert_eq!(local["score"]["same_dir"], 10);
+    assert_eq!(local["total"], 45);
+    let other = &candidates[1];
+    assert_eq!(other["symbol_id"], "rust:src/util.rs:fn:helper:1");
+    assert_eq!(other["score"]["same_file"], 0);
+}
+
+#[test]
+fn explain_matches_sites_that_consider_the_symbol_as_candidate() {
+    let (_tmp, repo) = setup_repo();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "--format",
+            "text",
+            "impact",
+            "--explain",
+            "rust:src/util.rs:fn:helper:1",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    assert!(
+        stdout.contains("src/main.rs:6 helper -> rust:src/main.rs:fn:helper:3"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("same_file +0, same_dir +10"), "{stdout}");
+}
@@ -0,0 +1,157 @@
+#![allow(deprecated)]
+use std::fs;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("vendor")).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "fn a() {}\n").unwrap();
+    fs::write(dir.path().join("src/app.py"), "def b():\n    pass\n").unwrap();
+    fs::write(dir.path().join("src/notes.txt"), "not source\n").unwrap();
+    fs::write(dir.path().join("vendor/dep.rs"), "fn c() {}\n").unwrap();
+    dir
+}
+
+#[test]
+fn files_lists_analyzed_sources_with_languages() {
+    let dir = setup();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["files", "-f", "json", "--ignore-dir", "vendor"])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let mut files: Vec<(String, String)> = v
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| {
+            (
+                f["path"].as_str().unwrap().to_string(),
+                f["language"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        vec![
+            ("src/app.py".to_string(), "python".to_string()),
+            ("src/lib.rs".to_string(), "rust".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn files_text_output_prints_one_path_per_line() {
+    let dir = setup();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["files", "-f", "text"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(lines, vec!["src/app.py", "src/lib.rs", "vendor/dep.rs"]);
+}
+
+#[test]
+fn files_map_assigns_languages_to_nonstandard_extensions() {
+    let dir = setup();
+    fs::write(dir.path().join("src/legacy.es6"), "function d() {}\n").unwrap();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["files", "-f", "json", "--ignore-dir", "vendor"])
+        .args(["--map", "*.es6=javascript", "--map", "src/app.py=ruby"])
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let language = |path: &str| {
+        v.as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["path"] == path)
+            .map(|f| f["language"].as_str().unwrap().to_string())
+    };
+    assert_eq!(language("src/legacy.es6").as_deref(), Some("javascript"));
+    assert_eq!(language("src/app.py").as_deref(), Some("ruby"));
+    assert_eq!(language("src/lib.rs").as_deref(), Some("rust"));
+}
+
+#[test]
+fn files_rejects_a_malformed_map_entry() {
+    let dir = setup();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["files", "--map", "*.es6=cobol"])
+        .assert()
+        .failure();
+}
+
+fn listed_paths(dir: &TempDir, extra: &[&str]) -> Vec<String> {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["files", "-f", "text", "--ignore-dir", "vendor"])
+        .args(extra)
+        .assert()
+        .success();
+    let mut paths: Vec<String> = String::from_utf8_lossy(&assert.get_output().stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn files_skips_generated_sources_unless_asked() {
+    let dir = setup();
+    fs::write(
+        dir.path().join("src/proto.rs"),
+        "// @generated by protoc\nfn gen() {}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/stub.rs"),
+        "// AUTOGENERATED, DO NOT TOUCH\nfn stub() {}\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        listed_paths(&dir, &[]),
+        vec!["src/app.py", "src/lib.rs", "src/stub.rs"]
+    );
+    assert_eq!(
+        listed_paths(&dir, &["--include-generated"]),
+        vec!["src/app.py", "src/lib.rs", "src/proto.rs", "src/stub.rs"]
+    );
+    assert_eq!(
+        listed_paths(&dir, &["--generated-marker", "^// AUTOGENERATED"]),
+        vec!["src/app.py", "src/lib.rs", "src/proto.rs"]
+    );
+}
+
+#[test]
+fn files_reads_config_from_the_repository_root() {
+    let dir = setup();
+    fs::create_dir(dir.path().join(".git")).unwrap();
+    fs::write(dir.path().join(".dimpact.yaml"), "ignore_dir: [vendor]\n").unwrap();
+    fs::create_dir_all(dir.path().join("src/vendor")).unwrap();
+    fs::write(dir.path().join("src/vendor/dep.rs"), "fn c() {}\n").unwrap();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path().join("src"))
+        .args(["files", "-f", "text"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let mut lines: Vec<&str> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(lines, vec!["app.py", "lib.rs"]);
+}
@@ -0,0 +1,133 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+fn setup_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    fs::write(
+        repo.join("main.rs"),
+        "fn leaf() {}\nfn mid() { leaf(); }\nfn main() { mid(); leaf(); }\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+    dir
+}
+
+fn graph(dir: &TempDir, format: &str) -> String {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["graph", "--format", format])
+        .assert()
+        .success();
+    String::from_utf8_lossy(assert.get_output().stdout.as_ref()).into_owned()
+}
+
+#[test]
+fn graph_exports_every_symbol_and_reference_as_json() {
+    let dir = setup_repo();
+    let v = json_output::parse_payload(&graph(&dir, "json"));
+    let nodes: Vec<&str> = v["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(nodes, vec!["leaf", "main", "mid"]);
+    let node = &v["nodes"][0];
+    assert_eq!(node["id"], "rust:main.rs:fn:leaf:1");
+    assert_eq!(node["range"]["start_line"], 1);
+
+    let edges: Vec<(String, String, u64)> = v["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            assert_eq!(e["kind"], "call");
+            assert_eq!(e["file"], "main.rs");
+            (
+                e["from"]
+                    .as_str()
+                    .unwrap()
+                    .split(':')
+                    .nth(3)
+                    .unwrap()
+                    .to_string(),
+                e["to"]
+                    .as_str()
+                    .unwrap()
+                    .split(':')
+                    .nth(3)
+                    .unwrap()
+                    .to_string(),
+                e["line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("main".to_string(), "leaf".to_string(), 3),
+            ("main".to_string(), "mid".to_string(), 3),
+            ("mid".to_string(), "leaf".to_string(), 2),
+        ]
+    );
+}
+
+#[test]
+fn graph_renders_dot_and_graphml() {
+    let dir = setup_repo();
+    let dot = graph(&dir, "dot");
+    assert!(dot.starts_with("digraph project {"));
+    assert!(
+        dot.contains("\"rust:main.rs:fn:mid:2\" -> \"rust:main.rs:fn:leaf:1\" [label=\"call\"];")
+    );
+
+    let graphml = graph(&dir, "graphml");
+    assert!(graphml.contains("<graph id=\"project\" edgedefault=\"directed\">"));
+    assert_eq!(graphml.matches("<node id=").count(), 3);
+    assert_eq!(graphml.matches("<edge id=").count(), 3);
+}
+
+#[test]
+fn graph_sees_files_added_after_the_cache_was_built() {
+    let dir = setup_repo();
+    let node_names = |v: &serde_json::Value| -> Vec<String> {
+        v["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let before = json_output::parse_payload(&graph(&dir, "json"));
+    assert_eq!(node_names(&before), vec!["leaf", "main", "mid"]);
+
+    fs::write(dir.path().join("extra.rs"), "fn extra() {}\n").unwrap();
+    let after = json_output::parse_payload(&graph(&dir, "json"));
+    assert!(node_names(&after).contains(&"extra".to_string()));
+}
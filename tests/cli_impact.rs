@@ -70,3 +70,691 @@ fn cli_mode_impact_basic_callers() {
     let v = json_output::parse_payload(&stdout);
     assert!(v["impacted_symbols"].is_array());
 }
+
+#[test]
+fn cli_impact_text_format_prints_tree() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["impact", "--lang", "rust", "-f", "text"])
+        .write_stdin(diff)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "fn bar  main.rs:1\n  depth 1\n    fn foo  main.rs:2\n",
+        ))
+        .stdout(predicate::str::contains(
+            "1 changed, 1 impacted in 1 file(s)",
+        ))
+        .stdout(predicate::str::contains("\x1b[").not());
+}
+
+#[test]
+fn cli_impact_output_writes_report_to_file() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args([
+            "impact",
+            "--lang",
+            "rust",
+            "-f",
+            "html",
+            "--html-external-assets",
+            "--output",
+            "reports/out/impact.html",
+        ])
+        .write_stdin(diff)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let html = fs::read_to_string(repo.join("reports/out/impact.html")).unwrap();
+    assert!(html.contains("<html"));
+    assert!(repo.join("reports/out/report.css").exists());
+    assert!(!repo.join("report.css").exists());
+    // The graph data is a sidecar the page fetches instead of inline JSON
+    assert!(html.contains("const IMPACT_DATA = null;"));
+    let data = fs::read_to_string(repo.join("reports/out/report_data.json")).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let labels: Vec<&str> = v["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["data"]["label"].as_str())
+        .collect();
+    assert_eq!(labels, ["bar", "foo"]);
+}
+
+#[test]
+fn cli_impact_compact_prints_single_line_json() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--lang", "rust", "-f", "json", "--compact"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    let v: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(v["impacted_symbols"].is_array());
+}
+
+#[test]
+fn cli_impact_root_analyzes_subproject() {
+    let (_tmp, repo) = setup_repo();
+    // Turn the repo into a monorepo with the project under sub/
+    let sub = repo.join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::rename(repo.join("main.rs"), sub.join("main.rs")).unwrap();
+    let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -1 +1 @@\n-fn bar() {}\n+fn bar() { let _x = 1; }\n";
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["--root", "sub", "impact", "--lang", "rust", "-f", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["foo"]);
+    assert!(sub.join(".dimpact").join("cache").exists());
+    assert!(!repo.join(".dimpact").exists());
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["--root", "missing", "impact"])
+        .write_stdin(diff)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --root missing"));
+}
+
+#[test]
+fn cli_impact_root_keeps_command_line_paths_relative() {
+    let (_tmp, repo) = setup_repo();
+    let sub = repo.join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::rename(repo.join("main.rs"), sub.join("main.rs")).unwrap();
+    let diff = "diff --git a/main.rs b/main.rs\n--- a/main.rs\n+++ b/main.rs\n@@ -1 +1 @@\n-fn bar() {}\n+fn bar() { let _x = 1; }\n";
+    fs::write(repo.join("change.diff"), diff).unwrap();
+
+    // --diff and --output are taken from where dimpact runs, not from --root
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["--root", "sub", "impact", "--lang", "rust", "-f", "json"])
+        .args([
+            "--with-snippets",
+            "--diff",
+            "change.diff",
+            "-o",
+            "out/impact.json",
+        ])
+        .assert()
+        .success();
+    assert!(!sub.join("out").exists());
+    let text = fs::read_to_string(repo.join("out").join("impact.json")).unwrap();
+    let v = json_output::parse_payload(&text);
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["foo"]);
+    assert!(
+        v["snippets"]
+            .as_object()
+            .expect("snippets map")
+            .values()
+            .any(|s| s.as_str().is_some_and(|s| s.contains("fn foo")))
+    );
+
+    // --seed-file names the file as seen from the current directory
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["--root", "sub", "impact", "--lang", "rust", "-f", "json"])
+        .args(["--seed-file", "sub/main.rs"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    let changed: Vec<&str> = v["changed_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["file"].as_str().unwrap())
+        .collect();
+    assert_eq!(changed, vec!["main.rs", "main.rs"]);
+}
+
+#[test]
+fn cli_impact_with_snippets_embeds_symbol_source() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "-f", "json"])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string();
+        json_output::parse_payload(&stdout)
+    };
+
+    assert!(run(&[]).get("snippets").is_none());
+
+    let v = run(&["--with-snippets"]);
+    let snippets = v["snippets"].as_object().expect("snippets map");
+    let by_name = |name: &str| {
+        snippets
+            .iter()
+            .find(|(id, _)| id.split(':').nth(3) == Some(name))
+            .and_then(|(_, text)| text.as_str())
+            .map(str::to_string)
+    };
+    assert_eq!(by_name("bar").as_deref(), Some("fn bar() { let _x = 1; }"));
+    assert_eq!(by_name("foo").as_deref(), Some("fn foo() { bar(); }"));
+}
+
+#[test]
+fn cli_impact_graph_limits_fail_or_truncate() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args([
+            "impact",
+            "--lang",
+            "rust",
+            "--engine",
+            "ts",
+            "--max-symbols",
+            "1",
+        ])
+        .write_stdin(diff.clone())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "graph has 2 symbols, over the limit of 1",
+        ));
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "impact",
+            "--lang",
+            "rust",
+            "--engine",
+            "ts",
+            "--max-visited",
+            "1",
+        ])
+        .args(["-f", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    assert_eq!(v["summary"]["truncated"], serde_json::json!(true));
+    assert!(v["impacted_symbols"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn cli_impact_union_seeds_adds_diff_changes_to_seeds() {
+    let (_tmp, repo) = setup_repo();
+    // bar changes in the diff; baz is only named as a seed
+    let tail = "fn baz() {}\nfn qux() { baz(); }\n";
+    fs::write(
+        repo.join("main.rs"),
+        format!("fn bar() {{}}\nfn foo() {{ bar(); }}\n{tail}"),
+    )
+    .unwrap();
+    git(&repo, &["commit", "-am", "add baz", "-q"]);
+    fs::write(
+        repo.join("main.rs"),
+        format!("fn bar() {{ let _x = 1; }}\nfn foo() {{ bar(); }}\n{tail}"),
+    )
+    .unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let names = |v: &serde_json::Value, key: &str| -> Vec<String> {
+        let mut out: Vec<String> = v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        out.sort();
+        out
+    };
+    let run = |extra: &[&str]| -> serde_json::Value {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(["--seed-symbol", "rust:main.rs:fn:baz:3"])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ))
+    };
+
+    let seeds_only = run(&[]);
+    assert_eq!(names(&seeds_only, "changed_symbols"), vec!["baz"]);
+    assert_eq!(names(&seeds_only, "impacted_symbols"), vec!["qux"]);
+
+    let union = run(&["--union-seeds"]);
+    assert_eq!(names(&union, "changed_symbols"), vec!["bar", "baz"]);
+    assert_eq!(names(&union, "impacted_symbols"), vec!["foo", "qux"]);
+}
+
+#[test]
+fn cli_impact_seed_symbol_accepts_unescaped_qualified_name() {
+    let (_tmp, repo) = setup_repo();
+    let src =
+        "struct Foo;\nimpl Foo {\n    fn new() -> Foo { Foo }\n}\nfn make() { Foo::new(); }\n";
+    fs::write(repo.join("main.rs"), src).unwrap();
+    git(&repo, &["commit", "-am", "impl", "-q"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+        .args(["--seed-symbol", "rust:main.rs:fn:Foo::new:3"])
+        .assert()
+        .success();
+    let v = json_output::parse_payload(&String::from_utf8_lossy(
+        assert.get_output().stdout.as_ref(),
+    ));
+    let changed = v["changed_symbols"].as_array().unwrap();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0]["id"], "rust:main.rs:fn:Foo%3A%3Anew:3");
+    let impacted: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(impacted, vec!["make"]);
+}
+
+#[test]
+fn cli_impact_max_results_keeps_the_nearest_symbols() {
+    let (_tmp, repo) = setup_repo();
+    git(&repo, &["checkout", "--", "."]);
+    let src = "fn bar() {}\nfn foo() { bar(); }\nfn baz() { bar(); }\nfn top() { foo(); }\n";
+    fs::write(repo.join("main.rs"), src).unwrap();
+    git(&repo, &["commit", "-am", "callers", "-q"]);
+    fs::write(
+        repo.join("main.rs"),
+        src.replace("fn bar() {}", "fn bar() { let _x = 1; }"),
+    )
+    .unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+        .args(["--max-results", "2"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let v = json_output::parse_payload(&String::from_utf8_lossy(
+        assert.get_output().stdout.as_ref(),
+    ));
+    let names: Vec<&str> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    // depth-1 callers win over `top` (depth 2), ties broken by id
+    assert_eq!(names, vec!["baz", "foo"]);
+    assert_eq!(v["summary"]["truncated"], serde_json::json!(true));
+    assert_eq!(v["summary"]["total_impacted"], serde_json::json!(3));
+    assert_eq!(v["summary"]["impacted_symbol_count"], serde_json::json!(2));
+    assert!(
+        v["impacted_witnesses"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .all(|id| !id.contains(":top:"))
+    );
+}
+
+#[test]
+fn cli_impact_seed_file_seeds_every_symbol_in_the_files() {
+    let (_tmp, repo) = setup_repo();
+    fs::write(repo.join("limits.rs"), "fn low() {}\nfn high() {}\n").unwrap();
+    fs::write(repo.join("util.rs"), "fn helper() {}\n").unwrap();
+    fs::write(
+        repo.join("main.rs"),
+        "fn bar() { low(); }\nfn foo() { high(); }\nfn baz() { helper(); }\n",
+    )
+    .unwrap();
+
+    let run = |extra: &[&str]| -> serde_json::Value {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(extra)
+            .assert()
+            .success();
+        json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ))
+    };
+    let names = |v: &serde_json::Value, key: &str| -> Vec<String> {
+        let mut out: Vec<String> = v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        out.sort();
+        out
+    };
+
+    let one = run(&["--seed-file", "./limits.rs"]);
+    assert_eq!(names(&one, "changed_symbols"), vec!["high", "low"]);
+    assert_eq!(names(&one, "impacted_symbols"), vec!["bar", "foo"]);
+
+    let both = run(&["--seed-file", "limits.rs", "--seed-file", "util.rs"]);
+    assert_eq!(
+        names(&both, "changed_symbols"),
+        vec!["helper", "high", "low"]
+    );
+    assert_eq!(names(&both, "impacted_symbols"), vec!["bar", "baz", "foo"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["impact", "--lang", "rust", "--engine", "ts"])
+        .args(["--seed-file", "missing.rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--seed-file missing.rs"));
+}
+
+#[test]
+fn cli_impact_profile_prints_phase_timings_to_stderr() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+        .arg("--profile")
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let out = assert.get_output();
+    let v = json_output::parse_payload(&String::from_utf8_lossy(&out.stdout));
+    assert_eq!(v["impacted_symbols"][0]["name"], "foo");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let keys: Vec<&str> = stderr
+        .lines()
+        .filter_map(|l| l.split_once('='))
+        .filter(|(k, v)| k.ends_with("_ms") && v.parse::<f64>().is_ok())
+        .map(|(k, _)| k)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "enumerate_ms",
+            "analyze_ms",
+            "resolve_ms",
+            "impact_ms",
+            "render_ms",
+            "total_ms"
+        ]
+    );
+}
+
+#[test]
+fn cli_impact_unions_changes_from_several_diffs() {
+    let (_tmp, repo) = setup_repo();
+    git(&repo, &["checkout", "--", "."]);
+    let src = "fn bar() {}\nfn foo() { bar(); }\nfn baz() {}\nfn qux() { baz(); }\n";
+    fs::write(repo.join("main.rs"), src).unwrap();
+    git(&repo, &["commit", "-am", "base", "-q"]);
+    let src = src.replace("fn bar() {}", "fn bar() { let _x = 1; }");
+    fs::write(repo.join("main.rs"), &src).unwrap();
+    git(&repo, &["commit", "-am", "bar", "-q"]);
+    let src = src.replace("fn baz() {}", "fn baz() { let _y = 2; }");
+    fs::write(repo.join("main.rs"), &src).unwrap();
+    git(&repo, &["commit", "-am", "baz", "-q"]);
+    for (name, range) in [("a.patch", "HEAD~2..HEAD~1"), ("b.patch", "HEAD~1..HEAD")] {
+        let out = git(&repo, &["diff", "--no-ext-diff", "--unified=0", range]);
+        fs::write(repo.join(name), out.stdout).unwrap();
+    }
+
+    let names = |v: &serde_json::Value, key: &str| -> Vec<String> {
+        let mut out: Vec<String> = v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        out.sort();
+        out
+    };
+    let run = |extra: &[&str], stdin: Vec<u8>| -> serde_json::Value {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(extra)
+            .write_stdin(stdin)
+            .assert()
+            .success();
+        json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ))
+    };
+
+    let batch = run(&["--diff", "a.patch", "--diff", "b.patch"], Vec::new());
+    assert_eq!(names(&batch, "changed_symbols"), vec!["bar", "baz"]);
+    assert_eq!(names(&batch, "impacted_symbols"), vec!["foo", "qux"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["changed", "--lang", "rust", "--engine", "ts", "-f", "json"])
+        .args(["--diff", "a.patch", "--diff", "b.patch"])
+        .assert()
+        .success();
+    let changed = json_output::parse_payload(&String::from_utf8_lossy(
+        assert.get_output().stdout.as_ref(),
+    ));
+    assert_eq!(changed["changed_files"], serde_json::json!(["main.rs"]));
+
+    // The same branch as one format-patch stream on stdin
+    let series = git(&repo, &["format-patch", "--stdout", "-U0", "HEAD~2..HEAD"]);
+    let stream = run(&[], series.stdout);
+    assert_eq!(names(&stream, "changed_symbols"), vec!["bar", "baz"]);
+    assert_eq!(names(&stream, "impacted_symbols"), vec!["foo", "qux"]);
+}
+
+#[test]
+fn cli_impact_api_only_skips_body_only_changes() {
+    let (_tmp, repo) = setup_repo();
+    git(&repo, &["checkout", "--", "."]);
+    let src = "fn bar(a: u32) {\n    let _x = a;\n}\nfn foo() { bar(1); }\n";
+    fs::write(repo.join("main.rs"), src).unwrap();
+    git(&repo, &["commit", "-am", "multi-line bar", "-q"]);
+
+    let impacted = |new_src: &str, extra: &[&str]| {
+        fs::write(repo.join("main.rs"), new_src).unwrap();
+        let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(extra)
+            .write_stdin(diff_out.stdout)
+            .assert()
+            .success();
+        let v = json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ));
+        v["impacted_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let body_edit = src.replace("let _x = a;", "let _x = a + 1;");
+    assert_eq!(impacted(&body_edit, &[]), vec!["foo"]);
+    assert!(impacted(&body_edit, &["--api-only"]).is_empty());
+
+    let signature_edit = src.replace("bar(a: u32)", "bar(a: u64)");
+    assert_eq!(impacted(&signature_edit, &["--api-only"]), vec!["foo"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["impact", "--api-only", "--per-seed"])
+        .write_stdin("")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn cli_impact_strict_resolution_requires_the_exact_rust_module() {
+    let (_tmp, repo) = setup_repo();
+    fs::create_dir_all(repo.join("src/net")).unwrap();
+    fs::write(repo.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+    fs::write(repo.join("src/net/util.rs"), "pub fn helper() {}\n").unwrap();
+    fs::write(
+        repo.join("src/net/client.rs"),
+        "pub fn run() {\n    crate::util::helper();\n}\n",
+    )
+    .unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-qm", "two helpers"]);
+    // edit the helper `run` does not call
+    fs::write(
+        repo.join("src/net/util.rs"),
+        "pub fn helper() {\n    let _x = 1;\n}\n",
+    )
+    .unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--", "src/net/util.rs"]);
+
+    let impacted = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(extra)
+            .write_stdin(diff_out.stdout.clone())
+            .assert()
+            .success();
+        let v = json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ));
+        v["impacted_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // both files end in `util`, and the heuristics favor the sibling module
+    assert_eq!(impacted(&[]), vec!["run"]);
+    assert!(impacted(&["--strict-resolution"]).is_empty());
+}
+
+#[test]
+fn cli_impact_include_external_reports_boundary_symbols() {
+    let (_tmp, repo) = setup_repo();
+    fs::write(
+        repo.join("json.rs"),
+        "use serde_json::from_str;\nfn load(s: &str) {\n    let _v = from_str(s);\n}\nfn other(s: &str) {\n    let _v = from_str(s);\n}\n",
+    )
+    .unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-qm", "json"]);
+    fs::write(
+        repo.join("json.rs"),
+        "use serde_json::from_str;\nfn load(s: &str) {\n    let _v = from_str(s.trim());\n}\nfn other(s: &str) {\n    let _v = from_str(s);\n}\n",
+    )
+    .unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--", "json.rs"]);
+
+    let impacted = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args(["impact", "--lang", "rust", "--engine", "ts", "-f", "json"])
+            .args(extra)
+            .write_stdin(diff_out.stdout.clone())
+            .assert()
+            .success();
+        let v = json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ));
+        v["impacted_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                format!(
+                    "{}@{}",
+                    s["name"].as_str().unwrap(),
+                    s["file"].as_str().unwrap()
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert!(impacted(&["--direction", "callees"]).is_empty());
+    assert_eq!(
+        impacted(&["--direction", "callees", "--include-external"]),
+        vec!["serde_json::from_str@<external>"]
+    );
+    // `other` calls the same external function, but boundaries are not traversed
+    assert_eq!(
+        impacted(&["--direction", "both", "--include-external"]),
+        vec!["serde_json::from_str@<external>"]
+    );
+}
@@ -0,0 +1,82 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use tempfile::TempDir;
+
+const SEED: &str = "rust:main.rs:fn:leaf:1";
+
+fn impact_json(repo: &std::path::Path, extra: &[&str]) -> String {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(repo)
+        .args([
+            "impact",
+            "--seed-symbol",
+            SEED,
+            "--with-edges",
+            "-f",
+            "json",
+        ])
+        .args(extra)
+        .assert()
+        .success();
+    String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string()
+}
+
+#[test]
+fn baseline_reports_newly_and_no_longer_impacted_symbols() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    fs::write(
+        repo.join("main.rs"),
+        "fn leaf() {}\n\nfn mid() {\n    leaf();\n}\n\nfn old() {\n    leaf();\n}\n",
+    )
+    .unwrap();
+    let baseline = impact_json(repo, &[]);
+    fs::write(repo.join("baseline.json"), &baseline).unwrap();
+    // Seed runs reuse the cache as is; start the second run from a fresh one
+    fs::remove_dir_all(repo.join(".dimpact")).unwrap();
+
+    fs::write(
+        repo.join("main.rs"),
+        "fn leaf() {}\n\nfn mid() {\n    leaf();\n}\n\nfn new() {\n    leaf();\n}\n",
+    )
+    .unwrap();
+    let stdout = impact_json(repo, &["--baseline", "baseline.json"]);
+    let v = json_output::parse_payload(&stdout);
+    let ids = |key: &str| -> Vec<String> {
+        v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(ids("added_symbols"), vec!["rust:main.rs:fn:new:7"]);
+    assert_eq!(ids("removed_symbols"), vec!["rust:main.rs:fn:old:7"]);
+    assert_eq!(v["edges_compared"], true);
+    assert_eq!(v["added_edges"][0]["from"], "rust:main.rs:fn:new:7");
+    assert_eq!(v["removed_edges"][0]["from"], "rust:main.rs:fn:old:7");
+    assert!(v.get("impacted_symbols").is_none());
+}
+
+#[test]
+fn baseline_must_be_impact_json() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    fs::write(repo.join("main.rs"), "fn leaf() {}\n").unwrap();
+    fs::write(repo.join("baseline.json"), "[]").unwrap();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(repo)
+        .args([
+            "impact",
+            "--seed-symbol",
+            SEED,
+            "--baseline",
+            "baseline.json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not `impact -f json` output"));
+}
@@ -126,7 +126,8 @@ fn cli_impact_by_depth_present_without_edges_output() {
 
     let v = run_impact_json(&repo, &diff, &[]);
 
-    assert!(v["edges"].as_array().unwrap().is_empty());
+    assert!(v.get("edges").is_none());
+    assert_eq!(v["edges_included"], false);
     assert_eq!(by_depth_tuples(&v), vec![(1, 1, 1), (2, 1, 1)]);
     assert_eq!(risk_tuple(&v), ("medium", 1, 1, 1, 2));
 }
@@ -154,7 +155,7 @@ fn cli_impact_by_depth_confidence_filter_updates_summary() {
     let v = run_impact_json(&repo, &diff, &["--min-confidence", "confirmed"]);
 
     assert!(v["impacted_symbols"].as_array().unwrap().is_empty());
-    assert!(v["edges"].as_array().unwrap().is_empty());
+    assert!(v.get("edges").is_none());
     assert!(by_depth_tuples(&v).is_empty());
     assert_eq!(risk_tuple(&v), ("low", 0, 0, 0, 0));
     assert_eq!(v["confidence_filter"]["kept_edge_count"].as_u64(), Some(0));
@@ -165,3 +166,31 @@ fn cli_impact_by_depth_confidence_filter_updates_summary() {
             > 0
     );
 }
+
+#[test]
+fn depth_histogram_flag_prints_buckets_to_stderr() {
+    let (_tmp, repo) = setup_repo_chain();
+    let diff = diff_text(&repo);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "impact",
+            "--lang",
+            "rust",
+            "-f",
+            "json",
+            "--depth-histogram",
+        ])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(assert.get_output().stderr.as_ref());
+    assert!(
+        stderr.contains("depth 1: 1 symbol(s) in 1 file(s)\ndepth 2: 1 symbol(s) in 1 file(s)\n"),
+        "{stderr}"
+    );
+    // stdout stays plain JSON
+    json_output::parse_payload_slice(assert.get_output().stdout.as_ref());
+}
@@ -57,3 +57,47 @@ fn edges_are_included_when_flag_set() {
     assert!(v["edges"].is_array());
     assert!(!v["edges"].as_array().unwrap().is_empty());
 }
+
+#[test]
+fn edge_kind_restricts_traversal_to_selected_reference_kinds() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    fs::write(
+        repo.join("main.rs"),
+        "struct Coord { x: i32 }\nfn origin(c: Coord) -> i32 { c.x }\nfn run() { helper(); }\nfn helper() {}\n",
+    )
+    .unwrap();
+
+    let impacted = |extra: &[&str]| -> Vec<String> {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(repo)
+            .env("DIMPACT_CACHE_DIR", repo.join(".cache"))
+            .args([
+                "impact",
+                "--seed-symbol",
+                "rust:main.rs:struct:Coord:1",
+                "--seed-symbol",
+                "rust:main.rs:fn:helper:4",
+                "-f",
+                "json",
+            ])
+            .args(extra)
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+        let v = json_output::parse_payload(&stdout);
+        let mut names: Vec<String> = v["impacted_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(impacted(&[]), vec!["origin", "run"]);
+    assert_eq!(impacted(&["--edge-kind", "call"]), vec!["run"]);
+    assert_eq!(impacted(&["--edge-kind", "type-use"]), vec!["origin"]);
+}
@@ -0,0 +1,94 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+fn impact_names(repo: &std::path::Path, diff: &str, extra: &[&str]) -> (Vec<String>, Vec<String>) {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(repo)
+        .env("DIMPACT_CACHE_DIR", repo.join(".dimpact-test-cache"))
+        .arg("impact")
+        .arg("--direction")
+        .arg("callers")
+        .args(extra)
+        .arg("--format")
+        .arg("json")
+        .write_stdin(diff.to_string());
+    let assert = cmd.assert().success();
+    let v: serde_json::Value = serde_json::from_slice(assert.get_output().stdout.as_ref()).unwrap();
+    let names = |key: &str| {
+        let mut names: Vec<String> = v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    (names("changed_symbols"), names("impacted_symbols"))
+}
+
+#[test]
+fn graph_base_sees_callers_deleted_by_the_change() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    fs::write(
+        repo.join("main.rs"),
+        "fn leaf() {\n    let _x = 1;\n}\n\nfn caller() {\n    leaf();\n}\n\nfn top() {\n    caller();\n}\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+
+    // Change leaf and delete the caller that linked it to top.
+    fs::write(
+        repo.join("main.rs"),
+        "fn leaf() {\n    let _x = 2;\n}\n\nfn top() {\n    caller();\n}\n",
+    )
+    .unwrap();
+    let diff =
+        String::from_utf8(git(repo, &["diff", "--no-ext-diff", "--unified=0"]).stdout).unwrap();
+
+    // The working tree no longer knows about `caller`.
+    let (changed, impacted) = impact_names(repo, &diff, &[]);
+    assert!(!changed.contains(&"caller".to_string()));
+    assert!(!impacted.contains(&"caller".to_string()));
+
+    let (changed, impacted) = impact_names(repo, &diff, &["--graph-base", "HEAD"]);
+    assert_eq!(changed, vec!["caller".to_string(), "leaf".to_string()]);
+    assert_eq!(impacted, vec!["top".to_string()]);
+}
+
+#[test]
+fn graph_base_rejects_unknown_treeish() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(repo)
+        .arg("impact")
+        .arg("--graph-base")
+        .arg("no-such-rev")
+        .write_stdin("");
+    cmd.assert().failure();
+}
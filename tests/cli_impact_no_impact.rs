@@ -0,0 +1,99 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+const BEFORE: &str = "// helpers\n\nfn leaf() {}\n\nfn top() {\n    leaf();\n}\n";
+
+fn setup_repo(after: &str) -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    git(&path, &["init", "-q"]);
+    git(&path, &["config", "user.email", "tester@example.com"]);
+    git(&path, &["config", "user.name", "Tester"]);
+    fs::write(path.join("main.rs"), BEFORE).unwrap();
+    git(&path, &["add", "."]);
+    git(&path, &["commit", "-m", "init", "-q"]);
+    fs::write(path.join("main.rs"), after).unwrap();
+    (dir, path)
+}
+
+fn diff_text(repo: &std::path::Path) -> String {
+    let out = git(repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn comment_only_change_reports_no_impact() {
+    let (_tmp, repo) = setup_repo(&BEFORE.replace("// helpers", "// shared helpers"));
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["--mode", "impact", "--format", "json"])
+        .write_stdin(diff_text(&repo))
+        .assert()
+        .success();
+    let output = assert.get_output();
+    let v = json_output::parse_payload_slice(output.stdout.as_ref());
+    let summary = &v["summary"];
+    assert_eq!(summary["has_impact"], false);
+    assert_eq!(summary["changed_symbol_count"], 0);
+    assert_eq!(summary["impacted_symbol_count"], 0);
+    assert_eq!(summary["impacted_file_count"], 0);
+    let stderr = String::from_utf8_lossy(output.stderr.as_ref());
+    assert!(stderr.contains("no symbols impacted"), "{stderr}");
+}
+
+#[test]
+fn no_impact_exit_code_is_configurable() {
+    let (_tmp, repo) = setup_repo(&BEFORE.replace("// helpers", "// shared helpers"));
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--no-impact-exit-code", "3", "--format", "json"])
+        .write_stdin(diff_text(&repo))
+        .assert()
+        .code(3);
+    // The report is still written before exiting
+    let v = json_output::parse_payload_slice(assert.get_output().stdout.as_ref());
+    assert_eq!(v["summary"]["has_impact"], false);
+}
+
+#[test]
+fn impacted_change_reports_counts() {
+    let (_tmp, repo) = setup_repo(&BEFORE.replace("fn leaf() {}", "fn leaf() { let _x = 1; }"));
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--no-impact-exit-code", "3", "--format", "json"])
+        .write_stdin(diff_text(&repo))
+        .assert()
+        .success();
+    let output = assert.get_output();
+    let v = json_output::parse_payload_slice(output.stdout.as_ref());
+    let summary = &v["summary"];
+    assert_eq!(summary["has_impact"], true);
+    assert_eq!(summary["changed_symbol_count"], 1);
+    assert_eq!(summary["impacted_symbol_count"], 1);
+    assert_eq!(summary["impacted_file_count"], 1);
+    let stderr = String::from_utf8_lossy(output.stderr.as_ref());
+    assert!(!stderr.contains("no symbols impacted"), "{stderr}");
+}
@@ -168,10 +168,8 @@ fn cli_impact_min_confidence_filters_inferred_edges() {
         .success();
     let v2 = json_output::parse_payload_slice(strict_assert.get_output().stdout.as_ref());
     let impacted2 = v2["impacted_symbols"].as_array().unwrap();
-    let edges2 = v2["edges"].as_array().unwrap();
-
     assert!(impacted2.is_empty());
-    assert!(edges2.is_empty());
+    assert!(v2.get("edges").is_none());
 }
 
 #[test]
@@ -371,9 +369,8 @@ fn cli_impact_confidence_fixture_compares_confirmed_vs_inferred() {
     assert!(confirmed_impacted.is_empty());
 
     let inferred_edges = inferred_v["edges"].as_array().unwrap();
-    let confirmed_edges = confirmed_v["edges"].as_array().unwrap();
     assert!(!inferred_edges.is_empty());
-    assert!(confirmed_edges.is_empty());
+    assert!(confirmed_v.get("edges").is_none());
 }
 
 #[test]
@@ -434,9 +431,7 @@ fn cli_impact_op_profile_balanced_matches_min_confidence_inferred() {
         .collect();
     assert_eq!(prof_impacted, explicit_impacted);
 
-    let prof_edges = prof_v["edges"].as_array().unwrap();
-    let explicit_edges = explicit_v["edges"].as_array().unwrap();
-    assert_eq!(prof_edges.len(), explicit_edges.len());
+    assert_eq!(prof_v.get("edges"), explicit_v.get("edges"));
 
     let cf = &prof_v["confidence_filter"];
     assert_eq!(cf["min_confidence"], "inferred");
@@ -502,11 +497,86 @@ fn cli_impact_op_profile_precision_first_matches_confirmed_plus_exclude_dynamic_
         .collect();
     assert_eq!(prof_impacted, explicit_impacted);
 
-    let prof_edges = prof_v["edges"].as_array().unwrap();
-    let explicit_edges = explicit_v["edges"].as_array().unwrap();
-    assert_eq!(prof_edges.len(), explicit_edges.len());
+    assert_eq!(prof_v.get("edges"), explicit_v.get("edges"));
 
     let cf = &prof_v["confidence_filter"];
     assert_eq!(cf["min_confidence"], "confirmed");
     assert_eq!(cf["exclude_dynamic_fallback"], true);
 }
+
+#[test]
+fn cli_impact_lang_list_restricts_analysis_to_those_languages() {
+    let dir = TempDir::new().expect("tempdir");
+    let repo = dir.path().to_path_buf();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "tester@example.com"]);
+    git(&repo, &["config", "user.name", "Tester"]);
+    fs::write(repo.join("main.rs"), "fn c() {}\nfn b() { c(); }\n").unwrap();
+    fs::write(repo.join("lib.rb"), "def c\nend\n\ndef b\n  c\nend\n").unwrap();
+    fs::write(
+        repo.join("app.py"),
+        "def c():\n    pass\n\ndef b():\n    c()\n",
+    )
+    .unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-m", "init", "-q"]);
+    fs::write(
+        repo.join("main.rs"),
+        "fn c() { let _k = 1; }\nfn b() { c(); }\n",
+    )
+    .unwrap();
+    fs::write(repo.join("lib.rb"), "def c\n  1\nend\n\ndef b\n  c\nend\n").unwrap();
+    fs::write(
+        repo.join("app.py"),
+        "def c():\n    return 1\n\ndef b():\n    c()\n",
+    )
+    .unwrap();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let languages = |v: &serde_json::Value, key: &str| -> std::collections::BTreeSet<String> {
+        v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["language"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let expected: std::collections::BTreeSet<String> =
+        ["ruby", "rust"].iter().map(|s| s.to_string()).collect();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--lang", "rust,ruby", "--format", "json"])
+        .write_stdin(diff.clone())
+        .assert()
+        .success();
+    let v = json_output::parse_payload_slice(assert.get_output().stdout.as_ref());
+    assert_eq!(languages(&v, "changed_symbols"), expected);
+    assert_eq!(languages(&v, "impacted_symbols"), expected);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args([
+            "changed", "--lang", "rust", "--lang", "ruby", "--format", "json",
+        ])
+        .write_stdin(diff.clone())
+        .assert()
+        .success();
+    let v = json_output::parse_payload_slice(assert.get_output().stdout.as_ref());
+    assert_eq!(languages(&v, "changed_symbols"), expected);
+    assert_eq!(v["changed_files"], serde_json::json!(["lib.rb", "main.rs"]));
+
+    // Without --lang every language is analyzed
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--format", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let v = json_output::parse_payload_slice(assert.get_output().stdout.as_ref());
+    assert!(languages(&v, "changed_symbols").contains("python"));
+}
@@ -0,0 +1,68 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+#[test]
+fn attr_accessor_change_reaches_readers_in_other_files() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path().to_path_buf();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "tester@example.com"]);
+    git(&repo, &["config", "user.name", "Tester"]);
+    fs::write(
+        repo.join("person.rb"),
+        "class Person\n  attr_accessor :age\n  attr_reader :name\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        repo.join("greet.rb"),
+        "def greet(person)\n  puts person.name\nend\n\ndef birthday(person)\n  person.age\nend\n",
+    )
+    .unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-m", "init", "-q"]);
+    fs::write(
+        repo.join("person.rb"),
+        "class Person\n  attr_accessor :age\n  attr_reader :name, :nick\nend\n",
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["impact", "--engine", "ts", "-f", "json"])
+        .write_stdin(String::from_utf8(diff.stdout).unwrap())
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let names = |key: &str| -> Vec<String> {
+        v[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let changed = names("changed_symbols");
+    assert!(changed.contains(&"name".to_string()), "{changed:?}");
+    assert!(!changed.contains(&"age".to_string()), "{changed:?}");
+    assert_eq!(names("impacted_symbols"), vec!["greet"]);
+}
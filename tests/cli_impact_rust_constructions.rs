@@ -0,0 +1,94 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+const BEFORE: &str = r#"struct Point {
+    x: i32,
+}
+
+enum Shape {
+    Dot,
+    Square(i32),
+}
+
+fn origin() -> i32 {
+    let p = Point { x: 0 };
+    p.x
+}
+
+fn side(s: &Shape) -> i32 {
+    match s {
+        Shape::Dot => 0,
+        Shape::Square(n) => *n,
+    }
+}
+
+fn main() {
+    let _ = origin();
+}
+"#;
+
+fn impacted_names(repo: &std::path::Path, diff: &str, extra: &[&str]) -> Vec<String> {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(repo)
+        .args(["impact", "--engine", "ts", "--lang", "rust", "-f", "json"])
+        .args(extra)
+        .write_stdin(diff.to_string())
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let mut names: Vec<String> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn construction_edges_are_opt_in() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path().to_path_buf();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "tester@example.com"]);
+    git(&repo, &["config", "user.name", "Tester"]);
+    fs::write(repo.join("main.rs"), BEFORE).unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-m", "init", "-q"]);
+    fs::write(
+        repo.join("main.rs"),
+        BEFORE
+            .replace("    x: i32,\n", "    x: i32,\n    y: i32,\n")
+            .replace("    Dot,\n", "    Dot,\n    Line(i32),\n"),
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff.stdout).unwrap();
+
+    // `side` names Shape in its signature, so it is impacted either way
+    assert_eq!(impacted_names(&repo, &diff, &[]), vec!["side".to_string()]);
+    assert_eq!(
+        impacted_names(&repo, &diff, &["--include-constructions"]),
+        vec!["main".to_string(), "origin".to_string(), "side".to_string()]
+    );
+}
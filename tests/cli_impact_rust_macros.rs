@@ -0,0 +1,86 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+const BEFORE: &str = r#"macro_rules! square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+
+#[derive(Debug)]
+struct Area(i32);
+
+fn area() -> i32 {
+    square!(3)
+}
+
+fn main() {
+    let a = area();
+    println!("{a}");
+}
+"#;
+
+fn impacted_names(repo: &std::path::Path, diff: &str, extra: &[&str]) -> Vec<String> {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(repo)
+        .args(["impact", "--engine", "ts", "--lang", "rust", "-f", "json"])
+        .args(extra)
+        .write_stdin(diff.to_string())
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let mut names: Vec<String> = v["impacted_symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn macro_edges_are_opt_in() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path().to_path_buf();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "tester@example.com"]);
+    git(&repo, &["config", "user.name", "Tester"]);
+    fs::write(repo.join("main.rs"), BEFORE).unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-m", "init", "-q"]);
+    fs::write(
+        repo.join("main.rs"),
+        BEFORE.replace("$x * $x", "$x * $x * 1"),
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff.stdout).unwrap();
+
+    assert!(impacted_names(&repo, &diff, &[]).is_empty());
+    assert_eq!(
+        impacted_names(&repo, &diff, &["--include-macros"]),
+        vec!["area".to_string(), "main".to_string()]
+    );
+    // the cached graph was built with macros; turning them off rebuilds it
+    assert!(impacted_names(&repo, &diff, &[]).is_empty());
+}
@@ -152,8 +152,9 @@ fn json_compat_restore_fixtures_capture_pre_envelope_top_level_shapes() {
             "changed_symbols".to_string(),
             "impacted_symbols".to_string(),
             "impacted_files".to_string(),
-            "edges".to_string(),
+            "edges_included".to_string(),
             "impacted_by_file".to_string(),
+            "changed_by_file".to_string(),
             "impacted_witnesses".to_string(),
             "summary".to_string(),
         ]),
@@ -0,0 +1,122 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+// App.kt imports the top-level `slug` from package com.acme.util; another
+// `slug` lives in com.acme.other. Neither directory matches its package, so
+// only the package declarations tell them apart.
+fn setup_repo_kotlin_packages() -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    git(&path, &["init", "-q"]);
+    git(&path, &["config", "user.email", "tester@example.com"]);
+    git(&path, &["config", "user.name", "Tester"]);
+
+    fs::create_dir_all(path.join("src/main/kotlin/app")).unwrap();
+    fs::create_dir_all(path.join("src/main/kotlin/text")).unwrap();
+    fs::create_dir_all(path.join("legacy")).unwrap();
+    fs::write(
+        path.join("src/main/kotlin/text/Strings.kt"),
+        "package com.acme.util\n\nfun slug(s: String): String {\n    return s.lowercase()\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("legacy/Helpers.kt"),
+        "package com.acme.other\n\nfun slug(s: String): String {\n    return s\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("src/main/kotlin/app/Repo.kt"),
+        "package com.acme.app\n\nclass Repo {\n    fun find(name: String): String {\n        return name\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("src/main/kotlin/app/App.kt"),
+        "package com.acme.app\n\nimport com.acme.util.slug\n\nclass App(private val repo: Repo) {\n    fun run(name: String): String {\n        return slug(repo.find(name))\n    }\n}\n",
+    )
+    .unwrap();
+    git(&path, &["add", "."]);
+    git(&path, &["commit", "-m", "init", "-q"]);
+    (dir, path)
+}
+
+fn impact_callers(repo: &std::path::Path, diff: String) -> serde_json::Value {
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(repo)
+        .arg("impact")
+        .arg("--direction")
+        .arg("callers")
+        .arg("--format")
+        .arg("json")
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    json_output::parse_payload(&stdout)
+}
+
+fn names(v: &serde_json::Value, key: &str) -> Vec<String> {
+    v[key]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|s| s["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn impact_resolves_kotlin_imports_by_package_and_receiver_type() {
+    let (_tmp, repo) = setup_repo_kotlin_packages();
+    fs::write(
+        repo.join("src/main/kotlin/text/Strings.kt"),
+        "package com.acme.util\n\nfun slug(s: String): String {\n    return s.trim().lowercase()\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        repo.join("src/main/kotlin/app/Repo.kt"),
+        "package com.acme.app\n\nclass Repo {\n    fun find(name: String): String {\n        return name.trim()\n    }\n}\n",
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let v = impact_callers(&repo, String::from_utf8(diff.stdout).unwrap());
+
+    let mut changed = names(&v, "changed_symbols");
+    changed.sort();
+    assert_eq!(changed, vec!["Repo", "find", "slug"]);
+    assert_eq!(names(&v, "impacted_symbols"), vec!["run"]);
+}
+
+#[test]
+fn impact_ignores_same_named_kotlin_function_in_another_package() {
+    let (_tmp, repo) = setup_repo_kotlin_packages();
+    fs::write(
+        repo.join("legacy/Helpers.kt"),
+        "package com.acme.other\n\nfun slug(s: String): String {\n    return s.trim()\n}\n",
+    )
+    .unwrap();
+    let diff = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let v = impact_callers(&repo, String::from_utf8(diff.stdout).unwrap());
+
+    assert_eq!(names(&v, "changed_symbols"), vec!["slug"]);
+    assert!(names(&v, "impacted_symbols").is_empty());
+}
@@ -0,0 +1,121 @@
+#![allow(deprecated)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+#[test]
+fn metrics_ranks_by_in_degree_and_honors_top() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    fs::write(
+        repo.join("main.rs"),
+        "fn hub() {}\nfn a() { hub(); }\nfn b() { hub(); a(); }\nfn main() { a(); b(); hub(); }\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(repo)
+        .arg("metrics")
+        .arg("--top")
+        .arg("2")
+        .arg("--format")
+        .arg("json");
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let rows = v.as_array().expect("metrics array");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["name"], "hub");
+    assert_eq!(rows[0]["in_degree"], 3);
+    assert_eq!(rows[0]["out_degree"], 0);
+    assert_eq!(rows[1]["name"], "a");
+    assert_eq!(rows[1]["in_degree"], 2);
+}
+
+#[test]
+fn jobs_flag_does_not_change_scan_results() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    for i in 0..8 {
+        fs::write(
+            repo.join(format!("m{i}.rs")),
+            format!("fn f{i}() {{ g{i}(); }}\nfn g{i}() {{}}\n"),
+        )
+        .unwrap();
+    }
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+
+    let run = |cache_dir: &str, jobs: Option<&str>| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        cmd.current_dir(repo)
+            .env("DIMPACT_CACHE_DIR", repo.join(cache_dir))
+            .arg("metrics");
+        if let Some(j) = jobs {
+            cmd.arg("--jobs").arg(j);
+        }
+        let assert = cmd.assert().success();
+        String::from_utf8_lossy(assert.get_output().stdout.as_ref()).to_string()
+    };
+    let default_out = run(".cache-default", None);
+    let single_out = run(".cache-single", Some("1"));
+    assert_eq!(default_out, single_out);
+}
+
+#[test]
+fn metrics_sees_edits_made_between_runs() {
+    let dir = TempDir::new().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    git(repo, &["config", "user.email", "tester@example.com"]);
+    git(repo, &["config", "user.name", "Tester"]);
+    fs::write(repo.join("main.rs"), "fn hub() {}\nfn a() { hub(); }\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-m", "init", "-q"]);
+
+    let hub_in_degree = || {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(repo)
+            .args(["metrics", "--format", "json"])
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+        let v: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let rows = v.as_array().expect("metrics array");
+        let hub = rows.iter().find(|r| r["name"] == "hub").expect("hub row");
+        hub["in_degree"].as_u64().unwrap()
+    };
+    assert_eq!(hub_in_degree(), 1);
+
+    fs::write(
+        repo.join("main.rs"),
+        "fn hub() {}\nfn a() { hub(); }\nfn b() { hub(); }\n",
+    )
+    .unwrap();
+    assert_eq!(hub_in_degree(), 2);
+}
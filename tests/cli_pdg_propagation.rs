@@ -1722,6 +1722,48 @@ fn pdg_propagation_adds_var_to_callee_edge() {
     assert!(stdout.contains("\"rust:f.rs:fn:callee:1\""));
 }
 
+#[test]
+fn via_dataflow_matches_with_propagation() {
+    let (_tmp, repo) = setup_repo();
+    let diff_out = git(&repo, &["diff", "--no-ext-diff", "--unified=0"]);
+    let diff = String::from_utf8(diff_out.stdout).unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(&repo)
+            .args([
+                "impact",
+                "--engine",
+                "ts",
+                "--with-edges",
+                "--format",
+                "json",
+            ])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        json_output::parse_payload(&String::from_utf8_lossy(
+            assert.get_output().stdout.as_ref(),
+        ))
+    };
+
+    let via = run(&["--via", "dataflow", "--direction", "callees"]);
+    assert_eq!(via, run(&["--with-propagation", "--direction", "callees"]));
+    assert_ne!(via, run(&["--via", "call-graph", "--direction", "callees"]));
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["impact", "--via", "dataflow", "--api-only"])
+        .write_stdin(diff)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--api-only cannot be used with --via dataflow",
+        ));
+}
+
 #[test]
 fn pdg_path_assigns_confirmed_or_inferred_confidence_only() {
     let (_tmp, repo) = setup_repo();
@@ -3921,8 +3963,9 @@ fn ruby_chain_fixture_only_gains_symbolic_edges_with_propagation() {
         ],
     );
 
-    assert_eq!(baseline["edges"].as_array().unwrap().len(), 0);
-    assert_eq!(pdg["edges"].as_array().unwrap().len(), 0);
+    assert!(baseline.get("edges").is_none());
+    assert!(pdg.get("edges").is_none());
+    assert_eq!(baseline["edges_included"], true);
 
     let prop_edges = prop["edges"].as_array().expect("edges array");
     assert_eq!(
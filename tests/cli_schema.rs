@@ -69,7 +69,16 @@ fn changed_diff_and_id_schemas_are_concrete_and_model_payload_shapes() {
     assert_eq!(
         changed.pointer("/$defs/symbol/properties/kind/enum"),
         Some(&serde_json::json!([
-            "function", "method", "struct", "enum", "trait", "module"
+            "function",
+            "method",
+            "struct",
+            "enum",
+            "trait",
+            "module",
+            "const",
+            "static",
+            "type_alias",
+            "macro"
         ]))
     );
 
@@ -141,7 +150,15 @@ fn impact_default_schema_is_concrete_and_models_summary_only_shape() {
     );
     assert_eq!(
         value.pointer("/$defs/impact_summary/required"),
-        Some(&serde_json::json!(["by_depth", "affected_modules", "risk"]))
+        Some(&serde_json::json!([
+            "has_impact",
+            "changed_symbol_count",
+            "impacted_symbol_count",
+            "impacted_file_count",
+            "by_depth",
+            "affected_modules",
+            "risk"
+        ]))
     );
     assert!(
         value
@@ -193,7 +210,7 @@ fn impact_variant_schemas_are_concrete_and_capture_profile_specific_constraints(
     let pdg = fetch_schema_document("dimpact:json/v1/impact/default/summary_only/pdg");
     assert_eq!(
         pdg.pointer("/$defs/ref_kind/enum"),
-        Some(&serde_json::json!(["call", "data", "control"]))
+        Some(&serde_json::json!(["call", "data", "control", "type_use", "inherit"]))
     );
     assert_eq!(
         pdg.pointer("/$defs/edge_provenance/enum"),
@@ -202,6 +219,10 @@ fn impact_variant_schemas_are_concrete_and_capture_profile_specific_constraints(
     assert_eq!(
         pdg.pointer("/$defs/impact_summary/required"),
         Some(&serde_json::json!([
+            "has_impact",
+            "changed_symbol_count",
+            "impacted_symbol_count",
+            "impacted_file_count",
             "by_depth",
             "affected_modules",
             "risk",
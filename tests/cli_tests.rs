@@ -0,0 +1,109 @@
+#![allow(deprecated)]
+use std::fs;
+use tempfile::TempDir;
+
+const LIB: &str = "pub fn core() {}\npub fn wrapper() { core(); }\nfn other() {}\n#[test]\nfn checks_wrapper() { wrapper(); }\nfn test_other() { other(); }\n";
+
+fn setup() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), LIB).unwrap();
+    fs::write(
+        dir.path().join("tests/it.rs"),
+        "fn end_to_end() { core(); }\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn diff_for(lines: &[(u32, &str, &str)]) -> String {
+    let mut diff =
+        String::from("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n");
+    for (line, old, new) in lines {
+        diff.push_str(&format!("@@ -{line} +{line} @@\n-{old}\n+{new}\n"));
+    }
+    diff
+}
+
+#[test]
+fn tests_lists_tests_reaching_changed_symbols() {
+    let dir = setup();
+    let diff = diff_for(&[(1, "pub fn core() {}", "pub fn core() {}")]);
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["tests", "-f", "json"])
+        .write_stdin(diff)
+        .assert()
+        .success();
+    let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let tests: Vec<(String, Vec<String>)> = v
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            let reached = t["reached_from"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| id.as_str().unwrap().split(':').nth(3).unwrap().to_string())
+                .collect();
+            (t["test"]["name"].as_str().unwrap().to_string(), reached)
+        })
+        .collect();
+    assert_eq!(
+        tests,
+        vec![
+            ("checks_wrapper".to_string(), vec!["core".to_string()]),
+            ("end_to_end".to_string(), vec!["core".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn tests_text_output_names_the_changes() {
+    let dir = setup();
+    let diff = diff_for(&[
+        (
+            2,
+            "pub fn wrapper() { core(); }",
+            "pub fn wrapper() { core(); }",
+        ),
+        (3, "fn other() {}", "fn other() {}"),
+    ]);
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(dir.path())
+        .args(["tests", "-f", "text"])
+        .write_stdin(diff)
+        .assert()
+        .success()
+        .stdout("fn checks_wrapper  src/lib.rs:5  <- wrapper\nfn test_other  src/lib.rs:6  <- other\n2 impacted test(s)\n");
+}
+
+#[test]
+fn impact_exclude_tests_drops_test_callers() {
+    let dir = setup();
+    let diff = diff_for(&[(1, "pub fn core() {}", "pub fn core() {}")]);
+    let names = |extra: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+        let assert = cmd
+            .current_dir(dir.path())
+            .args(["impact", "-f", "json"])
+            .args(extra)
+            .write_stdin(diff.clone())
+            .assert()
+            .success();
+        let v: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+        let mut names: Vec<String> = v["impacted_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&[]), vec!["checks_wrapper", "end_to_end", "wrapper"]);
+    assert_eq!(names(&["--exclude-tests"]), vec!["wrapper"]);
+}
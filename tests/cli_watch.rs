@@ -0,0 +1,256 @@
+#![allow(deprecated)]
+mod json_output;
+
+use std::fs;
+use std::io::BufRead;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn git(cwd: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    let out = cmd.output().expect("git command failed to spawn");
+    if !out.status.success() {
+        panic!(
+            "git {:?} failed: status {:?}\nstdout:{}\nstderr:{}",
+            args,
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    out
+}
+
+fn setup_repo() -> (TempDir, std::path::PathBuf) {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    git(&path, &["init", "-q"]);
+    git(&path, &["config", "user.email", "tester@example.com"]);
+    git(&path, &["config", "user.name", "Tester"]);
+    fs::create_dir_all(path.join("src")).unwrap();
+    fs::write(
+        path.join("src/lib.rs"),
+        "fn leaf() {}\n\nfn mid() {\n    leaf();\n}\n\nfn top() {\n    mid();\n}\n",
+    )
+    .unwrap();
+    fs::write(path.join("src/other.rs"), "fn other() {}\n").unwrap();
+    git(&path, &["add", "."]);
+    git(&path, &["commit", "-m", "init", "-q"]);
+    (dir, path)
+}
+
+fn names(v: &serde_json::Value, key: &str) -> Vec<String> {
+    let mut out: Vec<String> = v[key]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|s| s["name"].as_str().map(str::to_string))
+        .collect();
+    out.sort();
+    out
+}
+
+#[test]
+fn watch_prints_initial_report_for_worktree_diff() {
+    let (_tmp, repo) = setup_repo();
+    fs::write(
+        repo.join("src/lib.rs"),
+        "fn leaf() { let _x = 1; }\n\nfn mid() {\n    leaf();\n}\n\nfn top() {\n    mid();\n}\n",
+    )
+    .unwrap();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    let assert = cmd
+        .current_dir(&repo)
+        .args(["watch", "--max-runs", "1", "--format", "json"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(assert.get_output().stdout.as_ref());
+    let v = json_output::parse_payload(&stdout);
+    assert!(names(&v, "changed_symbols").contains(&"leaf".to_string()));
+    assert!(names(&v, "impacted_symbols").contains(&"top".to_string()));
+}
+
+#[test]
+fn watch_reruns_after_a_source_file_changes() {
+    let (_tmp, repo) = setup_repo();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("dimpact"))
+        .current_dir(&repo)
+        .args([
+            "watch",
+            "--changed-only",
+            "--compact",
+            "--max-runs",
+            "2",
+            "--debounce-ms",
+            "100",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn dimpact watch");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            let _ = tx.send(line);
+        }
+    });
+
+    let first = rx
+        .recv_timeout(Duration::from_secs(60))
+        .expect("initial report");
+    let v = json_output::parse_payload(&first);
+    assert!(names(&v, "changed_symbols").is_empty());
+
+    // Give the watcher a moment to settle, then save a change
+    std::thread::sleep(Duration::from_millis(300));
+    fs::write(
+        repo.join("src/other.rs"),
+        "fn other() {\n    let _y = 2;\n}\n",
+    )
+    .unwrap();
+
+    let second = match rx.recv_timeout(Duration::from_secs(60)) {
+        Ok(line) => line,
+        Err(_) => {
+            let _ = child.kill();
+            panic!("watch did not re-run after the file change");
+        }
+    };
+    let v = json_output::parse_payload(&second);
+    assert_eq!(names(&v, "changed_symbols"), vec!["other"]);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn watch_rewrites_the_output_file_on_each_run() {
+    let (_tmp, repo) = setup_repo();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("dimpact"))
+        .current_dir(&repo)
+        .args([
+            "watch",
+            "--changed-only",
+            "--compact",
+            "--max-runs",
+            "2",
+            "--debounce-ms",
+            "100",
+            "--output",
+            "out/report.json",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn dimpact watch");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stderr = child.stderr.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stderr).lines() {
+            let Ok(line) = line else { break };
+            if line.starts_with("watch: run=") {
+                let _ = tx.send(line);
+            }
+        }
+    });
+
+    rx.recv_timeout(Duration::from_secs(60))
+        .expect("initial report");
+    std::thread::sleep(Duration::from_millis(300));
+    fs::write(
+        repo.join("src/other.rs"),
+        "fn other() {\n    let _y = 2;\n}\n",
+    )
+    .unwrap();
+    if rx.recv_timeout(Duration::from_secs(60)).is_err() {
+        let _ = child.kill();
+        panic!("watch did not re-run after the file change");
+    }
+    assert!(child.wait().unwrap().success());
+
+    let written = fs::read_to_string(repo.join("out/report.json")).unwrap();
+    assert_eq!(written.lines().count(), 1, "{written}");
+    let v = json_output::parse_payload(&written);
+    assert_eq!(names(&v, "changed_symbols"), vec!["other"]);
+}
+
+#[test]
+fn watch_rereads_diff_files_on_each_run() {
+    let (_tmp, repo) = setup_repo();
+    let patch = |file: &str, body: &str| {
+        format!(
+            "diff --git a/{file} b/{file}\n--- a/{file}\n+++ b/{file}\n@@ -1,1 +1,1 @@\n-{body}\n+{body}\n"
+        )
+    };
+    fs::write(
+        repo.join("change.diff"),
+        patch("src/lib.rs", "fn leaf() {}"),
+    )
+    .unwrap();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("dimpact"))
+        .current_dir(&repo)
+        .args([
+            "watch",
+            "--changed-only",
+            "--compact",
+            "--max-runs",
+            "2",
+            "--debounce-ms",
+            "100",
+            "--diff",
+            "change.diff",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn dimpact watch");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            let _ = tx.send(line);
+        }
+    });
+
+    let first = rx
+        .recv_timeout(Duration::from_secs(60))
+        .expect("initial report");
+    let v = json_output::parse_payload(&first);
+    assert_eq!(names(&v, "changed_symbols"), vec!["leaf"]);
+
+    // Point the patch at another file, then save a source file to trigger a run
+    std::thread::sleep(Duration::from_millis(300));
+    fs::write(
+        repo.join("change.diff"),
+        patch("src/other.rs", "fn other() {}"),
+    )
+    .unwrap();
+    fs::write(repo.join("src/other.rs"), "fn other() {}\n\n").unwrap();
+
+    let second = match rx.recv_timeout(Duration::from_secs(60)) {
+        Ok(line) => line,
+        Err(_) => {
+            let _ = child.kill();
+            panic!("watch did not re-run after the file change");
+        }
+    };
+    let v = json_output::parse_payload(&second);
+    assert_eq!(names(&v, "changed_symbols"), vec!["other"]);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn watch_rejects_a_diff_on_stdin() {
+    let (_tmp, repo) = setup_repo();
+    let mut cmd = assert_cmd::Command::cargo_bin("dimpact").unwrap();
+    cmd.current_dir(&repo)
+        .args(["watch", "--max-runs", "1", "--diff", "-"])
+        .write_stdin("")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot take it from stdin"));
+}
@@ -779,6 +779,9 @@ fn lsp_engine_falls_back_changed() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
 
@@ -805,6 +808,9 @@ fn lsp_engine_falls_back_impact_callers() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Auto, cfg);
     let opts = dimpact::ImpactOptions {
@@ -812,6 +818,13 @@ fn lsp_engine_falls_back_impact_callers() {
         max_depth: Some(100),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -839,6 +852,9 @@ fn lsp_engine_strict_errors() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
 
@@ -869,6 +885,9 @@ fn lsp_engine_strict_mock_succeeds() {
         dump_capabilities: true,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
 
@@ -882,6 +901,13 @@ fn lsp_engine_strict_mock_succeeds() {
         max_depth: Some(100),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let out = engine
         .impact(&files, dimpact::LanguageMode::Rust, &opts)
@@ -914,6 +940,9 @@ fn lsp_engine_strict_errors_when_caps_missing() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
 
@@ -951,6 +980,9 @@ fn lsp_engine_strict_impact_errors_when_caps_missing() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -958,6 +990,13 @@ fn lsp_engine_strict_impact_errors_when_caps_missing() {
         max_depth: Some(1),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -991,6 +1030,9 @@ fn lsp_engine_strict_impact_from_symbols_errors_when_caps_missing() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let changed = vec![dimpact::Symbol {
@@ -1009,6 +1051,13 @@ fn lsp_engine_strict_impact_from_symbols_errors_when_caps_missing() {
         max_depth: Some(3),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1022,7 +1071,7 @@ fn lsp_engine_strict_impact_from_symbols_errors_when_caps_missing() {
     assert!(msg.contains("impact_from_symbols capability missing"));
     assert!(msg.contains("language=Rust"));
     assert!(msg.contains("direction=Callees"));
-    assert!(msg.contains("required=call_hierarchy or (definition/references)"));
+    assert!(msg.contains("required=call_hierarchy or definition"));
 }
 
 #[test]
@@ -1036,6 +1085,9 @@ fn lsp_engine_non_strict_impact_from_symbols_falls_back_when_lsp_unavailable() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let changed = vec![dimpact::Symbol {
@@ -1054,6 +1106,13 @@ fn lsp_engine_non_strict_impact_from_symbols_falls_back_when_lsp_unavailable() {
         max_depth: Some(3),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1082,6 +1141,9 @@ fn lsp_engine_strict_mock_typescript_callers_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1089,6 +1151,13 @@ fn lsp_engine_strict_mock_typescript_callers_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1121,6 +1190,9 @@ fn lsp_engine_strict_mock_tsx_callers_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1128,6 +1200,13 @@ fn lsp_engine_strict_mock_tsx_callers_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1160,6 +1239,9 @@ fn lsp_engine_strict_mock_typescript_callees_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1167,6 +1249,13 @@ fn lsp_engine_strict_mock_typescript_callees_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1200,6 +1289,9 @@ fn lsp_engine_strict_mock_javascript_callers_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1207,6 +1299,13 @@ fn lsp_engine_strict_mock_javascript_callers_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1239,6 +1338,9 @@ fn lsp_engine_strict_mock_javascript_both_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1246,6 +1348,13 @@ fn lsp_engine_strict_mock_javascript_both_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1279,6 +1388,9 @@ fn lsp_engine_strict_mock_ruby_callers_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1286,6 +1398,13 @@ fn lsp_engine_strict_mock_ruby_callers_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -1318,6 +1437,9 @@ fn lsp_engine_strict_mock_go_callers_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1325,6 +1447,13 @@ fn lsp_engine_strict_mock_go_callers_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("go", "main.go", &dimpact::SymbolKind::Function, "bar", 3),
@@ -1383,6 +1512,9 @@ fn lsp_engine_strict_mock_java_callers_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1390,6 +1522,13 @@ fn lsp_engine_strict_mock_java_callers_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("java", "Main.java", &dimpact::SymbolKind::Method, "bar", 2),
@@ -1448,6 +1587,9 @@ fn lsp_engine_strict_mock_java_callees_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1455,6 +1597,13 @@ fn lsp_engine_strict_mock_java_callees_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("java", "Main.java", &dimpact::SymbolKind::Method, "b", 4),
@@ -1512,6 +1661,9 @@ fn lsp_engine_strict_mock_java_both_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1519,6 +1671,13 @@ fn lsp_engine_strict_mock_java_both_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("java", "Main.java", &dimpact::SymbolKind::Method, "foo", 4),
@@ -1635,6 +1794,9 @@ fn lsp_engine_strict_mock_java_callees_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1642,6 +1804,13 @@ fn lsp_engine_strict_mock_java_callees_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("java", "Main.java", &dimpact::SymbolKind::Method, "b", 4),
@@ -1702,6 +1871,9 @@ fn lsp_engine_strict_mock_java_both_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1709,6 +1881,13 @@ fn lsp_engine_strict_mock_java_both_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("java", "Main.java", &dimpact::SymbolKind::Method, "foo", 4),
@@ -1768,6 +1947,9 @@ fn lsp_engine_strict_mock_go_callees_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1775,6 +1957,13 @@ fn lsp_engine_strict_mock_go_callees_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("go", "main.go", &dimpact::SymbolKind::Function, "b", 5),
@@ -1832,6 +2021,9 @@ fn lsp_engine_strict_mock_go_both_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1839,6 +2031,13 @@ fn lsp_engine_strict_mock_go_both_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("go", "main.go", &dimpact::SymbolKind::Function, "foo", 5),
@@ -1897,6 +2096,9 @@ fn lsp_engine_strict_mock_go_callees_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1904,6 +2106,13 @@ fn lsp_engine_strict_mock_go_callees_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("go", "main.go", &dimpact::SymbolKind::Function, "b", 5),
@@ -1964,6 +2173,9 @@ fn lsp_engine_strict_mock_go_both_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -1971,6 +2183,13 @@ fn lsp_engine_strict_mock_go_both_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new("go", "main.go", &dimpact::SymbolKind::Function, "foo", 5),
@@ -2030,6 +2249,9 @@ fn lsp_engine_strict_mock_python_callers_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2037,6 +2259,13 @@ fn lsp_engine_strict_mock_python_callers_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new(
@@ -2105,6 +2334,9 @@ fn lsp_engine_strict_mock_python_callees_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2112,6 +2344,13 @@ fn lsp_engine_strict_mock_python_callees_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new(
@@ -2164,6 +2403,9 @@ fn lsp_engine_strict_mock_python_both_fixture_runs() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2171,6 +2413,13 @@ fn lsp_engine_strict_mock_python_both_fixture_runs() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new(
@@ -2234,6 +2483,9 @@ fn lsp_engine_strict_mock_python_callees_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2241,6 +2493,13 @@ fn lsp_engine_strict_mock_python_callees_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new(
@@ -2307,6 +2566,9 @@ fn lsp_engine_strict_mock_python_both_fixture_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2314,6 +2576,13 @@ fn lsp_engine_strict_mock_python_both_fixture_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
     let changed = vec![dimpact::Symbol {
         id: dimpact::SymbolId::new(
@@ -2377,6 +2646,9 @@ fn lsp_engine_strict_mock_ruby_both_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2384,6 +2656,13 @@ fn lsp_engine_strict_mock_ruby_both_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2422,6 +2701,9 @@ fn lsp_engine_strict_mock_ruby_both_chain_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2429,6 +2711,13 @@ fn lsp_engine_strict_mock_ruby_both_chain_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2467,6 +2756,9 @@ fn lsp_engine_strict_mock_ruby_callees_chain_with_refs_only_caps() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: Some(caps),
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2474,6 +2766,13 @@ fn lsp_engine_strict_mock_ruby_callees_chain_with_refs_only_caps() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2508,6 +2807,9 @@ fn lsp_engine_strict_mock_ruby_method_callers_chain() {
         dump_capabilities: false,
         mock_lsp: true,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2515,6 +2817,13 @@ fn lsp_engine_strict_mock_ruby_method_callers_chain() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2666,6 +2975,9 @@ fn lsp_engine_strict_tsx_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2673,6 +2985,13 @@ fn lsp_engine_strict_tsx_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2721,6 +3040,9 @@ fn lsp_engine_strict_tsx_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2728,6 +3050,13 @@ fn lsp_engine_strict_tsx_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2772,6 +3101,9 @@ fn lsp_engine_strict_tsx_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2779,6 +3111,13 @@ fn lsp_engine_strict_tsx_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2824,6 +3163,9 @@ fn lsp_engine_strict_typescript_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2831,6 +3173,13 @@ fn lsp_engine_strict_typescript_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2879,6 +3228,9 @@ fn lsp_engine_strict_typescript_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2886,6 +3238,13 @@ fn lsp_engine_strict_typescript_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -2930,6 +3289,9 @@ fn lsp_engine_strict_typescript_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -2937,6 +3299,13 @@ fn lsp_engine_strict_typescript_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3012,6 +3381,9 @@ fn lsp_engine_strict_javascript_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3019,6 +3391,13 @@ fn lsp_engine_strict_javascript_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3067,6 +3446,9 @@ fn lsp_engine_strict_javascript_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3074,6 +3456,13 @@ fn lsp_engine_strict_javascript_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3118,6 +3507,9 @@ fn lsp_engine_strict_javascript_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3125,6 +3517,13 @@ fn lsp_engine_strict_javascript_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3200,6 +3599,9 @@ fn lsp_engine_strict_ruby_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3207,6 +3609,13 @@ fn lsp_engine_strict_ruby_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3255,6 +3664,9 @@ fn lsp_engine_strict_ruby_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3262,6 +3674,13 @@ fn lsp_engine_strict_ruby_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3306,6 +3725,9 @@ fn lsp_engine_strict_ruby_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3313,6 +3735,13 @@ fn lsp_engine_strict_ruby_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3423,6 +3852,9 @@ fn lsp_engine_strict_java_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3430,6 +3862,13 @@ fn lsp_engine_strict_java_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3479,6 +3918,9 @@ fn lsp_engine_strict_java_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3486,6 +3928,13 @@ fn lsp_engine_strict_java_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3531,6 +3980,9 @@ fn lsp_engine_strict_java_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3538,6 +3990,13 @@ fn lsp_engine_strict_java_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3583,6 +4042,9 @@ fn lsp_engine_strict_go_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3590,6 +4052,13 @@ fn lsp_engine_strict_go_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3639,6 +4108,9 @@ fn lsp_engine_strict_go_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3646,6 +4118,13 @@ fn lsp_engine_strict_go_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3691,6 +4170,9 @@ fn lsp_engine_strict_go_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3698,6 +4180,13 @@ fn lsp_engine_strict_go_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3770,6 +4259,9 @@ fn lsp_engine_strict_python_callers_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3777,6 +4269,13 @@ fn lsp_engine_strict_python_callers_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3825,6 +4324,9 @@ fn lsp_engine_strict_python_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3832,6 +4334,13 @@ fn lsp_engine_strict_python_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3876,6 +4385,9 @@ fn lsp_engine_strict_python_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3883,6 +4395,13 @@ fn lsp_engine_strict_python_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3930,6 +4449,9 @@ fn lsp_engine_strict_callers_chain_is_stable_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3937,6 +4459,13 @@ fn lsp_engine_strict_callers_chain_is_stable_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -3987,6 +4516,9 @@ fn lsp_engine_strict_rust_callees_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -3994,6 +4526,13 @@ fn lsp_engine_strict_rust_callees_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -4042,6 +4581,9 @@ fn lsp_engine_strict_rust_both_chain_e2e_when_available() {
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -4049,6 +4591,13 @@ fn lsp_engine_strict_rust_both_chain_e2e_when_available() {
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();
@@ -4111,6 +4660,9 @@ fn main() { let s = S; s.foo(); }
         dump_capabilities: false,
         mock_lsp: false,
         mock_caps: None,
+        analysis: Default::default(),
+        mapping: Default::default(),
+        lsp_retry: Default::default(),
     };
     let engine = dimpact::engine::make_engine(dimpact::EngineKind::Lsp, cfg);
     let opts = dimpact::ImpactOptions {
@@ -4118,6 +4670,13 @@ fn main() { let s = S; s.foo(); }
         max_depth: Some(5),
         with_edges: Some(false),
         ignore_dirs: Vec::new(),
+        edge_kinds: Vec::new(),
+        edge_weights: std::collections::HashMap::new(),
+        exclude_tests: false,
+        limits: dimpact::GraphLimits::default(),
+        time_budget_ms: None,
+        languages: Vec::new(),
+        root: None,
     };
 
     let cwd = std::env::current_dir().unwrap();